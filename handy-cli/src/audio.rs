@@ -0,0 +1,133 @@
+//! Decoding of audio files to 16 kHz mono samples.
+
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use rubato::{FftFixedIn, Resampler};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Sample rate expected by every engine.
+pub const SAMPLE_RATE: usize = 16000;
+
+const RESAMPLER_CHUNK_SIZE: usize = 1024;
+
+/// Decode the audio file at `path`.
+pub fn decode_file(path: &Path) -> Result<Vec<f32>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    decode(Box::new(file), &hint)
+}
+
+/// Decode an in-memory audio file, e.g. an upload.
+pub fn decode_bytes(bytes: Vec<u8>) -> Result<Vec<f32>> {
+    decode(Box::new(Cursor::new(bytes)), &Hint::new())
+}
+
+/// Decode the default track, downmix it to mono and resample to 16 kHz.
+fn decode(source: Box<dyn MediaSource>, hint: &Hint) -> Result<Vec<f32>> {
+    let mss = MediaSourceStream::new(source, Default::default());
+    let probed = symphonia::default::get_probe()
+        .format(
+            hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .context("Unsupported or unrecognised audio format")?;
+
+    let mut format = probed.format;
+    let track = format
+        .default_track()
+        .ok_or_else(|| anyhow!("No audio track found"))?;
+    let track_id = track.id;
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        .ok_or_else(|| anyhow!("Unknown sample rate"))? as usize;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .context("Failed to create decoder")?;
+
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(ref e))
+                if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break;
+            }
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(anyhow!("Error reading packet: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                let channels = spec.channels.count().max(1);
+                let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                buffer.copy_interleaved_ref(decoded);
+                for frame in buffer.samples().chunks(channels) {
+                    samples.push(frame.iter().sum::<f32>() / channels as f32);
+                }
+            }
+            Err(SymphoniaError::DecodeError(e)) => {
+                log::warn!("Decode error on packet (skipping): {}", e);
+            }
+            Err(e) => return Err(anyhow!("Fatal decode error: {}", e)),
+        }
+    }
+
+    if samples.is_empty() {
+        return Err(anyhow!("No audio samples decoded"));
+    }
+    resample(&samples, sample_rate)
+}
+
+/// Resample a whole recording from `from_hz` to 16 kHz, trimming the
+/// resampler delay so the output lines up with the input.
+fn resample(samples: &[f32], from_hz: usize) -> Result<Vec<f32>> {
+    if from_hz == SAMPLE_RATE {
+        return Ok(samples.to_vec());
+    }
+
+    let mut resampler = FftFixedIn::<f32>::new(from_hz, SAMPLE_RATE, RESAMPLER_CHUNK_SIZE, 1, 1)?;
+    let delay = resampler.output_delay();
+    let expected = (samples.len() * SAMPLE_RATE).div_ceil(from_hz);
+    let mut out = Vec::with_capacity(expected + delay);
+
+    let mut chunks = samples.chunks_exact(RESAMPLER_CHUNK_SIZE);
+    for chunk in &mut chunks {
+        out.extend_from_slice(&resampler.process(&[chunk], None)?[0]);
+    }
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        out.extend_from_slice(&resampler.process_partial(Some(&[rest]), None)?[0]);
+    }
+    // Drain the delay line
+    while out.len() < expected + delay {
+        let tail = resampler.process_partial(None::<&[&[f32]]>, None)?;
+        if tail[0].is_empty() {
+            break;
+        }
+        out.extend_from_slice(&tail[0]);
+    }
+
+    out.drain(..delay.min(out.len()));
+    out.truncate(expected);
+    Ok(out)
+}
@@ -0,0 +1,103 @@
+//! `handy-cli download`: fetch model files with resume and checksum
+//! verification.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use transcribe_rs::model_hub::{self, DownloadOptions};
+
+/// URL for `source`: an http(s) URL as is, or `<owner>/<repo>/<file>` of a
+/// Hugging Face repository at `revision`.
+pub fn resolve_url(source: &str, revision: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        return Ok(source.to_string());
+    }
+    let mut parts = source.trim_matches('/').splitn(3, '/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(owner), Some(repo), Some(file)) if !file.is_empty() => Ok(model_hub::hf_url_at(
+            &format!("{}/{}", owner, repo),
+            revision,
+            file,
+        )),
+        _ => Err(anyhow!(
+            "expected a URL or <owner>/<repo>/<file> on Hugging Face, got '{}'",
+            source
+        )),
+    }
+}
+
+/// Last path segment of `url`, without the query string.
+fn file_name(url: &str) -> Option<&str> {
+    url.split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+}
+
+pub fn run(
+    source: &str,
+    revision: &str,
+    output: Option<&Path>,
+    sha256: Option<String>,
+) -> Result<()> {
+    let url = resolve_url(source, revision)?;
+    let dest = match output {
+        Some(path) if path.is_dir() || path.to_string_lossy().ends_with(['/', '\\']) => {
+            path.join(file_name(&url).unwrap_or("model.bin"))
+        }
+        Some(path) => path.to_path_buf(),
+        None => PathBuf::from(
+            file_name(&url).ok_or_else(|| anyhow!("Can't name the file for {}, use -o", url))?,
+        ),
+    };
+
+    info!("Downloading {} to {}", url, dest.display());
+    let options = DownloadOptions {
+        sha256,
+        ..Default::default()
+    };
+    model_hub::download(&url, &dest, &options, |progress| {
+        match progress.percentage() {
+            Some(percentage) => eprint!(
+                "\r{:.1} / {:.1} MB ({:.0}%)",
+                progress.downloaded as f64 / 1e6,
+                progress.total.unwrap_or(0) as f64 / 1e6,
+                percentage
+            ),
+            None => eprint!("\r{:.1} MB", progress.downloaded as f64 / 1e6),
+        }
+        let _ = std::io::stderr().flush();
+    })
+    .with_context(|| format!("Failed to download {}", url))?;
+    eprintln!();
+
+    if options.sha256.is_some() {
+        info!("Checksum verified");
+    }
+    println!("{}", dest.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_hugging_face_sources() {
+        assert_eq!(
+            resolve_url("ggerganov/whisper.cpp/ggml-small.bin", "main").unwrap(),
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.bin"
+        );
+        assert_eq!(
+            resolve_url("https://blob.handy.computer/ggml-small.bin", "main").unwrap(),
+            "https://blob.handy.computer/ggml-small.bin"
+        );
+        assert!(resolve_url("ggml-small.bin", "main").is_err());
+        assert_eq!(
+            file_name("https://huggingface.co/a/b/resolve/main/onnx/encoder.onnx?download=true"),
+            Some("encoder.onnx")
+        );
+    }
+}
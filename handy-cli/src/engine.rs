@@ -0,0 +1,144 @@
+//! Loading and running the engine selected on the command line.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use transcribe_rs::TranscriptionResult;
+
+#[cfg(feature = "canary")]
+use transcribe_rs::engines::canary::{CanaryEngine, CanaryModelParams};
+#[cfg(feature = "moonshine")]
+use transcribe_rs::engines::moonshine::{ModelVariant, MoonshineEngine, MoonshineModelParams};
+#[cfg(feature = "parakeet")]
+use transcribe_rs::engines::parakeet::{ParakeetEngine, ParakeetModelParams};
+#[cfg(feature = "whisper")]
+use transcribe_rs::engines::whisper::WhisperEngine;
+#[cfg(any(
+    feature = "whisper",
+    feature = "parakeet",
+    feature = "moonshine",
+    feature = "canary"
+))]
+use transcribe_rs::TranscriptionEngine;
+
+/// Engines that can be selected with `--engine`. All are listed; the ones
+/// not compiled in fail to load with a hint about the missing feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EngineKind {
+    /// Whisper GGML model file
+    Whisper,
+    /// NVIDIA Parakeet ONNX model directory
+    Parakeet,
+    /// Moonshine ONNX model directory; "base" in the name selects the base model
+    Moonshine,
+    /// NVIDIA Canary ONNX model directory
+    Canary,
+}
+
+impl EngineKind {
+    fn name(&self) -> &'static str {
+        match self {
+            EngineKind::Whisper => "whisper",
+            EngineKind::Parakeet => "parakeet",
+            EngineKind::Moonshine => "moonshine",
+            EngineKind::Canary => "canary",
+        }
+    }
+}
+
+/// A loaded engine.
+pub enum Engine {
+    #[cfg(feature = "whisper")]
+    Whisper(WhisperEngine),
+    #[cfg(feature = "parakeet")]
+    Parakeet(ParakeetEngine),
+    #[cfg(feature = "moonshine")]
+    Moonshine(MoonshineEngine),
+    #[cfg(feature = "canary")]
+    Canary(CanaryEngine),
+}
+
+impl Engine {
+    /// Load the model at `model_path` with `kind`. `quantized` selects the
+    /// int8 weights of Parakeet and Canary models.
+    #[allow(unused_variables)]
+    pub fn load(kind: EngineKind, model_path: &Path, quantized: bool) -> Result<Self> {
+        if !model_path.exists() {
+            return Err(anyhow!("Model not found at {}", model_path.display()));
+        }
+
+        match kind {
+            #[cfg(feature = "whisper")]
+            EngineKind::Whisper => {
+                let mut engine = WhisperEngine::new();
+                engine
+                    .load_model(model_path)
+                    .map_err(|e| anyhow!("Failed to load Whisper model: {}", e))?;
+                Ok(Engine::Whisper(engine))
+            }
+            #[cfg(feature = "parakeet")]
+            EngineKind::Parakeet => {
+                let params = if quantized {
+                    ParakeetModelParams::int8()
+                } else {
+                    ParakeetModelParams::fp32()
+                };
+                let mut engine = ParakeetEngine::new();
+                engine
+                    .load_model_with_params(model_path, params)
+                    .map_err(|e| anyhow!("Failed to load Parakeet model: {}", e))?;
+                Ok(Engine::Parakeet(engine))
+            }
+            #[cfg(feature = "moonshine")]
+            EngineKind::Moonshine => {
+                let is_base = model_path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().contains("base"));
+                let variant = if is_base {
+                    ModelVariant::Base
+                } else {
+                    ModelVariant::Tiny
+                };
+                let mut engine = MoonshineEngine::new();
+                engine
+                    .load_model_with_params(model_path, MoonshineModelParams::variant(variant))
+                    .map_err(|e| anyhow!("Failed to load Moonshine model: {}", e))?;
+                Ok(Engine::Moonshine(engine))
+            }
+            #[cfg(feature = "canary")]
+            EngineKind::Canary => {
+                let params = if quantized {
+                    CanaryModelParams::int8()
+                } else {
+                    CanaryModelParams::fp32()
+                };
+                let mut engine = CanaryEngine::new();
+                engine
+                    .load_model_with_params(model_path, params)
+                    .map_err(|e| anyhow!("Failed to load Canary model: {}", e))?;
+                Ok(Engine::Canary(engine))
+            }
+            #[allow(unreachable_patterns)]
+            other => Err(anyhow!(
+                "handy-cli was built without the '{0}' engine. Rebuild with `--features {0}`.",
+                other.name()
+            )),
+        }
+    }
+
+    /// Transcribe 16 kHz mono `samples` with default inference settings.
+    pub fn transcribe(&mut self, samples: Vec<f32>) -> Result<TranscriptionResult> {
+        let result = match self {
+            #[cfg(feature = "whisper")]
+            Engine::Whisper(engine) => engine.transcribe_samples(samples, None),
+            #[cfg(feature = "parakeet")]
+            Engine::Parakeet(engine) => engine.transcribe_samples(samples, None),
+            #[cfg(feature = "moonshine")]
+            Engine::Moonshine(engine) => engine.transcribe_samples(samples, None),
+            #[cfg(feature = "canary")]
+            Engine::Canary(engine) => engine.transcribe_samples(samples, None),
+        };
+        result.map_err(|e| anyhow!("Transcription failed: {}", e))
+    }
+}
@@ -0,0 +1,229 @@
+//! Command-line front end for transcribe-rs: transcribe audio files, or
+//! serve the REST API with a single model, without the desktop app.
+
+mod audio;
+mod download;
+mod engine;
+mod serve;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
+use log::info;
+use transcribe_rs::formats::{Transcript, TranscriptFormat};
+
+use crate::engine::{Engine, EngineKind};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "handy-cli",
+    version,
+    about = "Handy - Speech to Text from the command line"
+)]
+struct Cli {
+    /// Enable debug logging
+    #[arg(long, global = true)]
+    debug: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Transcribe an audio file (WAV, MP3, FLAC, OGG Vorbis or AAC)
+    Transcribe {
+        /// Audio file to transcribe
+        file: PathBuf,
+
+        #[command(flatten)]
+        model: ModelArgs,
+
+        /// Output format: txt, json, srt, vtt or md. Defaults to the
+        /// extension of --output, or txt
+        #[arg(long, value_parser = parse_format)]
+        format: Option<TranscriptFormat>,
+
+        /// Write the transcript to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Serve the REST API (POST /transcribe, GET /health) with one model
+    Serve {
+        #[command(flatten)]
+        model: ModelArgs,
+
+        /// Address to bind to
+        #[arg(long, default_value = "0.0.0.0")]
+        host: String,
+
+        /// Port to listen on
+        #[arg(long, default_value_t = 8720)]
+        port: u16,
+    },
+    /// Download a model file, resuming an interrupted download
+    Download {
+        /// URL, or <owner>/<repo>/<file> on Hugging Face
+        source: String,
+
+        /// Branch, tag or commit of a Hugging Face repository
+        #[arg(long, default_value = "main")]
+        revision: String,
+
+        /// File or directory to save to. Defaults to the file name in the
+        /// current directory
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Expected SHA-256 of the file; a mismatching download is deleted
+        #[arg(long)]
+        sha256: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct ModelArgs {
+    /// Transcription engine
+    #[arg(long, value_enum, default_value_t = EngineKind::Whisper)]
+    engine: EngineKind,
+
+    /// Model file (Whisper) or directory (ONNX engines)
+    #[arg(long)]
+    model: PathBuf,
+
+    /// Load int8 weights for Parakeet and Canary models
+    #[arg(long)]
+    quantized: bool,
+}
+
+impl ModelArgs {
+    fn load(&self) -> Result<Engine> {
+        let start = Instant::now();
+        let engine = Engine::load(self.engine, &self.model, self.quantized)?;
+        info!("Loaded {} in {:.2?}", self.model.display(), start.elapsed());
+        Ok(engine)
+    }
+}
+
+fn parse_format(value: &str) -> Result<TranscriptFormat, String> {
+    TranscriptFormat::parse(value)
+        .ok_or_else(|| format!("expected one of txt, json, srt, vtt or md, got '{}'", value))
+}
+
+/// Format named by the output file's extension, if it is a known one.
+fn format_for_output(output: &Path) -> Option<TranscriptFormat> {
+    output
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| TranscriptFormat::parse(&ext.to_ascii_lowercase()))
+}
+
+fn transcribe(
+    file: &Path,
+    model: &ModelArgs,
+    format: Option<TranscriptFormat>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let format = format
+        .or_else(|| output.and_then(format_for_output))
+        .unwrap_or(TranscriptFormat::Txt);
+
+    let samples = audio::decode_file(file)?;
+    let duration = samples.len() as f32 / audio::SAMPLE_RATE as f32;
+    let mut engine = model.load()?;
+
+    let start = Instant::now();
+    let result = engine.transcribe(samples)?;
+    let elapsed = start.elapsed();
+    info!(
+        "Transcribed {:.1}s of audio in {:.2?} (RTF {:.3})",
+        duration,
+        elapsed,
+        elapsed.as_secs_f32() / duration.max(f32::EPSILON)
+    );
+
+    let rendered = format.render(&Transcript::from_result(&result, duration));
+    match output {
+        Some(path) => fs::write(path, rendered)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let level = if cli.debug { "debug" } else { "info" };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level)).init();
+
+    match cli.command {
+        Command::Transcribe {
+            file,
+            model,
+            format,
+            output,
+        } => tokio::task::block_in_place(|| transcribe(&file, &model, format, output.as_deref())),
+        Command::Serve { model, host, port } => {
+            let engine = model.load()?;
+            serve::run(engine, &host, port).await
+        }
+        Command::Download {
+            source,
+            revision,
+            output,
+            sha256,
+        } => tokio::task::block_in_place(|| {
+            download::run(&source, &revision, output.as_deref(), sha256)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_format_from_output_extension() {
+        assert_eq!(
+            format_for_output(Path::new("out.SRT")),
+            Some(TranscriptFormat::Srt)
+        );
+        assert_eq!(
+            format_for_output(Path::new("notes.md")),
+            Some(TranscriptFormat::Md)
+        );
+        assert_eq!(format_for_output(Path::new("transcript")), None);
+        assert_eq!(format_for_output(Path::new("audio.wav")), None);
+    }
+
+    #[test]
+    fn parses_command_line() {
+        let cli = Cli::try_parse_from([
+            "handy-cli",
+            "transcribe",
+            "call.mp3",
+            "--engine",
+            "parakeet",
+            "--model",
+            "models/parakeet-tdt-0.6b-v3-int8",
+            "--quantized",
+            "-o",
+            "call.srt",
+        ])
+        .expect("valid arguments");
+
+        match cli.command {
+            Command::Transcribe { model, output, .. } => {
+                assert_eq!(model.engine, EngineKind::Parakeet);
+                assert!(model.quantized);
+                assert_eq!(output.as_deref(), Some(Path::new("call.srt")));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+}
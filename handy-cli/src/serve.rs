@@ -0,0 +1,137 @@
+//! Minimal REST API around a single loaded engine, for servers and scripts
+//! that don't run the desktop app.
+//!
+//! - `GET /health` returns `{"status": "ok"}`.
+//! - `POST /transcribe` takes a multipart upload in a `file` or `audio`
+//!   field and an optional `response_format` field (`json`, `txt`, `srt`,
+//!   `vtt` or `md`).
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use log::{debug, info};
+use transcribe_rs::formats::{Transcript, TranscriptFormat};
+
+use crate::audio::{decode_bytes, SAMPLE_RATE};
+use crate::engine::Engine;
+
+/// Cap on the request body size (100 MB), as in the app's API server.
+const MAX_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
+
+struct ServeState {
+    /// Engines are not thread-safe; requests take turns.
+    engine: Mutex<Engine>,
+}
+
+type ErrorReply = (StatusCode, Json<serde_json::Value>);
+
+fn error_reply(status: StatusCode, msg: impl Into<String>) -> ErrorReply {
+    (status, Json(serde_json::json!({ "error": msg.into() })))
+}
+
+/// Serve the API on `host:port` until the process is stopped.
+pub async fn run(engine: Engine, host: &str, port: u16) -> Result<()> {
+    let state = Arc::new(ServeState {
+        engine: Mutex::new(engine),
+    });
+    let app = Router::new()
+        .route("/health", get(health))
+        .route("/transcribe", post(transcribe))
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind((host, port)).await?;
+    info!(
+        "Transcription API listening on http://{}",
+        listener.local_addr()?
+    );
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+async fn transcribe(
+    State(state): State<Arc<ServeState>>,
+    mut multipart: Multipart,
+) -> Result<Response, ErrorReply> {
+    let mut upload = None;
+    let mut format = TranscriptFormat::Json;
+
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let name = field.name().unwrap_or("").to_string();
+        match name.as_str() {
+            "file" | "audio" => {
+                let bytes = field.bytes().await.map_err(|e| {
+                    error_reply(
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed to read file field: {}", e),
+                    )
+                })?;
+                upload = Some(bytes.to_vec());
+            }
+            "response_format" => {
+                let value = field.text().await.unwrap_or_default();
+                format = TranscriptFormat::parse(value.trim()).ok_or_else(|| {
+                    error_reply(
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "Invalid value '{}' for 'response_format': expected 'json', 'txt', 'srt', 'vtt' or 'md'",
+                            value.trim()
+                        ),
+                    )
+                })?;
+            }
+            _ => debug!("Ignoring unknown multipart field '{}'", name),
+        }
+    }
+
+    let upload = match upload {
+        Some(upload) if !upload.is_empty() => upload,
+        _ => {
+            return Err(error_reply(
+                StatusCode::BAD_REQUEST,
+                "No audio file provided. Send a multipart field named 'file' or 'audio'.",
+            ))
+        }
+    };
+
+    let result = tokio::task::spawn_blocking(move || -> Result<Transcript, ErrorReply> {
+        let samples = decode_bytes(upload).map_err(|e| {
+            error_reply(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Failed to decode audio: {:#}", e),
+            )
+        })?;
+        let duration = samples.len() as f32 / SAMPLE_RATE as f32;
+        let mut engine = state.engine.lock().unwrap_or_else(|e| e.into_inner());
+        let result = engine
+            .transcribe(samples)
+            .map_err(|e| error_reply(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        Ok(Transcript::from_result(&result, duration))
+    })
+    .await
+    .map_err(|e| {
+        error_reply(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Transcription task panicked: {}", e),
+        )
+    })??;
+
+    info!("API transcription result: {}", result.text);
+    let mut response = format.render(&result).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    Ok(response)
+}
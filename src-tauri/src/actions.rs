@@ -1,9 +1,18 @@
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 use crate::apple_intelligence;
 use crate::audio_feedback::{play_feedback_sound, play_feedback_sound_blocking, SoundType};
-use crate::managers::audio::AudioRecordingManager;
-use crate::managers::history::HistoryManager;
-use crate::managers::transcription::TranscriptionManager;
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::VoiceCommands;
+use crate::events::{EventBus, EventKind};
+use crate::foreground;
+use crate::llm_client::strip_invisible_chars;
+use crate::managers::audio::{microphone_mode, AudioRecordingManager, MicrophoneMode};
+use crate::managers::history::{
+    HistoryManager, HistorySegment, HistorySource, TranscriptionRecord,
+};
+use crate::managers::output::OutputRouter;
+use crate::managers::transcription::{TranscriptionManager, TranscriptionOptions};
+use crate::redact::redact;
 use crate::settings::{get_settings, AppSettings, APPLE_INTELLIGENCE_PROVIDER_ID};
 use crate::shortcut;
 use crate::tray::{change_tray_icon, TrayIconState};
@@ -45,11 +54,6 @@ struct TranscribeAction {
 /// Field name for structured output JSON schema
 const TRANSCRIPTION_FIELD: &str = "transcription";
 
-/// Strip invisible Unicode characters that some LLMs may insert
-fn strip_invisible_chars(s: &str) -> String {
-    s.replace(['\u{200B}', '\u{200C}', '\u{200D}', '\u{FEFF}'], "")
-}
-
 /// Build a system prompt from the user's prompt template.
 /// Removes `${output}` placeholder since the transcription is sent as the user message.
 fn build_system_prompt(prompt_template: &str) -> String {
@@ -308,6 +312,31 @@ async fn maybe_convert_chinese_variant(
     }
 }
 
+/// Interpret spoken punctuation and editing commands in a dictation, if
+/// enabled, with the grammar of its language plus the user's own phrases.
+pub(crate) fn apply_voice_commands(
+    settings: &AppSettings,
+    text: &str,
+    language: Option<&str>,
+) -> String {
+    if !settings.voice_commands {
+        return text.to_string();
+    }
+    // Phrases are validated when saved; skip any that no longer compile
+    let custom = settings
+        .voice_command_phrases
+        .iter()
+        .filter_map(|phrase| {
+            phrase
+                .compile()
+                .map_err(|e| warn!("Skipping voice command '{}': {}", phrase.phrase, e))
+                .ok()
+        })
+        .collect();
+    let language = language.or(Some(settings.selected_language.as_str()));
+    VoiceCommands::for_language(language, custom).apply(text)
+}
+
 impl ShortcutAction for TranscribeAction {
     fn start(&self, app: &AppHandle, binding_id: &str, _shortcut_str: &str) {
         let start_time = Instant::now();
@@ -325,7 +354,7 @@ impl ShortcutAction for TranscribeAction {
 
         // Get the microphone mode to determine audio feedback timing
         let settings = get_settings(app);
-        let is_always_on = settings.always_on_microphone;
+        let is_always_on = matches!(microphone_mode(&settings), MicrophoneMode::AlwaysOn);
         debug!("Microphone mode - always_on: {}", is_always_on);
 
         let mut recording_error: Option<String> = None;
@@ -373,6 +402,8 @@ impl ShortcutAction for TranscribeAction {
         }
 
         if recording_error.is_none() {
+            app.state::<Arc<EventBus>>()
+                .publish(EventKind::RecordingStarted);
             // Dynamically register the cancel shortcut in a separate task to avoid deadlock
             shortcut::register_cancel_shortcut(app);
         } else {
@@ -398,10 +429,19 @@ impl ShortcutAction for TranscribeAction {
         let stop_time = Instant::now();
         debug!("TranscribeAction::stop called for binding: {}", binding_id);
 
+        // The text goes to the app in the foreground, so its profile applies
+        let options = TranscriptionOptions {
+            app: foreground::foreground_app(),
+            ..Default::default()
+        };
+        debug!("Foreground app: {:?}", options.app);
+
         let ah = app.clone();
         let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
         let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
         let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+        let events = Arc::clone(&app.state::<Arc<EventBus>>());
+        let output = Arc::clone(&app.state::<Arc<OutputRouter>>());
 
         change_tray_icon(app, TrayIconState::Transcribing);
         show_transcribing_overlay(app);
@@ -430,18 +470,39 @@ impl ShortcutAction for TranscribeAction {
                     stop_recording_time.elapsed(),
                     samples.len()
                 );
+                events.publish(EventKind::RecordingStopped);
 
                 let transcription_time = Instant::now();
                 let samples_clone = samples.clone(); // Clone for history saving
-                match tm.transcribe(samples) {
-                    Ok(transcription) => {
+                let duration_ms = samples.len() as i64 * 1000 / WHISPER_SAMPLE_RATE as i64;
+                match tm.transcribe_with_options(samples, &options) {
+                    Ok(detailed) => {
+                        let mut settings = get_settings(&ah);
+                        // The app profile can turn post-processing on or off
+                        // and pick its prompt
+                        let mut post_process = post_process;
+                        let profile = options
+                            .app
+                            .as_ref()
+                            .and_then(|app| settings.app_profile(app));
+                        if let Some(profile) = profile.cloned() {
+                            post_process = profile.post_process.unwrap_or(post_process);
+                            if profile.post_process_prompt_id.is_some() {
+                                settings.post_process_selected_prompt_id =
+                                    profile.post_process_prompt_id;
+                            }
+                        }
+                        let transcription = apply_voice_commands(
+                            &settings,
+                            &detailed.text,
+                            detailed.language.as_deref(),
+                        );
                         debug!(
                             "Transcription completed in {:?}: '{}'",
                             transcription_time.elapsed(),
-                            transcription
+                            redact(&transcription)
                         );
                         if !transcription.is_empty() {
-                            let settings = get_settings(&ah);
                             let mut final_text = transcription.clone();
                             let mut post_processed_text: Option<String> = None;
                             let mut post_process_prompt: Option<String> = None;
@@ -482,33 +543,56 @@ impl ShortcutAction for TranscribeAction {
                                 post_processed_text = Some(final_text.clone());
                             }
 
+                            events.publish(EventKind::TranscriptionCompleted {
+                                text: final_text.clone(),
+                                language: detailed.language.clone(),
+                                duration_ms,
+                            });
+                            output.deliver(&final_text, detailed.language.as_deref(), duration_ms);
+
                             // Save to history with post-processed text and prompt
                             let hm_clone = Arc::clone(&hm);
-                            let transcription_for_history = transcription.clone();
+                            let record = TranscriptionRecord {
+                                source: HistorySource::App,
+                                transcription_text: transcription.clone(),
+                                segments: detailed
+                                    .segments
+                                    .into_iter()
+                                    .map(|seg| HistorySegment {
+                                        start: seg.start,
+                                        end: seg.end,
+                                        text: seg.text,
+                                        speaker: None,
+                                    })
+                                    .collect(),
+                                post_processed_text,
+                                post_process_prompt,
+                                model_id: tm.get_current_model(),
+                                duration_ms,
+                                audio_hash: None,
+                            };
                             tauri::async_runtime::spawn(async move {
-                                if let Err(e) = hm_clone
-                                    .save_transcription(
-                                        samples_clone,
-                                        transcription_for_history,
-                                        post_processed_text,
-                                        post_process_prompt,
-                                    )
-                                    .await
+                                if let Err(e) =
+                                    hm_clone.save_transcription(samples_clone, record).await
                                 {
                                     error!("Failed to save transcription to history: {}", e);
                                 }
                             });
 
-                            // Paste the final text (either processed or original)
+                            // Paste the final text (either processed or original),
+                            // unless it only goes to the configured outputs
                             let ah_clone = ah.clone();
                             let paste_time = Instant::now();
+                            let paste = output.paste();
                             ah.run_on_main_thread(move || {
-                                match utils::paste(final_text, ah_clone.clone()) {
-                                    Ok(()) => debug!(
-                                        "Text pasted successfully in {:?}",
-                                        paste_time.elapsed()
-                                    ),
-                                    Err(e) => error!("Failed to paste transcription: {}", e),
+                                if paste {
+                                    match utils::paste(final_text, ah_clone.clone()) {
+                                        Ok(()) => debug!(
+                                            "Text pasted successfully in {:?}",
+                                            paste_time.elapsed()
+                                        ),
+                                        Err(e) => error!("Failed to paste transcription: {}", e),
+                                    }
                                 }
                                 // Hide the overlay after transcription is complete
                                 utils::hide_recording_overlay(&ah_clone);
@@ -0,0 +1,93 @@
+//! Word-level timestamps for API transcriptions.
+//!
+//! Engines report segment timing at best, which is too coarse for subtitles.
+//! When enabled, transcripts are force-aligned to the audio with a wav2vec2
+//! CTC model (see `transcribe_rs::alignment`), WhisperX-style.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use log::info;
+use transcribe_rs::alignment::Aligner;
+use transcribe_rs::{TranscriptionResult, TranscriptionSegment};
+
+use crate::managers::transcription::{DetailedTranscription, TranscribedSegment};
+
+/// Aligns transcripts to audio for word timestamps.
+///
+/// Configured from the environment for the API server:
+/// - `HANDY_API_ALIGN_MODEL`: directory containing the alignment model's
+///   `model.onnx` and `vocab.json`
+///
+/// The model is loaded on first use and kept for later requests.
+pub struct WordAligner {
+    model_dir: PathBuf,
+    aligner: Mutex<Option<Aligner>>,
+}
+
+impl WordAligner {
+    /// Returns `Ok(None)` when `HANDY_API_ALIGN_MODEL` is not set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let model_dir = match std::env::var("HANDY_API_ALIGN_MODEL") {
+            Ok(dir) if !dir.trim().is_empty() => PathBuf::from(dir.trim()),
+            _ => return Ok(None),
+        };
+        if !model_dir.join("model.onnx").exists() {
+            return Err(format!(
+                "Alignment model not found in {}",
+                model_dir.display()
+            ));
+        }
+        Ok(Some(Self {
+            model_dir,
+            aligner: Mutex::new(None),
+        }))
+    }
+
+    /// Word timings for each segment of `transcription`, in the same order.
+    /// Blocks while the model runs.
+    pub fn align(
+        &self,
+        samples: &[f32],
+        transcription: &DetailedTranscription,
+    ) -> anyhow::Result<Vec<Vec<TranscribedSegment>>> {
+        let mut guard = self.aligner.lock().unwrap();
+        if guard.is_none() {
+            info!("Loading alignment model from {}", self.model_dir.display());
+            *guard = Some(Aligner::new(&self.model_dir)?);
+        }
+        let aligner = guard.as_mut().unwrap();
+
+        let result = TranscriptionResult {
+            text: transcription.text.clone(),
+            segments: Some(
+                transcription
+                    .segments
+                    .iter()
+                    .map(|seg| TranscriptionSegment {
+                        start: seg.start,
+                        end: seg.end,
+                        text: seg.text.clone(),
+                    })
+                    .collect(),
+            ),
+            language: transcription.language.clone(),
+        };
+
+        Ok(aligner
+            .align(samples, &result)?
+            .into_iter()
+            .map(|segment| {
+                segment
+                    .words
+                    .into_iter()
+                    .map(|word| TranscribedSegment {
+                        start: word.start,
+                        end: word.end,
+                        text: word.text,
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+}
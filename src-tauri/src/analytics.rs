@@ -0,0 +1,235 @@
+//! Talk-time analytics of a transcript, for coaching and meeting review:
+//! how long each speaker talked, how fast, how often they cut someone off,
+//! and how much of the recording is silence.
+//!
+//! Everything is computed from segment timings, so the numbers are as good
+//! as the engine's timestamps. Speakers come from the segment labels, i.e.
+//! multitrack speakers, separate channels or meeting sources; without
+//! labels there are only the totals. Words are counted at whitespace, which
+//! undercounts languages written without spaces.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Overlap a segment may have with the previous speaker's before it counts
+/// as an interruption, as segment timestamps are not that precise.
+const INTERRUPTION_TOLERANCE: f32 = 0.25;
+
+/// A segment to analyze. Times are in seconds.
+pub struct TalkSegment<'a> {
+    pub start: f32,
+    pub end: f32,
+    pub speaker: Option<&'a str>,
+    pub text: &'a str,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct TalkAnalytics {
+    /// Seconds in which at least one speaker talks.
+    pub talk_seconds: f32,
+    /// Share of the recording (0 to 1) in which nobody talks.
+    pub silence_ratio: f32,
+    pub words: usize,
+    /// Words per minute of talk time.
+    pub words_per_minute: f32,
+    /// Times a speaker started while another was still talking.
+    pub interruptions: usize,
+    /// Per speaker or channel, most talk time first; empty when the
+    /// segments have no labels.
+    pub speakers: Vec<SpeakerAnalytics>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct SpeakerAnalytics {
+    pub speaker: String,
+    pub talk_seconds: f32,
+    /// Share of the total talk time (0 to 1).
+    pub talk_share: f32,
+    pub words: usize,
+    pub words_per_minute: f32,
+    /// Times this speaker started while another was still talking.
+    pub interruptions: usize,
+}
+
+/// Analytics of `segments` from a recording of `duration` seconds, or
+/// `None` without segments.
+pub fn analyze(segments: &[TalkSegment], duration: f32) -> Option<TalkAnalytics> {
+    if segments.is_empty() {
+        return None;
+    }
+    let mut segments: Vec<&TalkSegment> = segments.iter().collect();
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let talk_seconds = talk_time(segments.iter().copied());
+    // Segments may run a little past the decoded audio
+    let duration = segments.iter().map(|seg| seg.end).fold(duration, f32::max);
+    let words: usize = segments.iter().map(|seg| word_count(seg.text)).sum();
+    let interrupters = interrupters(&segments);
+
+    let mut names: Vec<&str> = Vec::new();
+    for seg in &segments {
+        if let Some(speaker) = seg.speaker {
+            if !names.contains(&speaker) {
+                names.push(speaker);
+            }
+        }
+    }
+    let mut speakers: Vec<SpeakerAnalytics> = names
+        .into_iter()
+        .map(|name| {
+            let own = || {
+                segments
+                    .iter()
+                    .copied()
+                    .filter(|seg| seg.speaker == Some(name))
+            };
+            let seconds = talk_time(own());
+            let words = own().map(|seg| word_count(seg.text)).sum();
+            SpeakerAnalytics {
+                speaker: name.to_string(),
+                talk_seconds: round(seconds),
+                talk_share: round(ratio(seconds, talk_seconds)),
+                words,
+                words_per_minute: round(per_minute(words, seconds)),
+                interruptions: interrupters.iter().filter(|&&who| who == name).count(),
+            }
+        })
+        .collect();
+    speakers.sort_by(|a, b| b.talk_seconds.total_cmp(&a.talk_seconds));
+
+    Some(TalkAnalytics {
+        talk_seconds: round(talk_seconds),
+        silence_ratio: round(1.0 - ratio(talk_seconds, duration)),
+        words,
+        words_per_minute: round(per_minute(words, talk_seconds)),
+        interruptions: interrupters.len(),
+        speakers,
+    })
+}
+
+/// Seconds covered by `segments`, sorted by start, counting overlaps once.
+fn talk_time<'a>(segments: impl Iterator<Item = &'a TalkSegment<'a>>) -> f32 {
+    let mut total = 0.0;
+    let mut covered_until = f32::NEG_INFINITY;
+    for seg in segments {
+        let start = seg.start.max(covered_until);
+        if seg.end > start {
+            total += seg.end - start;
+            covered_until = seg.end;
+        }
+    }
+    total
+}
+
+/// The speaker of each segment that starts while another speaker's segment,
+/// sorted by start, is still running.
+fn interrupters<'a>(segments: &[&TalkSegment<'a>]) -> Vec<&'a str> {
+    let mut interrupters = Vec::new();
+    // The speaker whose segment ends last so far, and when
+    let mut talking: Option<(&str, f32)> = None;
+    for seg in segments {
+        let Some(speaker) = seg.speaker else {
+            continue;
+        };
+        match talking {
+            Some((other, end)) if other != speaker && seg.start < end - INTERRUPTION_TOLERANCE => {
+                interrupters.push(speaker);
+            }
+            _ => {}
+        }
+        if talking.is_none_or(|(_, end)| seg.end > end) {
+            talking = Some((speaker, seg.end));
+        }
+    }
+    interrupters
+}
+
+fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+fn per_minute(words: usize, seconds: f32) -> f32 {
+    ratio(words as f32 * 60.0, seconds)
+}
+
+fn ratio(part: f32, whole: f32) -> f32 {
+    if whole > 0.0 {
+        (part / whole).max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// Two decimals are plenty for these and keep the JSON readable.
+fn round(value: f32) -> f32 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg<'a>(start: f32, end: f32, speaker: Option<&'a str>, text: &'a str) -> TalkSegment<'a> {
+        TalkSegment {
+            start,
+            end,
+            speaker,
+            text,
+        }
+    }
+
+    #[test]
+    fn test_analyze_speakers() {
+        let segments = [
+            seg(
+                0.0,
+                6.0,
+                Some("Ann"),
+                "Let's go through the numbers for March",
+            ),
+            // Cuts Ann off
+            seg(5.0, 8.0, Some("Bob"), "Sorry, which numbers?"),
+            // Starts as Bob ends, within the tolerance
+            seg(7.9, 10.0, Some("Ann"), "The sales numbers"),
+            seg(14.0, 20.0, Some("Bob"), "Right, they look good"),
+        ];
+        let analytics = analyze(&segments, 20.0).unwrap();
+
+        // 0-10 and 14-20
+        assert_eq!(analytics.talk_seconds, 16.0);
+        assert_eq!(analytics.silence_ratio, 0.2);
+        assert_eq!(analytics.words, 17);
+        assert_eq!(analytics.words_per_minute, 63.75);
+        assert_eq!(analytics.interruptions, 1);
+
+        let [bob, ann] = analytics.speakers.as_slice() else {
+            panic!("expected two speakers");
+        };
+        assert_eq!(bob.speaker, "Bob");
+        assert_eq!(bob.talk_seconds, 9.0);
+        assert_eq!(bob.talk_share, 0.56);
+        assert_eq!(bob.words, 7);
+        assert_eq!(bob.interruptions, 1);
+        assert_eq!(ann.speaker, "Ann");
+        assert_eq!(ann.talk_seconds, 8.1);
+        assert_eq!(ann.words, 10);
+        assert_eq!(ann.words_per_minute, 74.07);
+        assert_eq!(ann.interruptions, 0);
+    }
+
+    #[test]
+    fn test_analyze_without_speakers() {
+        let segments = [
+            seg(1.0, 3.0, None, "Hello there"),
+            seg(2.0, 4.0, None, "again"),
+        ];
+        let analytics = analyze(&segments, 8.0).unwrap();
+        assert_eq!(analytics.talk_seconds, 3.0);
+        assert_eq!(analytics.silence_ratio, 0.63);
+        assert_eq!(analytics.words_per_minute, 60.0);
+        assert_eq!(analytics.interruptions, 0);
+        assert!(analytics.speakers.is_empty());
+
+        assert!(analyze(&[], 8.0).is_none());
+    }
+}
@@ -0,0 +1,225 @@
+//! Bearer authentication for the API: the `auth_token` and the scoped API
+//! keys, and recording each key's usage.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::{error, warn};
+use std::sync::Arc;
+
+use super::error_response;
+use crate::managers::api_keys::{ApiKey, ApiKeyManager, ApiScope};
+
+/// Who may call the API: the `auth_token`, which may do everything, and
+/// the API keys.
+pub(super) struct ApiAuth {
+    pub(super) token: Option<Arc<str>>,
+    pub(super) keys: Arc<ApiKeyManager>,
+}
+
+/// Audio transcribed for a response, counted in the usage of the API key
+/// that asked for it.
+#[derive(Clone, Copy)]
+pub(super) struct TranscribedAudio(pub(super) f32);
+
+/// What a request may do, going by its bearer token.
+enum Access {
+    /// The `auth_token` was given, or the API is open.
+    Granted,
+    /// An API key with the route's scope.
+    Key(ApiKey),
+    /// An API key lacking the route's scope.
+    Forbidden(ApiKey, ApiScope),
+    /// No valid token or key where one is needed.
+    Unauthorized,
+}
+
+/// Reject requests without `Authorization: Bearer <token or key>`, and keys
+/// without `scope`. Routes without a scope are open to every key. Without a
+/// token or any active key, everything is allowed. Requests made with a key
+/// carry it as an extension.
+pub(super) async fn authorize(
+    State((auth, scope)): State<(Arc<ApiAuth>, Option<ApiScope>)>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+
+    let (key, response) = match check_access(&auth, scope, provided) {
+        Ok(Access::Granted) => return next.run(request).await,
+        Ok(Access::Unauthorized) => {
+            let mut response =
+                error_response(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token")
+                    .into_response();
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            return response;
+        }
+        Err(e) => {
+            error!("Failed to check API key: {}", e);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check API key")
+                .into_response();
+        }
+        Ok(Access::Forbidden(key, scope)) => {
+            let response = error_response(
+                StatusCode::FORBIDDEN,
+                format!("This API key lacks the '{}' scope", scope.as_str()),
+            )
+            .into_response();
+            (key, response)
+        }
+        Ok(Access::Key(key)) => {
+            request.extensions_mut().insert(key.clone());
+            (key, next.run(request).await)
+        }
+    };
+
+    let status = response.status();
+    let failed = status.is_client_error() || status.is_server_error();
+    let audio_seconds = response
+        .extensions()
+        .get::<TranscribedAudio>()
+        .map_or(0.0, |audio| audio.0 as f64);
+    let keys = auth.keys.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = keys.record_usage(key.id, failed, audio_seconds) {
+            warn!("Failed to record usage of API key {}: {}", key.id, e);
+        }
+    });
+    response
+}
+
+/// Decide what the `provided` bearer token may do on a route that needs
+/// `scope`. Fails only if the API keys couldn't be checked.
+fn check_access(
+    auth: &ApiAuth,
+    scope: Option<ApiScope>,
+    provided: Option<&str>,
+) -> anyhow::Result<Access> {
+    if let Some(token) = &auth.token {
+        if provided.is_some_and(|provided| tokens_match(provided, token)) {
+            return Ok(Access::Granted);
+        }
+    }
+    let key = match provided {
+        Some(provided) => auth.keys.authenticate(provided)?,
+        None => None,
+    };
+    let Some(key) = key else {
+        // Without a token or keys the API is open
+        let open = auth.token.is_none() && !auth.keys.has_active_keys().unwrap_or(true);
+        return Ok(if open {
+            Access::Granted
+        } else {
+            Access::Unauthorized
+        });
+    };
+    Ok(match scope.filter(|&scope| !key.allows(scope)) {
+        Some(scope) => Access::Forbidden(key, scope),
+        None => Access::Key(key),
+    })
+}
+
+/// Compare tokens without returning early on the first differing byte.
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{HeadlessHost, Host};
+
+    fn auth(dir: &tempfile::TempDir, token: Option<&str>) -> ApiAuth {
+        let host = HeadlessHost::new(dir.path().to_path_buf(), dir.path().to_path_buf()).unwrap();
+        ApiAuth {
+            token: token.map(Arc::from),
+            keys: Arc::new(ApiKeyManager::new(&Host::Headless(Arc::new(host))).unwrap()),
+        }
+    }
+
+    #[test]
+    fn open_without_token_or_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let auth = auth(&dir, None);
+        let access = check_access(&auth, Some(ApiScope::Admin), None).unwrap();
+        assert!(matches!(access, Access::Granted));
+        let access = check_access(&auth, Some(ApiScope::Admin), Some("anything")).unwrap();
+        assert!(matches!(access, Access::Granted));
+    }
+
+    #[test]
+    fn token_is_required_once_configured() {
+        let dir = tempfile::tempdir().unwrap();
+        let auth = auth(&dir, Some("secret"));
+        let scope = Some(ApiScope::Transcribe);
+        assert!(matches!(
+            check_access(&auth, scope, None).unwrap(),
+            Access::Unauthorized
+        ));
+        assert!(matches!(
+            check_access(&auth, scope, Some("secreT")).unwrap(),
+            Access::Unauthorized
+        ));
+        assert!(matches!(
+            check_access(&auth, scope, Some("secret")).unwrap(),
+            Access::Granted
+        ));
+    }
+
+    #[test]
+    fn keys_are_checked_against_the_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        let auth = auth(&dir, None);
+        let (key, secret) = auth.keys.create("ci", &[ApiScope::Transcribe]).unwrap();
+
+        // An active key closes the API to anonymous requests
+        assert!(matches!(
+            check_access(&auth, None, None).unwrap(),
+            Access::Unauthorized
+        ));
+        assert!(matches!(
+            check_access(&auth, None, Some("hk_guess")).unwrap(),
+            Access::Unauthorized
+        ));
+
+        match check_access(&auth, Some(ApiScope::Transcribe), Some(&secret)).unwrap() {
+            Access::Key(found) => assert_eq!(found.id, key.id),
+            _ => panic!("expected the key to be allowed"),
+        }
+        match check_access(&auth, Some(ApiScope::History), Some(&secret)).unwrap() {
+            Access::Forbidden(found, scope) => {
+                assert_eq!(found.id, key.id);
+                assert_eq!(scope, ApiScope::History);
+            }
+            _ => panic!("expected the key to be forbidden"),
+        }
+        assert!(matches!(
+            check_access(&auth, None, Some(&secret)).unwrap(),
+            Access::Key(_)
+        ));
+    }
+
+    #[test]
+    fn tokens_match_exactly() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+        assert!(!tokens_match("", "secret"));
+        assert!(tokens_match("", ""));
+    }
+}
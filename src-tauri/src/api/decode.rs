@@ -0,0 +1,762 @@
+//! Decoding uploads to 16kHz samples with symphonia, falling back to
+//! ffmpeg for formats it can't handle, keeping only the requested clip and
+//! spilling long mono audio to disk.
+
+use axum::{body::Bytes, http::StatusCode, response::Json};
+use log::{debug, warn};
+use std::io::{BufWriter, Read, Write};
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use tempfile::NamedTempFile;
+
+use super::upload::{AudioUpload, UploadFormat};
+use super::{error_response, ErrorResponse};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::format::{detect_format, FORMAT_SNIFF_LEN};
+use crate::audio_toolkit::resample::StreamingResampler;
+use crate::ffmpeg::{self, FfmpegError};
+
+/// Formats decoded natively by symphonia.
+const NATIVE_FORMATS: &[&str] = &["wav", "flac", "mp3", "ogg", "aac"];
+/// Additional formats handled when ffmpeg is installed.
+const FFMPEG_FORMATS: &[&str] = &["opus", "m4a", "webm", "amr", "aiff", "caf"];
+
+fn unsupported_format_response(
+    detected: &str,
+    details: String,
+) -> (StatusCode, Json<ErrorResponse>) {
+    let mut supported = NATIVE_FORMATS.to_vec();
+    supported.extend_from_slice(FFMPEG_FORMATS);
+    (
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        Json(ErrorResponse {
+            error: format!(
+                "Unsupported audio format, detected {}. {}",
+                detected, details
+            ),
+            code: None,
+            detected_format: Some(detected.to_string()),
+            supported_formats: Some(supported),
+        }),
+    )
+}
+
+/// Decoded 16kHz audio: one buffer per channel in memory, or raw f32le mono
+/// samples in a temp file once it outgrew the decode buffer.
+pub(super) enum DecodedAudio {
+    Memory(Vec<Vec<f32>>),
+    Spooled { file: NamedTempFile, samples: usize },
+}
+
+impl DecodedAudio {
+    /// Length of each channel in samples.
+    pub(super) fn num_samples(&self) -> usize {
+        match self {
+            DecodedAudio::Memory(channels) => channels.first().map_or(0, Vec::len),
+            DecodedAudio::Spooled { samples, .. } => *samples,
+        }
+    }
+
+    pub(super) fn num_channels(&self) -> usize {
+        match self {
+            DecodedAudio::Memory(channels) => channels.len(),
+            DecodedAudio::Spooled { .. } => 1,
+        }
+    }
+}
+
+/// Part of the decoded audio to keep, in 16kHz samples.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct Clip {
+    skip: usize,
+    /// Everything after `skip` when unset.
+    take: Option<usize>,
+}
+
+impl Clip {
+    /// The clip for `offset_seconds` and `duration_seconds`.
+    pub(super) fn from_secs(offset: f64, duration: Option<f64>) -> Self {
+        let samples = |secs: f64| (secs * WHISPER_SAMPLE_RATE as f64).round() as usize;
+        Self {
+            skip: samples(offset),
+            take: duration.map(samples),
+        }
+    }
+
+    /// `max_duration_secs` for the decoders, which have to get through the
+    /// skipped audio before the limit starts to count.
+    fn decode_limit(&self, max_duration_secs: u64) -> u64 {
+        if max_duration_secs == 0 {
+            return 0;
+        }
+        max_duration_secs + self.skip.div_ceil(WHISPER_SAMPLE_RATE as usize) as u64
+    }
+}
+
+/// Collects decoded 16kHz audio as the decoders produce it, switching from
+/// memory to a temp file once mono audio grows past `max_bytes`. Separate
+/// channels always stay in memory. Audio outside the clip is dropped.
+pub(super) struct DecodeBuffer {
+    channels: Vec<Vec<f32>>,
+    /// 0 keeps everything in memory.
+    max_bytes: usize,
+    spool: Option<(BufWriter<NamedTempFile>, usize)>,
+    clip: Clip,
+    /// Samples per channel pushed so far, kept or not.
+    seen: usize,
+}
+
+impl DecodeBuffer {
+    fn new(max_bytes: usize, clip: Clip) -> Self {
+        Self {
+            channels: Vec::new(),
+            max_bytes,
+            spool: None,
+            clip,
+            seen: 0,
+        }
+    }
+
+    /// Whether the end of the clip has been reached, so decoding can stop.
+    fn is_full(&self) -> bool {
+        self.clip
+            .take
+            .is_some_and(|take| self.seen >= self.clip.skip + take)
+    }
+
+    /// Append a piece of audio, one slice of equal length per channel.
+    fn push(&mut self, chunk: &[Vec<f32>]) -> Result<(), String> {
+        let Some(len) = chunk.first().map(Vec::len) else {
+            return Ok(());
+        };
+        let start = self.clip.skip.saturating_sub(self.seen).min(len);
+        let end = match self.clip.take {
+            Some(take) => (self.clip.skip + take).saturating_sub(self.seen).min(len),
+            None => len,
+        };
+        self.seen += len;
+        if start >= end {
+            return Ok(());
+        }
+        let chunk: Vec<&[f32]> = chunk.iter().map(|samples| &samples[start..end]).collect();
+
+        if let Some((writer, len)) = self.spool.as_mut() {
+            write_samples(writer, chunk[0])?;
+            *len += chunk[0].len();
+            return Ok(());
+        }
+
+        if self.channels.is_empty() {
+            self.channels = vec![Vec::new(); chunk.len()];
+        }
+        for (buf, samples) in self.channels.iter_mut().zip(chunk) {
+            buf.extend_from_slice(samples);
+        }
+
+        let bytes = self.channels[0].len() * std::mem::size_of::<f32>();
+        if self.max_bytes > 0 && self.channels.len() == 1 && bytes > self.max_bytes {
+            debug!(
+                "Decoded audio exceeds {} bytes, spilling to disk",
+                self.max_bytes
+            );
+            let file = NamedTempFile::new()
+                .map_err(|e| format!("Failed to create temp file for decoded audio: {}", e))?;
+            let mut writer = BufWriter::new(file);
+            let samples = std::mem::take(&mut self.channels[0]);
+            write_samples(&mut writer, &samples)?;
+            self.spool = Some((writer, samples.len()));
+            self.channels.clear();
+        }
+        Ok(())
+    }
+
+    /// Drop everything collected so far, e.g. before trying another decoder.
+    fn clear(&mut self) {
+        self.channels.clear();
+        self.spool = None;
+        self.seen = 0;
+    }
+
+    /// Merge two identical channels into one; ffmpeg upmixes mono sources
+    /// by duplicating the channel.
+    fn merge_identical_channels(&mut self) {
+        if self.channels.len() == 2 && self.channels[0] == self.channels[1] {
+            self.channels.truncate(1);
+        }
+    }
+
+    fn finish(self) -> Result<DecodedAudio, String> {
+        match self.spool {
+            Some((writer, samples)) => {
+                let file = writer
+                    .into_inner()
+                    .map_err(|e| format!("Failed to spill decoded audio: {}", e))?;
+                Ok(DecodedAudio::Spooled { file, samples })
+            }
+            None => Ok(DecodedAudio::Memory(self.channels)),
+        }
+    }
+}
+
+fn write_samples(writer: &mut impl Write, samples: &[f32]) -> Result<(), String> {
+    samples
+        .iter()
+        .try_for_each(|sample| writer.write_all(&sample.to_le_bytes()))
+        .map_err(|e| format!("Failed to spill decoded audio: {}", e))
+}
+
+/// Decode an upload to 16kHz samples in memory, falling back to ffmpeg for
+/// formats symphonia can't handle, and map failures to an error response.
+///
+/// On success at least one channel with at least one sample is returned.
+pub(super) fn decode_upload(
+    upload: &AudioUpload,
+    upload_format: &UploadFormat,
+    max_duration_secs: u64,
+    keep_channels: bool,
+) -> Result<Vec<Vec<f32>>, (StatusCode, Json<ErrorResponse>)> {
+    match decode_upload_bounded(
+        upload,
+        upload_format,
+        max_duration_secs,
+        keep_channels,
+        0,
+        Clip::default(),
+    )? {
+        DecodedAudio::Memory(channels) => Ok(channels),
+        // Nothing is spilled without a buffer limit
+        DecodedAudio::Spooled { .. } => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Decoded audio was spilled to disk unexpectedly",
+        )),
+    }
+}
+
+/// Like [`decode_upload`], but only `clip` is kept and decoded mono audio
+/// past `buffer_bytes` is spilled to a temp file; 0 keeps everything in
+/// memory. `max_duration_secs` applies to the clip.
+pub(super) fn decode_upload_bounded(
+    upload: &AudioUpload,
+    upload_format: &UploadFormat,
+    max_duration_secs: u64,
+    keep_channels: bool,
+    buffer_bytes: usize,
+    clip: Clip,
+) -> Result<DecodedAudio, (StatusCode, Json<ErrorResponse>)> {
+    let max_duration_secs = clip.decode_limit(max_duration_secs);
+    let mut buffer = DecodeBuffer::new(buffer_bytes, clip);
+    if let Err(e) = decode_audio(
+        upload,
+        upload_format,
+        max_duration_secs,
+        keep_channels,
+        &mut buffer,
+    ) {
+        // Try ffmpeg as fallback (handles OGG Opus from Telegram, etc.)
+        debug!("Symphonia decode failed ({}), trying ffmpeg fallback", e);
+        buffer.clear();
+        if let Err(ff_err) =
+            decode_with_ffmpeg(upload, max_duration_secs, keep_channels, &mut buffer)
+        {
+            let details = format!("Symphonia: {}. ffmpeg: {}", e, ff_err);
+            let format = upload_format.best_guess();
+            if matches!(ff_err, FfmpegError::DecodeTimeout(_)) {
+                let mut response = error_response(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Failed to decode audio. {}", details),
+                );
+                response.1.code = Some("decode_timeout");
+                return Err(response);
+            }
+            // A recognised native format that still failed is most likely corrupt
+            if format.is_some_and(|f| NATIVE_FORMATS.contains(&f)) {
+                return Err(error_response(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Failed to decode audio. {}", details),
+                ));
+            }
+            let mut response = unsupported_format_response(format.unwrap_or("unknown"), details);
+            if matches!(ff_err, FfmpegError::NotFound { .. }) {
+                response.1.code = Some("ffmpeg_not_found");
+            }
+            return Err(response);
+        }
+    }
+
+    let decoded = match buffer.finish() {
+        Ok(decoded) => decoded,
+        Err(e) => return Err(error_response(StatusCode::INTERNAL_SERVER_ERROR, e)),
+    };
+    if decoded.num_samples() == 0 {
+        return Err(error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            if clip.skip > 0 {
+                "'offset_seconds' is past the end of the audio"
+            } else {
+                "Decoded audio contains no samples"
+            },
+        ));
+    }
+    Ok(decoded)
+}
+
+/// Decode a complete audio file held in memory to 16kHz mono samples, with
+/// the same format detection, ffmpeg fallback and duration limit as
+/// /transcribe. `file_extension` is used as a format hint.
+pub(crate) fn decode_audio_bytes(
+    bytes: Bytes,
+    file_extension: Option<String>,
+    max_duration_secs: u64,
+) -> Result<Vec<f32>, String> {
+    let format = UploadFormat {
+        mime_type: None,
+        file_extension,
+        detected: detect_format(&bytes[..bytes.len().min(FORMAT_SNIFF_LEN)]),
+    };
+    let upload = AudioUpload::Memory(bytes);
+    let mut channels = decode_upload(&upload, &format, max_duration_secs, false)
+        .map_err(|(_, Json(response))| response.error)?;
+
+    let samples = channels.swap_remove(0);
+    let max_samples = max_duration_secs * WHISPER_SAMPLE_RATE as u64;
+    if max_duration_secs > 0 && samples.len() as u64 > max_samples {
+        return Err(format!(
+            "Audio exceeds the maximum duration of {} seconds",
+            max_duration_secs
+        ));
+    }
+    Ok(samples)
+}
+
+/// Decode audio bytes using symphonia (supports WAV, MP3, FLAC, OGG Vorbis, AAC).
+/// Passes f32 samples resampled to 16kHz to `out` packet by packet, one
+/// buffer per channel when `keep_channels` is set, otherwise a single mono
+/// downmix.
+///
+/// When `max_duration_secs` is non-zero, decoding stops shortly after that
+/// duration is exceeded so the caller can reject the request without decoding
+/// the whole file.
+fn decode_audio(
+    upload: &AudioUpload,
+    format: &UploadFormat,
+    max_duration_secs: u64,
+    keep_channels: bool,
+    out: &mut DecodeBuffer,
+) -> Result<(), String> {
+    let mss = MediaSourceStream::new(upload.media_source()?, Default::default());
+
+    let hint = format.hint();
+    let format_opts = FormatOptions::default();
+    let metadata_opts = MetadataOptions::default();
+    let decoder_opts = DecoderOptions::default();
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &format_opts, &metadata_opts)
+        .map_err(|e| format!("Failed to probe audio format: {}", e))?;
+
+    let mut format = probed.format;
+    let track = format
+        .default_track()
+        .ok_or_else(|| "No audio track found".to_string())?;
+
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        .ok_or_else(|| "Unknown sample rate".to_string())?;
+    let channels = track.codec_params.channels.map(|c| c.count()).unwrap_or(1);
+
+    let track_id = track.id;
+
+    // Stop one second past the limit; enough for the caller to detect overflow
+    let max_frames = if max_duration_secs > 0 {
+        Some((max_duration_secs + 1) as usize * sample_rate as usize)
+    } else {
+        None
+    };
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &decoder_opts)
+        .map_err(|e| format!("Failed to create decoder: {}", e))?;
+
+    let out_channels = if keep_channels { channels.max(1) } else { 1 };
+    if sample_rate != WHISPER_SAMPLE_RATE {
+        debug!(
+            "Resampling from {}Hz to {}Hz ({} channels)",
+            sample_rate, WHISPER_SAMPLE_RATE, out_channels
+        );
+    }
+    let mut resamplers = (0..out_channels)
+        .map(|_| StreamingResampler::new(sample_rate as usize, WHISPER_SAMPLE_RATE as usize))
+        .collect::<Result<Vec<_>, _>>()?;
+    // One packet per channel at the source rate, then at 16kHz
+    let mut frames: Vec<Vec<f32>> = vec![Vec::new(); out_channels];
+    let mut resampled: Vec<Vec<f32>> = vec![Vec::new(); out_channels];
+    let mut decoded_frames = 0usize;
+
+    loop {
+        if out.is_full() {
+            break;
+        }
+        if max_frames.is_some_and(|max| decoded_frames > max) {
+            debug!(
+                "Decoded audio exceeds {}s limit, stopping early",
+                max_duration_secs
+            );
+            break;
+        }
+
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(symphonia::core::errors::Error::IoError(ref e))
+                if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break;
+            }
+            Err(symphonia::core::errors::Error::ResetRequired) => {
+                // Some formats require a reset after seeking
+                break;
+            }
+            Err(e) => return Err(format!("Error reading packet: {}", e)),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                let num_frames = decoded.capacity();
+                if num_frames == 0 {
+                    continue;
+                }
+                let mut sample_buf = SampleBuffer::<f32>::new(num_frames as u64, spec);
+                sample_buf.copy_interleaved_ref(decoded);
+                let samples = sample_buf.samples();
+
+                frames.iter_mut().for_each(Vec::clear);
+                if channels <= 1 {
+                    frames[0].extend_from_slice(samples);
+                } else if out_channels > 1 {
+                    // Deinterleave into per-channel buffers
+                    for frame in samples.chunks(channels) {
+                        for (buf, sample) in frames.iter_mut().zip(frame) {
+                            buf.push(*sample);
+                        }
+                    }
+                } else {
+                    // Convert to mono by averaging channels
+                    for chunk in samples.chunks(channels) {
+                        let mono: f32 = chunk.iter().sum::<f32>() / channels as f32;
+                        frames[0].push(mono);
+                    }
+                }
+                decoded_frames += frames[0].len();
+
+                for ((resampler, src), dst) in
+                    resamplers.iter_mut().zip(&frames).zip(&mut resampled)
+                {
+                    dst.clear();
+                    resampler.push(src, dst)?;
+                }
+                out.push(&resampled)?;
+            }
+            Err(symphonia::core::errors::Error::DecodeError(e)) => {
+                warn!("Decode error on packet (skipping): {}", e);
+                continue;
+            }
+            Err(e) => return Err(format!("Fatal decode error: {}", e)),
+        }
+    }
+
+    if decoded_frames == 0 {
+        return Err("No audio samples decoded".to_string());
+    }
+
+    for (resampler, dst) in resamplers.iter_mut().zip(&mut resampled) {
+        dst.clear();
+        resampler.finish(dst)?;
+    }
+    out.push(&resampled)
+}
+
+/// Decode audio using ffmpeg as a subprocess.
+/// This handles formats that symphonia doesn't support (e.g., OGG Opus from Telegram).
+/// Passes f32 samples at 16kHz to `out` as ffmpeg produces them: mono, or
+/// left/right buffers when `keep_channels` is set and the source has two
+/// distinct channels.
+///
+/// When `max_duration_secs` is non-zero, output is truncated one second past
+/// the limit so the caller can detect overflow.
+fn decode_with_ffmpeg(
+    upload: &AudioUpload,
+    max_duration_secs: u64,
+    keep_channels: bool,
+    out: &mut DecodeBuffer,
+) -> Result<(), FfmpegError> {
+    let out_channels: usize = if keep_channels { 2 } else { 1 };
+    let mut cmd = ffmpeg::command()?;
+    // Spooled uploads are read from disk so ffmpeg can seek (e.g. MP4 with a
+    // trailing moov atom); in-memory uploads are streamed over stdin.
+    let input_bytes = match upload {
+        AudioUpload::Memory(bytes) => {
+            cmd.args(["-i", "pipe:0"]).stdin(Stdio::piped());
+            Some(bytes.clone())
+        }
+        AudioUpload::Spooled { file, .. } => {
+            cmd.arg("-i").arg(file.path()).stdin(Stdio::null());
+            None
+        }
+    };
+    if max_duration_secs > 0 {
+        cmd.args(["-t", &(max_duration_secs + 1).to_string()]);
+    }
+    cmd.args([
+        "-f",
+        "f32le",
+        "-ar",
+        &WHISPER_SAMPLE_RATE.to_string(),
+        "-ac",
+        &out_channels.to_string(),
+        "-loglevel",
+        "error",
+        "pipe:1",
+    ])
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+
+    // Hide console window on Windows
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let mut child = cmd.spawn().map_err(|e| {
+        format!(
+            "Failed to start ffmpeg at {}: {}",
+            cmd.get_program().to_string_lossy(),
+            e
+        )
+    })?;
+    if let Err(e) = ffmpeg::attach(&child) {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(e.into());
+    }
+
+    // Write stdin in a separate thread to avoid deadlock with large files
+    let stdin = child.stdin.take();
+    let stdin_thread = std::thread::spawn(move || {
+        if let (Some(mut stdin), Some(input_bytes)) = (stdin, input_bytes) {
+            let _ = stdin.write_all(&input_bytes);
+            // stdin is dropped here, closing the pipe
+        }
+    });
+    // Likewise drain stderr so ffmpeg never blocks on it
+    let stderr = child.stderr.take();
+    let stderr_thread = std::thread::spawn(move || {
+        let mut message = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut message);
+        }
+        message
+    });
+
+    // Kill ffmpeg if it hangs, e.g. on malformed input; that also ends the
+    // read below
+    let stdout = child.stdout.take();
+    let child = Arc::new(Mutex::new(child));
+    let watchdog = ffmpeg::watchdog(&child);
+
+    // Convert raw f32le output to samples as it arrives
+    let read_result = match stdout {
+        Some(stdout) => read_f32le(stdout, out_channels, ffmpeg::max_output_bytes(), out),
+        None => Err("Failed to capture ffmpeg output".to_string()),
+    };
+    // Stop ffmpeg once the clip is complete
+    let full = out.is_full();
+    if read_result.is_err() || full {
+        let _ = child.lock().unwrap().kill();
+    }
+
+    let status = ffmpeg::wait(&child);
+    let timed_out = watchdog.finish();
+    let _ = stdin_thread.join();
+    let stderr = stderr_thread.join().unwrap_or_default();
+
+    timed_out?;
+    let status = status.map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+    let frames = read_result?;
+    if !status.success() && !full {
+        return Err(format!("ffmpeg exited with error: {}", stderr).into());
+    }
+
+    if frames == 0 {
+        return Err("ffmpeg produced no output".to_string().into());
+    }
+
+    debug!("ffmpeg decoded {} samples at 16kHz", frames);
+    if out_channels > 1 {
+        out.merge_identical_channels();
+    }
+    Ok(())
+}
+
+/// Read interleaved f32le frames of `channels` samples from `reader` into
+/// `out`, returning the number of frames read. Fails once more than
+/// `max_bytes` have been read.
+fn read_f32le(
+    mut reader: impl Read,
+    channels: usize,
+    max_bytes: Option<u64>,
+    out: &mut DecodeBuffer,
+) -> Result<usize, String> {
+    let frame_bytes = channels * std::mem::size_of::<f32>();
+    let mut bytes = vec![0u8; 64 * 1024];
+    let mut filled = 0;
+    let mut chunk: Vec<Vec<f32>> = vec![Vec::new(); channels];
+    let mut frames = 0;
+    let mut total_bytes = 0u64;
+
+    loop {
+        let read = match reader.read(&mut bytes[filled..]) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Failed to read ffmpeg output: {}", e)),
+        };
+        filled += read;
+        total_bytes += read as u64;
+        if let Some(max_bytes) = max_bytes.filter(|&max_bytes| total_bytes > max_bytes) {
+            return Err(format!(
+                "ffmpeg output exceeds {} MB; raise [ffmpeg] max_output_mb for longer audio",
+                max_bytes / (1024 * 1024)
+            ));
+        }
+
+        // Keep a trailing partial frame for the next read
+        let whole = filled - filled % frame_bytes;
+        chunk.iter_mut().for_each(Vec::clear);
+        for frame in bytes[..whole].chunks_exact(frame_bytes) {
+            for (buf, b) in chunk.iter_mut().zip(frame.chunks_exact(4)) {
+                buf.push(f32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+            }
+        }
+        bytes.copy_within(whole..filled, 0);
+        filled -= whole;
+
+        frames += chunk[0].len();
+        out.push(&chunk)?;
+        if out.is_full() {
+            break;
+        }
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Push `count` chunks of `len` mono samples, numbering the samples
+    /// on from those pushed before.
+    fn push_ramp(buffer: &mut DecodeBuffer, count: usize, len: usize) {
+        for _ in 0..count {
+            let start = buffer.seen as f32;
+            let samples = (0..len).map(|i| start + i as f32).collect();
+            buffer.push(&[samples]).unwrap();
+        }
+    }
+
+    fn memory(decoded: DecodedAudio) -> Vec<Vec<f32>> {
+        match decoded {
+            DecodedAudio::Memory(channels) => channels,
+            DecodedAudio::Spooled { .. } => panic!("expected audio in memory"),
+        }
+    }
+
+    #[test]
+    fn offset_past_the_end_keeps_nothing() {
+        let clip = Clip {
+            skip: 100,
+            take: Some(10),
+        };
+        let mut buffer = DecodeBuffer::new(0, clip);
+        push_ramp(&mut buffer, 3, 30);
+        assert!(!buffer.is_full());
+        assert_eq!(buffer.finish().unwrap().num_samples(), 0);
+
+        let mut buffer = DecodeBuffer::new(0, Clip::from_secs(10.0, None));
+        push_ramp(&mut buffer, 4, WHISPER_SAMPLE_RATE as usize);
+        assert_eq!(buffer.finish().unwrap().num_samples(), 0);
+    }
+
+    #[test]
+    fn take_spans_chunks() {
+        let clip = Clip {
+            skip: 5,
+            take: Some(10),
+        };
+        let mut buffer = DecodeBuffer::new(0, clip);
+        push_ramp(&mut buffer, 3, 4);
+        assert!(!buffer.is_full());
+        push_ramp(&mut buffer, 1, 4);
+        assert!(buffer.is_full());
+        // Audio past the clip is dropped
+        buffer.push(&[vec![99.0; 4]]).unwrap();
+
+        let channels = memory(buffer.finish().unwrap());
+        let expected: Vec<f32> = (5..15).map(|i| i as f32).collect();
+        assert_eq!(channels, [expected]);
+    }
+
+    #[test]
+    fn clip_applies_to_every_channel() {
+        let clip = Clip {
+            skip: 2,
+            take: Some(3),
+        };
+        let mut buffer = DecodeBuffer::new(0, clip);
+        buffer
+            .push(&[vec![0.0, 1.0, 2.0, 3.0], vec![0.0, -1.0, -2.0, -3.0]])
+            .unwrap();
+        buffer
+            .push(&[vec![4.0, 5.0, 6.0, 7.0], vec![-4.0, -5.0, -6.0, -7.0]])
+            .unwrap();
+
+        let decoded = buffer.finish().unwrap();
+        assert_eq!(decoded.num_channels(), 2);
+        assert_eq!(
+            memory(decoded),
+            [vec![2.0, 3.0, 4.0], vec![-2.0, -3.0, -4.0]]
+        );
+    }
+
+    #[test]
+    fn spills_clipped_mono_audio() {
+        let clip = Clip {
+            skip: 3,
+            take: None,
+        };
+        // Spill once more than 4 samples are kept
+        let mut buffer = DecodeBuffer::new(16, clip);
+        push_ramp(&mut buffer, 3, 4);
+
+        let DecodedAudio::Spooled { file, samples } = buffer.finish().unwrap() else {
+            panic!("expected audio spilled to disk");
+        };
+        assert_eq!(samples, 9);
+        let bytes = std::fs::read(file.path()).unwrap();
+        let spilled: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let expected: Vec<f32> = (3..12).map(|i| i as f32).collect();
+        assert_eq!(spilled, expected);
+    }
+}
@@ -0,0 +1,4925 @@
+mod auth;
+mod decode;
+mod upload;
+
+use axum::{
+    body::Bytes,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        DefaultBodyLimit, FromRequest, Multipart, Path, Query, Request, State,
+    },
+    http::{header, HeaderValue, StatusCode, Uri},
+    middleware,
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post, put},
+    Router,
+};
+use chrono::{Local, NaiveDate, TimeZone};
+use futures_util::future::{select, Either};
+use futures_util::StreamExt;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::{oneshot, OwnedMutexGuard};
+use transcribe_rs::formats::{Cue, SubtitleLayout, Transcript, TranscriptFormat};
+use transcribe_rs::longform::LongFormTranscriber;
+use transcribe_rs::{TranscriptionResult, TranscriptionSegment};
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityRequirement, SecurityScheme};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::alignment::WordAligner;
+use crate::analytics::{self, SpeakerAnalytics, TalkAnalytics, TalkSegment};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::denoise::denoise;
+use crate::audio_toolkit::format::{detect_format, FORMAT_SNIFF_LEN};
+use crate::audio_toolkit::silence::is_silent;
+use crate::benchmark::{BenchmarkResult, Benchmarker};
+use crate::caption_preview::{self, PreviewOptions};
+use crate::chapters::{self, Chapter, ChapterSegment};
+use crate::config::{ApiConfig, Config, LlmConfig};
+use crate::dictation::{DictationError, DictationSessions, MAX_SESSION_DURATION};
+use crate::entities::{Amount, Entities, Entity};
+use crate::events::{AppEvent, EventBus};
+use crate::ffmpeg::FfmpegError;
+use crate::host::Host;
+use crate::managers::api_keys::{ApiKey, ApiKeyManager, ApiKeyUsage, ApiScope};
+use crate::managers::catalog::{CatalogEntry, CatalogModel, ModelCatalog, RtfTier};
+use crate::managers::hardware::{GpuInfo, HardwareInfo, ModelMemory};
+use crate::managers::history::{
+    HistoryEntry, HistoryManager, HistoryQuery, HistorySegment, HistorySource, TranscriptionRecord,
+};
+use crate::managers::model::{EngineType, ModelInfo, ModelManager};
+use crate::managers::transcription::{
+    DecodingOptions, DetectedLanguage, TranscriptionManager, TranscriptionOptions,
+    TranscriptionQuality,
+};
+use crate::mcp::McpServer;
+use crate::meeting::{
+    MeetingError, MeetingOptions, MeetingRecorder, MeetingSegment, MeetingTranscript,
+};
+use crate::mentions::{self, Mention, MentionSegment};
+use crate::post_process::{PostProcessMode, PostProcessor};
+use crate::redact::redact;
+use crate::services::downloaded_models_of_engine;
+use crate::settings::TextReplacement;
+use crate::transcript_cache::{CacheKey, KeyBuilder, TranscriptCache};
+use crate::translation::{is_valid_language_code, Translator};
+use crate::uploads::{Upload, UploadError, UploadStore};
+use auth::{authorize, ApiAuth, TranscribedAudio};
+pub(crate) use decode::decode_audio_bytes;
+use decode::{decode_upload, decode_upload_bounded, Clip, DecodedAudio};
+use upload::{
+    accepts_plain_text, file_extension, is_multipart, read_body_upload, read_upload,
+    upload_too_large, AudioUpload, UploadFormat,
+};
+
+/// Default cap on the request body size (100 MB).
+const DEFAULT_MAX_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
+/// Default cap on the decoded audio duration (30 minutes).
+const DEFAULT_MAX_DURATION_SECS: u64 = 30 * 60;
+/// Decoded audio kept in memory per request by default (256 MB, about 70
+/// minutes at 16kHz).
+const DEFAULT_DECODE_BUFFER_BYTES: usize = 256 * 1024 * 1024;
+/// Upper bound for `offset_seconds` and `duration_seconds`.
+const MAX_CLIP_SECS: f64 = 1_000_000.0;
+/// Only the start of the audio is used for language detection.
+const LANGUAGE_DETECTION_SECS: u64 = 30;
+/// How long cached transcriptions are kept unless `cache_ttl_secs` is set.
+const DEFAULT_CACHE_TTL_SECS: u64 = 3600;
+/// How long an idle resumable upload is kept unless `upload_ttl_secs` is
+/// set (24 hours).
+const DEFAULT_UPLOAD_TTL_SECS: u64 = 24 * 60 * 60;
+/// Resumable uploads in progress at once.
+const MAX_UPLOADS: usize = 64;
+/// Response header confirming that an `ephemeral` request retained nothing.
+const EPHEMERAL_HEADER: &str = "x-handy-ephemeral";
+/// Tracks accepted by /transcribe/multitrack.
+const MAX_TRACKS: usize = 16;
+/// Keywords a /transcribe request may spot.
+const MAX_KEYWORDS: usize = 200;
+/// Longest `context` name of a dictation session, in characters.
+const MAX_CONTEXT_NAME_CHARS: usize = 200;
+/// How long stopping the server waits for in-flight requests before
+/// aborting them.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of candidates returned by /detect-language unless `top_n` is set.
+const DEFAULT_TOP_LANGUAGES: usize = 5;
+/// Number of entries returned by /history unless `limit` is set.
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Limits applied to /transcribe requests.
+///
+/// A value of 0 disables the corresponding limit.
+#[derive(Clone, Copy, Debug)]
+pub struct ApiLimits {
+    pub max_upload_bytes: usize,
+    pub max_duration_secs: u64,
+    /// Decoded audio beyond this is spilled to disk and transcribed window
+    /// by window.
+    pub decode_buffer_bytes: usize,
+}
+
+impl Default for ApiLimits {
+    fn default() -> Self {
+        Self {
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES,
+            max_duration_secs: DEFAULT_MAX_DURATION_SECS,
+            decode_buffer_bytes: DEFAULT_DECODE_BUFFER_BYTES,
+        }
+    }
+}
+
+impl ApiLimits {
+    /// Limits from `max_upload_mb`, `max_duration_secs` and
+    /// `decode_buffer_mb`, falling back to the defaults for unset values.
+    pub fn from_config(config: &ApiConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            max_upload_bytes: config
+                .max_upload_mb
+                .map(|mb| mb.saturating_mul(1024 * 1024))
+                .unwrap_or(defaults.max_upload_bytes),
+            max_duration_secs: config
+                .max_duration_secs
+                .unwrap_or(defaults.max_duration_secs),
+            decode_buffer_bytes: config
+                .decode_buffer_mb
+                .map(|mb| mb.saturating_mul(1024 * 1024))
+                .unwrap_or(defaults.decode_buffer_bytes),
+        }
+    }
+}
+
+struct ApiState {
+    host: Host,
+    transcription_manager: Arc<TranscriptionManager>,
+    model_manager: Arc<ModelManager>,
+    limits: ApiLimits,
+    started_at: Instant,
+    /// Number of /transcribe requests currently decoding or waiting on the
+    /// engine, MCP tool calls over HTTP included.
+    in_flight: Arc<AtomicUsize>,
+    /// Cap on `in_flight`; 0 means no limit.
+    max_concurrency: Arc<AtomicUsize>,
+    translator: Option<Arc<Translator>>,
+    /// Rebuilt when post-processing is reconfigured through `PUT /config`.
+    post_processor: Arc<RwLock<Arc<PostProcessor>>>,
+    llm: LlmConfig,
+    aligner: Option<Arc<WordAligner>>,
+    benchmarker: Arc<Benchmarker>,
+    history_manager: Arc<HistoryManager>,
+    cache: Option<Arc<TranscriptCache<TranscribeResponse>>>,
+    uploads: Arc<UploadStore>,
+    api_keys: Arc<ApiKeyManager>,
+    dictation: Arc<DictationSessions>,
+    events: Arc<EventBus>,
+    meeting: Arc<MeetingRecorder>,
+}
+
+impl ApiState {
+    fn max_concurrency(&self) -> usize {
+        self.max_concurrency.load(Ordering::SeqCst)
+    }
+
+    fn post_processor(&self) -> Arc<PostProcessor> {
+        self.post_processor.read().unwrap().clone()
+    }
+}
+
+/// Decrements the in-flight request counter when dropped.
+pub(crate) struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl<'a> InFlightGuard<'a> {
+    /// Count a request, or return `None` if `max` requests are already in
+    /// flight. A `max` of 0 means no limit.
+    pub(crate) fn try_new(counter: &'a AtomicUsize, max: usize) -> Option<Self> {
+        let previous = counter.fetch_add(1, Ordering::SeqCst);
+        let guard = Self(counter);
+        if max > 0 && previous >= max {
+            return None;
+        }
+        Some(guard)
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+struct TranscribeResponse {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    segments: Option<Vec<SegmentResponse>>,
+    /// Present when `post_process` was requested; `text` stays the
+    /// transcript unless `return_raw` is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_processed: Option<PostProcessedResponse>,
+    /// Set when the audio was too short or quiet to contain speech and
+    /// wasn't transcribed.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    no_speech: bool,
+    /// Spoken language as detected or requested, e.g. "en"; absent when
+    /// the engine doesn't report it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    /// Duration of the decoded audio.
+    duration_seconds: f32,
+    /// Time from the upload being received to the response, including
+    /// decoding, translation and post-processing.
+    processing_ms: u64,
+    /// Set when the same audio was transcribed before.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_transcription: Option<PreviousTranscription>,
+    /// Talk time, pace and interruptions per speaker or channel, in JSON
+    /// responses with segments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analytics: Option<TalkAnalytics>,
+    /// Topical chapters with headings, when `chapters` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chapters: Option<Vec<Chapter>>,
+    /// Every mention of the requested `keywords`, in order of time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mentions: Option<Vec<Mention>>,
+    /// The subtitles in the spoken and the target language, when
+    /// `subtitle_tracks` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subtitle_tracks: Option<Vec<SubtitleTrack>>,
+}
+
+/// The latest earlier transcription of a re-uploaded recording.
+#[derive(Clone, Serialize, ToSchema)]
+struct PreviousTranscription {
+    /// Its id in `GET /history/{id}`.
+    history_id: i64,
+    /// Unix timestamp in seconds.
+    transcribed_at: i64,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+struct PostProcessedResponse {
+    /// `summarize`, `action_items`, `cleanup` or `entities`.
+    #[schema(value_type = String)]
+    mode: &'static str,
+    /// The processed text; the entities as JSON for `entities`.
+    text: String,
+    /// The entities found, for `entities`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entities: Option<Entities>,
+}
+
+/// A subtitle file of a translated transcript in one of its languages.
+#[derive(Clone, Serialize, ToSchema)]
+struct SubtitleTrack {
+    /// Language code, e.g. "de"; absent for the spoken language when the
+    /// engine doesn't report it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    /// Set for the translation, unset for the original.
+    translated: bool,
+    /// `srt` or `vtt`.
+    #[schema(value_type = String)]
+    format: &'static str,
+    content: String,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+struct SegmentResponse {
+    start: f32,
+    end: f32,
+    text: String,
+    /// The text in the spoken language, when `text` is a translation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel: Option<String>,
+    /// Track label from /transcribe/multitrack, e.g. the participant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speaker: Option<String>,
+    /// Word timings, present when `align` was requested. Words are in the
+    /// spoken language even when the segment text is translated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    words: Option<Vec<WordResponse>>,
+}
+
+impl SegmentResponse {
+    /// Speaker or channel label prefixed to the text in plain output.
+    fn label(&self) -> Option<&str> {
+        self.speaker.as_deref().or(self.channel.as_deref())
+    }
+
+    /// The text subtitles show in `language`; only translated segments
+    /// have a second one.
+    fn subtitle_text(&self, language: SubtitleLanguage) -> String {
+        match (language, &self.original) {
+            (SubtitleLanguage::Original, Some(original)) => original.clone(),
+            (SubtitleLanguage::Dual, Some(original)) => format!("{}\n{}", original, self.text),
+            _ => self.text.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+struct WordResponse {
+    start: f32,
+    end: f32,
+    text: String,
+}
+
+/// Body of `POST /uploads`; every field is optional.
+#[derive(Default, Deserialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+struct CreateUploadRequest {
+    /// Total size in bytes. When given, transcription waits for all of it.
+    length: Option<u64>,
+    /// Content type of the audio, e.g. `audio/mpeg`.
+    content_type: Option<String>,
+    /// File name, whose extension helps to identify the format.
+    filename: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct UploadResponse {
+    id: String,
+    /// Bytes received so far; the next chunk starts here.
+    offset: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    length: Option<u64>,
+    /// Seconds the upload is kept after its last chunk.
+    expires_in_secs: u64,
+}
+
+/// Body of `POST /sessions`; every field is optional.
+#[derive(Default, Deserialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+struct StartSessionRequest {
+    /// Input device to record from, as listed in the app's microphone
+    /// setting; the selected microphone when omitted.
+    device: Option<String>,
+    /// Paste the text into the focused app when the session stops, like a
+    /// shortcut would.
+    paste: bool,
+    /// Name of a document dictated over several sessions, e.g. `report`.
+    /// The session is transcribed with the end of the text dictated so far
+    /// under this name as context, keeping names and formatting consistent.
+    context: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct SessionResponse {
+    id: String,
+    /// Seconds after which a session that isn't stopped is cancelled.
+    expires_in_secs: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+struct SessionResultResponse {
+    text: String,
+    segments: Vec<SegmentResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    /// Duration of the recording.
+    duration_seconds: f32,
+    /// Set when the text was pasted into the focused app.
+    pasted: bool,
+}
+
+/// Body of `POST /meeting`; every field is optional.
+#[derive(Deserialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+struct StartMeetingRequest {
+    /// Label segments "Me" (the microphone) or "Others" (the system audio).
+    speaker_labels: bool,
+    /// Record the system audio next to the microphone.
+    system_audio: bool,
+}
+
+impl Default for StartMeetingRequest {
+    fn default() -> Self {
+        let options = MeetingOptions::default();
+        Self {
+            speaker_labels: options.speaker_labels,
+            system_audio: options.system_audio,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct MeetingResponse {
+    /// Unix timestamp in milliseconds.
+    started_at: i64,
+    /// Duration of the audio transcribed so far.
+    duration_seconds: f32,
+    /// The transcript as text, a line per segment with its time and speaker.
+    text: String,
+    segments: Vec<MeetingSegment>,
+    /// The history entry the transcript is saved in, once it has text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    history_id: Option<i64>,
+}
+
+impl From<MeetingTranscript> for MeetingResponse {
+    fn from(transcript: MeetingTranscript) -> Self {
+        Self {
+            started_at: transcript.started_at,
+            duration_seconds: transcript.duration_ms as f32 / 1000.0,
+            text: transcript.text(),
+            segments: transcript.segments,
+            history_id: transcript.history_id,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct DetectLanguageResponse {
+    /// Most likely first.
+    languages: Vec<DetectedLanguage>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct BenchmarkQuery {
+    /// Comma-separated model ids; the selected model when omitted.
+    models: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct BenchmarkResponse {
+    results: Vec<BenchmarkResult>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct BenchmarkHistoryResponse {
+    /// Oldest first.
+    results: Vec<BenchmarkResult>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ModelsResponse {
+    /// Sorted by id.
+    models: Vec<ModelInfo>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct HistoryParams {
+    /// Words that must all appear in the transcript.
+    q: Option<String>,
+    /// `app`, `api` or `meeting`.
+    source: Option<String>,
+    /// Unix timestamp in seconds or a `YYYY-MM-DD` local date, inclusive.
+    from: Option<String>,
+    /// Unix timestamp in seconds or a `YYYY-MM-DD` local date, inclusive.
+    to: Option<String>,
+    /// Only transcriptions of the recording with this `audio_hash`.
+    audio_hash: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ExportParams {
+    /// One of `txt` (default), `json`, `srt`, `vtt` or `md`.
+    format: Option<String>,
+    /// Characters per subtitle line (SRT and VTT; 42 if another limit is
+    /// given).
+    max_line_chars: Option<String>,
+    /// Lines per subtitle (SRT and VTT; default 2).
+    max_lines: Option<String>,
+    /// Reading speed subtitles are timed for (SRT and VTT; default 17).
+    max_chars_per_second: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct HistoryResponse {
+    /// Newest first.
+    entries: Vec<HistoryEntry>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ErrorResponse {
+    error: String,
+    /// Machine-readable reason for some errors: `ffmpeg_not_found` when the
+    /// audio needs ffmpeg and it isn't installed, `decode_timeout` when
+    /// ffmpeg hung on it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    code: Option<&'static str>,
+    /// Set with 415 for audio that couldn't be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detected_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<String>>)]
+    supported_formats: Option<Vec<&'static str>>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct HealthResponse {
+    status: String,
+}
+
+#[derive(Serialize, ToSchema)]
+struct StatusResponse {
+    status: String,
+    engine: Option<String>,
+    model_id: Option<String>,
+    model_name: Option<String>,
+    /// One of "loaded", "loading" or "unloaded".
+    model_state: String,
+    queue_depth: usize,
+    uptime_secs: u64,
+    /// Unix timestamp in milliseconds.
+    last_transcription_at: Option<u64>,
+    /// Model ids used instead of the selected model, by language.
+    model_routes: BTreeMap<String, String>,
+    /// Memory taken by each loaded model, including draft and routed ones.
+    model_memory: Vec<ModelMemory>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct UnloadResponse {
+    /// The model that was unloaded, if one was loaded.
+    unloaded: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct CreateApiKeyRequest {
+    /// Who or what the key is for, e.g. "ci" or "alice".
+    name: String,
+    scopes: Vec<ApiScope>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct CreatedApiKeyResponse {
+    key: ApiKey,
+    /// The key to send as `Authorization: Bearer <secret>`. It isn't
+    /// stored and can't be shown again.
+    secret: String,
+}
+
+/// The engine configuration, as changed with `PUT /config`.
+#[derive(Serialize, ToSchema)]
+struct ConfigResponse {
+    /// Engine of the selected model, e.g. "Whisper".
+    engine: Option<String>,
+    /// The selected model; empty when none is.
+    model: String,
+    /// Default spoken language, or "auto".
+    language: String,
+    /// Cap on concurrent transcriptions; 0 means no limit.
+    max_concurrency: usize,
+    post_processing: PostProcessingConfig,
+}
+
+#[derive(Serialize, ToSchema)]
+struct PostProcessingConfig {
+    /// Whether the app post-processes dictation.
+    enabled: bool,
+    /// Provider the API's post-processing uses, unless `[llm] url` is set.
+    provider: String,
+    model: Option<String>,
+    prompt_id: Option<String>,
+    /// Whether `[llm] url` in the config file takes precedence over the
+    /// provider.
+    overridden_by_config: bool,
+}
+
+/// Changes to the engine configuration. Fields left out stay as they are.
+#[derive(Default, Deserialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+struct UpdateConfigRequest {
+    /// Downloaded model to select and load.
+    model: Option<String>,
+    /// Switch to this engine's first downloaded model, e.g. "parakeet";
+    /// ignored when `model` is set.
+    engine: Option<String>,
+    /// Default spoken language, e.g. "ja", or "auto".
+    language: Option<String>,
+    /// Cap on concurrent transcriptions; 0 means no limit.
+    max_concurrency: Option<usize>,
+    post_processing: Option<UpdatePostProcessing>,
+}
+
+#[derive(Default, Deserialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+struct UpdatePostProcessing {
+    enabled: Option<bool>,
+    provider: Option<String>,
+    /// Model for the provider, which is `provider` or the current one.
+    model: Option<String>,
+    /// API key for the provider. It is never returned.
+    api_key: Option<String>,
+    prompt_id: Option<String>,
+}
+
+/// Response for requests over the `max_concurrency` limit.
+fn too_busy() -> (StatusCode, Json<ErrorResponse>) {
+    error_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Too many transcription requests in progress, try again later",
+    )
+}
+
+fn error_response(status: StatusCode, msg: impl Into<String>) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        status,
+        Json(ErrorResponse {
+            error: msg.into(),
+            code: None,
+            detected_format: None,
+            supported_formats: None,
+        }),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "status",
+    security(()),
+    responses((status = 200, description = "The server is up", body = HealthResponse))
+)]
+async fn health() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+    })
+}
+
+/// Multipart body of /transcribe as documented in the OpenAPI document;
+/// the fields are parsed by [`TranscribeOptions::set`].
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct TranscribeForm {
+    /// The audio; the field may also be named `audio`.
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// Run RNNoise noise suppression before transcription.
+    denoise: Option<bool>,
+    /// `mix` (default) or `separate` to transcribe each channel on its own.
+    channels: Option<String>,
+    /// Spoken language such as `ja`, or `auto`; the selected language when
+    /// omitted.
+    language: Option<String>,
+    /// Comma-separated languages the detection may pick from when the
+    /// language is `auto`, e.g. `en,de` for a bilingual speaker.
+    language_hints: Option<String>,
+    /// Translate the transcript into this language, e.g. `de`.
+    target_language: Option<String>,
+    /// Add word-level timestamps to each segment.
+    align: Option<bool>,
+    /// `fast`, `balanced` (default) or `best`.
+    quality: Option<String>,
+    /// Names and domain terms to steer the model (Whisper only).
+    prompt: Option<String>,
+    /// 0 to 1 (Whisper only).
+    temperature: Option<f32>,
+    /// 1 to 16 (Whisper only).
+    beam_size: Option<i32>,
+    /// 1 to 10 (Whisper only).
+    best_of: Option<i32>,
+    /// 0.1 to 10 (Whisper only).
+    patience: Option<f32>,
+    /// Whisper only.
+    condition_on_previous_text: Option<bool>,
+    /// Start of the part of the audio to transcribe, in seconds. Returned
+    /// timestamps stay relative to the start of the file.
+    offset_seconds: Option<f64>,
+    /// Length of the part of the audio to transcribe, in seconds; up to the
+    /// end when omitted.
+    duration_seconds: Option<f64>,
+    /// `json` (default), `txt`, `srt`, `vtt` or `md`.
+    response_format: Option<String>,
+    /// Re-split SRT and VTT cues to this many characters per line (42 if
+    /// another subtitle limit is given).
+    max_line_chars: Option<usize>,
+    /// Lines per SRT or VTT cue (default 2).
+    max_lines: Option<usize>,
+    /// Reading speed SRT and VTT cues are timed for, in characters per
+    /// second (default 17).
+    max_chars_per_second: Option<f32>,
+    /// What translated SRT and VTT cues show: `translated` (default),
+    /// `original`, or `dual` for the original on the first line and the
+    /// translation on the second.
+    subtitle_language: Option<String>,
+    /// `srt` or `vtt` to add the translated transcript's subtitles to the
+    /// JSON response as one file per language.
+    subtitle_tracks: Option<String>,
+    /// `summarize`, `action_items`, `cleanup` or `entities`.
+    post_process: Option<String>,
+    /// Split the transcript into topical chapters with start and end
+    /// timestamps: `keywords` titles them with their most distinctive
+    /// words, `llm` has the LLM write the titles.
+    chapters: Option<String>,
+    /// Comma-separated keywords and phrases whose mentions are returned
+    /// with timestamps and context; may be repeated.
+    keywords: Option<String>,
+    /// Keep the transcript in `text` next to the post-processed text
+    /// (default true).
+    return_raw: Option<bool>,
+    /// Retain nothing: no history or cache entry, no transcript in the logs
+    /// and no files outliving the request. Send it before the file or as a
+    /// query parameter so large uploads are kept in memory too.
+    ephemeral: Option<bool>,
+}
+
+/// Multipart body of /transcribe/multitrack as documented in the OpenAPI
+/// document. The /transcribe options apply to every track.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct MultitrackForm {
+    /// One field per track, e.g. a participant of a call. Tracks are
+    /// expected to start at the same time.
+    #[schema(value_type = Vec<String>, format = Binary)]
+    file: Vec<Vec<u8>>,
+    /// Comma-separated speaker labels in track order; the file names when
+    /// omitted.
+    speakers: Option<String>,
+}
+
+/// Multipart body of /detect-language as documented in the OpenAPI
+/// document.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct DetectLanguageForm {
+    /// The audio; the field may also be named `audio`.
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// Number of candidates returned (default 5).
+    top_n: Option<usize>,
+    /// Keep the upload in memory; send it before the file, or in the
+    /// query.
+    ephemeral: Option<bool>,
+}
+
+/// Multipart body of /captions/preview as documented in the OpenAPI
+/// document.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct CaptionPreviewForm {
+    /// The video; the field may also be named `video`.
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// The transcript as SRT or WebVTT, e.g. from /transcribe with
+    /// `response_format=srt` or a history export. May be sent as a file.
+    subtitles: String,
+    /// Height of the preview in pixels, 144 to 1080 (default 360).
+    height: Option<u32>,
+    /// Start the preview this many seconds into the video.
+    offset_seconds: Option<f64>,
+    /// Render only this many seconds; the whole video when omitted.
+    duration_seconds: Option<f64>,
+}
+
+/// Per-request options sent as multipart text fields alongside the audio.
+#[derive(Debug)]
+struct TranscribeOptions {
+    /// Run RNNoise noise suppression before transcription.
+    denoise: bool,
+    channels: ChannelMode,
+    /// Spoken language (e.g. `ja`) instead of the selected language; also
+    /// picks the model from the `[models.routes]` config.
+    language: Option<String>,
+    /// Languages auto-detection is restricted to.
+    language_hints: Vec<String>,
+    /// Translate the transcript into this language (e.g. `de`).
+    target_language: Option<String>,
+    /// Add word-level timestamps to each segment by forced alignment.
+    align: bool,
+    /// Speed/accuracy trade-off; `best` runs a draft pass first.
+    quality: TranscriptionQuality,
+    /// Context for the model, e.g. names and domain terms (Whisper only).
+    prompt: Option<String>,
+    /// Temperature, beam size etc. overriding the model's defaults
+    /// (Whisper only).
+    decoding: DecodingOptions,
+    /// Transcribe only this part of the audio, in seconds.
+    offset_seconds: Option<f64>,
+    duration_seconds: Option<f64>,
+    /// Body format; anything but `json` is returned as a plain document.
+    response_format: TranscriptFormat,
+    /// Limits SRT and VTT cues are re-split to.
+    subtitles: SubtitleLimits,
+    /// Language of translated SRT and VTT cues.
+    subtitle_language: SubtitleLanguage,
+    /// Format of the per-language subtitle files of translated JSON
+    /// responses.
+    subtitle_tracks: Option<TranscriptFormat>,
+    /// Run the transcript through the LLM, e.g. to summarize it.
+    post_process: Option<PostProcessMode>,
+    /// Split the transcript into chapters, titled as given.
+    chapters: Option<ChapterTitles>,
+    /// Keywords and phrases to spot in the transcript.
+    keywords: Vec<String>,
+    /// Keep the transcript in `text` next to the post-processed text.
+    /// When false, `text` is replaced, e.g. for paste-ready dictation.
+    return_raw: bool,
+    /// Keep the audio and transcript out of the history, the cache, the
+    /// logs and the disk, e.g. for sensitive dictation.
+    ephemeral: bool,
+}
+
+/// The `max_line_chars`, `max_lines` and `max_chars_per_second` fields
+/// shaping SRT and VTT cues.
+#[derive(Clone, Copy, Debug, Default)]
+struct SubtitleLimits {
+    max_line_chars: Option<usize>,
+    max_lines: Option<usize>,
+    max_chars_per_second: Option<f32>,
+}
+
+impl SubtitleLimits {
+    /// Apply the field `name` if it is one of the limits.
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "max_line_chars" => self.max_line_chars = parse_number_field(name, value, 10..=200)?,
+            "max_lines" => self.max_lines = parse_number_field(name, value, 1..=10)?,
+            "max_chars_per_second" => {
+                self.max_chars_per_second = parse_number_field(name, value, 1.0..=100.0)?
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The layout with defaults for the limits not given, or `None` when
+    /// none is and segments are kept as transcribed.
+    fn layout(&self) -> Option<SubtitleLayout> {
+        if self.max_line_chars.is_none()
+            && self.max_lines.is_none()
+            && self.max_chars_per_second.is_none()
+        {
+            return None;
+        }
+        let defaults = SubtitleLayout::default();
+        Some(SubtitleLayout {
+            max_line_chars: self.max_line_chars.unwrap_or(defaults.max_line_chars),
+            max_lines: self.max_lines.unwrap_or(defaults.max_lines),
+            max_chars_per_second: self
+                .max_chars_per_second
+                .unwrap_or(defaults.max_chars_per_second),
+        })
+    }
+}
+
+/// What the cues of translated SRT and VTT output show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SubtitleLanguage {
+    /// The translation (default).
+    #[default]
+    Translated,
+    /// The transcript in the spoken language.
+    Original,
+    /// The original on the first line, the translation on the second.
+    Dual,
+}
+
+/// How subtitles are rendered in a reply to /transcribe.
+#[derive(Clone, Debug)]
+struct SubtitleOutput {
+    layout: Option<SubtitleLayout>,
+    language: SubtitleLanguage,
+    /// Format and target language of the per-language files added to JSON
+    /// responses.
+    tracks: Option<(TranscriptFormat, String)>,
+}
+
+/// Who writes the headings of `chapters`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+enum ChapterTitles {
+    Keywords,
+    Llm,
+}
+
+/// How multi-channel audio is handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+enum ChannelMode {
+    /// Downmix all channels to mono (default).
+    #[default]
+    Mix,
+    /// Transcribe each channel separately and interleave the segments by
+    /// timestamp, e.g. for call recordings with one speaker per channel.
+    Separate,
+}
+
+/// The `TranscribeOptions` that shape a cached /transcribe response.
+/// Options that only change how the response is rendered are left out, so
+/// such requests share an entry, and so is `ephemeral`, as those requests
+/// are never cached.
+#[derive(Serialize)]
+struct CachedOptions<'a> {
+    denoise: bool,
+    channels: ChannelMode,
+    language: Option<&'a str>,
+    language_hints: &'a [String],
+    target_language: Option<&'a str>,
+    align: bool,
+    quality: TranscriptionQuality,
+    prompt: Option<&'a str>,
+    temperature: Option<f32>,
+    beam_size: Option<i32>,
+    best_of: Option<i32>,
+    patience: Option<f32>,
+    condition_on_previous_text: Option<bool>,
+    offset_seconds: Option<f64>,
+    duration_seconds: Option<f64>,
+    /// Timed formats are rendered from segments, which the response then
+    /// carries.
+    segments: bool,
+    post_process: Option<&'static str>,
+    chapters: Option<ChapterTitles>,
+    keywords: &'a [String],
+}
+
+impl Default for TranscribeOptions {
+    fn default() -> Self {
+        Self {
+            denoise: false,
+            channels: ChannelMode::default(),
+            language: None,
+            language_hints: Vec::new(),
+            target_language: None,
+            align: false,
+            quality: TranscriptionQuality::default(),
+            prompt: None,
+            decoding: DecodingOptions::default(),
+            offset_seconds: None,
+            duration_seconds: None,
+            response_format: TranscriptFormat::default(),
+            subtitles: SubtitleLimits::default(),
+            subtitle_language: SubtitleLanguage::default(),
+            subtitle_tracks: None,
+            post_process: None,
+            chapters: None,
+            keywords: Vec::new(),
+            return_raw: true,
+            ephemeral: false,
+        }
+    }
+}
+
+impl TranscribeOptions {
+    /// Apply the query parameters of `uri` as fields.
+    fn set_query(&mut self, uri: &Uri) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+        let query = match Query::<Vec<(String, String)>>::try_from_uri(uri) {
+            Ok(Query(query)) => query,
+            Err(e) => return Err(error_response(StatusCode::BAD_REQUEST, e.body_text())),
+        };
+        for (name, value) in &query {
+            if let Err(e) = self.set(name, value) {
+                return Err(error_response(StatusCode::BAD_REQUEST, e));
+            }
+        }
+        Ok(())
+    }
+
+    /// The options a cached response depends on.
+    fn cached(&self) -> CachedOptions<'_> {
+        CachedOptions {
+            denoise: self.denoise,
+            channels: self.channels,
+            language: self.language.as_deref(),
+            language_hints: &self.language_hints,
+            target_language: self.target_language.as_deref(),
+            align: self.align,
+            quality: self.quality,
+            prompt: self.prompt.as_deref(),
+            temperature: self.decoding.temperature,
+            beam_size: self.decoding.beam_size,
+            best_of: self.decoding.best_of,
+            patience: self.decoding.patience,
+            condition_on_previous_text: self.decoding.condition_on_previous_text,
+            offset_seconds: self.offset_seconds,
+            duration_seconds: self.duration_seconds,
+            segments: matches!(
+                self.response_format,
+                TranscriptFormat::Srt | TranscriptFormat::Vtt | TranscriptFormat::Md
+            ),
+            post_process: self.post_process.map(|mode| mode.as_str()),
+            chapters: self.chapters,
+            keywords: &self.keywords,
+        }
+    }
+
+    /// How the reply renders subtitles.
+    fn subtitle_output(&self) -> SubtitleOutput {
+        SubtitleOutput {
+            layout: self.subtitles.layout(),
+            language: self.subtitle_language,
+            tracks: self.subtitle_tracks.zip(self.target_language.clone()),
+        }
+    }
+
+    /// Apply a multipart text field. Unknown fields are ignored.
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "denoise" => self.denoise = parse_bool_field(name, value)?,
+            "align" => self.align = parse_bool_field(name, value)?,
+            "ephemeral" => self.ephemeral = parse_bool_field(name, value)?,
+            "return_raw" => {
+                self.return_raw = value.trim().is_empty() || parse_bool_field(name, value)?
+            }
+            "channels" => {
+                self.channels = match value.trim() {
+                    "mix" | "" => ChannelMode::Mix,
+                    "separate" => ChannelMode::Separate,
+                    other => {
+                        return Err(format!(
+                            "Invalid value '{}' for 'channels': expected 'mix' or 'separate'",
+                            other
+                        ))
+                    }
+                }
+            }
+            "quality" => self.quality = match value.trim() {
+                "" => TranscriptionQuality::default(),
+                other => TranscriptionQuality::parse(other).ok_or_else(|| {
+                    format!(
+                        "Invalid value '{}' for 'quality': expected 'fast', 'balanced' or 'best'",
+                        other
+                    )
+                })?,
+            },
+            "prompt" => {
+                self.prompt = Some(value.trim().to_string()).filter(|v| !v.is_empty())
+            }
+            "temperature" => {
+                self.decoding.temperature = parse_number_field(name, value, 0.0..=1.0)?
+            }
+            "beam_size" => self.decoding.beam_size = parse_number_field(name, value, 1..=16)?,
+            "best_of" => self.decoding.best_of = parse_number_field(name, value, 1..=10)?,
+            "patience" => {
+                self.decoding.patience = parse_number_field(name, value, 0.1..=10.0)?
+            }
+            "offset_seconds" => {
+                self.offset_seconds = parse_number_field(name, value, 0.0..=MAX_CLIP_SECS)?
+            }
+            "duration_seconds" => {
+                self.duration_seconds = parse_number_field(name, value, 0.1..=MAX_CLIP_SECS)?
+            }
+            "max_line_chars" | "max_lines" | "max_chars_per_second" => {
+                self.subtitles.set(name, value)?
+            }
+            "condition_on_previous_text" => {
+                self.decoding.condition_on_previous_text = match value.trim() {
+                    "" => None,
+                    _ => Some(parse_bool_field(name, value)?),
+                }
+            }
+            "response_format" => {
+                self.response_format = match value.trim() {
+                    "" => TranscriptFormat::default(),
+                    other => parse_transcript_format(name, other)?,
+                }
+            }
+            "language" => {
+                let value = value.trim();
+                if !value.is_empty() && value != "auto" && !is_valid_language_code(value) {
+                    return Err(format!(
+                        "Invalid value '{}' for 'language': expected a language code such as 'ja' or 'auto'",
+                        value
+                    ));
+                }
+                self.language = Some(value.to_string()).filter(|v| !v.is_empty());
+            }
+            "language_hints" => {
+                let hints: Vec<String> = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|hint| !hint.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                if let Some(hint) = hints.iter().find(|hint| !is_valid_language_code(hint)) {
+                    return Err(format!(
+                        "Invalid language '{}' in 'language_hints': expected codes such as 'en,de'",
+                        hint
+                    ));
+                }
+                self.language_hints = hints;
+            }
+            "target_language" => {
+                let value = value.trim();
+                if !value.is_empty() && !is_valid_language_code(value) {
+                    return Err(format!(
+                        "Invalid value '{}' for 'target_language': expected a language code such as 'de'",
+                        value
+                    ));
+                }
+                self.target_language = Some(value.to_string()).filter(|v| !v.is_empty());
+            }
+            "post_process" => {
+                self.post_process = match value.trim() {
+                    "" | "none" => None,
+                    other => Some(PostProcessMode::parse(other).ok_or_else(|| {
+                        format!(
+                            "Invalid value '{}' for 'post_process': expected 'summarize', 'action_items', 'cleanup' or 'entities'",
+                            other
+                        )
+                    })?),
+                }
+            }
+            "subtitle_language" => {
+                self.subtitle_language = match value.trim() {
+                    "" | "translated" => SubtitleLanguage::Translated,
+                    "original" => SubtitleLanguage::Original,
+                    "dual" => SubtitleLanguage::Dual,
+                    other => {
+                        return Err(format!(
+                            "Invalid value '{}' for 'subtitle_language': expected 'translated', 'original' or 'dual'",
+                            other
+                        ))
+                    }
+                }
+            }
+            "subtitle_tracks" => {
+                self.subtitle_tracks = match value.trim() {
+                    "" => None,
+                    "srt" => Some(TranscriptFormat::Srt),
+                    "vtt" => Some(TranscriptFormat::Vtt),
+                    other => {
+                        return Err(format!(
+                            "Invalid value '{}' for 'subtitle_tracks': expected 'srt' or 'vtt'",
+                            other
+                        ))
+                    }
+                }
+            }
+            "chapters" => {
+                self.chapters = match value.trim().to_ascii_lowercase().as_str() {
+                    "" | "none" | "false" => None,
+                    "keywords" | "true" => Some(ChapterTitles::Keywords),
+                    "llm" => Some(ChapterTitles::Llm),
+                    other => {
+                        return Err(format!(
+                            "Invalid value '{}' for 'chapters': expected 'keywords' or 'llm'",
+                            other
+                        ))
+                    }
+                }
+            }
+            "keywords" => {
+                self.keywords.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|keyword| !keyword.is_empty())
+                        .map(str::to_string),
+                );
+                if self.keywords.len() > MAX_KEYWORDS {
+                    return Err(format!(
+                        "Too many keywords: at most {} are allowed",
+                        MAX_KEYWORDS
+                    ));
+                }
+            }
+            _ => debug!("Ignoring unknown multipart field '{}'", name),
+        }
+        Ok(())
+    }
+}
+
+fn parse_transcript_format(name: &str, value: &str) -> Result<TranscriptFormat, String> {
+    TranscriptFormat::parse(value).ok_or_else(|| {
+        format!(
+            "Invalid value '{}' for '{}': expected 'json', 'txt', 'srt', 'vtt' or 'md'",
+            value, name
+        )
+    })
+}
+
+fn parse_bool_field(name: &str, value: &str) -> Result<bool, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" | "" => Ok(false),
+        other => Err(format!(
+            "Invalid value '{}' for '{}': expected true or false",
+            other, name
+        )),
+    }
+}
+
+/// Parse an optional number within `range`; empty values unset it.
+fn parse_number_field<T>(
+    name: &str,
+    value: &str,
+    range: RangeInclusive<T>,
+) -> Result<Option<T>, String>
+where
+    T: FromStr + PartialOrd + std::fmt::Display,
+{
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<T>() {
+        Ok(number) if range.contains(&number) => Ok(Some(number)),
+        _ => Err(format!(
+            "Invalid value '{}' for '{}': expected a number from {} to {}",
+            value,
+            name,
+            range.start(),
+            range.end()
+        )),
+    }
+}
+
+/// Detailed readiness information for orchestrators.
+#[utoipa::path(
+    get,
+    path = "/status",
+    tag = "status",
+    responses((status = 200, description = "Model and queue state", body = StatusResponse))
+)]
+async fn status(State(state): State<Arc<ApiState>>) -> Json<StatusResponse> {
+    let tm = &state.transcription_manager;
+    let model_state = if tm.is_model_loaded() {
+        "loaded"
+    } else if tm.is_loading() {
+        "loading"
+    } else {
+        "unloaded"
+    };
+
+    let model_id = tm.get_current_model();
+    let model_info = model_id
+        .as_deref()
+        .and_then(|id| state.model_manager.get_model_info(id));
+
+    Json(StatusResponse {
+        status: "ok".to_string(),
+        engine: model_info
+            .as_ref()
+            .map(|info| format!("{:?}", info.engine_type)),
+        model_name: model_info.map(|info| info.name),
+        model_id,
+        model_state: model_state.to_string(),
+        queue_depth: state.in_flight.load(Ordering::SeqCst),
+        uptime_secs: state.started_at.elapsed().as_secs(),
+        last_transcription_at: tm.last_transcription_time(),
+        model_routes: tm.routes().clone(),
+        model_memory: tm.model_memory(),
+    })
+}
+
+/// Transcribe a multipart upload, or a raw audio body with the options as
+/// query parameters (`curl --data-binary @note.wav -H 'Content-Type:
+/// audio/wav' '.../transcribe?language=en'`).
+#[utoipa::path(
+    post,
+    path = "/transcribe",
+    tag = "transcription",
+    request_body(
+        content = TranscribeForm,
+        content_type = "multipart/form-data",
+        description = "The audio and options. The audio can also be sent as the raw request body with its Content-Type, and the options as query parameters."
+    ),
+    responses(
+        (status = 200, description = "The transcript; a plain document for response_format other than json or with Accept: text/plain", body = TranscribeResponse),
+        (status = 400, description = "Invalid options or no audio", body = ErrorResponse),
+        (status = 413, description = "Upload too large", body = ErrorResponse),
+        (status = 415, description = "Unsupported audio format", body = ErrorResponse),
+        (status = 422, description = "Audio couldn't be decoded or is too long", body = ErrorResponse),
+        (status = 502, description = "Translation or post-processing failed", body = ErrorResponse),
+        (status = 503, description = "Too many requests in progress", body = ErrorResponse)
+    )
+)]
+async fn transcribe(
+    State(state): State<Arc<ApiState>>,
+    request: Request,
+) -> Result<Response, impl IntoResponse> {
+    let Some(_in_flight) = InFlightGuard::try_new(&state.in_flight, state.max_concurrency()) else {
+        return Err(too_busy());
+    };
+
+    let mut upload: Option<(AudioUpload, UploadFormat)> = None;
+    let mut options = TranscribeOptions::default();
+    // `Accept: text/plain` asks for the bare transcript; an explicit
+    // response_format still wins
+    if accepts_plain_text(request.headers()) {
+        options.response_format = TranscriptFormat::Txt;
+    }
+
+    // Query parameters work in both modes; multipart fields override them
+    options.set_query(request.uri())?;
+
+    if is_multipart(request.headers()) {
+        let mut multipart = match Multipart::from_request(request, &()).await {
+            Ok(multipart) => multipart,
+            Err(e) => return Err(error_response(StatusCode::BAD_REQUEST, e.body_text())),
+        };
+
+        // Extract audio file and options from multipart
+        while let Ok(Some(field)) = multipart.next_field().await {
+            let name = field.name().unwrap_or("").to_string();
+            if name == "file" || name == "audio" {
+                match read_upload(field, state.limits.max_upload_bytes, !options.ephemeral).await {
+                    Ok(u) => upload = Some(u),
+                    Err(e) => return Err(e),
+                }
+            } else {
+                let value = match field.text().await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        return Err(error_response(
+                            StatusCode::BAD_REQUEST,
+                            format!("Failed to read field '{}': {}", name, e),
+                        ));
+                    }
+                };
+                if let Err(e) = options.set(&name, &value) {
+                    return Err(error_response(StatusCode::BAD_REQUEST, e));
+                }
+            }
+        }
+    } else {
+        match read_body_upload(request, state.limits.max_upload_bytes, !options.ephemeral).await {
+            Ok(u) => upload = Some(u),
+            Err(e) => return Err(e),
+        }
+    }
+
+    check_options(&state, &options)?;
+
+    let (upload, upload_format) = match upload {
+        Some(upload) => upload,
+        None => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "No audio file provided. Send a multipart field named 'file' or 'audio'.",
+            ));
+        }
+    };
+
+    if upload.is_empty() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Audio file is empty. Send it as a multipart field named 'file' or 'audio', or as the request body.",
+        ));
+    }
+
+    let ephemeral = options.ephemeral;
+    run_transcription(&state, upload, upload_format, options)
+        .await
+        .map(|reply| confirm_ephemeral(reply, ephemeral))
+}
+
+/// Transcribe several recordings of one conversation, e.g. a track per
+/// participant from a call, and merge their segments into one timeline
+/// labeled by speaker.
+#[utoipa::path(
+    post,
+    path = "/transcribe/multitrack",
+    tag = "transcription",
+    request_body(
+        content = MultitrackForm,
+        content_type = "multipart/form-data",
+        description = "One 'file' field per track, plus the options of /transcribe"
+    ),
+    responses(
+        (status = 200, description = "The merged transcript; a plain document for response_format other than json or with Accept: text/plain", body = TranscribeResponse),
+        (status = 400, description = "Invalid options, or no or too many tracks", body = ErrorResponse),
+        (status = 413, description = "Upload too large", body = ErrorResponse),
+        (status = 415, description = "Unsupported audio format", body = ErrorResponse),
+        (status = 422, description = "Audio couldn't be decoded or is too long", body = ErrorResponse),
+        (status = 502, description = "Translation or post-processing failed", body = ErrorResponse),
+        (status = 503, description = "Too many requests in progress", body = ErrorResponse)
+    )
+)]
+async fn transcribe_multitrack(
+    State(state): State<Arc<ApiState>>,
+    request: Request,
+) -> Result<Response, impl IntoResponse> {
+    let Some(_in_flight) = InFlightGuard::try_new(&state.in_flight, state.max_concurrency()) else {
+        return Err(too_busy());
+    };
+    let started = Instant::now();
+
+    let mut options = TranscribeOptions::default();
+    if accepts_plain_text(request.headers()) {
+        options.response_format = TranscriptFormat::Txt;
+    }
+    options.set_query(request.uri())?;
+
+    if !is_multipart(request.headers()) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Send the tracks as multipart/form-data fields named 'file' or 'audio'.",
+        ));
+    }
+    let mut multipart = match Multipart::from_request(request, &()).await {
+        Ok(multipart) => multipart,
+        Err(e) => return Err(error_response(StatusCode::BAD_REQUEST, e.body_text())),
+    };
+
+    let mut tracks: Vec<(AudioUpload, UploadFormat, Option<String>)> = Vec::new();
+    let mut speakers: Option<Vec<String>> = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let name = field.name().unwrap_or("").to_string();
+        if name == "file" || name == "audio" {
+            if tracks.len() == MAX_TRACKS {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("At most {} tracks can be transcribed at once", MAX_TRACKS),
+                ));
+            }
+            let filename = field.file_name().map(str::to_string);
+            let (upload, upload_format) =
+                read_upload(field, state.limits.max_upload_bytes, !options.ephemeral).await?;
+            tracks.push((upload, upload_format, filename));
+        } else {
+            let value = match field.text().await {
+                Ok(v) => v,
+                Err(e) => {
+                    return Err(error_response(
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed to read field '{}': {}", name, e),
+                    ));
+                }
+            };
+            if name == "speakers" {
+                speakers = Some(value.split(',').map(|s| s.trim().to_string()).collect());
+            } else if let Err(e) = options.set(&name, &value) {
+                return Err(error_response(StatusCode::BAD_REQUEST, e));
+            }
+        }
+    }
+
+    check_options(&state, &options)?;
+    if options.channels == ChannelMode::Separate {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "'channels=separate' is not supported for multitrack uploads; send each channel as its own track",
+        ));
+    }
+    if tracks.is_empty() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "No audio provided. Send one multipart field named 'file' or 'audio' per track.",
+        ));
+    }
+    if let Some(speakers) = &speakers {
+        if speakers.len() != tracks.len() {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "'speakers' names {} speakers for {} tracks",
+                    speakers.len(),
+                    tracks.len()
+                ),
+            ));
+        }
+    }
+
+    let mut uploads = Vec::with_capacity(tracks.len());
+    let mut labels = Vec::with_capacity(tracks.len());
+    for (index, (upload, upload_format, filename)) in tracks.into_iter().enumerate() {
+        let label = match &speakers {
+            Some(speakers) => speakers[index].clone(),
+            None => track_label(index, filename.as_deref()),
+        };
+        if upload.is_empty() {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                format!("The track of '{}' is empty", label),
+            ));
+        }
+        uploads.push((upload, upload_format));
+        labels.push(label);
+    }
+
+    let track_uploads: Vec<&AudioUpload> = uploads.iter().map(|(upload, _)| upload).collect();
+    let audio_hash = if options.ephemeral {
+        None
+    } else {
+        match audio_hash(&track_uploads).await {
+            Ok(hash) => Some(hash),
+            Err(e) => {
+                warn!("Not linking transcription to earlier uploads: {}", e);
+                None
+            }
+        }
+    };
+    let previous = previous_transcription(&state, audio_hash.as_deref()).await;
+
+    // Tracks of one call are in sync, so a clip applies to each of them
+    let max_duration_secs = state.limits.max_duration_secs;
+    let buffer_bytes = if options.align || options.ephemeral {
+        0
+    } else {
+        state.limits.decode_buffer_bytes
+    };
+    let clip_offset = options.offset_seconds.unwrap_or(0.0);
+    let clip = Clip::from_secs(clip_offset, options.duration_seconds);
+    let decoded = tokio::task::spawn_blocking(move || {
+        uploads
+            .iter()
+            .map(|(upload, upload_format)| {
+                decode_upload_bounded(
+                    upload,
+                    upload_format,
+                    max_duration_secs,
+                    false,
+                    buffer_bytes,
+                    clip,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await;
+    let decoded = match decoded {
+        Ok(Ok(decoded)) => decoded,
+        Ok(Err(e)) => return Err(e),
+        Err(e) => {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Decoding task panicked: {}", e),
+            ));
+        }
+    };
+    let num_samples = decoded
+        .iter()
+        .map(DecodedAudio::num_samples)
+        .max()
+        .unwrap_or(0);
+    let duration = num_samples as f32 / WHISPER_SAMPLE_RATE as f32;
+
+    let max_samples = max_duration_secs * WHISPER_SAMPLE_RATE as u64;
+    if max_duration_secs > 0 && num_samples as u64 > max_samples {
+        return Err(error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "Audio exceeds the maximum duration of {} seconds",
+                max_duration_secs
+            ),
+        ));
+    }
+
+    let tm = state.transcription_manager.clone();
+    let aligner = state.aligner.clone().filter(|_| options.align);
+    let denoise_audio = options.denoise;
+    let transcription_options = TranscriptionOptions {
+        quality: options.quality,
+        prompt: options.prompt.clone(),
+        decoding: options.decoding.clone(),
+        language: options.language.clone(),
+        language_hints: options.language_hints.clone(),
+        app: None,
+    };
+    let ephemeral = options.ephemeral;
+    let result = tokio::task::spawn_blocking(move || -> anyhow::Result<TranscribeResponse> {
+        let _suppressed = ephemeral.then(crate::redact::suppress);
+        tm.initiate_model_load();
+        let mut segments = Vec::new();
+        let mut language = None;
+        for (decoded, label) in decoded.into_iter().zip(labels) {
+            let (track_segments, track_language) = match decoded {
+                DecodedAudio::Memory(channels) => {
+                    let samples = channels.into_iter().next().unwrap_or_default();
+                    // Participants are silent on their own track most of
+                    // the time; engines tend to hallucinate text for that
+                    if is_silent(&samples, WHISPER_SAMPLE_RATE as usize) {
+                        debug!("No speech on the track of '{}'", label);
+                        continue;
+                    }
+                    let samples = if denoise_audio {
+                        denoise(&samples, WHISPER_SAMPLE_RATE as usize)
+                            .map_err(|e| anyhow::anyhow!("Denoising failed: {}", e))?
+                    } else {
+                        samples
+                    };
+                    transcribe_segments(&tm, samples, &transcription_options, aligner.as_deref())?
+                }
+                DecodedAudio::Spooled { file, .. } => {
+                    let response = transcribe_spooled(
+                        &tm,
+                        &file,
+                        &transcription_options,
+                        denoise_audio,
+                        duration,
+                    )?;
+                    (response.segments.unwrap_or_default(), response.language)
+                }
+            };
+            if language.is_none() {
+                language = track_language;
+            }
+            segments.extend(track_segments.into_iter().map(|seg| SegmentResponse {
+                speaker: Some(label.clone()),
+                ..seg
+            }));
+        }
+
+        // Interleave the tracks into a single conversation timeline
+        segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        Ok(TranscribeResponse {
+            text: segments_text(&segments),
+            no_speech: segments.is_empty(),
+            segments: Some(segments),
+            post_processed: None,
+            language,
+            duration_seconds: duration,
+            processing_ms: 0,
+            previous_transcription: None,
+            analytics: None,
+            chapters: None,
+            mentions: None,
+            subtitle_tracks: None,
+        })
+    })
+    .await;
+
+    let mut response = match result {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Transcription failed: {}", e),
+            ))
+        }
+        Err(e) => {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Transcription task panicked: {}", e),
+            ))
+        }
+    };
+    info!(
+        "API multitrack transcription result: {}",
+        redact(&response.text).always(ephemeral)
+    );
+    if clip_offset > 0.0 {
+        shift_segments(response.segments.iter_mut().flatten(), clip_offset as f32);
+    }
+    response.previous_transcription = previous;
+    finish_response(
+        &state,
+        &mut response,
+        options.target_language.as_deref(),
+        options.post_process,
+        num_samples,
+        audio_hash,
+        ephemeral,
+    )
+    .await?;
+    if let Some(titles) = options.chapters {
+        add_chapters(&state, &mut response, titles).await?;
+    }
+    if !options.keywords.is_empty() {
+        add_mentions(&mut response, &options.keywords);
+    }
+    response.processing_ms = started.elapsed().as_millis() as u64;
+    let reply = transcribe_reply(
+        response,
+        options.response_format,
+        options.subtitle_output(),
+        options.return_raw,
+    );
+    Ok(confirm_ephemeral(reply, ephemeral))
+}
+
+/// Label of a multitrack track without a `speakers` entry: the file name
+/// without its extension, or "track N" (1-based).
+fn track_label(index: usize, filename: Option<&str>) -> String {
+    filename
+        .and_then(|name| std::path::Path::new(name).file_stem())
+        .map(|stem| stem.to_string_lossy().trim().to_string())
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| format!("track {}", index + 1))
+}
+
+/// Mark the reply to an `ephemeral` request as such.
+fn confirm_ephemeral(mut reply: Response, ephemeral: bool) -> Response {
+    if ephemeral {
+        reply
+            .headers_mut()
+            .insert(EPHEMERAL_HEADER, HeaderValue::from_static("true"));
+    }
+    reply
+}
+
+/// Reject options the server isn't configured for before any audio is
+/// decoded.
+fn check_options(
+    state: &ApiState,
+    options: &TranscribeOptions,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if options.target_language.is_some() && state.translator.is_none() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Translation is not configured. Set HANDY_API_TRANSLATION_PROVIDER to enable 'target_language'.",
+        ));
+    }
+
+    if let Some(mode) = options.post_process {
+        if !state.post_processor().supports(mode) {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "No LLM is configured for post_process={}. Set HANDY_LLM_URL or select a post-processing provider and model in Handy.",
+                    mode.as_str()
+                ),
+            ));
+        }
+        // Only JSON has room for both texts; plain text can carry the
+        // processed text alone
+        let format_ok = match options.response_format {
+            TranscriptFormat::Json => true,
+            TranscriptFormat::Txt => !options.return_raw,
+            _ => false,
+        };
+        if !format_ok {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "'post_process' requires response_format=json, or txt with return_raw=false",
+            ));
+        }
+    }
+
+    if let Some(titles) = options.chapters {
+        if titles == ChapterTitles::Llm && !state.post_processor().has_llm() {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "No LLM is configured for chapters=llm. Set HANDY_LLM_URL or select a post-processing provider and model in Handy.",
+            ));
+        }
+        if options.response_format != TranscriptFormat::Json {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "'chapters' requires response_format=json",
+            ));
+        }
+    }
+
+    if !options.keywords.is_empty() && options.response_format != TranscriptFormat::Json {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "'keywords' requires response_format=json",
+        ));
+    }
+
+    let bilingual = options.subtitle_language != SubtitleLanguage::Translated
+        || options.subtitle_tracks.is_some();
+    if bilingual && options.target_language.is_none() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "'subtitle_language' and 'subtitle_tracks' require 'target_language'",
+        ));
+    }
+    if options.subtitle_tracks.is_some() && options.response_format != TranscriptFormat::Json {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "'subtitle_tracks' requires response_format=json",
+        ));
+    }
+    // Cues are shaped by their words, which the two lines of a dual cue
+    // don't share
+    if options.subtitle_language == SubtitleLanguage::Dual && options.subtitles.layout().is_some() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "'subtitle_language=dual' can't be combined with 'max_line_chars', 'max_lines' or 'max_chars_per_second'",
+        ));
+    }
+
+    if options.align && state.aligner.is_none() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Alignment is not configured. Set HANDY_API_ALIGN_MODEL to enable 'align'.",
+        ));
+    }
+    Ok(())
+}
+
+/// Decode and transcribe an upload for /transcribe and
+/// /uploads/{id}/transcribe, then translate, post-process, cache and store
+/// the result as requested.
+async fn run_transcription(
+    state: &ApiState,
+    upload: AudioUpload,
+    upload_format: UploadFormat,
+    options: TranscribeOptions,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let started = Instant::now();
+
+    // Re-uploads are linked to earlier transcriptions of the same audio.
+    // Ephemeral requests are neither linked nor cached
+    let ephemeral = options.ephemeral;
+    let audio_hash = if ephemeral {
+        None
+    } else {
+        match audio_hash(&[&upload]).await {
+            Ok(hash) => Some(hash),
+            Err(e) => {
+                warn!("Not linking transcription to earlier uploads: {}", e);
+                None
+            }
+        }
+    };
+    let previous = previous_transcription(state, audio_hash.as_deref()).await;
+
+    // Repeated uploads of the same audio with the same options get the
+    // earlier result
+    let cache_key = match (&state.cache, &audio_hash) {
+        (Some(cache), Some(audio_hash)) => match request_cache_key(state, audio_hash, &options) {
+            Ok(key) => {
+                if let Some(mut response) = cache.get(&key) {
+                    debug!("Returning cached transcription");
+                    response.previous_transcription = previous;
+                    response.processing_ms = started.elapsed().as_millis() as u64;
+                    let mut reply = transcribe_reply(
+                        response,
+                        options.response_format,
+                        options.subtitle_output(),
+                        options.return_raw,
+                    );
+                    reply
+                        .headers_mut()
+                        .insert("x-handy-cache", HeaderValue::from_static("hit"));
+                    return Ok(reply);
+                }
+                Some(key)
+            }
+            Err(e) => {
+                warn!("Not caching transcription: {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+
+    debug!(
+        "Received audio file: {} bytes{}",
+        upload.len(),
+        if upload.is_spooled() {
+            " (spooled to disk)"
+        } else {
+            ""
+        }
+    );
+    debug!(
+        "Upload format: detected={:?} content_type={:?} extension={:?}",
+        upload_format.detected, upload_format.mime_type, upload_format.file_extension
+    );
+
+    // Decode audio to f32 samples at 16kHz mono. Long audio is spilled to
+    // disk unless alignment needs all of it at once or nothing may be
+    // written
+    let max_duration_secs = state.limits.max_duration_secs;
+    let keep_channels = options.channels == ChannelMode::Separate;
+    let buffer_bytes = if options.align || options.ephemeral {
+        0
+    } else {
+        state.limits.decode_buffer_bytes
+    };
+    let clip_offset = options.offset_seconds.unwrap_or(0.0);
+    let clip = Clip::from_secs(clip_offset, options.duration_seconds);
+    let decoded = tokio::task::spawn_blocking(move || {
+        decode_upload_bounded(
+            &upload,
+            &upload_format,
+            max_duration_secs,
+            keep_channels,
+            buffer_bytes,
+            clip,
+        )
+    })
+    .await;
+    let decoded = match decoded {
+        Ok(Ok(decoded)) => decoded,
+        Ok(Err(e)) => return Err(e),
+        Err(e) => {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Decoding task panicked: {}", e),
+            ));
+        }
+    };
+    let num_samples = decoded.num_samples();
+    let duration = num_samples as f32 / WHISPER_SAMPLE_RATE as f32;
+
+    debug!(
+        "Decoded {} samples x {} channels at 16kHz{}",
+        num_samples,
+        decoded.num_channels(),
+        if matches!(decoded, DecodedAudio::Spooled { .. }) {
+            " (spilled to disk)"
+        } else {
+            ""
+        }
+    );
+
+    let max_samples = max_duration_secs * WHISPER_SAMPLE_RATE as u64;
+    if max_duration_secs > 0 && num_samples as u64 > max_samples {
+        return Err(error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "Audio exceeds the maximum duration of {} seconds",
+                max_duration_secs
+            ),
+        ));
+    }
+
+    // Ensure model is loaded, then transcribe
+    // transcribe() is blocking (holds mutex), so use spawn_blocking
+    let tm = state.transcription_manager.clone();
+    let target_language = options.target_language.clone();
+    let aligner = state.aligner.clone().filter(|_| options.align);
+    let response_format = options.response_format;
+    let subtitles = options.subtitle_output();
+    let post_process = options.post_process;
+    let chapters = options.chapters;
+    let keywords = options.keywords.clone();
+    let return_raw = options.return_raw;
+    // Translation, alignment, chapters, keywords and timed formats work on
+    // segments so their timestamps can be preserved
+    let detailed = target_language.is_some()
+        || aligner.is_some()
+        || chapters.is_some()
+        || !keywords.is_empty()
+        || matches!(
+            response_format,
+            TranscriptFormat::Srt | TranscriptFormat::Vtt | TranscriptFormat::Md
+        );
+    let transcription_options = TranscriptionOptions {
+        quality: options.quality,
+        prompt: options.prompt.clone(),
+        decoding: options.decoding.clone(),
+        language: options.language.clone(),
+        language_hints: options.language_hints.clone(),
+        app: None,
+    };
+
+    // Engines tend to hallucinate text for silence, so don't run them on
+    // it. Spilled audio is checked window by window instead
+    if let DecodedAudio::Memory(channels) = &decoded {
+        if channels
+            .iter()
+            .all(|samples| is_silent(samples, WHISPER_SAMPLE_RATE as usize))
+        {
+            debug!("No speech in the upload, skipping transcription");
+            let response = TranscribeResponse {
+                text: String::new(),
+                segments: detailed.then(Vec::new),
+                post_processed: None,
+                no_speech: true,
+                language: options.language.filter(|language| language != "auto"),
+                duration_seconds: duration,
+                processing_ms: started.elapsed().as_millis() as u64,
+                previous_transcription: previous,
+                analytics: None,
+                chapters: None,
+                mentions: None,
+                subtitle_tracks: None,
+            };
+            return Ok(transcribe_reply(
+                response,
+                response_format,
+                subtitles,
+                return_raw,
+            ));
+        }
+    }
+    let result = tokio::task::spawn_blocking(move || -> anyhow::Result<TranscribeResponse> {
+        let _suppressed = ephemeral.then(crate::redact::suppress);
+        tm.initiate_model_load();
+        let channels = match decoded {
+            DecodedAudio::Memory(channels) => channels,
+            DecodedAudio::Spooled { file, .. } => {
+                let mut response = transcribe_spooled(
+                    &tm,
+                    &file,
+                    &transcription_options,
+                    options.denoise,
+                    duration,
+                )?;
+                if !detailed {
+                    response.segments = None;
+                }
+                return Ok(response);
+            }
+        };
+        let num_channels = channels.len();
+        let mut segments = Vec::new();
+        // Channels may differ; report the first one that has a language
+        let mut language = None;
+
+        for (index, samples) in channels.into_iter().enumerate() {
+            let samples = if options.denoise {
+                let denoise_start = Instant::now();
+                let denoised = denoise(&samples, WHISPER_SAMPLE_RATE as usize)
+                    .map_err(|e| anyhow::anyhow!("Denoising failed: {}", e))?;
+                debug!("Denoised audio in {:?}", denoise_start.elapsed());
+                denoised
+            } else {
+                samples
+            };
+
+            if num_channels == 1 && !detailed {
+                let transcription = tm.transcribe_with_options(samples, &transcription_options)?;
+                return Ok(TranscribeResponse {
+                    text: transcription.text,
+                    segments: None,
+                    post_processed: None,
+                    no_speech: false,
+                    language: transcription.language,
+                    duration_seconds: duration,
+                    processing_ms: 0,
+                    previous_transcription: None,
+                    analytics: None,
+                    chapters: None,
+                    mentions: None,
+                    subtitle_tracks: None,
+                });
+            }
+
+            let label = (num_channels > 1).then(|| channel_label(index, num_channels));
+            let (channel_segments, channel_language) =
+                transcribe_segments(&tm, samples, &transcription_options, aligner.as_deref())?;
+            if language.is_none() {
+                language = channel_language;
+            }
+            segments.extend(channel_segments.into_iter().map(|seg| SegmentResponse {
+                channel: label.clone(),
+                ..seg
+            }));
+        }
+
+        // Interleave the channels into a single conversation timeline
+        segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        Ok(TranscribeResponse {
+            text: segments_text(&segments),
+            segments: Some(segments),
+            post_processed: None,
+            no_speech: false,
+            language,
+            duration_seconds: duration,
+            processing_ms: 0,
+            previous_transcription: None,
+            analytics: None,
+            chapters: None,
+            mentions: None,
+            subtitle_tracks: None,
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(mut response)) => {
+            info!(
+                "API transcription result: {}",
+                redact(&response.text).always(ephemeral)
+            );
+            // Timestamps of a clip count from the start of the file
+            if clip_offset > 0.0 {
+                shift_segments(response.segments.iter_mut().flatten(), clip_offset as f32);
+            }
+            response.previous_transcription = previous;
+            finish_response(
+                state,
+                &mut response,
+                target_language.as_deref(),
+                post_process,
+                num_samples,
+                audio_hash,
+                ephemeral,
+            )
+            .await?;
+            if let Some(titles) = chapters {
+                add_chapters(state, &mut response, titles).await?;
+            }
+            if !keywords.is_empty() {
+                add_mentions(&mut response, &keywords);
+            }
+            response.processing_ms = started.elapsed().as_millis() as u64;
+            if let (Some(cache), Some(key)) = (&state.cache, cache_key) {
+                cache.insert(key, response.clone());
+            }
+            Ok(transcribe_reply(
+                response,
+                response_format,
+                subtitles,
+                return_raw,
+            ))
+        }
+        Ok(Err(e)) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Transcription failed: {}", e),
+        )),
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Transcription task panicked: {}", e),
+        )),
+    }
+}
+
+/// Translate, post-process and store a transcript in the history as
+/// requested.
+async fn finish_response(
+    state: &ApiState,
+    response: &mut TranscribeResponse,
+    target_language: Option<&str>,
+    post_process: Option<PostProcessMode>,
+    num_samples: usize,
+    audio_hash: Option<String>,
+    ephemeral: bool,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if let (Some(target), Some(translator)) = (target_language, &state.translator) {
+        if let Err(e) = translate_response(translator, response, target).await {
+            return Err(error_response(
+                StatusCode::BAD_GATEWAY,
+                format!("Translation failed: {}", e),
+            ));
+        }
+        info!(
+            "API translation result ({}): {}",
+            target,
+            redact(&response.text).always(ephemeral)
+        );
+    }
+    if let Some(mode) = post_process {
+        if !response.text.trim().is_empty() {
+            let post_processor = state.post_processor();
+            let result = match mode {
+                PostProcessMode::Entities => post_processor
+                    .extract_entities(&response.text)
+                    .await
+                    .map(|entities| {
+                        let text = serde_json::to_string(&entities).unwrap_or_default();
+                        (text, Some(entities))
+                    }),
+                _ => post_processor
+                    .process(mode, &response.text)
+                    .await
+                    .map(|text| (text, None)),
+            };
+            match result {
+                Ok((text, entities)) => {
+                    debug!(
+                        "API post-processing result ({}): {}",
+                        mode.as_str(),
+                        redact(&text).always(ephemeral)
+                    );
+                    response.post_processed = Some(PostProcessedResponse {
+                        mode: mode.as_str(),
+                        text,
+                        entities,
+                    });
+                }
+                Err(e) => {
+                    return Err(error_response(
+                        StatusCode::BAD_GATEWAY,
+                        format!("Post-processing failed: {}", e),
+                    ));
+                }
+            }
+        }
+    }
+    if !response.text.is_empty() && !ephemeral {
+        let record = TranscriptionRecord {
+            source: HistorySource::Api,
+            transcription_text: response.text.clone(),
+            segments: response
+                .segments
+                .iter()
+                .flatten()
+                .map(|seg| HistorySegment {
+                    start: seg.start,
+                    end: seg.end,
+                    text: seg.text.clone(),
+                    speaker: seg.label().map(str::to_string),
+                })
+                .collect(),
+            post_processed_text: response
+                .post_processed
+                .as_ref()
+                .map(|processed| processed.text.clone()),
+            post_process_prompt: post_process
+                .filter(|_| response.post_processed.is_some())
+                .and_then(|mode| state.post_processor().prompt(mode))
+                .map(str::to_string),
+            model_id: state.transcription_manager.get_current_model(),
+            duration_ms: num_samples as i64 * 1000 / WHISPER_SAMPLE_RATE as i64,
+            audio_hash,
+            ..Default::default()
+        };
+        let hm = state.history_manager.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = hm.save_transcription_text(record) {
+                error!("Failed to save API transcription to history: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Split the (translated) transcript into chapters, with headings written
+/// by the LLM for `ChapterTitles::Llm`.
+async fn add_chapters(
+    state: &ApiState,
+    response: &mut TranscribeResponse,
+    titles: ChapterTitles,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let segments: Vec<ChapterSegment> = response
+        .segments
+        .iter()
+        .flatten()
+        .map(|seg| ChapterSegment {
+            start: seg.start,
+            end: seg.end,
+            text: &seg.text,
+        })
+        .collect();
+    let mut split = chapters::split(&segments);
+    if titles == ChapterTitles::Llm {
+        let post_processor = state.post_processor();
+        for chapter in &mut split {
+            let text = chapter.text(&segments);
+            match post_processor.chapter_title(&text).await {
+                Ok(title) if !title.is_empty() => chapter.title = title,
+                Ok(_) => {}
+                Err(e) => {
+                    return Err(error_response(
+                        StatusCode::BAD_GATEWAY,
+                        format!("Titling chapters failed: {}", e),
+                    ));
+                }
+            }
+        }
+    }
+    debug!("Split transcript into {} chapters", split.len());
+    response.chapters = Some(split);
+    Ok(())
+}
+
+/// Find the mentions of `keywords` in the (translated) transcript.
+fn add_mentions(response: &mut TranscribeResponse, keywords: &[String]) {
+    let segments: Vec<MentionSegment> = response
+        .segments
+        .iter()
+        .flatten()
+        .map(|seg| MentionSegment {
+            start: seg.start,
+            end: seg.end,
+            text: &seg.text,
+            speaker: seg.label(),
+            words: seg
+                .words
+                .as_ref()
+                .map(|words| words.iter().map(|word| (word.start, word.end)).collect()),
+        })
+        .collect();
+    let found = mentions::find(&segments, keywords);
+    debug!("Found {} keyword mentions", found.len());
+    response.mentions = Some(found);
+}
+
+/// Transcribe mono samples into segments, with word timings when `aligner`
+/// is given, and the spoken language if the engine reports it.
+fn transcribe_segments(
+    tm: &TranscriptionManager,
+    samples: Vec<f32>,
+    options: &TranscriptionOptions,
+    aligner: Option<&WordAligner>,
+) -> anyhow::Result<(Vec<SegmentResponse>, Option<String>)> {
+    let audio = aligner.map(|_| samples.clone());
+    let transcription = tm.transcribe_with_options(samples, options)?;
+
+    // One list of words per segment, empty without alignment
+    let mut words = match (aligner, audio) {
+        (Some(aligner), Some(audio)) => {
+            let align_start = Instant::now();
+            let words = aligner
+                .align(&audio, &transcription)
+                .map_err(|e| anyhow::anyhow!("Alignment failed: {}", e))?;
+            debug!("Aligned words in {:?}", align_start.elapsed());
+            words
+        }
+        _ => Vec::new(),
+    }
+    .into_iter();
+
+    let segments = transcription
+        .segments
+        .into_iter()
+        .map(|seg| SegmentResponse {
+            start: seg.start,
+            end: seg.end,
+            text: seg.text,
+            original: None,
+            channel: None,
+            speaker: None,
+            words: words.next().map(|words| {
+                words
+                    .into_iter()
+                    .map(|word| WordResponse {
+                        start: word.start,
+                        end: word.end,
+                        text: word.text,
+                    })
+                    .collect()
+            }),
+        })
+        .collect();
+    Ok((segments, transcription.language))
+}
+
+/// Move segments and their words `offset` seconds later.
+fn shift_segments<'a>(segments: impl Iterator<Item = &'a mut SegmentResponse>, offset: f32) {
+    for segment in segments {
+        segment.start += offset;
+        segment.end += offset;
+        for word in segment.words.iter_mut().flatten() {
+            word.start += offset;
+            word.end += offset;
+        }
+    }
+}
+
+/// Samples read at a time from audio spilled to disk.
+const SPOOL_READ_SAMPLES: usize = 10 * WHISPER_SAMPLE_RATE as usize;
+
+/// Transcribe audio spilled to disk by [`DecodeBuffer`](decode::DecodeBuffer)
+/// window by window, so memory use doesn't grow with its length. Silent
+/// windows are skipped.
+fn transcribe_spooled(
+    tm: &TranscriptionManager,
+    file: &NamedTempFile,
+    options: &TranscriptionOptions,
+    denoise_audio: bool,
+    duration: f32,
+) -> anyhow::Result<TranscribeResponse> {
+    let mut reader = file.reopen()?;
+    let mut stream = LongFormTranscriber::default().stream();
+    let mut speech = false;
+    let mut transcribe_window =
+        |samples: Vec<f32>| -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+            if is_silent(&samples, WHISPER_SAMPLE_RATE as usize) {
+                return Ok(TranscriptionResult {
+                    text: String::new(),
+                    segments: Some(Vec::new()),
+                    language: None,
+                });
+            }
+            speech = true;
+            let samples = if denoise_audio {
+                denoise(&samples, WHISPER_SAMPLE_RATE as usize)
+                    .map_err(|e| format!("Denoising failed: {}", e))?
+            } else {
+                samples
+            };
+            let transcription = tm.transcribe_with_options(samples, options)?;
+            Ok(TranscriptionResult {
+                text: transcription.text,
+                segments: Some(
+                    transcription
+                        .segments
+                        .into_iter()
+                        .map(|seg| TranscriptionSegment {
+                            start: seg.start,
+                            end: seg.end,
+                            text: seg.text,
+                        })
+                        .collect(),
+                ),
+                language: transcription.language,
+            })
+        };
+
+    let mut bytes = vec![0u8; SPOOL_READ_SAMPLES * std::mem::size_of::<f32>()];
+    loop {
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match reader.read(&mut bytes[filled..])? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+        let samples: Vec<f32> = bytes[..filled]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        stream
+            .push(&samples, &mut transcribe_window)
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+    }
+    let result = stream
+        .finish(&mut transcribe_window)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    Ok(TranscribeResponse {
+        text: result.text,
+        segments: Some(
+            result
+                .segments
+                .unwrap_or_default()
+                .into_iter()
+                .map(|seg| SegmentResponse {
+                    start: seg.start,
+                    end: seg.end,
+                    text: seg.text,
+                    original: None,
+                    channel: None,
+                    speaker: None,
+                    words: None,
+                })
+                .collect(),
+        ),
+        post_processed: None,
+        no_speech: !speech,
+        language: result.language,
+        duration_seconds: duration,
+        processing_ms: 0,
+        previous_transcription: None,
+        analytics: None,
+        chapters: None,
+        mentions: None,
+        subtitle_tracks: None,
+    })
+}
+
+/// Hash of the uploaded audio, stored in the history to recognize
+/// re-uploads of the same recording. Tracks are hashed together.
+async fn audio_hash(uploads: &[&AudioUpload]) -> Result<String, String> {
+    let sources = uploads
+        .iter()
+        .map(|upload| upload.media_source())
+        .collect::<Result<Vec<_>, _>>()?;
+    let key = tokio::task::spawn_blocking(move || {
+        sources
+            .into_iter()
+            .try_fold(KeyBuilder::default(), |key, source| key.reader(source))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Failed to hash upload: {}", e))?;
+    Ok(key.finish().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// The latest stored transcription of the audio with `audio_hash`.
+async fn previous_transcription(
+    state: &ApiState,
+    audio_hash: Option<&str>,
+) -> Option<PreviousTranscription> {
+    let query = HistoryQuery {
+        audio_hash: Some(audio_hash?.to_string()),
+        limit: Some(1),
+        ..Default::default()
+    };
+    let hm = state.history_manager.clone();
+    match tokio::task::spawn_blocking(move || hm.search_entries(&query)).await {
+        Ok(Ok(entries)) => entries.first().map(|entry| PreviousTranscription {
+            history_id: entry.id,
+            transcribed_at: entry.timestamp,
+        }),
+        Ok(Err(e)) => {
+            warn!("Failed to look up earlier transcriptions: {}", e);
+            None
+        }
+        Err(e) => {
+            warn!("History lookup task panicked: {}", e);
+            None
+        }
+    }
+}
+
+/// Cache key for a /transcribe request: the audio, the options and the
+/// settings that shape the transcript (model, language, custom words, ...).
+fn request_cache_key(
+    state: &ApiState,
+    audio_hash: &str,
+    options: &TranscribeOptions,
+) -> Result<CacheKey, String> {
+    let options = serde_json::to_string(&options.cached()).map_err(|e| e.to_string())?;
+    let settings = serde_json::to_string(&state.host.settings()).map_err(|e| e.to_string())?;
+    Ok(KeyBuilder::default()
+        .str(audio_hash)
+        .str(&options)
+        .str(&settings)
+        .finish())
+}
+
+/// Start a resumable upload. The audio is then sent in chunks with
+/// `PATCH /uploads/{id}` and transcribed with `POST /uploads/{id}/transcribe`.
+#[utoipa::path(
+    post,
+    path = "/uploads",
+    tag = "uploads",
+    request_body(content = Option<CreateUploadRequest>, content_type = "application/json"),
+    responses(
+        (status = 201, description = "The upload; its URL is in the Location header", body = UploadResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 413, description = "Declared length too large", body = ErrorResponse),
+        (status = 503, description = "Too many uploads in progress", body = ErrorResponse)
+    )
+)]
+async fn create_upload(
+    State(state): State<Arc<ApiState>>,
+    body: Bytes,
+) -> Result<Response, impl IntoResponse> {
+    let request: CreateUploadRequest = if body.iter().all(u8::is_ascii_whitespace) {
+        CreateUploadRequest::default()
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid upload request: {}", e),
+                ));
+            }
+        }
+    };
+
+    let max_upload_bytes = state.limits.max_upload_bytes;
+    if let Some(length) = request.length {
+        if max_upload_bytes > 0 && length > max_upload_bytes as u64 {
+            return Err(upload_too_large(max_upload_bytes));
+        }
+    }
+
+    let id = match state
+        .uploads
+        .create(request.length, request.content_type, request.filename)
+    {
+        Ok(id) => id,
+        Err(UploadError::TooMany) => {
+            return Err(error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Too many uploads in progress, try again later",
+            ));
+        }
+        Err(e) => {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                e.to_string(),
+            ))
+        }
+    };
+    debug!("Started upload {}", id);
+
+    let mut response = upload_response(&state, &id, 0, request.length);
+    *response.status_mut() = StatusCode::CREATED;
+    if let Ok(location) = HeaderValue::from_str(&format!("/uploads/{}", id)) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    Ok(response)
+}
+
+/// Bytes received so far, also as the `Upload-Offset` header. `HEAD` works
+/// too, for clients resuming after a dropped connection.
+#[utoipa::path(
+    get,
+    path = "/uploads/{id}",
+    tag = "uploads",
+    params(("id" = String, Path, description = "Upload id")),
+    responses(
+        (status = 200, description = "The upload", body = UploadResponse),
+        (status = 404, description = "No such upload, or it expired", body = ErrorResponse),
+        (status = 409, description = "A chunk is being written or the upload is being transcribed", body = ErrorResponse)
+    )
+)]
+async fn upload_status(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+) -> Result<Response, impl IntoResponse> {
+    let upload = match lock_upload(&state, &id) {
+        Ok(upload) => upload,
+        Err(e) => return Err(e),
+    };
+    Ok(upload_response(
+        &state,
+        &id,
+        upload.offset(),
+        upload.length(),
+    ))
+}
+
+/// Append the request body to an upload. The `Upload-Offset` header must
+/// match the bytes received so far; a chunk cut off by a dropped
+/// connection keeps what arrived.
+#[utoipa::path(
+    patch,
+    path = "/uploads/{id}",
+    tag = "uploads",
+    params(
+        ("id" = String, Path, description = "Upload id"),
+        ("Upload-Offset" = u64, Header, description = "Offset the chunk starts at")
+    ),
+    request_body(content = Vec<u8>, content_type = "application/offset+octet-stream"),
+    responses(
+        (status = 200, description = "The upload after the chunk", body = UploadResponse),
+        (status = 400, description = "Missing Upload-Offset, chunk past the declared length or interrupted body", body = ErrorResponse),
+        (status = 404, description = "No such upload, or it expired", body = ErrorResponse),
+        (status = 409, description = "Upload-Offset doesn't match, or the upload is in use", body = ErrorResponse),
+        (status = 413, description = "Upload too large", body = ErrorResponse)
+    )
+)]
+async fn append_upload(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+    request: Request,
+) -> Result<Response, impl IntoResponse> {
+    let Some(offset) = request
+        .headers()
+        .get(UPLOAD_OFFSET)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+    else {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Send the offset the chunk starts at in the Upload-Offset header",
+        ));
+    };
+
+    let mut upload = match lock_upload(&state, &id) {
+        Ok(upload) => upload,
+        Err(e) => return Err(e),
+    };
+    if offset != upload.offset() {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            format!(
+                "Upload-Offset {} doesn't match the {} bytes received",
+                offset,
+                upload.offset()
+            ),
+        ));
+    }
+    if let Err(e) = upload.begin_append() {
+        return Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to write upload: {}", e),
+        ));
+    }
+
+    // The body limit only applies to extractors, so the size is checked
+    // here
+    let max_upload_bytes = state.limits.max_upload_bytes as u64;
+    let mut chunks = request.into_body().into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Chunk interrupted after {} bytes: {}", upload.offset(), e),
+                ));
+            }
+        };
+        let end = upload.offset() + chunk.len() as u64;
+        if max_upload_bytes > 0 && end > max_upload_bytes {
+            return Err(upload_too_large(max_upload_bytes as usize));
+        }
+        if upload.length().is_some_and(|length| end > length) {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "Chunk extends past the declared length of the upload",
+            ));
+        }
+        if let Err(e) = upload.append(&chunk) {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to write upload: {}", e),
+            ));
+        }
+    }
+
+    Ok(upload_response(
+        &state,
+        &id,
+        upload.offset(),
+        upload.length(),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/uploads/{id}",
+    tag = "uploads",
+    params(("id" = String, Path, description = "Upload id")),
+    responses(
+        (status = 204, description = "Upload discarded"),
+        (status = 404, description = "No such upload, or it expired", body = ErrorResponse)
+    )
+)]
+async fn delete_upload(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, impl IntoResponse> {
+    if state.uploads.remove(&id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(error_response(StatusCode::NOT_FOUND, "No such upload"))
+    }
+}
+
+/// Transcribe a finished upload, with the /transcribe options as query
+/// parameters. The upload is discarded once transcribed, and kept for
+/// another attempt if transcription fails.
+#[utoipa::path(
+    post,
+    path = "/uploads/{id}/transcribe",
+    tag = "uploads",
+    params(("id" = String, Path, description = "Upload id")),
+    responses(
+        (status = 200, description = "The transcript, as from /transcribe", body = TranscribeResponse),
+        (status = 400, description = "Invalid options or nothing uploaded", body = ErrorResponse),
+        (status = 404, description = "No such upload, or it expired", body = ErrorResponse),
+        (status = 409, description = "The upload is incomplete or in use", body = ErrorResponse),
+        (status = 415, description = "Unsupported audio format", body = ErrorResponse),
+        (status = 422, description = "Audio couldn't be decoded or is too long", body = ErrorResponse),
+        (status = 502, description = "Translation or post-processing failed", body = ErrorResponse),
+        (status = 503, description = "Too many requests in progress", body = ErrorResponse)
+    )
+)]
+async fn transcribe_upload(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+    request: Request,
+) -> Result<Response, impl IntoResponse> {
+    let Some(_in_flight) = InFlightGuard::try_new(&state.in_flight, state.max_concurrency()) else {
+        return Err(too_busy());
+    };
+
+    let mut options = TranscribeOptions::default();
+    if accepts_plain_text(request.headers()) {
+        options.response_format = TranscriptFormat::Txt;
+    }
+    options.set_query(request.uri())?;
+    check_options(&state, &options)?;
+    if options.ephemeral {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "'ephemeral' can't be used with resumable uploads, which are stored on disk",
+        ));
+    }
+
+    let upload = match lock_upload(&state, &id) {
+        Ok(upload) => upload,
+        Err(e) => return Err(e),
+    };
+    if upload.offset() == 0 {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Nothing has been uploaded yet",
+        ));
+    }
+    if !upload.is_complete() {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            format!(
+                "Upload is incomplete: {} of {} bytes received",
+                upload.offset(),
+                upload.length().unwrap_or_default()
+            ),
+        ));
+    }
+
+    let file = upload.file();
+    let mut header = Vec::with_capacity(FORMAT_SNIFF_LEN);
+    if let Ok(reader) = file.reopen() {
+        let _ = reader
+            .take(FORMAT_SNIFF_LEN as u64)
+            .read_to_end(&mut header);
+    }
+    let format = UploadFormat {
+        mime_type: upload.content_type.clone(),
+        file_extension: upload.filename.as_deref().and_then(file_extension),
+        detected: detect_format(&header),
+    };
+    let audio = AudioUpload::Spooled {
+        file,
+        len: upload.offset(),
+    };
+
+    // Keep the upload locked so no chunk is appended while it's decoded
+    let result = run_transcription(&state, audio, format, options).await;
+    drop(upload);
+    if result.is_ok() {
+        state.uploads.remove(&id);
+    }
+    result
+}
+
+const UPLOAD_OFFSET: &str = "upload-offset";
+
+fn lock_upload(
+    state: &ApiState,
+    id: &str,
+) -> Result<OwnedMutexGuard<Upload>, (StatusCode, Json<ErrorResponse>)> {
+    match state.uploads.lock(id) {
+        Ok(Some(upload)) => Ok(upload),
+        Ok(None) => Err(error_response(StatusCode::NOT_FOUND, "No such upload")),
+        Err(e) => Err(error_response(StatusCode::CONFLICT, e.to_string())),
+    }
+}
+
+/// The upload's state as JSON, with the offset also in `Upload-Offset`.
+fn upload_response(state: &ApiState, id: &str, offset: u64, length: Option<u64>) -> Response {
+    let mut response = Json(UploadResponse {
+        id: id.to_string(),
+        offset,
+        length,
+        expires_in_secs: state.uploads.ttl().as_secs(),
+    })
+    .into_response();
+    response
+        .headers_mut()
+        .insert(UPLOAD_OFFSET, HeaderValue::from(offset));
+    response
+}
+
+/// Start recording a dictation session, for push-to-talk from a Stream Deck
+/// button or a script. Stop it with `POST /sessions/{id}/stop` to get the
+/// text. Needs the desktop app; there is one microphone, so one session at
+/// a time.
+#[utoipa::path(
+    post,
+    path = "/sessions",
+    tag = "dictation",
+    request_body(content = Option<StartSessionRequest>, content_type = "application/json"),
+    responses(
+        (status = 201, description = "Recording; the session URL is in the Location header", body = SessionResponse),
+        (status = 400, description = "Invalid request or unknown input device", body = ErrorResponse),
+        (status = 409, description = "Already recording", body = ErrorResponse),
+        (status = 503, description = "Running headless, without a microphone", body = ErrorResponse)
+    )
+)]
+async fn start_session(
+    State(state): State<Arc<ApiState>>,
+    body: Bytes,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let request: StartSessionRequest = if body.iter().all(u8::is_ascii_whitespace) {
+        StartSessionRequest::default()
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid session request: {}", e),
+                ));
+            }
+        }
+    };
+
+    let context = request
+        .context
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    if context.is_some_and(|name| name.chars().count() > MAX_CONTEXT_NAME_CHARS) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "'context' is too long: at most {} characters are allowed",
+                MAX_CONTEXT_NAME_CHARS
+            ),
+        ));
+    }
+
+    let id = state
+        .dictation
+        .start(request.device.as_deref(), request.paste, context)
+        .map_err(dictation_error)?;
+
+    let mut response = Json(SessionResponse {
+        id: id.clone(),
+        expires_in_secs: MAX_SESSION_DURATION.as_secs(),
+    })
+    .into_response();
+    *response.status_mut() = StatusCode::CREATED;
+    if let Ok(location) = HeaderValue::from_str(&format!("/sessions/{}", id)) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    Ok(response)
+}
+
+/// Stop recording and transcribe the session. The dictation is saved in the
+/// history, and pasted when the session was started with `paste`.
+#[utoipa::path(
+    post,
+    path = "/sessions/{id}/stop",
+    tag = "dictation",
+    params(("id" = String, Path, description = "Session id")),
+    responses(
+        (status = 200, description = "The dictation", body = SessionResultResponse),
+        (status = 404, description = "No such session; it was stopped, cancelled or expired", body = ErrorResponse),
+        (status = 500, description = "Transcription failed", body = ErrorResponse),
+        (status = 503, description = "Running headless, without a microphone", body = ErrorResponse)
+    )
+)]
+async fn stop_session(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+) -> Result<Json<SessionResultResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let dictation = state.dictation.stop(&id).await.map_err(dictation_error)?;
+    Ok(Json(SessionResultResponse {
+        text: dictation.text,
+        segments: dictation
+            .segments
+            .into_iter()
+            .map(|seg| SegmentResponse {
+                start: seg.start,
+                end: seg.end,
+                text: seg.text,
+                original: None,
+                channel: None,
+                speaker: None,
+                words: None,
+            })
+            .collect(),
+        language: dictation.language,
+        duration_seconds: dictation.duration_ms as f32 / 1000.0,
+        pasted: dictation.pasted,
+    }))
+}
+
+/// Stop recording and discard the session's audio.
+#[utoipa::path(
+    delete,
+    path = "/sessions/{id}",
+    tag = "dictation",
+    params(("id" = String, Path, description = "Session id")),
+    responses(
+        (status = 204, description = "Session cancelled"),
+        (status = 404, description = "No such session; it was stopped, cancelled or expired", body = ErrorResponse),
+        (status = 503, description = "Running headless, without a microphone", body = ErrorResponse)
+    )
+)]
+async fn cancel_session(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    state.dictation.cancel(&id).map_err(dictation_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Forget the text dictated so far in a session `context`, to start over
+/// under the same name. Contexts are also forgotten after two hours unused.
+#[utoipa::path(
+    delete,
+    path = "/contexts/{name}",
+    tag = "dictation",
+    params(("name" = String, Path, description = "Context name")),
+    responses(
+        (status = 204, description = "Context cleared"),
+        (status = 404, description = "No such context", body = ErrorResponse)
+    )
+)]
+async fn clear_context(
+    State(state): State<Arc<ApiState>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    if state.dictation.clear_context(name.trim()) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(error_response(
+            StatusCode::NOT_FOUND,
+            "No such context; nothing was dictated in it or it expired",
+        ))
+    }
+}
+
+fn dictation_error(e: DictationError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e {
+        DictationError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        DictationError::Busy => StatusCode::CONFLICT,
+        DictationError::UnknownDevice { .. } => StatusCode::BAD_REQUEST,
+        DictationError::NotFound => StatusCode::NOT_FOUND,
+        DictationError::Failed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error_response(status, e.to_string())
+}
+
+/// Start transcribing a meeting: the microphone and the system audio are
+/// recorded until `POST /meeting/stop`, and transcribed every 30 seconds
+/// into a transcript that is saved in the history as it grows. Needs the
+/// desktop app; one meeting at a time.
+#[utoipa::path(
+    post,
+    path = "/meeting",
+    tag = "meeting",
+    request_body(content = Option<StartMeetingRequest>, content_type = "application/json"),
+    responses(
+        (status = 201, description = "Recording", body = MeetingResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 409, description = "A meeting is already running", body = ErrorResponse),
+        (status = 500, description = "The microphone or system audio couldn't be opened", body = ErrorResponse),
+        (status = 503, description = "Running headless, without a microphone", body = ErrorResponse)
+    )
+)]
+async fn start_meeting(
+    State(state): State<Arc<ApiState>>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<MeetingResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let request: StartMeetingRequest = if body.iter().all(u8::is_ascii_whitespace) {
+        StartMeetingRequest::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| {
+            error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid meeting request: {}", e),
+            )
+        })?
+    };
+
+    let transcript = state
+        .meeting
+        .start(MeetingOptions {
+            speaker_labels: request.speaker_labels,
+            system_audio: request.system_audio,
+        })
+        .map_err(meeting_error)?;
+    if let Host::App(app) = &state.host {
+        crate::tray::refresh_tray_menu(app);
+    }
+    Ok((StatusCode::CREATED, Json(transcript.into())))
+}
+
+/// The transcript of the running meeting so far.
+#[utoipa::path(
+    get,
+    path = "/meeting",
+    tag = "meeting",
+    responses(
+        (status = 200, description = "The transcript so far", body = MeetingResponse),
+        (status = 404, description = "No meeting is running", body = ErrorResponse)
+    )
+)]
+async fn meeting(
+    State(state): State<Arc<ApiState>>,
+) -> Result<Json<MeetingResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let transcript = state.meeting.transcript().map_err(meeting_error)?;
+    Ok(Json(transcript.into()))
+}
+
+/// Stop the meeting and return its transcript, once the last chunk is
+/// transcribed.
+#[utoipa::path(
+    post,
+    path = "/meeting/stop",
+    tag = "meeting",
+    responses(
+        (status = 200, description = "The whole transcript", body = MeetingResponse),
+        (status = 404, description = "No meeting is running", body = ErrorResponse)
+    )
+)]
+async fn stop_meeting(
+    State(state): State<Arc<ApiState>>,
+) -> Result<Json<MeetingResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let meeting = state.meeting.clone();
+    let transcript = tokio::task::spawn_blocking(move || meeting.stop())
+        .await
+        .map_err(|e| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Meeting task failed: {}", e),
+            )
+        })?
+        .map_err(meeting_error)?;
+    if let Host::App(app) = &state.host {
+        crate::tray::refresh_tray_menu(app);
+    }
+    Ok(Json(transcript.into()))
+}
+
+fn meeting_error(e: MeetingError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e {
+        MeetingError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        MeetingError::AlreadyRunning => StatusCode::CONFLICT,
+        MeetingError::NotRunning => StatusCode::NOT_FOUND,
+        MeetingError::Failed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error_response(status, e.to_string())
+}
+
+/// Push dictation events to a companion tool over a WebSocket: recording
+/// started, stopped and cancelled, and the text of each completed
+/// transcription, from the shortcuts and from sessions. Each event is a
+/// JSON text message with a `type` and a `timestamp` in Unix milliseconds.
+#[utoipa::path(
+    get,
+    path = "/ws/events",
+    tag = "dictation",
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol"),
+        (status = 400, description = "Not a WebSocket upgrade request")
+    )
+)]
+async fn events_ws(State(state): State<Arc<ApiState>>, ws: WebSocketUpgrade) -> Response {
+    let events = state.events.subscribe();
+    ws.on_upgrade(move |socket| push_events(socket, events))
+}
+
+async fn push_events(mut socket: WebSocket, mut events: broadcast::Receiver<AppEvent>) {
+    debug!("WebSocket client subscribed to events");
+    loop {
+        let next = {
+            let event = std::pin::pin!(events.recv());
+            let message = std::pin::pin!(socket.recv());
+            match select(event, message).await {
+                Either::Left((event, _)) => Either::Left(event),
+                Either::Right((message, _)) => Either::Right(message),
+            }
+        };
+        let event = match next {
+            Either::Left(Ok(event)) => event,
+            Either::Left(Err(RecvError::Lagged(missed))) => {
+                warn!("WebSocket client missed {} events", missed);
+                continue;
+            }
+            Either::Left(Err(RecvError::Closed)) => break,
+            // Clients only listen; anything but a close is ignored
+            Either::Right(Some(Ok(Message::Close(_))) | Some(Err(_)) | None) => break,
+            Either::Right(Some(Ok(_))) => continue,
+        };
+        let json = match serde_json::to_string(&event) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Failed to serialize event: {}", e);
+                continue;
+            }
+        };
+        if socket.send(Message::Text(json)).await.is_err() {
+            break;
+        }
+    }
+    debug!("WebSocket client unsubscribed from events");
+}
+
+/// Identify the spoken language from the first 30 seconds of the upload.
+///
+/// Accepts the same `file`/`audio` and `ephemeral` fields as /transcribe and
+/// an optional `top_n` field (default 5) limiting the number of candidates
+/// returned.
+#[utoipa::path(
+    post,
+    path = "/detect-language",
+    tag = "transcription",
+    request_body(content = DetectLanguageForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Candidate languages", body = DetectLanguageResponse),
+        (status = 400, description = "Invalid options or no audio", body = ErrorResponse),
+        (status = 415, description = "Unsupported audio format", body = ErrorResponse),
+        (status = 501, description = "The loaded model can't identify languages", body = ErrorResponse),
+        (status = 503, description = "Too many requests in progress", body = ErrorResponse)
+    )
+)]
+async fn detect_language(
+    State(state): State<Arc<ApiState>>,
+    uri: Uri,
+    mut multipart: Multipart,
+) -> Result<Response, impl IntoResponse> {
+    let Some(_in_flight) = InFlightGuard::try_new(&state.in_flight, state.max_concurrency()) else {
+        return Err(too_busy());
+    };
+
+    let mut upload: Option<(AudioUpload, UploadFormat)> = None;
+    let mut top_n = DEFAULT_TOP_LANGUAGES;
+    // Ephemeral uploads stay in memory, as for /transcribe
+    let mut ephemeral = false;
+    let query = match Query::<Vec<(String, String)>>::try_from_uri(&uri) {
+        Ok(Query(query)) => query,
+        Err(e) => return Err(error_response(StatusCode::BAD_REQUEST, e.body_text())),
+    };
+    if let Some((name, value)) = query.iter().rev().find(|(name, _)| name == "ephemeral") {
+        ephemeral = parse_bool_field(name, value)
+            .map_err(|e| error_response(StatusCode::BAD_REQUEST, e))?;
+    }
+
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let name = field.name().unwrap_or("").to_string();
+        if name == "file" || name == "audio" {
+            match read_upload(field, state.limits.max_upload_bytes, !ephemeral).await {
+                Ok(u) => upload = Some(u),
+                Err(e) => return Err(e),
+            }
+        } else if name == "ephemeral" {
+            let value = field.text().await.unwrap_or_default();
+            ephemeral = parse_bool_field(&name, &value)
+                .map_err(|e| error_response(StatusCode::BAD_REQUEST, e))?;
+        } else if name == "top_n" {
+            let value = field.text().await.unwrap_or_default();
+            top_n = match value.trim().parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    return Err(error_response(
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "Invalid value '{}' for 'top_n': expected a positive integer",
+                            value.trim()
+                        ),
+                    ));
+                }
+            };
+        } else {
+            debug!("Ignoring unknown multipart field '{}'", name);
+        }
+    }
+
+    let (upload, upload_format) = match upload {
+        Some(upload) if !upload.0.is_empty() => upload,
+        Some(_) => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "Audio file is empty",
+            ));
+        }
+        None => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "No audio file provided. Send a multipart field named 'file' or 'audio'.",
+            ));
+        }
+    };
+
+    // Decoding stops shortly after the detection window
+    let mut channels = match decode_upload(&upload, &upload_format, LANGUAGE_DETECTION_SECS, false)
+    {
+        Ok(channels) => channels,
+        Err(e) => return Err(e),
+    };
+    let mut samples = channels.swap_remove(0);
+    samples.truncate(LANGUAGE_DETECTION_SECS as usize * WHISPER_SAMPLE_RATE as usize);
+
+    let tm = state.transcription_manager.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        tm.initiate_model_load();
+        tm.detect_language(&samples)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(mut languages))) => {
+            languages.truncate(top_n);
+            if let Some(top) = languages.first() {
+                info!(
+                    "API detected language: {} ({:.2})",
+                    top.language, top.probability
+                );
+            }
+            let reply = Json(DetectLanguageResponse { languages }).into_response();
+            Ok(confirm_ephemeral(reply, ephemeral))
+        }
+        Ok(Ok(None)) => Err(error_response(
+            StatusCode::NOT_IMPLEMENTED,
+            "The loaded model does not support language detection. Select a multilingual Whisper model.",
+        )),
+        Ok(Err(e)) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Language detection task panicked: {}", e),
+        )),
+    }
+}
+
+/// Render a low-resolution MP4 of a video with its subtitles burned in, to
+/// check the caption timing by eye before exporting the SRT. Needs ffmpeg
+/// built with libass and libx264.
+#[utoipa::path(
+    post,
+    path = "/captions/preview",
+    tag = "transcription",
+    request_body(content = CaptionPreviewForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "The preview", body = String, content_type = "video/mp4"),
+        (status = 400, description = "Invalid options, or no video or subtitles", body = ErrorResponse),
+        (status = 413, description = "Upload too large", body = ErrorResponse),
+        (status = 422, description = "ffmpeg couldn't render the video, or took too long", body = ErrorResponse),
+        (status = 503, description = "ffmpeg is not installed, or too many requests in progress", body = ErrorResponse)
+    )
+)]
+async fn caption_preview(
+    State(state): State<Arc<ApiState>>,
+    mut multipart: Multipart,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let Some(_in_flight) = InFlightGuard::try_new(&state.in_flight, state.max_concurrency()) else {
+        return Err(too_busy());
+    };
+
+    let mut video: Option<AudioUpload> = None;
+    let mut subtitles: Option<String> = None;
+    let mut options = PreviewOptions::default();
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let name = field.name().unwrap_or("").to_string();
+        if name == "file" || name == "video" {
+            let (upload, _) = read_upload(field, state.limits.max_upload_bytes, true).await?;
+            video = Some(upload);
+            continue;
+        }
+        let value = field.text().await.map_err(|e| {
+            error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Failed to read field '{}': {}", name, e),
+            )
+        })?;
+        let parsed = match name.as_str() {
+            "subtitles" => {
+                subtitles = Some(value);
+                Ok(())
+            }
+            "height" => parse_number_field(&name, &value, 144..=1080)
+                .map(|height| options.height = height.unwrap_or(caption_preview::DEFAULT_HEIGHT)),
+            "offset_seconds" => parse_number_field(&name, &value, 0.0..=f64::MAX)
+                .map(|start| options.start = start.unwrap_or(0.0)),
+            "duration_seconds" => parse_number_field(&name, &value, 0.1..=f64::MAX)
+                .map(|duration| options.duration = duration),
+            _ => {
+                debug!("Ignoring unknown multipart field '{}'", name);
+                Ok(())
+            }
+        };
+        parsed.map_err(|e| error_response(StatusCode::BAD_REQUEST, e))?;
+    }
+
+    let video = match video {
+        Some(video) if !video.is_empty() => video,
+        Some(_) => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "Video file is empty",
+            ))
+        }
+        None => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "No video provided. Send a multipart field named 'file' or 'video'.",
+            ));
+        }
+    };
+    let subtitles = match subtitles {
+        Some(subtitles) if !subtitles.trim().is_empty() => subtitles,
+        _ => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "No subtitles provided. Send the SRT or WebVTT transcript as a multipart field named 'subtitles'.",
+            ));
+        }
+    };
+
+    let result = tokio::task::spawn_blocking(move || {
+        // ffmpeg reads the video from disk so it can seek, e.g. to an MP4's
+        // trailing moov atom
+        let file = match &video {
+            AudioUpload::Spooled { file, .. } => file.clone(),
+            AudioUpload::Memory(bytes) => {
+                let mut file = NamedTempFile::new()
+                    .map_err(|e| format!("Failed to create temp file for upload: {}", e))?;
+                file.write_all(bytes)
+                    .map_err(|e| format!("Failed to write upload to temp file: {}", e))?;
+                Arc::new(file)
+            }
+        };
+        caption_preview::render(file.path(), &subtitles, &options)
+    })
+    .await;
+
+    let preview = match result {
+        Ok(Ok(preview)) => preview,
+        Ok(Err(e)) => {
+            let (status, code) = match &e {
+                FfmpegError::NotFound { .. } => {
+                    (StatusCode::SERVICE_UNAVAILABLE, Some("ffmpeg_not_found"))
+                }
+                FfmpegError::DecodeTimeout(_) => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, Some("decode_timeout"))
+                }
+                FfmpegError::Failed(_) => (StatusCode::UNPROCESSABLE_ENTITY, None),
+            };
+            let mut response =
+                error_response(status, format!("Failed to render the preview: {}", e));
+            response.1.code = code;
+            return Err(response);
+        }
+        Err(e) => {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Caption preview task panicked: {}", e),
+            ));
+        }
+    };
+
+    let mut response = preview.into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("video/mp4"));
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_static("inline; filename=\"caption-preview.mp4\""),
+    );
+    Ok(response)
+}
+
+/// Benchmark models on the reference clip. Slow: each model is loaded and
+/// run twice, and other requests are served by whichever model is loaded in
+/// the meantime.
+#[utoipa::path(
+    get,
+    path = "/benchmark",
+    tag = "benchmark",
+    params(BenchmarkQuery),
+    responses(
+        (status = 200, description = "One result per model", body = BenchmarkResponse),
+        (status = 400, description = "Unknown or missing model", body = ErrorResponse),
+        (status = 409, description = "A benchmark is already running", body = ErrorResponse)
+    )
+)]
+async fn benchmark(
+    State(state): State<Arc<ApiState>>,
+    Query(query): Query<BenchmarkQuery>,
+) -> Result<Json<BenchmarkResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let model_ids: Vec<String> = query
+        .models
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect();
+
+    for model_id in &model_ids {
+        match state.model_manager.get_model_info(model_id) {
+            Some(info) if info.is_downloaded => {}
+            Some(_) => {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Model '{}' is not downloaded", model_id),
+                ))
+            }
+            None => {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown model '{}'", model_id),
+                ))
+            }
+        }
+    }
+
+    if state.benchmarker.is_running() {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            "A benchmark is already running",
+        ));
+    }
+
+    let benchmarker = state.benchmarker.clone();
+    let result = tokio::task::spawn_blocking(move || benchmarker.run(&model_ids)).await;
+
+    match result {
+        Ok(Ok(results)) => Ok(Json(BenchmarkResponse { results })),
+        Ok(Err(e)) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Benchmark task panicked: {}", e),
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/benchmark/history",
+    tag = "benchmark",
+    responses((status = 200, description = "Past results", body = BenchmarkHistoryResponse))
+)]
+async fn benchmark_history(State(state): State<Arc<ApiState>>) -> Json<BenchmarkHistoryResponse> {
+    Json(BenchmarkHistoryResponse {
+        results: state.benchmarker.history(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/models",
+    tag = "models",
+    responses((status = 200, description = "Available models", body = ModelsResponse))
+)]
+async fn models(State(state): State<Arc<ApiState>>) -> Json<ModelsResponse> {
+    let mut models = state.model_manager.get_available_models();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    Json(ModelsResponse { models })
+}
+
+/// Built-in models with memory needs, expected speed and license, and a
+/// `warning` for those that likely won't fit this machine, with the
+/// detected hardware and the model recommended for it.
+#[utoipa::path(
+    get,
+    path = "/models/catalog",
+    tag = "models",
+    responses((status = 200, description = "Catalog for this machine", body = ModelCatalog))
+)]
+async fn model_catalog(State(state): State<Arc<ApiState>>) -> Json<ModelCatalog> {
+    Json(state.model_manager.catalog())
+}
+
+#[utoipa::path(
+    get,
+    path = "/models/{id}",
+    tag = "models",
+    params(("id" = String, Path, description = "Model id")),
+    responses(
+        (status = 200, description = "The model", body = ModelInfo),
+        (status = 404, description = "Unknown model", body = ErrorResponse)
+    )
+)]
+async fn model(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ModelInfo>, (StatusCode, Json<ErrorResponse>)> {
+    find_model(&state, &id).map(Json)
+}
+
+/// Start downloading a model in the background and return its info with
+/// 202. Poll `GET /models/:id` for progress (`partial_size`) and completion
+/// (`is_downloaded`); the download resumes where an earlier one stopped.
+#[utoipa::path(
+    post,
+    path = "/models/{id}/download",
+    tag = "models",
+    params(("id" = String, Path, description = "Model id")),
+    responses(
+        (status = 200, description = "Already downloaded", body = ModelInfo),
+        (status = 202, description = "Download started", body = ModelInfo),
+        (status = 400, description = "The model has no download URL", body = ErrorResponse),
+        (status = 404, description = "Unknown model", body = ErrorResponse),
+        (status = 409, description = "Already downloading", body = ErrorResponse)
+    )
+)]
+async fn download_model(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<ModelInfo>), (StatusCode, Json<ErrorResponse>)> {
+    let info = find_model(&state, &id)?;
+    if info.is_downloaded {
+        return Ok((StatusCode::OK, Json(info)));
+    }
+    if info.is_downloading {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            format!("Model '{}' is already downloading", id),
+        ));
+    }
+    if info.url.is_none() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Model '{}' has no download URL", id),
+        ));
+    }
+
+    let model_manager = state.model_manager.clone();
+    let model_id = id.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = model_manager.download_model(&model_id).await {
+            error!("Failed to download model {}: {}", model_id, e);
+        }
+    });
+
+    // Report the download as started, even if the task hasn't run yet
+    let info = find_model(&state, &id)?;
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ModelInfo {
+            is_downloading: true,
+            ..info
+        }),
+    ))
+}
+
+/// Stop a running download. The partial file is kept for resuming.
+#[utoipa::path(
+    delete,
+    path = "/models/{id}/download",
+    tag = "models",
+    params(("id" = String, Path, description = "Model id")),
+    responses(
+        (status = 204, description = "Download stopped"),
+        (status = 404, description = "Unknown model", body = ErrorResponse),
+        (status = 409, description = "Not downloading", body = ErrorResponse)
+    )
+)]
+async fn cancel_model_download(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let info = find_model(&state, &id)?;
+    if !info.is_downloading {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            format!("Model '{}' is not downloading", id),
+        ));
+    }
+    state
+        .model_manager
+        .cancel_download(&id)
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Unload the transcription model to free RAM and VRAM. The next request
+/// loads it again.
+#[utoipa::path(
+    post,
+    path = "/models/unload",
+    tag = "models",
+    responses(
+        (status = 200, description = "Unloaded", body = UnloadResponse),
+        (status = 409, description = "The model is in use", body = ErrorResponse)
+    )
+)]
+async fn unload_model(
+    State(state): State<Arc<ApiState>>,
+) -> Result<Json<UnloadResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let tm = state.transcription_manager.clone();
+    if state.in_flight.load(Ordering::SeqCst) > 0 || tm.is_loading() {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            "The model is in use, try again when requests have finished",
+        ));
+    }
+
+    let unloaded = tm.get_current_model();
+    tokio::task::spawn_blocking(move || tm.unload_model())
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(UnloadResponse { unloaded }))
+}
+
+fn find_model(state: &ApiState, id: &str) -> Result<ModelInfo, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .model_manager
+        .get_model_info(id)
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, format!("Unknown model '{}'", id)))
+}
+
+/// Search stored transcriptions, from the app and from this API, newest first.
+#[utoipa::path(
+    get,
+    path = "/history",
+    tag = "history",
+    params(HistoryParams),
+    responses(
+        (status = 200, description = "Matching entries", body = HistoryResponse),
+        (status = 400, description = "Invalid filter", body = ErrorResponse)
+    )
+)]
+async fn history(
+    State(state): State<Arc<ApiState>>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<HistoryResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let source = match params.source.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(value) => Some(HistorySource::parse(value).ok_or_else(|| {
+            error_response(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Invalid value '{}' for 'source': expected 'app', 'api' or 'meeting'",
+                    value
+                ),
+            )
+        })?),
+    };
+    let from = params
+        .from
+        .as_deref()
+        .map(|value| parse_time_bound("from", value, false))
+        .transpose()
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e))?;
+    let to = params
+        .to
+        .as_deref()
+        .map(|value| parse_time_bound("to", value, true))
+        .transpose()
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e))?;
+
+    let query = HistoryQuery {
+        text: params.q,
+        source,
+        from,
+        to,
+        audio_hash: params.audio_hash,
+        limit: Some(params.limit.unwrap_or(DEFAULT_HISTORY_LIMIT)),
+        offset: params.offset.unwrap_or(0),
+    };
+    let hm = state.history_manager.clone();
+    let result = tokio::task::spawn_blocking(move || hm.search_entries(&query)).await;
+
+    match result {
+        Ok(Ok(entries)) => Ok(Json(HistoryResponse { entries })),
+        Ok(Err(e)) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("History search failed: {}", e),
+        )),
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("History search task panicked: {}", e),
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/history/{id}",
+    tag = "history",
+    params(("id" = i64, Path, description = "History entry id")),
+    responses(
+        (status = 200, description = "The entry", body = HistoryEntry),
+        (status = 404, description = "No such entry", body = ErrorResponse)
+    )
+)]
+async fn history_entry(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<HistoryEntry>, (StatusCode, Json<ErrorResponse>)> {
+    find_history_entry(&state, id).await.map(Json)
+}
+
+/// Download a stored transcript as a file in the requested format. Exports
+/// use the original transcription text, which is what the segments hold.
+/// JSON exports add talk-time `analytics` when the entry has segments.
+#[utoipa::path(
+    get,
+    path = "/history/{id}/export",
+    tag = "history",
+    params(("id" = i64, Path, description = "History entry id"), ExportParams),
+    responses(
+        (status = 200, description = "The transcript as a file attachment", body = String, content_type = "text/plain"),
+        (status = 400, description = "Invalid format", body = ErrorResponse),
+        (status = 404, description = "No such entry", body = ErrorResponse)
+    )
+)]
+async fn export_history_entry(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<i64>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let format = match params.format.as_deref().map(str::trim) {
+        None | Some("") => TranscriptFormat::Txt,
+        Some(value) => parse_transcript_format("format", value)
+            .map_err(|e| error_response(StatusCode::BAD_REQUEST, e))?,
+    };
+    let mut subtitles = SubtitleLimits::default();
+    for (name, value) in [
+        ("max_line_chars", &params.max_line_chars),
+        ("max_lines", &params.max_lines),
+        ("max_chars_per_second", &params.max_chars_per_second),
+    ] {
+        if let Some(value) = value {
+            subtitles
+                .set(name, value)
+                .map_err(|e| error_response(StatusCode::BAD_REQUEST, e))?;
+        }
+    }
+    let entry = find_history_entry(&state, id).await?;
+    let duration = entry.duration_ms.unwrap_or(0) as f32 / 1000.0;
+    let file_name = format!("handy-{}.{}", entry.timestamp, format.extension());
+    let analytics = {
+        let segments: Vec<TalkSegment> = entry
+            .segments
+            .iter()
+            .map(|seg| TalkSegment {
+                start: seg.start,
+                end: seg.end,
+                speaker: seg.speaker.as_deref(),
+                text: &seg.text,
+            })
+            .collect();
+        analytics::analyze(&segments, duration)
+    };
+
+    let mut transcript = Transcript {
+        title: Some(entry.title),
+        text: entry.transcription_text,
+        segments: entry
+            .segments
+            .into_iter()
+            .map(|seg| Cue {
+                start: seg.start,
+                end: seg.end,
+                text: match seg.speaker {
+                    Some(speaker) => format!("[{}] {}", speaker, seg.text),
+                    None => seg.text,
+                },
+            })
+            .collect(),
+        duration,
+    };
+    if format == TranscriptFormat::Json {
+        let body = serde_json::to_string_pretty(&ExportedTranscript {
+            transcript: &transcript,
+            analytics,
+        })
+        .unwrap_or_default();
+        return Ok(document_response(format, body, Some(&file_name)));
+    }
+    shape_subtitles(&mut transcript, format, subtitles.layout());
+    Ok(formatted_response(format, &transcript, Some(&file_name)))
+}
+
+/// A JSON history export: the transcript with its analytics.
+#[derive(Serialize)]
+struct ExportedTranscript<'a> {
+    #[serde(flatten)]
+    transcript: &'a Transcript,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analytics: Option<TalkAnalytics>,
+}
+
+async fn find_history_entry(
+    state: &ApiState,
+    id: i64,
+) -> Result<HistoryEntry, (StatusCode, Json<ErrorResponse>)> {
+    match state.history_manager.get_entry_by_id(id).await {
+        Ok(Some(entry)) => Ok(entry),
+        Ok(None) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            format!("No history entry with id {}", id),
+        )),
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read history entry: {}", e),
+        )),
+    }
+}
+
+/// Parse a /history time bound given as Unix seconds or a `YYYY-MM-DD`
+/// local date. Dates used as an upper bound include the whole day.
+/// Text replacement rules applied to every transcription.
+#[utoipa::path(
+    get,
+    path = "/replacements",
+    tag = "settings",
+    responses((status = 200, description = "The rules, in order", body = [TextReplacement]))
+)]
+async fn replacements(State(state): State<Arc<ApiState>>) -> Json<Vec<TextReplacement>> {
+    Json(state.host.settings().text_replacements)
+}
+
+/// Replace all text replacement rules. Rules are applied in order.
+#[utoipa::path(
+    put,
+    path = "/replacements",
+    tag = "settings",
+    request_body = [TextReplacement],
+    responses(
+        (status = 200, description = "The new rules", body = [TextReplacement]),
+        (status = 400, description = "Invalid rule", body = ErrorResponse)
+    )
+)]
+async fn set_replacements(
+    State(state): State<Arc<ApiState>>,
+    Json(rules): Json<Vec<TextReplacement>>,
+) -> Result<Json<Vec<TextReplacement>>, impl IntoResponse> {
+    for rule in &rules {
+        if let Err(e) = rule.compile() {
+            return Err(error_response(StatusCode::BAD_REQUEST, e));
+        }
+    }
+
+    let mut settings = state.host.settings();
+    settings.text_replacements = rules.clone();
+    state.host.write_settings(settings);
+    info!("Updated {} text replacement rules via API", rules.len());
+    Ok(Json(rules))
+}
+
+/// The engine configuration: selected model, default language, concurrency
+/// and post-processing. API keys for post-processing aren't included.
+#[utoipa::path(
+    get,
+    path = "/config",
+    tag = "settings",
+    responses((status = 200, description = "The configuration", body = ConfigResponse))
+)]
+async fn config(State(state): State<Arc<ApiState>>) -> Json<ConfigResponse> {
+    Json(current_config(&state))
+}
+
+fn current_config(state: &ApiState) -> ConfigResponse {
+    let settings = state.host.settings();
+    let engine = state
+        .model_manager
+        .get_model_info(&settings.selected_model)
+        .map(|info| format!("{:?}", info.engine_type));
+    ConfigResponse {
+        engine,
+        language: settings.selected_language.clone(),
+        max_concurrency: state.max_concurrency(),
+        post_processing: PostProcessingConfig {
+            enabled: settings.post_process_enabled,
+            model: settings
+                .post_process_models
+                .get(&settings.post_process_provider_id)
+                .filter(|model| !model.is_empty())
+                .cloned(),
+            provider: settings.post_process_provider_id,
+            prompt_id: settings.post_process_selected_prompt_id,
+            overridden_by_config: state
+                .llm
+                .url
+                .as_deref()
+                .is_some_and(|url| !url.trim().is_empty()),
+        },
+        model: settings.selected_model,
+    }
+}
+
+/// Change the engine configuration at runtime. Changes are saved to the
+/// settings, so they survive a restart; `[api] max_concurrency` in the
+/// config file still takes precedence over the saved concurrency then.
+#[utoipa::path(
+    put,
+    path = "/config",
+    tag = "settings",
+    request_body = UpdateConfigRequest,
+    responses(
+        (status = 200, description = "The new configuration", body = ConfigResponse),
+        (status = 400, description = "Invalid value, or the model isn't downloaded", body = ErrorResponse),
+        (status = 404, description = "Unknown model or engine", body = ErrorResponse),
+        (status = 409, description = "Requests are in progress, so the model can't be switched", body = ErrorResponse)
+    )
+)]
+async fn update_config(
+    State(state): State<Arc<ApiState>>,
+    Json(request): Json<UpdateConfigRequest>,
+) -> Result<Json<ConfigResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut settings = state.host.settings();
+
+    if let Some(language) = &request.language {
+        if language != "auto" && !is_valid_language_code(language) {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Invalid value '{}' for 'language': expected a language code such as 'ja' or 'auto'",
+                    language
+                ),
+            ));
+        }
+    }
+
+    if let Some(update) = &request.post_processing {
+        if let Some(provider) = &update.provider {
+            if settings.post_process_provider(provider).is_none() {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown post-processing provider '{}'", provider),
+                ));
+            }
+        }
+        if let Some(prompt_id) = &update.prompt_id {
+            if !settings
+                .post_process_prompts
+                .iter()
+                .any(|prompt| &prompt.id == prompt_id)
+            {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown post-processing prompt '{}'", prompt_id),
+                ));
+            }
+        }
+    }
+
+    let model_id = match (&request.model, &request.engine) {
+        (Some(model), _) => Some(model.clone()),
+        (None, Some(engine)) => {
+            let models = downloaded_models_of_engine(&state.model_manager, engine);
+            if models.is_empty() {
+                return Err(error_response(
+                    StatusCode::NOT_FOUND,
+                    format!("No downloaded model of engine '{}'", engine),
+                ));
+            }
+            // Keep the selected model when it already is of that engine
+            Some(if models.contains(&settings.selected_model) {
+                settings.selected_model.clone()
+            } else {
+                models[0].clone()
+            })
+        }
+        (None, None) => None,
+    };
+
+    if let Some(model_id) = model_id {
+        let info = find_model(&state, &model_id)?;
+        if !info.is_downloaded {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Model '{}' isn't downloaded", model_id),
+            ));
+        }
+        let tm = state.transcription_manager.clone();
+        if tm.get_current_model().as_deref() != Some(model_id.as_str()) {
+            if state.in_flight.load(Ordering::SeqCst) > 0 || tm.is_loading() {
+                return Err(error_response(
+                    StatusCode::CONFLICT,
+                    "The model is in use, try again when requests have finished",
+                ));
+            }
+            let id = model_id.clone();
+            tokio::task::spawn_blocking(move || tm.load_model(&id))
+                .await
+                .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        }
+        // Choosing another model than the recommended one overrides the
+        // auto mode, as it does in the app
+        if settings.auto_select_model && !info.is_recommended {
+            info!("Model {} chosen via API, leaving auto mode", model_id);
+            settings.auto_select_model = false;
+        }
+        settings.selected_model = model_id;
+    }
+
+    if let Some(language) = request.language {
+        settings.selected_language = language;
+    }
+
+    if let Some(max_concurrency) = request.max_concurrency {
+        state
+            .max_concurrency
+            .store(max_concurrency, Ordering::SeqCst);
+        settings.api_max_concurrency = Some(max_concurrency);
+    }
+
+    let rebuild_post_processor = request.post_processing.is_some();
+    if let Some(update) = request.post_processing {
+        if let Some(enabled) = update.enabled {
+            settings.post_process_enabled = enabled;
+        }
+        if let Some(provider) = update.provider {
+            settings.post_process_provider_id = provider;
+        }
+        let provider = settings.post_process_provider_id.clone();
+        if let Some(model) = update.model {
+            settings.post_process_models.insert(provider.clone(), model);
+        }
+        if let Some(api_key) = update.api_key {
+            settings.post_process_api_keys.insert(provider, api_key);
+        }
+        if let Some(prompt_id) = update.prompt_id {
+            settings.post_process_selected_prompt_id = Some(prompt_id);
+        }
+    }
+
+    if rebuild_post_processor {
+        *state.post_processor.write().unwrap() =
+            Arc::new(PostProcessor::from_config(&state.llm, &settings));
+    }
+    state.host.write_settings(settings);
+    info!("Updated the engine configuration via API");
+    Ok(Json(current_config(&state)))
+}
+
+/// API keys, including revoked ones, with their usage.
+#[utoipa::path(
+    get,
+    path = "/keys",
+    tag = "keys",
+    responses((status = 200, description = "The keys, oldest first", body = [ApiKey]))
+)]
+async fn api_keys(
+    State(state): State<Arc<ApiState>>,
+) -> Result<Json<Vec<ApiKey>>, (StatusCode, Json<ErrorResponse>)> {
+    state.api_keys.list().map(Json).map_err(|e| {
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to list API keys: {}", e),
+        )
+    })
+}
+
+/// Create an API key. The response holds the key itself, which can't be
+/// retrieved later.
+#[utoipa::path(
+    post,
+    path = "/keys",
+    tag = "keys",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 201, description = "The new key", body = CreatedApiKeyResponse),
+        (status = 400, description = "No name or scopes", body = ErrorResponse)
+    )
+)]
+async fn create_api_key(
+    State(state): State<Arc<ApiState>>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedApiKeyResponse>), (StatusCode, Json<ErrorResponse>)> {
+    if request.name.trim().is_empty() || request.scopes.is_empty() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "An API key needs a name and at least one scope",
+        ));
+    }
+    match state.api_keys.create(&request.name, &request.scopes) {
+        Ok((key, secret)) => Ok((
+            StatusCode::CREATED,
+            Json(CreatedApiKeyResponse { key, secret }),
+        )),
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to create API key: {}", e),
+        )),
+    }
+}
+
+/// Revoke an API key; requests made with it are rejected from then on.
+#[utoipa::path(
+    delete,
+    path = "/keys/{id}",
+    tag = "keys",
+    params(("id" = i64, Path, description = "API key id")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 404, description = "No such key, or it is already revoked", body = ErrorResponse)
+    )
+)]
+async fn revoke_api_key(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    match state.api_keys.revoke(id) {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            format!("No active API key with id {}", id),
+        )),
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to revoke API key: {}", e),
+        )),
+    }
+}
+
+fn parse_time_bound(name: &str, value: &str, end_of_day: bool) -> Result<i64, String> {
+    let value = value.trim();
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return Ok(if end_of_day { timestamp + 1 } else { timestamp });
+    }
+
+    let invalid = || {
+        format!(
+            "Invalid value '{}' for '{}': expected a Unix timestamp or a YYYY-MM-DD date",
+            value, name
+        )
+    };
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| invalid())?;
+    let date = if end_of_day {
+        date.succ_opt().ok_or_else(invalid)?
+    } else {
+        date
+    };
+    let midnight = date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?;
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .ok_or_else(invalid)
+}
+
+/// The /transcribe response body: the JSON response as is, or a document
+/// rendered from its segments. Channel labels prefix the segment text.
+/// `response` in `format`, with the post-processed text in place of the
+/// transcript unless `return_raw` is set.
+fn transcribe_reply(
+    mut response: TranscribeResponse,
+    format: TranscriptFormat,
+    subtitles: SubtitleOutput,
+    return_raw: bool,
+) -> Response {
+    if !return_raw {
+        if let Some(processed) = response.post_processed.take() {
+            response.text = processed.text;
+        }
+    }
+    let audio = TranscribedAudio(response.duration_seconds);
+    let mut reply = transcribe_output(format, subtitles, response);
+    reply.extensions_mut().insert(audio);
+    reply
+}
+
+fn transcribe_output(
+    format: TranscriptFormat,
+    subtitles: SubtitleOutput,
+    mut response: TranscribeResponse,
+) -> Response {
+    if format == TranscriptFormat::Json {
+        response.analytics = response
+            .segments
+            .as_deref()
+            .and_then(|segments| segment_analytics(segments, response.duration_seconds));
+        if let Some((track_format, target_language)) = subtitles.tracks {
+            response.subtitle_tracks = Some(subtitle_tracks(
+                &response,
+                track_format,
+                target_language,
+                subtitles.layout,
+            ));
+        }
+        return Json(response).into_response();
+    }
+
+    let language = match format {
+        TranscriptFormat::Srt | TranscriptFormat::Vtt => subtitles.language,
+        _ => SubtitleLanguage::Translated,
+    };
+    let mut transcript = cue_transcript(
+        response.text,
+        response.segments.as_deref().unwrap_or_default(),
+        response.duration_seconds,
+        language,
+    );
+    shape_subtitles(&mut transcript, format, subtitles.layout);
+    formatted_response(format, &transcript, None)
+}
+
+/// A transcript of `text` with a cue per segment in `language`, labeled
+/// with its speaker or channel.
+fn cue_transcript(
+    text: String,
+    segments: &[SegmentResponse],
+    duration: f32,
+    language: SubtitleLanguage,
+) -> Transcript {
+    Transcript {
+        title: None,
+        text,
+        segments: segments
+            .iter()
+            .map(|seg| {
+                let text = seg.subtitle_text(language);
+                Cue {
+                    start: seg.start,
+                    end: seg.end,
+                    text: match seg.label() {
+                        Some(label) => format!("[{}] {}", label, text),
+                        None => text,
+                    },
+                }
+            })
+            .collect(),
+        duration,
+    }
+}
+
+/// The subtitles of a translated `response` as a file in the spoken
+/// language and one in `target_language`.
+fn subtitle_tracks(
+    response: &TranscribeResponse,
+    format: TranscriptFormat,
+    target_language: String,
+    layout: Option<SubtitleLayout>,
+) -> Vec<SubtitleTrack> {
+    let segments = response.segments.as_deref().unwrap_or_default();
+    [
+        (response.language.clone(), SubtitleLanguage::Original),
+        (Some(target_language), SubtitleLanguage::Translated),
+    ]
+    .into_iter()
+    .map(|(language, shown)| {
+        let mut transcript =
+            cue_transcript(String::new(), segments, response.duration_seconds, shown);
+        shape_subtitles(&mut transcript, format, layout);
+        SubtitleTrack {
+            language,
+            translated: shown == SubtitleLanguage::Translated,
+            format: format.extension(),
+            content: format.render(&transcript),
+        }
+    })
+    .collect()
+}
+
+/// Re-split the segments of `transcript` into cues within `layout` if
+/// given and `format` is a subtitle format.
+fn shape_subtitles(
+    transcript: &mut Transcript,
+    format: TranscriptFormat,
+    layout: Option<SubtitleLayout>,
+) {
+    if let Some(layout) = layout {
+        if matches!(format, TranscriptFormat::Srt | TranscriptFormat::Vtt) {
+            transcript.shape_subtitles(&layout);
+        }
+    }
+}
+
+/// Render `transcript` with a matching content type, as an attachment named
+/// `file_name` when given.
+fn formatted_response(
+    format: TranscriptFormat,
+    transcript: &Transcript,
+    file_name: Option<&str>,
+) -> Response {
+    document_response(format, format.render(transcript), file_name)
+}
+
+/// `body` as a document in `format`, as an attachment named `file_name`
+/// if given.
+fn document_response(format: TranscriptFormat, body: String, file_name: Option<&str>) -> Response {
+    let mut response = body.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    if let Some(value) = file_name
+        .and_then(|name| HeaderValue::from_str(&format!("attachment; filename=\"{}\"", name)).ok())
+    {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    response
+}
+
+/// Talk-time analytics of `segments`, by their speaker or channel labels.
+fn segment_analytics(segments: &[SegmentResponse], duration: f32) -> Option<TalkAnalytics> {
+    let segments: Vec<TalkSegment> = segments
+        .iter()
+        .map(|seg| TalkSegment {
+            start: seg.start,
+            end: seg.end,
+            speaker: seg.label(),
+            text: &seg.text,
+        })
+        .collect();
+    analytics::analyze(&segments, duration)
+}
+
+/// Full transcript text for `segments`: space-separated for a single
+/// channel, or one "[label] text" line per segment when channels are separate
+/// or the segments come from several tracks.
+fn segments_text(segments: &[SegmentResponse]) -> String {
+    if segments.iter().any(|seg| seg.label().is_some()) {
+        segments
+            .iter()
+            .map(|seg| format!("[{}] {}", seg.label().unwrap_or(""), seg.text))
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        segments
+            .iter()
+            .map(|seg| seg.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Replace the segment texts in `response` with their translations into
+/// `target_language`, keeping timestamps and channels.
+async fn translate_response(
+    translator: &Translator,
+    response: &mut TranscribeResponse,
+    target_language: &str,
+) -> Result<(), String> {
+    let Some(segments) = response.segments.as_mut() else {
+        return Ok(());
+    };
+
+    let texts: Vec<String> = segments.iter().map(|seg| seg.text.clone()).collect();
+    let translated = translator.translate(&texts, target_language).await?;
+    for (seg, text) in segments.iter_mut().zip(translated) {
+        seg.original = Some(std::mem::replace(&mut seg.text, text));
+    }
+    response.text = segments_text(segments);
+    Ok(())
+}
+
+/// Label for a channel in separated output: "left"/"right" for stereo,
+/// otherwise "channel N" (1-based).
+fn channel_label(index: usize, num_channels: usize) -> String {
+    match (num_channels, index) {
+        (2, 0) => "left".to_string(),
+        (2, 1) => "right".to_string(),
+        _ => format!("channel {}", index + 1),
+    }
+}
+
+/// OpenAPI document for the REST API, served at /openapi.json. The MCP
+/// routes are described by the MCP protocol instead.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Handy API",
+        description = "Local speech-to-text with the models downloaded in Handy."
+    ),
+    paths(
+        health,
+        status,
+        transcribe,
+        transcribe_multitrack,
+        create_upload,
+        upload_status,
+        append_upload,
+        delete_upload,
+        transcribe_upload,
+        detect_language,
+        caption_preview,
+        start_session,
+        stop_session,
+        cancel_session,
+        clear_context,
+        start_meeting,
+        meeting,
+        stop_meeting,
+        events_ws,
+        benchmark,
+        benchmark_history,
+        models,
+        model_catalog,
+        model,
+        download_model,
+        cancel_model_download,
+        unload_model,
+        history,
+        history_entry,
+        export_history_entry,
+        replacements,
+        set_replacements,
+        config,
+        update_config,
+        api_keys,
+        create_api_key,
+        revoke_api_key
+    ),
+    components(schemas(
+        TranscribeForm,
+        MultitrackForm,
+        CreateUploadRequest,
+        UploadResponse,
+        StartSessionRequest,
+        SessionResponse,
+        SessionResultResponse,
+        StartMeetingRequest,
+        MeetingResponse,
+        MeetingSegment,
+        DetectLanguageForm,
+        CaptionPreviewForm,
+        TranscribeResponse,
+        TalkAnalytics,
+        SpeakerAnalytics,
+        Chapter,
+        Mention,
+        PostProcessedResponse,
+        Entities,
+        Entity,
+        Amount,
+        PreviousTranscription,
+        SegmentResponse,
+        SubtitleTrack,
+        WordResponse,
+        DetectLanguageResponse,
+        DetectedLanguage,
+        BenchmarkResponse,
+        BenchmarkHistoryResponse,
+        BenchmarkResult,
+        ModelsResponse,
+        ModelInfo,
+        EngineType,
+        ModelCatalog,
+        CatalogModel,
+        CatalogEntry,
+        RtfTier,
+        HardwareInfo,
+        ModelMemory,
+        GpuInfo,
+        UnloadResponse,
+        HistoryResponse,
+        HistoryEntry,
+        HistorySegment,
+        HistorySource,
+        TextReplacement,
+        ConfigResponse,
+        PostProcessingConfig,
+        UpdateConfigRequest,
+        UpdatePostProcessing,
+        ApiKey,
+        ApiKeyUsage,
+        ApiScope,
+        CreateApiKeyRequest,
+        CreatedApiKeyResponse,
+        StatusResponse,
+        HealthResponse,
+        ErrorResponse
+    )),
+    tags(
+        (name = "transcription"),
+        (name = "uploads", description = "Resumable uploads for large files on unreliable connections"),
+        (name = "dictation", description = "Push-to-talk sessions recorded with the app's microphone, and dictation events"),
+        (name = "meeting", description = "Continuous transcription of the microphone and the system audio"),
+        (name = "models", description = "Downloading, inspecting and unloading models"),
+        (name = "history", description = "Stored transcriptions from the app and the API"),
+        (name = "benchmark"),
+        (name = "settings", description = "Text replacements and the engine configuration"),
+        (name = "keys", description = "API keys with scopes, for sharing the server"),
+        (name = "status")
+    )
+)]
+struct ApiDoc;
+
+impl ApiDoc {
+    /// The document, requiring a bearer token everywhere but /health when
+    /// the server has one.
+    fn document(auth: bool) -> utoipa::openapi::OpenApi {
+        let mut openapi = Self::openapi();
+        if auth {
+            if let Some(components) = openapi.components.as_mut() {
+                components.add_security_scheme(
+                    "bearer",
+                    SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+                );
+            }
+            openapi.security = Some(vec![SecurityRequirement::new(
+                "bearer",
+                Vec::<String>::new(),
+            )]);
+        }
+        openapi
+    }
+}
+
+struct RunningServer {
+    port: u16,
+    shutdown_tx: oneshot::Sender<()>,
+    /// Closed on stop, since its SSE streams would hold up the shutdown.
+    mcp: Arc<McpServer>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Owns the REST API server task so it can be started, stopped and restarted
+/// at runtime. The server binds to all interfaces unless `bind` is configured.
+pub struct ApiServer {
+    host: Host,
+    transcription_manager: Arc<TranscriptionManager>,
+    model_manager: Arc<ModelManager>,
+    limits: ApiLimits,
+    bind: IpAddr,
+    auth_token: Option<Arc<str>>,
+    max_concurrency: Arc<AtomicUsize>,
+    /// Serve Swagger UI at /docs next to /openapi.json.
+    docs: bool,
+    translator: Option<Arc<Translator>>,
+    post_processor: Arc<RwLock<Arc<PostProcessor>>>,
+    llm: LlmConfig,
+    aligner: Option<Arc<WordAligner>>,
+    benchmarker: Arc<Benchmarker>,
+    history_manager: Arc<HistoryManager>,
+    /// Kept across restarts of the server.
+    cache: Option<Arc<TranscriptCache<TranscribeResponse>>>,
+    /// Resumable uploads, kept across restarts of the server.
+    uploads: Arc<UploadStore>,
+    api_keys: Arc<ApiKeyManager>,
+    /// Dictation sessions, kept across restarts of the server.
+    dictation: Arc<DictationSessions>,
+    events: Arc<EventBus>,
+    meeting: Arc<MeetingRecorder>,
+    running: tokio::sync::Mutex<Option<RunningServer>>,
+}
+
+impl ApiServer {
+    /// Translation (HANDY_API_TRANSLATION_*, see translation::Translator)
+    /// and word alignment (HANDY_API_ALIGN_MODEL, see alignment::WordAligner)
+    /// are configured from the environment, and the LLM for post-processing
+    /// from `[llm]` or the app's post-processing settings at startup.
+    pub fn new(
+        host: Host,
+        transcription_manager: Arc<TranscriptionManager>,
+        model_manager: Arc<ModelManager>,
+        benchmarker: Arc<Benchmarker>,
+        history_manager: Arc<HistoryManager>,
+        api_keys: Arc<ApiKeyManager>,
+        events: Arc<EventBus>,
+        meeting: Arc<MeetingRecorder>,
+        config: &Config,
+    ) -> Self {
+        let translator = Translator::from_env().unwrap_or_else(|e| {
+            error!("Translation disabled: {}", e);
+            None
+        });
+        let aligner = WordAligner::from_env().unwrap_or_else(|e| {
+            error!("Word alignment disabled: {}", e);
+            None
+        });
+        let settings = host.settings();
+        let post_processor = PostProcessor::from_config(&config.llm, &settings);
+        let llm = config.llm.clone();
+        let config = &config.api;
+        let dictation = Arc::new(DictationSessions::new(
+            host.clone(),
+            transcription_manager.clone(),
+            history_manager.clone(),
+            events.clone(),
+        ));
+
+        Self {
+            host,
+            transcription_manager,
+            model_manager,
+            limits: ApiLimits::from_config(config),
+            bind: config.bind.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            auth_token: config
+                .auth_token
+                .as_deref()
+                .filter(|token| !token.is_empty())
+                .map(Arc::from),
+            max_concurrency: Arc::new(AtomicUsize::new(
+                config
+                    .max_concurrency
+                    .or(settings.api_max_concurrency)
+                    .unwrap_or(0),
+            )),
+            docs: config.docs.unwrap_or(true),
+            translator: translator.map(Arc::new),
+            post_processor: Arc::new(RwLock::new(Arc::new(post_processor))),
+            llm,
+            aligner: aligner.map(Arc::new),
+            benchmarker,
+            history_manager,
+            cache: config
+                .cache_entries
+                .filter(|&entries| entries > 0)
+                .map(|entries| {
+                    let ttl = config.cache_ttl_secs.unwrap_or(DEFAULT_CACHE_TTL_SECS);
+                    Arc::new(TranscriptCache::new(entries, Duration::from_secs(ttl)))
+                }),
+            uploads: Arc::new(UploadStore::new(
+                MAX_UPLOADS,
+                Duration::from_secs(config.upload_ttl_secs.unwrap_or(DEFAULT_UPLOAD_TTL_SECS)),
+            )),
+            api_keys,
+            dictation,
+            events,
+            meeting,
+            running: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    fn router(&self, in_flight: Arc<AtomicUsize>, mcp: Arc<McpServer>) -> Router {
+        let state = Arc::new(ApiState {
+            host: self.host.clone(),
+            transcription_manager: self.transcription_manager.clone(),
+            model_manager: self.model_manager.clone(),
+            limits: self.limits,
+            started_at: Instant::now(),
+            in_flight,
+            max_concurrency: self.max_concurrency.clone(),
+            translator: self.translator.clone(),
+            post_processor: self.post_processor.clone(),
+            llm: self.llm.clone(),
+            aligner: self.aligner.clone(),
+            benchmarker: self.benchmarker.clone(),
+            history_manager: self.history_manager.clone(),
+            cache: self.cache.clone(),
+            uploads: self.uploads.clone(),
+            api_keys: self.api_keys.clone(),
+            dictation: self.dictation.clone(),
+            events: self.events.clone(),
+            meeting: self.meeting.clone(),
+        });
+
+        let body_limit = if self.limits.max_upload_bytes > 0 {
+            DefaultBodyLimit::max(self.limits.max_upload_bytes)
+        } else {
+            DefaultBodyLimit::disable()
+        };
+
+        let auth = Arc::new(ApiAuth {
+            token: self.auth_token.clone(),
+            keys: self.api_keys.clone(),
+        });
+        let scope = |scope: Option<ApiScope>| {
+            middleware::from_fn_with_state((auth.clone(), scope), authorize)
+        };
+
+        let transcription = Router::new()
+            .route("/transcribe", post(transcribe))
+            .route("/transcribe/multitrack", post(transcribe_multitrack))
+            .route("/uploads", post(create_upload))
+            .route(
+                "/uploads/:id",
+                get(upload_status)
+                    .patch(append_upload)
+                    .delete(delete_upload),
+            )
+            .route("/uploads/:id/transcribe", post(transcribe_upload))
+            .route("/detect-language", post(detect_language))
+            .route("/captions/preview", post(caption_preview))
+            .route("/sessions", post(start_session))
+            .route("/sessions/:id", delete(cancel_session))
+            .route("/sessions/:id/stop", post(stop_session))
+            .route("/contexts/:name", delete(clear_context))
+            .route("/meeting", get(meeting).post(start_meeting))
+            .route("/meeting/stop", post(stop_meeting))
+            .merge(mcp.routes())
+            .route_layer(scope(Some(ApiScope::Transcribe)));
+        let model_management = Router::new()
+            .route("/benchmark", get(benchmark))
+            .route("/models/unload", post(unload_model))
+            .route(
+                "/models/:id/download",
+                post(download_model).delete(cancel_model_download),
+            )
+            .route_layer(scope(Some(ApiScope::Models)));
+        let history = Router::new()
+            .route("/history", get(history))
+            .route("/history/:id", get(history_entry))
+            .route("/history/:id/export", get(export_history_entry))
+            .route("/ws/events", get(events_ws))
+            .route_layer(scope(Some(ApiScope::History)));
+        let admin = Router::new()
+            .route("/keys", get(api_keys).post(create_api_key))
+            .route("/keys/:id", delete(revoke_api_key))
+            .route("/config", get(config).put(update_config))
+            .route_layer(scope(Some(ApiScope::Admin)));
+        let info = Router::new()
+            .route("/status", get(status))
+            .route("/benchmark/history", get(benchmark_history))
+            .route("/models", get(models))
+            .route("/models/catalog", get(model_catalog))
+            .route("/models/:id", get(model))
+            .route_layer(scope(None));
+
+        let mut router = Router::new()
+            .merge(transcription)
+            .merge(model_management)
+            .merge(history)
+            .merge(admin)
+            .merge(info)
+            .route(
+                "/replacements",
+                get(replacements)
+                    .route_layer(scope(None))
+                    .merge(put(set_replacements).route_layer(scope(Some(ApiScope::Admin)))),
+            );
+
+        // Health checks and the API description stay open so orchestrators
+        // and client generators don't need the token
+        let openapi = ApiDoc::document(
+            self.auth_token.is_some() || self.api_keys.has_active_keys().unwrap_or(false),
+        );
+        router = router.route("/health", get(health));
+        router = if self.docs {
+            router.merge(SwaggerUi::new("/docs").url("/openapi.json", openapi))
+        } else {
+            router.route("/openapi.json", get(move || async move { Json(openapi) }))
+        };
+        router.layer(body_limit).with_state(state)
+    }
+
+    /// Port the server is currently listening on, if running.
+    pub async fn running_port(&self) -> Option<u16> {
+        self.running.lock().await.as_ref().map(|r| r.port)
+    }
+
+    /// Bind to `port` and start serving. Fails if the server is already
+    /// running or the port cannot be bound.
+    pub async fn start(&self, port: u16) -> Result<(), String> {
+        let mut running = self.running.lock().await;
+        if let Some(server) = running.as_ref() {
+            return Err(format!(
+                "API server is already running on port {}",
+                server.port
+            ));
+        }
+
+        let addr = SocketAddr::new(self.bind, port);
+        let listener = tokio::net::TcpListener::bind(&addr)
+            .await
+            .map_err(|e| format!("Failed to bind API server to {}: {}", addr, e))?;
+        info!("Transcription API server listening on http://{}", addr);
+        if self.auth_token.is_some() || self.api_keys.has_active_keys().unwrap_or(false) {
+            info!("API requests require a bearer token or API key");
+        }
+
+        // MCP over HTTP+SSE, with the same limits as /transcribe and its
+        // tool calls counted towards `max_concurrency` and key usage
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let mcp = Arc::new(McpServer::http(
+            self.transcription_manager.clone(),
+            self.model_manager.clone(),
+            self.limits,
+            in_flight.clone(),
+            self.max_concurrency.clone(),
+            self.api_keys.clone(),
+        ));
+        let app = self.router(in_flight, mcp.clone());
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let task = tauri::async_runtime::spawn(async move {
+            let result = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            if let Err(e) = result {
+                error!("API server error: {}", e);
+            }
+        });
+
+        *running = Some(RunningServer {
+            port,
+            shutdown_tx,
+            mcp,
+            task,
+        });
+        Ok(())
+    }
+
+    /// Stop the server and wait up to `SHUTDOWN_TIMEOUT` for in-flight
+    /// requests to finish, then abort them. Does nothing if the server is
+    /// not running.
+    pub async fn stop(&self) {
+        let server = self.running.lock().await.take();
+        if let Some(mut server) = server {
+            let _ = server.shutdown_tx.send(());
+            server.mcp.close();
+            match tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut server.task).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => warn!("API server task ended abnormally: {}", e),
+                Err(_) => {
+                    warn!(
+                        "API server on port {} did not stop within {}s; aborting in-flight requests",
+                        server.port,
+                        SHUTDOWN_TIMEOUT.as_secs()
+                    );
+                    server.task.abort();
+                }
+            }
+            info!("Transcription API server on port {} stopped", server.port);
+        }
+    }
+
+    /// Stop the server if running, then start it again on `port`.
+    pub async fn restart(&self, port: u16) -> Result<(), String> {
+        self.stop().await;
+        self.start(port).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(fields: &[(&str, &str)]) -> TranscribeOptions {
+        let mut options = TranscribeOptions::default();
+        for (name, value) in fields {
+            options.set(name, value).unwrap();
+        }
+        options
+    }
+
+    fn cached(fields: &[(&str, &str)]) -> String {
+        serde_json::to_string(&options(fields).cached()).unwrap()
+    }
+
+    #[test]
+    fn cache_key_ignores_rendering_options() {
+        let plain = cached(&[]);
+        assert_eq!(plain, cached(&[("response_format", "txt")]));
+        assert_eq!(plain, cached(&[("ephemeral", "true")]));
+        assert_eq!(plain, cached(&[("return_raw", "false")]));
+        assert_eq!(plain, cached(&[("max_line_chars", "42")]));
+        assert_eq!(
+            cached(&[("response_format", "srt")]),
+            cached(&[("response_format", "vtt")])
+        );
+    }
+
+    #[test]
+    fn cache_key_covers_transcript_options() {
+        let plain = cached(&[]);
+        for field in [
+            ("language", "de"),
+            ("prompt", "Handy"),
+            ("temperature", "0.2"),
+            ("offset_seconds", "5"),
+            ("keywords", "handy"),
+            ("denoise", "true"),
+            ("response_format", "srt"),
+        ] {
+            assert_ne!(plain, cached(&[field]), "{:?}", field);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Reading uploaded audio, from a multipart field or a raw request body,
+//! into memory or a temp file, and what is known about its format before
+//! decoding.
+
+use axum::{
+    body::Bytes,
+    extract::{multipart::Field, Request},
+    http::{header, HeaderMap, StatusCode},
+    response::Json,
+};
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use std::io::Write;
+use std::sync::Arc;
+use symphonia::core::io::MediaSource;
+use symphonia::core::probe::Hint;
+use tempfile::NamedTempFile;
+
+use super::{error_response, ErrorResponse};
+use crate::audio_toolkit::format::{detect_format, extension_for_mime, FORMAT_SNIFF_LEN};
+
+/// Uploads larger than this are spooled to a temporary file instead of memory.
+const SPOOL_THRESHOLD_BYTES: usize = 8 * 1024 * 1024;
+
+/// An uploaded audio file, held in memory or spooled to disk when large.
+pub(super) enum AudioUpload {
+    Memory(Bytes),
+    Spooled { file: Arc<NamedTempFile>, len: u64 },
+}
+
+impl AudioUpload {
+    pub(super) fn len(&self) -> u64 {
+        match self {
+            AudioUpload::Memory(bytes) => bytes.len() as u64,
+            AudioUpload::Spooled { len, .. } => *len,
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(super) fn is_spooled(&self) -> bool {
+        matches!(self, AudioUpload::Spooled { .. })
+    }
+
+    /// Open an independent, seekable reader over the upload for symphonia.
+    pub(super) fn media_source(&self) -> Result<Box<dyn MediaSource>, String> {
+        match self {
+            AudioUpload::Memory(bytes) => Ok(Box::new(std::io::Cursor::new(bytes.clone()))),
+            AudioUpload::Spooled { file, .. } => file
+                .reopen()
+                .map(|f| Box::new(f) as Box<dyn MediaSource>)
+                .map_err(|e| format!("Failed to reopen spooled upload: {}", e)),
+        }
+    }
+}
+
+/// What is known about an upload's format before decoding.
+#[derive(Default)]
+pub(super) struct UploadFormat {
+    /// Content type declared on the multipart field.
+    pub(super) mime_type: Option<String>,
+    /// Extension of the client-supplied file name.
+    pub(super) file_extension: Option<String>,
+    /// Format sniffed from the file's magic bytes.
+    pub(super) detected: Option<&'static str>,
+}
+
+impl UploadFormat {
+    /// Most trustworthy format guess: magic bytes, then content type.
+    pub(super) fn best_guess(&self) -> Option<&'static str> {
+        self.detected
+            .or_else(|| self.mime_type.as_deref().and_then(extension_for_mime))
+    }
+
+    /// Build a symphonia probe hint from everything known about the upload.
+    pub(super) fn hint(&self) -> Hint {
+        let mut hint = Hint::new();
+        let extension = self
+            .best_guess()
+            .map(str::to_string)
+            .or_else(|| self.file_extension.clone());
+        if let Some(ext) = extension {
+            hint.with_extension(&ext);
+        }
+        if let Some(mime) = &self.mime_type {
+            hint.mime_type(mime);
+        }
+        hint
+    }
+}
+
+/// Lowercased extension of a client-supplied file name.
+pub(super) fn file_extension(name: &str) -> Option<String> {
+    std::path::Path::new(name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
+
+/// Whether the request body is `multipart/form-data`.
+pub(super) fn is_multipart(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .trim_start()
+                .to_ascii_lowercase()
+                .starts_with("multipart/form-data")
+        })
+}
+
+/// Whether the client asked for plain text rather than JSON.
+pub(super) fn accepts_plain_text(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let media_types: Vec<String> = accept
+        .split(',')
+        .map(|entry| {
+            entry
+                .split(';')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .collect();
+    media_types.iter().any(|t| t == "text/plain")
+        && !media_types.iter().any(|t| t == "application/json")
+}
+
+/// Read a multipart field chunk by chunk, switching from memory to a temp
+/// file once it grows past `SPOOL_THRESHOLD_BYTES` if `spool` is set.
+pub(super) async fn read_upload(
+    field: Field<'_>,
+    max_upload_bytes: usize,
+    spool: bool,
+) -> Result<(AudioUpload, UploadFormat), (StatusCode, Json<ErrorResponse>)> {
+    let format = UploadFormat {
+        mime_type: field.content_type().map(str::to_string),
+        file_extension: field.file_name().and_then(file_extension),
+        detected: None,
+    };
+    let chunks = field.map_err(|e| {
+        // Exceeding the body limit surfaces here as a 413
+        let status = e.status();
+        if status == StatusCode::PAYLOAD_TOO_LARGE {
+            return upload_too_large(max_upload_bytes);
+        }
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to read file field: {}", e),
+        )
+    });
+    spool_upload(chunks, format, max_upload_bytes, spool).await
+}
+
+/// Read a raw request body as the upload, with its format from the
+/// `Content-Type` header. Large bodies are spooled to disk if `spool` is set.
+pub(super) async fn read_body_upload(
+    request: Request,
+    max_upload_bytes: usize,
+    spool: bool,
+) -> Result<(AudioUpload, UploadFormat), (StatusCode, Json<ErrorResponse>)> {
+    let format = UploadFormat {
+        mime_type: request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        ..Default::default()
+    };
+    // The body limit only applies to extractors, so `spool_upload` checks
+    // the size itself
+    let chunks = request.into_body().into_data_stream().map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to read request body: {}", e),
+        )
+    });
+    spool_upload(chunks, format, max_upload_bytes, spool).await
+}
+
+pub(super) fn upload_too_large(max_upload_bytes: usize) -> (StatusCode, Json<ErrorResponse>) {
+    error_response(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!(
+            "Upload exceeds the maximum size of {} bytes",
+            max_upload_bytes
+        ),
+    )
+}
+
+/// Collect upload chunks, switching from memory to a temp file once they
+/// grow past `SPOOL_THRESHOLD_BYTES` unless spooling isn't allowed, and
+/// sniff the format.
+async fn spool_upload(
+    chunks: impl Stream<Item = Result<Bytes, (StatusCode, Json<ErrorResponse>)>>,
+    mut format: UploadFormat,
+    max_upload_bytes: usize,
+    spool_allowed: bool,
+) -> Result<(AudioUpload, UploadFormat), (StatusCode, Json<ErrorResponse>)> {
+    let mut chunks = std::pin::pin!(chunks);
+    let mut header: Vec<u8> = Vec::with_capacity(FORMAT_SNIFF_LEN);
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut spool: Option<(NamedTempFile, u64)> = None;
+    let mut received = 0usize;
+
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        received += chunk.len();
+        if max_upload_bytes > 0 && received > max_upload_bytes {
+            return Err(upload_too_large(max_upload_bytes));
+        }
+
+        if header.len() < FORMAT_SNIFF_LEN {
+            let take = (FORMAT_SNIFF_LEN - header.len()).min(chunk.len());
+            header.extend_from_slice(&chunk[..take]);
+        }
+
+        if spool_allowed && spool.is_none() && buffer.len() + chunk.len() > SPOOL_THRESHOLD_BYTES {
+            let mut file = NamedTempFile::new().map_err(|e| {
+                error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to create temp file for upload: {}", e),
+                )
+            })?;
+            file.write_all(&buffer).map_err(|e| {
+                error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to spool upload: {}", e),
+                )
+            })?;
+            spool = Some((file, buffer.len() as u64));
+            buffer = Vec::new();
+        }
+
+        match spool.as_mut() {
+            Some((file, len)) => {
+                file.write_all(&chunk).map_err(|e| {
+                    error_response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to spool upload: {}", e),
+                    )
+                })?;
+                *len += chunk.len() as u64;
+            }
+            None => buffer.extend_from_slice(&chunk),
+        }
+    }
+
+    format.detected = detect_format(&header);
+
+    let upload = match spool {
+        Some((mut file, len)) => {
+            file.flush().map_err(|e| {
+                error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to spool upload: {}", e),
+                )
+            })?;
+            AudioUpload::Spooled {
+                file: Arc::new(file),
+                len,
+            }
+        }
+        None => AudioUpload::Memory(Bytes::from(buffer)),
+    };
+    Ok((upload, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::{FromRequest, Multipart};
+
+    fn headers(accept: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, accept.parse().unwrap());
+        headers
+    }
+
+    fn wav(len: usize) -> Vec<u8> {
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        bytes.resize(len, 0);
+        bytes
+    }
+
+    fn multipart_request(file: &[u8]) -> Request {
+        let mut body = b"--X\r\nContent-Disposition: form-data; name=\"file\"; \
+            filename=\"clip.WAV\"\r\nContent-Type: audio/wav\r\n\r\n"
+            .to_vec();
+        body.extend_from_slice(file);
+        body.extend_from_slice(b"\r\n--X--\r\n");
+        Request::builder()
+            .header(header::CONTENT_TYPE, "multipart/form-data; boundary=X")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn read_multipart(
+        file: &[u8],
+        max_upload_bytes: usize,
+    ) -> Result<(AudioUpload, UploadFormat), (StatusCode, Json<ErrorResponse>)> {
+        tauri::async_runtime::block_on(async {
+            let mut multipart = Multipart::from_request(multipart_request(file), &())
+                .await
+                .unwrap();
+            let field = multipart.next_field().await.unwrap().unwrap();
+            read_upload(field, max_upload_bytes, false).await
+        })
+    }
+
+    fn read_body(
+        body: &[u8],
+        max_upload_bytes: usize,
+    ) -> Result<(AudioUpload, UploadFormat), (StatusCode, Json<ErrorResponse>)> {
+        let request = Request::builder()
+            .header(header::CONTENT_TYPE, "audio/wav")
+            .body(Body::from(body.to_vec()))
+            .unwrap();
+        tauri::async_runtime::block_on(read_body_upload(request, max_upload_bytes, false))
+    }
+
+    #[test]
+    fn plain_text_only_when_json_is_not_accepted() {
+        assert!(accepts_plain_text(&headers("text/plain")));
+        assert!(accepts_plain_text(&headers("Text/Plain; charset=utf-8")));
+        assert!(accepts_plain_text(&headers("text/html, text/plain;q=0.9")));
+        assert!(!accepts_plain_text(&headers(
+            "text/plain, application/json"
+        )));
+        assert!(!accepts_plain_text(&headers("application/json")));
+        assert!(!accepts_plain_text(&headers("*/*")));
+        assert!(!accepts_plain_text(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn reads_multipart_upload() {
+        let Ok((upload, format)) = read_multipart(&wav(64), 64) else {
+            panic!("expected the upload to be read");
+        };
+        assert_eq!(upload.len(), 64);
+        assert!(!upload.is_spooled());
+        assert_eq!(format.mime_type.as_deref(), Some("audio/wav"));
+        assert_eq!(format.file_extension.as_deref(), Some("wav"));
+        assert_eq!(format.detected, Some("wav"));
+    }
+
+    #[test]
+    fn rejects_oversized_multipart_upload() {
+        let Err((status, _)) = read_multipart(&wav(64), 63) else {
+            panic!("expected the upload to be rejected");
+        };
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn reads_body_upload() {
+        let Ok((upload, format)) = read_body(&wav(64), 0) else {
+            panic!("expected the upload to be read");
+        };
+        assert_eq!(upload.len(), 64);
+        assert_eq!(format.mime_type.as_deref(), Some("audio/wav"));
+        assert_eq!(format.detected, Some("wav"));
+    }
+
+    #[test]
+    fn rejects_oversized_body_upload() {
+        let Err((status, _)) = read_body(&wav(64), 63) else {
+            panic!("expected the upload to be rejected");
+        };
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,82 @@
+//! System audio (loopback) capture devices.
+//!
+//! - Windows: WASAPI opens an input stream on an output device in loopback
+//!   mode, so every output device is a loopback source.
+//! - Linux: PulseAudio/PipeWire monitor sources exposed as ALSA devices, or
+//!   else the monitor of the default output, which the `pulse` device stands
+//!   for and which is recorded with [`PulseMonitor`].
+//! - macOS: there is no native system audio capture. cpal has no
+//!   ScreenCaptureKit/process tap support, so a third-party virtual loopback
+//!   driver such as BlackHole must be installed.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+#[cfg(not(target_os = "windows"))]
+use transcribe_rs::audio::capture::is_loopback_device_name;
+#[cfg(target_os = "linux")]
+pub use transcribe_rs::audio::capture::PulseMonitor;
+
+use super::device::CpalDeviceInfo;
+
+pub fn list_loopback_devices() -> Result<Vec<CpalDeviceInfo>, Box<dyn std::error::Error>> {
+    #[cfg(target_os = "windows")]
+    {
+        super::device::list_output_devices()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Ok(super::device::list_input_devices()?
+            .into_iter()
+            .filter(|d| is_loopback_device_name(&d.name))
+            .collect())
+    }
+}
+
+/// The loopback device used when none is selected.
+pub fn default_loopback_device() -> Option<cpal::Device> {
+    let host = crate::audio_toolkit::get_cpal_host();
+
+    #[cfg(target_os = "windows")]
+    {
+        host.default_output_device()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut devices: Vec<cpal::Device> = host.input_devices().ok()?.collect();
+        let position = devices
+            .iter()
+            .position(|d| d.name().is_ok_and(|n| is_loopback_device_name(&n)));
+
+        #[cfg(target_os = "linux")]
+        let position = position.or_else(|| {
+            devices
+                .iter()
+                .position(|d| d.name().is_ok_and(|n| n == "pulse"))
+        });
+
+        position.map(|i| devices.swap_remove(i))
+    }
+}
+
+/// Stream configuration to use for a loopback `device`.
+pub fn loopback_config(
+    device: &cpal::Device,
+) -> Result<cpal::SupportedStreamConfig, cpal::DefaultStreamConfigError> {
+    #[cfg(target_os = "windows")]
+    {
+        device.default_output_config()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        device.default_input_config()
+    }
+}
+
+/// Whether `device` is the ALSA `pulse` device, which stands for the
+/// monitor of the default output when recording system audio.
+#[cfg(target_os = "linux")]
+pub fn is_default_monitor(device: &cpal::Device) -> bool {
+    device.name().is_ok_and(|n| n == "pulse")
+}
@@ -1,12 +1,13 @@
 // Re-export all audio components
 mod device;
+pub mod loopback;
 mod recorder;
 mod resampler;
 mod utils;
 mod visualizer;
 
 pub use device::{list_input_devices, list_output_devices, CpalDeviceInfo};
-pub use recorder::AudioRecorder;
+pub use recorder::{AudioRecorder, RecorderFrame};
 pub use resampler::FrameResampler;
-pub use utils::save_wav_file;
+pub use utils::{encode_wav, save_wav_file};
 pub use visualizer::AudioVisualiser;
@@ -10,12 +10,30 @@ use cpal::{
 };
 
 use crate::audio_toolkit::{
-    audio::{AudioVisualiser, FrameResampler},
+    audio::{loopback, AudioVisualiser, FrameResampler},
     constants,
     vad::{self, VadFrame},
     VoiceActivityDetector,
 };
 
+/// A 16 kHz frame of the input, passed to the frame callback.
+pub enum RecorderFrame<'a> {
+    /// Audio while not recording.
+    Idle(&'a [f32]),
+    /// A frame of a recording, and whether the VAD kept it as speech.
+    Recording { speech: bool },
+}
+
+type FrameCallback = Arc<dyn Fn(RecorderFrame) + Send + Sync + 'static>;
+
+/// The open input, held until the recorder closes.
+#[allow(dead_code)]
+enum InputStream {
+    Cpal(cpal::Stream),
+    #[cfg(target_os = "linux")]
+    PulseMonitor(loopback::PulseMonitor),
+}
+
 enum Cmd {
     Start,
     Stop(mpsc::Sender<Vec<f32>>),
@@ -28,6 +46,7 @@ pub struct AudioRecorder {
     worker_handle: Option<std::thread::JoinHandle<()>>,
     vad: Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
     level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    frame_cb: Option<FrameCallback>,
 }
 
 impl AudioRecorder {
@@ -38,6 +57,7 @@ impl AudioRecorder {
             worker_handle: None,
             vad: None,
             level_cb: None,
+            frame_cb: None,
         })
     }
 
@@ -54,15 +74,21 @@ impl AudioRecorder {
         self
     }
 
+    /// Call `cb` with every 16 kHz frame while the stream is open, e.g. to
+    /// listen for a wake word between recordings.
+    pub fn with_frame_callback<F>(mut self, cb: F) -> Self
+    where
+        F: Fn(RecorderFrame) + Send + Sync + 'static,
+    {
+        self.frame_cb = Some(Arc::new(cb));
+        self
+    }
+
     pub fn open(&mut self, device: Option<Device>) -> Result<(), Box<dyn std::error::Error>> {
         if self.worker_handle.is_some() {
             return Ok(()); // already open
         }
 
-        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
-        let (cmd_tx, cmd_rx) = mpsc::channel::<Cmd>();
-        let (init_tx, init_rx) = mpsc::sync_channel::<Result<(), String>>(1);
-
         let host = crate::audio_toolkit::get_cpal_host();
         let device = match device {
             Some(dev) => dev,
@@ -71,15 +97,59 @@ impl AudioRecorder {
                 .ok_or_else(|| Error::new(std::io::ErrorKind::NotFound, "No input device found"))?,
         };
 
+        self.open_device(device, false)
+    }
+
+    /// Open `device` to record the system audio playing through it.
+    pub fn open_loopback(&mut self, device: Device) -> Result<(), Box<dyn std::error::Error>> {
+        if self.worker_handle.is_some() {
+            return Ok(()); // already open
+        }
+
+        self.open_device(device, true)
+    }
+
+    fn open_device(
+        &mut self,
+        device: Device,
+        loopback: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
+        let (cmd_tx, cmd_rx) = mpsc::channel::<Cmd>();
+        let (init_tx, init_rx) = mpsc::sync_channel::<Result<(), String>>(1);
+
         let thread_device = device.clone();
         let vad = self.vad.clone();
         // Move the optional level callback into the worker thread
         let level_cb = self.level_cb.clone();
+        let frame_cb = self.frame_cb.clone();
 
         let worker = std::thread::spawn(move || {
-            let init_result = (|| -> Result<(cpal::Stream, u32), String> {
-                let config = AudioRecorder::get_preferred_config(&thread_device)
-                    .map_err(|e| format!("Failed to fetch preferred config: {e}"))?;
+            let init_result = (|| -> Result<(InputStream, u32), String> {
+                // The default output's monitor isn't an ALSA device, so it's
+                // recorded through the sound server by name
+                #[cfg(target_os = "linux")]
+                if loopback && loopback::is_default_monitor(&thread_device) {
+                    let source =
+                        loopback::PulseMonitor::default_source().map_err(|e| e.to_string())?;
+                    log::info!("Using PulseAudio source: {}", source);
+                    let monitor = loopback::PulseMonitor::open(&source, move |samples| {
+                        let _ = sample_tx.send(samples);
+                    })
+                    .map_err(|e| format!("Failed to record {source}: {e}"))?;
+                    return Ok((
+                        InputStream::PulseMonitor(monitor),
+                        loopback::PulseMonitor::SAMPLE_RATE,
+                    ));
+                }
+
+                let config = if loopback {
+                    loopback::loopback_config(&thread_device)
+                        .map_err(|e| format!("Failed to fetch loopback config: {e}"))?
+                } else {
+                    AudioRecorder::get_preferred_config(&thread_device)
+                        .map_err(|e| format!("Failed to fetch preferred config: {e}"))?
+                };
 
                 let sample_rate = config.sample_rate().0;
                 let channels = config.channels() as usize;
@@ -137,14 +207,14 @@ impl AudioRecorder {
                     .play()
                     .map_err(|e| format!("Failed to start microphone stream: {e}"))?;
 
-                Ok((stream, sample_rate))
+                Ok((InputStream::Cpal(stream), sample_rate))
             })();
 
             match init_result {
                 Ok((stream, sample_rate)) => {
                     let _ = init_tx.send(Ok(()));
                     // Keep the stream alive while we process samples.
-                    run_consumer(sample_rate, vad, sample_rx, cmd_rx, level_cb);
+                    run_consumer(sample_rate, vad, sample_rx, cmd_rx, level_cb, frame_cb);
                     drop(stream);
                 }
                 Err(error_message) => {
@@ -313,6 +383,7 @@ fn run_consumer(
     sample_rx: mpsc::Receiver<Vec<f32>>,
     cmd_rx: mpsc::Receiver<Cmd>,
     level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    frame_cb: Option<FrameCallback>,
 ) {
     let mut frame_resampler = FrameResampler::new(
         in_sample_rate as usize,
@@ -338,20 +409,31 @@ fn run_consumer(
         samples: &[f32],
         recording: bool,
         vad: &Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
+        frame_cb: &Option<FrameCallback>,
         out_buf: &mut Vec<f32>,
     ) {
         if !recording {
+            if let Some(cb) = frame_cb {
+                cb(RecorderFrame::Idle(samples));
+            }
             return;
         }
 
-        if let Some(vad_arc) = vad {
+        let speech = if let Some(vad_arc) = vad {
             let mut det = vad_arc.lock().unwrap();
             match det.push_frame(samples).unwrap_or(VadFrame::Speech(samples)) {
-                VadFrame::Speech(buf) => out_buf.extend_from_slice(buf),
-                VadFrame::Noise => {}
+                VadFrame::Speech(buf) => {
+                    out_buf.extend_from_slice(buf);
+                    true
+                }
+                VadFrame::Noise => false,
             }
         } else {
             out_buf.extend_from_slice(samples);
+            true
+        };
+        if let Some(cb) = frame_cb {
+            cb(RecorderFrame::Recording { speech });
         }
     }
 
@@ -370,7 +452,7 @@ fn run_consumer(
 
         // ---------- existing pipeline ------------------------------------ //
         frame_resampler.push(&raw, &mut |frame: &[f32]| {
-            handle_frame(frame, recording, &vad, &mut processed_samples)
+            handle_frame(frame, recording, &vad, &frame_cb, &mut processed_samples)
         });
 
         // non-blocking check for a command
@@ -390,12 +472,12 @@ fn run_consumer(
                     // Drain any audio chunks that were captured but not yet consumed
                     while let Ok(remaining) = sample_rx.try_recv() {
                         frame_resampler.push(&remaining, &mut |frame: &[f32]| {
-                            handle_frame(frame, true, &vad, &mut processed_samples)
+                            handle_frame(frame, true, &vad, &frame_cb, &mut processed_samples)
                         });
                     }
 
                     frame_resampler.finish(&mut |frame: &[f32]| {
-                        handle_frame(frame, true, &vad, &mut processed_samples)
+                        handle_frame(frame, true, &vad, &frame_cb, &mut processed_samples)
                     });
 
                     let _ = reply_tx.send(std::mem::take(&mut processed_samples));
@@ -1,13 +1,10 @@
-use rubato::{FftFixedIn, Resampler};
+use crate::audio_toolkit::resample::StreamingResampler;
 use std::time::Duration;
 
-// Make this a constant you can tweak
-const RESAMPLER_CHUNK_SIZE: usize = 1024;
-
+/// Resamples a live stream and re-chunks the output into fixed-duration frames.
 pub struct FrameResampler {
-    resampler: Option<FftFixedIn<f32>>,
-    chunk_in: usize,
-    in_buf: Vec<f32>,
+    resampler: Option<StreamingResampler>,
+    out_buf: Vec<f32>,
     frame_samples: usize,
     pending: Vec<f32>,
 }
@@ -17,62 +14,42 @@ impl FrameResampler {
         let frame_samples = ((out_hz as f64 * frame_dur.as_secs_f64()).round()) as usize;
         assert!(frame_samples > 0, "frame duration too short");
 
-        // Use fixed chunk size instead of GCD-based
-        let chunk_in = RESAMPLER_CHUNK_SIZE;
-
-        let resampler = (in_hz != out_hz).then(|| {
-            FftFixedIn::<f32>::new(in_hz, out_hz, chunk_in, 1, 1)
-                .expect("Failed to create resampler")
-        });
+        let resampler = (in_hz != out_hz)
+            .then(|| StreamingResampler::new(in_hz, out_hz).expect("Failed to create resampler"));
 
         Self {
             resampler,
-            chunk_in,
-            in_buf: Vec::with_capacity(chunk_in),
+            out_buf: Vec::new(),
             frame_samples,
             pending: Vec::with_capacity(frame_samples),
         }
     }
 
-    pub fn push(&mut self, mut src: &[f32], mut emit: impl FnMut(&[f32])) {
-        if self.resampler.is_none() {
+    pub fn push(&mut self, src: &[f32], mut emit: impl FnMut(&[f32])) {
+        let Some(resampler) = self.resampler.as_mut() else {
             self.emit_frames(src, &mut emit);
             return;
-        }
+        };
 
-        while !src.is_empty() {
-            let space = self.chunk_in - self.in_buf.len();
-            let take = space.min(src.len());
-            self.in_buf.extend_from_slice(&src[..take]);
-            src = &src[take..];
-
-            if self.in_buf.len() == self.chunk_in {
-                // let start = std::time::Instant::now();
-                if let Ok(out) = self
-                    .resampler
-                    .as_mut()
-                    .unwrap()
-                    .process(&[&self.in_buf[..]], None)
-                {
-                    // let duration = start.elapsed();
-                    // log::debug!("Resampler took: {:?}", duration);
-                    self.emit_frames(&out[0], &mut emit);
-                }
-                self.in_buf.clear();
-            }
+        let mut out = std::mem::take(&mut self.out_buf);
+        if let Err(e) = resampler.push(src, &mut out) {
+            log::warn!("{}", e);
         }
+        self.emit_frames(&out, &mut emit);
+        out.clear();
+        self.out_buf = out;
     }
 
     pub fn finish(&mut self, mut emit: impl FnMut(&[f32])) {
-        // Process any remaining input samples
-        if let Some(ref mut resampler) = self.resampler {
-            if !self.in_buf.is_empty() {
-                // Pad with zeros to reach chunk size
-                self.in_buf.resize(self.chunk_in, 0.0);
-                if let Ok(out) = resampler.process(&[&self.in_buf[..]], None) {
-                    self.emit_frames(&out[0], &mut emit);
-                }
+        // Flush any buffered input and the resampler tail
+        if let Some(resampler) = self.resampler.as_mut() {
+            let mut out = std::mem::take(&mut self.out_buf);
+            if let Err(e) = resampler.finish(&mut out) {
+                log::warn!("{}", e);
             }
+            self.emit_frames(&out, &mut emit);
+            out.clear();
+            self.out_buf = out;
         }
 
         // Emit any remaining pending frame (padded with zeros)
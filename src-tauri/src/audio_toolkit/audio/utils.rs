@@ -1,19 +1,34 @@
 use anyhow::Result;
 use hound::{WavSpec, WavWriter};
 use log::debug;
+use std::io::{Cursor, Seek, Write};
 use std::path::Path;
 
 /// Save audio samples as a WAV file
 pub async fn save_wav_file<P: AsRef<Path>>(file_path: P, samples: &[f32]) -> Result<()> {
-    let spec = WavSpec {
+    let writer = WavWriter::create(file_path.as_ref(), wav_spec())?;
+    write_samples(writer, samples)?;
+    debug!("Saved WAV file: {:?}", file_path.as_ref());
+    Ok(())
+}
+
+/// Encode audio samples as WAV in memory, e.g. to encrypt before saving.
+pub fn encode_wav(samples: &[f32]) -> Result<Vec<u8>> {
+    let mut buffer = Cursor::new(Vec::new());
+    write_samples(WavWriter::new(&mut buffer, wav_spec())?, samples)?;
+    Ok(buffer.into_inner())
+}
+
+fn wav_spec() -> WavSpec {
+    WavSpec {
         channels: 1,
         sample_rate: 16000,
         bits_per_sample: 16,
         sample_format: hound::SampleFormat::Int,
-    };
-
-    let mut writer = WavWriter::create(file_path.as_ref(), spec)?;
+    }
+}
 
+fn write_samples<W: Write + Seek>(mut writer: WavWriter<W>, samples: &[f32]) -> Result<()> {
     // Convert f32 samples to i16 for WAV
     for sample in samples {
         let sample_i16 = (sample * i16::MAX as f32) as i16;
@@ -21,6 +36,5 @@ pub async fn save_wav_file<P: AsRef<Path>>(file_path: P, samples: &[f32]) -> Res
     }
 
     writer.finalize()?;
-    debug!("Saved WAV file: {:?}", file_path.as_ref());
     Ok(())
 }
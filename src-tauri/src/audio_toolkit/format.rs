@@ -0,0 +1,121 @@
+//! Audio container/codec detection from magic bytes.
+
+/// Number of leading bytes needed by [`detect_format`].
+pub const FORMAT_SNIFF_LEN: usize = 64;
+
+/// Guess the audio format of `header` (the first bytes of a file) from its
+/// magic bytes. Returns a lowercase file extension such as `"wav"` or `"opus"`.
+pub fn detect_format(header: &[u8]) -> Option<&'static str> {
+    let starts_with = |magic: &[u8]| header.starts_with(magic);
+    let at = |offset: usize, magic: &[u8]| {
+        header
+            .get(offset..offset + magic.len())
+            .is_some_and(|s| s == magic)
+    };
+
+    if starts_with(b"RIFF") && at(8, b"WAVE") {
+        Some("wav")
+    } else if starts_with(b"fLaC") {
+        Some("flac")
+    } else if starts_with(b"OggS") {
+        // The first page carries the codec identification header
+        if at(28, b"OpusHead") {
+            Some("opus")
+        } else {
+            Some("ogg")
+        }
+    } else if starts_with(b"ID3") {
+        Some("mp3")
+    } else if starts_with(b"#!AMR") {
+        Some("amr")
+    } else if starts_with(b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")) {
+        Some("aiff")
+    } else if starts_with(b"caff") {
+        Some("caf")
+    } else if starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        Some("webm")
+    } else if at(4, b"ftyp") {
+        Some("m4a")
+    } else if header.len() >= 2 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0 {
+        // MPEG frame sync: layer bits of 00 indicate ADTS AAC, otherwise MP3
+        if header[1] & 0x06 == 0 {
+            Some("aac")
+        } else {
+            Some("mp3")
+        }
+    } else {
+        None
+    }
+}
+
+/// Map a MIME type to the extension used by [`detect_format`].
+pub fn extension_for_mime(mime: &str) -> Option<&'static str> {
+    let essence = mime.split(';').next().unwrap_or("").trim();
+    match essence.to_ascii_lowercase().as_str() {
+        "audio/wav" | "audio/x-wav" | "audio/wave" | "audio/vnd.wave" => Some("wav"),
+        "audio/flac" | "audio/x-flac" => Some("flac"),
+        "audio/ogg" | "application/ogg" => Some("ogg"),
+        "audio/opus" => Some("opus"),
+        "audio/mpeg" | "audio/mp3" => Some("mp3"),
+        "audio/aac" | "audio/aacp" | "audio/x-aac" => Some("aac"),
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" | "video/mp4" => Some("m4a"),
+        "audio/amr" => Some("amr"),
+        "audio/webm" | "video/webm" => Some("webm"),
+        "audio/aiff" | "audio/x-aiff" => Some("aiff"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_wav() {
+        let mut header = b"RIFF\x24\x00\x00\x00WAVEfmt ".to_vec();
+        header.resize(FORMAT_SNIFF_LEN, 0);
+        assert_eq!(detect_format(&header), Some("wav"));
+    }
+
+    #[test]
+    fn test_detect_ogg_vs_opus() {
+        let mut vorbis = b"OggS".to_vec();
+        vorbis.resize(28, 0);
+        vorbis.extend_from_slice(b"\x01vorbis");
+        assert_eq!(detect_format(&vorbis), Some("ogg"));
+
+        let mut opus = b"OggS".to_vec();
+        opus.resize(28, 0);
+        opus.extend_from_slice(b"OpusHead");
+        assert_eq!(detect_format(&opus), Some("opus"));
+    }
+
+    #[test]
+    fn test_detect_mpeg_frames() {
+        assert_eq!(detect_format(&[0xFF, 0xFB, 0x90, 0x00]), Some("mp3"));
+        assert_eq!(detect_format(&[0xFF, 0xF1, 0x50, 0x80]), Some("aac"));
+        assert_eq!(detect_format(b"ID3\x04\x00"), Some("mp3"));
+    }
+
+    #[test]
+    fn test_detect_other_containers() {
+        assert_eq!(detect_format(b"#!AMR\n"), Some("amr"));
+        assert_eq!(detect_format(b"\x00\x00\x00\x20ftypM4A "), Some("m4a"));
+        assert_eq!(detect_format(&[0x1A, 0x45, 0xDF, 0xA3, 0x01]), Some("webm"));
+        assert_eq!(detect_format(b"fLaC\x00"), Some("flac"));
+    }
+
+    #[test]
+    fn test_detect_unknown() {
+        assert_eq!(detect_format(b""), None);
+        assert_eq!(detect_format(b"hello world"), None);
+    }
+
+    #[test]
+    fn test_extension_for_mime() {
+        assert_eq!(extension_for_mime("audio/mpeg"), Some("mp3"));
+        assert_eq!(extension_for_mime("audio/ogg; codecs=opus"), Some("ogg"));
+        assert_eq!(extension_for_mime("AUDIO/X-WAV"), Some("wav"));
+        assert_eq!(extension_for_mime("application/octet-stream"), None);
+    }
+}
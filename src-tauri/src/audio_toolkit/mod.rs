@@ -1,12 +1,24 @@
 pub mod audio;
 pub mod constants;
+pub mod denoise;
+pub mod format;
+pub mod resample;
+pub mod silence;
 pub mod text;
 pub mod utils;
 pub mod vad;
+pub mod voice_commands;
+pub mod wake_word;
 
 pub use audio::{
-    list_input_devices, list_output_devices, save_wav_file, AudioRecorder, CpalDeviceInfo,
+    encode_wav, list_input_devices, list_output_devices, save_wav_file, AudioRecorder,
+    CpalDeviceInfo, RecorderFrame,
+};
+pub use text::{
+    apply_custom_words, apply_replacements, clean_up_transcript, filter_transcription_output,
+    Replacement,
 };
-pub use text::{apply_custom_words, filter_transcription_output};
 pub use utils::get_cpal_host;
 pub use vad::{SileroVad, VoiceActivityDetector};
+pub use voice_commands::{VoiceCommand, VoiceCommands};
+pub use wake_word::{WakeWordDetector, WakeWordModels};
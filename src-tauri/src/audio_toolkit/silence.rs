@@ -0,0 +1,68 @@
+//! Cheap energy-based check for audio without speech, so silent or very
+//! short uploads can skip the engine, which tends to hallucinate text for
+//! silence.
+
+/// Frame length for the energy measurement.
+const FRAME_SECS: f32 = 0.03;
+
+/// Audio shorter than this is treated as having no speech.
+const MIN_DURATION_SECS: f32 = 0.3;
+
+/// Frames quieter than this RMS (about -46 dBFS) count as silence.
+const SILENCE_RMS: f32 = 0.005;
+
+/// Louder frames must add up to at least this much for the audio to count
+/// as speech.
+const MIN_ACTIVE_SECS: f32 = 0.1;
+
+/// Whether mono `samples` at `sample_rate` are too short or too quiet to
+/// contain speech.
+pub fn is_silent(samples: &[f32], sample_rate: usize) -> bool {
+    if (samples.len() as f32) < MIN_DURATION_SECS * sample_rate as f32 {
+        return true;
+    }
+
+    let frame_len = ((FRAME_SECS * sample_rate as f32) as usize).max(1);
+    let active_frames = samples
+        .chunks(frame_len)
+        .filter(|frame| {
+            let energy = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+            energy.sqrt() >= SILENCE_RMS
+        })
+        .count();
+    (active_frames as f32) * FRAME_SECS < MIN_ACTIVE_SECS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(secs: f32, amplitude: f32) -> Vec<f32> {
+        (0..(secs * 16000.0) as usize)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin() * amplitude)
+            .collect()
+    }
+
+    #[test]
+    fn test_is_silent() {
+        assert!(is_silent(&[], 16000));
+        assert!(is_silent(&vec![0.0; 32000], 16000));
+        // Faint noise floor
+        assert!(is_silent(&tone(2.0, 0.002), 16000));
+        // Too short, however loud
+        assert!(is_silent(&tone(0.2, 0.5), 16000));
+        assert!(!is_silent(&tone(1.0, 0.1), 16000));
+    }
+
+    #[test]
+    fn test_short_sound_in_silence() {
+        // A 50ms click in two seconds of silence isn't speech
+        let mut audio = vec![0.0; 32000];
+        audio[16000..16800].copy_from_slice(&tone(0.05, 0.5));
+        assert!(is_silent(&audio, 16000));
+
+        // Half a second of sound is
+        audio[16000..24000].copy_from_slice(&tone(0.5, 0.1));
+        assert!(!is_silent(&audio, 16000));
+    }
+}
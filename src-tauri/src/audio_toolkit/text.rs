@@ -320,6 +320,161 @@ pub fn filter_transcription_output(
     filtered.trim().to_string()
 }
 
+static SPACE_BEFORE_PUNCTUATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+([,.!?;:])").unwrap());
+static REPEATED_PUNCTUATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"([,;:])(?:\s*[,;:])+").unwrap());
+static LEADING_PUNCTUATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[\s,;:.]+").unwrap());
+static ENGLISH_I_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bi\b").unwrap());
+
+/// Drops a word repeated right after itself ("the the" -> "the"), keeping
+/// the first word's case and the last word's punctuation. Repetitions
+/// separated by punctuation ("no, no") are kept.
+fn remove_repeated_words(text: &str) -> String {
+    let mut result: Vec<String> = Vec::new();
+
+    for word in text.split_whitespace() {
+        if let Some(previous) = result.last_mut() {
+            let (prefix, suffix) = extract_punctuation(word);
+            // Empty for words that are all punctuation
+            let core = word
+                .get(prefix.len()..word.len().saturating_sub(suffix.len()))
+                .unwrap_or("");
+            let previous_is_bare = previous.chars().all(|c| c.is_alphanumeric() || c == '\'');
+            if previous_is_bare
+                && prefix.is_empty()
+                && !core.is_empty()
+                && previous.to_lowercase() == core.to_lowercase()
+            {
+                previous.push_str(suffix);
+                continue;
+            }
+        }
+        result.push(word.to_string());
+    }
+
+    result.join(" ")
+}
+
+/// Capitalizes the first letter of the text and of each sentence.
+/// A sentence ends with '.', '!' or '?' followed by whitespace, so numbers
+/// like "3.5" are left alone.
+fn capitalize_sentences(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut capitalize = true;
+    let mut after_terminator = false;
+
+    for c in text.chars() {
+        if after_terminator {
+            capitalize = c.is_whitespace();
+            after_terminator = false;
+        }
+        if capitalize && c.is_alphanumeric() {
+            result.extend(c.to_uppercase());
+            capitalize = false;
+        } else {
+            result.push(c);
+        }
+        if matches!(c, '.' | '!' | '?') {
+            after_terminator = true;
+        }
+    }
+
+    result
+}
+
+/// Rule-based cleanup for paste-ready dictation, more aggressive than
+/// [`filter_transcription_output`]:
+/// 1. Removing filler words and stutters
+/// 2. Removing immediately repeated words ("I I think" -> "I think")
+/// 3. Fixing spacing around punctuation and punctuation left behind by
+///    removed words
+/// 4. Capitalizing sentences, and the pronoun "I" in English
+pub fn clean_up_transcript(text: &str, lang: &str) -> String {
+    let filtered = filter_transcription_output(text, lang, &None);
+    let deduplicated = remove_repeated_words(&filtered);
+
+    let tidied = SPACE_BEFORE_PUNCTUATION.replace_all(&deduplicated, "$1");
+    let tidied = REPEATED_PUNCTUATION.replace_all(&tidied, "$1");
+    let tidied = LEADING_PUNCTUATION.replace(&tidied, "");
+
+    let mut cleaned = capitalize_sentences(&tidied);
+    if lang.split(&['-', '_'][..]).next() == Some("en") {
+        cleaned = ENGLISH_I_PATTERN.replace_all(&cleaned, "I").to_string();
+    }
+    cleaned.trim().to_string()
+}
+
+static SPACE_AROUND_NEWLINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t]*\n[ \t]*").unwrap());
+static MULTI_BLANK_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t]{2,}").unwrap());
+
+/// A compiled text replacement rule, see [`apply_replacements`].
+pub struct Replacement {
+    pattern: Regex,
+    replacement: String,
+}
+
+impl Replacement {
+    /// Replace `text` wherever it is spoken: case-insensitively, with any
+    /// whitespace between words and, when `text` starts or ends with a word
+    /// character, only at word boundaries. A comma or period right after
+    /// the match is removed too, since speech models often punctuate
+    /// around spoken commands ("first, new line, second").
+    pub fn literal(text: &str, replacement: &str) -> Result<Self, String> {
+        let words: Vec<String> = text.split_whitespace().map(regex::escape).collect();
+        if words.is_empty() {
+            return Err("Replacement text is empty".to_string());
+        }
+        let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let start = if is_word_char(text.trim().chars().next()) {
+            r"\b"
+        } else {
+            ""
+        };
+        let end = if is_word_char(text.trim().chars().last()) {
+            r"\b"
+        } else {
+            ""
+        };
+        let pattern = format!(r"(?i){}{}{}[,.]?", start, words.join(r"\s+"), end);
+        Ok(Self {
+            pattern: Regex::new(&pattern).map_err(|e| e.to_string())?,
+            // `$` is literal in a literal rule
+            replacement: replacement.replace('$', "$$"),
+        })
+    }
+
+    /// Replace matches of the regular expression `pattern`; `replacement`
+    /// may refer to capture groups as `$1` or `${name}`.
+    pub fn regex(pattern: &str, replacement: &str) -> Result<Self, String> {
+        Ok(Self {
+            pattern: Regex::new(pattern)
+                .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+/// Applies `replacements` in order, e.g. voice macros like "new line" ->
+/// "\n". Spaces left around inserted line breaks and doubled spaces are
+/// removed.
+pub fn apply_replacements(text: &str, replacements: &[Replacement]) -> String {
+    if replacements.is_empty() {
+        return text.to_string();
+    }
+
+    let mut result = text.to_string();
+    for rule in replacements {
+        result = rule
+            .pattern
+            .replace_all(&result, rule.replacement.as_str())
+            .to_string();
+    }
+
+    let result = SPACE_AROUND_NEWLINE.replace_all(&result, "\n");
+    let result = MULTI_BLANK_PATTERN.replace_all(&result, " ");
+    result.trim_matches(' ').to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,4 +713,73 @@ mod tests {
             result
         );
     }
+
+    #[test]
+    fn test_clean_up_removes_repeated_words() {
+        let text = "I I think the the plan is fine, fine";
+        let result = clean_up_transcript(text, "en");
+        assert_eq!(result, "I think the plan is fine, fine");
+    }
+
+    #[test]
+    fn test_clean_up_keeps_punctuation_of_repeat() {
+        let text = "we should go go. then stop";
+        let result = clean_up_transcript(text, "en");
+        assert_eq!(result, "We should go. Then stop");
+    }
+
+    #[test]
+    fn test_clean_up_fixes_punctuation_and_casing() {
+        let text = "uh, so i think , um, we can ship it . what do you think ?";
+        let result = clean_up_transcript(text, "en");
+        assert_eq!(result, "So I think, we can ship it. What do you think?");
+    }
+
+    #[test]
+    fn test_clean_up_preserves_numbers() {
+        let text = "version 3.5 is out. ship it";
+        let result = clean_up_transcript(text, "en");
+        assert_eq!(result, "Version 3.5 is out. Ship it");
+    }
+
+    #[test]
+    fn test_clean_up_only_capitalizes_i_in_english() {
+        let text = "i vini e i cibi";
+        let result = clean_up_transcript(text, "it");
+        assert_eq!(result, "I vini e i cibi");
+    }
+
+    #[test]
+    fn test_replacement_literal_voice_command() {
+        let rules = vec![Replacement::literal("new line", "\n").unwrap()];
+        let result = apply_replacements("First point, New  line, second point", &rules);
+        assert_eq!(result, "First point,\nsecond point");
+    }
+
+    #[test]
+    fn test_replacement_literal_word_boundaries() {
+        let rules = vec![Replacement::literal("cat", "dog").unwrap()];
+        let result = apply_replacements("cat concatenate Cat.", &rules);
+        assert_eq!(result, "dog concatenate dog");
+    }
+
+    #[test]
+    fn test_replacement_literal_symbols() {
+        let rules = vec![Replacement::literal("@@sig", "Best,\nJane $5").unwrap()];
+        let result = apply_replacements("Thanks @@sig", &rules);
+        assert_eq!(result, "Thanks Best,\nJane $5");
+    }
+
+    #[test]
+    fn test_replacement_regex_captures() {
+        let rules = vec![Replacement::regex(r"(?i)\bticket (\d+)", "#$1").unwrap()];
+        let result = apply_replacements("see ticket 42 please", &rules);
+        assert_eq!(result, "see #42 please");
+    }
+
+    #[test]
+    fn test_replacement_invalid() {
+        assert!(Replacement::regex("(", "").is_err());
+        assert!(Replacement::literal("  ", "x").is_err());
+    }
 }
@@ -0,0 +1,447 @@
+//! Voice commands in dictation: spoken punctuation ("comma"), layout ("new
+//! paragraph"), editing ("delete last sentence") and casing ("all caps next
+//! word"), interpreted after transcription.
+//!
+//! Each language with a built-in grammar has its own phrases; others use
+//! the English ones. Speech models tend to punctuate around spoken commands
+//! ("Hello, comma, world."), so punctuation around a command is dropped.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VoiceCommand {
+    /// Attached to the previous word, e.g. "," or ")". Sentence punctuation
+    /// replaces punctuation the model put after that word.
+    Punctuation(String),
+    /// Attached to the next word, e.g. "(".
+    Opening(String),
+    /// Text between spaces, e.g. "–".
+    Symbol(String),
+    NewLine,
+    NewParagraph,
+    DeleteLastWord,
+    DeleteLastSentence,
+    CapitalizeNextWord,
+    UppercaseNextWord,
+    LowercaseNextWord,
+    /// Take the next word as spoken, e.g. "literal comma".
+    Literal,
+}
+
+/// The phrases of a grammar, longest first so "question mark" wins over a
+/// shorter phrase starting the same way.
+pub struct VoiceCommands {
+    phrases: Vec<(Vec<String>, VoiceCommand)>,
+}
+
+impl VoiceCommands {
+    /// The built-in grammar for `lang`, or the English one for languages
+    /// without one, with `custom` phrases taking precedence.
+    pub fn for_language(lang: Option<&str>, custom: Vec<(String, VoiceCommand)>) -> Self {
+        let base_lang = lang
+            .and_then(|lang| lang.split(&['-', '_'][..]).next())
+            .unwrap_or("en");
+        let builtin = builtin_grammar(base_lang)
+            .into_iter()
+            .map(|(phrase, command)| (phrase.to_string(), command));
+
+        let mut phrases: Vec<(Vec<String>, VoiceCommand)> = custom
+            .into_iter()
+            .chain(builtin)
+            .map(|(phrase, command)| (phrase.split_whitespace().map(normalize).collect(), command))
+            .filter(|(words, _): &(Vec<String>, _)| !words.is_empty())
+            .collect();
+        // Stable, so custom phrases stay ahead of built-in ones of a length
+        phrases.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        Self { phrases }
+    }
+
+    /// The command spoken at the start of `words`, and its length in words.
+    fn match_at(&self, words: &[Token]) -> Option<(usize, &VoiceCommand)> {
+        self.phrases.iter().find_map(|(phrase, command)| {
+            let matches = words.len() >= phrase.len()
+                && phrase
+                    .iter()
+                    .zip(words)
+                    .all(|(expected, word)| matches!(word, Token::Word(_, key) if key == expected));
+            matches.then_some((phrase.len(), command))
+        })
+    }
+
+    /// Interpret the commands in `text`.
+    pub fn apply(&self, text: &str) -> String {
+        let tokens = tokenize(text);
+        let mut out: Vec<Piece> = Vec::new();
+        let mut case: Option<Case> = None;
+        // Set by commands that end a sentence
+        let mut capitalize = false;
+        let mut literal = false;
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let word = match &tokens[i] {
+                Token::Break => {
+                    out.push(Piece::new(Kind::Break, "\n"));
+                    i += 1;
+                    continue;
+                }
+                Token::Word(word, _) => *word,
+            };
+            let command = if literal {
+                None
+            } else {
+                self.match_at(&tokens[i..])
+            };
+            let Some((len, command)) = command else {
+                let word = match case.take() {
+                    Some(case) => case.apply(word),
+                    None if capitalize => Case::Capitalize.apply(word),
+                    None => word.to_string(),
+                };
+                out.push(Piece::new(Kind::Word, &word));
+                capitalize = false;
+                literal = false;
+                i += 1;
+                continue;
+            };
+            i += len;
+
+            match command {
+                VoiceCommand::Punctuation(punctuation) => {
+                    if is_sentence_punctuation(punctuation) {
+                        if let Some(last) = out.last_mut().filter(|last| last.kind == Kind::Word) {
+                            let trimmed = TRAILING_PUNCTUATION.replace(&last.text, "").len();
+                            last.text.truncate(trimmed);
+                        }
+                    }
+                    out.push(Piece::new(Kind::Punctuation, punctuation));
+                    capitalize = punctuation.ends_with(SENTENCE_END);
+                }
+                VoiceCommand::Opening(opening) => out.push(Piece::new(Kind::Opening, opening)),
+                VoiceCommand::Symbol(symbol) => out.push(Piece::new(Kind::Word, symbol)),
+                VoiceCommand::NewLine => {
+                    out.push(Piece::new(Kind::Break, "\n"));
+                    capitalize = true;
+                }
+                VoiceCommand::NewParagraph => {
+                    out.push(Piece::new(Kind::Break, "\n\n"));
+                    capitalize = true;
+                }
+                VoiceCommand::DeleteLastWord => {
+                    while let Some(piece) = out.pop() {
+                        if piece.kind == Kind::Word {
+                            break;
+                        }
+                    }
+                }
+                VoiceCommand::DeleteLastSentence => {
+                    // Back to the end of the sentence before, skipping line
+                    // breaks after the last one
+                    let mut removed = false;
+                    while let Some(last) = out.last() {
+                        if removed && last.ends_sentence() {
+                            break;
+                        }
+                        removed |= last.kind != Kind::Break;
+                        out.pop();
+                    }
+                    capitalize = false;
+                }
+                VoiceCommand::CapitalizeNextWord => case = Some(Case::Capitalize),
+                VoiceCommand::UppercaseNextWord => case = Some(Case::Upper),
+                VoiceCommand::LowercaseNextWord => case = Some(Case::Lower),
+                VoiceCommand::Literal => literal = true,
+            }
+        }
+
+        render(&out)
+    }
+}
+
+const SENTENCE_END: [char; 4] = ['.', '!', '?', '…'];
+
+static TRAILING_PUNCTUATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"[,.;:!?…]+$").unwrap());
+
+enum Token<'a> {
+    /// The word as transcribed, and as matched against phrases.
+    Word(&'a str, String),
+    Break,
+}
+
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            tokens.push(Token::Break);
+        }
+        tokens.extend(
+            line.split_whitespace()
+                .map(|word| Token::Word(word, normalize(word))),
+        );
+    }
+    tokens
+}
+
+/// Lowercase without surrounding punctuation, so "Comma," matches "comma".
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+        .replace('’', "'")
+        .to_lowercase()
+}
+
+fn is_sentence_punctuation(punctuation: &str) -> bool {
+    TRAILING_PUNCTUATION.is_match(punctuation)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Case {
+    Capitalize,
+    Upper,
+    Lower,
+}
+
+impl Case {
+    fn apply(self, word: &str) -> String {
+        match self {
+            Case::Capitalize => {
+                let mut chars = word.chars();
+                let mut capitalized = String::with_capacity(word.len());
+                // Past an opening quote or parenthesis
+                for c in chars.by_ref() {
+                    if c.is_alphanumeric() {
+                        capitalized.extend(c.to_uppercase());
+                        break;
+                    }
+                    capitalized.push(c);
+                }
+                capitalized.extend(chars);
+                capitalized
+            }
+            Case::Upper => word.to_uppercase(),
+            Case::Lower => word.to_lowercase(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    /// Words and symbols, with a space before them.
+    Word,
+    /// No space before.
+    Punctuation,
+    /// No space after.
+    Opening,
+    /// Line breaks, without spaces around them.
+    Break,
+}
+
+struct Piece {
+    kind: Kind,
+    text: String,
+}
+
+impl Piece {
+    fn new(kind: Kind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+        }
+    }
+
+    fn ends_sentence(&self) -> bool {
+        self.kind == Kind::Break || self.text.ends_with(SENTENCE_END)
+    }
+}
+
+fn render(pieces: &[Piece]) -> String {
+    let mut text = String::new();
+    let mut glued = true;
+    for piece in pieces {
+        if !glued && matches!(piece.kind, Kind::Word | Kind::Opening) {
+            text.push(' ');
+        }
+        text.push_str(&piece.text);
+        glued = matches!(piece.kind, Kind::Opening | Kind::Break);
+    }
+    text
+}
+
+fn builtin_grammar(lang: &str) -> Vec<(&'static str, VoiceCommand)> {
+    use VoiceCommand::*;
+    let punctuation = |text: &str| Punctuation(text.to_string());
+    let opening = |text: &str| Opening(text.to_string());
+    let symbol = |text: &str| Symbol(text.to_string());
+
+    match lang {
+        "de" => vec![
+            ("punkt", punctuation(".")),
+            ("komma", punctuation(",")),
+            ("fragezeichen", punctuation("?")),
+            ("ausrufezeichen", punctuation("!")),
+            ("doppelpunkt", punctuation(":")),
+            ("semikolon", punctuation(";")),
+            ("auslassungspunkte", punctuation("…")),
+            ("anführungszeichen auf", opening("„")),
+            ("anführungszeichen zu", punctuation("“")),
+            ("klammer auf", opening("(")),
+            ("klammer zu", punctuation(")")),
+            ("gedankenstrich", symbol("–")),
+            ("neue zeile", NewLine),
+            ("neuer absatz", NewParagraph),
+            ("letztes wort löschen", DeleteLastWord),
+            ("letzten satz löschen", DeleteLastSentence),
+            ("nächstes wort groß", CapitalizeNextWord),
+            ("nächstes wort in großbuchstaben", UppercaseNextWord),
+            ("nächstes wort klein", LowercaseNextWord),
+            ("wörtlich", Literal),
+        ],
+        "fr" => vec![
+            ("point", punctuation(".")),
+            ("virgule", punctuation(",")),
+            ("point d'interrogation", punctuation("?")),
+            ("point d'exclamation", punctuation("!")),
+            ("deux points", punctuation(":")),
+            ("point-virgule", punctuation(";")),
+            ("points de suspension", punctuation("…")),
+            ("ouvrir les guillemets", opening("«")),
+            ("fermer les guillemets", punctuation("»")),
+            ("ouvrir la parenthèse", opening("(")),
+            ("fermer la parenthèse", punctuation(")")),
+            ("tiret", symbol("–")),
+            ("à la ligne", NewLine),
+            ("nouveau paragraphe", NewParagraph),
+            ("supprimer le dernier mot", DeleteLastWord),
+            ("supprimer la dernière phrase", DeleteLastSentence),
+            ("majuscule au mot suivant", CapitalizeNextWord),
+            ("mot suivant en majuscules", UppercaseNextWord),
+            ("mot suivant en minuscules", LowercaseNextWord),
+            ("littéralement", Literal),
+        ],
+        "es" => vec![
+            ("punto", punctuation(".")),
+            ("coma", punctuation(",")),
+            ("signo de interrogación", punctuation("?")),
+            ("signo de exclamación", punctuation("!")),
+            ("dos puntos", punctuation(":")),
+            ("punto y coma", punctuation(";")),
+            ("puntos suspensivos", punctuation("…")),
+            ("abrir comillas", opening("«")),
+            ("cerrar comillas", punctuation("»")),
+            ("abrir paréntesis", opening("(")),
+            ("cerrar paréntesis", punctuation(")")),
+            ("guion", symbol("–")),
+            ("nueva línea", NewLine),
+            ("nuevo párrafo", NewParagraph),
+            ("borrar última palabra", DeleteLastWord),
+            ("borrar última frase", DeleteLastSentence),
+            ("mayúscula siguiente palabra", CapitalizeNextWord),
+            ("siguiente palabra en mayúsculas", UppercaseNextWord),
+            ("siguiente palabra en minúsculas", LowercaseNextWord),
+            ("literal", Literal),
+        ],
+        _ => vec![
+            ("period", punctuation(".")),
+            ("full stop", punctuation(".")),
+            ("comma", punctuation(",")),
+            ("question mark", punctuation("?")),
+            ("exclamation mark", punctuation("!")),
+            ("exclamation point", punctuation("!")),
+            ("colon", punctuation(":")),
+            ("semicolon", punctuation(";")),
+            ("ellipsis", punctuation("…")),
+            ("open quote", opening("“")),
+            ("close quote", punctuation("”")),
+            ("open parenthesis", opening("(")),
+            ("close parenthesis", punctuation(")")),
+            ("dash", symbol("–")),
+            ("new line", NewLine),
+            ("new paragraph", NewParagraph),
+            ("delete last word", DeleteLastWord),
+            ("delete last sentence", DeleteLastSentence),
+            ("scratch that", DeleteLastSentence),
+            ("cap next word", CapitalizeNextWord),
+            ("all caps next word", UppercaseNextWord),
+            ("no caps next word", LowercaseNextWord),
+            ("literal", Literal),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(text: &str) -> String {
+        VoiceCommands::for_language(Some("en"), Vec::new()).apply(text)
+    }
+
+    #[test]
+    fn test_punctuation() {
+        assert_eq!(
+            apply("hello comma world period how are you question mark"),
+            "hello, world. How are you?"
+        );
+        // Punctuation the model added around commands is dropped
+        assert_eq!(apply("Hello, comma, world. Period."), "Hello, world.");
+        assert_eq!(
+            apply("he said open quote fine close quote period"),
+            "he said “fine”."
+        );
+        assert_eq!(
+            apply("see open parenthesis below close parenthesis"),
+            "see (below)"
+        );
+        assert_eq!(apply("yes dash no"), "yes – no");
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(
+            apply("Dear Sam, new paragraph thanks for the notes. New line Alex"),
+            "Dear Sam,\n\nThanks for the notes.\nAlex"
+        );
+        // Line breaks already in the text are kept
+        assert_eq!(apply("one\ntwo comma three"), "one\ntwo, three");
+    }
+
+    #[test]
+    fn test_editing() {
+        assert_eq!(
+            apply("First point. Second point is wrong. Delete last sentence."),
+            "First point."
+        );
+        assert_eq!(apply("It's ready. Scratch that."), "");
+        assert_eq!(
+            apply("buy milk eggs delete last word bread"),
+            "buy milk bread"
+        );
+        assert_eq!(
+            apply("the cap next word rust book uses all caps next word api no caps next word Docs"),
+            "the Rust book uses API docs"
+        );
+        assert_eq!(
+            apply("add a literal comma here comma please"),
+            "add a comma here, please"
+        );
+    }
+
+    #[test]
+    fn test_languages() {
+        let german = VoiceCommands::for_language(Some("de-DE"), Vec::new());
+        assert_eq!(
+            german.apply("Hallo Komma wie geht's Fragezeichen"),
+            "Hallo, wie geht's?"
+        );
+        let french = VoiceCommands::for_language(Some("fr"), Vec::new());
+        assert_eq!(french.apply("Oui point d'interrogation"), "Oui?");
+
+        // Custom phrases, and English for languages without a grammar
+        let custom = vec![(
+            "punto e virgola".to_string(),
+            VoiceCommand::Punctuation(";".to_string()),
+        )];
+        let italian = VoiceCommands::for_language(Some("it"), custom);
+        assert_eq!(italian.apply("uno punto e virgola due period"), "uno; due.");
+    }
+}
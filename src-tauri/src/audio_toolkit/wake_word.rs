@@ -0,0 +1,230 @@
+//! Wake-word detection with openWakeWord ONNX models.
+//!
+//! Detection runs three models on 16 kHz audio: a shared melspectrogram
+//! model turns each 80 ms of audio into 8 mel frames, a shared embedding
+//! model turns the last 76 mel frames into a 96-value feature vector, and
+//! the model of the phrase scores the last 16 or so feature vectors. Each
+//! model is small enough to run on every 80 ms chunk on one CPU thread.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ort::session::Session;
+use ort::value::TensorRef;
+
+/// Audio per step of the detector: 80 ms at 16 kHz.
+pub const CHUNK_SAMPLES: usize = 1280;
+
+/// Audio before each chunk that the melspectrogram model also needs.
+const CONTEXT_SAMPLES: usize = 480;
+
+const MEL_BINS: usize = 32;
+
+/// Mel frames per feature vector.
+const MEL_WINDOW: usize = 76;
+
+const FEATURE_SIZE: usize = 96;
+
+/// Feature vectors a phrase model scores when its input shape doesn't say.
+const DEFAULT_FEATURE_FRAMES: usize = 16;
+
+/// Chunks to ignore after a detection, so one utterance triggers once.
+const COOLDOWN_CHUNKS: usize = 25;
+
+/// The model files of a wake word.
+#[derive(Clone, Debug)]
+pub struct WakeWordModels {
+    pub melspectrogram: PathBuf,
+    pub embedding: PathBuf,
+    /// The model of the phrase, e.g. `hey_jarvis_v0.1.onnx`.
+    pub keyword: PathBuf,
+}
+
+pub struct WakeWordDetector {
+    melspectrogram: Session,
+    embedding: Session,
+    keyword: Session,
+    threshold: f32,
+    feature_frames: usize,
+    chunks: Chunker,
+    /// The last `MEL_WINDOW` mel frames, `MEL_BINS` values each.
+    mel: VecDeque<f32>,
+    features: VecDeque<Vec<f32>>,
+    cooldown: usize,
+}
+
+impl WakeWordDetector {
+    /// Load the models; `threshold` is the score (0 to 1) of a detection.
+    pub fn new(models: &WakeWordModels, threshold: f32) -> Result<Self> {
+        if !(0.0..=1.0).contains(&threshold) {
+            anyhow::bail!("threshold must be between 0.0 and 1.0");
+        }
+        let keyword = load_session(&models.keyword)?;
+        // The phrase model takes [batch, frames, 96]
+        let feature_frames = keyword
+            .inputs
+            .first()
+            .and_then(|input| input.input_type.tensor_shape())
+            .and_then(|shape| shape.get(1).copied())
+            .filter(|&frames| frames > 0)
+            .map_or(DEFAULT_FEATURE_FRAMES, |frames| frames as usize);
+
+        Ok(Self {
+            melspectrogram: load_session(&models.melspectrogram)?,
+            embedding: load_session(&models.embedding)?,
+            keyword,
+            threshold,
+            feature_frames,
+            chunks: Chunker::new(),
+            mel: VecDeque::with_capacity(MEL_WINDOW * MEL_BINS),
+            features: VecDeque::with_capacity(feature_frames),
+            cooldown: 0,
+        })
+    }
+
+    /// Feed 16 kHz mono samples; returns whether the wake word was heard.
+    pub fn push(&mut self, samples: &[f32]) -> Result<bool> {
+        self.chunks.push(samples);
+        let mut detected = false;
+        while let Some(window) = self.chunks.next_window() {
+            detected |= self.process(&window)?;
+        }
+        Ok(detected)
+    }
+
+    /// Forget the audio heard so far, e.g. after a recording.
+    pub fn reset(&mut self) {
+        self.chunks = Chunker::new();
+        self.mel.clear();
+        self.features.clear();
+        self.cooldown = 0;
+    }
+
+    /// Run the models on one chunk with its context.
+    fn process(&mut self, window: &[f32]) -> Result<bool> {
+        // The melspectrogram model expects 16-bit sample values
+        let audio: Vec<f32> = window.iter().map(|s| s * 32767.0).collect();
+        let outputs = self
+            .melspectrogram
+            .run(ort::inputs![TensorRef::from_array_view((
+                [1usize, audio.len()],
+                &audio[..]
+            ))?])?;
+        let (_, mel) = outputs[0].try_extract_tensor::<f32>()?;
+        // Scaled like openWakeWord does for its embedding model
+        self.mel.extend(mel.iter().map(|value| value / 10.0 + 2.0));
+        drop(outputs);
+        let excess = self.mel.len().saturating_sub(MEL_WINDOW * MEL_BINS);
+        self.mel.drain(..excess);
+        if self.mel.len() < MEL_WINDOW * MEL_BINS {
+            return Ok(false);
+        }
+
+        let mel: Vec<f32> = self.mel.iter().copied().collect();
+        let outputs = self
+            .embedding
+            .run(ort::inputs![TensorRef::from_array_view((
+                [1usize, MEL_WINDOW, MEL_BINS, 1],
+                &mel[..]
+            ))?])?;
+        let (_, feature) = outputs[0].try_extract_tensor::<f32>()?;
+        if feature.len() != FEATURE_SIZE {
+            anyhow::bail!(
+                "embedding model returned {} values, expected {FEATURE_SIZE}",
+                feature.len()
+            );
+        }
+        if self.features.len() == self.feature_frames {
+            self.features.pop_front();
+        }
+        self.features.push_back(feature.to_vec());
+        drop(outputs);
+
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            return Ok(false);
+        }
+        if self.features.len() < self.feature_frames {
+            return Ok(false);
+        }
+
+        let features: Vec<f32> = self.features.iter().flatten().copied().collect();
+        let outputs = self.keyword.run(ort::inputs![TensorRef::from_array_view((
+            [1usize, self.feature_frames, FEATURE_SIZE],
+            &features[..]
+        ))?])?;
+        let (_, scores) = outputs[0].try_extract_tensor::<f32>()?;
+        let score = scores.first().copied().unwrap_or(0.0);
+        if score >= self.threshold {
+            log::debug!("Wake word detected with score {score:.2}");
+            self.cooldown = COOLDOWN_CHUNKS;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+fn load_session(path: &Path) -> Result<Session> {
+    Session::builder()?
+        .with_intra_threads(1)?
+        .with_inter_threads(1)?
+        .commit_from_file(path)
+        .with_context(|| format!("Failed to load wake word model {}", path.display()))
+}
+
+/// Splits audio into `CHUNK_SAMPLES` chunks, each with the
+/// `CONTEXT_SAMPLES` before it.
+struct Chunker {
+    buffer: Vec<f32>,
+}
+
+impl Chunker {
+    fn new() -> Self {
+        // Silence as the context of the first chunk
+        Self {
+            buffer: vec![0.0; CONTEXT_SAMPLES],
+        }
+    }
+
+    fn push(&mut self, samples: &[f32]) {
+        self.buffer.extend_from_slice(samples);
+    }
+
+    fn next_window(&mut self) -> Option<Vec<f32>> {
+        let len = CONTEXT_SAMPLES + CHUNK_SAMPLES;
+        if self.buffer.len() < len {
+            return None;
+        }
+        let window = self.buffer[..len].to_vec();
+        self.buffer.drain(..CHUNK_SAMPLES);
+        Some(window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunker() {
+        let mut chunks = Chunker::new();
+        // Recorder frames are 30 ms
+        let samples: Vec<f32> = (1..=3000).map(|i| i as f32).collect();
+        for frame in samples.chunks(480) {
+            chunks.push(frame);
+        }
+
+        let first = chunks.next_window().unwrap();
+        assert_eq!(first.len(), CONTEXT_SAMPLES + CHUNK_SAMPLES);
+        assert!(first[..CONTEXT_SAMPLES].iter().all(|&s| s == 0.0));
+        assert_eq!(first[CONTEXT_SAMPLES], 1.0);
+
+        let second = chunks.next_window().unwrap();
+        assert_eq!(second[0], (CHUNK_SAMPLES - CONTEXT_SAMPLES + 1) as f32);
+        assert_eq!(second[CONTEXT_SAMPLES], (CHUNK_SAMPLES + 1) as f32);
+
+        // 3000 samples make two chunks; the rest waits for more audio
+        assert!(chunks.next_window().is_none());
+    }
+}
@@ -0,0 +1,273 @@
+//! Engine benchmarks on the user's own hardware.
+//!
+//! A bundled reference clip is transcribed with each requested model to
+//! measure load time, real-time factor and memory use, so models can be
+//! compared before committing to one. Results are appended to a history file
+//! in the app data directory.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::host::Host;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+
+/// Reference clip, relative to the resource directory.
+const REFERENCE_CLIP: &str = "resources/benchmark.wav";
+/// History file, relative to the app data directory.
+const HISTORY_FILE: &str = "benchmarks.json";
+/// Number of results kept in the history.
+const MAX_HISTORY: usize = 100;
+
+/// Measurements for one model.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct BenchmarkResult {
+    pub model_id: String,
+    pub model_name: String,
+    pub engine: String,
+    /// Duration of the reference clip in seconds.
+    pub audio_secs: f32,
+    pub load_ms: u64,
+    /// Time to transcribe the clip after a warm-up run.
+    pub transcribe_ms: u64,
+    /// Transcription time divided by audio duration; below 1 is faster than
+    /// real time.
+    pub real_time_factor: f32,
+    /// Resident memory added by loading the model, if the platform reports it.
+    pub model_memory_bytes: Option<u64>,
+    /// Resident memory of the whole process after transcription.
+    pub process_memory_bytes: Option<u64>,
+    pub text: String,
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+}
+
+/// Runs benchmarks and keeps their history.
+pub struct Benchmarker {
+    host: Host,
+    transcription_manager: Arc<TranscriptionManager>,
+    model_manager: Arc<ModelManager>,
+    clip_path: PathBuf,
+    history_path: PathBuf,
+    /// Held for the duration of a run, which swaps the loaded model.
+    running: Mutex<()>,
+}
+
+impl Benchmarker {
+    pub fn new(
+        host: Host,
+        transcription_manager: Arc<TranscriptionManager>,
+        model_manager: Arc<ModelManager>,
+    ) -> Result<Self> {
+        let clip_path = host.resolve_resource(REFERENCE_CLIP)?;
+        let history_path = host.app_data_dir()?.join(HISTORY_FILE);
+        Ok(Self {
+            host,
+            transcription_manager,
+            model_manager,
+            clip_path,
+            history_path,
+            running: Mutex::new(()),
+        })
+    }
+
+    /// `--benchmark` entry point: benchmark the comma-separated `models` (the
+    /// selected model when empty) and print the results as JSON. Returns the
+    /// process exit code.
+    pub fn run_cli(&self, models: &str) -> i32 {
+        let model_ids: Vec<String> = models
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(String::from)
+            .collect();
+
+        match self.run(&model_ids) {
+            Ok(results) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&results).unwrap_or_default()
+                );
+                0
+            }
+            Err(e) => {
+                eprintln!("Benchmark failed: {}", e);
+                1
+            }
+        }
+    }
+
+    /// Benchmark each of `model_ids` in turn, or the selected model when
+    /// empty, and append the results to the history.
+    ///
+    /// Blocks for the whole run. The previously loaded model is restored
+    /// afterwards, and other transcriptions wait for or use whichever model is
+    /// loaded in the meantime. Fails if a run is already in progress.
+    pub fn run(&self, model_ids: &[String]) -> Result<Vec<BenchmarkResult>> {
+        let _running = self
+            .running
+            .try_lock()
+            .map_err(|_| anyhow!("A benchmark is already running"))?;
+
+        let model_ids = if model_ids.is_empty() {
+            let selected = self
+                .transcription_manager
+                .get_current_model()
+                .unwrap_or_else(|| self.host.settings().selected_model);
+            if selected.is_empty() {
+                return Err(anyhow!("No model selected"));
+            }
+            vec![selected]
+        } else {
+            model_ids.to_vec()
+        };
+
+        // Check every model up front rather than failing halfway through
+        for model_id in &model_ids {
+            let info = self
+                .model_manager
+                .get_model_info(model_id)
+                .ok_or_else(|| anyhow!("Model not found: {}", model_id))?;
+            if !info.is_downloaded {
+                return Err(anyhow!("Model not downloaded: {}", model_id));
+            }
+        }
+
+        let audio = self.load_clip()?;
+        let previous_model = self.transcription_manager.get_current_model();
+
+        let mut results = Vec::with_capacity(model_ids.len());
+        let mut outcome = Ok(());
+        for model_id in &model_ids {
+            match self.run_model(model_id, &audio) {
+                Ok(result) => {
+                    info!(
+                        "Benchmarked {}: load {}ms, RTF {:.3}",
+                        model_id, result.load_ms, result.real_time_factor
+                    );
+                    results.push(result);
+                }
+                Err(e) => {
+                    outcome = Err(anyhow!("Benchmark of {} failed: {}", model_id, e));
+                    break;
+                }
+            }
+        }
+
+        let restored = match &previous_model {
+            Some(model_id) => self.transcription_manager.load_model(model_id),
+            None => self.transcription_manager.unload_model(),
+        };
+        if let Err(e) = restored {
+            warn!("Failed to restore model after benchmark: {}", e);
+        }
+
+        if !results.is_empty() {
+            if let Err(e) = self.append_history(&results) {
+                warn!("Failed to save benchmark history: {}", e);
+            }
+        }
+
+        outcome.map(|_| results)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.try_lock().is_err()
+    }
+
+    /// Previous results, oldest first.
+    pub fn history(&self) -> Vec<BenchmarkResult> {
+        fs::read_to_string(&self.history_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn run_model(&self, model_id: &str, audio: &[f32]) -> Result<BenchmarkResult> {
+        let tm = &self.transcription_manager;
+        let info = self
+            .model_manager
+            .get_model_info(model_id)
+            .ok_or_else(|| anyhow!("Model not found: {}", model_id))?;
+
+        // Start from nothing loaded so the memory delta covers this model only
+        tm.unload_model()?;
+        let memory_before = resident_memory();
+        let load_start = Instant::now();
+        tm.load_model(model_id)?;
+        let load_ms = load_start.elapsed().as_millis() as u64;
+        let memory_after_load = resident_memory();
+
+        // Warm-up run, so one-off setup like GPU kernel compilation is not timed
+        tm.transcribe(audio.to_vec())?;
+        // The model may be unloaded after each transcription, depending on settings
+        if !tm.is_model_loaded() {
+            tm.load_model(model_id)?;
+        }
+
+        let transcribe_start = Instant::now();
+        let text = tm.transcribe(audio.to_vec())?;
+        let transcribe_ms = transcribe_start.elapsed().as_millis() as u64;
+
+        let audio_secs = audio.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+        Ok(BenchmarkResult {
+            model_id: model_id.to_string(),
+            model_name: info.name,
+            engine: format!("{:?}", info.engine_type),
+            audio_secs,
+            load_ms,
+            transcribe_ms,
+            real_time_factor: transcribe_ms as f32 / 1000.0 / audio_secs,
+            model_memory_bytes: memory_before
+                .zip(memory_after_load)
+                .map(|(before, after)| after.saturating_sub(before)),
+            process_memory_bytes: resident_memory(),
+            text,
+            timestamp: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Read the reference clip as 16 kHz mono samples.
+    fn load_clip(&self) -> Result<Vec<f32>> {
+        let mut reader = hound::WavReader::open(&self.clip_path).map_err(|e| {
+            anyhow!(
+                "Failed to open reference clip {}: {}",
+                self.clip_path.display(),
+                e
+            )
+        })?;
+        let spec = reader.spec();
+        if spec.sample_rate != WHISPER_SAMPLE_RATE
+            || spec.channels != 1
+            || spec.bits_per_sample != 16
+        {
+            return Err(anyhow!("Reference clip must be 16 kHz mono 16-bit PCM"));
+        }
+        Ok(reader
+            .samples::<i16>()
+            .map(|s| s.map(|s| s as f32 / i16::MAX as f32))
+            .collect::<Result<_, _>>()?)
+    }
+
+    fn append_history(&self, results: &[BenchmarkResult]) -> Result<()> {
+        let mut history = self.history();
+        history.extend_from_slice(results);
+        let excess = history.len().saturating_sub(MAX_HISTORY);
+        history.drain(..excess);
+        fs::write(&self.history_path, serde_json::to_string_pretty(&history)?)?;
+        Ok(())
+    }
+}
+
+/// Resident set size of this process in bytes.
+fn resident_memory() -> Option<u64> {
+    memory_stats::memory_stats().map(|stats| stats.physical_mem as u64)
+}
@@ -0,0 +1,135 @@
+//! Low-resolution previews of a video with its subtitles burned in, so the
+//! caption timing can be checked by eye before the SRT is exported.
+//!
+//! ffmpeg scales the video down, draws the cues with its `subtitles` filter
+//! (libass) and encodes H.264 with AAC audio into an MP4 that browsers play.
+//! The cues and the output live in a temporary directory that ffmpeg runs
+//! in, so the filter only ever sees a plain relative file name and no path
+//! needs escaping for the filtergraph.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::ffmpeg::{self, FfmpegError};
+
+/// Height of the preview in pixels unless requested otherwise.
+pub const DEFAULT_HEIGHT: u32 = 360;
+
+pub struct PreviewOptions {
+    /// Output height; the width follows the aspect ratio.
+    pub height: u32,
+    /// Where the preview starts in the video, in seconds.
+    pub start: f64,
+    /// How much of the video is rendered, in seconds; all of it when unset.
+    pub duration: Option<f64>,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        Self {
+            height: DEFAULT_HEIGHT,
+            start: 0.0,
+            duration: None,
+        }
+    }
+}
+
+/// Render `video` with `captions`, an SRT or WebVTT document, burned in.
+/// Returns the MP4.
+pub fn render(
+    video: &Path,
+    captions: &str,
+    options: &PreviewOptions,
+) -> Result<Vec<u8>, FfmpegError> {
+    let dir = tempfile::tempdir()
+        .map_err(|e| format!("Failed to create a directory for the preview: {}", e))?;
+    let captions_name = if captions.trim_start().starts_with("WEBVTT") {
+        "captions.vtt"
+    } else {
+        "captions.srt"
+    };
+    std::fs::write(dir.path().join(captions_name), captions)
+        .map_err(|e| format!("Failed to write the captions: {}", e))?;
+
+    let mut cmd = ffmpeg::command()?;
+    cmd.current_dir(dir.path())
+        .args(["-loglevel", "error", "-i"])
+        .arg(video);
+    // Seeking after the input keeps the original timestamps, which the
+    // cues are timed against
+    if options.start > 0.0 {
+        cmd.args(["-ss", &options.start.to_string()]);
+    }
+    if let Some(duration) = options.duration {
+        cmd.args(["-t", &duration.to_string()]);
+    }
+    cmd.args([
+        "-vf",
+        &format!("scale=-2:{},subtitles={}", options.height, captions_name),
+        "-c:v",
+        "libx264",
+        "-preset",
+        "veryfast",
+        "-crf",
+        "30",
+        "-c:a",
+        "aac",
+        "-b:a",
+        "64k",
+        "-movflags",
+        "+faststart",
+        "-y",
+        "preview.mp4",
+    ])
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped());
+
+    // Hide console window on Windows
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let mut child = cmd.spawn().map_err(|e| {
+        format!(
+            "Failed to start ffmpeg at {}: {}",
+            cmd.get_program().to_string_lossy(),
+            e
+        )
+    })?;
+    if let Err(e) = ffmpeg::attach(&child) {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(e.into());
+    }
+    let stderr = child.stderr.take();
+    let stderr_thread = std::thread::spawn(move || {
+        let mut message = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut message);
+        }
+        message
+    });
+
+    let child = Arc::new(Mutex::new(child));
+    let watchdog = ffmpeg::watchdog(&child);
+    let status = ffmpeg::wait(&child);
+    let timed_out = watchdog.finish();
+    let stderr = stderr_thread.join().unwrap_or_default();
+
+    timed_out?;
+    let status = status.map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+    if !status.success() {
+        return Err(format!("ffmpeg exited with error: {}", stderr.trim()).into());
+    }
+    let preview = std::fs::read(dir.path().join("preview.mp4"))
+        .map_err(|e| format!("Failed to read the preview: {}", e))?;
+    if preview.is_empty() {
+        return Err("ffmpeg produced no output".to_string().into());
+    }
+    Ok(preview)
+}
@@ -0,0 +1,335 @@
+//! Topical chapters of long transcripts, e.g. for podcast show notes.
+//!
+//! The segments are grouped into blocks of about half a minute, and each
+//! block is embedded as a TF-IDF vector of its words. A chapter starts
+//! where the vocabulary before and after a block boundary shifts the most,
+//! i.e. where the cosine similarity of the surrounding windows dips deepest
+//! (TextTiling). Headings are the most distinctive words of each chapter,
+//! or titles written by the LLM when the caller asks for them.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Length of the blocks the transcript is compared in.
+const BLOCK_SECONDS: f32 = 30.0;
+
+/// Blocks on each side of a boundary that are compared.
+const WINDOW_BLOCKS: usize = 3;
+
+/// Chapters are at least this long; shorter transcripts are one chapter.
+const MIN_CHAPTER_SECONDS: f32 = 120.0;
+
+/// Words shorter than this are mostly function words and ignored.
+const MIN_WORD_CHARS: usize = 4;
+
+/// Keywords in a heading without an LLM.
+const TITLE_KEYWORDS: usize = 3;
+
+/// A segment to split. Times are in seconds.
+pub struct ChapterSegment<'a> {
+    pub start: f32,
+    pub end: f32,
+    pub text: &'a str,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct Chapter {
+    pub start: f32,
+    pub end: f32,
+    /// Generated heading, e.g. "Hiring, budget, roadmap".
+    pub title: String,
+}
+
+impl Chapter {
+    /// Text of the `segments` starting within the chapter.
+    pub fn text(&self, segments: &[ChapterSegment]) -> String {
+        segments
+            .iter()
+            .filter(|seg| seg.start >= self.start && seg.start < self.end)
+            .map(|seg| seg.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Split `segments`, sorted by start, into chapters with keyword headings.
+/// Empty without segments.
+pub fn split(segments: &[ChapterSegment]) -> Vec<Chapter> {
+    let blocks = blocks(segments);
+    let (Some(first), Some(last)) = (blocks.first(), blocks.last()) else {
+        return Vec::new();
+    };
+    let (start, end) = (first.start, last.end);
+
+    let idf = inverse_document_frequency(&blocks);
+    let vectors: Vec<Vector> = blocks
+        .iter()
+        .map(|block| weigh(&block.counts, &idf))
+        .collect();
+    let mut boundaries = boundaries(&blocks, &vectors, start, end);
+    boundaries.sort_unstable();
+
+    // Block ranges of the chapters
+    let mut edges = vec![0];
+    edges.extend(&boundaries);
+    edges.push(blocks.len());
+    edges
+        .windows(2)
+        .enumerate()
+        .map(|(index, range)| {
+            let blocks = &blocks[range[0]..range[1]];
+            Chapter {
+                start: blocks[0].start,
+                end: blocks[blocks.len() - 1].end,
+                title: keyword_title(blocks, &idf)
+                    .unwrap_or_else(|| format!("Chapter {}", index + 1)),
+            }
+        })
+        .collect()
+}
+
+/// Consecutive segments spanning about `BLOCK_SECONDS`, as word counts.
+struct Block {
+    start: f32,
+    end: f32,
+    counts: HashMap<String, f32>,
+    /// Words in order of first use, to break ties between keywords.
+    order: Vec<String>,
+}
+
+fn blocks(segments: &[ChapterSegment]) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    for seg in segments {
+        let block = match blocks.last_mut() {
+            Some(block) if seg.start - block.start < BLOCK_SECONDS => block,
+            _ => {
+                blocks.push(Block {
+                    start: seg.start,
+                    end: seg.end,
+                    counts: HashMap::new(),
+                    order: Vec::new(),
+                });
+                blocks.last_mut().unwrap()
+            }
+        };
+        block.end = block.end.max(seg.end);
+        for word in words(seg.text) {
+            let count = block.counts.entry(word.clone()).or_insert(0.0);
+            if *count == 0.0 {
+                block.order.push(word);
+            }
+            *count += 1.0;
+        }
+    }
+    blocks
+}
+
+/// Lowercase words of `text` that are long enough to carry the topic.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_WORD_CHARS)
+        .filter(|word| !word.chars().all(|c| c.is_numeric()))
+        .map(str::to_lowercase)
+}
+
+type Vector = HashMap<String, f32>;
+
+/// How rare each word is across the blocks; words used everywhere weigh
+/// nothing.
+fn inverse_document_frequency(blocks: &[Block]) -> HashMap<String, f32> {
+    let mut documents: HashMap<&str, usize> = HashMap::new();
+    for block in blocks {
+        for word in block.counts.keys() {
+            *documents.entry(word).or_default() += 1;
+        }
+    }
+    let total = blocks.len() as f32;
+    documents
+        .into_iter()
+        .map(|(word, count)| (word.to_string(), (total / count as f32).ln()))
+        .collect()
+}
+
+fn weigh(counts: &HashMap<String, f32>, idf: &HashMap<String, f32>) -> Vector {
+    counts
+        .iter()
+        .map(|(word, count)| (word.clone(), count * idf.get(word).copied().unwrap_or(0.0)))
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect()
+}
+
+fn cosine(a: &Vector, b: &Vector) -> f32 {
+    let dot: f32 = a
+        .iter()
+        .filter_map(|(word, x)| b.get(word).map(|y| x * y))
+        .sum();
+    let norm = |v: &Vector| v.values().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms > 0.0 {
+        dot / norms
+    } else {
+        0.0
+    }
+}
+
+fn sum<'a>(vectors: impl Iterator<Item = &'a Vector>) -> Vector {
+    let mut total = Vector::new();
+    for vector in vectors {
+        for (word, weight) in vector {
+            *total.entry(word.clone()).or_default() += weight;
+        }
+    }
+    total
+}
+
+/// Indexes of the blocks that start a new chapter, deepest topic shifts
+/// first, keeping chapters at least `MIN_CHAPTER_SECONDS` long.
+fn boundaries(blocks: &[Block], vectors: &[Vector], start: f32, end: f32) -> Vec<usize> {
+    if blocks.len() < 2 || end - start < 2.0 * MIN_CHAPTER_SECONDS {
+        return Vec::new();
+    }
+
+    // Similarity across the gap before each block but the first
+    let scores: Vec<f32> = (1..blocks.len())
+        .map(|gap| {
+            let before = sum(vectors[gap.saturating_sub(WINDOW_BLOCKS)..gap].iter());
+            let after = sum(vectors[gap..(gap + WINDOW_BLOCKS).min(vectors.len())].iter());
+            cosine(&before, &after)
+        })
+        .collect();
+    let depths = depths(&scores);
+    let mean = depths.iter().sum::<f32>() / depths.len() as f32;
+
+    let mut candidates: Vec<(usize, f32)> = depths
+        .into_iter()
+        .enumerate()
+        .map(|(index, depth)| (index + 1, depth))
+        .filter(|&(_, depth)| depth > 0.0 && depth >= mean)
+        .collect();
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut chosen: Vec<usize> = Vec::new();
+    for (block, _) in candidates {
+        let at = blocks[block].start;
+        let fits = at - start >= MIN_CHAPTER_SECONDS
+            && end - at >= MIN_CHAPTER_SECONDS
+            && chosen
+                .iter()
+                .all(|&other| (blocks[other].start - at).abs() >= MIN_CHAPTER_SECONDS);
+        if fits {
+            chosen.push(block);
+        }
+    }
+    chosen
+}
+
+/// How deep each score lies below the peaks on either side of it.
+fn depths(scores: &[f32]) -> Vec<f32> {
+    (0..scores.len())
+        .map(|index| {
+            let score = scores[index];
+            let peak = |iter: &mut dyn Iterator<Item = &f32>| {
+                let mut peak = score;
+                for &value in iter {
+                    if value < peak {
+                        break;
+                    }
+                    peak = value;
+                }
+                peak
+            };
+            let left = peak(&mut scores[..index].iter().rev());
+            let right = peak(&mut scores[index + 1..].iter());
+            (left - score) + (right - score)
+        })
+        .collect()
+}
+
+/// The most distinctive words of `blocks`, e.g. "Hiring, budget, roadmap".
+fn keyword_title(blocks: &[Block], idf: &HashMap<String, f32>) -> Option<String> {
+    let counts = sum(blocks.iter().map(|block| &block.counts));
+    let weights = weigh(&counts, idf);
+    let mut order: Vec<&String> = Vec::new();
+    for word in blocks.iter().flat_map(|block| &block.order) {
+        if weights.contains_key(word) && !order.contains(&word) {
+            order.push(word);
+        }
+    }
+    // Stable, so equally weighted words keep the order they came up in
+    order.sort_by(|a, b| weights[*b].total_cmp(&weights[*a]));
+
+    let mut title = order
+        .into_iter()
+        .take(TITLE_KEYWORDS)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let first = title.chars().next()?;
+    title.replace_range(..first.len_utf8(), &first.to_uppercase().to_string());
+    Some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A segment every 10 seconds, cycling through `sentences`.
+    fn talk(start: f32, minutes: f32, sentences: &[&'static str]) -> Vec<ChapterSegment<'static>> {
+        let count = (minutes * 6.0) as usize;
+        (0..count)
+            .map(|index| ChapterSegment {
+                start: start + index as f32 * 10.0,
+                end: start + index as f32 * 10.0 + 9.0,
+                text: sentences[index % sentences.len()],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split_topics() {
+        let mut segments = talk(
+            0.0,
+            5.0,
+            &[
+                "Welcome back everyone, today we talk about hiring",
+                "Hiring engineers takes months, interviews are slow",
+                "Our hiring pipeline needs better interviews",
+            ],
+        );
+        segments.extend(talk(
+            300.0,
+            5.0,
+            &[
+                "Now the budget for next quarter",
+                "The budget has room for marketing spend",
+                "Marketing wants more budget this quarter",
+            ],
+        ));
+        let chapters = split(&segments);
+
+        let [hiring, budget] = chapters.as_slice() else {
+            panic!("expected two chapters, got {:?}", chapters);
+        };
+        assert_eq!(hiring.start, 0.0);
+        assert_eq!(hiring.end, 299.0);
+        assert!(hiring.title.starts_with("Hiring"), "{}", hiring.title);
+        assert_eq!(budget.start, 300.0);
+        assert_eq!(budget.end, 599.0);
+        assert!(budget.title.starts_with("Budget"), "{}", budget.title);
+        assert!(budget.text(&segments).starts_with("Now the budget"));
+    }
+
+    #[test]
+    fn test_split_short() {
+        let segments = talk(0.0, 3.0, &["Hiring engineers", "Budget for marketing"]);
+        let chapters = split(&segments);
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].start, 0.0);
+        assert_eq!(chapters[0].end, 179.0);
+
+        assert!(split(&[]).is_empty());
+    }
+}
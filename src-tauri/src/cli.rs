@@ -23,7 +23,47 @@ pub struct CliArgs {
     #[arg(long)]
     pub cancel: bool,
 
+    /// Benchmark models on a reference clip, print the results as JSON and
+    /// exit. Takes comma-separated model ids; the selected model by default
+    #[arg(long, value_name = "MODELS", num_args = 0..=1, default_missing_value = "")]
+    pub benchmark: Option<String>,
+
+    /// Run only the REST API, without windows, tray or a display server.
+    /// Data directory: HANDY_DATA_DIR (defaults to the app's)
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Serve MCP tools (transcribe_file, transcribe_url, list_models) over
+    /// stdin/stdout for LLM clients. Runs headless
+    #[arg(long)]
+    pub mcp: bool,
+
+    /// Create a REST API key with this name, print it and exit. The key is
+    /// shown only once
+    #[arg(long, value_name = "NAME")]
+    pub create_api_key: Option<String>,
+
+    /// Comma-separated scopes for --create-api-key: transcribe, models,
+    /// history, admin. Defaults to transcribe
+    #[arg(long, value_name = "SCOPES", requires = "create_api_key")]
+    pub scopes: Option<String>,
+
+    /// Print the REST API keys with their scopes and usage as JSON and exit
+    #[arg(long)]
+    pub list_api_keys: bool,
+
+    /// Revoke the REST API key with this id and exit
+    #[arg(long, value_name = "ID")]
+    pub revoke_api_key: Option<i64>,
+
     /// Enable debug mode with verbose logging
     #[arg(long)]
     pub debug: bool,
 }
+
+impl CliArgs {
+    /// Whether the arguments manage API keys; that runs headless.
+    pub fn manages_api_keys(&self) -> bool {
+        self.create_api_key.is_some() || self.list_api_keys || self.revoke_api_key.is_some()
+    }
+}
@@ -0,0 +1,99 @@
+use crate::api::ApiServer;
+use crate::settings::{get_settings, write_settings};
+use serde::Serialize;
+use specta::Type;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+#[derive(Serialize, Type)]
+pub struct ApiServerStatus {
+    is_running: bool,
+    port: Option<u16>,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_api_server_status(
+    api_server: State<'_, Arc<ApiServer>>,
+) -> Result<ApiServerStatus, String> {
+    let port = api_server.running_port().await;
+    Ok(ApiServerStatus {
+        is_running: port.is_some(),
+        port,
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn start_api_server(
+    app: AppHandle,
+    api_server: State<'_, Arc<ApiServer>>,
+) -> Result<(), String> {
+    let port = get_settings(&app).api_server_port;
+    api_server.start(port).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_api_server(api_server: State<'_, Arc<ApiServer>>) -> Result<(), String> {
+    api_server.stop().await;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn restart_api_server(
+    app: AppHandle,
+    api_server: State<'_, Arc<ApiServer>>,
+) -> Result<(), String> {
+    let port = get_settings(&app).api_server_port;
+    api_server.restart(port).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn change_api_server_enabled_setting(
+    app: AppHandle,
+    api_server: State<'_, Arc<ApiServer>>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.api_server_enabled = enabled;
+    let port = settings.api_server_port;
+    write_settings(&app, settings);
+
+    // Apply change immediately
+    if enabled {
+        if api_server.running_port().await.is_none() {
+            api_server.start(port).await?;
+        }
+    } else {
+        api_server.stop().await;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn change_api_server_port_setting(
+    app: AppHandle,
+    api_server: State<'_, Arc<ApiServer>>,
+    port: u16,
+) -> Result<(), String> {
+    if port == 0 {
+        return Err("Port must be between 1 and 65535".to_string());
+    }
+
+    let mut settings = get_settings(&app);
+    settings.api_server_port = port;
+    let enabled = settings.api_server_enabled;
+    write_settings(&app, settings);
+
+    // Rebind on the new port if the server is running
+    if enabled && api_server.running_port().await != Some(port) {
+        api_server.restart(port).await?;
+    }
+
+    Ok(())
+}
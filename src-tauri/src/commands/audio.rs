@@ -1,7 +1,8 @@
 use crate::audio_feedback;
-use crate::audio_toolkit::audio::{list_input_devices, list_output_devices};
-use crate::managers::audio::{AudioRecordingManager, MicrophoneMode};
-use crate::settings::{get_settings, write_settings};
+use crate::audio_toolkit::audio::{list_input_devices, list_output_devices, loopback};
+use crate::managers::audio::{microphone_mode, AudioRecordingManager};
+use crate::managers::wake_word::WakeWordManager;
+use crate::settings::{get_settings, write_settings, AppSettings, RecordingSource};
 use log::warn;
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -41,20 +42,61 @@ pub fn update_microphone_mode(app: AppHandle, always_on: bool) -> Result<(), Str
     // Update settings
     let mut settings = get_settings(&app);
     settings.always_on_microphone = always_on;
+    let new_mode = microphone_mode(&settings);
     write_settings(&app, settings);
 
     // Update the audio manager mode
     let rm = app.state::<Arc<AudioRecordingManager>>();
-    let new_mode = if always_on {
-        MicrophoneMode::AlwaysOn
-    } else {
-        MicrophoneMode::OnDemand
-    };
+    rm.update_mode(new_mode)
+        .map_err(|e| format!("Failed to update microphone mode: {}", e))
+}
 
+/// Load the wake word for `settings`, then save them and open or close the
+/// microphone to match. Nothing is saved if the wake word fails to load.
+async fn apply_wake_word_settings(app: &AppHandle, settings: AppSettings) -> Result<(), String> {
+    let wake_word = app.state::<Arc<WakeWordManager>>().inner().clone();
+    wake_word.configure(&settings).await?;
+
+    let new_mode = microphone_mode(&settings);
+    write_settings(app, settings);
+    let rm = app.state::<Arc<AudioRecordingManager>>();
     rm.update_mode(new_mode)
         .map_err(|e| format!("Failed to update microphone mode: {}", e))
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn change_wake_word_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.wake_word_enabled = enabled;
+    apply_wake_word_settings(&app, settings).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn change_wake_word_phrase_setting(
+    app: AppHandle,
+    wake_word: String,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.wake_word = wake_word;
+    apply_wake_word_settings(&app, settings).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn change_wake_word_threshold_setting(
+    app: AppHandle,
+    threshold: f32,
+) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("Threshold must be between 0 and 1".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.wake_word_threshold = threshold;
+    apply_wake_word_settings(&app, settings).await
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn get_microphone_mode(app: AppHandle) -> Result<bool, String> {
@@ -111,6 +153,70 @@ pub fn get_selected_microphone(app: AppHandle) -> Result<String, String> {
         .unwrap_or_else(|| "default".to_string()))
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn update_recording_source(app: AppHandle, source: RecordingSource) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.recording_source = source;
+    write_settings(&app, settings);
+
+    // Reopen the stream on the new source if it is currently active
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    rm.update_selected_device()
+        .map_err(|e| format!("Failed to switch recording source: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_available_loopback_devices() -> Result<Vec<AudioDevice>, String> {
+    let devices = loopback::list_loopback_devices()
+        .map_err(|e| format!("Failed to list system audio devices: {}", e))?;
+
+    let mut result = vec![AudioDevice {
+        index: "default".to_string(),
+        name: "Default".to_string(),
+        is_default: true,
+    }];
+
+    result.extend(devices.into_iter().map(|d| AudioDevice {
+        index: d.index,
+        name: d.name,
+        is_default: false,
+    }));
+
+    Ok(result)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_selected_loopback_device(app: AppHandle, device_name: String) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.selected_loopback_device = if device_name == "default" {
+        None
+    } else {
+        Some(device_name)
+    };
+    let source = settings.recording_source;
+    write_settings(&app, settings);
+
+    if source == RecordingSource::SystemAudio {
+        let rm = app.state::<Arc<AudioRecordingManager>>();
+        rm.update_selected_device()
+            .map_err(|e| format!("Failed to update selected device: {}", e))?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_selected_loopback_device(app: AppHandle) -> Result<String, String> {
+    let settings = get_settings(&app);
+    Ok(settings
+        .selected_loopback_device
+        .unwrap_or_else(|| "default".to_string()))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn get_available_output_devices() -> Result<Vec<AudioDevice>, String> {
@@ -1,8 +1,10 @@
+use crate::managers::catalog::{self, ModelCatalog};
 use crate::managers::model::{ModelInfo, ModelManager};
 use crate::managers::transcription::TranscriptionManager;
 use crate::settings::{get_settings, write_settings};
+use log::{info, warn};
 use std::sync::Arc;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, State};
 
 #[tauri::command]
 #[specta::specta]
@@ -21,6 +23,14 @@ pub async fn get_model_info(
     Ok(model_manager.get_model_info(&model_id))
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn get_model_catalog(
+    model_manager: State<'_, Arc<ModelManager>>,
+) -> Result<ModelCatalog, String> {
+    Ok(model_manager.catalog())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn download_model(
@@ -75,19 +85,60 @@ pub async fn set_active_model(
         return Err(format!("Model not downloaded: {}", model_id));
     }
 
+    // Loading still goes ahead, the estimate may be pessimistic
+    if let Some(warning) = catalog::check_fit(&model_info) {
+        warn!("{}", warning);
+        let _ = app_handle.emit("model-fit-warning", &warning);
+    }
+
     // Load the model in the transcription manager
     transcription_manager
         .load_model(&model_id)
         .map_err(|e| e.to_string())?;
 
-    // Update settings
+    // Update settings. Choosing another model than the recommended one
+    // overrides the auto mode.
     let mut settings = get_settings(&app_handle);
     settings.selected_model = model_id.clone();
+    if settings.auto_select_model && !model_info.is_recommended {
+        info!("Model {} chosen manually, leaving auto mode", model_id);
+        settings.auto_select_model = false;
+    }
     write_settings(&app_handle, settings);
 
     Ok(())
 }
 
+/// Turn the auto model mode on or off. Turning it on selects and loads the
+/// best downloaded model for this machine.
+#[tauri::command]
+#[specta::specta]
+pub async fn change_auto_select_model_setting(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app_handle);
+    settings.auto_select_model = enabled;
+    let previous_model = settings.selected_model.clone();
+    write_settings(&app_handle, settings);
+    if !enabled {
+        return Ok(());
+    }
+
+    model_manager
+        .auto_select_model_if_needed()
+        .map_err(|e| e.to_string())?;
+    let selected_model = get_settings(&app_handle).selected_model;
+    if selected_model != previous_model && !selected_model.is_empty() {
+        transcription_manager
+            .load_model(&selected_model)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_current_model(app_handle: AppHandle) -> Result<String, String> {
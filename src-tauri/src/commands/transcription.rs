@@ -1,3 +1,4 @@
+use crate::managers::hardware::ModelMemory;
 use crate::managers::transcription::TranscriptionManager;
 use crate::settings::{get_settings, write_settings, ModelUnloadTimeout};
 use serde::Serialize;
@@ -8,6 +9,8 @@ use tauri::{AppHandle, State};
 pub struct ModelLoadStatus {
     is_loaded: bool,
     current_model: Option<String>,
+    /// Memory taken by each loaded model.
+    memory: Vec<ModelMemory>,
 }
 
 #[tauri::command]
@@ -26,6 +29,7 @@ pub fn get_model_load_status(
     Ok(ModelLoadStatus {
         is_loaded: transcription_manager.is_model_loaded(),
         current_model: transcription_manager.get_current_model(),
+        memory: transcription_manager.model_memory(),
     })
 }
 
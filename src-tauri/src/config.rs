@@ -0,0 +1,656 @@
+//! Deployment configuration for the API server, so containers and other
+//! headless setups don't depend on the GUI settings store.
+//!
+//! Values come from a TOML file and from environment variables, which take
+//! precedence. The file is `HANDY_CONFIG` if set, otherwise `handy.toml` in
+//! the data directory when it exists:
+//!
+//! ```toml
+//! [api]
+//! port = 8720                # HANDY_API_PORT
+//! bind = "127.0.0.1"         # HANDY_API_BIND
+//! auth_token = "secret"      # HANDY_API_TOKEN
+//! max_concurrency = 4        # HANDY_API_MAX_CONCURRENCY
+//! max_upload_mb = 100        # HANDY_API_MAX_UPLOAD_MB
+//! max_duration_secs = 1800   # HANDY_API_MAX_DURATION_SECS
+//! decode_buffer_mb = 256     # HANDY_API_DECODE_BUFFER_MB
+//! cache_entries = 100        # HANDY_API_CACHE_ENTRIES
+//! cache_ttl_secs = 3600      # HANDY_API_CACHE_TTL_SECS
+//! upload_ttl_secs = 86400    # HANDY_API_UPLOAD_TTL_SECS
+//! docs = true                # HANDY_API_DOCS
+//!
+//! [models]
+//! dir = "/models"            # HANDY_MODELS_DIR
+//! default = "parakeet-tdt-0.6b-v3"  # HANDY_MODEL
+//! engine = "parakeet"        # HANDY_ENGINE
+//! whisper_threads = 8        # HANDY_WHISPER_THREADS
+//!
+//! [llm]
+//! url = "http://localhost:8080/v1"  # HANDY_LLM_URL
+//! api_key = "sk-..."         # HANDY_LLM_API_KEY
+//! model = "llama-3.2-3b"     # HANDY_LLM_MODEL
+//!
+//! [logging]
+//! transcripts = false        # HANDY_LOG_TRANSCRIPTS
+//!
+//! [history]
+//! passphrase = "..."         # HANDY_HISTORY_PASSPHRASE
+//! keychain = false           # HANDY_HISTORY_KEYCHAIN
+//!
+//! [ffmpeg]
+//! path = "/opt/ffmpeg/bin/ffmpeg"  # HANDY_FFMPEG_PATH
+//! nice = 10                  # HANDY_FFMPEG_NICE
+//! cpu_affinity = [0, 1]      # HANDY_FFMPEG_CPU_AFFINITY="0,1"
+//! memory_mb = 2048           # HANDY_FFMPEG_MEMORY_MB
+//! timeout_secs = 600         # HANDY_FFMPEG_TIMEOUT_SECS
+//! max_output_mb = 2048       # HANDY_FFMPEG_MAX_OUTPUT_MB
+//!
+//! [output]
+//! paste = true               # HANDY_OUTPUT_PASTE
+//! file = "~/Notes/{date}.md"  # HANDY_OUTPUT_FILE
+//! pipe = "/tmp/handy.fifo"   # HANDY_OUTPUT_PIPE
+//! stdout = false             # HANDY_OUTPUT_STDOUT
+//! webhook = "http://localhost:5678/dictation"  # HANDY_OUTPUT_WEBHOOK
+//! ```
+//!
+//! Unset values fall back to the app settings (port, selected model,
+//! post-processing provider) or the built-in defaults. Translation and alignment keep their own
+//! `HANDY_API_TRANSLATION_*` / `HANDY_API_ALIGN_MODEL` variables.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+/// Name of the config file looked up in the data directory.
+pub const CONFIG_FILE: &str = "handy.toml";
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub api: ApiConfig,
+    pub models: ModelsConfig,
+    pub llm: LlmConfig,
+    pub logging: LoggingConfig,
+    pub history: HistoryConfig,
+    pub ffmpeg: FfmpegConfig,
+    pub output: OutputConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    /// Port to listen on; the port from settings when unset.
+    pub port: Option<u16>,
+    /// Address to bind to; all interfaces (0.0.0.0) when unset.
+    pub bind: Option<IpAddr>,
+    /// Bearer token required on every endpoint except /health.
+    pub auth_token: Option<String>,
+    /// Transcription requests (/transcribe, /detect-language) handled at
+    /// once, including those waiting for the engine. Further requests get
+    /// 503. 0 or unset means no limit, or the value set with `PUT /config`.
+    pub max_concurrency: Option<usize>,
+    pub max_upload_mb: Option<usize>,
+    pub max_duration_secs: Option<u64>,
+    /// Decoded audio kept in memory per request. Longer audio is spilled to
+    /// a temporary file and transcribed window by window; 256 MB (about 70
+    /// minutes) when unset, 0 keeps everything in memory.
+    pub decode_buffer_mb: Option<usize>,
+    /// Transcription results kept to answer repeated uploads of the same
+    /// audio with the same options. 0 or unset disables the cache.
+    pub cache_entries: Option<usize>,
+    /// How long cached results are kept; one hour when unset.
+    pub cache_ttl_secs: Option<u64>,
+    /// How long a resumable upload is kept after its last chunk; 24 hours
+    /// when unset.
+    pub upload_ttl_secs: Option<u64>,
+    /// Serve Swagger UI at /docs; on when unset. /openapi.json is always
+    /// served.
+    pub docs: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelsConfig {
+    /// Directory holding downloaded models; `models/` in the data directory
+    /// when unset.
+    pub dir: Option<PathBuf>,
+    /// Model id selected at startup.
+    pub default: Option<String>,
+    /// Engine whose first downloaded model is selected at startup when no
+    /// `default` model is given, e.g. "whisper" or "parakeet".
+    pub engine: Option<String>,
+    /// How long the model stays loaded after the last request; overrides
+    /// the `model_unload_timeout` setting.
+    pub keep_alive: Option<KeepAlive>,
+    /// CPU threads for decoding long audio with Whisper, split over
+    /// several windows decoded at once. Unset or 1 decodes in one pass.
+    pub whisper_threads: Option<usize>,
+    /// Model id to transcribe each language with instead of the selected
+    /// model, keyed by language code.
+    pub routes: BTreeMap<String, String>,
+}
+
+/// Idle time before the model is unloaded, written like Ollama's
+/// `keep_alive`: a duration such as "30s", "5m" or "1h" (bare numbers are
+/// seconds), 0 to unload after every request, or -1 to keep it loaded.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "KeepAliveValue")]
+pub enum KeepAlive {
+    Forever,
+    /// `Duration::ZERO` unloads right after each request.
+    Idle(Duration),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeepAliveValue {
+    Seconds(i64),
+    Text(String),
+}
+
+impl TryFrom<KeepAliveValue> for KeepAlive {
+    type Error = String;
+
+    fn try_from(value: KeepAliveValue) -> Result<Self, Self::Error> {
+        match value {
+            KeepAliveValue::Seconds(seconds) => Self::from_str(&seconds.to_string()),
+            KeepAliveValue::Text(text) => Self::from_str(&text),
+        }
+    }
+}
+
+impl FromStr for KeepAlive {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.starts_with('-') || value.eq_ignore_ascii_case("forever") {
+            return Ok(KeepAlive::Forever);
+        }
+        let (number, unit) = value.split_at(value.trim_end_matches(char::is_alphabetic).len());
+        let multiplier = match unit {
+            "" | "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            _ => return Err(format!("unknown unit '{}', expected s, m or h", unit)),
+        };
+        let number: u64 = number
+            .trim()
+            .parse()
+            .map_err(|_| format!("expected a duration like \"5m\", got '{}'", value))?;
+        Ok(KeepAlive::Idle(Duration::from_secs(number * multiplier)))
+    }
+}
+
+/// OpenAI-compatible chat completions API used for `post_process` on
+/// /transcribe.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmConfig {
+    /// Base URL, e.g. `http://localhost:8080/v1` for a llama.cpp server.
+    /// When unset, the post-processing provider selected in the app
+    /// settings is used, if any.
+    pub url: Option<String>,
+    pub api_key: Option<String>,
+    /// Model name; local servers that serve a single model ignore it.
+    pub model: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Log transcripts, post-processed text and audio file names verbatim.
+    /// Off when unset, so logs only show their length.
+    pub transcripts: Option<bool>,
+}
+
+/// Encryption of the stored transcripts and recordings. Existing plaintext
+/// history is encrypted when either option is first set; once encrypted,
+/// the history can only be opened with the same key.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Passphrase the key is derived from.
+    pub passphrase: Option<String>,
+    /// Keep a random key in the OS keychain (Keychain, Credential Manager or
+    /// Secret Service) instead of using a passphrase.
+    pub keychain: Option<bool>,
+}
+
+/// ffmpeg, which decodes formats Symphonia can't, and resource limits so a
+/// runaway decode can't take over the machine.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FfmpegConfig {
+    /// The ffmpeg binary; overrides the `ffmpeg_path` setting. Searched for
+    /// when neither is set.
+    pub path: Option<PathBuf>,
+    /// Scheduling priority from -20 to 19, like `nice`; higher values yield
+    /// to other programs.
+    pub nice: Option<i32>,
+    /// CPU cores ffmpeg may run on, numbered from 0. Ignored on macOS.
+    pub cpu_affinity: Vec<usize>,
+    /// Memory limit; on Unix it caps the address space.
+    pub memory_mb: Option<u64>,
+    /// ffmpeg is killed after running this long; 600 when unset, 0 for no
+    /// limit.
+    pub timeout_secs: Option<u64>,
+    /// Decoded audio read from ffmpeg, at 64 KB per second of mono audio;
+    /// 2048 when unset, 0 for no limit.
+    pub max_output_mb: Option<u64>,
+}
+
+/// Where dictations from the shortcuts and API sessions go besides, or
+/// instead of, the focused app.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// Paste into the focused app; true when unset. Sessions paste when
+    /// they ask to either way.
+    pub paste: Option<bool>,
+    /// Markdown file each dictation is appended to under a timestamp
+    /// heading. `{date}` is replaced with the day, e.g. `Notes/{date}.md`
+    /// for daily notes, and a leading `~` with the home directory.
+    pub file: Option<PathBuf>,
+    /// Named pipe (FIFO) each dictation is written to as a line. Lines wait
+    /// for a reader to open it.
+    pub pipe: Option<PathBuf>,
+    /// Print each dictation as a line on standard output.
+    pub stdout: Option<bool>,
+    /// URL each dictation is POSTed to as JSON.
+    pub webhook: Option<String>,
+}
+
+impl Config {
+    /// Load the config file and apply environment overrides. `data_dir` is
+    /// where `handy.toml` is looked up when HANDY_CONFIG is not set.
+    pub fn load(data_dir: &Path) -> Result<Self> {
+        let mut config = match std::env::var_os("HANDY_CONFIG") {
+            Some(path) => Self::from_file(Path::new(&path))?,
+            None => {
+                let path = data_dir.join(CONFIG_FILE);
+                if path.exists() {
+                    Self::from_file(&path)?
+                } else {
+                    Self::default()
+                }
+            }
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Invalid config in {}", path.display()))
+    }
+
+    /// Override values with the environment variables `var` returns. Empty
+    /// variables are ignored.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+
+        override_parsed(&mut self.api.port, var("HANDY_API_PORT"), "HANDY_API_PORT")?;
+        override_parsed(&mut self.api.bind, var("HANDY_API_BIND"), "HANDY_API_BIND")?;
+        if let Some(token) = var("HANDY_API_TOKEN") {
+            self.api.auth_token = Some(token);
+        }
+        override_parsed(
+            &mut self.api.max_concurrency,
+            var("HANDY_API_MAX_CONCURRENCY"),
+            "HANDY_API_MAX_CONCURRENCY",
+        )?;
+        override_parsed(
+            &mut self.api.max_upload_mb,
+            var("HANDY_API_MAX_UPLOAD_MB"),
+            "HANDY_API_MAX_UPLOAD_MB",
+        )?;
+        override_parsed(
+            &mut self.api.max_duration_secs,
+            var("HANDY_API_MAX_DURATION_SECS"),
+            "HANDY_API_MAX_DURATION_SECS",
+        )?;
+        override_parsed(
+            &mut self.api.decode_buffer_mb,
+            var("HANDY_API_DECODE_BUFFER_MB"),
+            "HANDY_API_DECODE_BUFFER_MB",
+        )?;
+        override_parsed(
+            &mut self.api.cache_entries,
+            var("HANDY_API_CACHE_ENTRIES"),
+            "HANDY_API_CACHE_ENTRIES",
+        )?;
+        override_parsed(
+            &mut self.api.cache_ttl_secs,
+            var("HANDY_API_CACHE_TTL_SECS"),
+            "HANDY_API_CACHE_TTL_SECS",
+        )?;
+        override_parsed(
+            &mut self.api.upload_ttl_secs,
+            var("HANDY_API_UPLOAD_TTL_SECS"),
+            "HANDY_API_UPLOAD_TTL_SECS",
+        )?;
+        override_parsed(&mut self.api.docs, var("HANDY_API_DOCS"), "HANDY_API_DOCS")?;
+
+        if let Some(dir) = var("HANDY_MODELS_DIR") {
+            self.models.dir = Some(PathBuf::from(dir));
+        }
+        if let Some(model) = var("HANDY_MODEL") {
+            self.models.default = Some(model);
+        }
+        if let Some(engine) = var("HANDY_ENGINE") {
+            self.models.engine = Some(engine);
+        }
+        override_parsed(
+            &mut self.models.keep_alive,
+            var("HANDY_KEEP_ALIVE"),
+            "HANDY_KEEP_ALIVE",
+        )?;
+        override_parsed(
+            &mut self.models.whisper_threads,
+            var("HANDY_WHISPER_THREADS"),
+            "HANDY_WHISPER_THREADS",
+        )?;
+        if let Some(routes) = var("HANDY_MODEL_ROUTES") {
+            self.models.routes = parse_routes(&routes)?;
+        }
+
+        if let Some(url) = var("HANDY_LLM_URL") {
+            self.llm.url = Some(url);
+        }
+        if let Some(api_key) = var("HANDY_LLM_API_KEY") {
+            self.llm.api_key = Some(api_key);
+        }
+        if let Some(model) = var("HANDY_LLM_MODEL") {
+            self.llm.model = Some(model);
+        }
+
+        override_parsed(
+            &mut self.logging.transcripts,
+            var("HANDY_LOG_TRANSCRIPTS"),
+            "HANDY_LOG_TRANSCRIPTS",
+        )?;
+
+        if let Some(passphrase) = var("HANDY_HISTORY_PASSPHRASE") {
+            self.history.passphrase = Some(passphrase);
+        }
+        override_parsed(
+            &mut self.history.keychain,
+            var("HANDY_HISTORY_KEYCHAIN"),
+            "HANDY_HISTORY_KEYCHAIN",
+        )?;
+
+        if let Some(path) = var("HANDY_FFMPEG_PATH") {
+            self.ffmpeg.path = Some(PathBuf::from(path));
+        }
+        override_parsed(
+            &mut self.ffmpeg.nice,
+            var("HANDY_FFMPEG_NICE"),
+            "HANDY_FFMPEG_NICE",
+        )?;
+        if let Some(cores) = var("HANDY_FFMPEG_CPU_AFFINITY") {
+            self.ffmpeg.cpu_affinity = parse_cores(&cores)?;
+        }
+        override_parsed(
+            &mut self.ffmpeg.memory_mb,
+            var("HANDY_FFMPEG_MEMORY_MB"),
+            "HANDY_FFMPEG_MEMORY_MB",
+        )?;
+        override_parsed(
+            &mut self.ffmpeg.timeout_secs,
+            var("HANDY_FFMPEG_TIMEOUT_SECS"),
+            "HANDY_FFMPEG_TIMEOUT_SECS",
+        )?;
+        override_parsed(
+            &mut self.ffmpeg.max_output_mb,
+            var("HANDY_FFMPEG_MAX_OUTPUT_MB"),
+            "HANDY_FFMPEG_MAX_OUTPUT_MB",
+        )?;
+
+        override_parsed(
+            &mut self.output.paste,
+            var("HANDY_OUTPUT_PASTE"),
+            "HANDY_OUTPUT_PASTE",
+        )?;
+        if let Some(path) = var("HANDY_OUTPUT_FILE") {
+            self.output.file = Some(PathBuf::from(path));
+        }
+        if let Some(path) = var("HANDY_OUTPUT_PIPE") {
+            self.output.pipe = Some(PathBuf::from(path));
+        }
+        override_parsed(
+            &mut self.output.stdout,
+            var("HANDY_OUTPUT_STDOUT"),
+            "HANDY_OUTPUT_STDOUT",
+        )?;
+        if let Some(url) = var("HANDY_OUTPUT_WEBHOOK") {
+            self.output.webhook = Some(url);
+        }
+        Ok(())
+    }
+}
+
+/// Parse `language=model` pairs separated by commas.
+fn parse_routes(value: &str) -> Result<BTreeMap<String, String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((language, model)) if !language.trim().is_empty() && !model.trim().is_empty() => {
+                Ok((language.trim().to_string(), model.trim().to_string()))
+            }
+            _ => Err(anyhow!(
+                "Invalid HANDY_MODEL_ROUTES entry '{}': expected language=model",
+                pair
+            )),
+        })
+        .collect()
+}
+
+/// Parse CPU core numbers separated by commas.
+fn parse_cores(value: &str) -> Result<Vec<usize>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|core| !core.is_empty())
+        .map(|core| {
+            core.parse()
+                .map_err(|_| anyhow!("Invalid HANDY_FFMPEG_CPU_AFFINITY entry '{}'", core))
+        })
+        .collect()
+}
+
+fn override_parsed<T: FromStr>(
+    field: &mut Option<T>,
+    value: Option<String>,
+    name: &str,
+) -> Result<()>
+where
+    T::Err: std::fmt::Display,
+{
+    if let Some(value) = value {
+        let parsed = value
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("Invalid {} '{}': {}", name, value, e))?;
+        *field = Some(parsed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn parses_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [api]
+            port = 9000
+            bind = "127.0.0.1"
+            auth_token = "secret"
+            max_concurrency = 2
+
+            [models]
+            dir = "/models"
+            default = "small"
+            keep_alive = "10m"
+            whisper_threads = 8
+
+            [models.routes]
+            ja = "sense-voice-int8"
+
+            [llm]
+            url = "http://localhost:8080/v1"
+
+            [logging]
+            transcripts = true
+
+            [history]
+            keychain = true
+
+            [ffmpeg]
+            nice = 10
+            cpu_affinity = [0, 1]
+
+            [output]
+            paste = false
+            file = "notes/{date}.md"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.api.port, Some(9000));
+        assert_eq!(config.api.bind, Some("127.0.0.1".parse().unwrap()));
+        assert_eq!(config.api.auth_token.as_deref(), Some("secret"));
+        assert_eq!(config.api.max_concurrency, Some(2));
+        assert_eq!(config.api.max_upload_mb, None);
+        assert_eq!(config.models.dir, Some(PathBuf::from("/models")));
+        assert_eq!(config.models.default.as_deref(), Some("small"));
+        assert_eq!(
+            config.models.keep_alive,
+            Some(KeepAlive::Idle(Duration::from_secs(600)))
+        );
+        assert_eq!(config.models.whisper_threads, Some(8));
+        assert_eq!(
+            config.models.routes.get("ja").map(String::as_str),
+            Some("sense-voice-int8")
+        );
+        assert_eq!(config.llm.url.as_deref(), Some("http://localhost:8080/v1"));
+        assert_eq!(config.llm.model, None);
+        assert_eq!(config.logging.transcripts, Some(true));
+        assert_eq!(config.history.keychain, Some(true));
+        assert_eq!(config.history.passphrase, None);
+        assert_eq!(config.ffmpeg.nice, Some(10));
+        assert_eq!(config.ffmpeg.cpu_affinity, vec![0, 1]);
+        assert_eq!(config.ffmpeg.memory_mb, None);
+        assert_eq!(config.output.paste, Some(false));
+        assert_eq!(config.output.file, Some(PathBuf::from("notes/{date}.md")));
+        assert_eq!(config.output.webhook, None);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(toml::from_str::<Config>("[api]\nprot = 9000\n").is_err());
+    }
+
+    #[test]
+    fn environment_overrides_file() {
+        let mut config: Config = toml::from_str("[api]\nport = 9000\nbind = \"::1\"\n").unwrap();
+        config
+            .apply_env(env(&[
+                ("HANDY_API_PORT", "9100"),
+                ("HANDY_API_TOKEN", "abc"),
+                ("HANDY_API_BIND", ""),
+                ("HANDY_API_DOCS", "false"),
+                ("HANDY_API_DECODE_BUFFER_MB", "64"),
+                ("HANDY_ENGINE", "parakeet"),
+                ("HANDY_WHISPER_THREADS", "4"),
+                ("HANDY_LLM_MODEL", "qwen2.5"),
+                ("HANDY_MODEL_ROUTES", "ja=sense-voice-int8, en = small"),
+                ("HANDY_LOG_TRANSCRIPTS", "false"),
+                ("HANDY_HISTORY_PASSPHRASE", "correct horse"),
+                ("HANDY_FFMPEG_PATH", "/opt/ffmpeg/bin/ffmpeg"),
+                ("HANDY_FFMPEG_CPU_AFFINITY", "2, 3"),
+                ("HANDY_FFMPEG_MEMORY_MB", "1024"),
+                ("HANDY_FFMPEG_TIMEOUT_SECS", "0"),
+                ("HANDY_OUTPUT_STDOUT", "true"),
+                ("HANDY_OUTPUT_WEBHOOK", "http://localhost:5678/dictation"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.api.port, Some(9100));
+        assert_eq!(config.api.auth_token.as_deref(), Some("abc"));
+        // Empty variables don't clear file values
+        assert_eq!(config.api.bind, Some("::1".parse().unwrap()));
+        assert_eq!(config.api.docs, Some(false));
+        assert_eq!(config.api.decode_buffer_mb, Some(64));
+        assert_eq!(config.models.engine.as_deref(), Some("parakeet"));
+        assert_eq!(config.models.whisper_threads, Some(4));
+        assert_eq!(config.llm.model.as_deref(), Some("qwen2.5"));
+        assert_eq!(config.logging.transcripts, Some(false));
+        assert_eq!(config.history.passphrase.as_deref(), Some("correct horse"));
+        assert_eq!(
+            config.ffmpeg.path,
+            Some(PathBuf::from("/opt/ffmpeg/bin/ffmpeg"))
+        );
+        assert_eq!(config.ffmpeg.cpu_affinity, vec![2, 3]);
+        assert_eq!(config.ffmpeg.memory_mb, Some(1024));
+        assert_eq!(config.ffmpeg.timeout_secs, Some(0));
+        assert_eq!(config.output.stdout, Some(true));
+        assert_eq!(
+            config.output.webhook.as_deref(),
+            Some("http://localhost:5678/dictation")
+        );
+        assert_eq!(config.models.routes.len(), 2);
+        assert_eq!(
+            config.models.routes.get("en").map(String::as_str),
+            Some("small")
+        );
+    }
+
+    #[test]
+    fn parses_keep_alive() {
+        let parse = |value: &str| value.parse::<KeepAlive>();
+        assert_eq!(parse("30s"), Ok(KeepAlive::Idle(Duration::from_secs(30))));
+        assert_eq!(parse("2h"), Ok(KeepAlive::Idle(Duration::from_secs(7200))));
+        assert_eq!(parse("90"), Ok(KeepAlive::Idle(Duration::from_secs(90))));
+        assert_eq!(parse("0"), Ok(KeepAlive::Idle(Duration::ZERO)));
+        assert_eq!(parse("-1"), Ok(KeepAlive::Forever));
+        assert!(parse("5 days").is_err());
+        assert!(parse("m").is_err());
+
+        let config: Config = toml::from_str("[models]\nkeep_alive = -1\n").unwrap();
+        assert_eq!(config.models.keep_alive, Some(KeepAlive::Forever));
+    }
+
+    #[test]
+    fn invalid_environment_value_is_an_error() {
+        let mut config = Config::default();
+        let err = config
+            .apply_env(env(&[("HANDY_API_MAX_CONCURRENCY", "many")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("HANDY_API_MAX_CONCURRENCY"));
+        assert!(config
+            .apply_env(env(&[("HANDY_MODEL_ROUTES", "ja")]))
+            .is_err());
+    }
+}
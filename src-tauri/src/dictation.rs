@@ -0,0 +1,552 @@
+//! Dictation sessions started over the REST API: the app's push-to-talk flow
+//! without the hotkey, for Stream Deck buttons and scripts.
+//!
+//! A session records from the selected microphone, or another named input
+//! device, until it is stopped, then transcribes the recording, saves it in
+//! the history, sends it to the configured outputs and optionally pastes it
+//! like a shortcut would. There is one recorder, so only one session runs at
+//! a time and none while dictating with a shortcut. Headless mode has no
+//! recorder and no sessions.
+//!
+//! A document dictated in many short sessions can name a context: each
+//! session of it is transcribed with the end of the text dictated so far as
+//! the prompt, which keeps the spelling of names and the formatting
+//! consistent from one recording to the next.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{debug, error, info, warn};
+use tauri::{AppHandle, Manager};
+
+use crate::actions::apply_voice_commands;
+use crate::audio_feedback::{play_feedback_sound, SoundType};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::list_input_devices;
+use crate::events::{EventBus, EventKind};
+use crate::host::Host;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::history::{
+    HistoryManager, HistorySegment, HistorySource, TranscriptionRecord,
+};
+use crate::managers::output::OutputRouter;
+use crate::managers::transcription::{
+    TranscribedSegment, TranscriptionManager, TranscriptionOptions,
+};
+use crate::redact::redact;
+use crate::settings::get_settings;
+use crate::tray::{change_tray_icon, TrayIconState};
+use crate::uploads::new_id;
+use crate::utils;
+
+/// Recording binding of API sessions, next to the shortcut bindings.
+const BINDING_ID: &str = "api_session";
+
+/// Sessions left running longer than this are cancelled, so a client that
+/// never stops one doesn't keep the microphone open.
+pub const MAX_SESSION_DURATION: Duration = Duration::from_secs(30 * 60);
+
+/// Characters at the end of a context passed on as the prompt. Whisper
+/// keeps about 220 tokens of prompt, which the custom words share.
+const CONTEXT_TAIL_CHARS: usize = 400;
+
+/// Contexts unused for this long are forgotten.
+const CONTEXT_TTL: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// Contexts kept at most; the least recently used one goes first.
+const MAX_CONTEXTS: usize = 64;
+
+#[derive(Debug)]
+pub enum DictationError {
+    /// Headless mode, which has no recorder.
+    Unavailable,
+    /// A session or shortcut dictation is already recording.
+    Busy,
+    UnknownDevice {
+        name: String,
+        available: Vec<String>,
+    },
+    /// No such session, or it already ended.
+    NotFound,
+    Failed(String),
+}
+
+impl fmt::Display for DictationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DictationError::Unavailable => f.write_str(
+                "Dictation sessions need the desktop app; headless mode has no microphone",
+            ),
+            DictationError::Busy => f.write_str("Already recording"),
+            DictationError::UnknownDevice { name, available } => write!(
+                f,
+                "No input device named '{}'. Available: {}",
+                name,
+                available.join(", ")
+            ),
+            DictationError::NotFound => {
+                f.write_str("No such session; it was stopped, cancelled or expired")
+            }
+            DictationError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+/// The outcome of a stopped session.
+pub struct Dictation {
+    pub text: String,
+    pub segments: Vec<TranscribedSegment>,
+    pub language: Option<String>,
+    pub duration_ms: i64,
+    /// Whether the text was pasted into the focused app.
+    pub pasted: bool,
+}
+
+struct ActiveSession {
+    id: String,
+    paste: bool,
+    /// Name of the context the dictation continues.
+    context: Option<String>,
+    /// Dropped when the session ends, which stops its expiry watchdog.
+    _ended: mpsc::Sender<()>,
+}
+
+pub struct DictationSessions {
+    host: Host,
+    transcription_manager: Arc<TranscriptionManager>,
+    history_manager: Arc<HistoryManager>,
+    events: Arc<EventBus>,
+    active: Mutex<Option<ActiveSession>>,
+    contexts: Mutex<Contexts>,
+}
+
+/// The text dictated so far in a context.
+struct Context {
+    /// Its last `CONTEXT_TAIL_CHARS` or so.
+    tail: String,
+    used: Instant,
+}
+
+/// The contexts by name. Unused ones expire after `CONTEXT_TTL`, and the
+/// least recently used one makes way once there are `MAX_CONTEXTS`.
+#[derive(Default)]
+struct Contexts(HashMap<String, Context>);
+
+impl Contexts {
+    fn remove(&mut self, name: &str) -> bool {
+        self.0.remove(name).is_some()
+    }
+
+    /// The end of the text dictated in context `name` so far.
+    fn tail(&mut self, name: &str, now: Instant) -> Option<String> {
+        self.0
+            .retain(|_, context| now.saturating_duration_since(context.used) < CONTEXT_TTL);
+        self.0.get(name).map(|context| context.tail.clone())
+    }
+
+    /// Append `text` to context `name`, creating it if needed.
+    fn extend(&mut self, name: &str, text: &str, now: Instant) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        if !self.0.contains_key(name) && self.0.len() >= MAX_CONTEXTS {
+            let oldest = self
+                .0
+                .iter()
+                .min_by_key(|(_, context)| context.used)
+                .map(|(name, _)| name.clone());
+            if let Some(oldest) = oldest {
+                self.0.remove(&oldest);
+            }
+        }
+        let context = self.0.entry(name.to_string()).or_insert(Context {
+            tail: String::new(),
+            used: now,
+        });
+        if !context.tail.is_empty() {
+            context.tail.push(' ');
+        }
+        context.tail.push_str(text);
+        context.tail = tail(&context.tail, CONTEXT_TAIL_CHARS).to_string();
+        context.used = now;
+    }
+}
+
+impl DictationSessions {
+    pub fn new(
+        host: Host,
+        transcription_manager: Arc<TranscriptionManager>,
+        history_manager: Arc<HistoryManager>,
+        events: Arc<EventBus>,
+    ) -> Self {
+        Self {
+            host,
+            transcription_manager,
+            history_manager,
+            events,
+            active: Mutex::new(None),
+            contexts: Mutex::new(Contexts::default()),
+        }
+    }
+
+    fn app(&self) -> Result<(AppHandle, Arc<AudioRecordingManager>), DictationError> {
+        let Host::App(app) = &self.host else {
+            return Err(DictationError::Unavailable);
+        };
+        let rm = app
+            .try_state::<Arc<AudioRecordingManager>>()
+            .ok_or(DictationError::Unavailable)?;
+        Ok((app.clone(), Arc::clone(&rm)))
+    }
+
+    /// Start recording from `device`, or the selected microphone, and return
+    /// the session id. With `paste`, the text is pasted when it stops. With
+    /// `context`, it is transcribed as the continuation of that context.
+    pub fn start(
+        self: &Arc<Self>,
+        device: Option<&str>,
+        paste: bool,
+        context: Option<&str>,
+    ) -> Result<String, DictationError> {
+        let (app, rm) = self.app()?;
+        let mut active = self.active.lock().unwrap();
+        if active.is_some() && !rm.is_recording() {
+            // Cancelled from the app, e.g. with the cancel shortcut
+            *active = None;
+        }
+        if active.is_some() || rm.is_recording() {
+            return Err(DictationError::Busy);
+        }
+
+        if let Some(name) = device {
+            let devices = list_input_devices()
+                .map_err(|e| DictationError::Failed(format!("Failed to list devices: {}", e)))?;
+            if !devices.iter().any(|d| d.name == name) {
+                return Err(DictationError::UnknownDevice {
+                    name: name.to_string(),
+                    available: devices.into_iter().map(|d| d.name).collect(),
+                });
+            }
+        }
+
+        self.transcription_manager.initiate_model_load();
+        let started = match device {
+            Some(name) => rm.try_start_recording_from(BINDING_ID, name),
+            None => rm.try_start_recording(BINDING_ID),
+        };
+        started.map_err(|e| match e.as_str() {
+            "Already recording" => DictationError::Busy,
+            _ => DictationError::Failed(format!("Failed to start recording: {}", e)),
+        })?;
+
+        change_tray_icon(&app, TrayIconState::Recording);
+        utils::show_recording_overlay(&app);
+        play_feedback_sound(&app, SoundType::Start);
+        self.events.publish(EventKind::RecordingStarted);
+
+        let id = new_id();
+        info!("Started dictation session {}", id);
+        let (ended, watchdog) = mpsc::channel();
+        *active = Some(ActiveSession {
+            id: id.clone(),
+            paste,
+            context: context.map(str::to_string),
+            _ended: ended,
+        });
+
+        let sessions = Arc::clone(self);
+        let expiring = id.clone();
+        std::thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = watchdog.recv_timeout(MAX_SESSION_DURATION) {
+                if sessions.cancel(&expiring).is_ok() {
+                    warn!(
+                        "Cancelled dictation session {} after {} minutes",
+                        expiring,
+                        MAX_SESSION_DURATION.as_secs() / 60
+                    );
+                }
+            }
+        });
+        Ok(id)
+    }
+
+    /// End session `id`, taking it out of the active slot.
+    fn take(&self, id: &str) -> Result<ActiveSession, DictationError> {
+        let mut active = self.active.lock().unwrap();
+        match active.take() {
+            Some(session) if session.id == id => Ok(session),
+            other => {
+                *active = other;
+                Err(DictationError::NotFound)
+            }
+        }
+    }
+
+    /// Stop recording, transcribe and store the dictation, and paste it if
+    /// the session asked for that.
+    pub async fn stop(&self, id: &str) -> Result<Dictation, DictationError> {
+        let (app, rm) = self.app()?;
+        let session = self.take(id)?;
+
+        utils::show_transcribing_overlay(&app);
+        rm.remove_mute();
+        play_feedback_sound(&app, SoundType::Stop);
+
+        let finish = |app: &AppHandle| {
+            utils::hide_recording_overlay(app);
+            change_tray_icon(app, TrayIconState::Idle);
+        };
+
+        // Cancelled with the cancel shortcut in the meantime
+        let Some(samples) = rm.stop_recording(BINDING_ID) else {
+            finish(&app);
+            return Err(DictationError::NotFound);
+        };
+        let duration_ms = samples.len() as i64 * 1000 / WHISPER_SAMPLE_RATE as i64;
+        self.events.publish(EventKind::RecordingStopped);
+
+        let tm = self.transcription_manager.clone();
+        let audio = samples.clone();
+        let options = TranscriptionOptions {
+            prompt: session
+                .context
+                .as_deref()
+                .and_then(|name| self.contexts.lock().unwrap().tail(name, Instant::now())),
+            ..Default::default()
+        };
+        let transcribe = move || tm.transcribe_with_options(audio, &options);
+        let transcription = tokio::task::spawn_blocking(transcribe)
+            .await
+            .map_err(|e| DictationError::Failed(format!("Transcription task failed: {}", e)))
+            .and_then(|result| {
+                result.map_err(|e| DictationError::Failed(format!("Transcription failed: {}", e)))
+            });
+        let mut transcription = match transcription {
+            Ok(transcription) => transcription,
+            Err(e) => {
+                finish(&app);
+                return Err(e);
+            }
+        };
+        transcription.text = apply_voice_commands(
+            &get_settings(&app),
+            &transcription.text,
+            transcription.language.as_deref(),
+        );
+        debug!(
+            "Dictation session {} transcribed: '{}'",
+            id,
+            redact(&transcription.text)
+        );
+        if let Some(name) = &session.context {
+            self.contexts
+                .lock()
+                .unwrap()
+                .extend(name, &transcription.text, Instant::now());
+        }
+        self.events.publish(EventKind::TranscriptionCompleted {
+            text: transcription.text.clone(),
+            language: transcription.language.clone(),
+            duration_ms,
+        });
+        if let Some(output) = app.try_state::<Arc<OutputRouter>>() {
+            output.deliver(
+                &transcription.text,
+                transcription.language.as_deref(),
+                duration_ms,
+            );
+        }
+
+        if !transcription.text.is_empty() {
+            let record = TranscriptionRecord {
+                source: HistorySource::App,
+                transcription_text: transcription.text.clone(),
+                segments: transcription
+                    .segments
+                    .iter()
+                    .map(|seg| HistorySegment {
+                        start: seg.start,
+                        end: seg.end,
+                        text: seg.text.clone(),
+                        speaker: None,
+                    })
+                    .collect(),
+                model_id: self.transcription_manager.get_current_model(),
+                duration_ms,
+                ..Default::default()
+            };
+            let hm = self.history_manager.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = hm.save_transcription(samples, record).await {
+                    error!("Failed to save dictation to history: {}", e);
+                }
+            });
+        }
+
+        let pasted = if session.paste && !transcription.text.is_empty() {
+            paste(&app, transcription.text.clone()).await
+        } else {
+            false
+        };
+        finish(&app);
+        self.transcription_manager
+            .maybe_unload_immediately("dictation session");
+
+        Ok(Dictation {
+            text: transcription.text,
+            segments: transcription.segments,
+            language: transcription.language,
+            duration_ms,
+            pasted,
+        })
+    }
+
+    /// Forget the text dictated in context `name`, e.g. to start a new
+    /// document under the same name. False if there was none.
+    pub fn clear_context(&self, name: &str) -> bool {
+        self.contexts.lock().unwrap().remove(name)
+    }
+
+    /// Stop recording and discard the audio.
+    pub fn cancel(&self, id: &str) -> Result<(), DictationError> {
+        let (app, rm) = self.app()?;
+        self.take(id)?;
+        rm.remove_mute();
+        rm.cancel_recording();
+        self.events.publish(EventKind::RecordingCancelled);
+        utils::hide_recording_overlay(&app);
+        change_tray_icon(&app, TrayIconState::Idle);
+        info!("Cancelled dictation session {}", id);
+        Ok(())
+    }
+}
+
+/// The last `max_chars` or fewer characters of `text`, starting at a word.
+fn tail(text: &str, max_chars: usize) -> &str {
+    if max_chars == 0 {
+        return "";
+    }
+    let Some((start, _)) = text.char_indices().rev().nth(max_chars - 1) else {
+        return text;
+    };
+    if start == 0 {
+        return text;
+    }
+    // Already at the start of a word
+    if text[..start].ends_with(char::is_whitespace) {
+        return text[start..].trim_start();
+    }
+    match text[start..].find(char::is_whitespace) {
+        Some(space) => text[start + space..].trim_start(),
+        // One long word
+        None => &text[start..],
+    }
+}
+
+/// Paste `text` into the focused app, on the main thread like the
+/// shortcuts do.
+async fn paste(app: &AppHandle, text: String) -> bool {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let handle = app.clone();
+    let scheduled = app.run_on_main_thread(move || {
+        let _ = tx.send(utils::paste(text, handle));
+    });
+    if let Err(e) = scheduled {
+        error!("Failed to run paste on main thread: {:?}", e);
+        return false;
+    }
+    match rx.await {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            error!("Failed to paste dictation: {}", e);
+            false
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail() {
+        let text = "The quick brown fox";
+        assert_eq!(tail(text, 100), text);
+        assert_eq!(tail(text, text.chars().count()), text);
+        // Starts at the next word, never inside one
+        assert_eq!(tail(text, 8), "fox");
+        assert_eq!(tail(text, 9), "brown fox");
+        assert_eq!(tail(text, 10), "brown fox");
+        assert_eq!(tail(text, 0), "");
+        assert_eq!(tail("", 5), "");
+        // One long word is cut
+        assert_eq!(tail("Donaudampfschifffahrt", 5), "fahrt");
+        // Counts characters, not bytes
+        assert_eq!(tail("café crème brûlée", 7), "brûlée");
+        assert_eq!(tail("日本語のテキスト", 4), "テキスト");
+    }
+
+    #[test]
+    fn test_contexts() {
+        let start = Instant::now();
+        let mut contexts = Contexts::default();
+        contexts.extend("memo", " Dear Ms. Novak, ", start);
+        contexts.extend("memo", "thanks for the call.", start);
+        contexts.extend("memo", "  ", start);
+        assert_eq!(
+            contexts.tail("memo", start).as_deref(),
+            Some("Dear Ms. Novak, thanks for the call.")
+        );
+        assert_eq!(contexts.tail("other", start), None);
+
+        let long = "word ".repeat(200);
+        contexts.extend("memo", &long, start);
+        let kept = contexts.tail("memo", start).unwrap();
+        assert!(kept.chars().count() <= CONTEXT_TAIL_CHARS);
+        assert!(kept.starts_with("word") && kept.ends_with("word"));
+
+        assert!(contexts.remove("memo"));
+        assert!(!contexts.remove("memo"));
+    }
+
+    #[test]
+    fn test_contexts_evict_least_recently_used() {
+        let start = Instant::now();
+        let mut contexts = Contexts::default();
+        for i in 0..MAX_CONTEXTS {
+            contexts.extend(
+                &format!("doc{}", i),
+                "text",
+                start + Duration::from_secs(i as u64),
+            );
+        }
+        // Using doc0 again makes doc1 the least recently used
+        let later = start + Duration::from_secs(MAX_CONTEXTS as u64);
+        contexts.extend("doc0", "more", later);
+        contexts.extend("new", "text", later);
+
+        assert_eq!(contexts.0.len(), MAX_CONTEXTS);
+        assert_eq!(contexts.tail("doc0", later).as_deref(), Some("text more"));
+        assert!(contexts.tail("doc1", later).is_none());
+        assert!(contexts.tail("doc2", later).is_some());
+        assert!(contexts.tail("new", later).is_some());
+    }
+
+    #[test]
+    fn test_contexts_expire() {
+        let start = Instant::now();
+        let mut contexts = Contexts::default();
+        contexts.extend("old", "text", start);
+        contexts.extend("recent", "text", start + Duration::from_secs(60 * 60));
+
+        let later = start + CONTEXT_TTL;
+        assert_eq!(contexts.tail("old", later), None);
+        assert_eq!(contexts.tail("recent", later).as_deref(), Some("text"));
+        assert_eq!(contexts.0.len(), 1);
+    }
+}
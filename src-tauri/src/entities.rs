@@ -0,0 +1,361 @@
+//! Structured entities in a transcript for voice form filling: dates,
+//! amounts, phone numbers, email addresses and names.
+//!
+//! Without an LLM, built-in patterns find email addresses, phone numbers,
+//! amounts with a currency, and dates written as `2024-03-05` or with an
+//! English month name. Names and relative dates such as "next Tuesday"
+//! need the LLM, whose results are completed with the pattern matches it
+//! missed.
+
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct Entities {
+    /// Dates, with `value` as `YYYY-MM-DD` when the text names the year.
+    pub dates: Vec<Entity>,
+    pub amounts: Vec<Amount>,
+    /// Phone numbers, with `value` as digits and an optional leading `+`.
+    pub phone_numbers: Vec<Entity>,
+    /// Email addresses, with `value` in lowercase.
+    pub emails: Vec<Entity>,
+    /// People and organizations; only found by the LLM.
+    pub names: Vec<Entity>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Entity {
+    /// As written in the transcript.
+    pub text: String,
+    /// Normalized value, if there is one.
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Amount {
+    /// As written in the transcript, e.g. "$1,200".
+    pub text: String,
+    #[serde(default)]
+    pub value: Option<f64>,
+    /// ISO 4217 code, e.g. "USD".
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+const MONTHS: &str = "january|february|march|april|may|june|july|august|september|october|november|december|jan|feb|mar|apr|jun|jul|aug|sep|sept|oct|nov|dec";
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b").unwrap()
+});
+static PHONE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:\+|\(\s?)?\b\d[\d().\s-]{5,}\d\b").unwrap());
+static ISO_DATE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})\b").unwrap());
+static MONTH_DAY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"(?i)\b({MONTHS})\.?\s+(\d{{1,2}})(?:st|nd|rd|th)?(?:,?\s+(\d{{4}}))?\b"
+    ))
+    .unwrap()
+});
+static DAY_MONTH: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"(?i)\b(\d{{1,2}})(?:st|nd|rd|th)?\s+(?:of\s+)?({MONTHS})\b\.?(?:,?\s+(\d{{4}}))?"
+    ))
+    .unwrap()
+});
+static AMOUNT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)(?:([$€£¥])\s?(\d+(?:,\d{3})*(?:\.\d+)?)(?:\s?(thousand|million|billion)\b)?)|(?:\b(\d+(?:,\d{3})*(?:\.\d+)?)\s?(?:(thousand|million|billion)\s)?\s?(dollars?|euros?|pounds?|yen|francs?|usd|eur|gbp|jpy|chf)\b)",
+    )
+    .unwrap()
+});
+
+/// The entities the built-in patterns find in `text`.
+pub fn extract(text: &str) -> Entities {
+    let mut entities = Entities::default();
+    // Spans already taken, so e.g. a date isn't also read as a phone number
+    let mut taken: Vec<(usize, usize)> = Vec::new();
+
+    for found in EMAIL.find_iter(text) {
+        taken.push((found.start(), found.end()));
+        entities.emails.push(Entity {
+            text: found.as_str().to_string(),
+            value: Some(found.as_str().to_lowercase()),
+        });
+    }
+
+    let mut dates: Vec<(usize, Entity)> = Vec::new();
+    for caps in ISO_DATE.captures_iter(text) {
+        let value = date(&caps[1], month_number(&caps[2]), &caps[3]);
+        if value.is_some() && is_free(&taken, &caps) {
+            dates.push(take(&mut taken, &caps, value));
+        }
+    }
+    for caps in MONTH_DAY.captures_iter(text) {
+        let value = caps
+            .get(3)
+            .and_then(|year| date(year.as_str(), month_number(&caps[1]), &caps[2]));
+        if is_free(&taken, &caps) {
+            dates.push(take(&mut taken, &caps, value));
+        }
+    }
+    for caps in DAY_MONTH.captures_iter(text) {
+        let value = caps
+            .get(3)
+            .and_then(|year| date(year.as_str(), month_number(&caps[2]), &caps[1]));
+        if is_free(&taken, &caps) {
+            dates.push(take(&mut taken, &caps, value));
+        }
+    }
+    dates.sort_by_key(|(start, _)| *start);
+    entities.dates = dates.into_iter().map(|(_, date)| date).collect();
+
+    for caps in AMOUNT.captures_iter(text) {
+        if !is_free(&taken, &caps) {
+            continue;
+        }
+        let whole = caps.get(0).unwrap();
+        taken.push((whole.start(), whole.end()));
+        let (number, scale, currency) = match caps.get(1) {
+            Some(symbol) => (&caps[2], caps.get(3), currency(symbol.as_str())),
+            None => (&caps[4], caps.get(5), currency(&caps[6])),
+        };
+        entities.amounts.push(Amount {
+            text: whole.as_str().trim().to_string(),
+            value: number
+                .replace(',', "")
+                .parse::<f64>()
+                .ok()
+                .map(|value| value * scale.map_or(1.0, |scale| multiplier(scale.as_str()))),
+            currency: currency.map(str::to_string),
+        });
+    }
+
+    for found in PHONE.find_iter(text) {
+        let digits: String = found
+            .as_str()
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect();
+        let overlaps = taken
+            .iter()
+            .any(|&(start, end)| found.start() < end && start < found.end());
+        if overlaps || !(7..=15).contains(&digits.len()) {
+            continue;
+        }
+        let plus = if found.as_str().starts_with('+') {
+            "+"
+        } else {
+            ""
+        };
+        entities.phone_numbers.push(Entity {
+            text: found.as_str().to_string(),
+            value: Some(format!("{}{}", plus, digits)),
+        });
+    }
+    entities
+}
+
+impl Entities {
+    /// Add the entities of `other` that aren't in `self` yet.
+    pub fn merge(&mut self, other: Entities) {
+        fn add<T>(into: &mut Vec<T>, from: Vec<T>, text: impl Fn(&T) -> &str) {
+            for item in from {
+                let exists = into
+                    .iter()
+                    .any(|existing| text(existing).eq_ignore_ascii_case(text(&item)));
+                if !exists {
+                    into.push(item);
+                }
+            }
+        }
+        add(&mut self.dates, other.dates, |e| &e.text);
+        add(&mut self.amounts, other.amounts, |a| &a.text);
+        add(&mut self.phone_numbers, other.phone_numbers, |e| &e.text);
+        add(&mut self.emails, other.emails, |e| &e.text);
+        add(&mut self.names, other.names, |e| &e.text);
+    }
+
+    /// JSON schema of the LLM's reply, in the strict form of structured
+    /// outputs.
+    pub fn json_schema() -> Value {
+        let entity = json!({
+            "type": "object",
+            "properties": {
+                "text": {"type": "string"},
+                "value": {"type": ["string", "null"]}
+            },
+            "required": ["text", "value"],
+            "additionalProperties": false
+        });
+        let list = |item: &Value| json!({"type": "array", "items": item});
+        json!({
+            "type": "object",
+            "properties": {
+                "dates": list(&entity),
+                "amounts": list(&json!({
+                    "type": "object",
+                    "properties": {
+                        "text": {"type": "string"},
+                        "value": {"type": ["number", "null"]},
+                        "currency": {"type": ["string", "null"]}
+                    },
+                    "required": ["text", "value", "currency"],
+                    "additionalProperties": false
+                })),
+                "phone_numbers": list(&entity),
+                "emails": list(&entity),
+                "names": list(&entity)
+            },
+            "required": ["dates", "amounts", "phone_numbers", "emails", "names"],
+            "additionalProperties": false
+        })
+    }
+
+    /// Parse the LLM's reply, which may be wrapped in a Markdown code
+    /// block when the provider doesn't enforce the schema.
+    pub fn parse(reply: &str) -> Result<Self, String> {
+        let json = reply
+            .trim()
+            .trim_start_matches("```json")
+            .trim_start_matches("```")
+            .trim_end_matches("```")
+            .trim();
+        serde_json::from_str(json).map_err(|e| format!("Invalid entities from the LLM: {}", e))
+    }
+}
+
+/// Whether the match `caps` overlaps none of the `taken` spans.
+fn is_free(taken: &[(usize, usize)], caps: &Captures) -> bool {
+    let whole = caps.get(0).unwrap();
+    !taken
+        .iter()
+        .any(|&(start, end)| whole.start() < end && start < whole.end())
+}
+
+/// Mark the match `caps` as taken and return it as an entity.
+fn take(
+    taken: &mut Vec<(usize, usize)>,
+    caps: &Captures,
+    value: Option<String>,
+) -> (usize, Entity) {
+    let whole = caps.get(0).unwrap();
+    taken.push((whole.start(), whole.end()));
+    (
+        whole.start(),
+        Entity {
+            text: whole.as_str().trim_end_matches('.').to_string(),
+            value,
+        },
+    )
+}
+
+/// `YYYY-MM-DD` if the parts make a valid date.
+fn date(year: &str, month: Option<u32>, day: &str) -> Option<String> {
+    let date = NaiveDate::from_ymd_opt(year.parse().ok()?, month?, day.parse().ok()?)?;
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+/// Number of a month given as digits or an English (abbreviated) name.
+fn month_number(month: &str) -> Option<u32> {
+    if let Ok(number) = month.parse() {
+        return Some(number);
+    }
+    let month = month.to_lowercase();
+    MONTHS
+        .split('|')
+        .take(12)
+        .position(|name| name.starts_with(&month[..3.min(month.len())]))
+        .map(|index| index as u32 + 1)
+}
+
+fn currency(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    let code = match name.trim_end_matches('s') {
+        "$" | "dollar" | "usd" => "USD",
+        "€" | "euro" | "eur" => "EUR",
+        "£" | "pound" | "gbp" => "GBP",
+        "¥" | "yen" | "jpy" => "JPY",
+        "franc" | "chf" => "CHF",
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn multiplier(scale: &str) -> f64 {
+    match scale.to_lowercase().as_str() {
+        "thousand" => 1e3,
+        "million" => 1e6,
+        "billion" => 1e9,
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let entities = extract(
+            "Call me at +1 415-555-0132 or mail Jane.Doe@Example.com. The deposit of $1,200 \
+             is due March 5th, 2025, the rest, 3 million euros, on 2025-06-30 or by 12 July.",
+        );
+
+        let dates: Vec<(&str, Option<&str>)> = entities
+            .dates
+            .iter()
+            .map(|e| (e.text.as_str(), e.value.as_deref()))
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                ("March 5th, 2025", Some("2025-03-05")),
+                ("2025-06-30", Some("2025-06-30")),
+                ("12 July", None),
+            ]
+        );
+
+        let [deposit, rest] = entities.amounts.as_slice() else {
+            panic!("expected two amounts, got {:?}", entities.amounts);
+        };
+        assert_eq!(deposit.text, "$1,200");
+        assert_eq!(deposit.value, Some(1200.0));
+        assert_eq!(deposit.currency.as_deref(), Some("USD"));
+        assert_eq!(rest.text, "3 million euros");
+        assert_eq!(rest.value, Some(3e6));
+        assert_eq!(rest.currency.as_deref(), Some("EUR"));
+
+        assert_eq!(entities.phone_numbers.len(), 1);
+        assert_eq!(
+            entities.phone_numbers[0].value.as_deref(),
+            Some("+14155550132")
+        );
+        assert_eq!(
+            entities.emails[0].value.as_deref(),
+            Some("jane.doe@example.com")
+        );
+        assert!(entities.names.is_empty());
+    }
+
+    #[test]
+    fn test_parse_and_merge() {
+        let mut entities = Entities::parse(
+            "```json\n{\"dates\": [{\"text\": \"next Tuesday\", \"value\": \"2025-03-11\"}], \
+             \"names\": [{\"text\": \"Jane Doe\", \"value\": null}]}\n```",
+        )
+        .unwrap();
+        assert_eq!(entities.names[0].text, "Jane Doe");
+        assert!(entities.emails.is_empty());
+
+        entities.merge(extract("Jane Doe, jane@example.com, next Tuesday"));
+        assert_eq!(entities.emails.len(), 1);
+        assert_eq!(entities.dates.len(), 1);
+        assert!(Entities::parse("Sorry, I can't").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! Dictation events for companion tools, pushed to them over the REST API's
+//! `GET /ws/events` WebSocket.
+//!
+//! Recording and transcription with the shortcuts and with API sessions, and
+//! the transcript of a running meeting, are published to an in-process broadcast channel; each WebSocket subscribes
+//! to it and gets every event from then on as a JSON text message. Events
+//! aren't stored, so a client that isn't connected misses them.
+
+use chrono::Utc;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::meeting::MeetingSegment;
+
+/// Events kept for slow subscribers before they start missing some.
+const CAPACITY: usize = 64;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AppEvent {
+    /// Unix timestamp in milliseconds.
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    RecordingStarted,
+    RecordingStopped,
+    /// The recording was discarded without transcribing it.
+    RecordingCancelled,
+    TranscriptionCompleted {
+        /// The text as pasted, after any post-processing.
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        /// Duration of the recording.
+        duration_ms: i64,
+    },
+    MeetingStarted,
+    /// A new piece of the running meeting's transcript.
+    MeetingSegment(MeetingSegment),
+    MeetingStopped {
+        duration_ms: i64,
+    },
+}
+
+pub struct EventBus {
+    sender: broadcast::Sender<AppEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::channel(CAPACITY).0,
+        }
+    }
+
+    /// Send `kind` to the current subscribers, if any.
+    pub fn publish(&self, kind: EventKind) {
+        let _ = self.sender.send(AppEvent {
+            timestamp: Utc::now().timestamp_millis(),
+            kind,
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events() {
+        let bus = EventBus::new();
+        // Nobody listening yet
+        bus.publish(EventKind::RecordingStarted);
+
+        let mut events = bus.subscribe();
+        bus.publish(EventKind::RecordingStopped);
+        bus.publish(EventKind::TranscriptionCompleted {
+            text: "Hello".to_string(),
+            language: None,
+            duration_ms: 1500,
+        });
+
+        let stopped = serde_json::to_value(events.try_recv().unwrap()).unwrap();
+        assert_eq!(stopped["type"], "recording_stopped");
+        assert!(stopped["timestamp"].as_i64().unwrap() > 0);
+        let completed = serde_json::to_value(events.try_recv().unwrap()).unwrap();
+        assert_eq!(completed["type"], "transcription_completed");
+        assert_eq!(completed["text"], "Hello");
+        assert_eq!(completed["duration_ms"], 1500);
+        assert!(completed.get("language").is_none());
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! Finding and spawning ffmpeg, which decodes formats Symphonia can't, with
+//! the resource limits, timeout and output cap from `[ffmpeg]` in the
+//! config.
+//!
+//! The binary is `[ffmpeg] path` (HANDY_FFMPEG_PATH) if set, otherwise the
+//! `ffmpeg_path` setting, otherwise the first one found next to the Handy
+//! executable (a sidecar bundled with Tauri's `externalBin`), on the PATH,
+//! or in the usual install locations, which GUI apps on macOS don't have on
+//! their PATH.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use log::warn;
+
+use transcribe_rs::limits::ResourceLimits;
+
+use crate::config::FfmpegConfig;
+
+struct Options {
+    /// From the config file or environment; takes precedence.
+    path: Option<PathBuf>,
+    settings_path: Option<PathBuf>,
+    limits: Option<ResourceLimits>,
+    timeout: Option<Duration>,
+    max_output_bytes: Option<u64>,
+}
+
+static OPTIONS: RwLock<Options> = RwLock::new(Options {
+    path: None,
+    settings_path: None,
+    limits: None,
+    timeout: Some(DEFAULT_TIMEOUT),
+    max_output_bytes: Some(DEFAULT_MAX_OUTPUT_MB * 1024 * 1024),
+});
+
+/// ffmpeg decodes far faster than real time, so this only trips on a hang.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+/// About 4.5 hours of stereo or 9 hours of mono audio at 16 kHz.
+const DEFAULT_MAX_OUTPUT_MB: u64 = 2048;
+
+#[derive(Debug)]
+pub enum FfmpegError {
+    /// No ffmpeg binary at the configured path or any of the searched ones.
+    NotFound {
+        searched: Vec<PathBuf>,
+    },
+    /// ffmpeg ran longer than `[ffmpeg] timeout_secs` and was killed, most
+    /// likely stuck on malformed input.
+    DecodeTimeout(Duration),
+    Failed(String),
+}
+
+impl fmt::Display for FfmpegError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FfmpegError::NotFound { searched } => {
+                write!(
+                    f,
+                    "ffmpeg was not found; install it for OGG/Opus, M4A and WebM support, or set its \
+                     location with [ffmpeg] path (HANDY_FFMPEG_PATH) or the ffmpeg_path setting. \
+                     Searched: "
+                )?;
+                let searched: Vec<_> = searched.iter().map(|p| p.display().to_string()).collect();
+                f.write_str(&searched.join(", "))
+            }
+            FfmpegError::DecodeTimeout(timeout) => write!(
+                f,
+                "ffmpeg did not finish decoding within {} seconds",
+                timeout.as_secs()
+            ),
+            FfmpegError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for FfmpegError {
+    fn from(message: String) -> Self {
+        FfmpegError::Failed(message)
+    }
+}
+
+/// Apply `config` and the `ffmpeg_path` setting to every ffmpeg started
+/// from now on.
+pub fn configure(config: &FfmpegConfig, settings_path: Option<&str>) {
+    let limits = ResourceLimits {
+        nice: config.nice,
+        cpu_affinity: config.cpu_affinity.clone(),
+        memory_bytes: config.memory_mb.map(|mb| mb * 1024 * 1024),
+    };
+    let mut options = OPTIONS.write().unwrap();
+    options.path = config.path.clone();
+    options.limits = Some(limits).filter(|limits| !limits.is_empty());
+    options.timeout = match config.timeout_secs {
+        Some(0) => None,
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => Some(DEFAULT_TIMEOUT),
+    };
+    options.max_output_bytes = match config.max_output_mb.unwrap_or(DEFAULT_MAX_OUTPUT_MB) {
+        0 => None,
+        mb => Some(mb * 1024 * 1024),
+    };
+    drop(options);
+    set_settings_path(settings_path);
+}
+
+/// Use the `ffmpeg_path` setting, after it was changed in the app.
+pub fn set_settings_path(path: Option<&str>) {
+    OPTIONS.write().unwrap().settings_path = path
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from);
+}
+
+/// The ffmpeg binary to run. A configured path must exist; the search only
+/// happens without one.
+pub fn find() -> Result<PathBuf, FfmpegError> {
+    let options = OPTIONS.read().unwrap();
+    if let Some(path) = options.path.as_ref().or(options.settings_path.as_ref()) {
+        return if path.is_file() {
+            Ok(path.clone())
+        } else {
+            Err(FfmpegError::NotFound {
+                searched: vec![path.clone()],
+            })
+        };
+    }
+
+    let candidates = candidates();
+    match candidates.iter().find(|path| path.is_file()) {
+        Some(path) => Ok(path.clone()),
+        None => Err(FfmpegError::NotFound {
+            searched: candidates,
+        }),
+    }
+}
+
+/// Where ffmpeg is looked for, in order.
+fn candidates() -> Vec<PathBuf> {
+    let name = format!("ffmpeg{}", std::env::consts::EXE_SUFFIX);
+    let mut dirs = Vec::new();
+    if let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        dirs.push(dir);
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    dirs.extend(install_dirs());
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        let candidate = dir.join(&name);
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+#[cfg(target_os = "macos")]
+fn install_dirs() -> Vec<PathBuf> {
+    // Homebrew on Apple Silicon and Intel, MacPorts
+    ["/opt/homebrew/bin", "/usr/local/bin", "/opt/local/bin"]
+        .iter()
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn install_dirs() -> Vec<PathBuf> {
+    let env_dir =
+        |var: &str, rest: &str| std::env::var_os(var).map(|dir| Path::new(&dir).join(rest));
+    [
+        env_dir("LOCALAPPDATA", r"Microsoft\WinGet\Links"),
+        env_dir("USERPROFILE", r"scoop\shims"),
+        env_dir("ProgramData", r"chocolatey\bin"),
+        env_dir("ProgramFiles", r"ffmpeg\bin"),
+        Some(PathBuf::from(r"C:\ffmpeg\bin")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn install_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = ["/usr/bin", "/usr/local/bin", "/snap/bin"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(Path::new(&home).join(".local/bin"));
+    }
+    dirs
+}
+
+/// An ffmpeg command that runs with the configured limits. Pass the
+/// spawned process to [`attach`].
+pub fn command() -> Result<Command, FfmpegError> {
+    let mut command = Command::new(find()?);
+    if let Some(limits) = OPTIONS.read().unwrap().limits.as_ref() {
+        limits.apply(&mut command);
+    }
+    Ok(command)
+}
+
+/// Finish applying the limits to an ffmpeg process started with
+/// [`command`]; needed on Windows.
+pub fn attach(child: &Child) -> Result<(), String> {
+    match OPTIONS.read().unwrap().limits.as_ref() {
+        Some(limits) => limits
+            .attach(child)
+            .map_err(|e| format!("Failed to limit ffmpeg's resources: {}", e)),
+        None => Ok(()),
+    }
+}
+
+/// Most bytes of decoded audio to read from ffmpeg, if limited.
+pub fn max_output_bytes() -> Option<u64> {
+    OPTIONS.read().unwrap().max_output_bytes
+}
+
+/// Wait for `child` to exit without holding its lock, so the watchdog can
+/// still kill it meanwhile.
+pub fn wait(child: &Mutex<Child>) -> io::Result<ExitStatus> {
+    loop {
+        if let Some(status) = child.lock().unwrap().try_wait()? {
+            return Ok(status);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Kills an ffmpeg process that runs past the configured timeout.
+pub struct Watchdog {
+    done: mpsc::Sender<()>,
+    thread: Option<JoinHandle<bool>>,
+    timeout: Duration,
+}
+
+/// Watch `child`, killing it after the timeout unless [`Watchdog::finish`]
+/// is called first. Killing it closes its output, so a read blocked on a
+/// hung ffmpeg returns.
+pub fn watchdog(child: &Arc<Mutex<Child>>) -> Watchdog {
+    let timeout = OPTIONS.read().unwrap().timeout;
+    let (done, finished) = mpsc::channel();
+    let thread = timeout.map(|timeout| {
+        let child = child.clone();
+        std::thread::spawn(move || match finished.recv_timeout(timeout) {
+            Err(RecvTimeoutError::Timeout) => {
+                warn!("ffmpeg timed out after {:?}, killing it", timeout);
+                let _ = child.lock().unwrap().kill();
+                true
+            }
+            _ => false,
+        })
+    });
+    Watchdog {
+        done,
+        thread,
+        timeout: timeout.unwrap_or_default(),
+    }
+}
+
+impl Watchdog {
+    /// Stop watching. Fails with [`FfmpegError::DecodeTimeout`] when the
+    /// process was killed for running too long.
+    pub fn finish(mut self) -> Result<(), FfmpegError> {
+        let _ = self.done.send(());
+        match self.thread.take().map(|thread| thread.join()) {
+            Some(Ok(true)) => Err(FfmpegError::DecodeTimeout(self.timeout)),
+            _ => Ok(()),
+        }
+    }
+}
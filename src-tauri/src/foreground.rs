@@ -0,0 +1,152 @@
+//! The application in the foreground, which dictation profiles are picked
+//! by.
+//!
+//! Windows reports the executable of the foreground window and macOS the
+//! name and bundle id of the frontmost app (via `lsappinfo`, which needs no
+//! accessibility permission). On Linux the process of the active X11 window
+//! is found with xdotool; Wayland doesn't tell other apps which window has
+//! focus, so there is no foreground app there.
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::process::Command;
+
+/// An application as dictation profiles match it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ForegroundApp {
+    /// Process or display name, e.g. "Code" or "WindowsTerminal.exe".
+    pub name: String,
+    /// Bundle id on macOS, e.g. "com.apple.mail".
+    pub bundle_id: Option<String>,
+}
+
+impl ForegroundApp {
+    /// Whether `pattern` names this app, ignoring case and a `.exe` or
+    /// `.app` extension.
+    pub fn matches(&self, pattern: &str) -> bool {
+        let pattern = normalize(pattern);
+        !pattern.is_empty()
+            && (normalize(&self.name) == pattern
+                || self
+                    .bundle_id
+                    .as_deref()
+                    .is_some_and(|id| normalize(id) == pattern))
+    }
+}
+
+fn normalize(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    let stem = name
+        .strip_suffix(".exe")
+        .or_else(|| name.strip_suffix(".app"))
+        .unwrap_or(&name);
+    stem.to_string()
+}
+
+/// The application whose window has focus, if it can be told.
+#[cfg(target_os = "windows")]
+pub fn foreground_app() -> Option<ForegroundApp> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    let path = unsafe {
+        let window = GetForegroundWindow();
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(window, Some(&mut pid));
+        if pid == 0 {
+            return None;
+        }
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut len = buffer.len() as u32;
+        let queried = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(process);
+        queried.ok()?;
+        String::from_utf16_lossy(&buffer[..len as usize])
+    };
+    let name = std::path::Path::new(&path).file_name()?;
+    Some(ForegroundApp {
+        name: name.to_string_lossy().into_owned(),
+        bundle_id: None,
+    })
+}
+
+/// The application whose window has focus, if it can be told.
+#[cfg(target_os = "macos")]
+pub fn foreground_app() -> Option<ForegroundApp> {
+    let front = run("lsappinfo", &["front"])?;
+    let asn = front.trim();
+    // Prints e.g. "LSDisplayName"="Mail"
+    let value = |key: &str| {
+        let output = run("lsappinfo", &["info", "-only", key, asn])?;
+        let (_, value) = output.trim().split_once("=\"")?;
+        Some(value.trim_end_matches('"').to_string()).filter(|value| !value.is_empty())
+    };
+    Some(ForegroundApp {
+        name: value("name")?,
+        bundle_id: value("bundleid"),
+    })
+}
+
+/// The application whose window has focus, if it can be told.
+#[cfg(target_os = "linux")]
+pub fn foreground_app() -> Option<ForegroundApp> {
+    if crate::utils::is_wayland() {
+        return None;
+    }
+    let pid = run("xdotool", &["getactivewindow", "getwindowpid"])?;
+    let name = std::fs::read_to_string(format!("/proc/{}/comm", pid.trim())).ok()?;
+    Some(ForegroundApp {
+        name: name.trim().to_string(),
+        bundle_id: None,
+    })
+    .filter(|app| !app.name.is_empty())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn foreground_app() -> Option<ForegroundApp> {
+    None
+}
+
+/// Standard output of `program`, if it ran and succeeded.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let terminal = ForegroundApp {
+            name: "WindowsTerminal.exe".to_string(),
+            bundle_id: None,
+        };
+        assert!(terminal.matches("windowsterminal"));
+        assert!(terminal.matches(" WindowsTerminal.exe "));
+        assert!(!terminal.matches("Terminal"));
+        assert!(!terminal.matches(""));
+
+        let mail = ForegroundApp {
+            name: "Mail".to_string(),
+            bundle_id: Some("com.apple.mail".to_string()),
+        };
+        assert!(mail.matches("Mail.app"));
+        assert!(mail.matches("com.apple.Mail"));
+    }
+}
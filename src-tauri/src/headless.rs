@@ -0,0 +1,177 @@
+//! `handy --headless`: run the REST API without Tauri, windows or a tray, so
+//! the server works where there is no display server (Docker, SSH sessions,
+//! CI).
+//!
+//! Settings, models and history live in the same data directory as the
+//! desktop app unless HANDY_DATA_DIR points elsewhere (see
+//! `host::HeadlessHost::from_env`); the server itself is configured through
+//! `config`. Recording, shortcuts and pasting are not available. The API is
+//! always started, whatever `api_server_enabled` says, and an invalid config
+//! is fatal.
+
+use std::sync::Arc;
+
+use log::{error, info};
+
+use crate::cli::CliArgs;
+use crate::config::Config;
+use crate::host::{HeadlessHost, Host};
+use crate::managers::api_keys::{ApiKeyManager, ApiScope};
+use crate::services::Services;
+
+/// Console logger for headless mode; the app logs through tauri-plugin-log.
+struct ConsoleLogger {
+    filter: env_filter::Filter,
+}
+
+impl log::Log for ConsoleLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.filter.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.filter.matches(record) {
+            eprintln!(
+                "[{}][{}][{}] {}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn init_logging(debug: bool) {
+    let filter = if debug && std::env::var_os("RUST_LOG").is_none() {
+        env_filter::Builder::new()
+            .filter_level(log::LevelFilter::Debug)
+            .build()
+    } else {
+        crate::build_console_filter()
+    };
+    let max_level = filter.filter();
+    if log::set_boxed_logger(Box::new(ConsoleLogger { filter })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Block until the process is asked to stop.
+fn wait_for_shutdown() {
+    #[cfg(unix)]
+    {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+        use signal_hook::iterator::Signals;
+
+        match Signals::new([SIGINT, SIGTERM]) {
+            Ok(mut signals) => {
+                if let Some(signal) = signals.forever().next() {
+                    info!("Received signal {}, shutting down", signal);
+                }
+                return;
+            }
+            Err(e) => error!("Failed to install signal handlers: {}", e),
+        }
+    }
+
+    // Without signal handlers, run until killed
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Create, list or revoke API keys as asked by `cli_args`, printing the
+/// result as JSON.
+fn manage_api_keys(host: &Host, cli_args: &CliArgs) -> anyhow::Result<()> {
+    let keys = ApiKeyManager::new(host)?;
+    if let Some(name) = &cli_args.create_api_key {
+        let scopes = ApiScope::parse_list(cli_args.scopes.as_deref().unwrap_or("transcribe"))?;
+        let (key, secret) = keys.create(name, &scopes)?;
+        let created = serde_json::json!({ "key": key, "secret": secret });
+        println!("{}", serde_json::to_string_pretty(&created)?);
+    }
+    if let Some(id) = cli_args.revoke_api_key {
+        if !keys.revoke(id)? {
+            anyhow::bail!("No active API key with id {}", id);
+        }
+    }
+    if cli_args.list_api_keys {
+        println!("{}", serde_json::to_string_pretty(&keys.list()?)?);
+    }
+    Ok(())
+}
+
+/// Run headless until SIGINT/SIGTERM and return the process exit code.
+/// With `--benchmark`, run the benchmark instead and exit; with `--mcp`,
+/// serve MCP over stdio until stdin closes; with the API key options,
+/// manage the keys and exit.
+pub fn run(cli_args: CliArgs) -> i32 {
+    crate::portable::init();
+    init_logging(cli_args.debug);
+
+    let host = match HeadlessHost::from_env() {
+        Ok(host) => Host::Headless(Arc::new(host)),
+        Err(e) => {
+            error!("Failed to set up data directory: {:#}", e);
+            return 1;
+        }
+    };
+    if cli_args.manages_api_keys() {
+        return match manage_api_keys(&host, &cli_args) {
+            Ok(()) => 0,
+            Err(e) => {
+                error!("{:#}", e);
+                1
+            }
+        };
+    }
+    let config = match host.app_data_dir().and_then(|dir| Config::load(&dir)) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{:#}", e);
+            return 1;
+        }
+    };
+    let services = match Services::new(&host, config) {
+        Ok(services) => services,
+        Err(e) => {
+            error!("Failed to initialize: {:#}", e);
+            return 1;
+        }
+    };
+
+    if let Some(models) = cli_args.benchmark.as_deref() {
+        return services.benchmarker.run_cli(models);
+    }
+
+    if cli_args.mcp {
+        let mcp = McpServer::stdio(
+            services.transcription_manager.clone(),
+            services.model_manager.clone(),
+            ApiLimits::from_config(&services.config.api),
+        );
+        info!("Serving MCP over stdio");
+        return match mcp::run_stdio(&mcp) {
+            Ok(()) => 0,
+            Err(e) => {
+                error!("MCP stdio transport failed: {}", e);
+                1
+            }
+        };
+    }
+
+    let port = services.api_port(&host.settings());
+    let api_server = services.api_server.clone();
+    if let Err(e) = tauri::async_runtime::block_on(api_server.start(port)) {
+        error!("{}", e);
+        return 1;
+    }
+    info!("Running headless; press Ctrl+C to stop");
+
+    wait_for_shutdown();
+    tauri::async_runtime::block_on(api_server.stop());
+    services.transcription_manager.unload_model().ok();
+    0
+}
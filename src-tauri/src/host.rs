@@ -0,0 +1,232 @@
+//! What the transcription stack needs from its surroundings: settings,
+//! data and resource directories, and a place to send events.
+//!
+//! The desktop app provides these through the Tauri `AppHandle`. In headless
+//! mode (`handy --headless`) there is no Tauri runtime and no display server;
+//! settings are read from the same `settings_store.json` the app writes, and
+//! events are dropped.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::{
+    ensure_post_process_defaults, get_default_settings, get_settings, write_settings, AppSettings,
+    SETTINGS_STORE_PATH,
+};
+
+/// Identifier from `tauri.conf.json`, used to find the app data directory
+/// without Tauri's path resolver.
+const APP_IDENTIFIER: &str = "com.pais.handy";
+
+#[derive(Clone)]
+pub enum Host {
+    App(AppHandle),
+    Headless(Arc<HeadlessHost>),
+}
+
+impl From<&AppHandle> for Host {
+    fn from(app_handle: &AppHandle) -> Self {
+        Host::App(app_handle.clone())
+    }
+}
+
+impl Host {
+    pub fn settings(&self) -> AppSettings {
+        match self {
+            Host::App(app) => get_settings(app),
+            Host::Headless(host) => host.settings(),
+        }
+    }
+
+    pub fn write_settings(&self, settings: AppSettings) {
+        match self {
+            Host::App(app) => write_settings(app, settings),
+            Host::Headless(host) => host.write_settings(settings),
+        }
+    }
+
+    /// Send an event to the frontend. A no-op in headless mode.
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+        match self {
+            Host::App(app) => app.emit(event, payload),
+            Host::Headless(_) => Ok(()),
+        }
+    }
+
+    /// Portable-aware app data directory.
+    pub fn app_data_dir(&self) -> Result<PathBuf> {
+        match self {
+            Host::App(app) => Ok(crate::portable::app_data_dir(app)?),
+            Host::Headless(host) => Ok(host.data_dir.clone()),
+        }
+    }
+
+    /// Resolve `path` against the bundled resource directory.
+    pub fn resolve_resource(&self, path: &str) -> Result<PathBuf> {
+        match self {
+            Host::App(app) => Ok(app
+                .path()
+                .resolve(path, tauri::path::BaseDirectory::Resource)?),
+            Host::Headless(host) => Ok(host.resource_dir.join(path)),
+        }
+    }
+}
+
+/// Host for running without Tauri.
+pub struct HeadlessHost {
+    data_dir: PathBuf,
+    resource_dir: PathBuf,
+    settings: RwLock<AppSettings>,
+}
+
+impl HeadlessHost {
+    /// Use `data_dir` for settings, models and history, and `resource_dir`
+    /// for bundled resources. Settings are loaded from `data_dir`, or the
+    /// defaults are used if the app has never been run there.
+    pub fn new(data_dir: PathBuf, resource_dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&data_dir)
+            .map_err(|e| anyhow!("Failed to create {}: {}", data_dir.display(), e))?;
+        let settings = load_settings(&data_dir.join(SETTINGS_STORE_PATH));
+        Ok(Self {
+            data_dir,
+            resource_dir,
+            settings: RwLock::new(settings),
+        })
+    }
+
+    /// Directories from `HANDY_DATA_DIR` and `HANDY_RESOURCE_DIR`.
+    ///
+    /// The data directory defaults to the portable `Data/` directory or the
+    /// desktop app's data directory, so a headless server shares models and
+    /// settings with the app on the same machine. Resources default to the
+    /// directory containing the executable.
+    pub fn from_env() -> Result<Self> {
+        let data_dir = match std::env::var_os("HANDY_DATA_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => match crate::portable::data_dir() {
+                Some(dir) => dir.clone(),
+                None => default_data_dir()?,
+            },
+        };
+        let resource_dir = match std::env::var_os("HANDY_RESOURCE_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::current_exe()?
+                .parent()
+                .map(Path::to_path_buf)
+                .ok_or_else(|| anyhow!("Executable has no parent directory"))?,
+        };
+        Self::new(data_dir, resource_dir)
+    }
+
+    fn settings(&self) -> AppSettings {
+        self.settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Keep `settings` and write them back in the store's file format, so
+    /// changes (e.g. an auto-selected model) survive a restart.
+    fn write_settings(&self, settings: AppSettings) {
+        let path = self.data_dir.join(SETTINGS_STORE_PATH);
+        let mut store = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+            .filter(|value| value.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        store["settings"] = serde_json::to_value(&settings).unwrap_or_default();
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = settings;
+
+        match serde_json::to_vec_pretty(&store) {
+            Ok(bytes) => {
+                if let Err(e) = fs::write(&path, bytes) {
+                    warn!("Failed to save settings to {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => warn!("Failed to serialize settings: {}", e),
+        }
+    }
+}
+
+/// Read the `settings` key of a store file written by `tauri-plugin-store`.
+fn load_settings(path: &Path) -> AppSettings {
+    let stored = fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+        .and_then(|mut store| store.get_mut("settings").map(serde_json::Value::take))
+        .and_then(|value| serde_json::from_value::<AppSettings>(value).ok());
+
+    let mut settings = stored.unwrap_or_else(|| {
+        debug!("No usable settings at {}, using defaults", path.display());
+        get_default_settings()
+    });
+    ensure_post_process_defaults(&mut settings);
+    settings
+}
+
+/// Tauri's app data directory for this app, resolved the same way as
+/// `app.path().app_data_dir()`.
+fn default_data_dir() -> Result<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).map(PathBuf::from);
+
+    #[cfg(target_os = "windows")]
+    let base = env_dir("APPDATA");
+    #[cfg(target_os = "macos")]
+    let base = env_dir("HOME").map(|home| home.join("Library/Application Support"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = env_dir("XDG_DATA_HOME")
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env_dir("HOME").map(|home| home.join(".local/share")));
+
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| anyhow!("Could not determine the data directory; set HANDY_DATA_DIR"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_and_saves_store_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_path = dir.path().join(SETTINGS_STORE_PATH);
+
+        let mut stored = get_default_settings();
+        stored.selected_model = "parakeet-tdt-0.6b-v3".to_string();
+        fs::write(
+            &store_path,
+            serde_json::to_vec(&serde_json::json!({ "settings": stored, "other": 1 })).unwrap(),
+        )
+        .unwrap();
+
+        let host = HeadlessHost::new(dir.path().to_path_buf(), dir.path().to_path_buf()).unwrap();
+        let mut settings = host.settings();
+        assert_eq!(settings.selected_model, "parakeet-tdt-0.6b-v3");
+
+        settings.selected_model = "small".to_string();
+        host.write_settings(settings);
+        assert_eq!(host.settings().selected_model, "small");
+
+        let store: serde_json::Value =
+            serde_json::from_slice(&fs::read(&store_path).unwrap()).unwrap();
+        assert_eq!(store["settings"]["selected_model"], "small");
+        assert_eq!(store["other"], 1);
+    }
+
+    #[test]
+    fn missing_store_uses_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let host = HeadlessHost::new(dir.path().join("data"), dir.path().to_path_buf()).unwrap();
+        assert_eq!(
+            host.settings().selected_model,
+            get_default_settings().selected_model
+        );
+        assert!(host.data_dir.exists());
+    }
+}
@@ -1,24 +1,46 @@
 mod actions;
+mod alignment;
+mod analytics;
 mod api;
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 mod apple_intelligence;
 mod audio_feedback;
+mod benchmark;
+mod caption_preview;
+mod chapters;
 pub mod audio_toolkit;
 pub mod cli;
 mod clipboard;
 mod commands;
+mod config;
+mod dictation;
+mod entities;
+mod events;
+mod ffmpeg;
+mod foreground;
+mod headless;
 mod helpers;
+mod host;
 mod input;
 mod llm_client;
 mod managers;
+mod mcp;
+mod meeting;
+mod mentions;
 mod overlay;
 pub mod portable;
+mod post_process;
+mod redact;
+mod services;
 mod settings;
 mod shortcut;
 mod signal_handle;
+mod transcript_cache;
 mod transcription_coordinator;
+mod translation;
 mod tray;
 mod tray_i18n;
+mod uploads;
 mod utils;
 
 pub use cli::CliArgs;
@@ -26,10 +48,11 @@ use specta_typescript::{BigIntExportBehavior, Typescript};
 use tauri_specta::{collect_commands, Builder};
 
 use env_filter::Builder as EnvFilterBuilder;
+use host::Host;
 use managers::audio::AudioRecordingManager;
-use managers::history::HistoryManager;
-use managers::model::ModelManager;
+use managers::output::OutputRouter;
 use managers::transcription::TranscriptionManager;
+use managers::wake_word::WakeWordManager;
 #[cfg(unix)]
 use signal_hook::consts::{SIGUSR1, SIGUSR2};
 #[cfg(unix)]
@@ -106,6 +129,17 @@ fn show_main_window(app: &AppHandle) {
     }
 }
 
+/// Benchmark the comma-separated `models` (the selected model when empty),
+/// print the results as JSON and exit.
+fn run_cli_benchmark(app_handle: AppHandle, models: &str) {
+    let models = models.to_string();
+    std::thread::spawn(move || {
+        let benchmarker = app_handle.state::<Arc<benchmark::Benchmarker>>();
+        let exit_code = benchmarker.run_cli(&models);
+        app_handle.exit(exit_code);
+    });
+}
+
 fn initialize_core_logic(app_handle: &AppHandle) {
     // Note: Enigo (keyboard/mouse simulation) is NOT initialized here.
     // The frontend is responsible for calling the `initialize_enigo` command
@@ -116,31 +150,55 @@ fn initialize_core_logic(app_handle: &AppHandle) {
     let recording_manager = Arc::new(
         AudioRecordingManager::new(app_handle).expect("Failed to initialize recording manager"),
     );
-    let model_manager =
-        Arc::new(ModelManager::new(app_handle).expect("Failed to initialize model manager"));
-    let transcription_manager = Arc::new(
-        TranscriptionManager::new(app_handle, model_manager.clone())
-            .expect("Failed to initialize transcription manager"),
-    );
-    let history_manager =
-        Arc::new(HistoryManager::new(app_handle).expect("Failed to initialize history manager"));
+    // Deployment config (handy.toml / HANDY_* variables); a broken config
+    // shouldn't keep the desktop app from starting
+    let host = Host::from(app_handle);
+    let config = host
+        .app_data_dir()
+        .and_then(|dir| config::Config::load(&dir))
+        .unwrap_or_else(|e| {
+            log::error!("Ignoring API configuration: {:#}", e);
+            config::Config::default()
+        });
+    let services = services::Services::new(&host, config)
+        .unwrap_or_else(|e| panic!("Failed to initialize core services: {:#}", e));
 
     // Add managers to Tauri's managed state
     app_handle.manage(recording_manager.clone());
-    app_handle.manage(model_manager.clone());
-    app_handle.manage(transcription_manager.clone());
-    app_handle.manage(history_manager.clone());
-
-    // Start the REST API server (default port 8720, override with HANDY_API_PORT)
-    let port: u16 = std::env::var("HANDY_API_PORT")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(8720);
-    api::start_api_server(
-        transcription_manager.clone(),
-        model_manager.clone(),
-        port,
-    );
+    app_handle.manage(services.model_manager.clone());
+    app_handle.manage(services.transcription_manager.clone());
+    app_handle.manage(services.history_manager.clone());
+    app_handle.manage(services.benchmarker.clone());
+    app_handle.manage(services.api_server.clone());
+    app_handle.manage(services.events.clone());
+    app_handle.manage(services.meeting.clone());
+    app_handle.manage(Arc::new(OutputRouter::new(&services.config.output)));
+    let wake_word = Arc::new(WakeWordManager::new(
+        app_handle,
+        services.model_manager.models_dir(),
+    ));
+    app_handle.manage(wake_word.clone());
+
+    // Start the REST API server. The port comes from settings (default 8720)
+    // unless the config sets one.
+    let settings = settings::get_settings(app_handle);
+    if settings.wake_word_enabled {
+        let settings = settings.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = wake_word.configure(&settings).await {
+                log::error!("Failed to start the wake word: {}", e);
+            }
+        });
+    }
+    if settings.api_server_enabled {
+        let port = services.api_port(&settings);
+        let api_server = services.api_server.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = api_server.start(port).await {
+                log::error!("{}", e);
+            }
+        });
+    }
 
     // Note: Shortcuts are NOT initialized here.
     // The frontend is responsible for calling the `initialize_shortcuts` command
@@ -194,6 +252,9 @@ fn initialize_core_logic(app_handle: &AppHandle) {
             "copy_last_transcript" => {
                 tray::copy_last_transcript(app);
             }
+            "meeting" => {
+                tray::toggle_meeting(app);
+            }
             "unload_model" => {
                 let transcription_manager = app.state::<Arc<TranscriptionManager>>();
                 if !transcription_manager.is_model_loaded() {
@@ -263,6 +324,12 @@ fn trigger_update_check(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Run the transcription API without Tauri (`--headless`) and return the
+/// process exit code.
+pub fn run_headless(cli_args: CliArgs) -> i32 {
+    headless::run(cli_args)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run(cli_args: CliArgs) {
     // Detect portable mode before anything else
@@ -282,6 +349,7 @@ pub fn run(cli_args: CliArgs) {
         shortcut::change_start_hidden_setting,
         shortcut::change_autostart_setting,
         shortcut::change_translate_to_english_setting,
+        shortcut::change_suppress_hallucinations_setting,
         shortcut::change_selected_language_setting,
         shortcut::change_overlay_position_setting,
         shortcut::change_debug_mode_setting,
@@ -290,6 +358,7 @@ pub fn run(cli_args: CliArgs) {
         shortcut::get_available_typing_tools,
         shortcut::change_typing_tool_setting,
         shortcut::change_external_script_path_setting,
+        shortcut::change_ffmpeg_path_setting,
         shortcut::change_clipboard_handling_setting,
         shortcut::change_auto_submit_setting,
         shortcut::change_auto_submit_key_setting,
@@ -305,6 +374,10 @@ pub fn run(cli_args: CliArgs) {
         shortcut::delete_post_process_prompt,
         shortcut::set_post_process_selected_prompt,
         shortcut::update_custom_words,
+        shortcut::update_text_replacements,
+        shortcut::change_voice_commands_setting,
+        shortcut::update_voice_command_phrases,
+        shortcut::update_app_profiles,
         shortcut::suspend_binding,
         shortcut::resume_binding,
         shortcut::change_mute_while_recording_setting,
@@ -331,10 +404,12 @@ pub fn run(cli_args: CliArgs) {
         commands::initialize_shortcuts,
         commands::models::get_available_models,
         commands::models::get_model_info,
+        commands::models::get_model_catalog,
         commands::models::download_model,
         commands::models::delete_model,
         commands::models::cancel_download,
         commands::models::set_active_model,
+        commands::models::change_auto_select_model_setting,
         commands::models::get_current_model,
         commands::models::get_transcription_model_status,
         commands::models::is_model_loading,
@@ -342,9 +417,16 @@ pub fn run(cli_args: CliArgs) {
         commands::models::has_any_models_or_downloads,
         commands::audio::update_microphone_mode,
         commands::audio::get_microphone_mode,
+        commands::audio::change_wake_word_setting,
+        commands::audio::change_wake_word_phrase_setting,
+        commands::audio::change_wake_word_threshold_setting,
         commands::audio::get_available_microphones,
         commands::audio::set_selected_microphone,
         commands::audio::get_selected_microphone,
+        commands::audio::update_recording_source,
+        commands::audio::get_available_loopback_devices,
+        commands::audio::set_selected_loopback_device,
+        commands::audio::get_selected_loopback_device,
         commands::audio::get_available_output_devices,
         commands::audio::set_selected_output_device,
         commands::audio::get_selected_output_device,
@@ -356,6 +438,12 @@ pub fn run(cli_args: CliArgs) {
         commands::transcription::set_model_unload_timeout,
         commands::transcription::get_model_load_status,
         commands::transcription::unload_model_manually,
+        commands::api::get_api_server_status,
+        commands::api::start_api_server,
+        commands::api::stop_api_server,
+        commands::api::restart_api_server,
+        commands::api::change_api_server_enabled_setting,
+        commands::api::change_api_server_port_setting,
         commands::history::get_history_entries,
         commands::history::toggle_history_entry_saved,
         commands::history::get_audio_file_path,
@@ -420,6 +508,8 @@ pub fn run(cli_args: CliArgs) {
                 signal_handle::send_transcription_input(app, "transcribe_with_post_process", "CLI");
             } else if args.iter().any(|a| a == "--cancel") {
                 crate::utils::cancel_current_operation(app);
+            } else if args.iter().any(|a| a.starts_with("--benchmark")) {
+                log::warn!("--benchmark ignored: Handy is already running, quit it first");
             } else {
                 show_main_window(app);
             }
@@ -473,6 +563,12 @@ pub fn run(cli_args: CliArgs) {
 
             initialize_core_logic(&app_handle);
 
+            // --benchmark runs without showing the window and exits when done
+            if let Some(models) = cli_args.benchmark.as_deref() {
+                run_cli_benchmark(app_handle, models);
+                return Ok(());
+            }
+
             // Hide tray icon if --no-tray was passed
             if cli_args.no_tray {
                 tray::set_tray_visibility(&app_handle, false);
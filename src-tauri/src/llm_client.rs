@@ -47,6 +47,11 @@ struct ChatMessageResponse {
     content: Option<String>,
 }
 
+/// Strip invisible Unicode characters that some LLMs may insert
+pub fn strip_invisible_chars(s: &str) -> String {
+    s.replace(['\u{200B}', '\u{200C}', '\u{200D}', '\u{FEFF}'], "")
+}
+
 /// Build headers for API requests based on provider type
 fn build_headers(provider: &PostProcessProvider, api_key: &str) -> Result<HeaderMap, String> {
     let mut headers = HeaderMap::new();
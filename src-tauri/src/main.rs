@@ -7,6 +7,10 @@ use handy_app_lib::CliArgs;
 fn main() {
     let cli_args = CliArgs::parse();
 
+    if cli_args.headless || cli_args.mcp || cli_args.manages_api_keys() {
+        std::process::exit(handy_app_lib::run_headless(cli_args));
+    }
+
     #[cfg(target_os = "linux")]
     {
         // DMABUF renderer causes crashes on various GPU/display server configurations
@@ -0,0 +1,398 @@
+//! API keys for sharing one server between several people or services.
+//! Each key has a name, a set of scopes and usage counters. Only a SHA-256
+//! hash of the key is stored; the key itself is shown once, when created.
+
+use anyhow::{bail, Result};
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use chrono::Utc;
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use rusqlite_migration::{Migrations, M};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use utoipa::ToSchema;
+
+use crate::host::Host;
+
+static MIGRATIONS: &[M] = &[M::up(
+    "CREATE TABLE api_keys (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        key_hash TEXT NOT NULL UNIQUE,
+        prefix TEXT NOT NULL,
+        scopes TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        revoked_at INTEGER,
+        last_used_at INTEGER,
+        requests INTEGER NOT NULL DEFAULT 0,
+        failed_requests INTEGER NOT NULL DEFAULT 0,
+        audio_seconds REAL NOT NULL DEFAULT 0
+    );",
+)];
+
+/// Columns selected for an `ApiKey`, in the order `key_from_row` expects.
+const KEY_COLUMNS: &str = "id, name, prefix, scopes, created_at, revoked_at, last_used_at, requests, failed_requests, audio_seconds";
+
+/// Start of every key, so leaked keys are easy to recognize.
+const KEY_PREFIX: &str = "hk_";
+/// Characters after `KEY_PREFIX` kept to tell keys apart in listings.
+const SHOWN_CHARS: usize = 8;
+
+/// What a key may do. Every key may read the server status and model list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    /// Transcribe, upload audio, detect languages and run dictation sessions,
+    /// over REST or MCP.
+    Transcribe,
+    /// Download, unload and benchmark models.
+    Models,
+    /// Read the transcription history and subscribe to dictation events.
+    History,
+    /// Everything, including managing keys and text replacements.
+    Admin,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 4] = [
+        ApiScope::Transcribe,
+        ApiScope::Models,
+        ApiScope::History,
+        ApiScope::Admin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::Transcribe => "transcribe",
+            ApiScope::Models => "models",
+            ApiScope::History => "history",
+            ApiScope::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str() == value)
+    }
+
+    /// Parse comma-separated scope names.
+    pub fn parse_list(value: &str) -> Result<Vec<Self>> {
+        let mut scopes = Vec::new();
+        for name in value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            match Self::parse(name) {
+                Some(scope) if !scopes.contains(&scope) => scopes.push(scope),
+                Some(_) => {}
+                None => bail!(
+                    "Unknown scope '{}'; expected one of transcribe, models, history, admin",
+                    name
+                ),
+            }
+        }
+        Ok(scopes)
+    }
+}
+
+/// Requests made with a key.
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+pub struct ApiKeyUsage {
+    pub requests: i64,
+    /// Requests answered with an error status.
+    pub failed_requests: i64,
+    /// Audio transcribed with the key.
+    pub audio_seconds: f64,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    /// The first characters of the key, e.g. "hk_3f9a0c1d".
+    pub prefix: String,
+    pub scopes: Vec<ApiScope>,
+    /// Unix timestamps in seconds.
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<i64>,
+    pub usage: ApiKeyUsage,
+}
+
+impl ApiKey {
+    /// Whether the key grants `scope`; admin keys grant every scope.
+    pub fn allows(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&scope) || self.scopes.contains(&ApiScope::Admin)
+    }
+}
+
+/// Keys live in `api_keys.db` in the data directory and are read on every
+/// request, so keys created or revoked with the CLI while the app is
+/// running take effect right away.
+pub struct ApiKeyManager {
+    db_path: PathBuf,
+}
+
+impl ApiKeyManager {
+    pub fn new(host: &Host) -> Result<Self> {
+        let db_path = host.app_data_dir()?.join("api_keys.db");
+        let mut conn = Connection::open(&db_path)?;
+        Migrations::new(MIGRATIONS.to_vec()).to_latest(&mut conn)?;
+        Ok(Self { db_path })
+    }
+
+    fn get_connection(&self) -> Result<Connection> {
+        Ok(Connection::open(&self.db_path)?)
+    }
+
+    /// Whether any key can be used; without one, and without the
+    /// `auth_token`, the API is open.
+    pub fn has_active_keys(&self) -> Result<bool> {
+        let conn = self.get_connection()?;
+        Ok(conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM api_keys WHERE revoked_at IS NULL)",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// The active key `secret` belongs to.
+    pub fn authenticate(&self, secret: &str) -> Result<Option<ApiKey>> {
+        let conn = self.get_connection()?;
+        Self::authenticate_with_conn(&conn, secret)
+    }
+
+    fn authenticate_with_conn(conn: &Connection, secret: &str) -> Result<Option<ApiKey>> {
+        let key = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM api_keys WHERE key_hash = ?1 AND revoked_at IS NULL",
+                    KEY_COLUMNS
+                ),
+                [hash_key(secret)],
+                key_from_row,
+            )
+            .optional()?;
+        Ok(key)
+    }
+
+    /// Create a key and return it with its secret, which isn't stored.
+    pub fn create(&self, name: &str, scopes: &[ApiScope]) -> Result<(ApiKey, String)> {
+        let conn = self.get_connection()?;
+        let created = Self::create_with_conn(&conn, name, scopes)?;
+        info!(
+            "Created API key {} ({}) with scopes {:?}",
+            created.0.id, created.0.prefix, created.0.scopes
+        );
+        Ok(created)
+    }
+
+    fn create_with_conn(
+        conn: &Connection,
+        name: &str,
+        scopes: &[ApiScope],
+    ) -> Result<(ApiKey, String)> {
+        if name.trim().is_empty() {
+            bail!("API key name is empty");
+        }
+        if scopes.is_empty() {
+            bail!("API key needs at least one scope");
+        }
+
+        let mut random = [0u8; 24];
+        OsRng.fill_bytes(&mut random);
+        let secret = format!("{}{}", KEY_PREFIX, hex(&random));
+        let prefix = secret[..KEY_PREFIX.len() + SHOWN_CHARS].to_string();
+        let scope_names: Vec<&str> = scopes.iter().map(ApiScope::as_str).collect();
+
+        conn.execute(
+            "INSERT INTO api_keys (name, key_hash, prefix, scopes, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                name.trim(),
+                hash_key(&secret),
+                prefix,
+                scope_names.join(","),
+                Utc::now().timestamp()
+            ],
+        )?;
+        let key = Self::get_with_conn(conn, conn.last_insert_rowid())?
+            .expect("API key was just inserted");
+        Ok((key, secret))
+    }
+
+    /// All keys, including revoked ones, oldest first.
+    pub fn list(&self) -> Result<Vec<ApiKey>> {
+        let conn = self.get_connection()?;
+        Self::list_with_conn(&conn)
+    }
+
+    fn list_with_conn(conn: &Connection) -> Result<Vec<ApiKey>> {
+        let mut stmt =
+            conn.prepare(&format!("SELECT {} FROM api_keys ORDER BY id", KEY_COLUMNS))?;
+        let rows = stmt.query_map([], key_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn get_with_conn(conn: &Connection, id: i64) -> Result<Option<ApiKey>> {
+        let key = conn
+            .query_row(
+                &format!("SELECT {} FROM api_keys WHERE id = ?1", KEY_COLUMNS),
+                [id],
+                key_from_row,
+            )
+            .optional()?;
+        Ok(key)
+    }
+
+    /// Revoke a key so it is rejected from now on. Returns false when there
+    /// is no such active key.
+    pub fn revoke(&self, id: i64) -> Result<bool> {
+        let conn = self.get_connection()?;
+        let revoked = Self::revoke_with_conn(&conn, id)?;
+        if revoked {
+            info!("Revoked API key {}", id);
+        }
+        Ok(revoked)
+    }
+
+    fn revoke_with_conn(conn: &Connection, id: i64) -> Result<bool> {
+        let changed = conn.execute(
+            "UPDATE api_keys SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+            params![Utc::now().timestamp(), id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Count a request made with key `id`.
+    pub fn record_usage(&self, id: i64, failed: bool, audio_seconds: f64) -> Result<()> {
+        let conn = self.get_connection()?;
+        Self::record_usage_with_conn(&conn, id, failed, audio_seconds)
+    }
+
+    /// Add audio transcribed for a request with key `id` that was already
+    /// counted, such as an MCP tool call answered after its POST.
+    pub fn record_audio(&self, id: i64, audio_seconds: f64) -> Result<()> {
+        let conn = self.get_connection()?;
+        Self::record_audio_with_conn(&conn, id, audio_seconds)
+    }
+
+    fn record_audio_with_conn(conn: &Connection, id: i64, audio_seconds: f64) -> Result<()> {
+        conn.execute(
+            "UPDATE api_keys SET audio_seconds = audio_seconds + ?1 WHERE id = ?2",
+            params![audio_seconds, id],
+        )?;
+        Ok(())
+    }
+
+    fn record_usage_with_conn(
+        conn: &Connection,
+        id: i64,
+        failed: bool,
+        audio_seconds: f64,
+    ) -> Result<()> {
+        conn.execute(
+            "UPDATE api_keys
+             SET requests = requests + 1,
+                 failed_requests = failed_requests + ?1,
+                 audio_seconds = audio_seconds + ?2,
+                 last_used_at = ?3
+             WHERE id = ?4",
+            params![failed as i64, audio_seconds, Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+}
+
+fn key_from_row(row: &Row) -> rusqlite::Result<ApiKey> {
+    let scopes: String = row.get("scopes")?;
+    Ok(ApiKey {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        prefix: row.get("prefix")?,
+        scopes: scopes.split(',').filter_map(ApiScope::parse).collect(),
+        created_at: row.get("created_at")?,
+        revoked_at: row.get("revoked_at")?,
+        last_used_at: row.get("last_used_at")?,
+        usage: ApiKeyUsage {
+            requests: row.get("requests")?,
+            failed_requests: row.get("failed_requests")?,
+            audio_seconds: row.get("audio_seconds")?,
+        },
+    })
+}
+
+fn hash_key(secret: &str) -> String {
+    hex(&Sha256::digest(secret.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_conn() -> Connection {
+        let mut conn = Connection::open_in_memory().expect("open in-memory db");
+        Migrations::new(MIGRATIONS.to_vec())
+            .to_latest(&mut conn)
+            .expect("apply migrations");
+        conn
+    }
+
+    #[test]
+    fn creates_checks_and_revokes_keys() {
+        let conn = setup_conn();
+        let (key, secret) =
+            ApiKeyManager::create_with_conn(&conn, "ci", &[ApiScope::Transcribe]).unwrap();
+        assert!(secret.starts_with(KEY_PREFIX));
+        assert!(secret.starts_with(&key.prefix));
+        assert!(key.allows(ApiScope::Transcribe));
+        assert!(!key.allows(ApiScope::History));
+
+        let found = ApiKeyManager::authenticate_with_conn(&conn, &secret).unwrap();
+        assert_eq!(found.map(|k| k.id), Some(key.id));
+        assert!(ApiKeyManager::authenticate_with_conn(&conn, "hk_guess")
+            .unwrap()
+            .is_none());
+
+        ApiKeyManager::record_usage_with_conn(&conn, key.id, false, 12.5).unwrap();
+        ApiKeyManager::record_usage_with_conn(&conn, key.id, true, 0.0).unwrap();
+        ApiKeyManager::record_audio_with_conn(&conn, key.id, 2.5).unwrap();
+        let listed = ApiKeyManager::list_with_conn(&conn).unwrap();
+        assert_eq!(listed[0].usage.requests, 2);
+        assert_eq!(listed[0].usage.failed_requests, 1);
+        assert_eq!(listed[0].usage.audio_seconds, 15.0);
+        assert!(listed[0].last_used_at.is_some());
+
+        assert!(ApiKeyManager::revoke_with_conn(&conn, key.id).unwrap());
+        assert!(!ApiKeyManager::revoke_with_conn(&conn, key.id).unwrap());
+        assert!(ApiKeyManager::authenticate_with_conn(&conn, &secret)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn parses_scope_lists() {
+        assert_eq!(
+            ApiScope::parse_list("transcribe, history,transcribe").unwrap(),
+            vec![ApiScope::Transcribe, ApiScope::History]
+        );
+        assert!(ApiScope::parse_list("transcribe,root").is_err());
+        let admin = ApiKey {
+            id: 1,
+            name: "admin".to_string(),
+            prefix: String::new(),
+            scopes: vec![ApiScope::Admin],
+            created_at: 0,
+            revoked_at: None,
+            last_used_at: None,
+            usage: ApiKeyUsage::default(),
+        };
+        assert!(admin.allows(ApiScope::Models));
+    }
+}
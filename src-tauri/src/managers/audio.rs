@@ -1,6 +1,8 @@
+use crate::audio_toolkit::audio::loopback;
 use crate::audio_toolkit::{list_input_devices, vad::SmoothedVad, AudioRecorder, SileroVad};
 use crate::helpers::clamshell;
-use crate::settings::{get_settings, AppSettings};
+use crate::managers::wake_word::WakeWordManager;
+use crate::settings::{get_settings, AppSettings, RecordingSource};
 use crate::utils;
 use log::{debug, error, info};
 use std::sync::{Arc, Mutex};
@@ -112,6 +114,16 @@ pub enum MicrophoneMode {
     OnDemand,
 }
 
+/// Keep the microphone open between recordings when it's always on or the
+/// wake word is listening.
+pub fn microphone_mode(settings: &AppSettings) -> MicrophoneMode {
+    if settings.always_on_microphone || settings.wake_word_enabled {
+        MicrophoneMode::AlwaysOn
+    } else {
+        MicrophoneMode::OnDemand
+    }
+}
+
 /* ──────────────────────────────────────────────────────────────── */
 
 fn create_audio_recorder(
@@ -132,6 +144,14 @@ fn create_audio_recorder(
             move |levels| {
                 utils::emit_levels(&app_handle, &levels);
             }
+        })
+        .with_frame_callback({
+            let app_handle = app_handle.clone();
+            move |frame| {
+                if let Some(wake_word) = app_handle.try_state::<Arc<WakeWordManager>>() {
+                    wake_word.on_frame(frame);
+                }
+            }
         });
 
     Ok(recorder)
@@ -149,18 +169,16 @@ pub struct AudioRecordingManager {
     is_open: Arc<Mutex<bool>>,
     is_recording: Arc<Mutex<bool>>,
     did_mute: Arc<Mutex<bool>>,
+    /// Input device used instead of the selected one for the current
+    /// recording.
+    device_override: Arc<Mutex<Option<String>>>,
 }
 
 impl AudioRecordingManager {
     /* ---------- construction ------------------------------------------------ */
 
     pub fn new(app: &tauri::AppHandle) -> Result<Self, anyhow::Error> {
-        let settings = get_settings(app);
-        let mode = if settings.always_on_microphone {
-            MicrophoneMode::AlwaysOn
-        } else {
-            MicrophoneMode::OnDemand
-        };
+        let mode = microphone_mode(&get_settings(app));
 
         let manager = Self {
             state: Arc::new(Mutex::new(RecordingState::Idle)),
@@ -171,6 +189,7 @@ impl AudioRecordingManager {
             is_open: Arc::new(Mutex::new(false)),
             is_recording: Arc::new(Mutex::new(false)),
             did_mute: Arc::new(Mutex::new(false)),
+            device_override: Arc::new(Mutex::new(None)),
         };
 
         // Always-on?  Open immediately.
@@ -183,7 +202,9 @@ impl AudioRecordingManager {
 
     /* ---------- helper methods --------------------------------------------- */
 
-    fn get_effective_microphone_device(&self, settings: &AppSettings) -> Option<cpal::Device> {
+    pub fn get_effective_microphone_device(&self, settings: &AppSettings) -> Option<cpal::Device> {
+        let device_override = self.device_override.lock().unwrap().clone();
+
         // Check if we're in clamshell mode and have a clamshell microphone configured
         let use_clamshell_mic = if let Ok(is_clamshell) = clamshell::is_clamshell() {
             is_clamshell && settings.clamshell_microphone.is_some()
@@ -191,7 +212,9 @@ impl AudioRecordingManager {
             false
         };
 
-        let device_name = if use_clamshell_mic {
+        let device_name = if let Some(device_name) = device_override.as_ref() {
+            device_name
+        } else if use_clamshell_mic {
             settings.clamshell_microphone.as_ref().unwrap()
         } else {
             settings.selected_microphone.as_ref()?
@@ -210,6 +233,24 @@ impl AudioRecordingManager {
         }
     }
 
+    pub fn get_effective_loopback_device(&self, settings: &AppSettings) -> Option<cpal::Device> {
+        let Some(device_name) = settings.selected_loopback_device.as_ref() else {
+            return loopback::default_loopback_device();
+        };
+
+        match loopback::list_loopback_devices() {
+            Ok(devices) => devices
+                .into_iter()
+                .find(|d| d.name == *device_name)
+                .map(|d| d.device)
+                .or_else(loopback::default_loopback_device),
+            Err(e) => {
+                debug!("Failed to list loopback devices, using default: {}", e);
+                loopback::default_loopback_device()
+            }
+        }
+    }
+
     /* ---------- microphone life-cycle -------------------------------------- */
 
     /// Applies mute if mute_while_recording is enabled and stream is open
@@ -217,7 +258,11 @@ impl AudioRecordingManager {
         let settings = get_settings(&self.app_handle);
         let mut did_mute_guard = self.did_mute.lock().unwrap();
 
-        if settings.mute_while_recording && *self.is_open.lock().unwrap() {
+        // Muting the output would silence the system audio being recorded
+        if settings.mute_while_recording
+            && settings.recording_source == RecordingSource::Microphone
+            && *self.is_open.lock().unwrap()
+        {
             set_mute(true);
             *did_mute_guard = true;
             debug!("Mute applied");
@@ -264,13 +309,26 @@ impl AudioRecordingManager {
             )?);
         }
 
-        // Get the selected device from settings, considering clamshell mode
         let settings = get_settings(&self.app_handle);
-        let selected_device = self.get_effective_microphone_device(&settings);
 
         if let Some(rec) = recorder_opt.as_mut() {
-            rec.open(selected_device)
-                .map_err(|e| anyhow::anyhow!("Failed to open recorder: {}", e))?;
+            match settings.recording_source {
+                RecordingSource::Microphone => {
+                    // Get the selected device from settings, considering clamshell mode
+                    let selected_device = self.get_effective_microphone_device(&settings);
+                    rec.open(selected_device)
+                        .map_err(|e| anyhow::anyhow!("Failed to open recorder: {}", e))?;
+                }
+                RecordingSource::SystemAudio => {
+                    let device =
+                        self.get_effective_loopback_device(&settings)
+                            .ok_or_else(|| {
+                                anyhow::anyhow!("No system audio (loopback) device available")
+                            })?;
+                    rec.open_loopback(device)
+                        .map_err(|e| anyhow::anyhow!("Failed to open system audio: {}", e))?;
+                }
+            }
         }
 
         *open_flag = true;
@@ -361,6 +419,34 @@ impl AudioRecordingManager {
         }
     }
 
+    /// Like [`Self::try_start_recording`], but from the microphone named
+    /// `device` instead of the selected one, until the recording ends.
+    pub fn try_start_recording_from(&self, binding_id: &str, device: &str) -> Result<(), String> {
+        if self.is_recording() {
+            return Err("Already recording".to_string());
+        }
+        *self.device_override.lock().unwrap() = Some(device.to_string());
+        // An always-on stream is already open on the selected device
+        if let Err(e) = self.update_selected_device() {
+            self.clear_device_override();
+            return Err(format!("{e}"));
+        }
+        let result = self.try_start_recording(binding_id);
+        if result.is_err() {
+            self.clear_device_override();
+        }
+        result
+    }
+
+    /// Go back to the selected device after a recording from another one.
+    fn clear_device_override(&self) {
+        if self.device_override.lock().unwrap().take().is_some() {
+            if let Err(e) = self.update_selected_device() {
+                error!("Failed to reopen the selected microphone: {e}");
+            }
+        }
+    }
+
     pub fn update_selected_device(&self) -> Result<(), anyhow::Error> {
         // If currently open, restart the microphone stream to use the new device
         if *self.is_open.lock().unwrap() {
@@ -399,6 +485,7 @@ impl AudioRecordingManager {
                 if matches!(*self.mode.lock().unwrap(), MicrophoneMode::OnDemand) {
                     self.stop_microphone_stream();
                 }
+                self.clear_device_override();
 
                 // Pad if very short
                 let s_len = samples.len();
@@ -439,6 +526,7 @@ impl AudioRecordingManager {
             if matches!(*self.mode.lock().unwrap(), MicrophoneMode::OnDemand) {
                 self.stop_microphone_stream();
             }
+            self.clear_device_override();
         }
     }
 }
@@ -0,0 +1,155 @@
+//! Built-in model catalog: memory needs, expected speed and license of each
+//! model Handy can download, embedded from `model_catalog.json`.
+//!
+//! The catalog adds to `ModelInfo` (name, engine, languages, size) and is
+//! used to warn before a model is loaded on a machine without enough RAM.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::managers::hardware::{self, HardwareInfo};
+use crate::managers::model::{EngineType, ModelInfo};
+
+/// A model may use at most this share of the machine's RAM before a warning.
+const MAX_MEMORY_SHARE: f64 = 0.75;
+
+static CATALOG: Lazy<HashMap<String, CatalogEntry>> = Lazy::new(|| {
+    serde_json::from_str(include_str!("model_catalog.json")).expect("Invalid model catalog")
+});
+
+/// Expected real-time factor (processing time / audio duration).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RtfTier {
+    /// Below 0.1
+    Fast,
+    /// 0.1 to 0.5
+    Medium,
+    /// Above 0.5
+    Slow,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct CatalogEntry {
+    pub license: String,
+    /// Approximate peak RAM while transcribing on the CPU
+    pub ram_mb: u64,
+    /// Approximate GPU memory when the engine runs on the GPU; `None` for
+    /// CPU-only engines
+    pub vram_mb: Option<u64>,
+    /// On a recent 4-core laptop CPU
+    pub rtf_cpu: RtfTier,
+    /// With GPU acceleration, if the engine supports it
+    pub rtf_gpu: Option<RtfTier>,
+}
+
+#[derive(Debug, Clone, Serialize, Type, ToSchema)]
+pub struct CatalogModel {
+    pub id: String,
+    pub name: String,
+    pub engine: EngineType,
+    pub languages: Vec<String>,
+    pub size_mb: u64,
+    pub is_downloaded: bool,
+    #[serde(flatten)]
+    pub entry: CatalogEntry,
+    /// Set when the model likely won't fit this machine
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Type, ToSchema)]
+pub struct ModelCatalog {
+    pub hardware: HardwareInfo,
+    /// The model the "auto" mode picks for this machine and the selected
+    /// language
+    pub recommended: Option<String>,
+    /// Sorted by id
+    pub models: Vec<CatalogModel>,
+}
+
+pub fn entry(model_id: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.get(model_id)
+}
+
+/// Catalog entries for `models`, with fit warnings for this machine and
+/// the recommendation for `language`. Custom models have no entry and are
+/// left out.
+pub fn build(models: Vec<ModelInfo>, hardware: HardwareInfo, language: &str) -> ModelCatalog {
+    let total_memory_mb = hardware.total_memory_mb;
+    let recommended = hardware::recommend(&hardware, &models, language);
+    let mut models: Vec<CatalogModel> = models
+        .into_iter()
+        .filter_map(|info| {
+            let entry = entry(&info.id)?.clone();
+            Some(CatalogModel {
+                warning: fit_warning(&info.name, &entry, total_memory_mb),
+                id: info.id,
+                name: info.name,
+                engine: info.engine_type,
+                languages: info.supported_languages,
+                size_mb: info.size_mb,
+                is_downloaded: info.is_downloaded,
+                entry,
+            })
+        })
+        .collect();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+
+    ModelCatalog {
+        hardware,
+        recommended,
+        models,
+    }
+}
+
+/// Warning for loading `model` on this machine, if its catalog entry says
+/// it needs more memory than the machine can spare.
+pub fn check_fit(model: &ModelInfo) -> Option<String> {
+    fit_warning(&model.name, entry(&model.id)?, hardware::total_memory_mb())
+}
+
+/// Whether a model with `entry` leaves enough RAM for the rest of the
+/// machine. Unknown memory counts as enough.
+pub fn fits_memory(entry: &CatalogEntry, total_memory_mb: Option<u64>) -> bool {
+    match total_memory_mb {
+        Some(total) => entry.ram_mb as f64 <= total as f64 * MAX_MEMORY_SHARE,
+        None => true,
+    }
+}
+
+fn fit_warning(name: &str, entry: &CatalogEntry, total_memory_mb: Option<u64>) -> Option<String> {
+    let total = total_memory_mb?;
+    if fits_memory(entry, Some(total)) {
+        return None;
+    }
+    Some(format!(
+        "{} needs about {} MB of RAM, but this machine has {} MB. Expect swapping or a crash; a smaller model is recommended.",
+        name, entry.ram_mb, total
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_parses() {
+        let small = entry("small").expect("small is in the catalog");
+        assert_eq!(small.license, "MIT");
+        assert_eq!(small.rtf_gpu, Some(RtfTier::Fast));
+        assert!(entry("parakeet-tdt-0.6b-v3").unwrap().vram_mb.is_none());
+        assert!(entry("my-custom-model").is_none());
+    }
+
+    #[test]
+    fn test_fit_warning() {
+        let turbo = entry("turbo").unwrap();
+        assert!(fit_warning("Whisper Turbo", turbo, Some(16 * 1024)).is_none());
+        let warning = fit_warning("Whisper Turbo", turbo, Some(2048)).unwrap();
+        assert!(warning.contains("2200 MB"));
+        assert!(fit_warning("Whisper Turbo", turbo, None).is_none());
+    }
+}
@@ -0,0 +1,425 @@
+//! Hardware detection and the model recommendation for the "auto" model
+//! mode: the best built-in model that fits the machine's RAM and GPU.
+//!
+//! GPU memory is only known for NVIDIA cards (via `nvidia-smi`) and Apple
+//! Silicon, where the GPU shares system memory. Other GPUs are treated as
+//! absent, so the recommendation falls back to a CPU model.
+//!
+//! [`ProcessMemory`] measures what this process uses, so the memory taken by
+//! a model can be reported as the growth while it loaded.
+
+use log::debug;
+use serde::Serialize;
+use specta::Type;
+use utoipa::ToSchema;
+
+use crate::managers::catalog;
+use crate::managers::model::ModelInfo;
+
+/// GPU memory needed before Whisper models are preferred over Parakeet.
+const MIN_GPU_VRAM_MB: u64 = 4096;
+
+/// Share of Apple Silicon's unified memory the GPU can use.
+#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+const UNIFIED_VRAM_SHARE: f64 = 0.65;
+
+/// Preferred models with a usable GPU. Whisper runs on Vulkan and Metal.
+const GPU_MODELS: &[&str] = &["turbo", "large", "parakeet-tdt-0.6b-v3", "small"];
+
+/// Preferred models on the CPU. Parakeet int8 is both fast and accurate.
+const CPU_MODELS: &[&str] = &[
+    "parakeet-tdt-0.6b-v3",
+    "small",
+    "moonshine-base",
+    "moonshine-tiny-streaming-en",
+];
+
+/// Preferred models on CPUs without AVX2 or with few cores.
+const SLOW_CPU_MODELS: &[&str] = &[
+    "moonshine-base",
+    "moonshine-tiny-streaming-en",
+    "parakeet-tdt-0.6b-v3",
+    "small",
+];
+
+#[derive(Debug, Clone, Serialize, Type, ToSchema)]
+pub struct GpuInfo {
+    pub name: String,
+    pub vram_mb: u64,
+    /// The GPU shares system memory (Apple Silicon)
+    pub unified_memory: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Type, ToSchema)]
+pub struct HardwareInfo {
+    /// `None` if the platform doesn't report it
+    pub total_memory_mb: Option<u64>,
+    pub gpu: Option<GpuInfo>,
+    pub cpu_cores: usize,
+    /// Always `true` on non-x86 CPUs
+    pub avx2: bool,
+}
+
+impl HardwareInfo {
+    pub fn detect() -> Self {
+        let total_memory_mb = total_memory_mb();
+        let info = Self {
+            total_memory_mb,
+            gpu: detect_gpu(total_memory_mb),
+            cpu_cores: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            avx2: has_avx2(),
+        };
+        debug!("Detected hardware: {:?}", info);
+        info
+    }
+
+    fn has_fast_cpu(&self) -> bool {
+        self.avx2 && self.cpu_cores >= 4
+    }
+}
+
+/// Memory used by a loaded model, measured as the growth of this process's
+/// memory while it loaded.
+#[derive(Debug, Clone, PartialEq, Serialize, Type, ToSchema)]
+pub struct ModelMemory {
+    pub model_id: String,
+    /// Resident memory in MB. Includes GPU memory on Apple Silicon, which
+    /// shares system memory. `None` if the platform doesn't report it.
+    pub resident_mb: Option<u64>,
+    /// GPU memory in MB; only known for NVIDIA GPUs.
+    pub vram_mb: Option<u64>,
+}
+
+/// Memory in use by this process.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProcessMemory {
+    pub resident_mb: Option<u64>,
+    pub vram_mb: Option<u64>,
+}
+
+impl ProcessMemory {
+    pub fn measure() -> Self {
+        Self {
+            resident_mb: process_resident_mb(),
+            vram_mb: process_vram_mb(),
+        }
+    }
+
+    /// The memory `model_id` took, given the process's memory `before` it
+    /// was loaded.
+    pub fn attribute(self, before: ProcessMemory, model_id: &str) -> ModelMemory {
+        let growth = |after: Option<u64>, before: Option<u64>| {
+            after.map(|after| after.saturating_sub(before.unwrap_or(0)))
+        };
+        ModelMemory {
+            model_id: model_id.to_string(),
+            resident_mb: growth(self.resident_mb, before.resident_mb),
+            vram_mb: growth(self.vram_mb, before.vram_mb),
+        }
+    }
+}
+
+/// The best of `models` for this hardware that supports `language` ("auto"
+/// for any), or `None` if none of them fits.
+pub fn recommend(hardware: &HardwareInfo, models: &[ModelInfo], language: &str) -> Option<String> {
+    let usable_gpu = hardware
+        .gpu
+        .as_ref()
+        .filter(|gpu| gpu.vram_mb >= MIN_GPU_VRAM_MB);
+    let preferences = if usable_gpu.is_some() {
+        GPU_MODELS
+    } else if hardware.has_fast_cpu() {
+        CPU_MODELS
+    } else {
+        SLOW_CPU_MODELS
+    };
+
+    preferences
+        .iter()
+        .filter_map(|id| models.iter().find(|model| model.id == *id))
+        .find(|model| {
+            let Some(entry) = catalog::entry(&model.id) else {
+                return false;
+            };
+            let fits_gpu = match (usable_gpu, entry.vram_mb) {
+                (Some(gpu), Some(vram_mb)) => vram_mb <= gpu.vram_mb,
+                _ => true,
+            };
+            fits_gpu
+                && catalog::fits_memory(entry, hardware.total_memory_mb)
+                && supports_language(model, language)
+        })
+        .map(|model| model.id.clone())
+}
+
+fn supports_language(model: &ModelInfo, language: &str) -> bool {
+    language == "auto" || model.supported_languages.iter().any(|l| l == language)
+}
+
+/// Physical memory of the machine in MB.
+#[cfg(target_os = "linux")]
+pub fn total_memory_mb() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))
+        .and_then(|value| {
+            value
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+        .map(|kb| kb / 1024)
+}
+
+/// Physical memory of the machine in MB.
+#[cfg(target_os = "macos")]
+pub fn total_memory_mb() -> Option<u64> {
+    let output = std::process::Command::new("sysctl")
+        .args(["-n", "hw.memsize"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|bytes| bytes / (1024 * 1024))
+}
+
+/// Physical memory of the machine in MB.
+#[cfg(target_os = "windows")]
+pub fn total_memory_mb() -> Option<u64> {
+    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status = MEMORYSTATUSEX {
+        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+        ..Default::default()
+    };
+    // SAFETY: `status` is a properly sized MEMORYSTATUSEX
+    unsafe { GlobalMemoryStatusEx(&mut status) }.ok()?;
+    Some(status.ullTotalPhys / (1024 * 1024))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn total_memory_mb() -> Option<u64> {
+    None
+}
+
+/// Resident memory of this process in MB.
+fn process_resident_mb() -> Option<u64> {
+    memory_stats::memory_stats().map(|stats| stats.physical_mem as u64 / (1024 * 1024))
+}
+
+/// GPU memory this process uses on NVIDIA GPUs in MB, from `nvidia-smi`.
+/// `None` without one, or when the process isn't listed, e.g. in a
+/// container that can't see host process ids.
+#[cfg(not(target_os = "macos"))]
+fn process_vram_mb() -> Option<u64> {
+    let mut cmd = std::process::Command::new("nvidia-smi");
+    cmd.args([
+        "--query-compute-apps=pid,used_memory",
+        "--format=csv,noheader,nounits",
+    ]);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = cmd.output().ok().filter(|output| output.status.success())?;
+    parse_compute_apps(&String::from_utf8_lossy(&output.stdout), std::process::id())
+}
+
+/// Apple Silicon GPU memory is part of the resident memory.
+#[cfg(target_os = "macos")]
+fn process_vram_mb() -> Option<u64> {
+    None
+}
+
+/// Memory `pid` uses across all GPUs from `nvidia-smi --query-compute-apps`
+/// CSV output.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn parse_compute_apps(output: &str, pid: u32) -> Option<u64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (app_pid, used) = line.split_once(',')?;
+            if app_pid.trim().parse::<u32>().ok()? != pid {
+                return None;
+            }
+            // "[N/A]" on Windows without per-process accounting
+            used.trim().parse::<u64>().ok()
+        })
+        .reduce(|total, used| total + used)
+}
+
+#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+fn detect_gpu(total_memory_mb: Option<u64>) -> Option<GpuInfo> {
+    Some(GpuInfo {
+        name: "Apple Silicon".to_string(),
+        vram_mb: (total_memory_mb? as f64 * UNIFIED_VRAM_SHARE) as u64,
+        unified_memory: true,
+    })
+}
+
+#[cfg(not(all(target_os = "macos", target_arch = "aarch64")))]
+fn detect_gpu(_total_memory_mb: Option<u64>) -> Option<GpuInfo> {
+    let mut cmd = std::process::Command::new("nvidia-smi");
+    cmd.args([
+        "--query-gpu=name,memory.total",
+        "--format=csv,noheader,nounits",
+    ]);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = cmd.output().ok().filter(|output| output.status.success())?;
+    parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The GPU with the most memory from `nvidia-smi` CSV output.
+#[cfg_attr(all(target_os = "macos", target_arch = "aarch64"), allow(dead_code))]
+fn parse_nvidia_smi(output: &str) -> Option<GpuInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, vram) = line.rsplit_once(',')?;
+            Some(GpuInfo {
+                name: name.trim().to_string(),
+                vram_mb: vram.trim().parse().ok()?,
+                unified_memory: false,
+            })
+        })
+        .max_by_key(|gpu| gpu.vram_mb)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn has_avx2() -> bool {
+    std::arch::is_x86_feature_detected!("avx2")
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn has_avx2() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::model::EngineType;
+
+    fn model(id: &str, languages: &[&str]) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            filename: id.to_string(),
+            url: None,
+            size_mb: 0,
+            is_downloaded: true,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: false,
+            engine_type: EngineType::Whisper,
+            accuracy_score: 0.5,
+            speed_score: 0.5,
+            supports_translation: false,
+            is_recommended: false,
+            supported_languages: languages.iter().map(|l| l.to_string()).collect(),
+            is_custom: false,
+            sha256: None,
+        }
+    }
+
+    fn models() -> Vec<ModelInfo> {
+        vec![
+            model("small", &["en", "de", "ja"]),
+            model("turbo", &["en", "de", "ja"]),
+            model("parakeet-tdt-0.6b-v3", &["en", "de"]),
+            model("moonshine-base", &["en"]),
+        ]
+    }
+
+    fn hardware(memory_mb: u64, vram_mb: Option<u64>) -> HardwareInfo {
+        HardwareInfo {
+            total_memory_mb: Some(memory_mb),
+            gpu: vram_mb.map(|vram_mb| GpuInfo {
+                name: "Test GPU".to_string(),
+                vram_mb,
+                unified_memory: false,
+            }),
+            cpu_cores: 8,
+            avx2: true,
+        }
+    }
+
+    #[test]
+    fn test_recommend() {
+        let models = models();
+        // Turbo on a 12 GB GPU, Parakeet on the CPU
+        assert_eq!(
+            recommend(&hardware(16384, Some(12288)), &models, "auto").as_deref(),
+            Some("turbo")
+        );
+        assert_eq!(
+            recommend(&hardware(16384, None), &models, "auto").as_deref(),
+            Some("parakeet-tdt-0.6b-v3")
+        );
+        // A small GPU doesn't count
+        assert_eq!(
+            recommend(&hardware(16384, Some(2048)), &models, "auto").as_deref(),
+            Some("parakeet-tdt-0.6b-v3")
+        );
+        // Parakeet doesn't do Japanese
+        assert_eq!(
+            recommend(&hardware(16384, None), &models, "ja").as_deref(),
+            Some("small")
+        );
+        // Only Moonshine fits 1 GB of RAM
+        assert_eq!(
+            recommend(&hardware(1024, None), &models, "auto").as_deref(),
+            Some("moonshine-base")
+        );
+        assert_eq!(recommend(&hardware(1024, None), &models, "ja"), None);
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        let gpu =
+            parse_nvidia_smi("NVIDIA GeForce GTX 1050, 2048\nNVIDIA GeForce RTX 3060, 12288\n")
+                .unwrap();
+        assert_eq!(gpu.name, "NVIDIA GeForce RTX 3060");
+        assert_eq!(gpu.vram_mb, 12288);
+        assert!(parse_nvidia_smi("").is_none());
+    }
+
+    #[test]
+    fn test_model_memory() {
+        let output = "4242, 1500\n17, 800\n4242, 300\n99, [N/A]\n";
+        assert_eq!(parse_compute_apps(output, 4242), Some(1800));
+        assert_eq!(parse_compute_apps(output, 99), None);
+        assert_eq!(parse_compute_apps(output, 1), None);
+
+        let before = ProcessMemory {
+            resident_mb: Some(300),
+            vram_mb: None,
+        };
+        let after = ProcessMemory {
+            resident_mb: Some(1100),
+            vram_mb: Some(1800),
+        };
+        let memory = after.attribute(before, "small");
+        assert_eq!(memory.resident_mb, Some(800));
+        assert_eq!(memory.vram_mb, Some(1800));
+        assert_eq!(
+            ProcessMemory::default()
+                .attribute(after, "small")
+                .resident_mb,
+            None
+        );
+    }
+}
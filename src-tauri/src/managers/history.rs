@@ -1,15 +1,21 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use chrono::{DateTime, Local, Utc};
-use log::{debug, error, info};
-use rusqlite::{params, Connection, OptionalExtension};
+use log::{debug, error, info, warn};
+use rusqlite::types::{Type as SqlType, Value};
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use rusqlite_migration::{Migrations, M};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use std::fs;
 use std::path::PathBuf;
-use tauri::{AppHandle, Emitter, Manager};
+use std::sync::Mutex;
+use tempfile::NamedTempFile;
+use utoipa::ToSchema;
 
-use crate::audio_toolkit::save_wav_file;
+use crate::audio_toolkit::{encode_wav, save_wav_file};
+use crate::config::HistoryConfig;
+use crate::host::Host;
+use crate::managers::history_crypto::{new_salt, HistoryCipher, KeySource};
 
 /// Database migrations for transcription history.
 /// Each migration is applied in order. The library tracks which migrations
@@ -31,9 +37,103 @@ static MIGRATIONS: &[M] = &[
     ),
     M::up("ALTER TABLE transcription_history ADD COLUMN post_processed_text TEXT;"),
     M::up("ALTER TABLE transcription_history ADD COLUMN post_process_prompt TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN source TEXT NOT NULL DEFAULT 'app';"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN model_id TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN duration_ms INTEGER;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN segments TEXT;"),
+    // Full-text index over both texts, kept in sync by triggers. The rebuild
+    // indexes entries created before this migration.
+    M::up(
+        "CREATE VIRTUAL TABLE transcription_history_fts USING fts5(
+            transcription_text,
+            post_processed_text,
+            content='transcription_history',
+            content_rowid='id'
+        );
+        CREATE TRIGGER transcription_history_fts_insert AFTER INSERT ON transcription_history BEGIN
+            INSERT INTO transcription_history_fts (rowid, transcription_text, post_processed_text)
+            VALUES (new.id, new.transcription_text, new.post_processed_text);
+        END;
+        CREATE TRIGGER transcription_history_fts_delete AFTER DELETE ON transcription_history BEGIN
+            INSERT INTO transcription_history_fts (transcription_history_fts, rowid, transcription_text, post_processed_text)
+            VALUES ('delete', old.id, old.transcription_text, old.post_processed_text);
+        END;
+        CREATE TRIGGER transcription_history_fts_update AFTER UPDATE OF transcription_text, post_processed_text ON transcription_history BEGIN
+            INSERT INTO transcription_history_fts (transcription_history_fts, rowid, transcription_text, post_processed_text)
+            VALUES ('delete', old.id, old.transcription_text, old.post_processed_text);
+            INSERT INTO transcription_history_fts (rowid, transcription_text, post_processed_text)
+            VALUES (new.id, new.transcription_text, new.post_processed_text);
+        END;
+        INSERT INTO transcription_history_fts (transcription_history_fts) VALUES ('rebuild');",
+    ),
+    // Hash of uploaded audio, linking re-uploads of a recording to earlier
+    // transcriptions of it.
+    M::up(
+        "ALTER TABLE transcription_history ADD COLUMN audio_hash TEXT;
+        CREATE INDEX transcription_history_audio_hash ON transcription_history (audio_hash);",
+    ),
+    // Set once the history is encrypted: the passphrase salt (NULL for a
+    // keychain key) and `KEY_CHECK` encrypted with the key.
+    M::up(
+        "CREATE TABLE history_encryption (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            salt BLOB,
+            key_check BLOB NOT NULL
+        );",
+    ),
 ];
 
-#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+/// Encrypted into `history_encryption` to tell a wrong key from a right one.
+const KEY_CHECK: &[u8] = b"handy-history";
+
+/// Columns selected for a `HistoryEntry`, in the order `entry_from_row` expects.
+const ENTRY_COLUMNS: &str = "id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, source, model_id, duration_ms, segments, audio_hash";
+
+/// Where a transcription came from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HistorySource {
+    /// Dictated in the app with a shortcut.
+    #[default]
+    App,
+    /// Uploaded to the REST API.
+    Api,
+    /// Transcribed continuously in meeting mode.
+    Meeting,
+}
+
+impl HistorySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistorySource::App => "app",
+            HistorySource::Api => "api",
+            HistorySource::Meeting => "meeting",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "app" => Some(HistorySource::App),
+            "api" => Some(HistorySource::Api),
+            "meeting" => Some(HistorySource::Meeting),
+            _ => None,
+        }
+    }
+}
+
+/// A timestamped piece of a stored transcription. Times are in seconds.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, ToSchema)]
+pub struct HistorySegment {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+    /// Speaker or channel label, e.g. a multitrack speaker or a meeting
+    /// source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type, ToSchema)]
 pub struct HistoryEntry {
     pub id: i64,
     pub file_name: String,
@@ -43,18 +143,63 @@ pub struct HistoryEntry {
     pub transcription_text: String,
     pub post_processed_text: Option<String>,
     pub post_process_prompt: Option<String>,
+    pub source: HistorySource,
+    /// Model that produced the transcription, when known.
+    pub model_id: Option<String>,
+    /// Length of the transcribed audio in milliseconds.
+    pub duration_ms: Option<i64>,
+    /// Timestamped segments; empty for entries saved before segments were stored.
+    pub segments: Vec<HistorySegment>,
+    /// Hash of the uploaded audio for API transcriptions; equal for
+    /// re-uploads of the same file.
+    pub audio_hash: Option<String>,
+}
+
+/// A transcription to be added to the history.
+#[derive(Clone, Debug, Default)]
+pub struct TranscriptionRecord {
+    pub source: HistorySource,
+    pub transcription_text: String,
+    pub segments: Vec<HistorySegment>,
+    pub post_processed_text: Option<String>,
+    pub post_process_prompt: Option<String>,
+    pub model_id: Option<String>,
+    pub duration_ms: i64,
+    pub audio_hash: Option<String>,
+}
+
+/// Filters for [`HistoryManager::search_entries`]. Unset fields match everything.
+#[derive(Clone, Debug, Default)]
+pub struct HistoryQuery {
+    /// Words that must all appear in the original or post-processed text.
+    /// Each word also matches longer words it is a prefix of.
+    pub text: Option<String>,
+    pub source: Option<HistorySource>,
+    /// Unix timestamp (seconds), inclusive.
+    pub from: Option<i64>,
+    /// Unix timestamp (seconds), exclusive.
+    pub to: Option<i64>,
+    /// Only transcriptions of the audio with this hash.
+    pub audio_hash: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: usize,
 }
 
 pub struct HistoryManager {
-    app_handle: AppHandle,
+    host: Host,
     recordings_dir: PathBuf,
     db_path: PathBuf,
+    /// Key for transcripts and recordings when the history is encrypted.
+    cipher: Option<HistoryCipher>,
+    /// Decrypted copy of the recording last opened for playback; removed
+    /// when the next one is opened or the app exits.
+    playback: Mutex<Option<NamedTempFile>>,
 }
 
 impl HistoryManager {
-    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+    pub fn new(host: Host, config: &HistoryConfig) -> Result<Self> {
         // Create recordings directory in app data dir
-        let app_data_dir = crate::portable::app_data_dir(app_handle)?;
+        let app_data_dir = host.app_data_dir()?;
         let recordings_dir = app_data_dir.join("recordings");
         let db_path = app_data_dir.join("history.db");
 
@@ -64,18 +209,146 @@ impl HistoryManager {
             debug!("Created recordings directory: {:?}", recordings_dir);
         }
 
-        let manager = Self {
-            app_handle: app_handle.clone(),
+        let mut manager = Self {
+            host,
             recordings_dir,
             db_path,
+            cipher: None,
+            playback: Mutex::new(None),
         };
 
         // Initialize database and run migrations synchronously
         manager.init_database()?;
 
+        let key_source = KeySource::from_config(config)?;
+        let mut conn = manager.get_connection()?;
+        manager.cipher = Self::open_cipher(&mut conn, key_source.as_ref())?;
+        manager.encrypt_recordings();
+
         Ok(manager)
     }
 
+    /// The history key: checked against an encrypted database, or used to
+    /// encrypt a plaintext one when a key is configured for the first time.
+    fn open_cipher(
+        conn: &mut Connection,
+        key_source: Option<&KeySource>,
+    ) -> Result<Option<HistoryCipher>> {
+        let stored: Option<(Option<Vec<u8>>, Vec<u8>)> = conn
+            .query_row(
+                "SELECT salt, key_check FROM history_encryption WHERE id = 1",
+                [],
+                |row| Ok((row.get("salt")?, row.get("key_check")?)),
+            )
+            .optional()?;
+
+        match (stored, key_source) {
+            (None, None) => Ok(None),
+            (Some(_), None) => bail!(
+                "The history is encrypted; set [history] passphrase or keychain \
+                 (HANDY_HISTORY_PASSPHRASE / HANDY_HISTORY_KEYCHAIN) to open it"
+            ),
+            (Some((salt, key_check)), Some(key_source)) => {
+                let cipher = key_source.cipher(salt.as_deref(), false)?;
+                if cipher.decrypt(&key_check).ok().as_deref() != Some(KEY_CHECK) {
+                    bail!("Wrong key for the encrypted history");
+                }
+                Ok(Some(cipher))
+            }
+            (None, Some(key_source)) => {
+                let salt = matches!(key_source, KeySource::Passphrase(_)).then(new_salt);
+                let cipher = key_source.cipher(salt.as_ref().map(|salt| &salt[..]), true)?;
+                Self::encrypt_database(conn, &cipher, salt.as_ref().map(|salt| &salt[..]))?;
+                Ok(Some(cipher))
+            }
+        }
+    }
+
+    /// Encrypt the transcripts of a plaintext database and record the key.
+    /// The full-text index would keep the words in plaintext, so it is
+    /// emptied and encrypted history is searched by decrypting instead.
+    fn encrypt_database(
+        conn: &mut Connection,
+        cipher: &HistoryCipher,
+        salt: Option<&[u8]>,
+    ) -> Result<()> {
+        let tx = conn.transaction()?;
+        tx.execute_batch(
+            "DROP TRIGGER IF EXISTS transcription_history_fts_insert;
+             DROP TRIGGER IF EXISTS transcription_history_fts_delete;
+             DROP TRIGGER IF EXISTS transcription_history_fts_update;
+             INSERT INTO transcription_history_fts (transcription_history_fts) VALUES ('delete-all');",
+        )?;
+
+        let rows = {
+            let mut stmt = tx.prepare(
+                "SELECT id, transcription_text, post_processed_text, post_process_prompt, segments
+                 FROM transcription_history",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>("id")?,
+                    row.get::<_, String>("transcription_text")?,
+                    row.get::<_, Option<String>>("post_processed_text")?,
+                    row.get::<_, Option<String>>("post_process_prompt")?,
+                    row.get::<_, Option<String>>("segments")?,
+                ))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for (id, text, post_processed, prompt, segments) in &rows {
+            let seal_opt =
+                |text: &Option<String>| text.as_deref().map(|text| seal(Some(cipher), text));
+            tx.execute(
+                "UPDATE transcription_history
+                 SET transcription_text = ?1, post_processed_text = ?2, post_process_prompt = ?3, segments = ?4
+                 WHERE id = ?5",
+                params![
+                    seal(Some(cipher), text),
+                    seal_opt(post_processed),
+                    seal_opt(prompt),
+                    seal_opt(segments),
+                    id
+                ],
+            )?;
+        }
+        tx.execute(
+            "INSERT INTO history_encryption (id, salt, key_check) VALUES (1, ?1, ?2)",
+            params![salt, cipher.encrypt(KEY_CHECK)],
+        )?;
+        tx.commit()?;
+
+        // Overwrite the plaintext left in freed pages
+        conn.execute_batch("VACUUM")?;
+        info!("Encrypted {} history entries", rows.len());
+        Ok(())
+    }
+
+    /// Encrypt recordings saved before the history was encrypted. Runs on
+    /// every start so an interrupted migration is finished.
+    fn encrypt_recordings(&self) {
+        let Some(cipher) = &self.cipher else {
+            return;
+        };
+        let Ok(dir) = fs::read_dir(&self.recordings_dir) else {
+            return;
+        };
+        let mut encrypted = 0;
+        for path in dir.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "wav") {
+                continue;
+            }
+            match cipher.encrypt_file(&path) {
+                Ok(true) => encrypted += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Failed to encrypt recording {:?}: {}", path, e),
+            }
+        }
+        if encrypted > 0 {
+            info!("Encrypted {} recordings", encrypted);
+        }
+    }
+
     fn init_database(&self) -> Result<()> {
         info!("Initializing database at {:?}", self.db_path);
 
@@ -180,60 +453,125 @@ impl HistoryManager {
     pub async fn save_transcription(
         &self,
         audio_samples: Vec<f32>,
-        transcription_text: String,
-        post_processed_text: Option<String>,
-        post_process_prompt: Option<String>,
+        record: TranscriptionRecord,
     ) -> Result<()> {
         let timestamp = Utc::now().timestamp();
         let file_name = format!("handy-{}.wav", timestamp);
-        let title = self.format_timestamp_title(timestamp);
 
         // Save WAV file
         let file_path = self.recordings_dir.join(&file_name);
-        save_wav_file(file_path, &audio_samples).await?;
+        match &self.cipher {
+            Some(cipher) => cipher.write_file(&file_path, &encode_wav(&audio_samples)?)?,
+            None => save_wav_file(file_path, &audio_samples).await?,
+        }
+
+        self.save_record(file_name, timestamp, record)?;
+        Ok(())
+    }
+
+    /// Save a transcription to history without its audio, e.g. for API
+    /// uploads that may be long and are already stored by the client.
+    pub fn save_transcription_text(&self, record: TranscriptionRecord) -> Result<()> {
+        self.save_record(String::new(), Utc::now().timestamp(), record)?;
+        Ok(())
+    }
+
+    /// Save a transcription that is still growing, like a meeting's, without
+    /// its audio: without an `id` it's added as a new entry, otherwise entry
+    /// `id` gets its text, segments and duration. Returns the entry's id.
+    pub fn save_transcription_progress(
+        &self,
+        id: Option<i64>,
+        record: TranscriptionRecord,
+    ) -> Result<i64> {
+        let Some(id) = id else {
+            return self.save_record(String::new(), Utc::now().timestamp(), record);
+        };
+
+        let conn = self.get_connection()?;
+        let segments = serde_json::to_string(&record.segments)?;
+        conn.execute(
+            "UPDATE transcription_history SET transcription_text = ?1, segments = ?2, duration_ms = ?3, model_id = ?4 WHERE id = ?5",
+            params![
+                seal(self.cipher.as_ref(), &record.transcription_text),
+                seal(self.cipher.as_ref(), &segments),
+                record.duration_ms,
+                record.model_id,
+                id
+            ],
+        )?;
+
+        if let Err(e) = self.host.emit("history-updated", ()) {
+            error!("Failed to emit history-updated event: {}", e);
+        }
+        Ok(id)
+    }
+
+    fn save_record(
+        &self,
+        file_name: String,
+        timestamp: i64,
+        record: TranscriptionRecord,
+    ) -> Result<i64> {
+        let title = self.format_timestamp_title(timestamp);
 
         // Save to database
-        self.save_to_database(
-            file_name,
+        let conn = self.get_connection()?;
+        Self::save_to_database(
+            &conn,
+            self.cipher.as_ref(),
+            &file_name,
             timestamp,
-            title,
-            transcription_text,
-            post_processed_text,
-            post_process_prompt,
+            &title,
+            &record,
         )?;
+        let id = conn.last_insert_rowid();
 
         // Clean up old entries
         self.cleanup_old_entries()?;
 
         // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+        if let Err(e) = self.host.emit("history-updated", ()) {
             error!("Failed to emit history-updated event: {}", e);
         }
 
-        Ok(())
+        Ok(id)
     }
 
     fn save_to_database(
-        &self,
-        file_name: String,
+        conn: &Connection,
+        cipher: Option<&HistoryCipher>,
+        file_name: &str,
         timestamp: i64,
-        title: String,
-        transcription_text: String,
-        post_processed_text: Option<String>,
-        post_process_prompt: Option<String>,
+        title: &str,
+        record: &TranscriptionRecord,
     ) -> Result<()> {
-        let conn = self.get_connection()?;
+        let segments = serde_json::to_string(&record.segments)?;
+        let seal_opt = |text: &Option<String>| text.as_deref().map(|text| seal(cipher, text));
         conn.execute(
-            "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![file_name, timestamp, false, title, transcription_text, post_processed_text, post_process_prompt],
+            "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, source, model_id, duration_ms, segments, audio_hash) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                file_name,
+                timestamp,
+                false,
+                title,
+                seal(cipher, &record.transcription_text),
+                seal_opt(&record.post_processed_text),
+                seal_opt(&record.post_process_prompt),
+                record.source.as_str(),
+                record.model_id,
+                record.duration_ms,
+                seal(cipher, &segments),
+                record.audio_hash
+            ],
         )?;
 
-        debug!("Saved transcription to database");
+        debug!("Saved {} transcription to database", record.source.as_str());
         Ok(())
     }
 
     pub fn cleanup_old_entries(&self) -> Result<()> {
-        let retention_period = crate::settings::get_recording_retention_period(&self.app_handle);
+        let retention_period = self.host.settings().recording_retention_period;
 
         match retention_period {
             crate::settings::RecordingRetentionPeriod::Never => {
@@ -242,7 +580,7 @@ impl HistoryManager {
             }
             crate::settings::RecordingRetentionPeriod::PreserveLimit => {
                 // Use the old count-based logic with history_limit
-                let limit = crate::settings::get_history_limit(&self.app_handle);
+                let limit = self.host.settings().history_limit;
                 return self.cleanup_by_count(limit);
             }
             _ => {
@@ -267,7 +605,10 @@ impl HistoryManager {
                 params![id],
             )?;
 
-            // Delete WAV file
+            // Delete WAV file, if the entry has one
+            if file_name.is_empty() {
+                continue;
+            }
             let file_path = self.recordings_dir.join(file_name);
             if file_path.exists() {
                 if let Err(e) = fs::remove_file(&file_path) {
@@ -354,22 +695,12 @@ impl HistoryManager {
 
     pub async fn get_history_entries(&self) -> Result<Vec<HistoryEntry>> {
         let conn = self.get_connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt FROM transcription_history ORDER BY timestamp DESC"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history ORDER BY timestamp DESC",
+            ENTRY_COLUMNS
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
-            Ok(HistoryEntry {
-                id: row.get("id")?,
-                file_name: row.get("file_name")?,
-                timestamp: row.get("timestamp")?,
-                saved: row.get("saved")?,
-                title: row.get("title")?,
-                transcription_text: row.get("transcription_text")?,
-                post_processed_text: row.get("post_processed_text")?,
-                post_process_prompt: row.get("post_process_prompt")?,
-            })
-        })?;
+        let rows = stmt.query_map([], |row| entry_from_row(row, self.cipher.as_ref()))?;
 
         let mut entries = Vec::new();
         for row in rows {
@@ -379,32 +710,95 @@ impl HistoryManager {
         Ok(entries)
     }
 
+    /// Entries matching `query`, newest first.
+    pub fn search_entries(&self, query: &HistoryQuery) -> Result<Vec<HistoryEntry>> {
+        let conn = self.get_connection()?;
+        Self::search_entries_with_conn(&conn, self.cipher.as_ref(), query)
+    }
+
+    fn search_entries_with_conn(
+        conn: &Connection,
+        cipher: Option<&HistoryCipher>,
+        query: &HistoryQuery,
+    ) -> Result<Vec<HistoryEntry>> {
+        // Encrypted history has no full-text index; its text is matched
+        // after decrypting, so paging has to wait until then too.
+        let words = query.text.as_deref().map(search_words).unwrap_or_default();
+        let filter_text = cipher.is_some() && !words.is_empty();
+        let text_match = match cipher {
+            Some(_) => None,
+            None => query.text.as_deref().and_then(fts_match_expression),
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history
+             WHERE (?1 IS NULL OR id IN (SELECT rowid FROM transcription_history_fts WHERE transcription_history_fts MATCH ?1))
+               AND (?2 IS NULL OR source = ?2)
+               AND (?3 IS NULL OR timestamp >= ?3)
+               AND (?4 IS NULL OR timestamp < ?4)
+               AND (?5 IS NULL OR audio_hash = ?5)
+             ORDER BY timestamp DESC
+             LIMIT ?6 OFFSET ?7",
+            ENTRY_COLUMNS
+        ))?;
+
+        // A negative LIMIT means no limit in SQLite
+        let (limit, offset) = if filter_text {
+            (-1, 0)
+        } else {
+            (
+                query.limit.map_or(-1, |limit| limit as i64),
+                query.offset as i64,
+            )
+        };
+        let rows = stmt.query_map(
+            params![
+                text_match,
+                query.source.map(|s| s.as_str()),
+                query.from,
+                query.to,
+                query.audio_hash,
+                limit,
+                offset
+            ],
+            |row| entry_from_row(row, cipher),
+        )?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+
+        if filter_text {
+            entries.retain(|entry| entry_matches(entry, &words));
+            entries = entries
+                .into_iter()
+                .skip(query.offset)
+                .take(query.limit.unwrap_or(usize::MAX))
+                .collect();
+        }
+
+        Ok(entries)
+    }
+
     pub fn get_latest_entry(&self) -> Result<Option<HistoryEntry>> {
         let conn = self.get_connection()?;
-        Self::get_latest_entry_with_conn(&conn)
+        Self::get_latest_entry_with_conn(&conn, self.cipher.as_ref())
     }
 
-    fn get_latest_entry_with_conn(conn: &Connection) -> Result<Option<HistoryEntry>> {
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt
+    fn get_latest_entry_with_conn(
+        conn: &Connection,
+        cipher: Option<&HistoryCipher>,
+    ) -> Result<Option<HistoryEntry>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}
              FROM transcription_history
              ORDER BY timestamp DESC
              LIMIT 1",
-        )?;
+            ENTRY_COLUMNS
+        ))?;
 
         let entry = stmt
-            .query_row([], |row| {
-                Ok(HistoryEntry {
-                    id: row.get("id")?,
-                    file_name: row.get("file_name")?,
-                    timestamp: row.get("timestamp")?,
-                    saved: row.get("saved")?,
-                    title: row.get("title")?,
-                    transcription_text: row.get("transcription_text")?,
-                    post_processed_text: row.get("post_processed_text")?,
-                    post_process_prompt: row.get("post_process_prompt")?,
-                })
-            })
+            .query_row([], |row| entry_from_row(row, cipher))
             .optional()?;
 
         Ok(entry)
@@ -430,7 +824,7 @@ impl HistoryManager {
         debug!("Toggled saved status for entry {}: {}", id, new_saved);
 
         // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+        if let Err(e) = self.host.emit("history-updated", ()) {
             error!("Failed to emit history-updated event: {}", e);
         }
 
@@ -441,26 +835,36 @@ impl HistoryManager {
         self.recordings_dir.join(file_name)
     }
 
+    /// A path the recording can be played from. Encrypted recordings are
+    /// decrypted to a temporary file that is kept until the next call.
+    pub fn get_playable_audio_path(&self, file_name: &str) -> Result<PathBuf> {
+        let path = self.get_audio_file_path(file_name);
+        let Some(cipher) = &self.cipher else {
+            return Ok(path);
+        };
+
+        let mut playback = self.playback.lock().unwrap();
+        // Remove the previous copy first
+        *playback = None;
+        let file = tempfile::Builder::new()
+            .prefix("handy-playback-")
+            .suffix(".wav")
+            .tempfile()?;
+        fs::write(file.path(), cipher.read_file(&path)?)?;
+        let playable = file.path().to_path_buf();
+        *playback = Some(file);
+        Ok(playable)
+    }
+
     pub async fn get_entry_by_id(&self, id: i64) -> Result<Option<HistoryEntry>> {
         let conn = self.get_connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt
-             FROM transcription_history WHERE id = ?1",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history WHERE id = ?1",
+            ENTRY_COLUMNS
+        ))?;
 
         let entry = stmt
-            .query_row([id], |row| {
-                Ok(HistoryEntry {
-                    id: row.get("id")?,
-                    file_name: row.get("file_name")?,
-                    timestamp: row.get("timestamp")?,
-                    saved: row.get("saved")?,
-                    title: row.get("title")?,
-                    transcription_text: row.get("transcription_text")?,
-                    post_processed_text: row.get("post_processed_text")?,
-                    post_process_prompt: row.get("post_process_prompt")?,
-                })
-            })
+            .query_row([id], |row| entry_from_row(row, self.cipher.as_ref()))
             .optional()?;
 
         Ok(entry)
@@ -473,7 +877,7 @@ impl HistoryManager {
         if let Some(entry) = self.get_entry_by_id(id).await? {
             // Delete the audio file first
             let file_path = self.get_audio_file_path(&entry.file_name);
-            if file_path.exists() {
+            if !entry.file_name.is_empty() && file_path.exists() {
                 if let Err(e) = fs::remove_file(&file_path) {
                     error!("Failed to delete audio file {}: {}", entry.file_name, e);
                     // Continue with database deletion even if file deletion fails
@@ -490,7 +894,7 @@ impl HistoryManager {
         debug!("Deleted history entry with id: {}", id);
 
         // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+        if let Err(e) = self.host.emit("history-updated", ()) {
             error!("Failed to emit history-updated event: {}", e);
         }
 
@@ -508,26 +912,108 @@ impl HistoryManager {
     }
 }
 
+/// Build a `HistoryEntry` from a row selected with `ENTRY_COLUMNS`,
+/// decrypting its texts with `cipher`.
+fn entry_from_row(row: &Row, cipher: Option<&HistoryCipher>) -> rusqlite::Result<HistoryEntry> {
+    let source: String = row.get("source")?;
+    let segments = open_text(row, "segments", cipher)?;
+    Ok(HistoryEntry {
+        id: row.get("id")?,
+        file_name: row.get("file_name")?,
+        timestamp: row.get("timestamp")?,
+        saved: row.get("saved")?,
+        title: row.get("title")?,
+        transcription_text: open_text(row, "transcription_text", cipher)?.unwrap_or_default(),
+        post_processed_text: open_text(row, "post_processed_text", cipher)?,
+        post_process_prompt: open_text(row, "post_process_prompt", cipher)?,
+        source: HistorySource::parse(&source).unwrap_or_default(),
+        model_id: row.get("model_id")?,
+        duration_ms: row.get("duration_ms")?,
+        segments: segments
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        audio_hash: row.get("audio_hash")?,
+    })
+}
+
+/// `text` as stored: encrypted when the history is, as a blob so it can be
+/// told apart from entries saved before encryption.
+fn seal(cipher: Option<&HistoryCipher>, text: &str) -> Value {
+    match cipher {
+        Some(cipher) => Value::Blob(cipher.encrypt(text.as_bytes())),
+        None => Value::Text(text.to_string()),
+    }
+}
+
+/// Read a text column written by `seal`.
+fn open_text(
+    row: &Row,
+    column: &str,
+    cipher: Option<&HistoryCipher>,
+) -> rusqlite::Result<Option<String>> {
+    let conversion_error = |data_type, e: anyhow::Error| {
+        let index = row.as_ref().column_index(column).unwrap_or_default();
+        rusqlite::Error::FromSqlConversionFailure(index, data_type, e.into())
+    };
+    match row.get::<_, Value>(column)? {
+        Value::Null => Ok(None),
+        Value::Text(text) => Ok(Some(text)),
+        Value::Blob(sealed) => cipher
+            .ok_or_else(|| anyhow!("History entry is encrypted but no key is set"))
+            .and_then(|cipher| cipher.decrypt_text(&sealed))
+            .map(Some)
+            .map_err(|e| conversion_error(SqlType::Blob, e)),
+        other => Err(conversion_error(
+            other.data_type(),
+            anyhow!("Expected text in {}", column),
+        )),
+    }
+}
+
+/// Lowercased words of search text, split like the full-text index splits
+/// them.
+fn search_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether every word in `words` starts a word of the entry's original or
+/// post-processed text, the way the full-text search matches.
+fn entry_matches(entry: &HistoryEntry, words: &[String]) -> bool {
+    let mut text_words = search_words(&entry.transcription_text);
+    if let Some(post_processed) = &entry.post_processed_text {
+        text_words.extend(search_words(post_processed));
+    }
+    words.iter().all(|word| {
+        text_words
+            .iter()
+            .any(|text_word| text_word.starts_with(word.as_str()))
+    })
+}
+
+/// Turn free-form search text into an FTS5 query that requires every word,
+/// each as a prefix. Words are quoted so FTS5 operators in user input are
+/// matched literally. Returns `None` when there are no words.
+fn fts_match_expression(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rusqlite::{params, Connection};
 
     fn setup_conn() -> Connection {
-        let conn = Connection::open_in_memory().expect("open in-memory db");
-        conn.execute_batch(
-            "CREATE TABLE transcription_history (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                file_name TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                saved BOOLEAN NOT NULL DEFAULT 0,
-                title TEXT NOT NULL,
-                transcription_text TEXT NOT NULL,
-                post_processed_text TEXT,
-                post_process_prompt TEXT
-            );",
-        )
-        .expect("create transcription_history table");
+        let mut conn = Connection::open_in_memory().expect("open in-memory db");
+        Migrations::new(MIGRATIONS.to_vec())
+            .to_latest(&mut conn)
+            .expect("apply migrations");
         conn
     }
 
@@ -551,7 +1037,8 @@ mod tests {
     #[test]
     fn get_latest_entry_returns_none_when_empty() {
         let conn = setup_conn();
-        let entry = HistoryManager::get_latest_entry_with_conn(&conn).expect("fetch latest entry");
+        let entry =
+            HistoryManager::get_latest_entry_with_conn(&conn, None).expect("fetch latest entry");
         assert!(entry.is_none());
     }
 
@@ -561,7 +1048,7 @@ mod tests {
         insert_entry(&conn, 100, "first", None);
         insert_entry(&conn, 200, "second", Some("processed"));
 
-        let entry = HistoryManager::get_latest_entry_with_conn(&conn)
+        let entry = HistoryManager::get_latest_entry_with_conn(&conn, None)
             .expect("fetch latest entry")
             .expect("entry exists");
 
@@ -569,4 +1056,188 @@ mod tests {
         assert_eq!(entry.transcription_text, "second");
         assert_eq!(entry.post_processed_text.as_deref(), Some("processed"));
     }
+
+    #[test]
+    fn get_latest_entry_reads_record_fields() {
+        let conn = setup_conn();
+        let record = TranscriptionRecord {
+            source: HistorySource::Api,
+            transcription_text: "hello world".to_string(),
+            segments: vec![HistorySegment {
+                start: 0.0,
+                end: 1.5,
+                text: "hello world".to_string(),
+                speaker: None,
+            }],
+            model_id: Some("small".to_string()),
+            duration_ms: 1500,
+            ..Default::default()
+        };
+        HistoryManager::save_to_database(&conn, None, "", 100, "Recording 100", &record)
+            .expect("save record");
+
+        let entry = HistoryManager::get_latest_entry_with_conn(&conn, None)
+            .expect("fetch latest entry")
+            .expect("entry exists");
+
+        assert_eq!(entry.source, HistorySource::Api);
+        assert_eq!(entry.model_id.as_deref(), Some("small"));
+        assert_eq!(entry.duration_ms, Some(1500));
+        assert_eq!(entry.segments.len(), 1);
+        assert_eq!(entry.segments[0].end, 1.5);
+    }
+
+    #[test]
+    fn search_matches_word_prefixes_in_either_text() {
+        let conn = setup_conn();
+        insert_entry(&conn, 100, "Quarterly budget review", None);
+        insert_entry(&conn, 200, "Grocery list", Some("Groceries: milk, eggs"));
+        insert_entry(&conn, 300, "Budget meeting notes", None);
+
+        let search = |text: &str| {
+            let query = HistoryQuery {
+                text: Some(text.to_string()),
+                ..Default::default()
+            };
+            HistoryManager::search_entries_with_conn(&conn, None, &query)
+                .expect("search entries")
+                .into_iter()
+                .map(|e| e.timestamp)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(search("budg"), vec![300, 100]);
+        assert_eq!(search("budget notes"), vec![300]);
+        assert_eq!(search("milk"), vec![200]);
+        assert_eq!(search("\"unbalanced"), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn search_finds_transcriptions_of_the_same_audio() {
+        let conn = setup_conn();
+        for (timestamp, hash) in [
+            (100, Some("aa")),
+            (200, None),
+            (300, Some("bb")),
+            (400, Some("aa")),
+        ] {
+            let record = TranscriptionRecord {
+                source: HistorySource::Api,
+                transcription_text: "entry".to_string(),
+                audio_hash: hash.map(str::to_string),
+                ..Default::default()
+            };
+            HistoryManager::save_to_database(&conn, None, "", timestamp, "Recording", &record)
+                .expect("save record");
+        }
+
+        let query = HistoryQuery {
+            audio_hash: Some("aa".to_string()),
+            ..Default::default()
+        };
+        let entries =
+            HistoryManager::search_entries_with_conn(&conn, None, &query).expect("search entries");
+        let timestamps: Vec<i64> = entries.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![400, 100]);
+        assert_eq!(entries[0].audio_hash.as_deref(), Some("aa"));
+    }
+
+    #[test]
+    fn search_filters_by_time_and_pages() {
+        let conn = setup_conn();
+        for timestamp in [100, 200, 300, 400] {
+            insert_entry(&conn, timestamp, "entry", None);
+        }
+
+        let query = HistoryQuery {
+            from: Some(200),
+            to: Some(400),
+            ..Default::default()
+        };
+        let timestamps: Vec<i64> = HistoryManager::search_entries_with_conn(&conn, None, &query)
+            .expect("search entries")
+            .into_iter()
+            .map(|e| e.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![300, 200]);
+
+        let query = HistoryQuery {
+            limit: Some(2),
+            offset: 1,
+            ..Default::default()
+        };
+        let timestamps: Vec<i64> = HistoryManager::search_entries_with_conn(&conn, None, &query)
+            .expect("search entries")
+            .into_iter()
+            .map(|e| e.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![300, 200]);
+    }
+
+    #[test]
+    fn encrypts_existing_history_and_searches_it() {
+        let mut conn = setup_conn();
+        insert_entry(&conn, 100, "Quarterly budget review", None);
+        insert_entry(&conn, 200, "Grocery list", Some("Groceries: milk, eggs"));
+
+        let cipher = HistoryCipher::from_key(&[7; 32]);
+        HistoryManager::encrypt_database(&mut conn, &cipher, None).expect("encrypt database");
+        let record = TranscriptionRecord {
+            transcription_text: "Budget meeting notes".to_string(),
+            ..Default::default()
+        };
+        HistoryManager::save_to_database(&conn, Some(&cipher), "", 300, "Recording", &record)
+            .expect("save record");
+
+        // Nothing is left in plaintext, in the table or the full-text index
+        let plaintext: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM transcription_history WHERE typeof(transcription_text) = 'text'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(plaintext, 0);
+        let query = |text: &str| HistoryQuery {
+            text: Some(text.to_string()),
+            ..Default::default()
+        };
+        let indexed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM transcription_history_fts WHERE transcription_history_fts MATCH 'budget'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 0);
+
+        let search = |query: &HistoryQuery| {
+            HistoryManager::search_entries_with_conn(&conn, Some(&cipher), query)
+                .expect("search entries")
+                .into_iter()
+                .map(|e| e.timestamp)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(search(&query("budg")), vec![300, 100]);
+        assert_eq!(search(&query("milk")), vec![200]);
+        let paged = HistoryQuery {
+            limit: Some(1),
+            offset: 1,
+            ..query("budget")
+        };
+        assert_eq!(search(&paged), vec![100]);
+
+        let entry = HistoryManager::get_latest_entry_with_conn(&conn, Some(&cipher))
+            .expect("fetch latest entry")
+            .expect("entry exists");
+        assert_eq!(entry.transcription_text, "Budget meeting notes");
+
+        let stored: Option<Vec<u8>> = conn
+            .query_row("SELECT key_check FROM history_encryption", [], |row| {
+                row.get(0)
+            })
+            .optional()
+            .unwrap();
+        assert_eq!(cipher.decrypt(&stored.unwrap()).unwrap(), KEY_CHECK);
+    }
 }
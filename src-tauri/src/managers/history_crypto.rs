@@ -0,0 +1,202 @@
+//! Encryption at rest for the transcription history. Transcripts are stored
+//! as ChaCha20-Poly1305 blobs (nonce followed by ciphertext) and recordings
+//! as files starting with [`FILE_MAGIC`]. The key is derived from a
+//! passphrase with PBKDF2, or is a random key kept in the OS keychain.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use log::info;
+use sha2::Sha256;
+
+use crate::config::HistoryConfig;
+
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+pub const SALT_LEN: usize = 16;
+/// OWASP's recommendation for PBKDF2-HMAC-SHA256.
+const PBKDF2_ROUNDS: u32 = 600_000;
+/// Start of an encrypted recording, so plaintext WAVs left over from before
+/// encryption was turned on can be told apart.
+const FILE_MAGIC: &[u8] = b"HANDYENC1\0";
+
+const KEYCHAIN_SERVICE: &str = "Handy";
+const KEYCHAIN_USER: &str = "history-key";
+
+/// Where the history key comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeySource {
+    Passphrase(String),
+    Keychain,
+}
+
+impl KeySource {
+    /// The key source set in `[history]`, if encryption is on.
+    pub fn from_config(config: &HistoryConfig) -> Result<Option<Self>> {
+        match (&config.passphrase, config.keychain.unwrap_or(false)) {
+            (Some(_), true) => {
+                bail!("Set either a history passphrase or keychain = true, not both")
+            }
+            (Some(passphrase), false) => Ok(Some(KeySource::Passphrase(passphrase.clone()))),
+            (None, true) => Ok(Some(KeySource::Keychain)),
+            (None, false) => Ok(None),
+        }
+    }
+
+    /// Derive or fetch the key. `salt` is only used for passphrases;
+    /// `create` stores a new random key in the keychain when none exists.
+    pub fn cipher(&self, salt: Option<&[u8]>, create: bool) -> Result<HistoryCipher> {
+        match self {
+            KeySource::Passphrase(passphrase) => {
+                let salt = salt.ok_or_else(|| anyhow!("The history database has no salt"))?;
+                Ok(HistoryCipher::from_passphrase(passphrase, salt))
+            }
+            KeySource::Keychain => keychain_key(create).map(|key| HistoryCipher::from_key(&key)),
+        }
+    }
+}
+
+/// Fetch the history key from the OS keychain, creating it when `create` is
+/// set and there is none yet.
+fn keychain_key(create: bool) -> Result<[u8; KEY_LEN]> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_USER)
+        .context("Failed to open the OS keychain")?;
+    match entry.get_secret() {
+        Ok(secret) => secret.try_into().map_err(|_| {
+            anyhow!(
+                "The history key in the OS keychain is not {} bytes",
+                KEY_LEN
+            )
+        }),
+        Err(keyring::Error::NoEntry) if create => {
+            let key: [u8; KEY_LEN] = ChaCha20Poly1305::generate_key(&mut OsRng).into();
+            entry
+                .set_secret(&key)
+                .context("Failed to store the history key in the OS keychain")?;
+            info!("Stored a new history key in the OS keychain");
+            Ok(key)
+        }
+        Err(keyring::Error::NoEntry) => {
+            bail!("The history key is missing from the OS keychain")
+        }
+        Err(e) => Err(e).context("Failed to read the history key from the OS keychain"),
+    }
+}
+
+/// A random salt for a new passphrase-encrypted database.
+pub fn new_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+pub struct HistoryCipher {
+    cipher: ChaCha20Poly1305,
+}
+
+impl HistoryCipher {
+    pub fn from_key(key: &[u8; KEY_LEN]) -> Self {
+        Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(key)),
+        }
+    }
+
+    pub fn from_passphrase(passphrase: &str, salt: &[u8]) -> Self {
+        let key = pbkdf2::pbkdf2_hmac_array::<Sha256, KEY_LEN>(
+            passphrase.as_bytes(),
+            salt,
+            PBKDF2_ROUNDS,
+        );
+        Self::from_key(&key)
+    }
+
+    /// A fresh nonce followed by the ciphertext of `plaintext`.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .expect("ChaCha20-Poly1305 encryption does not fail for in-memory data");
+        let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        sealed
+    }
+
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            bail!("Encrypted history data is truncated");
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt history data; wrong key?"))
+    }
+
+    pub fn decrypt_text(&self, sealed: &[u8]) -> Result<String> {
+        String::from_utf8(self.decrypt(sealed)?).context("Decrypted history text is not UTF-8")
+    }
+
+    /// Write `contents` encrypted to `path`, through a temporary file in the
+    /// same directory so a crash never leaves a half-written recording.
+    pub fn write_file(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        let dir = path
+            .parent()
+            .ok_or_else(|| anyhow!("{} has no parent directory", path.display()))?;
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        file.write_all(FILE_MAGIC)?;
+        file.write_all(&self.encrypt(contents))?;
+        file.persist(path)?;
+        Ok(())
+    }
+
+    /// Contents of a recording, decrypted when it is encrypted.
+    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>> {
+        let data = fs::read(path)?;
+        match data.strip_prefix(FILE_MAGIC) {
+            Some(sealed) => self.decrypt(sealed),
+            None => Ok(data),
+        }
+    }
+
+    /// Encrypt a recording saved before encryption was turned on. Returns
+    /// false when it already is encrypted.
+    pub fn encrypt_file(&self, path: &Path) -> Result<bool> {
+        let data = fs::read(path)?;
+        if data.starts_with(FILE_MAGIC) {
+            return Ok(false);
+        }
+        self.write_file(path, &data)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_text_and_files() {
+        let cipher = HistoryCipher::from_key(&[7; KEY_LEN]);
+        let sealed = cipher.encrypt(b"hello world");
+        assert_ne!(&sealed[NONCE_LEN..], b"hello world");
+        assert_eq!(cipher.decrypt_text(&sealed).unwrap(), "hello world");
+        assert!(HistoryCipher::from_key(&[8; KEY_LEN])
+            .decrypt(&sealed)
+            .is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("handy-1.wav");
+        fs::write(&path, b"RIFF plain").unwrap();
+        assert_eq!(cipher.read_file(&path).unwrap(), b"RIFF plain");
+        assert!(cipher.encrypt_file(&path).unwrap());
+        assert!(!cipher.encrypt_file(&path).unwrap());
+        assert!(fs::read(&path).unwrap().starts_with(FILE_MAGIC));
+        assert_eq!(cipher.read_file(&path).unwrap(), b"RIFF plain");
+    }
+}
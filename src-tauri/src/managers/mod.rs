@@ -1,4 +1,10 @@
+pub mod api_keys;
 pub mod audio;
+pub mod catalog;
+pub mod hardware;
 pub mod history;
+pub mod history_crypto;
 pub mod model;
+pub mod output;
 pub mod transcription;
+pub mod wake_word;
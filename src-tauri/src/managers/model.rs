@@ -1,4 +1,6 @@
-use crate::settings::{get_settings, write_settings};
+use crate::host::Host;
+use crate::managers::catalog::{self, ModelCatalog};
+use crate::managers::hardware::{self, HardwareInfo};
 use anyhow::Result;
 use flate2::read::GzDecoder;
 use futures_util::StreamExt;
@@ -14,9 +16,11 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tar::Archive;
-use tauri::{AppHandle, Emitter, Manager};
+use transcribe_rs::engines::whisper::WhisperPreset;
+use transcribe_rs::model_hub;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
 pub enum EngineType {
     Whisper,
     Parakeet,
@@ -26,7 +30,7 @@ pub enum EngineType {
     GigaAM,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
 pub struct ModelInfo {
     pub id: String,
     pub name: String,
@@ -45,6 +49,7 @@ pub struct ModelInfo {
     pub is_recommended: bool,       // Whether this is the recommended model for new users
     pub supported_languages: Vec<String>, // Languages this model can transcribe
     pub is_custom: bool,            // Whether this is a user-provided custom model
+    pub sha256: Option<String>,     // Expected SHA-256 of the download, verified when set
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -55,20 +60,38 @@ pub struct DownloadProgress {
     pub percentage: f64,
 }
 
+/// Recommended decode settings for the fast Whisper checkpoints.
+pub fn whisper_preset(model_id: &str) -> Option<WhisperPreset> {
+    match model_id {
+        "turbo" => Some(WhisperPreset::LargeV3Turbo),
+        "distil-large-v3" => Some(WhisperPreset::DistilLargeV3),
+        _ => None,
+    }
+}
+
+/// Directory in `models_dir` for the optimized ONNX sessions of each model.
+const SESSION_CACHE_DIR: &str = ".session-cache";
+
 pub struct ModelManager {
-    app_handle: AppHandle,
+    host: Host,
     models_dir: PathBuf,
     available_models: Mutex<HashMap<String, ModelInfo>>,
     cancel_flags: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
     extracting_models: Arc<Mutex<HashSet<String>>>,
+    hardware: HardwareInfo,
 }
 
 impl ModelManager {
-    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+    /// `models_dir` overrides the default `models/` directory in app data.
+    pub fn new(host: Host, models_dir: Option<PathBuf>) -> Result<Self> {
         // Create models directory in app data
-        let models_dir = crate::portable::app_data_dir(app_handle)
-            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
-            .join("models");
+        let models_dir = match models_dir {
+            Some(dir) => dir,
+            None => host
+                .app_data_dir()
+                .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
+                .join("models"),
+        };
 
         if !models_dir.exists() {
             fs::create_dir_all(&models_dir)?;
@@ -113,6 +136,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: whisper_languages.clone(),
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -137,6 +161,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: whisper_languages.clone(),
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -160,6 +185,31 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: whisper_languages.clone(),
                 is_custom: false,
+                sha256: None,
+            },
+        );
+
+        available_models.insert(
+            "distil-large-v3".to_string(),
+            ModelInfo {
+                id: "distil-large-v3".to_string(),
+                name: "Distil-Whisper Large".to_string(),
+                description: "English only. Near Whisper Large accuracy, much faster.".to_string(),
+                filename: WhisperPreset::DistilLargeV3.file_name().to_string(),
+                url: Some(WhisperPreset::DistilLargeV3.model_url().to_string()),
+                size_mb: 1520, // Approximate size
+                is_downloaded: false,
+                is_downloading: false,
+                partial_size: 0,
+                is_directory: false,
+                engine_type: EngineType::Whisper,
+                accuracy_score: 0.80,
+                speed_score: 0.55,
+                supports_translation: false,
+                is_recommended: false,
+                supported_languages: vec!["en".to_string()],
+                is_custom: false,
+                sha256: None,
             },
         );
 
@@ -183,6 +233,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: whisper_languages.clone(),
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -207,6 +258,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: whisper_languages,
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -231,6 +283,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: vec!["en".to_string()],
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -264,6 +317,7 @@ impl ModelManager {
                 is_recommended: true,
                 supported_languages: parakeet_v3_languages,
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -287,6 +341,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: vec!["en".to_string()],
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -312,6 +367,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: vec!["en".to_string()],
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -337,6 +393,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: vec!["en".to_string()],
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -362,6 +419,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: vec!["en".to_string()],
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -393,6 +451,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: sense_voice_languages,
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -419,6 +478,7 @@ impl ModelManager {
                 is_recommended: false,
                 supported_languages: gigaam_languages,
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -428,11 +488,12 @@ impl ModelManager {
         }
 
         let manager = Self {
-            app_handle: app_handle.clone(),
+            host,
             models_dir,
             available_models: Mutex::new(available_models),
             cancel_flags: Arc::new(Mutex::new(HashMap::new())),
             extracting_models: Arc::new(Mutex::new(HashSet::new())),
+            hardware: HardwareInfo::detect(),
         };
 
         // Migrate any bundled models to user directory
@@ -441,6 +502,9 @@ impl ModelManager {
         // Check which models are already downloaded
         manager.update_download_status()?;
 
+        // Recommend the best model for this machine
+        manager.update_recommendation();
+
         // Auto-select a model if none is currently selected
         manager.auto_select_model_if_needed()?;
 
@@ -457,15 +521,48 @@ impl ModelManager {
         models.get(model_id).cloned()
     }
 
+    pub fn hardware(&self) -> &HardwareInfo {
+        &self.hardware
+    }
+
+    /// The built-in models' catalog entries for this machine.
+    pub fn catalog(&self) -> ModelCatalog {
+        catalog::build(
+            self.get_available_models(),
+            self.hardware.clone(),
+            &self.host.settings().selected_language,
+        )
+    }
+
+    /// Mark the model recommended for this hardware and the selected
+    /// language. Keeps the default recommendation if no model fits.
+    fn update_recommendation(&self) {
+        let language = self.host.settings().selected_language;
+        let mut models = self.available_models.lock().unwrap();
+        let builtin: Vec<ModelInfo> = models
+            .values()
+            .filter(|model| !model.is_custom)
+            .cloned()
+            .collect();
+        let Some(recommended) = hardware::recommend(&self.hardware, &builtin, &language) else {
+            warn!("No model fits this machine, keeping the default recommendation");
+            return;
+        };
+
+        info!("Recommended model for this machine: {}", recommended);
+        for model in models.values_mut() {
+            model.is_recommended = model.id == recommended;
+        }
+    }
+
     fn migrate_bundled_models(&self) -> Result<()> {
         // Check for bundled models and copy them to user directory
         let bundled_models = ["ggml-small.bin"]; // Add other bundled models here if any
 
         for filename in &bundled_models {
-            let bundled_path = self.app_handle.path().resolve(
-                &format!("resources/models/{}", filename),
-                tauri::path::BaseDirectory::Resource,
-            );
+            let bundled_path = self
+                .host
+                .resolve_resource(&format!("resources/models/{}", filename));
 
             if let Ok(bundled_path) = bundled_path {
                 if bundled_path.exists() {
@@ -536,8 +633,10 @@ impl ModelManager {
         Ok(())
     }
 
-    fn auto_select_model_if_needed(&self) -> Result<()> {
-        let mut settings = get_settings(&self.app_handle);
+    /// Select a downloaded model if none is selected, or the best one for
+    /// this machine in auto mode.
+    pub fn auto_select_model_if_needed(&self) -> Result<()> {
+        let mut settings = self.host.settings();
 
         // Clear stale selection: selected model is set but doesn't exist
         // in available_models (e.g. deleted custom model file)
@@ -552,7 +651,29 @@ impl ModelManager {
                     settings.selected_model
                 );
                 settings.selected_model = String::new();
-                write_settings(&self.app_handle, settings.clone());
+                self.host.write_settings(settings.clone());
+            }
+        }
+
+        // In auto mode, select the best downloaded model for this machine
+        if settings.auto_select_model {
+            let downloaded: Vec<ModelInfo> = {
+                let models = self.available_models.lock().unwrap();
+                models
+                    .values()
+                    .filter(|model| model.is_downloaded && !model.is_custom)
+                    .cloned()
+                    .collect()
+            };
+            if let Some(best) =
+                hardware::recommend(&self.hardware, &downloaded, &settings.selected_language)
+            {
+                if best != settings.selected_model {
+                    info!("Auto mode: selecting model {} for this machine", best);
+                    settings.selected_model = best;
+                    self.host.write_settings(settings);
+                }
+                return Ok(());
             }
         }
 
@@ -569,7 +690,7 @@ impl ModelManager {
                 // Update settings with the selected model
                 let mut updated_settings = settings;
                 updated_settings.selected_model = available_model.id.clone();
-                write_settings(&self.app_handle, updated_settings);
+                self.host.write_settings(updated_settings);
 
                 info!("Successfully auto-selected model: {}", available_model.id);
             }
@@ -690,6 +811,7 @@ impl ModelManager {
                     is_recommended: false,
                     supported_languages: vec![],
                     is_custom: true,
+                    sha256: None,
                 },
             );
         }
@@ -801,6 +923,32 @@ impl ModelManager {
             response.content_length().unwrap_or(0)
         };
 
+        // Check there is room for the rest of the download, and for the
+        // extracted files of directory-based models
+        let remaining = if total_size > 0 {
+            total_size - resume_from
+        } else {
+            (model_info.size_mb * 1024 * 1024).saturating_sub(resume_from)
+        };
+        let required = if model_info.is_directory {
+            remaining + total_size.max(model_info.size_mb * 1024 * 1024)
+        } else {
+            remaining
+        };
+        if let Err(e) = model_hub::ensure_free_space(&self.models_dir, required) {
+            {
+                let mut models = self.available_models.lock().unwrap();
+                if let Some(model) = models.get_mut(model_id) {
+                    model.is_downloading = false;
+                }
+            }
+            {
+                let mut flags = self.cancel_flags.lock().unwrap();
+                flags.remove(model_id);
+            }
+            return Err(anyhow::anyhow!(e));
+        }
+
         let mut downloaded = resume_from;
         let mut stream = response.bytes_stream();
 
@@ -825,9 +973,7 @@ impl ModelManager {
                 0.0
             },
         };
-        let _ = self
-            .app_handle
-            .emit("model-download-progress", &initial_progress);
+        let _ = self.host.emit("model-download-progress", &initial_progress);
 
         // Throttle progress events to max 10/sec (100ms intervals)
         let mut last_emit = Instant::now();
@@ -887,8 +1033,14 @@ impl ModelManager {
                     total: total_size,
                     percentage,
                 };
-                let _ = self.app_handle.emit("model-download-progress", &progress);
+                let _ = self.host.emit("model-download-progress", &progress);
                 last_emit = Instant::now();
+
+                // Keep partial_size current for API clients polling the model
+                let mut models = self.available_models.lock().unwrap();
+                if let Some(model) = models.get_mut(model_id) {
+                    model.partial_size = downloaded;
+                }
             }
         }
 
@@ -903,9 +1055,7 @@ impl ModelManager {
                 100.0
             },
         };
-        let _ = self
-            .app_handle
-            .emit("model-download-progress", &final_progress);
+        let _ = self.host.emit("model-download-progress", &final_progress);
 
         file.flush()?;
         drop(file); // Ensure file is closed before moving
@@ -930,6 +1080,31 @@ impl ModelManager {
             }
         }
 
+        // Verify the checksum before the file is used
+        if let Some(expected) = model_info.sha256.clone() {
+            info!("Verifying checksum of model {}", model_id);
+            let path = partial_path.clone();
+            let verified =
+                tokio::task::spawn_blocking(move || model_hub::verify_sha256(&path, &expected))
+                    .await?;
+            if let Err(e) = verified {
+                // A corrupt file can't be resumed - delete it
+                let _ = fs::remove_file(&partial_path);
+                {
+                    let mut models = self.available_models.lock().unwrap();
+                    if let Some(model) = models.get_mut(model_id) {
+                        model.is_downloading = false;
+                        model.partial_size = 0;
+                    }
+                }
+                {
+                    let mut flags = self.cancel_flags.lock().unwrap();
+                    flags.remove(model_id);
+                }
+                return Err(anyhow::anyhow!(e));
+            }
+        }
+
         // Handle directory-based models (extract tar.gz) vs file-based models
         if model_info.is_directory {
             // Track that this model is being extracted
@@ -939,7 +1114,7 @@ impl ModelManager {
             }
 
             // Emit extraction started event
-            let _ = self.app_handle.emit("model-extraction-started", model_id);
+            let _ = self.host.emit("model-extraction-started", model_id);
             info!("Extracting archive for directory-based model: {}", model_id);
 
             // Use a temporary extraction directory to ensure atomic operations
@@ -971,7 +1146,7 @@ impl ModelManager {
                     let mut extracting = self.extracting_models.lock().unwrap();
                     extracting.remove(model_id);
                 }
-                let _ = self.app_handle.emit(
+                let _ = self.host.emit(
                     "model-extraction-failed",
                     &serde_json::json!({
                         "model_id": model_id,
@@ -1011,7 +1186,7 @@ impl ModelManager {
                 extracting.remove(model_id);
             }
             // Emit extraction completed event
-            let _ = self.app_handle.emit("model-extraction-completed", model_id);
+            let _ = self.host.emit("model-extraction-completed", model_id);
 
             // Remove the downloaded tar.gz file
             let _ = fs::remove_file(&partial_path);
@@ -1037,7 +1212,7 @@ impl ModelManager {
         }
 
         // Emit completion event
-        let _ = self.app_handle.emit("model-download-complete", model_id);
+        let _ = self.host.emit("model-download-complete", model_id);
 
         info!(
             "Successfully downloaded model {} to {:?}",
@@ -1099,6 +1274,13 @@ impl ModelManager {
             return Err(anyhow::anyhow!("No model files found to delete"));
         }
 
+        let cache_dir = self.session_cache_dir(model_id);
+        if cache_dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&cache_dir) {
+                warn!("Failed to delete session cache {:?}: {}", cache_dir, e);
+            }
+        }
+
         // Custom models should be removed from the list entirely since they
         // have no download URL and can't be re-downloaded
         if model_info.is_custom {
@@ -1112,7 +1294,7 @@ impl ModelManager {
         }
 
         // Emit event to notify UI
-        let _ = self.app_handle.emit("model-deleted", model_id);
+        let _ = self.host.emit("model-deleted", model_id);
 
         Ok(())
     }
@@ -1162,6 +1344,16 @@ impl ModelManager {
         }
     }
 
+    pub fn models_dir(&self) -> &Path {
+        &self.models_dir
+    }
+
+    /// Where the optimized ONNX sessions of `model_id` are kept, so loading
+    /// it again skips graph optimization. Deleted with the model.
+    pub fn session_cache_dir(&self, model_id: &str) -> PathBuf {
+        self.models_dir.join(SESSION_CACHE_DIR).join(model_id)
+    }
+
     pub fn cancel_download(&self, model_id: &str) -> Result<()> {
         debug!("ModelManager: cancel_download called for: {}", model_id);
 
@@ -1188,7 +1380,7 @@ impl ModelManager {
         self.update_download_status()?;
 
         // Emit cancellation event so all UI components can clear their state
-        let _ = self.app_handle.emit("model-download-cancelled", model_id);
+        let _ = self.host.emit("model-download-cancelled", model_id);
 
         info!("Download cancellation initiated for: {}", model_id);
         Ok(())
@@ -1241,6 +1433,7 @@ mod tests {
                 is_recommended: false,
                 supported_languages: vec!["en".to_string()],
                 is_custom: false,
+                sha256: None,
             },
         );
 
@@ -0,0 +1,239 @@
+//! Routes completed dictations to the outputs in `[output]`, besides or
+//! instead of pasting them into the focused app: a Markdown notes file, a
+//! named pipe, standard output and a webhook.
+//!
+//! Delivery happens in the background and failures are only logged, so a
+//! missing directory or an unreachable webhook never holds up the paste.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeZone};
+use log::{debug, error, warn};
+use serde::Serialize;
+
+use crate::config::OutputConfig;
+
+/// Dictations waiting for a reader of the pipe before new ones are dropped.
+const PIPE_QUEUE: usize = 64;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of the webhook request.
+#[derive(Clone, Debug, Serialize)]
+pub struct CompletedDictation {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub duration_ms: i64,
+    /// Unix timestamp in milliseconds.
+    pub timestamp: i64,
+}
+
+pub struct OutputRouter {
+    paste: bool,
+    file: Option<PathBuf>,
+    pipe: Option<SyncSender<String>>,
+    stdout: bool,
+    webhook: Option<String>,
+    client: reqwest::Client,
+}
+
+impl OutputRouter {
+    pub fn new(config: &OutputConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            paste: config.paste.unwrap_or(true),
+            file: config.file.as_deref().map(expand_home),
+            pipe: config.pipe.clone().map(spawn_pipe_writer),
+            stdout: config.stdout.unwrap_or(false),
+            webhook: config.webhook.clone().filter(|url| !url.is_empty()),
+            client,
+        }
+    }
+
+    /// Whether dictations are pasted into the focused app.
+    pub fn paste(&self) -> bool {
+        self.paste
+    }
+
+    /// Send a dictation to every configured output.
+    pub fn deliver(&self, text: &str, language: Option<&str>, duration_ms: i64) {
+        if text.trim().is_empty() {
+            return;
+        }
+        let now = Local::now();
+
+        if self.stdout {
+            // Ignore a closed stdout rather than panicking like println!
+            let _ = writeln!(std::io::stdout().lock(), "{}", one_line(text));
+        }
+
+        if let Some(file) = &self.file {
+            let path = dated_path(file, &now);
+            let entry = markdown_entry(text, &now);
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(e) = append(&path, &entry) {
+                    error!("Failed to append dictation to {}: {}", path.display(), e);
+                }
+            });
+        }
+
+        if let Some(pipe) = &self.pipe {
+            match pipe.try_send(one_line(text)) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    warn!("Nothing is reading the output pipe; dropping the dictation")
+                }
+                Err(TrySendError::Disconnected(_)) => error!("The output pipe writer stopped"),
+            }
+        }
+
+        if let Some(url) = &self.webhook {
+            let request = self.client.post(url).json(&CompletedDictation {
+                text: text.to_string(),
+                language: language.map(str::to_string),
+                duration_ms,
+                timestamp: now.timestamp_millis(),
+            });
+            tauri::async_runtime::spawn(async move {
+                match request.send().await.and_then(|r| r.error_for_status()) {
+                    Ok(_) => debug!("Sent dictation to the webhook"),
+                    Err(e) => error!("Failed to send dictation to the webhook: {}", e),
+                }
+            });
+        }
+    }
+}
+
+/// Replace a leading `~` with the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    let Ok(rest) = path.strip_prefix("~") else {
+        return path.to_path_buf();
+    };
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(rest))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// `path` with `{date}` replaced by the day of `now`, e.g. 2026-03-14.
+fn dated_path<Tz: TimeZone>(path: &Path, now: &DateTime<Tz>) -> PathBuf
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let path = path.to_string_lossy();
+    if !path.contains("{date}") {
+        return PathBuf::from(path.as_ref());
+    }
+    PathBuf::from(path.replace("{date}", &now.format("%Y-%m-%d").to_string()))
+}
+
+fn markdown_entry<Tz: TimeZone>(text: &str, now: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    format!(
+        "### {}\n\n{}\n\n",
+        now.format("%Y-%m-%d %H:%M:%S"),
+        text.trim()
+    )
+}
+
+/// The text on a single line, for line-based readers.
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn append(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(contents.as_bytes())
+}
+
+/// Write lines to the pipe at `path` from a thread of its own, since opening
+/// a FIFO blocks until something reads it. The pipe is opened again when
+/// the reader goes away.
+fn spawn_pipe_writer(path: PathBuf) -> SyncSender<String> {
+    let (sender, lines) = mpsc::sync_channel::<String>(PIPE_QUEUE);
+    let spawned = std::thread::Builder::new()
+        .name("output-pipe".to_string())
+        .spawn(move || {
+            let mut pipe = None;
+            for line in lines {
+                // A second attempt after reopening a pipe whose reader left
+                for _ in 0..2 {
+                    if pipe.is_none() {
+                        match OpenOptions::new().write(true).open(&path) {
+                            Ok(file) => pipe = Some(file),
+                            Err(e) => {
+                                error!("Failed to open output pipe {}: {}", path.display(), e);
+                                break;
+                            }
+                        }
+                    }
+                    let Some(file) = pipe.as_mut() else {
+                        break;
+                    };
+                    if writeln!(file, "{}", line)
+                        .and_then(|_| file.flush())
+                        .is_ok()
+                    {
+                        break;
+                    }
+                    pipe = None;
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        error!("Failed to start the output pipe writer: {}", e);
+    }
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_file_output() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 9, 26, 53).unwrap();
+        assert_eq!(
+            dated_path(Path::new("notes/{date}.md"), &now),
+            PathBuf::from("notes/2026-03-14.md")
+        );
+        assert_eq!(
+            dated_path(Path::new("notes/inbox.md"), &now),
+            PathBuf::from("notes/inbox.md")
+        );
+        assert_eq!(
+            markdown_entry(" Call Sam back. \n", &now),
+            "### 2026-03-14 09:26:53\n\nCall Sam back.\n\n"
+        );
+        assert_eq!(
+            one_line("First line.\nSecond  line. "),
+            "First line. Second line."
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes").join("2026-03-14.md");
+        append(&path, "### one\n\n").unwrap();
+        append(&path, "### two\n\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "### one\n\n### two\n\n");
+    }
+}
@@ -1,15 +1,22 @@
-use crate::audio_toolkit::{apply_custom_words, filter_transcription_output};
-use crate::managers::model::{EngineType, ModelManager};
-use crate::settings::{get_settings, ModelUnloadTimeout};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::{apply_custom_words, apply_replacements, filter_transcription_output};
+use crate::config::{KeepAlive, ModelsConfig};
+use crate::foreground::ForegroundApp;
+use crate::host::Host;
+use crate::managers::hardware::{ModelMemory, ProcessMemory};
+use crate::managers::model::{whisper_preset, EngineType, ModelManager};
+use crate::redact::redact;
+use crate::settings::{AppProfile, AppSettings};
 use anyhow::Result;
 use log::{debug, error, info, warn};
 use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Condvar, Mutex, MutexGuard};
 use std::thread;
 use std::time::{Duration, SystemTime};
-use tauri::{AppHandle, Emitter};
 use transcribe_rs::{
     engines::{
         gigaam::GigaAMEngine,
@@ -24,10 +31,12 @@ use transcribe_rs::{
             Language as SenseVoiceLanguage, SenseVoiceEngine, SenseVoiceInferenceParams,
             SenseVoiceModelParams,
         },
-        whisper::{WhisperEngine, WhisperInferenceParams},
+        whisper::{WhisperEngine, WhisperInferenceParams, WhisperParallelParams, WhisperPreset},
     },
-    TranscriptionEngine,
+    hallucination::HallucinationFilter,
+    restrict_languages, LanguageDetection, TranscriptionEngine, TranscriptionResult,
 };
+use utoipa::ToSchema;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct ModelStateEvent {
@@ -37,6 +46,130 @@ pub struct ModelStateEvent {
     pub error: Option<String>,
 }
 
+/// A timestamped piece of a transcription. Times are in seconds.
+#[derive(Clone, Debug, Serialize)]
+pub struct TranscribedSegment {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+}
+
+/// Transcription text plus the segments it was assembled from.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DetailedTranscription {
+    pub text: String,
+    pub segments: Vec<TranscribedSegment>,
+    /// Spoken language, as detected or requested, if known.
+    pub language: Option<String>,
+}
+
+/// A candidate spoken language and its probability (0 to 1).
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct DetectedLanguage {
+    pub language: String,
+    pub probability: f32,
+}
+
+/// Speed/accuracy trade-off for a transcription.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionQuality {
+    /// Transcribe with a small draft model only.
+    Fast,
+    /// Transcribe with the selected model only.
+    #[default]
+    Balanced,
+    /// Run the draft model first to identify the language and build a
+    /// prompt, then transcribe with the selected model using that context.
+    Best,
+}
+
+impl TranscriptionQuality {
+    /// Parse `fast`, `balanced` or `best`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fast" => Some(Self::Fast),
+            "balanced" => Some(Self::Balanced),
+            "best" => Some(Self::Best),
+            _ => None,
+        }
+    }
+}
+
+/// Per-request settings for [`TranscriptionManager::transcribe_with_options`].
+#[derive(Clone, Debug, Default)]
+pub struct TranscriptionOptions {
+    pub quality: TranscriptionQuality,
+    /// Context for engines that accept a prompt (Whisper), e.g. names and
+    /// domain terms, to improve accuracy on specialised vocabulary.
+    pub prompt: Option<String>,
+    pub decoding: DecodingOptions,
+    /// Spoken language, overriding the selected language. Also picks the
+    /// model from the `[models.routes]` config.
+    pub language: Option<String>,
+    /// Languages to pick from when the language is "auto", e.g. the two
+    /// languages of a bilingual speaker. A single hint is used as the
+    /// language; several are told apart by a loaded Whisper model.
+    pub language_hints: Vec<String>,
+    /// The application dictated into, whose profile from the settings
+    /// picks the model, language and vocabulary.
+    pub app: Option<ForegroundApp>,
+}
+
+/// Decoding overrides for Whisper models; other engines ignore them. Unset
+/// values keep the model's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct DecodingOptions {
+    pub temperature: Option<f32>,
+    pub beam_size: Option<i32>,
+    pub best_of: Option<i32>,
+    pub patience: Option<f32>,
+    pub condition_on_previous_text: Option<bool>,
+}
+
+/// Draft models for the first pass of `Fast` and `Best` transcriptions,
+/// fastest first. The first one that is downloaded is used. Moonshine only
+/// understands English, so it is skipped unless English is selected.
+const DRAFT_MODELS: &[&str] = &["moonshine-tiny-streaming-en", "moonshine-base", "small"];
+
+/// Only the start of the audio is used to identify the language for routing.
+const ROUTING_DETECTION_SECS: usize = 30;
+
+/// Number of words from the start of the draft transcript used as the
+/// second pass prompt.
+const DRAFT_PROMPT_WORDS: usize = 100;
+
+/// Hints from the draft pass that steer the second pass.
+#[derive(Default)]
+struct PassContext {
+    /// Spoken language detected by the draft model.
+    language: Option<String>,
+    /// Draft transcript, giving the second pass names and domain terms,
+    /// followed by the caller's prompt.
+    prompt: Option<String>,
+}
+
+/// Result of the draft pass.
+struct Draft {
+    result: TranscriptionResult,
+    language: Option<String>,
+}
+
+impl Draft {
+    fn context(&self) -> PassContext {
+        let words: Vec<&str> = self
+            .result
+            .text
+            .split_whitespace()
+            .take(DRAFT_PROMPT_WORDS)
+            .collect();
+        PassContext {
+            language: self.language.clone(),
+            prompt: (!words.is_empty()).then(|| words.join(" ")),
+        }
+    }
+}
+
 enum LoadedEngine {
     Whisper(WhisperEngine),
     Parakeet(ParakeetEngine),
@@ -49,22 +182,40 @@ enum LoadedEngine {
 #[derive(Clone)]
 pub struct TranscriptionManager {
     engine: Arc<Mutex<Option<LoadedEngine>>>,
+    /// Draft model for two-pass transcription and its id, loaded on demand.
+    draft_engine: Arc<Mutex<Option<(String, LoadedEngine)>>>,
     model_manager: Arc<ModelManager>,
-    app_handle: AppHandle,
+    host: Host,
     current_model_id: Arc<Mutex<Option<String>>>,
     last_activity: Arc<AtomicU64>,
+    /// Overrides the `model_unload_timeout` setting when set.
+    keep_alive: Option<KeepAlive>,
+    /// Thread budget for decoding long audio with Whisper in parallel.
+    whisper_threads: Option<usize>,
+    /// Model ids used instead of the selected model, by language.
+    routes: Arc<BTreeMap<String, String>>,
+    /// Engines loaded for `routes` on first use, by model id.
+    routed_engines: Arc<Mutex<HashMap<String, LoadedEngine>>>,
+    /// Memory taken by each loaded model, by model id.
+    model_memory: Arc<Mutex<BTreeMap<String, ModelMemory>>>,
     shutdown_signal: Arc<AtomicBool>,
     watcher_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
     is_loading: Arc<Mutex<bool>>,
     loading_condvar: Arc<Condvar>,
+    last_transcription: Arc<AtomicU64>,
 }
 
 impl TranscriptionManager {
-    pub fn new(app_handle: &AppHandle, model_manager: Arc<ModelManager>) -> Result<Self> {
+    pub fn new(
+        host: Host,
+        model_manager: Arc<ModelManager>,
+        config: &ModelsConfig,
+    ) -> Result<Self> {
         let manager = Self {
             engine: Arc::new(Mutex::new(None)),
+            draft_engine: Arc::new(Mutex::new(None)),
             model_manager,
-            app_handle: app_handle.clone(),
+            host: host.clone(),
             current_model_id: Arc::new(Mutex::new(None)),
             last_activity: Arc::new(AtomicU64::new(
                 SystemTime::now()
@@ -72,15 +223,21 @@ impl TranscriptionManager {
                     .unwrap()
                     .as_millis() as u64,
             )),
+            keep_alive: config.keep_alive,
+            whisper_threads: config.whisper_threads,
+            routes: Arc::new(config.routes.clone()),
+            routed_engines: Arc::new(Mutex::new(HashMap::new())),
+            model_memory: Arc::new(Mutex::new(BTreeMap::new())),
             shutdown_signal: Arc::new(AtomicBool::new(false)),
             watcher_handle: Arc::new(Mutex::new(None)),
             is_loading: Arc::new(Mutex::new(false)),
             loading_condvar: Arc::new(Condvar::new()),
+            last_transcription: Arc::new(AtomicU64::new(0)),
         };
 
         // Start the idle watcher
         {
-            let app_handle_cloned = app_handle.clone();
+            let host_cloned = host.clone();
             let manager_cloned = manager.clone();
             let shutdown_signal = manager.shutdown_signal.clone();
             let handle = thread::spawn(move || {
@@ -92,12 +249,9 @@ impl TranscriptionManager {
                         break;
                     }
 
-                    let settings = get_settings(&app_handle_cloned);
-                    let timeout_seconds = settings.model_unload_timeout.to_seconds();
-
-                    if let Some(limit_seconds) = timeout_seconds {
+                    if let Some(limit) = manager_cloned.idle_timeout() {
                         // Skip polling-based unloading for immediate timeout since it's handled directly in transcribe()
-                        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately {
+                        if limit.is_zero() {
                             continue;
                         }
 
@@ -107,14 +261,14 @@ impl TranscriptionManager {
                             .unwrap()
                             .as_millis() as u64;
 
-                        if now_ms.saturating_sub(last) > limit_seconds * 1000 {
+                        if now_ms.saturating_sub(last) > limit.as_millis() as u64 {
                             // idle -> unload
                             if manager_cloned.is_model_loaded() {
                                 let unload_start = std::time::Instant::now();
                                 debug!("Starting to unload model due to inactivity");
 
                                 if let Ok(()) = manager_cloned.unload_model() {
-                                    let _ = app_handle_cloned.emit(
+                                    let _ = host_cloned.emit(
                                         "model-state-changed",
                                         ModelStateEvent {
                                             event_type: "unloaded".to_string(),
@@ -172,13 +326,22 @@ impl TranscriptionManager {
             }
             *engine = None; // Drop the engine to free memory
         }
+        {
+            let mut draft = self.draft_engine.lock().unwrap_or_else(|e| e.into_inner());
+            *draft = None;
+        }
+        self.routed_engines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.lock_model_memory().clear();
         {
             let mut current_model = self.current_model_id.lock().unwrap();
             *current_model = None;
         }
 
         // Emit unloaded event
-        let _ = self.app_handle.emit(
+        let _ = self.host.emit(
             "model-state-changed",
             ModelStateEvent {
                 event_type: "unloaded".to_string(),
@@ -196,12 +359,35 @@ impl TranscriptionManager {
         Ok(())
     }
 
+    /// Idle time after which the model is unloaded, from the `keep_alive`
+    /// config or the `model_unload_timeout` setting. `None` keeps it loaded.
+    fn idle_timeout(&self) -> Option<Duration> {
+        match self.keep_alive {
+            Some(KeepAlive::Forever) => None,
+            Some(KeepAlive::Idle(duration)) => Some(duration),
+            None => self
+                .host
+                .settings()
+                .model_unload_timeout
+                .to_seconds()
+                .map(Duration::from_secs),
+        }
+    }
+
+    /// Mark the model as in use, postponing the idle unload.
+    fn touch(&self) {
+        self.last_activity.store(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            Ordering::Relaxed,
+        );
+    }
+
     /// Unloads the model immediately if the setting is enabled and the model is loaded
     pub fn maybe_unload_immediately(&self, context: &str) {
-        let settings = get_settings(&self.app_handle);
-        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately
-            && self.is_model_loaded()
-        {
+        if self.idle_timeout() == Some(Duration::ZERO) && self.is_model_loaded() {
             info!("Immediately unloading model after {}", context);
             if let Err(e) = self.unload_model() {
                 warn!("Failed to immediately unload model: {}", e);
@@ -214,7 +400,7 @@ impl TranscriptionManager {
         debug!("Starting to load model: {}", model_id);
 
         // Emit loading started event
-        let _ = self.app_handle.emit(
+        let _ = self.host.emit(
             "model-state-changed",
             ModelStateEvent {
                 event_type: "loading_started".to_string(),
@@ -231,7 +417,7 @@ impl TranscriptionManager {
 
         if !model_info.is_downloaded {
             let error_msg = "Model not downloaded";
-            let _ = self.app_handle.emit(
+            let _ = self.host.emit(
                 "model-state-changed",
                 ModelStateEvent {
                     event_type: "loading_failed".to_string(),
@@ -244,6 +430,8 @@ impl TranscriptionManager {
         }
 
         let model_path = self.model_manager.get_model_path(model_id)?;
+        let cache_dir = self.model_manager.session_cache_dir(model_id);
+        let memory_before = ProcessMemory::measure();
 
         // Create appropriate engine based on model type
         let loaded_engine = match model_info.engine_type {
@@ -251,7 +439,7 @@ impl TranscriptionManager {
                 let mut engine = WhisperEngine::new();
                 engine.load_model(&model_path).map_err(|e| {
                     let error_msg = format!("Failed to load whisper model {}: {}", model_id, e);
-                    let _ = self.app_handle.emit(
+                    let _ = self.host.emit(
                         "model-state-changed",
                         ModelStateEvent {
                             event_type: "loading_failed".to_string(),
@@ -262,16 +450,29 @@ impl TranscriptionManager {
                     );
                     anyhow::anyhow!(error_msg)
                 })?;
+                // Keep whisper.cpp's first-decode setup out of the first request
+                let warm_up_start = std::time::Instant::now();
+                match engine.warm_up() {
+                    Ok(()) => debug!(
+                        "Warmed up whisper model {} in {}ms",
+                        model_id,
+                        warm_up_start.elapsed().as_millis()
+                    ),
+                    Err(e) => warn!("Failed to warm up whisper model {}: {}", model_id, e),
+                }
                 LoadedEngine::Whisper(engine)
             }
             EngineType::Parakeet => {
                 let mut engine = ParakeetEngine::new();
                 engine
-                    .load_model_with_params(&model_path, ParakeetModelParams::int8())
+                    .load_model_with_params(
+                        &model_path,
+                        ParakeetModelParams::int8().with_cache_dir(&cache_dir),
+                    )
                     .map_err(|e| {
                         let error_msg =
                             format!("Failed to load parakeet model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
+                        let _ = self.host.emit(
                             "model-state-changed",
                             ModelStateEvent {
                                 event_type: "loading_failed".to_string(),
@@ -289,12 +490,13 @@ impl TranscriptionManager {
                 engine
                     .load_model_with_params(
                         &model_path,
-                        MoonshineModelParams::variant(ModelVariant::Base),
+                        MoonshineModelParams::variant(ModelVariant::Base)
+                            .with_cache_dir(&cache_dir),
                     )
                     .map_err(|e| {
                         let error_msg =
                             format!("Failed to load moonshine model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
+                        let _ = self.host.emit(
                             "model-state-changed",
                             ModelStateEvent {
                                 event_type: "loading_failed".to_string(),
@@ -316,7 +518,7 @@ impl TranscriptionManager {
                             "Failed to load moonshine streaming model {}: {}",
                             model_id, e
                         );
-                        let _ = self.app_handle.emit(
+                        let _ = self.host.emit(
                             "model-state-changed",
                             ModelStateEvent {
                                 event_type: "loading_failed".to_string(),
@@ -336,7 +538,7 @@ impl TranscriptionManager {
                     .map_err(|e| {
                         let error_msg =
                             format!("Failed to load SenseVoice model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
+                        let _ = self.host.emit(
                             "model-state-changed",
                             ModelStateEvent {
                                 event_type: "loading_failed".to_string(),
@@ -353,7 +555,7 @@ impl TranscriptionManager {
                 let mut engine = GigaAMEngine::new();
                 engine.load_model(&model_path).map_err(|e| {
                     let error_msg = format!("Failed to load gigaam model {}: {}", model_id, e);
-                    let _ = self.app_handle.emit(
+                    let _ = self.host.emit(
                         "model-state-changed",
                         ModelStateEvent {
                             event_type: "loading_failed".to_string(),
@@ -368,18 +570,29 @@ impl TranscriptionManager {
             }
         };
 
+        // The idle timer starts once the model is ready
+        self.touch();
+        let memory = ProcessMemory::measure().attribute(memory_before, model_id);
+
         // Update the current engine and model ID
         {
             let mut engine = self.lock_engine();
             *engine = Some(loaded_engine);
         }
-        {
+        let previous_model = {
             let mut current_model = self.current_model_id.lock().unwrap();
-            *current_model = Some(model_id.to_string());
+            current_model.replace(model_id.to_string())
+        };
+        {
+            let mut model_memory = self.lock_model_memory();
+            if let Some(previous_model) = previous_model {
+                model_memory.remove(&previous_model);
+            }
+            model_memory.insert(model_id.to_string(), memory);
         }
 
         // Emit loading completed event
-        let _ = self.app_handle.emit(
+        let _ = self.host.emit(
             "model-state-changed",
             ModelStateEvent {
                 event_type: "loading_completed".to_string(),
@@ -408,7 +621,7 @@ impl TranscriptionManager {
         *is_loading = true;
         let self_clone = self.clone();
         thread::spawn(move || {
-            let settings = get_settings(&self_clone.app_handle);
+            let settings = self_clone.host.settings();
             if let Err(e) = self_clone.load_model(&settings.selected_model) {
                 error!("Failed to load model: {}", e);
             }
@@ -423,15 +636,401 @@ impl TranscriptionManager {
         current_model.clone()
     }
 
+    /// Memory taken by each loaded model: the selected one, and any draft
+    /// or routed models loaded next to it.
+    pub fn model_memory(&self) -> Vec<ModelMemory> {
+        self.lock_model_memory().values().cloned().collect()
+    }
+
+    fn lock_model_memory(&self) -> MutexGuard<'_, BTreeMap<String, ModelMemory>> {
+        self.model_memory.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether a background model load is currently in progress.
+    pub fn is_loading(&self) -> bool {
+        *self.is_loading.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Unix timestamp (ms) of the last completed transcription, if any.
+    pub fn last_transcription_time(&self) -> Option<u64> {
+        match self.last_transcription.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(ms),
+        }
+    }
+
+    /// Detect the spoken language of `audio` with the loaded model.
+    ///
+    /// Returns `Ok(None)` if the loaded engine can't identify languages, or the
+    /// candidates ordered by probability otherwise.
+    pub fn detect_language(&self, audio: &[f32]) -> Result<Option<Vec<DetectedLanguage>>> {
+        self.touch();
+
+        // If the model is loading, wait for it to complete.
+        {
+            let mut is_loading = self.is_loading.lock().unwrap();
+            while *is_loading {
+                is_loading = self.loading_condvar.wait(is_loading).unwrap();
+            }
+        }
+
+        let languages = {
+            let mut engine_guard = self.lock_engine();
+            match engine_guard.as_mut() {
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "Model is not loaded for language detection."
+                    ))
+                }
+                Some(LoadedEngine::Whisper(whisper_engine)) => Some(
+                    whisper_engine
+                        .detect_language(audio)
+                        .map_err(|e| anyhow::anyhow!("Language detection failed: {}", e))?
+                        .into_iter()
+                        .map(|l| DetectedLanguage {
+                            language: l.language,
+                            probability: l.probability,
+                        })
+                        .collect(),
+                ),
+                Some(_) => None,
+            }
+        };
+
+        self.maybe_unload_immediately("language detection");
+        Ok(languages)
+    }
+
+    /// Load `model_id` next to the selected model, as a draft model or for
+    /// a routed language.
+    fn load_extra_engine(&self, model_id: &str) -> Result<LoadedEngine> {
+        let model_info = self
+            .model_manager
+            .get_model_info(model_id)
+            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
+        let model_path = self.model_manager.get_model_path(model_id)?;
+        let cache_dir = self.model_manager.session_cache_dir(model_id);
+        let memory_before = ProcessMemory::measure();
+
+        let engine = match model_info.engine_type {
+            EngineType::Whisper => {
+                let mut engine = WhisperEngine::new();
+                engine.load_model(&model_path).map_err(|e| {
+                    anyhow::anyhow!("Failed to load whisper model {}: {}", model_id, e)
+                })?;
+                LoadedEngine::Whisper(engine)
+            }
+            EngineType::Moonshine => {
+                let mut engine = MoonshineEngine::new();
+                engine
+                    .load_model_with_params(
+                        &model_path,
+                        MoonshineModelParams::variant(ModelVariant::Base)
+                            .with_cache_dir(&cache_dir),
+                    )
+                    .map_err(|e| {
+                        anyhow::anyhow!("Failed to load moonshine model {}: {}", model_id, e)
+                    })?;
+                LoadedEngine::Moonshine(engine)
+            }
+            EngineType::MoonshineStreaming => {
+                let mut engine = MoonshineStreamingEngine::new();
+                engine
+                    .load_model_with_params(&model_path, StreamingModelParams::default())
+                    .map_err(|e| {
+                        anyhow::anyhow!(
+                            "Failed to load moonshine streaming model {}: {}",
+                            model_id,
+                            e
+                        )
+                    })?;
+                LoadedEngine::MoonshineStreaming(engine)
+            }
+            EngineType::Parakeet => {
+                let mut engine = ParakeetEngine::new();
+                engine
+                    .load_model_with_params(
+                        &model_path,
+                        ParakeetModelParams::int8().with_cache_dir(&cache_dir),
+                    )
+                    .map_err(|e| {
+                        anyhow::anyhow!("Failed to load parakeet model {}: {}", model_id, e)
+                    })?;
+                LoadedEngine::Parakeet(engine)
+            }
+            EngineType::SenseVoice => {
+                let mut engine = SenseVoiceEngine::new();
+                engine
+                    .load_model_with_params(&model_path, SenseVoiceModelParams::int8())
+                    .map_err(|e| {
+                        anyhow::anyhow!("Failed to load SenseVoice model {}: {}", model_id, e)
+                    })?;
+                LoadedEngine::SenseVoice(engine)
+            }
+            EngineType::GigaAM => {
+                let mut engine = GigaAMEngine::new();
+                engine.load_model(&model_path).map_err(|e| {
+                    anyhow::anyhow!("Failed to load gigaam model {}: {}", model_id, e)
+                })?;
+                LoadedEngine::GigaAM(engine)
+            }
+        };
+
+        info!("Loaded model {}", model_id);
+        let memory = ProcessMemory::measure().attribute(memory_before, model_id);
+        self.lock_model_memory()
+            .insert(model_id.to_string(), memory);
+        Ok(engine)
+    }
+
+    /// Transcribe `audio` with the first downloaded draft model, detecting
+    /// the language along the way when it is not set.
+    ///
+    /// Returns `None` if no draft model is available or the pass fails, in
+    /// which case callers fall back to a single pass.
+    fn draft_pass(&self, audio: &[f32], settings: &AppSettings) -> Option<Draft> {
+        let selected_model = self.get_current_model();
+        let model_id = DRAFT_MODELS.iter().copied().find(|id| {
+            (settings.selected_language == "en" || !id.starts_with("moonshine"))
+                && selected_model.as_deref() != Some(*id)
+                && self
+                    .model_manager
+                    .get_model_info(id)
+                    .is_some_and(|info| info.is_downloaded)
+        });
+        let Some(model_id) = model_id else {
+            debug!("No draft model downloaded, transcribing in a single pass");
+            return None;
+        };
+
+        let mut draft_guard = self.draft_engine.lock().unwrap_or_else(|e| e.into_inner());
+        if draft_guard.as_ref().map(|(id, _)| id.as_str()) != Some(model_id) {
+            if let Some((previous_id, _)) = draft_guard.take() {
+                self.lock_model_memory().remove(&previous_id);
+            }
+            match self.load_extra_engine(model_id) {
+                Ok(engine) => *draft_guard = Some((model_id.to_string(), engine)),
+                Err(e) => {
+                    warn!("Failed to load draft model: {}", e);
+                    return None;
+                }
+            }
+        }
+        let (_, engine) = draft_guard.as_mut()?;
+
+        let draft_start = std::time::Instant::now();
+        let outcome = catch_unwind(AssertUnwindSafe(|| -> Result<Draft> {
+            let language = match engine {
+                LoadedEngine::Whisper(whisper_engine) if settings.selected_language == "auto" => {
+                    whisper_engine
+                        .detect_language(audio)
+                        .map_err(|e| anyhow::anyhow!("Language detection failed: {}", e))?
+                        .into_iter()
+                        .next()
+                        .map(|l| l.language)
+                }
+                _ => None,
+            };
+            let result = run_engine(
+                engine,
+                audio.to_vec(),
+                settings,
+                None,
+                &PassContext::default(),
+                &DecodingOptions::default(),
+                None,
+            )?;
+            Ok(Draft { result, language })
+        }));
+
+        match outcome {
+            Ok(Ok(draft)) => {
+                debug!(
+                    "Draft pass with {} completed in {}ms",
+                    model_id,
+                    draft_start.elapsed().as_millis()
+                );
+                Some(draft)
+            }
+            Ok(Err(e)) => {
+                warn!("Draft pass failed: {}", e);
+                None
+            }
+            Err(_) => {
+                error!("Draft model {} panicked and has been unloaded", model_id);
+                *draft_guard = None;
+                self.lock_model_memory().remove(model_id);
+                None
+            }
+        }
+    }
+
+    /// Languages with a routed model, from the `[models.routes]` config.
+    pub fn routes(&self) -> &BTreeMap<String, String> {
+        &self.routes
+    }
+
+    /// The routed model and language for `audio`, or `None` to use the
+    /// selected model. With the language set to "auto", it's identified with
+    /// a loaded Whisper model.
+    fn route(&self, audio: &[f32], language: &str) -> Option<(String, String)> {
+        if self.routes.is_empty() {
+            return None;
+        }
+        let language = match language {
+            "auto" => self.identify_language(audio, &[])?,
+            language => language.to_string(),
+        };
+        let model_id = self.routes.get(&language).or_else(|| {
+            // "zh-Hans" falls back to a route for "zh"
+            language
+                .split_once('-')
+                .and_then(|(primary, _)| self.routes.get(primary))
+        })?;
+        let reason = format!("routed for '{}'", language);
+        self.route_to(model_id, language, &reason)
+    }
+
+    /// `model_id` and `language` to route to, or `None` if the model is
+    /// the selected one or isn't downloaded.
+    fn route_to(&self, model_id: &str, language: String, reason: &str) -> Option<(String, String)> {
+        if self.get_current_model().as_deref() == Some(model_id) {
+            return None;
+        }
+        match self.model_manager.get_model_info(model_id) {
+            Some(info) if info.is_downloaded => Some((model_id.to_string(), language)),
+            _ => {
+                warn!(
+                    "Model {} {} is not downloaded, using the selected model",
+                    model_id, reason
+                );
+                None
+            }
+        }
+    }
+
+    /// Most likely language of the start of `audio`, from the selected model
+    /// or a routed one if either is a Whisper model. Non-empty `hints`
+    /// restrict the candidates.
+    fn identify_language(&self, audio: &[f32], hints: &[String]) -> Option<String> {
+        let audio = &audio[..audio
+            .len()
+            .min(ROUTING_DETECTION_SECS * WHISPER_SAMPLE_RATE as usize)];
+        let detect = |engine: &mut LoadedEngine| match engine {
+            LoadedEngine::Whisper(whisper_engine) => match whisper_engine.detect_language(audio) {
+                Ok(languages) => restrict_languages(languages, hints)
+                    .into_iter()
+                    .next()
+                    .map(|l| l.language),
+                Err(e) => {
+                    warn!("Language detection failed: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        let detected = self.lock_engine().as_mut().and_then(detect);
+        let detected = detected.or_else(|| {
+            self.routed_engines
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .values_mut()
+                .find_map(detect)
+        });
+        match &detected {
+            Some(language) => debug!("Identified '{}'", language),
+            None => debug!("No Whisper model loaded to identify the language"),
+        }
+        detected
+    }
+
+    /// Transcribe `audio` with the routed `model_id`, loading it on first
+    /// use. It stays loaded next to the selected model until that is
+    /// unloaded.
+    fn routed_pass(
+        &self,
+        model_id: &str,
+        audio: Vec<f32>,
+        settings: &AppSettings,
+        options: &TranscriptionOptions,
+    ) -> Result<TranscriptionResult> {
+        let mut engines = self
+            .routed_engines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let engine = match engines.entry(model_id.to_string()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(self.load_extra_engine(model_id)?),
+        };
+
+        let context = PassContext {
+            language: None,
+            prompt: options.prompt.clone(),
+        };
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            run_engine(
+                engine,
+                audio,
+                settings,
+                whisper_preset(model_id),
+                &context,
+                &options.decoding,
+                self.whisper_threads,
+            )
+        }));
+        match outcome {
+            Ok(result) => result,
+            Err(_) => {
+                engines.remove(model_id);
+                self.lock_model_memory().remove(model_id);
+                Err(anyhow::anyhow!(
+                    "Routed model {} panicked and has been unloaded",
+                    model_id
+                ))
+            }
+        }
+    }
+
     pub fn transcribe(&self, audio: Vec<f32>) -> Result<String> {
+        self.transcribe_detailed(audio).map(|t| t.text)
+    }
+
+    /// Transcribe `audio` and return the text along with timestamped segments.
+    ///
+    /// Engines that don't report segments yield a single segment spanning the
+    /// whole input.
+    pub fn transcribe_detailed(&self, audio: Vec<f32>) -> Result<DetailedTranscription> {
+        self.transcribe_with_quality(audio, TranscriptionQuality::Balanced)
+    }
+
+    /// Transcribe `audio` trading speed for accuracy according to `quality`.
+    ///
+    /// `Fast` and `Best` need one of the draft models to be downloaded besides
+    /// the selected model, and fall back to `Balanced` without one.
+    pub fn transcribe_with_quality(
+        &self,
+        audio: Vec<f32>,
+        quality: TranscriptionQuality,
+    ) -> Result<DetailedTranscription> {
+        self.transcribe_with_options(
+            audio,
+            &TranscriptionOptions {
+                quality,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Transcribe `audio` with per-request `options`.
+    pub fn transcribe_with_options(
+        &self,
+        audio: Vec<f32>,
+        options: &TranscriptionOptions,
+    ) -> Result<DetailedTranscription> {
+        let quality = options.quality;
         // Update last activity timestamp
-        self.last_activity.store(
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            Ordering::Relaxed,
-        );
+        self.touch();
 
         let st = std::time::Instant::now();
 
@@ -440,9 +1039,11 @@ impl TranscriptionManager {
         if audio.is_empty() {
             debug!("Empty audio vector");
             self.maybe_unload_immediately("empty audio");
-            return Ok(String::new());
+            return Ok(DetailedTranscription::default());
         }
 
+        let audio_duration = audio.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+
         // Check if model is loaded, if not try to load it
         {
             // If the model is loading, wait for it to complete.
@@ -458,12 +1059,106 @@ impl TranscriptionManager {
         }
 
         // Get current settings for configuration
-        let settings = get_settings(&self.app_handle);
+        let mut settings = self.host.settings();
+        // The profile of the app dictated into adds its vocabulary, both as
+        // a prompt and for word correction
+        let profile = options
+            .app
+            .as_ref()
+            .and_then(|app| settings.app_profile(app))
+            .cloned();
+        let profiled;
+        let options = match &profile {
+            Some(profile) => {
+                debug!("Using the '{}' app profile", profile.name);
+                settings
+                    .custom_words
+                    .extend(profile.custom_words.iter().cloned());
+                profiled = TranscriptionOptions {
+                    prompt: vocabulary_prompt(&profile.custom_words, options.prompt.as_deref()),
+                    language: options.language.clone().or(profile.language.clone()),
+                    ..options.clone()
+                };
+                &profiled
+            }
+            None => options,
+        };
+        if let Some(language) = &options.language {
+            settings.selected_language = language.clone();
+        }
+        // Identify the language up front so routing, the draft pass and the
+        // selected model all keep to the hints
+        if settings.selected_language == "auto" {
+            match options.language_hints.as_slice() {
+                [] => {}
+                [language] => settings.selected_language = language.clone(),
+                hints => {
+                    if let Some(language) = self.identify_language(&audio, hints) {
+                        settings.selected_language = language;
+                    }
+                }
+            }
+        }
+
+        // A model configured for the app or the spoken language replaces the
+        // selected one
+        let route = match &profile {
+            Some(AppProfile {
+                name,
+                model: Some(model_id),
+                ..
+            }) => self.route_to(
+                model_id,
+                settings.selected_language.clone(),
+                &format!("of the '{}' app profile", name),
+            ),
+            _ => self.route(&audio, &settings.selected_language),
+        };
+        if let Some((model_id, language)) = &route {
+            debug!("Routing '{}' audio to {}", language, model_id);
+            settings.selected_language = language.clone();
+        }
+
+        // Fast Whisper checkpoints come with their own decode settings
+        let preset = self
+            .current_model_id
+            .lock()
+            .unwrap()
+            .as_deref()
+            .and_then(whisper_preset);
+
+        // First pass with a draft model, either as the final result or as
+        // context for the selected model
+        let mut context = PassContext::default();
+        let mut draft_result = None;
+        if quality != TranscriptionQuality::Balanced && route.is_none() {
+            if let Some(draft) = self.draft_pass(&audio, &settings) {
+                if quality == TranscriptionQuality::Fast {
+                    draft_result = Some(draft.result);
+                } else {
+                    context = draft.context();
+                }
+            }
+        }
+        // Whisper keeps the end of an overlong prompt, so the caller's prompt
+        // goes after the draft transcript
+        if let Some(prompt) = options.prompt.as_deref().map(str::trim) {
+            if !prompt.is_empty() {
+                context.prompt = Some(match context.prompt.take() {
+                    Some(draft) => format!("{} {}", draft, prompt),
+                    None => prompt.to_string(),
+                });
+            }
+        }
 
         // Perform transcription with the appropriate engine.
         // We use catch_unwind to prevent engine panics from poisoning the mutex,
         // which would make the app hang indefinitely on subsequent operations.
-        let result = {
+        let result = if let Some((model_id, _)) = &route {
+            self.routed_pass(model_id, audio, &settings, options)?
+        } else if let Some(result) = draft_result {
+            result
+        } else {
             let mut engine_guard = self.lock_engine();
 
             // Take the engine out so we own it during transcription.
@@ -481,77 +1176,17 @@ impl TranscriptionManager {
             // Release the lock before transcribing — no mutex held during the engine call
             drop(engine_guard);
 
-            let transcribe_result = catch_unwind(AssertUnwindSafe(
-                || -> Result<transcribe_rs::TranscriptionResult> {
-                    match &mut engine {
-                        LoadedEngine::Whisper(whisper_engine) => {
-                            let whisper_language = if settings.selected_language == "auto" {
-                                None
-                            } else {
-                                let normalized = if settings.selected_language == "zh-Hans"
-                                    || settings.selected_language == "zh-Hant"
-                                {
-                                    "zh".to_string()
-                                } else {
-                                    settings.selected_language.clone()
-                                };
-                                Some(normalized)
-                            };
-
-                            let params = WhisperInferenceParams {
-                                language: whisper_language,
-                                translate: settings.translate_to_english,
-                                ..Default::default()
-                            };
-
-                            whisper_engine
-                                .transcribe_samples(audio, Some(params))
-                                .map_err(|e| anyhow::anyhow!("Whisper transcription failed: {}", e))
-                        }
-                        LoadedEngine::Parakeet(parakeet_engine) => {
-                            let params = ParakeetInferenceParams {
-                                timestamp_granularity: TimestampGranularity::Segment,
-                                ..Default::default()
-                            };
-                            parakeet_engine
-                                .transcribe_samples(audio, Some(params))
-                                .map_err(|e| {
-                                    anyhow::anyhow!("Parakeet transcription failed: {}", e)
-                                })
-                        }
-                        LoadedEngine::Moonshine(moonshine_engine) => moonshine_engine
-                            .transcribe_samples(audio, None)
-                            .map_err(|e| anyhow::anyhow!("Moonshine transcription failed: {}", e)),
-                        LoadedEngine::MoonshineStreaming(streaming_engine) => streaming_engine
-                            .transcribe_samples(audio, None)
-                            .map_err(|e| {
-                                anyhow::anyhow!("Moonshine streaming transcription failed: {}", e)
-                            }),
-                        LoadedEngine::SenseVoice(sense_voice_engine) => {
-                            let language = match settings.selected_language.as_str() {
-                                "zh" | "zh-Hans" | "zh-Hant" => SenseVoiceLanguage::Chinese,
-                                "en" => SenseVoiceLanguage::English,
-                                "ja" => SenseVoiceLanguage::Japanese,
-                                "ko" => SenseVoiceLanguage::Korean,
-                                "yue" => SenseVoiceLanguage::Cantonese,
-                                _ => SenseVoiceLanguage::Auto,
-                            };
-                            let params = SenseVoiceInferenceParams {
-                                language,
-                                use_itn: true,
-                            };
-                            sense_voice_engine
-                                .transcribe_samples(audio, Some(params))
-                                .map_err(|e| {
-                                    anyhow::anyhow!("SenseVoice transcription failed: {}", e)
-                                })
-                        }
-                        LoadedEngine::GigaAM(gigaam_engine) => gigaam_engine
-                            .transcribe_samples(audio, None)
-                            .map_err(|e| anyhow::anyhow!("GigaAM transcription failed: {}", e)),
-                    }
-                },
-            ));
+            let transcribe_result = catch_unwind(AssertUnwindSafe(|| {
+                run_engine(
+                    &mut engine,
+                    audio,
+                    &settings,
+                    preset,
+                    &context,
+                    &options.decoding,
+                    self.whisper_threads,
+                )
+            }));
 
             match transcribe_result {
                 Ok(inner_result) => {
@@ -576,15 +1211,16 @@ impl TranscriptionManager {
                     );
 
                     // Clear the model ID so it will be reloaded on next attempt
-                    {
-                        let mut current_model = self
-                            .current_model_id
-                            .lock()
-                            .unwrap_or_else(|e| e.into_inner());
-                        *current_model = None;
+                    let panicked_model = self
+                        .current_model_id
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .take();
+                    if let Some(panicked_model) = panicked_model {
+                        self.lock_model_memory().remove(&panicked_model);
                     }
 
-                    let _ = self.app_handle.emit(
+                    let _ = self.host.emit(
                         "model-state-changed",
                         ModelStateEvent {
                             event_type: "unloaded".to_string(),
@@ -602,23 +1238,65 @@ impl TranscriptionManager {
             }
         };
 
-        // Apply word correction if custom words are configured
-        let corrected_result = if !settings.custom_words.is_empty() {
-            apply_custom_words(
-                &result.text,
-                &settings.custom_words,
-                settings.word_correction_threshold,
-            )
-        } else {
-            result.text
+        // Rules are validated when saved; skip any that no longer compile
+        let replacements: Vec<_> = settings
+            .text_replacements
+            .iter()
+            .filter_map(|rule| {
+                rule.compile()
+                    .map_err(|e| warn!("Skipping text replacement '{}': {}", rule.pattern, e))
+                    .ok()
+            })
+            .collect();
+
+        // Apply word correction if custom words are configured, filter out
+        // filler words and hallucinations, then apply text replacements
+        let clean_text = |text: &str| -> String {
+            let corrected = if !settings.custom_words.is_empty() {
+                apply_custom_words(
+                    text,
+                    &settings.custom_words,
+                    settings.word_correction_threshold,
+                )
+            } else {
+                text.to_string()
+            };
+            let filtered = filter_transcription_output(
+                &corrected,
+                &settings.app_language,
+                &settings.custom_filler_words,
+            );
+            apply_replacements(&filtered, &replacements)
         };
 
-        // Filter out filler words and hallucinations
-        let filtered_result = filter_transcription_output(
-            &corrected_result,
-            &settings.app_language,
-            &settings.custom_filler_words,
-        );
+        // Engines that don't report the language fall back to the requested
+        // one, then to the draft pass's guess
+        let language = result
+            .language
+            .clone()
+            .or_else(|| {
+                (settings.selected_language != "auto").then(|| settings.selected_language.clone())
+            })
+            .or_else(|| context.language.clone());
+
+        let filtered_result = clean_text(&result.text);
+        let segments: Vec<TranscribedSegment> = match result.segments {
+            Some(segments) if !segments.is_empty() => segments
+                .iter()
+                .map(|seg| TranscribedSegment {
+                    start: seg.start,
+                    end: seg.end,
+                    text: clean_text(&seg.text),
+                })
+                .filter(|seg| !seg.text.is_empty())
+                .collect(),
+            _ if !filtered_result.is_empty() => vec![TranscribedSegment {
+                start: 0.0,
+                end: audio_duration,
+                text: filtered_result.clone(),
+            }],
+            _ => Vec::new(),
+        };
 
         let et = std::time::Instant::now();
         let translation_note = if settings.translate_to_english {
@@ -637,12 +1315,26 @@ impl TranscriptionManager {
         if final_result.is_empty() {
             info!("Transcription result is empty");
         } else {
-            info!("Transcription result: {}", final_result);
+            info!("Transcription result: {}", redact(&final_result));
         }
 
+        self.last_transcription.store(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            Ordering::Relaxed,
+        );
+
+        // A long transcription shouldn't count as idle time
+        self.touch();
         self.maybe_unload_immediately("transcription");
 
-        Ok(final_result)
+        Ok(DetailedTranscription {
+            text: final_result,
+            segments,
+            language,
+        })
     }
 }
 
@@ -663,3 +1355,116 @@ impl Drop for TranscriptionManager {
         }
     }
 }
+
+/// A prompt listing `words`, followed by the caller's `prompt`, which
+/// Whisper keeps when the two are too long.
+fn vocabulary_prompt(words: &[String], prompt: Option<&str>) -> Option<String> {
+    let vocabulary = words.join(", ");
+    match prompt.map(str::trim).filter(|prompt| !prompt.is_empty()) {
+        Some(prompt) if vocabulary.is_empty() => Some(prompt.to_string()),
+        Some(prompt) => Some(format!("{}. {}", vocabulary, prompt)),
+        None => Some(vocabulary).filter(|vocabulary| !vocabulary.is_empty()),
+    }
+}
+
+/// Audio longer than this is decoded in parallel windows with Whisper when
+/// `whisper_threads` allows more than one thread.
+const PARALLEL_DECODE_MIN_SECS: usize = 60;
+
+/// Transcribe `audio` with `engine` using the user's settings, steered by
+/// `context` from a draft pass. Long audio is decoded with Whisper on up to
+/// `whisper_threads` threads.
+fn run_engine(
+    engine: &mut LoadedEngine,
+    audio: Vec<f32>,
+    settings: &AppSettings,
+    preset: Option<WhisperPreset>,
+    context: &PassContext,
+    decoding: &DecodingOptions,
+    whisper_threads: Option<usize>,
+) -> Result<TranscriptionResult> {
+    match engine {
+        LoadedEngine::Whisper(whisper_engine) => {
+            let whisper_language = if settings.selected_language == "auto" {
+                None
+            } else {
+                let normalized = if settings.selected_language == "zh-Hans"
+                    || settings.selected_language == "zh-Hant"
+                {
+                    "zh".to_string()
+                } else {
+                    settings.selected_language.clone()
+                };
+                Some(normalized)
+            };
+
+            let preset_params = preset.map(|p| p.inference_params()).unwrap_or_default();
+            let params = WhisperInferenceParams {
+                language: whisper_language
+                    .or_else(|| context.language.clone())
+                    .or(preset_params.language),
+                translate: settings.translate_to_english,
+                initial_prompt: context.prompt.clone().or(preset_params.initial_prompt),
+                hallucination_filter: settings
+                    .suppress_hallucinations
+                    .then(HallucinationFilter::default),
+                temperature: decoding.temperature.or(preset_params.temperature),
+                beam_size: decoding.beam_size.unwrap_or(preset_params.beam_size),
+                best_of: decoding.best_of.unwrap_or(preset_params.best_of),
+                patience: decoding.patience.unwrap_or(preset_params.patience),
+                condition_on_previous_text: decoding
+                    .condition_on_previous_text
+                    .unwrap_or(preset_params.condition_on_previous_text),
+                ..preset_params
+            };
+
+            let long = audio.len() > PARALLEL_DECODE_MIN_SECS * WHISPER_SAMPLE_RATE as usize;
+            let result = match whisper_threads {
+                Some(threads) if threads > 1 && long => {
+                    let parallel = WhisperParallelParams {
+                        thread_budget: Some(threads),
+                        ..Default::default()
+                    };
+                    whisper_engine.transcribe_parallel(&audio, Some(params), &parallel)
+                }
+                _ => whisper_engine.transcribe_samples(audio, Some(params)),
+            };
+            result.map_err(|e| anyhow::anyhow!("Whisper transcription failed: {}", e))
+        }
+        LoadedEngine::Parakeet(parakeet_engine) => {
+            let params = ParakeetInferenceParams {
+                timestamp_granularity: TimestampGranularity::Segment,
+                ..Default::default()
+            };
+            parakeet_engine
+                .transcribe_samples(audio, Some(params))
+                .map_err(|e| anyhow::anyhow!("Parakeet transcription failed: {}", e))
+        }
+        LoadedEngine::Moonshine(moonshine_engine) => moonshine_engine
+            .transcribe_samples(audio, None)
+            .map_err(|e| anyhow::anyhow!("Moonshine transcription failed: {}", e)),
+        LoadedEngine::MoonshineStreaming(streaming_engine) => streaming_engine
+            .transcribe_samples(audio, None)
+            .map_err(|e| anyhow::anyhow!("Moonshine streaming transcription failed: {}", e)),
+        LoadedEngine::SenseVoice(sense_voice_engine) => {
+            let language = match settings.selected_language.as_str() {
+                "zh" | "zh-Hans" | "zh-Hant" => SenseVoiceLanguage::Chinese,
+                "en" => SenseVoiceLanguage::English,
+                "ja" => SenseVoiceLanguage::Japanese,
+                "ko" => SenseVoiceLanguage::Korean,
+                "yue" => SenseVoiceLanguage::Cantonese,
+                _ => SenseVoiceLanguage::Auto,
+            };
+            let params = SenseVoiceInferenceParams {
+                language,
+                use_itn: true,
+            };
+            sense_voice_engine
+                .transcribe_samples(audio, Some(params))
+                .map_err(|e| anyhow::anyhow!("SenseVoice transcription failed: {}", e))
+        }
+        LoadedEngine::GigaAM(gigaam_engine) => gigaam_engine
+            .transcribe_samples(audio, None)
+            .map_err(|e| anyhow::anyhow!("GigaAM transcription failed: {}", e)),
+    }
+}
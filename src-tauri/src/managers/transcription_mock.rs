@@ -2,11 +2,15 @@
 // This file is copied over transcription.rs during CI tests.
 // Existing tests don't exercise transcription, so this is safe.
 
+use crate::config::ModelsConfig;
+use crate::foreground::ForegroundApp;
+use crate::host::Host;
 use crate::managers::model::ModelManager;
 use anyhow::Result;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use tauri::AppHandle;
+use utoipa::ToSchema;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct ModelStateEvent {
@@ -16,16 +20,81 @@ pub struct ModelStateEvent {
     pub error: Option<String>,
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct TranscribedSegment {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DetailedTranscription {
+    pub text: String,
+    pub segments: Vec<TranscribedSegment>,
+    pub language: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct DetectedLanguage {
+    pub language: String,
+    pub probability: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionQuality {
+    Fast,
+    #[default]
+    Balanced,
+    Best,
+}
+
+impl TranscriptionQuality {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fast" => Some(Self::Fast),
+            "balanced" => Some(Self::Balanced),
+            "best" => Some(Self::Best),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TranscriptionOptions {
+    pub quality: TranscriptionQuality,
+    pub prompt: Option<String>,
+    pub decoding: DecodingOptions,
+    pub language: Option<String>,
+    pub language_hints: Vec<String>,
+    pub app: Option<ForegroundApp>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DecodingOptions {
+    pub temperature: Option<f32>,
+    pub beam_size: Option<i32>,
+    pub best_of: Option<i32>,
+    pub patience: Option<f32>,
+    pub condition_on_previous_text: Option<bool>,
+}
+
 #[derive(Clone)]
 pub struct TranscriptionManager {
     #[allow(dead_code)]
-    app_handle: AppHandle,
+    host: Host,
+    routes: BTreeMap<String, String>,
 }
 
 impl TranscriptionManager {
-    pub fn new(app_handle: &AppHandle, _model_manager: Arc<ModelManager>) -> Result<Self> {
+    pub fn new(
+        host: Host,
+        _model_manager: Arc<ModelManager>,
+        config: &ModelsConfig,
+    ) -> Result<Self> {
         Ok(Self {
-            app_handle: app_handle.clone(),
+            host,
+            routes: config.routes.clone(),
         })
     }
 
@@ -49,7 +118,43 @@ impl TranscriptionManager {
         None
     }
 
+    pub fn is_loading(&self) -> bool {
+        false
+    }
+
+    pub fn last_transcription_time(&self) -> Option<u64> {
+        None
+    }
+
+    pub fn routes(&self) -> &BTreeMap<String, String> {
+        &self.routes
+    }
+
+    pub fn detect_language(&self, _audio: &[f32]) -> Result<Option<Vec<DetectedLanguage>>> {
+        Ok(None)
+    }
+
     pub fn transcribe(&self, _audio: Vec<f32>) -> Result<String> {
         Ok(String::new())
     }
+
+    pub fn transcribe_detailed(&self, _audio: Vec<f32>) -> Result<DetailedTranscription> {
+        Ok(DetailedTranscription::default())
+    }
+
+    pub fn transcribe_with_quality(
+        &self,
+        _audio: Vec<f32>,
+        _quality: TranscriptionQuality,
+    ) -> Result<DetailedTranscription> {
+        Ok(DetailedTranscription::default())
+    }
+
+    pub fn transcribe_with_options(
+        &self,
+        _audio: Vec<f32>,
+        _options: &TranscriptionOptions,
+    ) -> Result<DetailedTranscription> {
+        Ok(DetailedTranscription::default())
+    }
 }
@@ -0,0 +1,236 @@
+//! Wake-word activation, as an alternative to the shortcuts: while the
+//! microphone is open between recordings, its audio goes to a wake-word
+//! detector, and hearing the phrase starts a dictation like pressing the
+//! transcribe shortcut. That dictation stops by itself once the speaker goes
+//! quiet.
+//!
+//! The detector uses openWakeWord models, downloaded to `wake_word/` in the
+//! models directory the first time. A custom phrase needs a model trained
+//! with openWakeWord; its path takes the place of a built-in phrase.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::{debug, error, info};
+use tauri::{AppHandle, Manager};
+
+use crate::audio_toolkit::{RecorderFrame, WakeWordDetector, WakeWordModels};
+use crate::settings::AppSettings;
+use crate::TranscriptionCoordinator;
+
+const MODELS_URL: &str = "https://github.com/dscripka/openWakeWord/releases/download/v0.5.1";
+const MODELS_DIR: &str = "wake_word";
+const MELSPECTROGRAM_MODEL: &str = "melspectrogram.onnx";
+const EMBEDDING_MODEL: &str = "embedding_model.onnx";
+
+/// Built-in phrases and their model files.
+pub const BUILTIN_WAKE_WORDS: &[(&str, &str)] = &[
+    ("hey_jarvis", "hey_jarvis_v0.1.onnx"),
+    ("alexa", "alexa_v0.1.onnx"),
+    ("hey_mycroft", "hey_mycroft_v0.1.onnx"),
+    ("hey_rhasspy", "hey_rhasspy_v0.1.onnx"),
+];
+
+/// Binding that dictations started by the wake word run as.
+const BINDING_ID: &str = "transcribe";
+
+/// Length of a recorder frame.
+const FRAME_DURATION: Duration = Duration::from_millis(30);
+
+/// Silence after speech that ends a dictation.
+const END_SILENCE: Duration = Duration::from_millis(1500);
+
+/// A dictation without any speech ends after this.
+const NO_SPEECH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a detection waits for the recording to start before listening
+/// again.
+const START_TIMEOUT: Duration = Duration::from_secs(3);
+
+enum State {
+    Listening,
+    /// Heard the wake word; the recording is about to start.
+    Starting(Instant),
+    Dictating {
+        heard_speech: bool,
+        silence: Duration,
+        elapsed: Duration,
+    },
+}
+
+pub struct WakeWordManager {
+    app: AppHandle,
+    models_dir: PathBuf,
+    /// Loaded while the wake word is enabled.
+    detector: Mutex<Option<WakeWordDetector>>,
+    state: Mutex<State>,
+}
+
+impl WakeWordManager {
+    pub fn new(app: &AppHandle, models_dir: &Path) -> Self {
+        Self {
+            app: app.clone(),
+            models_dir: models_dir.join(MODELS_DIR),
+            detector: Mutex::new(None),
+            state: Mutex::new(State::Listening),
+        }
+    }
+
+    /// Load the detector for `settings`, downloading its models first if
+    /// needed, or unload it when the wake word is off.
+    pub async fn configure(&self, settings: &AppSettings) -> Result<(), String> {
+        if !settings.wake_word_enabled {
+            if self.detector.lock().unwrap().take().is_some() {
+                info!("Wake word disabled");
+            }
+            return Ok(());
+        }
+
+        let models = self.models(&settings.wake_word).await?;
+        let threshold = settings.wake_word_threshold;
+        let detector =
+            tauri::async_runtime::spawn_blocking(move || WakeWordDetector::new(&models, threshold))
+                .await
+                .map_err(|e| format!("Failed to load the wake word model: {}", e))?
+                .map_err(|e| format!("{:#}", e))?;
+
+        *self.detector.lock().unwrap() = Some(detector);
+        *self.state.lock().unwrap() = State::Listening;
+        info!("Listening for wake word '{}'", settings.wake_word);
+        Ok(())
+    }
+
+    /// The model files of `wake_word`, downloading any that are missing.
+    async fn models(&self, wake_word: &str) -> Result<WakeWordModels, String> {
+        let keyword = match BUILTIN_WAKE_WORDS.iter().find(|(id, _)| *id == wake_word) {
+            Some((_, file)) => self.download(file).await?,
+            None => {
+                let path = PathBuf::from(wake_word);
+                if path.extension().and_then(|ext| ext.to_str()) != Some("onnx") || !path.is_file()
+                {
+                    return Err(format!(
+                        "Unknown wake word '{}'. Use one of {} or the path of an openWakeWord .onnx model",
+                        wake_word,
+                        BUILTIN_WAKE_WORDS
+                            .iter()
+                            .map(|(id, _)| *id)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+                path
+            }
+        };
+        Ok(WakeWordModels {
+            melspectrogram: self.download(MELSPECTROGRAM_MODEL).await?,
+            embedding: self.download(EMBEDDING_MODEL).await?,
+            keyword,
+        })
+    }
+
+    /// Path of the model `file`, downloaded if it isn't there yet.
+    async fn download(&self, file: &str) -> Result<PathBuf, String> {
+        let path = self.models_dir.join(file);
+        if path.is_file() {
+            return Ok(path);
+        }
+
+        let url = format!("{}/{}", MODELS_URL, file);
+        info!("Downloading wake word model {}", url);
+        let bytes = async { reqwest::get(&url).await?.error_for_status()?.bytes().await }
+            .await
+            .map_err(|e| format!("Failed to download wake word model {}: {}", file, e))?;
+
+        // Write to a temporary file first so an interrupted download isn't
+        // mistaken for the model
+        let partial = path.with_extension("onnx.partial");
+        fs::create_dir_all(&self.models_dir)
+            .and_then(|_| fs::write(&partial, &bytes))
+            .and_then(|_| fs::rename(&partial, &path))
+            .map_err(|e| format!("Failed to save wake word model {}: {}", file, e))?;
+        Ok(path)
+    }
+
+    /// Called by the recorder with every frame while the microphone is
+    /// open.
+    pub fn on_frame(&self, frame: RecorderFrame) {
+        let mut detector = self.detector.lock().unwrap();
+        let Some(wake_word) = detector.as_mut() else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+
+        match frame {
+            RecorderFrame::Idle(samples) => {
+                match *state {
+                    State::Starting(since) if since.elapsed() < START_TIMEOUT => return,
+                    State::Listening => {}
+                    // The recording ended, or never started
+                    _ => {
+                        *state = State::Listening;
+                        wake_word.reset();
+                    }
+                }
+                match wake_word.push(samples) {
+                    Ok(true) => {
+                        info!("Wake word heard; starting dictation");
+                        *state = State::Starting(Instant::now());
+                        self.toggle_dictation();
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        error!("Wake word detection failed, turning it off: {:#}", e);
+                        *detector = None;
+                    }
+                }
+            }
+            RecorderFrame::Recording { speech } => {
+                let (heard_speech, silence, elapsed) = match &mut *state {
+                    // Started with a shortcut
+                    State::Listening => return,
+                    State::Starting(_) => {
+                        *state = State::Dictating {
+                            heard_speech: speech,
+                            silence: Duration::ZERO,
+                            elapsed: FRAME_DURATION,
+                        };
+                        return;
+                    }
+                    State::Dictating {
+                        heard_speech,
+                        silence,
+                        elapsed,
+                    } => (heard_speech, silence, elapsed),
+                };
+
+                *elapsed += FRAME_DURATION;
+                if speech {
+                    *heard_speech = true;
+                    *silence = Duration::ZERO;
+                } else {
+                    *silence += FRAME_DURATION;
+                }
+
+                let done = if *heard_speech {
+                    *silence >= END_SILENCE
+                } else {
+                    *elapsed >= NO_SPEECH_TIMEOUT
+                };
+                if done {
+                    debug!("Speaker went quiet; stopping wake word dictation");
+                    *state = State::Listening;
+                    self.toggle_dictation();
+                }
+            }
+        }
+    }
+
+    /// Start or stop a dictation, like pressing the transcribe shortcut.
+    fn toggle_dictation(&self) {
+        if let Some(coordinator) = self.app.try_state::<TranscriptionCoordinator>() {
+            coordinator.send_input(BINDING_ID, "wake word", true, false);
+        }
+    }
+}
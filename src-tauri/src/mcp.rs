@@ -0,0 +1,809 @@
+//! MCP (Model Context Protocol) server exposing local transcription as tools
+//! for LLM agents:
+//!
+//! - `transcribe_file`: transcribe an audio file on this machine (stdio only)
+//! - `transcribe_url`: download an audio file over HTTP(S) and transcribe it
+//! - `list_models`: list the models and whether they are downloaded/loaded
+//!
+//! Two transports share one handler. `handy --mcp` speaks newline-delimited
+//! JSON-RPC over stdin/stdout, which is how clients such as Claude Desktop
+//! launch local servers. The API server also offers the HTTP+SSE transport:
+//! `GET /mcp/sse` opens the event stream and announces the
+//! `POST /mcp/message?sessionId=...` endpoint that messages are sent to.
+//!
+//! HTTP callers may be on another machine, so that transport doesn't offer
+//! `transcribe_file`, and `transcribe_url` refuses URLs (and redirects) that
+//! lead to this machine or the local network.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Extension, Router,
+};
+use futures_util::{stream, Stream, StreamExt};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, watch};
+use transcribe_rs::formats::{Cue, Transcript, TranscriptFormat};
+
+use crate::api::{decode_audio_bytes, ApiLimits, InFlightGuard};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::managers::api_keys::{ApiKey, ApiKeyManager};
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::redact::redact;
+
+/// Protocol revision implemented here, the one that defines HTTP+SSE.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Redirects followed when downloading for `transcribe_url`.
+const MAX_REDIRECTS: usize = 5;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TranscribeFileArgs {
+    path: PathBuf,
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TranscribeUrlArgs {
+    url: String,
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ModelSummary {
+    id: String,
+    name: String,
+    engine: String,
+    downloaded: bool,
+    loaded: bool,
+}
+
+/// How a server is reached, which decides what its tools may touch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Transport {
+    /// Launched by a local client; may read files and reach any host.
+    Stdio,
+    /// Reachable over the network; sees only public hosts.
+    Http,
+}
+
+#[derive(Deserialize)]
+struct SessionQuery {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+pub struct McpServer {
+    transcription_manager: Arc<TranscriptionManager>,
+    model_manager: Arc<ModelManager>,
+    limits: ApiLimits,
+    transport: Transport,
+    /// Tool calls transcribing, shared with the API's other requests.
+    in_flight: Arc<AtomicUsize>,
+    /// Cap on `in_flight`; 0 means no limit.
+    max_concurrency: Arc<AtomicUsize>,
+    /// Where audio transcribed for calls made with an API key is counted.
+    api_keys: Option<Arc<ApiKeyManager>>,
+    /// Open SSE streams by session id.
+    sessions: Mutex<HashMap<String, mpsc::UnboundedSender<String>>>,
+    /// Set by `close` to end the SSE streams.
+    closed: watch::Sender<bool>,
+}
+
+impl McpServer {
+    /// Server for `handy --mcp`. Transcriptions are subject to the API's
+    /// upload size and duration limits.
+    pub fn stdio(
+        transcription_manager: Arc<TranscriptionManager>,
+        model_manager: Arc<ModelManager>,
+        limits: ApiLimits,
+    ) -> Self {
+        Self {
+            transcription_manager,
+            model_manager,
+            limits,
+            transport: Transport::Stdio,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            max_concurrency: Arc::new(AtomicUsize::new(0)),
+            api_keys: None,
+            sessions: Mutex::new(HashMap::new()),
+            closed: watch::Sender::new(false),
+        }
+    }
+
+    /// Server for the HTTP+SSE routes of the API server, with the same
+    /// limits as `/transcribe`. Tool calls share the API's `in_flight`
+    /// count and `max_concurrency`, and the audio they transcribe counts
+    /// towards the usage of the calling API key.
+    pub fn http(
+        transcription_manager: Arc<TranscriptionManager>,
+        model_manager: Arc<ModelManager>,
+        limits: ApiLimits,
+        in_flight: Arc<AtomicUsize>,
+        max_concurrency: Arc<AtomicUsize>,
+        api_keys: Arc<ApiKeyManager>,
+    ) -> Self {
+        Self {
+            transcription_manager,
+            model_manager,
+            limits,
+            transport: Transport::Http,
+            in_flight,
+            max_concurrency,
+            api_keys: Some(api_keys),
+            sessions: Mutex::new(HashMap::new()),
+            closed: watch::Sender::new(false),
+        }
+    }
+
+    /// End every SSE stream, including those with tool calls still running,
+    /// so a graceful shutdown of the API server doesn't wait on them.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Routes for the HTTP+SSE transport.
+    pub fn routes<S: Clone + Send + Sync + 'static>(self: Arc<Self>) -> Router<S> {
+        Router::new()
+            .route("/mcp/sse", get(sse_stream))
+            .route("/mcp/message", post(sse_message))
+            .with_state(self)
+    }
+
+    /// Handle one raw JSON-RPC message (or batch), returning the serialized
+    /// reply, or `None` when there is nothing to send back. `key` is the API
+    /// key the message was sent with, if any.
+    pub async fn handle_text(&self, text: &str, key: Option<&ApiKey>) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(text) {
+            Ok(Value::Array(batch)) => {
+                let mut replies = Vec::new();
+                for message in batch {
+                    replies.extend(self.handle_message(message, key).await);
+                }
+                (!replies.is_empty()).then_some(Value::Array(replies))
+            }
+            Ok(message) => self.handle_message(message, key).await,
+            Err(e) => Some(reply(
+                Value::Null,
+                Err(RpcError::new(PARSE_ERROR, format!("Parse error: {}", e))),
+            )),
+        };
+        response.map(|response| response.to_string())
+    }
+
+    async fn handle_message(&self, message: Value, key: Option<&ApiKey>) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Replies from the client are ignored; we never send requests
+            if message.get("result").is_some() || message.get("error").is_some() {
+                return None;
+            }
+            return Some(reply(
+                id.unwrap_or(Value::Null),
+                Err(RpcError::new(INVALID_REQUEST, "Missing method")),
+            ));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        debug!("MCP request: {}", method);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "handy", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions(self.transport) })),
+            "tools/call" => self.call_tool(params, key).await,
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            )),
+        };
+
+        // Notifications (no id) never get a reply
+        id.map(|id| reply(id, result))
+    }
+
+    async fn call_tool(&self, params: Value, key: Option<&ApiKey>) -> Result<Value, RpcError> {
+        let call: ToolCall = serde_json::from_value(params)
+            .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid tool call: {}", e)))?;
+        let arguments = match call.arguments {
+            Value::Null => json!({}),
+            arguments => arguments,
+        };
+        let invalid_arguments = |e: serde_json::Error| {
+            RpcError::new(INVALID_PARAMS, format!("Invalid arguments: {}", e))
+        };
+
+        let output = match call.name.as_str() {
+            "transcribe_file" if self.transport == Transport::Stdio => {
+                let args: TranscribeFileArgs =
+                    serde_json::from_value(arguments).map_err(invalid_arguments)?;
+                self.transcribe_file(args, key).await
+            }
+            "transcribe_url" => {
+                let args: TranscribeUrlArgs =
+                    serde_json::from_value(arguments).map_err(invalid_arguments)?;
+                self.transcribe_url(args, key).await
+            }
+            "list_models" => Ok(self.list_models()),
+            other => {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    format!("Unknown tool: {}", other),
+                ))
+            }
+        };
+
+        // Tool failures are results the model can read, not protocol errors
+        Ok(match output {
+            Ok(text) => tool_result(text, false),
+            Err(e) => tool_result(e, true),
+        })
+    }
+
+    async fn transcribe_file(
+        &self,
+        args: TranscribeFileArgs,
+        key: Option<&ApiKey>,
+    ) -> Result<String, String> {
+        let format = parse_format(args.format.as_deref())?;
+        let max_upload_bytes = self.limits.max_upload_bytes;
+        let extension = file_extension(&args.path);
+        info!(
+            "MCP transcribe_file: {}",
+            redact(&args.path.display().to_string())
+        );
+        let path = args.path;
+
+        let bytes = tokio::task::spawn_blocking(move || -> Result<Bytes, String> {
+            let len = std::fs::metadata(&path)
+                .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?
+                .len();
+            if max_upload_bytes > 0 && len > max_upload_bytes as u64 {
+                return Err(format!(
+                    "File exceeds the maximum size of {} bytes",
+                    max_upload_bytes
+                ));
+            }
+            std::fs::read(&path)
+                .map(Bytes::from)
+                .map_err(|e| format!("Cannot read {}: {}", path.display(), e))
+        });
+        let bytes = bytes
+            .await
+            .map_err(|e| format!("File read task panicked: {}", e))??;
+        self.transcribe(bytes, extension, format, key).await
+    }
+
+    async fn transcribe_url(
+        &self,
+        args: TranscribeUrlArgs,
+        key: Option<&ApiKey>,
+    ) -> Result<String, String> {
+        let format = parse_format(args.format.as_deref())?;
+        let url = reqwest::Url::parse(&args.url).map_err(|e| format!("Invalid URL: {}", e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err("Only http and https URLs are supported".to_string());
+        }
+        let extension = file_extension(Path::new(url.path()));
+
+        info!("MCP transcribe_url: {}", redact(url.as_str()));
+        let response = self.download(url).await?;
+
+        let max_upload_bytes = self.limits.max_upload_bytes;
+        let too_large = || {
+            format!(
+                "Download exceeds the maximum size of {} bytes",
+                max_upload_bytes
+            )
+        };
+        if max_upload_bytes > 0
+            && response
+                .content_length()
+                .is_some_and(|len| len > max_upload_bytes as u64)
+        {
+            return Err(too_large());
+        }
+
+        let mut body = Vec::new();
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|e| format!("Download failed: {}", e))?;
+            body.extend_from_slice(&chunk);
+            if max_upload_bytes > 0 && body.len() > max_upload_bytes {
+                return Err(too_large());
+            }
+        }
+
+        self.transcribe(Bytes::from(body), extension, format, key)
+            .await
+    }
+
+    /// GET `url`, following redirects by hand so each target is checked
+    /// like the first.
+    async fn download(&self, mut url: reqwest::Url) -> Result<reqwest::Response, String> {
+        for _ in 0..=MAX_REDIRECTS {
+            let client = self.client_for(&url).await?;
+            let response = client
+                .get(url.clone())
+                .send()
+                .await
+                .map_err(|e| format!("Download failed: {}", e))?;
+            if !response.status().is_redirection() {
+                return response
+                    .error_for_status()
+                    .map_err(|e| format!("Download failed: {}", e));
+            }
+
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| "Download failed: redirect without a location".to_string())?;
+            url = url
+                .join(location)
+                .map_err(|e| format!("Download failed: invalid redirect: {}", e))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err("Download failed: redirect to a non-http URL".to_string());
+            }
+            debug!("MCP transcribe_url redirected to {}", redact(url.as_str()));
+        }
+        Err(format!(
+            "Download failed: more than {} redirects",
+            MAX_REDIRECTS
+        ))
+    }
+
+    /// Client for requesting `url`. Over HTTP the host must resolve to
+    /// public addresses only, and the client connects to those addresses
+    /// rather than looking the host up again.
+    async fn client_for(&self, url: &reqwest::Url) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+        if self.transport == Transport::Http {
+            let host = url
+                .host_str()
+                .ok_or_else(|| "URL has no host".to_string())?;
+            let port = url.port_or_known_default().unwrap_or(80);
+            let literal = host.trim_start_matches('[').trim_end_matches(']');
+            let addrs: Vec<SocketAddr> = match literal.parse::<IpAddr>() {
+                Ok(ip) => vec![SocketAddr::new(ip, port)],
+                Err(_) => {
+                    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+                        .await
+                        .map_err(|e| format!("Cannot resolve {}: {}", host, e))?
+                        .collect();
+                    builder = builder.resolve_to_addrs(host, &addrs);
+                    addrs
+                }
+            };
+            if addrs.is_empty() || addrs.iter().any(|addr| !is_public(addr.ip())) {
+                return Err(format!(
+                    "Refusing to download from {}: only public addresses are allowed over HTTP",
+                    host
+                ));
+            }
+        }
+        builder
+            .build()
+            .map_err(|e| format!("Cannot create HTTP client: {}", e))
+    }
+
+    /// Decode and transcribe with the selected model, rendered in `format`.
+    /// Counts as an in-flight request, and the audio towards the usage of
+    /// `key`.
+    async fn transcribe(
+        &self,
+        bytes: Bytes,
+        extension: Option<String>,
+        format: TranscriptFormat,
+        key: Option<&ApiKey>,
+    ) -> Result<String, String> {
+        let max_concurrency = self.max_concurrency.load(Ordering::SeqCst);
+        let Some(_in_flight) = InFlightGuard::try_new(&self.in_flight, max_concurrency) else {
+            return Err("Too many transcription requests in progress, try again later".to_string());
+        };
+        let tm = self.transcription_manager.clone();
+        let max_duration_secs = self.limits.max_duration_secs;
+        let usage = self.api_keys.clone().zip(key.map(|key| key.id));
+
+        tokio::task::spawn_blocking(move || -> Result<String, String> {
+            let samples = decode_audio_bytes(bytes, extension, max_duration_secs)?;
+            let duration = samples.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+
+            tm.initiate_model_load();
+            let transcription = tm
+                .transcribe_detailed(samples)
+                .map_err(|e| format!("Transcription failed: {}", e))?;
+            let transcript = Transcript {
+                title: None,
+                text: transcription.text,
+                segments: transcription
+                    .segments
+                    .into_iter()
+                    .map(|seg| Cue {
+                        start: seg.start,
+                        end: seg.end,
+                        text: seg.text,
+                    })
+                    .collect(),
+                duration,
+            };
+            if let Some((keys, id)) = usage {
+                if let Err(e) = keys.record_audio(id, duration as f64) {
+                    warn!("Failed to record usage of API key {}: {}", id, e);
+                }
+            }
+            Ok(format.render(&transcript))
+        })
+        .await
+        .map_err(|e| format!("Transcription task panicked: {}", e))?
+    }
+
+    fn list_models(&self) -> String {
+        let loaded = self.transcription_manager.get_current_model();
+        let mut models: Vec<ModelSummary> = self
+            .model_manager
+            .get_available_models()
+            .into_iter()
+            .map(|info| ModelSummary {
+                loaded: loaded.as_deref() == Some(info.id.as_str()),
+                engine: format!("{:?}", info.engine_type),
+                downloaded: info.is_downloaded,
+                id: info.id,
+                name: info.name,
+            })
+            .collect();
+        models.sort_by(|a, b| a.id.cmp(&b.id));
+        serde_json::to_string_pretty(&models).unwrap_or_default()
+    }
+}
+
+/// Serve MCP over stdin/stdout, one message per line, until stdin closes.
+/// Logs go to stderr so stdout carries only protocol messages.
+pub fn run_stdio(server: &McpServer) -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = tauri::async_runtime::block_on(server.handle_text(&line, None)) {
+            writeln!(stdout, "{}", reply)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes an SSE session when its stream is dropped.
+struct SessionGuard {
+    server: Arc<McpServer>,
+    session_id: String,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        debug!("MCP session {} closed", self.session_id);
+        self.server
+            .sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.session_id);
+    }
+}
+
+/// Open an SSE session. The first event names the endpoint to POST
+/// messages to; replies arrive as `message` events. The stream ends when
+/// the server is closed.
+async fn sse_stream(
+    State(server): State<Arc<McpServer>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut closed = server.closed.subscribe();
+    let session_id = new_session_id();
+    let (tx, rx) = mpsc::unbounded_channel::<String>();
+    server
+        .sessions
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(session_id.clone(), tx);
+    debug!("MCP session {} opened", session_id);
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("/mcp/message?sessionId={}", session_id));
+    let guard = SessionGuard { server, session_id };
+    let messages = stream::unfold((rx, guard), |(mut rx, guard)| async move {
+        let message = rx.recv().await?;
+        let event = Event::default().event("message").data(message);
+        Some((Ok(event), (rx, guard)))
+    });
+
+    let closed = async move {
+        let _ = closed.wait_for(|&closed| closed).await;
+    };
+    Sse::new(
+        stream::once(async move { Ok(endpoint) })
+            .chain(messages)
+            .take_until(closed),
+    )
+    .keep_alive(KeepAlive::default())
+}
+
+/// Accept a message for an SSE session; the reply is sent on its stream.
+async fn sse_message(
+    State(server): State<Arc<McpServer>>,
+    Query(query): Query<SessionQuery>,
+    key: Option<Extension<ApiKey>>,
+    body: String,
+) -> Response {
+    let sender = server
+        .sessions
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&query.session_id)
+        .cloned();
+    let Some(sender) = sender else {
+        return (StatusCode::NOT_FOUND, "Unknown MCP session").into_response();
+    };
+
+    // Tool calls can take minutes; answer the POST right away
+    tauri::async_runtime::spawn(async move {
+        let key = key.map(|Extension(key)| key);
+        if let Some(reply) = server.handle_text(&body, key.as_ref()).await {
+            let _ = sender.send(reply);
+        }
+    });
+    StatusCode::ACCEPTED.into_response()
+}
+
+fn reply(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": e.code, "message": e.message },
+        }),
+    }
+}
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+fn tool_definitions(transport: Transport) -> Value {
+    let format = json!({
+        "type": "string",
+        "enum": ["txt", "json", "srt", "vtt", "md"],
+        "description": "Output format (default txt). srt/vtt/md include timestamps.",
+    });
+    let mut tools = Vec::new();
+    if transport == Transport::Stdio {
+        tools.push(json!({
+            "name": "transcribe_file",
+            "description": "Transcribe a local audio file (WAV, MP3, FLAC, OGG, AAC, or anything ffmpeg can decode) with the selected speech-to-text model.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path to the audio file" },
+                    "format": format,
+                },
+                "required": ["path"],
+            },
+        }));
+    }
+    tools.push(json!({
+        "name": "transcribe_url",
+        "description": "Download an audio file from an http(s) URL and transcribe it with the selected speech-to-text model.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "http or https URL of the audio file" },
+                "format": format,
+            },
+            "required": ["url"],
+        },
+    }));
+    tools.push(json!({
+        "name": "list_models",
+        "description": "List the available speech-to-text models, whether each is downloaded, and which one is loaded.",
+        "inputSchema": { "type": "object", "properties": {} },
+    }));
+    Value::Array(tools)
+}
+
+fn parse_format(value: Option<&str>) -> Result<TranscriptFormat, String> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(TranscriptFormat::Txt),
+        Some(value) => TranscriptFormat::parse(value).ok_or_else(|| {
+            format!(
+                "Invalid format '{}': expected 'txt', 'json', 'srt', 'vtt' or 'md'",
+                value
+            )
+        }),
+    }
+}
+
+fn file_extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
+
+/// Whether `ip` is on the internet, rather than this machine or a private,
+/// link-local or otherwise special-purpose network.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            // 0.0.0.0/8 ("this network") and 100.64.0.0/10 (carrier-grade NAT)
+            // have no std helpers
+            let special = a == 0 || (a == 100 && b & 0xc0 == 64);
+            !(special
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast())
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                let unique_local = first & 0xfe00 == 0xfc00;
+                let link_local = first & 0xffc0 == 0xfe80;
+                !(unique_local
+                    || link_local
+                    || ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast())
+            }
+        },
+    }
+}
+
+/// Unguessable id for an SSE session.
+fn new_session_id() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", random(), random())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_names(transport: Transport) -> Vec<String> {
+        tool_definitions(transport)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| {
+                assert_eq!(tool["inputSchema"]["type"], "object");
+                tool["name"].as_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tools_have_schemas() {
+        assert_eq!(
+            tool_names(Transport::Stdio),
+            ["transcribe_file", "transcribe_url", "list_models"]
+        );
+        assert_eq!(
+            tool_names(Transport::Http),
+            ["transcribe_url", "list_models"]
+        );
+    }
+
+    #[test]
+    fn only_public_addresses_are_public() {
+        for ip in ["8.8.8.8", "1.1.1.1", "2606:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn replies_carry_request_id() {
+        let ok = reply(json!(7), Ok(json!({})));
+        assert_eq!(ok["id"], 7);
+        assert_eq!(ok["jsonrpc"], "2.0");
+
+        let err = reply(
+            json!("a"),
+            Err(RpcError::new(METHOD_NOT_FOUND, "Method not found: x")),
+        );
+        assert_eq!(err["id"], "a");
+        assert_eq!(err["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn parses_format_argument() {
+        assert_eq!(parse_format(None), Ok(TranscriptFormat::Txt));
+        assert_eq!(parse_format(Some("srt")), Ok(TranscriptFormat::Srt));
+        assert!(parse_format(Some("docx")).is_err());
+    }
+
+    #[test]
+    fn session_ids_are_unique() {
+        assert_ne!(new_session_id(), new_session_id());
+        assert_eq!(new_session_id().len(), 32);
+    }
+}
@@ -0,0 +1,497 @@
+//! Meeting mode: a built-in notetaker that records the microphone and the
+//! system audio for as long as a meeting lasts and transcribes them as it
+//! goes.
+//!
+//! Both sources get recorders of their own, next to the dictation recorder,
+//! so the shortcuts keep working during a meeting. Every 30 seconds the audio
+//! so far is cut off and transcribed while the next chunk records. With
+//! speaker labels, the microphone and the system audio are transcribed
+//! separately and labeled "Me" and "Others"; otherwise they are mixed. Each
+//! new piece of the transcript is published on the event bus, and the whole
+//! transcript is kept in one history entry that is updated after every
+//! chunk, so a crash loses at most the last 30 seconds. Headless mode has
+//! no recorders and no meetings.
+
+use std::fmt;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use tauri::Manager;
+use utoipa::ToSchema;
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::silence::is_silent;
+use crate::audio_toolkit::AudioRecorder;
+use crate::events::{EventBus, EventKind};
+use crate::host::Host;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::history::{
+    HistoryManager, HistorySegment, HistorySource, TranscriptionRecord,
+};
+use crate::managers::transcription::TranscriptionManager;
+use crate::redact::redact;
+use crate::settings::{get_settings, AppSettings};
+
+/// Audio transcribed at a time.
+const CHUNK_DURATION: Duration = Duration::from_secs(30);
+
+const MIC_SPEAKER: &str = "Me";
+const SYSTEM_SPEAKER: &str = "Others";
+
+#[derive(Debug)]
+pub enum MeetingError {
+    /// Headless mode, which has no recorders.
+    Unavailable,
+    AlreadyRunning,
+    NotRunning,
+    Failed(String),
+}
+
+impl fmt::Display for MeetingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeetingError::Unavailable => {
+                f.write_str("Meetings need the desktop app; headless mode has no microphone")
+            }
+            MeetingError::AlreadyRunning => f.write_str("A meeting is already running"),
+            MeetingError::NotRunning => f.write_str("No meeting is running"),
+            MeetingError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MeetingOptions {
+    /// Label each segment with who spoke: the microphone or the system audio.
+    pub speaker_labels: bool,
+    /// Record the system audio next to the microphone.
+    pub system_audio: bool,
+}
+
+impl Default for MeetingOptions {
+    fn default() -> Self {
+        Self {
+            speaker_labels: true,
+            system_audio: true,
+        }
+    }
+}
+
+/// A piece of a meeting's transcript. Times are in seconds since the
+/// meeting started.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct MeetingSegment {
+    pub start: f32,
+    pub end: f32,
+    /// "Me" for the microphone and "Others" for the system audio, with
+    /// speaker labels on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// The transcript of a meeting so far.
+#[derive(Clone, Debug, Default)]
+pub struct MeetingTranscript {
+    /// Unix timestamp in milliseconds.
+    pub started_at: i64,
+    /// Duration of the audio transcribed so far.
+    pub duration_ms: i64,
+    pub segments: Vec<MeetingSegment>,
+    /// The history entry the transcript is saved in, once it has text.
+    pub history_id: Option<i64>,
+}
+
+impl MeetingTranscript {
+    /// The transcript as text, a line per segment such as
+    /// `[12:05] Me: Let's start.`
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|seg| match &seg.speaker {
+                Some(speaker) => format!("[{}] {}: {}", timestamp(seg.start), speaker, seg.text),
+                None => format!("[{}] {}", timestamp(seg.start), seg.text),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `mm:ss`, or `h:mm:ss` from the first hour on.
+fn timestamp(secs: f32) -> String {
+    let secs = secs.max(0.0) as u64;
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}", minutes, secs)
+    }
+}
+
+struct ActiveMeeting {
+    transcript: Arc<Mutex<MeetingTranscript>>,
+    /// Dropped or sent to, it makes the worker transcribe the last chunk and
+    /// close the recorders.
+    stop: mpsc::Sender<()>,
+    worker: JoinHandle<()>,
+}
+
+pub struct MeetingRecorder {
+    host: Host,
+    transcription_manager: Arc<TranscriptionManager>,
+    history_manager: Arc<HistoryManager>,
+    events: Arc<EventBus>,
+    active: Mutex<Option<ActiveMeeting>>,
+}
+
+impl MeetingRecorder {
+    pub fn new(
+        host: Host,
+        transcription_manager: Arc<TranscriptionManager>,
+        history_manager: Arc<HistoryManager>,
+        events: Arc<EventBus>,
+    ) -> Self {
+        Self {
+            host,
+            transcription_manager,
+            history_manager,
+            events,
+            active: Mutex::new(None),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.active.lock().unwrap().is_some()
+    }
+
+    /// Start recording a meeting.
+    pub fn start(&self, options: MeetingOptions) -> Result<MeetingTranscript, MeetingError> {
+        let Host::App(app) = &self.host else {
+            return Err(MeetingError::Unavailable);
+        };
+        let rm = app
+            .try_state::<Arc<AudioRecordingManager>>()
+            .ok_or(MeetingError::Unavailable)?;
+        let mut active = self.active.lock().unwrap();
+        if active.is_some() {
+            return Err(MeetingError::AlreadyRunning);
+        }
+
+        let (mic, system) = open_recorders(&rm, &get_settings(app), options.system_audio)?;
+        self.transcription_manager.initiate_model_load();
+
+        let transcript = Arc::new(Mutex::new(MeetingTranscript {
+            started_at: Utc::now().timestamp_millis(),
+            ..Default::default()
+        }));
+        let worker = ChunkWorker {
+            mic,
+            system,
+            speaker_labels: options.speaker_labels,
+            transcription_manager: self.transcription_manager.clone(),
+            history_manager: self.history_manager.clone(),
+            events: self.events.clone(),
+            transcript: transcript.clone(),
+        };
+        let (stop, stopped) = mpsc::channel();
+        let worker = std::thread::spawn(move || worker.run(stopped));
+
+        let snapshot = transcript.lock().unwrap().clone();
+        *active = Some(ActiveMeeting {
+            transcript,
+            stop,
+            worker,
+        });
+        info!("Meeting started");
+        self.events.publish(EventKind::MeetingStarted);
+        Ok(snapshot)
+    }
+
+    /// The transcript of the running meeting so far.
+    pub fn transcript(&self) -> Result<MeetingTranscript, MeetingError> {
+        let active = self.active.lock().unwrap();
+        let meeting = active.as_ref().ok_or(MeetingError::NotRunning)?;
+        let transcript = meeting.transcript.lock().unwrap().clone();
+        Ok(transcript)
+    }
+
+    /// Stop the meeting and return its transcript, once the last chunk is
+    /// transcribed and saved. Blocks until then.
+    pub fn stop(&self) -> Result<MeetingTranscript, MeetingError> {
+        let meeting = self
+            .active
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or(MeetingError::NotRunning)?;
+        let _ = meeting.stop.send(());
+        if meeting.worker.join().is_err() {
+            error!("Meeting worker panicked");
+        }
+
+        let transcript = meeting.transcript.lock().unwrap().clone();
+        info!(
+            "Meeting stopped after {} seconds",
+            transcript.duration_ms / 1000
+        );
+        self.events.publish(EventKind::MeetingStopped {
+            duration_ms: transcript.duration_ms,
+        });
+        self.transcription_manager
+            .maybe_unload_immediately("meeting");
+        Ok(transcript)
+    }
+}
+
+/// Cuts the recordings into chunks and transcribes them, on a thread of its
+/// own.
+struct ChunkWorker {
+    mic: AudioRecorder,
+    system: Option<AudioRecorder>,
+    speaker_labels: bool,
+    transcription_manager: Arc<TranscriptionManager>,
+    history_manager: Arc<HistoryManager>,
+    events: Arc<EventBus>,
+    transcript: Arc<Mutex<MeetingTranscript>>,
+}
+
+impl ChunkWorker {
+    fn run(mut self, stop: mpsc::Receiver<()>) {
+        let mut chunk_started = Instant::now();
+        let mut offset = 0.0;
+        loop {
+            // Transcribing the previous chunk took part of this one
+            let wait = CHUNK_DURATION.saturating_sub(chunk_started.elapsed());
+            let stopping = !matches!(stop.recv_timeout(wait), Err(RecvTimeoutError::Timeout));
+
+            let mic = take_chunk(&self.mic, !stopping);
+            let system = self
+                .system
+                .as_ref()
+                .map(|system| take_chunk(system, !stopping))
+                .unwrap_or_default();
+            chunk_started = Instant::now();
+
+            let duration = mic.len().max(system.len()) as f32 / WHISPER_SAMPLE_RATE as f32;
+            self.transcribe_chunk(offset, mic, system, stopping);
+            offset += duration;
+            if stopping {
+                break;
+            }
+        }
+
+        let _ = self.mic.close();
+        if let Some(system) = self.system.as_mut() {
+            let _ = system.close();
+        }
+    }
+
+    /// Add the chunk at `offset` seconds to the transcript and save it.
+    fn transcribe_chunk(&self, offset: f32, mic: Vec<f32>, system: Vec<f32>, last: bool) {
+        let duration = mic.len().max(system.len()) as f32 / WHISPER_SAMPLE_RATE as f32;
+        let sources = if self.speaker_labels {
+            vec![(Some(MIC_SPEAKER), mic), (Some(SYSTEM_SPEAKER), system)]
+        } else {
+            vec![(None, mix(mic, system))]
+        };
+
+        let mut segments = Vec::new();
+        for (speaker, samples) in sources {
+            // Engines tend to make up text for silence
+            if is_silent(&samples, WHISPER_SAMPLE_RATE as usize) {
+                continue;
+            }
+            self.transcription_manager.initiate_model_load();
+            let transcription = match self.transcription_manager.transcribe_detailed(samples) {
+                Ok(transcription) => transcription,
+                Err(e) => {
+                    error!("Failed to transcribe meeting audio: {}", e);
+                    continue;
+                }
+            };
+            let speaker = speaker.map(str::to_string);
+            if transcription.segments.is_empty() && !transcription.text.is_empty() {
+                segments.push(MeetingSegment {
+                    start: offset,
+                    end: offset + duration,
+                    speaker,
+                    text: transcription.text,
+                });
+                continue;
+            }
+            segments.extend(
+                transcription
+                    .segments
+                    .into_iter()
+                    .filter(|seg| !seg.text.trim().is_empty())
+                    .map(|seg| MeetingSegment {
+                        start: offset + seg.start,
+                        end: offset + seg.end,
+                        speaker: speaker.clone(),
+                        text: seg.text.trim().to_string(),
+                    }),
+            );
+        }
+        segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        let mut transcript = self.transcript.lock().unwrap();
+        transcript.duration_ms = ((offset + duration) * 1000.0) as i64;
+        for segment in &segments {
+            debug!(
+                "Meeting segment at {:.1}s: '{}'",
+                segment.start,
+                redact(&segment.text)
+            );
+            self.events
+                .publish(EventKind::MeetingSegment(segment.clone()));
+        }
+        let added = !segments.is_empty();
+        transcript.segments.extend(segments);
+        // The last chunk may have no speech but still adds to the duration
+        if added || (last && transcript.history_id.is_some()) {
+            self.save(&mut transcript);
+        }
+    }
+
+    /// Save the transcript in its history entry, adding the entry the first
+    /// time.
+    fn save(&self, transcript: &mut MeetingTranscript) {
+        let record = TranscriptionRecord {
+            source: HistorySource::Meeting,
+            transcription_text: transcript.text(),
+            segments: transcript
+                .segments
+                .iter()
+                .map(|seg| HistorySegment {
+                    start: seg.start,
+                    end: seg.end,
+                    text: seg.text.clone(),
+                    speaker: seg.speaker.clone(),
+                })
+                .collect(),
+            model_id: self.transcription_manager.get_current_model(),
+            duration_ms: transcript.duration_ms,
+            ..Default::default()
+        };
+        match self
+            .history_manager
+            .save_transcription_progress(transcript.history_id, record)
+        {
+            Ok(id) => transcript.history_id = Some(id),
+            Err(e) => error!("Failed to save meeting transcript to history: {}", e),
+        }
+    }
+}
+
+/// Open and start the recorders of a meeting, without the system audio if
+/// there is no loopback device.
+fn open_recorders(
+    rm: &AudioRecordingManager,
+    settings: &AppSettings,
+    system_audio: bool,
+) -> Result<(AudioRecorder, Option<AudioRecorder>), MeetingError> {
+    let failed = |what: &str, e: Box<dyn std::error::Error>| {
+        MeetingError::Failed(format!("Failed to record {}: {}", what, e))
+    };
+
+    let mut mic = AudioRecorder::new().map_err(|e| failed("the microphone", e))?;
+    if let Err(e) = mic
+        .open(rm.get_effective_microphone_device(settings))
+        .and_then(|_| mic.start())
+    {
+        // Recorders don't close when dropped
+        let _ = mic.close();
+        return Err(failed("the microphone", e));
+    }
+
+    let device = if system_audio {
+        rm.get_effective_loopback_device(settings)
+    } else {
+        None
+    };
+    let Some(device) = device else {
+        if system_audio {
+            warn!(
+                "No system audio (loopback) device; recording the meeting from the microphone only"
+            );
+        }
+        return Ok((mic, None));
+    };
+    let mut system = AudioRecorder::new().map_err(|e| failed("the system audio", e))?;
+    match system.open_loopback(device).and_then(|_| system.start()) {
+        Ok(()) => Ok((mic, Some(system))),
+        Err(e) => {
+            let _ = mic.close();
+            let _ = system.close();
+            Err(failed("the system audio", e))
+        }
+    }
+}
+
+/// The audio recorded since the last chunk, recording the next one if
+/// `more`.
+fn take_chunk(recorder: &AudioRecorder, more: bool) -> Vec<f32> {
+    let samples = recorder.stop().unwrap_or_else(|e| {
+        error!("Failed to read meeting audio: {}", e);
+        Vec::new()
+    });
+    if more {
+        if let Err(e) = recorder.start() {
+            error!("Failed to keep recording the meeting: {}", e);
+        }
+    }
+    samples
+}
+
+/// Mix two sources into one, as long as the longer one.
+fn mix(mut a: Vec<f32>, b: Vec<f32>) -> Vec<f32> {
+    if a.len() < b.len() {
+        a.resize(b.len(), 0.0);
+    }
+    for (sample, other) in a.iter_mut().zip(b) {
+        *sample = (*sample + other).clamp(-1.0, 1.0);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_text() {
+        let transcript = MeetingTranscript {
+            segments: vec![
+                MeetingSegment {
+                    start: 2.5,
+                    end: 4.0,
+                    speaker: Some(MIC_SPEAKER.to_string()),
+                    text: "Let's start.".to_string(),
+                },
+                MeetingSegment {
+                    start: 3725.0,
+                    end: 3727.0,
+                    speaker: None,
+                    text: "Thanks, bye.".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            transcript.text(),
+            "[00:02] Me: Let's start.\n[1:02:05] Thanks, bye."
+        );
+    }
+
+    #[test]
+    fn test_mix() {
+        assert_eq!(mix(vec![0.5, 0.75], vec![0.25]), vec![0.75, 0.75]);
+        assert_eq!(mix(vec![0.5], vec![0.75, -0.5]), vec![1.0, -0.5]);
+        assert_eq!(mix(Vec::new(), Vec::new()), Vec::<f32>::new());
+    }
+}
@@ -0,0 +1,191 @@
+//! Keyword spotting: every mention of the requested keywords and phrases
+//! in a transcript, with timestamps and the words around it, so hours of
+//! audio can be searched for names, products or compliance terms.
+//!
+//! Matching ignores case and punctuation and works on whole words, so
+//! "follow-up" matches "Follow up?" but "cat" doesn't match "category".
+//! Times come from the word timings when the segments have them and
+//! otherwise are interpolated within the segment by character position.
+//! Languages written without spaces only match whole phrases between
+//! punctuation.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Words of context on each side of a mention.
+const CONTEXT_WORDS: usize = 8;
+
+/// A segment to search. Times are in seconds.
+pub struct MentionSegment<'a> {
+    pub start: f32,
+    pub end: f32,
+    pub text: &'a str,
+    pub speaker: Option<&'a str>,
+    /// Start and end of each word of `text`, when aligned.
+    pub words: Option<Vec<(f32, f32)>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct Mention {
+    /// The keyword as requested.
+    pub keyword: String,
+    pub start: f32,
+    pub end: f32,
+    /// The mention with the words around it.
+    pub context: String,
+    /// Speaker or channel label of the segment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// A word of the transcript with its timing.
+struct Token<'a> {
+    text: &'a str,
+    start: f32,
+    end: f32,
+    speaker: Option<&'a str>,
+}
+
+/// Mentions of `keywords` in `segments`, sorted by start, in order of time.
+pub fn find(segments: &[MentionSegment], keywords: &[String]) -> Vec<Mention> {
+    let tokens: Vec<Token> = segments.iter().flat_map(tokens).collect();
+    // What is matched: the parts of each word, e.g. "follow" and "up" of
+    // "Follow-up?", with the index of their word
+    let parts: Vec<(usize, String)> = tokens
+        .iter()
+        .enumerate()
+        .flat_map(|(index, token)| normalize(token.text).map(move |part| (index, part)))
+        .collect();
+
+    let mut mentions = Vec::new();
+    for keyword in keywords {
+        let phrase: Vec<String> = normalize(keyword).collect();
+        if phrase.is_empty() || phrase.len() > parts.len() {
+            continue;
+        }
+        for window in parts.windows(phrase.len()) {
+            if !window
+                .iter()
+                .zip(&phrase)
+                .all(|((_, part), word)| part == word)
+            {
+                continue;
+            }
+            let (first, last) = (window[0].0, window[window.len() - 1].0);
+            let context = &tokens
+                [first.saturating_sub(CONTEXT_WORDS)..(last + 1 + CONTEXT_WORDS).min(tokens.len())];
+            mentions.push(Mention {
+                keyword: keyword.clone(),
+                start: tokens[first].start,
+                end: tokens[last].end,
+                context: context
+                    .iter()
+                    .map(|token| token.text)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                speaker: tokens[first].speaker.map(str::to_string),
+            });
+        }
+    }
+    mentions.sort_by(|a, b| a.start.total_cmp(&b.start));
+    mentions
+}
+
+/// The words of `seg` with their timing.
+fn tokens<'a>(seg: &MentionSegment<'a>) -> Vec<Token<'a>> {
+    let words: Vec<(usize, &str)> = seg
+        .text
+        .split_whitespace()
+        .map(|word| (word.as_ptr() as usize - seg.text.as_ptr() as usize, word))
+        .collect();
+    // Aligned words are in the spoken language, which only matches the
+    // text when it wasn't translated
+    let aligned = seg
+        .words
+        .as_ref()
+        .filter(|times| times.len() == words.len());
+    let chars = seg.text.chars().count().max(1) as f32;
+    let at = |offset: usize| {
+        let position = seg.text[..offset].chars().count() as f32 / chars;
+        seg.start + (seg.end - seg.start) * position
+    };
+
+    words
+        .into_iter()
+        .enumerate()
+        .map(|(index, (offset, word))| {
+            let (start, end) = match aligned {
+                Some(times) => times[index],
+                None => (at(offset), at(offset + word.len())),
+            };
+            Token {
+                text: word,
+                start,
+                end,
+                speaker: seg.speaker,
+            }
+        })
+        .collect()
+}
+
+/// The lowercase runs of letters and digits in `text`.
+fn normalize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg<'a>(start: f32, end: f32, text: &'a str) -> MentionSegment<'a> {
+        MentionSegment {
+            start,
+            end,
+            text,
+            speaker: None,
+            words: None,
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let segments = [
+            seg(0.0, 3.7, "Let's schedule a follow-up with Acme."),
+            MentionSegment {
+                speaker: Some("Bob"),
+                words: Some(vec![(10.0, 10.5), (10.6, 11.8)]),
+                ..seg(10.0, 12.0, "ACME signed.")
+            },
+            seg(20.0, 22.0, "Categories, not cats."),
+        ];
+        let keywords = [
+            "acme".to_string(),
+            "Follow up".to_string(),
+            "cat".to_string(),
+        ];
+        let mentions = find(&segments, &keywords);
+
+        let [follow_up, acme, signed] = mentions.as_slice() else {
+            panic!("expected three mentions, got {:?}", mentions);
+        };
+        // Interpolated by character position
+        assert_eq!(follow_up.keyword, "Follow up");
+        assert!((follow_up.start - 1.7).abs() < 0.01);
+        assert!((follow_up.end - 2.6).abs() < 0.01);
+        assert_eq!(acme.keyword, "acme");
+        assert!((acme.start - 3.2).abs() < 0.01);
+        assert_eq!(acme.end, 3.7);
+        assert_eq!(
+            acme.context,
+            "Let's schedule a follow-up with Acme. ACME signed. Categories, not cats."
+        );
+        assert_eq!(acme.speaker, None);
+        // Aligned
+        assert_eq!((signed.start, signed.end), (10.0, 10.5));
+        assert_eq!(signed.speaker.as_deref(), Some("Bob"));
+
+        assert!(find(&segments, &["?".to_string()]).is_empty());
+    }
+}
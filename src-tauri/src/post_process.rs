@@ -0,0 +1,314 @@
+//! LLM post-processing of API transcripts: summaries, action items,
+//! cleanup, entity extraction and chapter titles.
+//!
+//! The transcript is sent to an OpenAI-compatible chat completions API
+//! (hosted providers or a local llama.cpp/Ollama server) with the system
+//! prompt of the requested mode. Configured through `[llm]` in `config`,
+//! falling back to the post-processing provider selected in the app.
+//! Cleanup first runs the rule-based `clean_up_transcript`, and entity
+//! extraction the patterns of `entities`; both work without an LLM.
+
+use log::debug;
+
+use crate::audio_toolkit::clean_up_transcript;
+use crate::config::LlmConfig;
+use crate::entities::{self, Entities};
+use crate::llm_client::{self, strip_invisible_chars};
+use crate::settings::{AppSettings, PostProcessProvider, APPLE_INTELLIGENCE_PROVIDER_ID};
+
+/// What the LLM is asked to do with the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostProcessMode {
+    Summarize,
+    ActionItems,
+    Cleanup,
+    /// Dates, amounts, phone numbers, emails and names as JSON.
+    Entities,
+}
+
+impl PostProcessMode {
+    /// Parse a `post_process` value: `summarize` (or `summary`),
+    /// `action_items`, `cleanup` or `entities`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "summarize" | "summary" => Some(Self::Summarize),
+            "action_items" => Some(Self::ActionItems),
+            "cleanup" => Some(Self::Cleanup),
+            "entities" => Some(Self::Entities),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Summarize => "summarize",
+            Self::ActionItems => "action_items",
+            Self::Cleanup => "cleanup",
+            Self::Entities => "entities",
+        }
+    }
+
+    /// System prompt sent with the transcript.
+    fn prompt(&self) -> &'static str {
+        match self {
+            Self::Summarize => {
+                "Summarize the transcript provided by the user in a short paragraph. Keep \
+                 names, numbers and decisions. Write in the language of the transcript and \
+                 reply with the summary only."
+            }
+            Self::ActionItems => {
+                "List the action items in the transcript provided by the user as a Markdown \
+                 bullet list, one task per line, with the owner and due date when they are \
+                 mentioned. Write in the language of the transcript and reply with the list \
+                 only, or with \"No action items.\" if there are none."
+            }
+            Self::Cleanup => {
+                "Clean up the transcript provided by the user: remove filler words, false \
+                 starts and repetitions, and fix punctuation, casing and obvious \
+                 transcription errors. Do not summarize, rephrase or add anything. Reply \
+                 with the cleaned text only."
+            }
+            Self::Entities => {
+                "Extract the dates, amounts of money, phone numbers, email addresses and \
+                 names of people and organizations from the transcript provided by the user, \
+                 as JSON with the lists \"dates\", \"amounts\", \"phone_numbers\", \"emails\" \
+                 and \"names\". Give each entity's \"text\" as it appears in the transcript \
+                 and its normalized \"value\": dates as YYYY-MM-DD, phone numbers as digits \
+                 with a leading + if given, emails in lowercase, amounts as a number with \
+                 their ISO 4217 \"currency\", and null for names or when unknown. Reply with \
+                 the JSON only."
+            }
+        }
+    }
+}
+
+/// System prompt sent with the text of a chapter.
+const CHAPTER_TITLE_PROMPT: &str = "Write a heading of at most six words for the part of a \
+     transcript provided by the user, as for the chapters of podcast show notes. Write in \
+     the language of the transcript and reply with the heading only, without quotes.";
+
+pub struct PostProcessor {
+    llm: Option<LlmEndpoint>,
+    /// App language, selecting the filler words removed by cleanup.
+    language: String,
+}
+
+/// An OpenAI-compatible chat completions endpoint.
+struct LlmEndpoint {
+    provider: PostProcessProvider,
+    api_key: String,
+    model: String,
+}
+
+impl PostProcessor {
+    pub fn from_config(config: &LlmConfig, settings: &AppSettings) -> Self {
+        Self {
+            llm: LlmEndpoint::from_config(config, settings),
+            language: settings.app_language.clone(),
+        }
+    }
+
+    /// Whether `mode` can run. Everything but cleanup and entities needs
+    /// an LLM.
+    pub fn supports(&self, mode: PostProcessMode) -> bool {
+        self.llm.is_some() || matches!(mode, PostProcessMode::Cleanup | PostProcessMode::Entities)
+    }
+
+    /// The LLM prompt `mode` runs with, or `None` without an LLM.
+    pub fn prompt(&self, mode: PostProcessMode) -> Option<&'static str> {
+        self.llm.as_ref().map(|_| mode.prompt())
+    }
+
+    /// Whether an LLM is configured, e.g. to write chapter titles.
+    pub fn has_llm(&self) -> bool {
+        self.llm.is_some()
+    }
+
+    /// A heading for the text of a chapter, written by the LLM.
+    pub async fn chapter_title(&self, text: &str) -> Result<String, String> {
+        let llm = self
+            .llm
+            .as_ref()
+            .ok_or_else(|| "No LLM is configured for chapter titles".to_string())?;
+        debug!(
+            "Titling chapter with provider '{}' (model: {})",
+            llm.provider.id, llm.model
+        );
+        let title = llm.complete(CHAPTER_TITLE_PROMPT, text, None).await?;
+        Ok(title
+            .trim_matches(|c| c == '"' || c == '#')
+            .trim()
+            .to_string())
+    }
+
+    /// The entities in `text`, found by the LLM if there is one and by
+    /// the built-in patterns.
+    pub async fn extract_entities(&self, text: &str) -> Result<Entities, String> {
+        let found = entities::extract(text);
+        let Some(llm) = &self.llm else {
+            return Ok(found);
+        };
+        debug!(
+            "Extracting entities with provider '{}' (model: {})",
+            llm.provider.id, llm.model
+        );
+        let schema = llm
+            .provider
+            .supports_structured_output
+            .then(Entities::json_schema);
+        let reply = llm
+            .complete(PostProcessMode::Entities.prompt(), text, schema)
+            .await?;
+        let mut extracted = Entities::parse(&reply)?;
+        extracted.merge(found);
+        Ok(extracted)
+    }
+
+    /// Process `text` according to `mode`. Entities come as JSON.
+    pub async fn process(&self, mode: PostProcessMode, text: &str) -> Result<String, String> {
+        if mode == PostProcessMode::Entities {
+            let entities = self.extract_entities(text).await?;
+            return serde_json::to_string(&entities).map_err(|e| e.to_string());
+        }
+        let text = match mode {
+            PostProcessMode::Cleanup => clean_up_transcript(text, &self.language),
+            _ => text.to_string(),
+        };
+        match &self.llm {
+            Some(llm) if !text.is_empty() => llm.process(mode, &text).await,
+            Some(_) => Ok(text),
+            None if self.supports(mode) => Ok(text),
+            None => Err(format!(
+                "No LLM is configured for '{}' post-processing",
+                mode.as_str()
+            )),
+        }
+    }
+}
+
+impl LlmEndpoint {
+    /// The `[llm]` endpoint when `url` is set, otherwise the app's selected
+    /// post-processing provider if it has a model.
+    fn from_config(config: &LlmConfig, settings: &AppSettings) -> Option<Self> {
+        if let Some(url) = config.url.as_deref().filter(|url| !url.trim().is_empty()) {
+            return Some(Self {
+                provider: PostProcessProvider {
+                    id: "api_post_process".to_string(),
+                    label: "API post-processing".to_string(),
+                    base_url: url.trim().to_string(),
+                    allow_base_url_edit: false,
+                    models_endpoint: None,
+                    supports_structured_output: false,
+                },
+                api_key: config.api_key.clone().unwrap_or_default(),
+                model: config.model.clone().unwrap_or_default(),
+            });
+        }
+
+        // Apple Intelligence has no HTTP endpoint
+        let provider = settings
+            .active_post_process_provider()
+            .filter(|provider| provider.id != APPLE_INTELLIGENCE_PROVIDER_ID)?;
+        let model = settings
+            .post_process_models
+            .get(&provider.id)
+            .filter(|model| !model.trim().is_empty())?;
+        Some(Self {
+            provider: provider.clone(),
+            api_key: settings
+                .post_process_api_keys
+                .get(&provider.id)
+                .cloned()
+                .unwrap_or_default(),
+            model: model.clone(),
+        })
+    }
+
+    /// Run `text` through the LLM with the prompt for `mode`.
+    async fn process(&self, mode: PostProcessMode, text: &str) -> Result<String, String> {
+        debug!(
+            "Post-processing transcript ({}) with provider '{}' (model: {})",
+            mode.as_str(),
+            self.provider.id,
+            self.model
+        );
+        self.complete(mode.prompt(), text, None).await
+    }
+
+    /// Send `text` to the LLM with the system `prompt`, asking for a reply
+    /// in the shape of `json_schema` if given.
+    async fn complete(
+        &self,
+        prompt: &str,
+        text: &str,
+        json_schema: Option<serde_json::Value>,
+    ) -> Result<String, String> {
+        let content = llm_client::send_chat_completion_with_schema(
+            &self.provider,
+            self.api_key.clone(),
+            &self.model,
+            text.to_string(),
+            Some(prompt.to_string()),
+            json_schema,
+        )
+        .await?
+        .ok_or_else(|| "LLM response was empty".to_string())?;
+
+        Ok(strip_invisible_chars(&content).trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(
+            PostProcessMode::parse("summarize"),
+            Some(PostProcessMode::Summarize)
+        );
+        assert_eq!(
+            PostProcessMode::parse("Summary"),
+            Some(PostProcessMode::Summarize)
+        );
+        assert_eq!(
+            PostProcessMode::parse("action-items"),
+            Some(PostProcessMode::ActionItems)
+        );
+        assert_eq!(
+            PostProcessMode::parse("entities"),
+            Some(PostProcessMode::Entities)
+        );
+        assert_eq!(PostProcessMode::parse("translate"), None);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut settings = get_default_settings();
+        settings.post_process_provider_id = "custom".to_string();
+
+        // No URL and no model selected in the app: only rule-based cleanup
+        let processor = PostProcessor::from_config(&LlmConfig::default(), &settings);
+        assert!(processor.llm.is_none());
+        assert!(processor.supports(PostProcessMode::Cleanup));
+        assert!(processor.supports(PostProcessMode::Entities));
+        assert!(!processor.supports(PostProcessMode::Summarize));
+
+        settings
+            .post_process_models
+            .insert("custom".to_string(), "llama3".to_string());
+        let llm = LlmEndpoint::from_config(&LlmConfig::default(), &settings).unwrap();
+        assert_eq!(llm.provider.id, "custom");
+        assert_eq!(llm.model, "llama3");
+
+        let config = LlmConfig {
+            url: Some("http://localhost:8080/v1".to_string()),
+            ..Default::default()
+        };
+        let llm = LlmEndpoint::from_config(&config, &settings).unwrap();
+        assert_eq!(llm.provider.base_url, "http://localhost:8080/v1");
+        assert_eq!(llm.model, "");
+    }
+}
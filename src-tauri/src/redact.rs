@@ -0,0 +1,103 @@
+//! Keeps what people said out of the logs. Transcripts, post-processed and
+//! translated text, and audio file names and URLs are logged as their
+//! length only, unless transcript logging is turned on with
+//! `[logging] transcripts = true` (HANDY_LOG_TRANSCRIPTS) for debugging.
+//! Ephemeral API requests are redacted either way.
+
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static LOG_TRANSCRIPTS: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static SUPPRESSED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Log transcripts and audio metadata verbatim instead of redacting them.
+pub fn set_log_transcripts(enabled: bool) {
+    LOG_TRANSCRIPTS.store(enabled, Ordering::Relaxed);
+}
+
+/// `text` for a log message: itself when transcript logging is on,
+/// otherwise a placeholder with its length.
+pub fn redact(text: &str) -> Redacted<'_> {
+    Redacted {
+        text,
+        always: false,
+    }
+}
+
+/// Redact everything logged on this thread until the guard is dropped,
+/// even with transcript logging on; e.g. while an ephemeral request is
+/// transcribed.
+pub fn suppress() -> SuppressGuard {
+    SuppressGuard(SUPPRESSED.replace(true))
+}
+
+pub struct SuppressGuard(bool);
+
+impl Drop for SuppressGuard {
+    fn drop(&mut self) {
+        SUPPRESSED.set(self.0);
+    }
+}
+
+pub struct Redacted<'a> {
+    text: &'a str,
+    always: bool,
+}
+
+impl Redacted<'_> {
+    /// Redact the text even with transcript logging on when `always` is set.
+    pub fn always(mut self, always: bool) -> Self {
+        self.always = always;
+        self
+    }
+
+    fn verbatim(&self) -> bool {
+        !self.always && !SUPPRESSED.get() && LOG_TRANSCRIPTS.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.verbatim() {
+            f.write_str(self.text)
+        } else {
+            write!(f, "<redacted, {} chars>", self.text.chars().count())
+        }
+    }
+}
+
+impl fmt::Debug for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.verbatim() {
+            write!(f, "{:?}", self.text)
+        } else {
+            fmt::Display::fmt(self, f)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_unless_enabled() {
+        assert_eq!(redact("héllo").to_string(), "<redacted, 5 chars>");
+        assert_eq!(format!("{:?}", redact("hi")), "<redacted, 2 chars>");
+
+        set_log_transcripts(true);
+        assert_eq!(redact("héllo").to_string(), "héllo");
+        assert_eq!(format!("{:?}", redact("hi")), "\"hi\"");
+        assert_eq!(redact("hi").always(true).to_string(), "<redacted, 2 chars>");
+        {
+            let _suppressed = suppress();
+            assert_eq!(redact("hi").to_string(), "<redacted, 2 chars>");
+        }
+        assert_eq!(redact("hi").to_string(), "hi");
+        set_log_transcripts(false);
+    }
+}
@@ -0,0 +1,183 @@
+//! The transcription stack shared by the desktop app and headless mode:
+//! model, transcription and history managers, the benchmarker, the event
+//! bus, meetings and the REST API server.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use log::{error, info, warn};
+
+use crate::api::ApiServer;
+use crate::benchmark::Benchmarker;
+use crate::config::{Config, ModelsConfig};
+use crate::events::EventBus;
+use crate::host::Host;
+use crate::managers::api_keys::ApiKeyManager;
+use crate::managers::catalog;
+use crate::managers::history::HistoryManager;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::meeting::MeetingRecorder;
+use crate::settings::AppSettings;
+
+pub struct Services {
+    pub config: Config,
+    pub model_manager: Arc<ModelManager>,
+    pub transcription_manager: Arc<TranscriptionManager>,
+    pub history_manager: Arc<HistoryManager>,
+    pub benchmarker: Arc<Benchmarker>,
+    pub events: Arc<EventBus>,
+    pub meeting: Arc<MeetingRecorder>,
+    pub api_server: Arc<ApiServer>,
+}
+
+impl Services {
+    /// Create the managers and the (stopped) API server, and select the
+    /// configured default model.
+    pub fn new(host: &Host, config: Config) -> Result<Self> {
+        crate::redact::set_log_transcripts(config.logging.transcripts.unwrap_or(false));
+        crate::ffmpeg::configure(&config.ffmpeg, host.settings().ffmpeg_path.as_deref());
+
+        let model_manager = Arc::new(
+            ModelManager::new(host.clone(), config.models.dir.clone())
+                .context("Failed to initialize model manager")?,
+        );
+        if let Err(e) = select_default_model(host, &model_manager, &config.models) {
+            error!("{:#}", e);
+        }
+        check_routes(&model_manager, &config.models);
+
+        let transcription_manager = Arc::new(
+            TranscriptionManager::new(host.clone(), model_manager.clone(), &config.models)
+                .context("Failed to initialize transcription manager")?,
+        );
+        let history_manager = Arc::new(
+            HistoryManager::new(host.clone(), &config.history)
+                .context("Failed to initialize history manager")?,
+        );
+        let benchmarker = Arc::new(
+            Benchmarker::new(
+                host.clone(),
+                transcription_manager.clone(),
+                model_manager.clone(),
+            )
+            .context("Failed to initialize benchmarker")?,
+        );
+        let api_keys = Arc::new(ApiKeyManager::new(host).context("Failed to initialize API keys")?);
+        let events = Arc::new(EventBus::new());
+        let meeting = Arc::new(MeetingRecorder::new(
+            host.clone(),
+            transcription_manager.clone(),
+            history_manager.clone(),
+            events.clone(),
+        ));
+
+        let api_server = Arc::new(ApiServer::new(
+            host.clone(),
+            transcription_manager.clone(),
+            model_manager.clone(),
+            benchmarker.clone(),
+            history_manager.clone(),
+            api_keys,
+            events.clone(),
+            meeting.clone(),
+            &config,
+        ));
+
+        Ok(Self {
+            config,
+            model_manager,
+            transcription_manager,
+            history_manager,
+            benchmarker,
+            events,
+            meeting,
+            api_server,
+        })
+    }
+
+    /// Port for the REST API: the configured port, otherwise the port from
+    /// settings (default 8720).
+    pub fn api_port(&self, settings: &AppSettings) -> u16 {
+        self.config.api.port.unwrap_or(settings.api_server_port)
+    }
+}
+
+/// Warn about `[models.routes]` entries that can't be used; requests in
+/// those languages fall back to the selected model.
+fn check_routes(model_manager: &ModelManager, config: &ModelsConfig) {
+    for (language, model_id) in &config.routes {
+        match model_manager.get_model_info(model_id) {
+            Some(info) if info.is_downloaded => {
+                info!("Routing '{}' to model '{}'", language, model_id)
+            }
+            Some(_) => warn!(
+                "Model '{}' routed for '{}' is not downloaded",
+                model_id, language
+            ),
+            None => warn!(
+                "Model '{}' routed for '{}' does not exist",
+                model_id, language
+            ),
+        }
+    }
+}
+
+/// Make the configured `default` model, or a downloaded model of the
+/// configured `engine`, the selected model.
+fn select_default_model(
+    host: &Host,
+    model_manager: &ModelManager,
+    config: &ModelsConfig,
+) -> Result<()> {
+    let mut settings = host.settings();
+
+    let model_id = if let Some(model_id) = &config.default {
+        match model_manager.get_model_info(model_id) {
+            Some(info) if info.is_downloaded => model_id.clone(),
+            Some(_) => return Err(anyhow!("Configured model '{}' is not downloaded", model_id)),
+            None => return Err(anyhow!("Configured model '{}' does not exist", model_id)),
+        }
+    } else if let Some(engine) = &config.engine {
+        let candidates = downloaded_models_of_engine(model_manager, engine);
+        if candidates.contains(&settings.selected_model) {
+            return Ok(());
+        }
+        candidates
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No downloaded model for configured engine '{}'", engine))?
+    } else {
+        return Ok(());
+    };
+
+    if settings.selected_model != model_id {
+        info!("Selecting configured model '{}'", model_id);
+        if let Some(warning) = model_manager
+            .get_model_info(&model_id)
+            .and_then(|info| catalog::check_fit(&info))
+        {
+            warn!("{}", warning);
+        }
+        settings.selected_model = model_id;
+        host.write_settings(settings);
+    }
+    Ok(())
+}
+
+/// Ids of the downloaded models of `engine`, e.g. "whisper" or "parakeet",
+/// sorted.
+pub fn downloaded_models_of_engine(model_manager: &ModelManager, engine: &str) -> Vec<String> {
+    let normalized = engine.replace(['-', '_'], "");
+    let mut models: Vec<_> = model_manager
+        .get_available_models()
+        .into_iter()
+        .filter(|info| {
+            info.is_downloaded
+                && format!("{:?}", info.engine_type).eq_ignore_ascii_case(&normalized)
+        })
+        .map(|info| info.id)
+        .collect();
+    models.sort();
+    models
+}
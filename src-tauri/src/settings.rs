@@ -5,6 +5,10 @@ use specta::Type;
 use std::collections::HashMap;
 use tauri::AppHandle;
 use tauri_plugin_store::StoreExt;
+use utoipa::ToSchema;
+
+use crate::audio_toolkit::{Replacement, VoiceCommand};
+use crate::foreground::ForegroundApp;
 
 pub const APPLE_INTELLIGENCE_PROVIDER_ID: &str = "apple_intelligence";
 pub const APPLE_INTELLIGENCE_DEFAULT_MODEL_ID: &str = "Apple Intelligence";
@@ -92,6 +96,112 @@ pub struct LLMPrompt {
     pub prompt: String,
 }
 
+/// A user-defined replacement applied to transcription output, e.g. the
+/// voice macro "new line" -> "\n".
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type, ToSchema)]
+pub struct TextReplacement {
+    /// Spoken text to replace, or a regular expression when `regex` is set.
+    pub pattern: String,
+    pub replacement: String,
+    #[serde(default)]
+    pub regex: bool,
+}
+
+impl TextReplacement {
+    pub fn compile(&self) -> Result<Replacement, String> {
+        if self.regex {
+            Replacement::regex(&self.pattern, &self.replacement)
+        } else {
+            Replacement::literal(&self.pattern, &self.replacement)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceCommandAction {
+    /// Insert `text` after the previous word, e.g. ",".
+    Punctuation,
+    /// Insert `text` before the next word, e.g. "(".
+    Opening,
+    /// Insert `text` between spaces, e.g. "&".
+    Symbol,
+    NewLine,
+    NewParagraph,
+    DeleteLastWord,
+    DeleteLastSentence,
+    CapitalizeNextWord,
+    UppercaseNextWord,
+    LowercaseNextWord,
+    Literal,
+}
+
+/// A spoken phrase for a voice command, on top of the built-in phrases of
+/// the dictation language, e.g. "punto e virgola" -> punctuation ";".
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct VoiceCommandPhrase {
+    pub phrase: String,
+    pub action: VoiceCommandAction,
+    /// What `punctuation`, `opening` and `symbol` insert.
+    #[serde(default)]
+    pub text: String,
+}
+
+impl VoiceCommandPhrase {
+    pub fn compile(&self) -> Result<(String, VoiceCommand), String> {
+        if self.phrase.trim().is_empty() {
+            return Err("Voice command phrase is empty".to_string());
+        }
+        let text = || {
+            if self.text.is_empty() {
+                Err(format!("Voice command '{}' has no text", self.phrase))
+            } else {
+                Ok(self.text.clone())
+            }
+        };
+        let command = match self.action {
+            VoiceCommandAction::Punctuation => VoiceCommand::Punctuation(text()?),
+            VoiceCommandAction::Opening => VoiceCommand::Opening(text()?),
+            VoiceCommandAction::Symbol => VoiceCommand::Symbol(text()?),
+            VoiceCommandAction::NewLine => VoiceCommand::NewLine,
+            VoiceCommandAction::NewParagraph => VoiceCommand::NewParagraph,
+            VoiceCommandAction::DeleteLastWord => VoiceCommand::DeleteLastWord,
+            VoiceCommandAction::DeleteLastSentence => VoiceCommand::DeleteLastSentence,
+            VoiceCommandAction::CapitalizeNextWord => VoiceCommand::CapitalizeNextWord,
+            VoiceCommandAction::UppercaseNextWord => VoiceCommand::UppercaseNextWord,
+            VoiceCommandAction::LowercaseNextWord => VoiceCommand::LowercaseNextWord,
+            VoiceCommandAction::Literal => VoiceCommand::Literal,
+        };
+        Ok((self.phrase.clone(), command))
+    }
+}
+
+/// Dictation settings for some applications, applied when one of them is in
+/// the foreground, e.g. verbatim with code vocabulary in the terminal and
+/// cleaned-up prose in the mail client. Unset values keep the global
+/// settings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AppProfile {
+    pub name: String,
+    /// Process names, display names or bundle ids, e.g. "WindowsTerminal",
+    /// "Code" or "com.apple.mail". Case and a `.exe` or `.app` extension
+    /// don't matter.
+    pub apps: Vec<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Vocabulary on top of the custom words.
+    #[serde(default)]
+    pub custom_words: Vec<String>,
+    /// Whether the transcript is post-processed, whichever shortcut was
+    /// used.
+    #[serde(default)]
+    pub post_process: Option<bool>,
+    #[serde(default)]
+    pub post_process_prompt_id: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Type)]
 pub struct PostProcessProvider {
     pub id: String,
@@ -162,6 +272,13 @@ pub enum RecordingRetentionPeriod {
     Months3,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingSource {
+    Microphone,
+    SystemAudio,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
 #[serde(rename_all = "snake_case")]
 pub enum KeyboardImplementation {
@@ -180,6 +297,12 @@ impl Default for KeyboardImplementation {
     }
 }
 
+impl Default for RecordingSource {
+    fn default() -> Self {
+        RecordingSource::Microphone
+    }
+}
+
 impl Default for ModelUnloadTimeout {
     fn default() -> Self {
         ModelUnloadTimeout::Never
@@ -293,6 +416,10 @@ pub struct AppSettings {
     pub update_checks_enabled: bool,
     #[serde(default = "default_model")]
     pub selected_model: String,
+    /// Select the model recommended for this machine's hardware. Off for
+    /// settings saved before the option existed, so their choice is kept.
+    #[serde(default)]
+    pub auto_select_model: bool,
     #[serde(default = "default_always_on_microphone")]
     pub always_on_microphone: bool,
     #[serde(default)]
@@ -300,9 +427,27 @@ pub struct AppSettings {
     #[serde(default)]
     pub clamshell_microphone: Option<String>,
     #[serde(default)]
+    pub recording_source: RecordingSource,
+    #[serde(default)]
+    pub selected_loopback_device: Option<String>,
+    #[serde(default)]
     pub selected_output_device: Option<String>,
+    /// Start dictating when the wake word is heard. Keeps the microphone
+    /// open.
+    #[serde(default)]
+    pub wake_word_enabled: bool,
+    /// A built-in phrase, e.g. "hey_jarvis", or the path of a custom
+    /// openWakeWord model.
+    #[serde(default = "default_wake_word")]
+    pub wake_word: String,
+    /// Score (0 to 1) that counts as hearing the wake word; higher means
+    /// fewer false activations.
+    #[serde(default = "default_wake_word_threshold")]
+    pub wake_word_threshold: f32,
     #[serde(default = "default_translate_to_english")]
     pub translate_to_english: bool,
+    #[serde(default = "default_suppress_hallucinations")]
+    pub suppress_hallucinations: bool,
     #[serde(default = "default_selected_language")]
     pub selected_language: String,
     #[serde(default = "default_overlay_position")]
@@ -362,6 +507,30 @@ pub struct AppSettings {
     pub external_script_path: Option<String>,
     #[serde(default)]
     pub custom_filler_words: Option<Vec<String>>,
+    #[serde(default)]
+    pub text_replacements: Vec<TextReplacement>,
+    /// Interpret spoken commands like "comma", "new paragraph" and "delete
+    /// last sentence" in dictations.
+    #[serde(default)]
+    pub voice_commands: bool,
+    #[serde(default)]
+    pub voice_command_phrases: Vec<VoiceCommandPhrase>,
+    /// Profiles picked by the application in the foreground; the first
+    /// that lists it applies.
+    #[serde(default)]
+    pub app_profiles: Vec<AppProfile>,
+    #[serde(default = "default_api_server_enabled")]
+    pub api_server_enabled: bool,
+    #[serde(default = "default_api_server_port")]
+    pub api_server_port: u16,
+    /// Transcription requests the API handles at once, as set through
+    /// `PUT /config`; `[api] max_concurrency` takes precedence.
+    #[serde(default)]
+    pub api_max_concurrency: Option<usize>,
+    /// The ffmpeg binary, when it isn't found on its own; `[ffmpeg] path`
+    /// takes precedence.
+    #[serde(default)]
+    pub ffmpeg_path: Option<String>,
 }
 
 fn default_model() -> String {
@@ -376,6 +545,10 @@ fn default_translate_to_english() -> bool {
     false
 }
 
+fn default_suppress_hallucinations() -> bool {
+    true
+}
+
 fn default_start_hidden() -> bool {
     false
 }
@@ -407,6 +580,14 @@ fn default_log_level() -> LogLevel {
     LogLevel::Debug
 }
 
+fn default_wake_word() -> String {
+    "hey_jarvis".to_string()
+}
+
+fn default_wake_word_threshold() -> f32 {
+    0.5
+}
+
 fn default_word_correction_threshold() -> f64 {
     0.18
 }
@@ -415,6 +596,14 @@ fn default_paste_delay_ms() -> u64 {
     60
 }
 
+fn default_api_server_enabled() -> bool {
+    true
+}
+
+fn default_api_server_port() -> u16 {
+    8720
+}
+
 fn default_auto_submit() -> bool {
     false
 }
@@ -572,7 +761,7 @@ fn default_typing_tool() -> TypingTool {
     TypingTool::Auto
 }
 
-fn ensure_post_process_defaults(settings: &mut AppSettings) -> bool {
+pub(crate) fn ensure_post_process_defaults(settings: &mut AppSettings) -> bool {
     let mut changed = false;
     for provider in default_post_process_providers() {
         // Use match to do a single lookup - either sync existing or add new
@@ -692,11 +881,18 @@ pub fn get_default_settings() -> AppSettings {
         autostart_enabled: default_autostart_enabled(),
         update_checks_enabled: default_update_checks_enabled(),
         selected_model: "".to_string(),
+        auto_select_model: true,
         always_on_microphone: false,
         selected_microphone: None,
         clamshell_microphone: None,
+        recording_source: RecordingSource::default(),
+        selected_loopback_device: None,
         selected_output_device: None,
+        wake_word_enabled: false,
+        wake_word: default_wake_word(),
+        wake_word_threshold: default_wake_word_threshold(),
         translate_to_english: false,
+        suppress_hallucinations: default_suppress_hallucinations(),
         selected_language: "auto".to_string(),
         overlay_position: default_overlay_position(),
         debug_mode: false,
@@ -727,6 +923,14 @@ pub fn get_default_settings() -> AppSettings {
         typing_tool: default_typing_tool(),
         external_script_path: None,
         custom_filler_words: None,
+        text_replacements: Vec::new(),
+        voice_commands: false,
+        voice_command_phrases: Vec::new(),
+        app_profiles: Vec::new(),
+        api_server_enabled: default_api_server_enabled(),
+        api_server_port: default_api_server_port(),
+        api_max_concurrency: None,
+        ffmpeg_path: None,
     }
 }
 
@@ -751,6 +955,13 @@ impl AppSettings {
             .iter_mut()
             .find(|provider| provider.id == provider_id)
     }
+
+    /// The profile for `app`, if one lists it.
+    pub fn app_profile(&self, app: &ForegroundApp) -> Option<&AppProfile> {
+        self.app_profiles
+            .iter()
+            .find(|profile| profile.apps.iter().any(|pattern| app.matches(pattern)))
+    }
 }
 
 pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
@@ -850,16 +1061,6 @@ pub fn get_stored_binding(app: &AppHandle, id: &str) -> ShortcutBinding {
     binding
 }
 
-pub fn get_history_limit(app: &AppHandle) -> usize {
-    let settings = get_settings(app);
-    settings.history_limit
-}
-
-pub fn get_recording_retention_period(app: &AppHandle) -> RecordingRetentionPeriod {
-    let settings = get_settings(app);
-    settings.recording_retention_period
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -870,4 +1071,32 @@ mod tests {
         assert!(!settings.auto_submit);
         assert_eq!(settings.auto_submit_key, AutoSubmitKey::Enter);
     }
+
+    #[test]
+    fn app_profile_picks_first_listing_app() {
+        let profile = |name: &str, apps: &[&str]| AppProfile {
+            name: name.to_string(),
+            apps: apps.iter().map(|app| app.to_string()).collect(),
+            model: None,
+            language: None,
+            custom_words: Vec::new(),
+            post_process: None,
+            post_process_prompt_id: None,
+        };
+        let mut settings = get_default_settings();
+        settings.app_profiles = vec![
+            profile("Terminal", &["WindowsTerminal", "alacritty"]),
+            profile("Mail", &["com.apple.mail", "thunderbird"]),
+            profile("Other", &["thunderbird"]),
+        ];
+        let app = |name: &str| ForegroundApp {
+            name: name.to_string(),
+            bundle_id: None,
+        };
+
+        let found = |name| settings.app_profile(&app(name)).map(|p| p.name.as_str());
+        assert_eq!(found("WindowsTerminal.exe"), Some("Terminal"));
+        assert_eq!(found("thunderbird"), Some("Mail"));
+        assert_eq!(found("firefox"), None);
+    }
 }
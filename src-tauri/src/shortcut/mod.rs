@@ -523,6 +523,15 @@ pub fn change_translate_to_english_setting(app: AppHandle, enabled: bool) -> Res
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn change_suppress_hallucinations_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.suppress_hallucinations = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_selected_language_setting(app: AppHandle, language: String) -> Result<(), String> {
@@ -646,6 +655,77 @@ pub fn update_custom_words(app: AppHandle, words: Vec<String>) -> Result<(), Str
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn update_text_replacements(
+    app: AppHandle,
+    replacements: Vec<settings::TextReplacement>,
+) -> Result<(), String> {
+    for replacement in &replacements {
+        replacement.compile()?;
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.text_replacements = replacements;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_voice_commands_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.voice_commands = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_voice_command_phrases(
+    app: AppHandle,
+    phrases: Vec<settings::VoiceCommandPhrase>,
+) -> Result<(), String> {
+    for phrase in &phrases {
+        phrase.compile()?;
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.voice_command_phrases = phrases;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_app_profiles(
+    app: AppHandle,
+    profiles: Vec<settings::AppProfile>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    for profile in &profiles {
+        if profile.name.trim().is_empty() {
+            return Err("App profile name is empty".to_string());
+        }
+        if profile.apps.iter().all(|app| app.trim().is_empty()) {
+            return Err(format!("App profile '{}' lists no apps", profile.name));
+        }
+        if let Some(prompt_id) = &profile.post_process_prompt_id {
+            if !settings
+                .post_process_prompts
+                .iter()
+                .any(|prompt| &prompt.id == prompt_id)
+            {
+                return Err(format!(
+                    "App profile '{}' uses unknown prompt '{}'",
+                    profile.name, prompt_id
+                ));
+            }
+        }
+    }
+    settings.app_profiles = profiles;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_word_correction_threshold_setting(
@@ -725,6 +805,16 @@ pub fn change_external_script_path_setting(
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn change_ffmpeg_path_setting(app: AppHandle, path: Option<String>) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    crate::ffmpeg::set_settings_path(path.as_deref());
+    settings.ffmpeg_path = path;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_clipboard_handling_setting(app: AppHandle, handling: String) -> Result<(), String> {
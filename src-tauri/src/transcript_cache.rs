@@ -0,0 +1,173 @@
+//! Content-addressed cache of API transcription results, so repeated
+//! uploads of the same audio (webhook retries, bot redeliveries) don't run
+//! the model again.
+//!
+//! Keys are SHA-256 hashes of the audio bytes, the request options and the
+//! settings that shape the transcript. The least recently used entry is
+//! evicted once the cache is full, and entries expire after the TTL.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+pub type CacheKey = [u8; 32];
+
+/// Builds a [`CacheKey`] from the parts of a request.
+#[derive(Default)]
+pub struct KeyBuilder(Sha256);
+
+impl KeyBuilder {
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.update((bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+        self
+    }
+
+    /// Hash everything `reader` yields, e.g. an upload spooled to disk.
+    pub fn reader(mut self, mut reader: impl Read) -> std::io::Result<Self> {
+        let mut buffer = vec![0; 64 * 1024];
+        let mut len = 0u64;
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            self.0.update(&buffer[..read]);
+            len += read as u64;
+        }
+        self.0.update(len.to_le_bytes());
+        Ok(self)
+    }
+
+    pub fn str(self, value: &str) -> Self {
+        self.bytes(value.as_bytes())
+    }
+
+    pub fn finish(self) -> CacheKey {
+        self.0.finalize().into()
+    }
+}
+
+pub struct TranscriptCache<V> {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<Entries<V>>,
+}
+
+struct Entries<V> {
+    values: HashMap<CacheKey, (Instant, V)>,
+    /// Least recently used first.
+    order: VecDeque<CacheKey>,
+}
+
+impl<V: Clone> TranscriptCache<V> {
+    /// A cache of up to `capacity` results, each kept for `ttl`.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            inner: Mutex::new(Entries {
+                values: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+        }
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<V> {
+        let mut entries = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let (stored_at, value) = entries.values.get(key)?;
+        if stored_at.elapsed() > self.ttl {
+            entries.values.remove(key);
+            entries.order.retain(|k| k != key);
+            return None;
+        }
+        let value = value.clone();
+        entries.touch(key);
+        Some(value)
+    }
+
+    pub fn insert(&self, key: CacheKey, value: V) {
+        let mut entries = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if entries
+            .values
+            .insert(key, (Instant::now(), value))
+            .is_some()
+        {
+            entries.touch(&key);
+            return;
+        }
+        entries.order.push_back(key);
+        while entries.order.len() > self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.values.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl<V> Entries<V> {
+    /// Mark `key` as the most recently used.
+    fn touch(&mut self, key: &CacheKey) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(*key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(value: &str) -> CacheKey {
+        KeyBuilder::default().str(value).finish()
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = TranscriptCache::new(2, Duration::from_secs(60));
+        cache.insert(key("a"), 1);
+        cache.insert(key("b"), 2);
+        assert_eq!(cache.get(&key("a")), Some(1));
+        cache.insert(key("c"), 3);
+
+        assert_eq!(cache.get(&key("b")), None);
+        assert_eq!(cache.get(&key("a")), Some(1));
+        assert_eq!(cache.get(&key("c")), Some(3));
+        assert_eq!(cache.inner.lock().unwrap().values.len(), 2);
+    }
+
+    #[test]
+    fn expires_entries() {
+        let cache = TranscriptCache::new(2, Duration::ZERO);
+        cache.insert(key("a"), 1);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(cache.get(&key("a")), None);
+        assert!(cache.inner.lock().unwrap().values.is_empty());
+    }
+
+    #[test]
+    fn keys_separate_parts() {
+        assert_ne!(
+            KeyBuilder::default().str("ab").str("c").finish(),
+            KeyBuilder::default().str("a").str("bc").finish()
+        );
+        let from_reader = KeyBuilder::default()
+            .reader(&b"audio"[..])
+            .unwrap()
+            .str("small")
+            .finish();
+        assert_eq!(
+            from_reader,
+            KeyBuilder::default()
+                .reader(&b"audio"[..])
+                .unwrap()
+                .str("small")
+                .finish()
+        );
+        assert_ne!(from_reader, key("audio"));
+    }
+}
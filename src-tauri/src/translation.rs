@@ -0,0 +1,277 @@
+//! Translation of transcripts into arbitrary target languages.
+//!
+//! Whisper can only translate into English, so translation runs as a separate
+//! stage after transcription. Text is translated segment by segment, in one
+//! batched request, so timestamps are preserved.
+
+use crate::llm_client;
+use crate::settings::PostProcessProvider;
+use log::debug;
+use serde::Deserialize;
+use serde_json::json;
+
+const DEFAULT_OPENAI_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+const DEEPL_URL: &str = "https://api.deepl.com/v2";
+const DEEPL_FREE_URL: &str = "https://api-free.deepl.com/v2";
+
+/// A translation backend.
+///
+/// Configured from the environment for the API server:
+/// - `HANDY_API_TRANSLATION_PROVIDER`: `deepl` or `openai` (any
+///   OpenAI-compatible chat completions API)
+/// - `HANDY_API_TRANSLATION_API_KEY`: API key for the provider
+/// - `HANDY_API_TRANSLATION_URL`: optional base URL override
+/// - `HANDY_API_TRANSLATION_MODEL`: model for `openai` (default `gpt-4o-mini`)
+pub enum Translator {
+    DeepL {
+        api_key: String,
+        base_url: String,
+    },
+    Llm {
+        provider: PostProcessProvider,
+        api_key: String,
+        model: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct DeepLResponse {
+    translations: Vec<DeepLTranslation>,
+}
+
+#[derive(Deserialize)]
+struct DeepLTranslation {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct LlmTranslations {
+    translations: Vec<String>,
+}
+
+impl Translator {
+    /// Build a translator from `HANDY_API_TRANSLATION_*` environment
+    /// variables. Returns `Ok(None)` when no provider is configured.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let provider = match std::env::var("HANDY_API_TRANSLATION_PROVIDER") {
+            Ok(p) if !p.trim().is_empty() => p.trim().to_lowercase(),
+            _ => return Ok(None),
+        };
+        let api_key = std::env::var("HANDY_API_TRANSLATION_API_KEY").unwrap_or_default();
+        let base_url = std::env::var("HANDY_API_TRANSLATION_URL")
+            .ok()
+            .filter(|u| !u.trim().is_empty());
+
+        match provider.as_str() {
+            "deepl" => {
+                // DeepL Free keys end in ":fx" and use a separate host
+                let default_url = if api_key.ends_with(":fx") {
+                    DEEPL_FREE_URL
+                } else {
+                    DEEPL_URL
+                };
+                Ok(Some(Translator::DeepL {
+                    base_url: base_url.unwrap_or_else(|| default_url.to_string()),
+                    api_key,
+                }))
+            }
+            "openai" => {
+                let model = std::env::var("HANDY_API_TRANSLATION_MODEL")
+                    .ok()
+                    .filter(|m| !m.trim().is_empty())
+                    .unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string());
+                Ok(Some(Translator::Llm {
+                    provider: PostProcessProvider {
+                        id: "translation".to_string(),
+                        label: "Translation".to_string(),
+                        base_url: base_url.unwrap_or_else(|| DEFAULT_OPENAI_URL.to_string()),
+                        allow_base_url_edit: false,
+                        models_endpoint: None,
+                        supports_structured_output: true,
+                    },
+                    api_key,
+                    model,
+                }))
+            }
+            other => Err(format!(
+                "Unknown translation provider '{}': expected 'deepl' or 'openai'",
+                other
+            )),
+        }
+    }
+
+    /// Translate each of `texts` into `target_language`, returning the
+    /// translations in the same order. Empty strings are passed through.
+    pub async fn translate(
+        &self,
+        texts: &[String],
+        target_language: &str,
+    ) -> Result<Vec<String>, String> {
+        let pending: Vec<&String> = texts.iter().filter(|t| !t.trim().is_empty()).collect();
+        if pending.is_empty() {
+            return Ok(texts.to_vec());
+        }
+
+        debug!(
+            "Translating {} segments into '{}'",
+            pending.len(),
+            target_language
+        );
+        let mut translated = match self {
+            Translator::DeepL { api_key, base_url } => {
+                translate_deepl(api_key, base_url, &pending, target_language).await?
+            }
+            Translator::Llm {
+                provider,
+                api_key,
+                model,
+            } => translate_llm(provider, api_key, model, &pending, target_language).await?,
+        }
+        .into_iter();
+
+        Ok(texts
+            .iter()
+            .map(|t| {
+                if t.trim().is_empty() {
+                    t.clone()
+                } else {
+                    translated.next().unwrap_or_default()
+                }
+            })
+            .collect())
+    }
+}
+
+async fn translate_deepl(
+    api_key: &str,
+    base_url: &str,
+    texts: &[&String],
+    target_language: &str,
+) -> Result<Vec<String>, String> {
+    let url = format!("{}/translate", base_url.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .post(&url)
+        .header("Authorization", format!("DeepL-Auth-Key {}", api_key))
+        .json(&json!({
+            "text": texts,
+            "target_lang": target_language.to_uppercase(),
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("DeepL request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "DeepL request failed with status {}: {}",
+            status, error_text
+        ));
+    }
+
+    let body: DeepLResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse DeepL response: {}", e))?;
+    check_count(
+        body.translations.into_iter().map(|t| t.text).collect(),
+        texts.len(),
+    )
+}
+
+async fn translate_llm(
+    provider: &PostProcessProvider,
+    api_key: &str,
+    model: &str,
+    texts: &[&String],
+    target_language: &str,
+) -> Result<Vec<String>, String> {
+    let system_prompt = format!(
+        "Translate each string in the JSON array provided by the user into the language \
+         with code '{}'. Return the translations in the same order and the same number of \
+         items, as {{\"translations\": [...]}}. Do not merge, split, or explain items.",
+        target_language
+    );
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "translations": { "type": "array", "items": { "type": "string" } }
+        },
+        "required": ["translations"],
+        "additionalProperties": false
+    });
+    let input = serde_json::to_string(texts)
+        .map_err(|e| format!("Failed to encode translation request: {}", e))?;
+
+    let content = llm_client::send_chat_completion_with_schema(
+        provider,
+        api_key.to_string(),
+        model,
+        input,
+        Some(system_prompt),
+        Some(schema),
+    )
+    .await?
+    .ok_or_else(|| "Translation response was empty".to_string())?;
+
+    parse_llm_translations(&content, texts.len())
+}
+
+fn parse_llm_translations(content: &str, expected: usize) -> Result<Vec<String>, String> {
+    let parsed: LlmTranslations = serde_json::from_str(content.trim())
+        .map_err(|e| format!("Failed to parse translation response: {}", e))?;
+    check_count(parsed.translations, expected)
+}
+
+fn check_count(translations: Vec<String>, expected: usize) -> Result<Vec<String>, String> {
+    if translations.len() != expected {
+        return Err(format!(
+            "Expected {} translations but received {}",
+            expected,
+            translations.len()
+        ));
+    }
+    Ok(translations)
+}
+
+/// Whether `code` looks like a language code such as `de`, `pt-BR` or `zh-Hans`.
+pub fn is_valid_language_code(code: &str) -> bool {
+    let mut parts = code.split('-');
+    let primary = parts.next().unwrap_or("");
+    let primary_ok =
+        (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic());
+    let rest: Vec<&str> = parts.collect();
+    let subtag_ok = match rest.as_slice() {
+        [] => true,
+        [subtag] => {
+            (2..=4).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        _ => false,
+    };
+    primary_ok && subtag_ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_codes() {
+        assert!(is_valid_language_code("de"));
+        assert!(is_valid_language_code("pt-BR"));
+        assert!(is_valid_language_code("zh-Hans"));
+        assert!(!is_valid_language_code(""));
+        assert!(!is_valid_language_code("german"));
+        assert!(!is_valid_language_code("en-US-x"));
+    }
+
+    #[test]
+    fn test_parse_llm_translations() {
+        let parsed = parse_llm_translations(r#"{"translations": ["Hallo", "Welt"]}"#, 2).unwrap();
+        assert_eq!(parsed, vec!["Hallo", "Welt"]);
+
+        assert!(parse_llm_translations(r#"{"translations": ["Hallo"]}"#, 2).is_err());
+        assert!(parse_llm_translations("Hallo Welt", 2).is_err());
+    }
+}
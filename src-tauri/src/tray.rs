@@ -1,5 +1,7 @@
+use crate::managers::audio::AudioRecordingManager;
 use crate::managers::history::{HistoryEntry, HistoryManager};
 use crate::managers::transcription::TranscriptionManager;
+use crate::meeting::{MeetingOptions, MeetingRecorder};
 use crate::settings;
 use crate::tray_i18n::get_tray_translations;
 use log::{error, info, warn};
@@ -124,6 +126,21 @@ pub fn update_tray_menu(app: &AppHandle, state: &TrayIconState, locale: Option<&
         None::<&str>,
     )
     .expect("failed to create copy last transcript item");
+    let meeting_running = app
+        .try_state::<Arc<MeetingRecorder>>()
+        .is_some_and(|meeting| meeting.is_running());
+    let meeting_i = MenuItem::with_id(
+        app,
+        "meeting",
+        if meeting_running {
+            &strings.stop_meeting
+        } else {
+            &strings.start_meeting
+        },
+        true,
+        None::<&str>,
+    )
+    .expect("failed to create meeting item");
     let model_loaded = app.state::<Arc<TranscriptionManager>>().is_model_loaded();
     let unload_model_i = MenuItem::with_id(
         app,
@@ -149,6 +166,7 @@ pub fn update_tray_menu(app: &AppHandle, state: &TrayIconState, locale: Option<&
                     &cancel_i,
                     &separator(),
                     &copy_last_transcript_i,
+                    &meeting_i,
                     &separator(),
                     &settings_i,
                     &check_updates_i,
@@ -164,6 +182,7 @@ pub fn update_tray_menu(app: &AppHandle, state: &TrayIconState, locale: Option<&
                 &version_i,
                 &separator(),
                 &copy_last_transcript_i,
+                &meeting_i,
                 &unload_model_i,
                 &separator(),
                 &settings_i,
@@ -180,6 +199,38 @@ pub fn update_tray_menu(app: &AppHandle, state: &TrayIconState, locale: Option<&
     let _ = tray.set_icon_as_template(true);
 }
 
+/// Rebuild the menu for the current recording state, e.g. after a meeting
+/// started or stopped.
+pub fn refresh_tray_menu(app: &AppHandle) {
+    let recording = app
+        .try_state::<Arc<AudioRecordingManager>>()
+        .is_some_and(|rm| rm.is_recording());
+    let state = if recording {
+        TrayIconState::Recording
+    } else {
+        TrayIconState::Idle
+    };
+    update_tray_menu(app, &state, None);
+}
+
+/// Start a meeting, or stop the running one.
+pub fn toggle_meeting(app: &AppHandle) {
+    let meeting = app.state::<Arc<MeetingRecorder>>().inner().clone();
+    let app = app.clone();
+    // Stopping waits for the last chunk to be transcribed
+    std::thread::spawn(move || {
+        let result = if meeting.is_running() {
+            meeting.stop().map(|_| ())
+        } else {
+            meeting.start(MeetingOptions::default()).map(|_| ())
+        };
+        if let Err(e) = result {
+            error!("Failed to toggle meeting via tray: {}", e);
+        }
+        refresh_tray_menu(&app);
+    });
+}
+
 fn last_transcript_text(entry: &HistoryEntry) -> &str {
     entry
         .post_processed_text
@@ -221,7 +272,7 @@ pub fn copy_last_transcript(app: &AppHandle) {
 #[cfg(test)]
 mod tests {
     use super::last_transcript_text;
-    use crate::managers::history::HistoryEntry;
+    use crate::managers::history::{HistoryEntry, HistorySource};
 
     fn build_entry(transcription: &str, post_processed: Option<&str>) -> HistoryEntry {
         HistoryEntry {
@@ -233,6 +284,11 @@ mod tests {
             transcription_text: transcription.to_string(),
             post_processed_text: post_processed.map(|text| text.to_string()),
             post_process_prompt: None,
+            source: HistorySource::App,
+            model_id: None,
+            duration_ms: None,
+            segments: Vec::new(),
+            audio_hash: None,
         }
     }
 
@@ -0,0 +1,274 @@
+//! Resumable uploads for the API, so clients on flaky connections can send
+//! large files in chunks and continue after a dropped connection instead
+//! of restarting the whole upload.
+//!
+//! An upload is a temporary file with the number of bytes received so far
+//! (its offset). Chunks are only accepted at the current offset, so a
+//! client that lost track asks for the offset and continues from there.
+//! Uploads that haven't been touched for the TTL are removed.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{Seek, SeekFrom, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tempfile::NamedTempFile;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// An upload in progress. Held behind an async mutex while a chunk is
+/// written or the upload is transcribed.
+pub struct Upload {
+    file: Arc<NamedTempFile>,
+    offset: u64,
+    /// Total size declared by the client, if any.
+    length: Option<u64>,
+    pub content_type: Option<String>,
+    pub filename: Option<String>,
+    touched: Instant,
+}
+
+impl Upload {
+    /// Bytes received so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn length(&self) -> Option<u64> {
+        self.length
+    }
+
+    /// Whether all declared bytes have arrived. Uploads without a declared
+    /// length are complete once anything has arrived.
+    pub fn is_complete(&self) -> bool {
+        match self.length {
+            Some(length) => self.offset == length,
+            None => self.offset > 0,
+        }
+    }
+
+    /// The received bytes, shared so they can be decoded while the upload
+    /// is kept for a retry.
+    pub fn file(&self) -> Arc<NamedTempFile> {
+        self.file.clone()
+    }
+
+    /// Prepare to append at the current offset, dropping anything left
+    /// behind by a chunk that failed halfway.
+    pub fn begin_append(&mut self) -> std::io::Result<()> {
+        self.touched = Instant::now();
+        let mut file = self.file.as_file();
+        file.set_len(self.offset)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        Ok(())
+    }
+
+    /// Append a chunk after [`Upload::begin_append`].
+    pub fn append(&mut self, chunk: &[u8]) -> std::io::Result<()> {
+        self.touched = Instant::now();
+        self.file.as_file().write_all(chunk)?;
+        self.offset += chunk.len() as u64;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum UploadError {
+    /// `max_uploads` uploads are already in progress.
+    TooMany,
+    /// Another request is writing or transcribing the upload.
+    Busy,
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for UploadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadError::TooMany => write!(f, "Too many uploads in progress"),
+            UploadError::Busy => write!(f, "The upload is in use by another request"),
+            UploadError::Io(e) => write!(f, "Failed to create upload: {}", e),
+        }
+    }
+}
+
+pub struct UploadStore {
+    ttl: Duration,
+    max_uploads: usize,
+    uploads: Mutex<HashMap<String, Arc<AsyncMutex<Upload>>>>,
+}
+
+impl UploadStore {
+    /// A store of up to `max_uploads` uploads, each kept for `ttl` after
+    /// its last chunk.
+    pub fn new(max_uploads: usize, ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_uploads,
+            uploads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Start an upload, returning its id.
+    pub fn create(
+        &self,
+        length: Option<u64>,
+        content_type: Option<String>,
+        filename: Option<String>,
+    ) -> Result<String, UploadError> {
+        let mut uploads = self.uploads.lock().unwrap_or_else(|e| e.into_inner());
+        self.remove_expired(&mut uploads);
+        if uploads.len() >= self.max_uploads {
+            return Err(UploadError::TooMany);
+        }
+
+        let file = NamedTempFile::new().map_err(UploadError::Io)?;
+        let id = loop {
+            let id = new_id();
+            if !uploads.contains_key(&id) {
+                break id;
+            }
+        };
+        let upload = Upload {
+            file: Arc::new(file),
+            offset: 0,
+            length,
+            content_type,
+            filename,
+            touched: Instant::now(),
+        };
+        uploads.insert(id.clone(), Arc::new(AsyncMutex::new(upload)));
+        Ok(id)
+    }
+
+    /// Lock an upload, or `Ok(None)` if there is no such upload. Fails with
+    /// [`UploadError::Busy`] while another request holds it.
+    pub fn lock(&self, id: &str) -> Result<Option<OwnedMutexGuard<Upload>>, UploadError> {
+        let mut uploads = self.uploads.lock().unwrap_or_else(|e| e.into_inner());
+        self.remove_expired(&mut uploads);
+        match uploads.get(id) {
+            Some(upload) => upload
+                .clone()
+                .try_lock_owned()
+                .map(Some)
+                .map_err(|_| UploadError::Busy),
+            None => Ok(None),
+        }
+    }
+
+    /// Forget an upload, deleting its file once no request uses it.
+    pub fn remove(&self, id: &str) -> bool {
+        let mut uploads = self.uploads.lock().unwrap_or_else(|e| e.into_inner());
+        uploads.remove(id).is_some()
+    }
+
+    /// Drop uploads idle for longer than the TTL. Uploads in use are kept.
+    fn remove_expired(&self, uploads: &mut HashMap<String, Arc<AsyncMutex<Upload>>>) {
+        uploads.retain(|_, upload| match upload.try_lock() {
+            Ok(upload) => upload.touched.elapsed() < self.ttl,
+            Err(_) => true,
+        });
+    }
+}
+
+/// An unguessable id: 128 bits from the randomly keyed std hasher.
+pub(crate) fn new_id() -> String {
+    let mut id = String::with_capacity(32);
+    for _ in 0..2 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos()),
+        );
+        id.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn contents(upload: &Upload) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        upload
+            .file()
+            .reopen()
+            .unwrap()
+            .read_to_end(&mut bytes)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn appends_chunks_at_the_offset() {
+        let store = UploadStore::new(4, Duration::from_secs(60));
+        let id = store.create(Some(6), None, None).unwrap();
+        assert_eq!(id.len(), 32);
+
+        let mut upload = store.lock(&id).unwrap().unwrap();
+        upload.begin_append().unwrap();
+        upload.append(b"abc").unwrap();
+        assert!(!upload.is_complete());
+        drop(upload);
+
+        let mut upload = store.lock(&id).unwrap().unwrap();
+        upload.begin_append().unwrap();
+        upload.append(b"def").unwrap();
+        assert_eq!(upload.offset(), 6);
+        assert!(upload.is_complete());
+        assert_eq!(contents(&upload), b"abcdef");
+    }
+
+    #[test]
+    fn drops_bytes_past_the_offset() {
+        let store = UploadStore::new(4, Duration::from_secs(60));
+        let id = store.create(None, None, None).unwrap();
+        let mut upload = store.lock(&id).unwrap().unwrap();
+        upload.begin_append().unwrap();
+        upload.append(b"abc").unwrap();
+        // A chunk that failed halfway
+        upload.file().as_file().write_all(b"xx").unwrap();
+
+        upload.begin_append().unwrap();
+        upload.append(b"d").unwrap();
+        assert_eq!(contents(&upload), b"abcd");
+    }
+
+    #[test]
+    fn locks_one_request_at_a_time() {
+        let store = UploadStore::new(4, Duration::from_secs(60));
+        let id = store.create(None, None, None).unwrap();
+        let upload = store.lock(&id).unwrap();
+        assert!(upload.is_some());
+        assert!(matches!(store.lock(&id), Err(UploadError::Busy)));
+        drop(upload);
+        assert!(store.lock(&id).unwrap().is_some());
+        assert!(store.lock("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn limits_and_expires_uploads() {
+        let store = UploadStore::new(1, Duration::from_millis(10));
+        let id = store.create(None, None, None).unwrap();
+        let held = store.lock(&id).unwrap();
+        assert!(held.is_some());
+
+        // Uploads in use don't expire
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(matches!(
+            store.create(None, None, None),
+            Err(UploadError::TooMany)
+        ));
+        drop(held);
+
+        assert!(store.create(None, None, None).is_ok());
+        assert!(store.lock(&id).unwrap().is_none());
+    }
+}
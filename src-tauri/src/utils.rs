@@ -1,3 +1,4 @@
+use crate::events::{EventBus, EventKind};
 use crate::managers::audio::AudioRecordingManager;
 use crate::managers::transcription::TranscriptionManager;
 use crate::shortcut;
@@ -24,6 +25,10 @@ pub fn cancel_current_operation(app: &AppHandle) {
     let audio_manager = app.state::<Arc<AudioRecordingManager>>();
     let recording_was_active = audio_manager.is_recording();
     audio_manager.cancel_recording();
+    if recording_was_active {
+        app.state::<Arc<EventBus>>()
+            .publish(EventKind::RecordingCancelled);
+    }
 
     // Update tray icon and hide overlay
     change_tray_icon(app, crate::tray::TrayIconState::Idle);
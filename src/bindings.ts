@@ -77,6 +77,14 @@ async changeTranslateToEnglishSetting(enabled: boolean) : Promise<Result<null, s
     else return { status: "error", error: e  as any };
 }
 },
+async changeSuppressHallucinationsSetting(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_suppress_hallucinations_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async changeSelectedLanguageSetting(language: string) : Promise<Result<null, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("change_selected_language_setting", { language }) };
@@ -136,6 +144,14 @@ async changeExternalScriptPathSetting(path: string | null) : Promise<Result<null
     else return { status: "error", error: e  as any };
 }
 },
+async changeFfmpegPathSetting(path: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_ffmpeg_path_setting", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async changeClipboardHandlingSetting(handling: string) : Promise<Result<null, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("change_clipboard_handling_setting", { handling }) };
@@ -256,6 +272,38 @@ async updateCustomWords(words: string[]) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+async updateTextReplacements(replacements: TextReplacement[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_text_replacements", { replacements }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeVoiceCommandsSetting(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_voice_commands_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateVoiceCommandPhrases(phrases: VoiceCommandPhrase[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_voice_command_phrases", { phrases }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateAppProfiles(profiles: AppProfile[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_app_profiles", { profiles }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Temporarily unregister a binding while the user is editing it in the UI.
  * This avoids firing the action while keys are being recorded.
@@ -484,6 +532,14 @@ async getModelInfo(modelId: string) : Promise<Result<ModelInfo | null, string>>
     else return { status: "error", error: e  as any };
 }
 },
+async getModelCatalog() : Promise<Result<ModelCatalog, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_catalog") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async downloadModel(modelId: string) : Promise<Result<null, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("download_model", { modelId }) };
@@ -516,6 +572,18 @@ async setActiveModel(modelId: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Turn the auto model mode on or off. Turning it on selects and loads the
+ * best downloaded model for this machine.
+ */
+async changeAutoSelectModelSetting(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_auto_select_model_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getCurrentModel() : Promise<Result<string, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_current_model") };
@@ -572,6 +640,30 @@ async getMicrophoneMode() : Promise<Result<boolean, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+async changeWakeWordSetting(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_wake_word_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeWakeWordPhraseSetting(wakeWord: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_wake_word_phrase_setting", { wakeWord }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeWakeWordThresholdSetting(threshold: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_wake_word_threshold_setting", { threshold }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getAvailableMicrophones() : Promise<Result<AudioDevice[], string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_available_microphones") };
@@ -596,6 +688,38 @@ async getSelectedMicrophone() : Promise<Result<string, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+async updateRecordingSource(source: RecordingSource) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_recording_source", { source }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAvailableLoopbackDevices() : Promise<Result<AudioDevice[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_available_loopback_devices") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSelectedLoopbackDevice(deviceName: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_selected_loopback_device", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSelectedLoopbackDevice() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_selected_loopback_device") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getAvailableOutputDevices() : Promise<Result<AudioDevice[], string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_available_output_devices") };
@@ -664,6 +788,54 @@ async unloadModelManually() : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+async getApiServerStatus() : Promise<Result<ApiServerStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_api_server_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async startApiServer() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_api_server") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopApiServer() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_api_server") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async restartApiServer() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("restart_api_server") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeApiServerEnabledSetting(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_api_server_enabled_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeApiServerPortSetting(port: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_api_server_port_setting", { port }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getHistoryEntries() : Promise<Result<HistoryEntry[], string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_history_entries") };
@@ -738,14 +910,146 @@ async isLaptop() : Promise<Result<boolean, string>> {
 
 /** user-defined types **/
 
-export type AppSettings = { bindings: Partial<{ [key in string]: ShortcutBinding }>; push_to_talk: boolean; audio_feedback: boolean; audio_feedback_volume?: number; sound_theme?: SoundTheme; start_hidden?: boolean; autostart_enabled?: boolean; update_checks_enabled?: boolean; selected_model?: string; always_on_microphone?: boolean; selected_microphone?: string | null; clamshell_microphone?: string | null; selected_output_device?: string | null; translate_to_english?: boolean; selected_language?: string; overlay_position?: OverlayPosition; debug_mode?: boolean; log_level?: LogLevel; custom_words?: string[]; model_unload_timeout?: ModelUnloadTimeout; word_correction_threshold?: number; history_limit?: number; recording_retention_period?: RecordingRetentionPeriod; paste_method?: PasteMethod; clipboard_handling?: ClipboardHandling; auto_submit?: boolean; auto_submit_key?: AutoSubmitKey; post_process_enabled?: boolean; post_process_provider_id?: string; post_process_providers?: PostProcessProvider[]; post_process_api_keys?: Partial<{ [key in string]: string }>; post_process_models?: Partial<{ [key in string]: string }>; post_process_prompts?: LLMPrompt[]; post_process_selected_prompt_id?: string | null; mute_while_recording?: boolean; append_trailing_space?: boolean; app_language?: string; experimental_enabled?: boolean; keyboard_implementation?: KeyboardImplementation; show_tray_icon?: boolean; paste_delay_ms?: number; typing_tool?: TypingTool; external_script_path: string | null; custom_filler_words?: string[] | null }
+export type ApiServerStatus = { is_running: boolean; port: number | null }
+/**
+ * Dictation settings for some applications, applied when one of them is in
+ * the foreground, e.g. verbatim with code vocabulary in the terminal and
+ * cleaned-up prose in the mail client. Unset values keep the global
+ * settings.
+ */
+export type AppProfile = { name: string; 
+/**
+ * Process names, display names or bundle ids, e.g. "WindowsTerminal",
+ * "Code" or "com.apple.mail". Case and a `.exe` or `.app` extension
+ * don't matter.
+ */
+apps: string[]; model?: string | null; language?: string | null; 
+/**
+ * Vocabulary on top of the custom words.
+ */
+custom_words?: string[]; 
+/**
+ * Whether the transcript is post-processed, whichever shortcut was
+ * used.
+ */
+post_process?: boolean | null; post_process_prompt_id?: string | null }
+export type AppSettings = { bindings: Partial<{ [key in string]: ShortcutBinding }>; push_to_talk: boolean; audio_feedback: boolean; audio_feedback_volume?: number; sound_theme?: SoundTheme; start_hidden?: boolean; autostart_enabled?: boolean; update_checks_enabled?: boolean; selected_model?: string; 
+/**
+ * Select the model recommended for this machine's hardware. Off for
+ * settings saved before the option existed, so their choice is kept.
+ */
+auto_select_model?: boolean; always_on_microphone?: boolean; selected_microphone?: string | null; clamshell_microphone?: string | null; recording_source?: RecordingSource; selected_loopback_device?: string | null; selected_output_device?: string | null; 
+/**
+ * Start dictating when the wake word is heard. Keeps the microphone
+ * open.
+ */
+wake_word_enabled?: boolean; 
+/**
+ * A built-in phrase, e.g. "hey_jarvis", or the path of a custom
+ * openWakeWord model.
+ */
+wake_word?: string; 
+/**
+ * Score (0 to 1) that counts as hearing the wake word; higher means
+ * fewer false activations.
+ */
+wake_word_threshold?: number; translate_to_english?: boolean; suppress_hallucinations?: boolean; selected_language?: string; overlay_position?: OverlayPosition; debug_mode?: boolean; log_level?: LogLevel; custom_words?: string[]; model_unload_timeout?: ModelUnloadTimeout; word_correction_threshold?: number; history_limit?: number; recording_retention_period?: RecordingRetentionPeriod; paste_method?: PasteMethod; clipboard_handling?: ClipboardHandling; auto_submit?: boolean; auto_submit_key?: AutoSubmitKey; post_process_enabled?: boolean; post_process_provider_id?: string; post_process_providers?: PostProcessProvider[]; post_process_api_keys?: Partial<{ [key in string]: string }>; post_process_models?: Partial<{ [key in string]: string }>; post_process_prompts?: LLMPrompt[]; post_process_selected_prompt_id?: string | null; mute_while_recording?: boolean; append_trailing_space?: boolean; app_language?: string; experimental_enabled?: boolean; keyboard_implementation?: KeyboardImplementation; show_tray_icon?: boolean; paste_delay_ms?: number; typing_tool?: TypingTool; external_script_path: string | null; custom_filler_words?: string[] | null; text_replacements?: TextReplacement[]; 
+/**
+ * Interpret spoken commands like "comma", "new paragraph" and "delete
+ * last sentence" in dictations.
+ */
+voice_commands?: boolean; voice_command_phrases?: VoiceCommandPhrase[]; 
+/**
+ * Profiles picked by the application in the foreground; the first
+ * that lists it applies.
+ */
+app_profiles?: AppProfile[]; api_server_enabled?: boolean; api_server_port?: number; api_max_concurrency?: number | null; ffmpeg_path?: string | null }
 export type AudioDevice = { index: string; name: string; is_default: boolean }
 export type AutoSubmitKey = "enter" | "ctrl_enter" | "cmd_enter"
 export type BindingResponse = { success: boolean; binding: ShortcutBinding | null; error: string | null }
+export type CatalogEntry = { license: string; 
+/**
+ * Approximate peak RAM while transcribing on the CPU
+ */
+ram_mb: number; 
+/**
+ * Approximate GPU memory when the engine runs on the GPU; `None` for
+ * CPU-only engines
+ */
+vram_mb: number | null; 
+/**
+ * On a recent 4-core laptop CPU
+ */
+rtf_cpu: RtfTier; 
+/**
+ * With GPU acceleration, if the engine supports it
+ */
+rtf_gpu: RtfTier | null }
+export type CatalogModel = ({ id: string; name: string; engine: EngineType; languages: string[]; size_mb: number; is_downloaded: boolean; 
+/**
+ * Set when the model likely won't fit this machine
+ */
+warning: string | null }) & CatalogEntry
 export type ClipboardHandling = "dont_modify" | "copy_to_clipboard"
 export type CustomSounds = { start: boolean; stop: boolean }
 export type EngineType = "Whisper" | "Parakeet" | "Moonshine" | "MoonshineStreaming" | "SenseVoice" | "GigaAM"
-export type HistoryEntry = { id: number; file_name: string; timestamp: number; saved: boolean; title: string; transcription_text: string; post_processed_text: string | null; post_process_prompt: string | null }
+export type GpuInfo = { name: string; vram_mb: number; 
+/**
+ * The GPU shares system memory (Apple Silicon)
+ */
+unified_memory: boolean }
+export type HardwareInfo = { 
+/**
+ * `None` if the platform doesn't report it
+ */
+total_memory_mb: number | null; gpu: GpuInfo | null; cpu_cores: number; 
+/**
+ * Always `true` on non-x86 CPUs
+ */
+avx2: boolean }
+export type HistoryEntry = { id: number; file_name: string; timestamp: number; saved: boolean; title: string; transcription_text: string; post_processed_text: string | null; post_process_prompt: string | null; source: HistorySource; 
+/**
+ * Model that produced the transcription, when known.
+ */
+model_id: string | null; 
+/**
+ * Length of the transcribed audio in milliseconds.
+ */
+duration_ms: number | null; 
+/**
+ * Timestamped segments; empty for entries saved before segments were stored.
+ */
+segments: HistorySegment[]; 
+/**
+ * Hash of the uploaded audio for API transcriptions; equal for
+ * re-uploads of the same file.
+ */
+audio_hash: string | null }
+/**
+ * A timestamped piece of a stored transcription. Times are in seconds.
+ */
+export type HistorySegment = { start: number; end: number; text: string; 
+/**
+ * Speaker or channel label, e.g. a multitrack speaker or a meeting
+ * source.
+ */
+speaker?: string | null }
+/**
+ * Where a transcription came from.
+ */
+export type HistorySource = 
+/**
+ * Dictated in the app with a shortcut.
+ */
+"app" | 
+/**
+ * Uploaded to the REST API.
+ */
+"api" | 
+/**
+ * Transcribed continuously in meeting mode.
+ */
+"meeting"
 /**
  * Result of changing keyboard implementation
  */
@@ -757,16 +1061,84 @@ reset_bindings: string[] }
 export type KeyboardImplementation = "tauri" | "handy_keys"
 export type LLMPrompt = { id: string; name: string; prompt: string }
 export type LogLevel = "trace" | "debug" | "info" | "warn" | "error"
-export type ModelInfo = { id: string; name: string; description: string; filename: string; url: string | null; size_mb: number; is_downloaded: boolean; is_downloading: boolean; partial_size: number; is_directory: boolean; engine_type: EngineType; accuracy_score: number; speed_score: number; supports_translation: boolean; is_recommended: boolean; supported_languages: string[]; is_custom: boolean }
-export type ModelLoadStatus = { is_loaded: boolean; current_model: string | null }
+export type ModelCatalog = { hardware: HardwareInfo; 
+/**
+ * The model the "auto" mode picks for this machine and the selected
+ * language
+ */
+recommended: string | null; 
+/**
+ * Sorted by id
+ */
+models: CatalogModel[] }
+export type ModelInfo = { id: string; name: string; description: string; filename: string; url: string | null; size_mb: number; is_downloaded: boolean; is_downloading: boolean; partial_size: number; is_directory: boolean; engine_type: EngineType; accuracy_score: number; speed_score: number; supports_translation: boolean; is_recommended: boolean; supported_languages: string[]; is_custom: boolean; sha256: string | null }
+export type ModelLoadStatus = { is_loaded: boolean; current_model: string | null; 
+/**
+ * Memory taken by each loaded model.
+ */
+memory: ModelMemory[] }
+/**
+ * Memory used by a loaded model, measured as the growth of this process's
+ * memory while it loaded.
+ */
+export type ModelMemory = { model_id: string; 
+/**
+ * Resident memory in MB. Includes GPU memory on Apple Silicon, which
+ * shares system memory. `None` if the platform doesn't report it.
+ */
+resident_mb: number | null; 
+/**
+ * GPU memory in MB; only known for NVIDIA GPUs.
+ */
+vram_mb: number | null }
 export type ModelUnloadTimeout = "never" | "immediately" | "min_2" | "min_5" | "min_10" | "min_15" | "hour_1" | "sec_5"
 export type OverlayPosition = "none" | "top" | "bottom"
 export type PasteMethod = "ctrl_v" | "direct" | "none" | "shift_insert" | "ctrl_shift_v" | "external_script"
 export type PostProcessProvider = { id: string; label: string; base_url: string; allow_base_url_edit?: boolean; models_endpoint?: string | null; supports_structured_output?: boolean }
 export type RecordingRetentionPeriod = "never" | "preserve_limit" | "days_3" | "weeks_2" | "months_3"
+export type RecordingSource = "microphone" | "system_audio"
+/**
+ * Expected real-time factor (processing time / audio duration).
+ */
+export type RtfTier = 
+/**
+ * Below 0.1
+ */
+"fast" | 
+/**
+ * 0.1 to 0.5
+ */
+"medium" | 
+/**
+ * Above 0.5
+ */
+"slow"
 export type ShortcutBinding = { id: string; name: string; description: string; default_binding: string; current_binding: string }
 export type SoundTheme = "marimba" | "pop" | "custom"
+export type TextReplacement = { pattern: string; replacement: string; regex?: boolean }
 export type TypingTool = "auto" | "wtype" | "kwtype" | "dotool" | "ydotool" | "xdotool"
+export type VoiceCommandAction = 
+/**
+ * Insert `text` after the previous word, e.g. ",".
+ */
+"punctuation" | 
+/**
+ * Insert `text` before the next word, e.g. "(".
+ */
+"opening" | 
+/**
+ * Insert `text` between spaces, e.g. "&".
+ */
+"symbol" | "new_line" | "new_paragraph" | "delete_last_word" | "delete_last_sentence" | "capitalize_next_word" | "uppercase_next_word" | "lowercase_next_word" | "literal"
+/**
+ * A spoken phrase for a voice command, on top of the built-in phrases of
+ * the dictation language, e.g. "punto e virgola" -> punctuation ";".
+ */
+export type VoiceCommandPhrase = { phrase: string; action: VoiceCommandAction; 
+/**
+ * What `punctuation`, `opening` and `symbol` insert.
+ */
+text?: string }
 
 /** tauri-specta globals **/
 
@@ -1,7 +1,8 @@
 import React, { useState, useRef, useEffect } from "react";
 import { useTranslation } from "react-i18next";
 import { listen } from "@tauri-apps/api/event";
-import { commands } from "@/bindings";
+import { commands, type ModelMemory } from "@/bindings";
+import { formatModelSize } from "../../lib/utils/format";
 import { getTranslatedModelName } from "../../lib/utils/modelTranslation";
 import { useModelStore } from "../../stores/modelStore";
 import ModelStatusButton from "./ModelStatusButton";
@@ -41,6 +42,7 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
 
   const [modelStatus, setModelStatus] = useState<ModelStatus>("unloaded");
   const [modelError, setModelError] = useState<string | null>(null);
+  const [modelMemory, setModelMemory] = useState<ModelMemory | null>(null);
   const [showModelDropdown, setShowModelDropdown] = useState(false);
   // Track pending model switch for optimistic display
   const [pendingModelId, setPendingModelId] = useState<string | null>(null);
@@ -49,6 +51,20 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
 
   const displayModelId = pendingModelId || currentModel;
 
+  const refreshModelMemory = async () => {
+    try {
+      const result = await commands.getModelLoadStatus();
+      if (result.status === "ok") {
+        const { current_model, memory } = result.data;
+        setModelMemory(
+          memory.find((entry) => entry.model_id === current_model) ?? null,
+        );
+      }
+    } catch {
+      setModelMemory(null);
+    }
+  };
+
   // Check model status when currentModel changes
   useEffect(() => {
     const checkStatus = async () => {
@@ -56,9 +72,13 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
         try {
           const statusResult = await commands.getTranscriptionModelStatus();
           if (statusResult.status === "ok") {
-            setModelStatus(
-              statusResult.data === currentModel ? "ready" : "unloaded",
-            );
+            const isReady = statusResult.data === currentModel;
+            setModelStatus(isReady ? "ready" : "unloaded");
+            if (isReady) {
+              refreshModelMemory();
+            } else {
+              setModelMemory(null);
+            }
           }
         } catch {
           setModelStatus("error");
@@ -86,6 +106,7 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
             setModelStatus("ready");
             setModelError(null);
             setPendingModelId(null);
+            refreshModelMemory();
             break;
           case "loading_failed":
             setModelStatus("error");
@@ -95,6 +116,7 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
           case "unloaded":
             setModelStatus("unloaded");
             setModelError(null);
+            setModelMemory(null);
             break;
         }
       },
@@ -230,6 +252,24 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
     return modelStatus;
   };
 
+  const getMemoryText = (): string | null => {
+    if (modelStatus !== "ready" || !modelMemory) return null;
+    const parts = [];
+    if (modelMemory.resident_mb) {
+      parts.push(
+        t("modelSelector.memory", {
+          size: formatModelSize(modelMemory.resident_mb),
+        }),
+      );
+    }
+    if (modelMemory.vram_mb) {
+      parts.push(
+        t("modelSelector.vram", { size: formatModelSize(modelMemory.vram_mb) }),
+      );
+    }
+    return parts.length > 0 ? parts.join(", ") : null;
+  };
+
   return (
     <>
       {/* Model Status and Switcher */}
@@ -237,6 +277,7 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
         <ModelStatusButton
           status={getDisplayStatus()}
           displayText={getModelDisplayText()}
+          memoryText={getMemoryText()}
           isDropdownOpen={showModelDropdown}
           onClick={() => setShowModelDropdown(!showModelDropdown)}
         />
@@ -12,6 +12,8 @@ type ModelStatus =
 interface ModelStatusButtonProps {
   status: ModelStatus;
   displayText: string;
+  /** Memory the loaded model takes, shown in the tooltip. */
+  memoryText?: string | null;
   isDropdownOpen: boolean;
   onClick: () => void;
   className?: string;
@@ -20,6 +22,7 @@ interface ModelStatusButtonProps {
 const ModelStatusButton: React.FC<ModelStatusButtonProps> = ({
   status,
   displayText,
+  memoryText,
   isDropdownOpen,
   onClick,
   className = "",
@@ -49,7 +52,9 @@ const ModelStatusButton: React.FC<ModelStatusButtonProps> = ({
     <button
       onClick={onClick}
       className={`flex items-center gap-2 hover:text-text/80 transition-colors ${className}`}
-      title={`Model status: ${displayText}`}
+      title={[`Model status: ${displayText}`, memoryText]
+        .filter(Boolean)
+        .join("\n")}
     >
       <div className={`w-2 h-2 rounded-full ${getStatusColor(status)}`} />
       <span className="max-w-28 truncate">{displayText}</span>
@@ -0,0 +1,49 @@
+import React from "react";
+import { useTranslation } from "react-i18next";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+
+interface WakeWordPhraseProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+// Phrases with a built-in openWakeWord model
+const BUILTIN_PHRASES = [
+  { value: "hey_jarvis", label: "Hey Jarvis" },
+  { value: "alexa", label: "Alexa" },
+  { value: "hey_mycroft", label: "Hey Mycroft" },
+  { value: "hey_rhasspy", label: "Hey Rhasspy" },
+];
+
+export const WakeWordPhrase: React.FC<WakeWordPhraseProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { t } = useTranslation();
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const wakeWord = getSetting("wake_word") ?? "hey_jarvis";
+
+    // A custom model set in the settings file shows up by its file name
+    const customLabel = wakeWord.split(/[\\/]/).pop() ?? wakeWord;
+    const options = BUILTIN_PHRASES.some((p) => p.value === wakeWord)
+      ? BUILTIN_PHRASES
+      : [...BUILTIN_PHRASES, { value: wakeWord, label: customLabel }];
+
+    return (
+      <SettingContainer
+        title={t("settings.general.wakeWordPhrase.title")}
+        description={t("settings.general.wakeWordPhrase.description")}
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      >
+        <Dropdown
+          options={options}
+          selectedValue={wakeWord}
+          onSelect={(value) => updateSetting("wake_word", value)}
+          disabled={isUpdating("wake_word")}
+        />
+      </SettingContainer>
+    );
+  },
+);
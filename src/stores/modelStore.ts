@@ -2,6 +2,7 @@ import { create } from "zustand";
 import { subscribeWithSelector } from "zustand/middleware";
 import { produce } from "immer";
 import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
 import { commands, type ModelInfo } from "@/bindings";
 
 interface DownloadProgress {
@@ -366,6 +367,10 @@ export const useModelStore = create<ModelsStore>()(
         );
       });
 
+      listen<string>("model-fit-warning", (event) => {
+        toast.warning(event.payload);
+      });
+
       listen<string>("model-deleted", () => {
         get().loadModels();
         get().loadCurrentModel();
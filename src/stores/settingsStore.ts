@@ -72,6 +72,11 @@ const settingUpdaters: {
 } = {
   always_on_microphone: (value) =>
     commands.updateMicrophoneMode(value as boolean),
+  wake_word_enabled: (value) =>
+    commands.changeWakeWordSetting(value as boolean),
+  wake_word: (value) => commands.changeWakeWordPhraseSetting(value as string),
+  wake_word_threshold: (value) =>
+    commands.changeWakeWordThresholdSetting(value as number),
   audio_feedback: (value) =>
     commands.changeAudioFeedbackSetting(value as boolean),
   audio_feedback_volume: (value) =>
@@ -101,8 +106,12 @@ const settingUpdaters: {
     ),
   recording_retention_period: (value) =>
     commands.updateRecordingRetentionPeriod(value as string),
+  auto_select_model: (value) =>
+    commands.changeAutoSelectModelSetting(value as boolean),
   translate_to_english: (value) =>
     commands.changeTranslateToEnglishSetting(value as boolean),
+  suppress_hallucinations: (value) =>
+    commands.changeSuppressHallucinationsSetting(value as boolean),
   selected_language: (value) =>
     commands.changeSelectedLanguageSetting(value as string),
   overlay_position: (value) =>
@@ -115,6 +124,8 @@ const settingUpdaters: {
   typing_tool: (value) => commands.changeTypingToolSetting(value as string),
   external_script_path: (value) =>
     commands.changeExternalScriptPathSetting(value as string | null),
+  ffmpeg_path: (value) =>
+    commands.changeFfmpegPathSetting(value as string | null),
   clipboard_handling: (value) =>
     commands.changeClipboardHandlingSetting(value as string),
   auto_submit: (value) => commands.changeAutoSubmitSetting(value as boolean),
@@ -129,6 +140,8 @@ const settingUpdaters: {
     commands.changeMuteWhileRecordingSetting(value as boolean),
   append_trailing_space: (value) =>
     commands.changeAppendTrailingSpaceSetting(value as boolean),
+  voice_commands: (value) =>
+    commands.changeVoiceCommandsSetting(value as boolean),
   log_level: (value) => commands.setLogLevel(value as any),
   app_language: (value) => commands.changeAppLanguageSetting(value as string),
   experimental_enabled: (value) =>
@@ -0,0 +1,59 @@
+//! Decoder throughput of the Moonshine engine.
+//!
+//! Long utterances stress the KV cache, which grows by one step per token.
+//! Run with a Moonshine Base model in `models/moonshine-base`:
+//!
+//! ```sh
+//! cargo bench --features moonshine --bench moonshine
+//! ```
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use transcribe_rs::{
+    audio::read_wav_samples,
+    engines::moonshine::{ModelVariant, MoonshineEngine, MoonshineModelParams},
+    TranscriptionEngine,
+};
+
+const ITERATIONS: u32 = 5;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut engine = MoonshineEngine::new();
+    engine.load_model_with_params(
+        &PathBuf::from("models/moonshine-base"),
+        MoonshineModelParams::variant(ModelVariant::Base),
+    )?;
+
+    let samples = read_wav_samples(&PathBuf::from("samples/jfk.wav"))?;
+
+    // Short clip, and the same clip repeated to a long utterance
+    let mut long = Vec::new();
+    while long.len() < 60 * 16000 - samples.len() {
+        long.extend_from_slice(&samples);
+    }
+
+    for (name, clip) in [("11s", samples), ("long", long)] {
+        // Warm up
+        engine.transcribe_samples(clip.clone(), None)?;
+
+        let mut total = Duration::ZERO;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            engine.transcribe_samples(clip.clone(), None)?;
+            total += start.elapsed();
+        }
+
+        let audio_secs = clip.len() as f64 / 16000.0;
+        let mean = total / ITERATIONS;
+        println!(
+            "{:>5} ({:5.1}s audio): {:>8.2?} per run, {:6.1}x real time",
+            name,
+            audio_secs,
+            mean,
+            audio_secs / mean.as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! Decoding speed of the Parakeet engine at each precision, and whether
+//! [`QuantizationType::recommended`] picks the fastest one on this machine.
+//!
+//! Run with a Parakeet model directory holding FP32, FP16 and/or Int8 files
+//! in `models/parakeet-v0.3`:
+//!
+//! ```sh
+//! cargo bench --features parakeet --bench parakeet
+//! ```
+//!
+//! Exits with an error when the recommended precision is more than
+//! [`TOLERANCE`] slower than the fastest one.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use transcribe_rs::{
+    audio::read_wav_samples,
+    engines::parakeet::{ParakeetEngine, ParakeetModelParams, QuantizationType},
+    TranscriptionEngine,
+};
+
+const ITERATIONS: u32 = 5;
+/// How much slower than the fastest precision the recommended one may be.
+const TOLERANCE: f64 = 1.1;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let model_dir = PathBuf::from("models/parakeet-v0.3");
+    let samples = read_wav_samples(&PathBuf::from("samples/jfk.wav"))?;
+    let audio_secs = samples.len() as f64 / 16000.0;
+
+    let mut results: Vec<(QuantizationType, Duration)> = Vec::new();
+    for precision in [
+        QuantizationType::FP32,
+        QuantizationType::FP16,
+        QuantizationType::Int8,
+    ] {
+        let mut engine = ParakeetEngine::new();
+        engine.load_model_with_params(&model_dir, ParakeetModelParams::quantized(precision))?;
+        if engine
+            .precision()
+            .is_some_and(|loaded| loaded.is_fallback())
+        {
+            println!("{:>5}: no model files, skipped", format!("{:?}", precision));
+            continue;
+        }
+
+        // Warm up
+        engine.transcribe_samples(samples.clone(), None)?;
+
+        let mut total = Duration::ZERO;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            engine.transcribe_samples(samples.clone(), None)?;
+            total += start.elapsed();
+        }
+
+        let mean = total / ITERATIONS;
+        println!(
+            "{:>5}: {:>8.2?} per run, {:6.1}x real time",
+            format!("{:?}", precision),
+            mean,
+            audio_secs / mean.as_secs_f64()
+        );
+        results.push((precision, mean));
+    }
+
+    let recommended = QuantizationType::recommended();
+    let fastest = results.iter().min_by_key(|(_, mean)| *mean);
+    let recommended_time = results.iter().find(|(p, _)| *p == recommended);
+    println!("recommended: {:?}", recommended);
+    if let (Some((fastest, fastest_time)), Some((_, recommended_time))) =
+        (fastest, recommended_time)
+    {
+        if recommended_time.as_secs_f64() > fastest_time.as_secs_f64() * TOLERANCE {
+            return Err(format!(
+                "{:?} is recommended, but {:?} is faster on this machine",
+                recommended, fastest
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
+use transcribe_rs::{
+    engines::canary::{CanaryEngine, CanaryInferenceParams, CanaryModelParams},
+    TranscriptionEngine,
+};
+
+fn get_audio_duration(path: &PathBuf) -> Result<f64, Box<dyn std::error::Error>> {
+    let reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    let duration = reader.duration() as f64 / spec.sample_rate as f64;
+    Ok(duration)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logger
+    env_logger::init();
+
+    let mut engine = CanaryEngine::new();
+    let model_path = PathBuf::from("models/canary-1b-v2");
+    let wav_path = PathBuf::from("samples/dots.wav");
+
+    // Get audio duration
+    let audio_duration = get_audio_duration(&wav_path)?;
+    println!("Audio duration: {:.2}s", audio_duration);
+
+    println!("Using Canary engine");
+    println!("Loading model: {:?}", model_path);
+
+    let load_start = Instant::now();
+    engine.load_model_with_params(&model_path, CanaryModelParams::int8())?;
+    let load_duration = load_start.elapsed();
+    println!("Model loaded in {:.2?}", load_duration);
+    println!("Supported languages: {}", engine.languages().join(", "));
+
+    println!("Transcribing file: {:?}", wav_path);
+    let transcribe_start = Instant::now();
+
+    let params = CanaryInferenceParams {
+        source_language: Some("en".to_string()),
+        ..Default::default()
+    };
+
+    let result = engine.transcribe_file(&wav_path, Some(params))?;
+    let transcribe_duration = transcribe_start.elapsed();
+    println!("Transcription completed in {:.2?}", transcribe_duration);
+
+    // Calculate real-time speedup factor
+    let speedup_factor = audio_duration / transcribe_duration.as_secs_f64();
+    println!(
+        "Real-time speedup: {:.2}x faster than real-time",
+        speedup_factor
+    );
+
+    println!("Transcription result:");
+    println!("{}", result.text);
+
+    // The same model translates, here from English speech to German text
+    let translation = engine.transcribe_file(
+        &wav_path,
+        Some(CanaryInferenceParams::translate("en", "de")),
+    )?;
+    println!("\nTranslation (de):");
+    println!("{}", translation.text);
+
+    engine.unload_model();
+
+    Ok(())
+}
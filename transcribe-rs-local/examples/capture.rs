@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+use transcribe_rs::audio::capture::{
+    list_input_devices, list_loopback_devices, AudioCapture, CaptureConfig, CaptureSource,
+    CAPTURE_SAMPLE_RATE,
+};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logger
+    env_logger::init();
+
+    // Pass `--loopback` to record system audio instead of the microphone, and
+    // optionally a device name: `cargo run --example capture --features capture -- --loopback`
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let source = match args.iter().position(|a| a == "--loopback") {
+        Some(i) => {
+            args.remove(i);
+            CaptureSource::Loopback
+        }
+        None => CaptureSource::Microphone,
+    };
+
+    let devices = match source {
+        CaptureSource::Microphone => list_input_devices()?,
+        CaptureSource::Loopback => list_loopback_devices()?,
+    };
+    println!("{:?} devices:", source);
+    for device in devices {
+        let marker = if device.is_default { " (default)" } else { "" };
+        println!("  {}{}", device.name, marker);
+    }
+
+    let config = CaptureConfig {
+        source,
+        device_name: args.into_iter().next(),
+        ..Default::default()
+    };
+
+    let record_for = Duration::from_secs(5);
+    let capture = AudioCapture::start(config)?;
+    println!("Recording for {:?}...", record_for);
+
+    let start = Instant::now();
+    let mut samples = Vec::new();
+    while start.elapsed() < record_for {
+        if let Some(frame) = capture.recv_timeout(Duration::from_millis(100)) {
+            samples.extend_from_slice(&frame);
+        }
+    }
+    capture.stop();
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: CAPTURE_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create("capture.wav", spec)?;
+    for sample in &samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+
+    println!(
+        "Captured {:.2}s of audio to capture.wav",
+        samples.len() as f64 / CAPTURE_SAMPLE_RATE as f64
+    );
+
+    Ok(())
+}
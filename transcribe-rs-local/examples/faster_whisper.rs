@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use transcribe_rs::{
+    engines::faster_whisper::{
+        FasterWhisperEngine, FasterWhisperInferenceParams, FasterWhisperModelParams,
+    },
+    TranscriptionEngine,
+};
+
+fn get_audio_duration(path: &PathBuf) -> Result<f64, Box<dyn std::error::Error>> {
+    let reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    let duration = reader.duration() as f64 / spec.sample_rate as f64;
+    Ok(duration)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logger
+    env_logger::init();
+
+    // wyoming-faster-whisper server binary
+    // Install with: pip install wyoming-faster-whisper
+    // To use a server that is already running instead:
+    // FasterWhisperEngine::connect(FasterWhisperProtocol::OpenAI, "http://localhost:8000")
+    let mut engine = FasterWhisperEngine::new(PathBuf::from("wyoming-faster-whisper"));
+
+    // A model name is downloaded into data_dir on first use
+    let model = Path::new("small-int8");
+    let wav_path = PathBuf::from("samples/dots.wav");
+
+    // Get audio duration
+    let audio_duration = get_audio_duration(&wav_path)?;
+    println!("Audio duration: {:.2}s", audio_duration);
+
+    println!("Using faster-whisper engine");
+    println!("Loading model: {:?}", model);
+
+    let load_start = Instant::now();
+
+    // Configure server parameters
+    let model_params = FasterWhisperModelParams {
+        port: 10300,
+        device: Some("auto".to_string()), // "cuda" for NVIDIA GPUs, "cpu" otherwise
+        data_dir: Some(PathBuf::from("models/faster-whisper")),
+        ..Default::default()
+    };
+
+    engine.load_model_with_params(model, model_params)?;
+    let load_duration = load_start.elapsed();
+    println!("faster-whisper server started in {:.2?}", load_duration);
+
+    println!("Transcribing file: {:?}", wav_path);
+    let transcribe_start = Instant::now();
+
+    let params = FasterWhisperInferenceParams {
+        language: Some("en".to_string()), // Set to None for auto-detection
+        ..Default::default()
+    };
+
+    let result = engine.transcribe_file(&wav_path, Some(params))?;
+    let transcribe_duration = transcribe_start.elapsed();
+    println!("Transcription completed in {:.2?}", transcribe_duration);
+
+    // Calculate real-time speedup factor
+    let speedup_factor = audio_duration / transcribe_duration.as_secs_f64();
+    println!(
+        "Real-time speedup: {:.2}x faster than real-time",
+        speedup_factor
+    );
+
+    println!("Transcription result:");
+    println!("{}", result.text);
+
+    // Server is automatically stopped when engine is dropped
+    engine.unload_model();
+
+    Ok(())
+}
@@ -44,6 +44,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Moonshine parameters - max_length is automatically calculated from audio duration
     let params = MoonshineInferenceParams {
         max_length: None, // Auto-calculated based on audio duration and model token rate
+        ..Default::default()
     };
 
     let result = engine.transcribe_file(&wav_path, Some(params))?;
@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use transcribe_rs::{
+    engines::native::{NativeEngine, NativeModelParams},
+    TranscriptionEngine,
+};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logger
+    env_logger::init();
+
+    let locale = std::env::args().nth(1);
+    let wav_path = PathBuf::from("samples/jfk.wav");
+
+    if !NativeEngine::is_available(locale.as_deref()) {
+        eprintln!("No native speech recognizer available on this system");
+        return Ok(());
+    }
+
+    let mut engine = NativeEngine::new();
+    println!("Using native OS speech recognizer");
+
+    let load_start = Instant::now();
+    // Nothing to download: the model path is ignored
+    engine.load_model_with_params(
+        Path::new(""),
+        NativeModelParams {
+            locale,
+            ..Default::default()
+        },
+    )?;
+    println!("Recognizer ready in {:.2?}", load_start.elapsed());
+
+    let transcribe_start = Instant::now();
+    let result = engine.transcribe_file(&wav_path, None)?;
+    println!(
+        "Transcription completed in {:.2?}",
+        transcribe_start.elapsed()
+    );
+
+    if let Some(segments) = &result.segments {
+        for segment in segments {
+            println!(
+                "[{:.2}s - {:.2}s]: {}",
+                segment.start, segment.end, segment.text
+            );
+        }
+    }
+
+    println!("Transcription result:");
+    println!("{}", result.text);
+
+    Ok(())
+}
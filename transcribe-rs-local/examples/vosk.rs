@@ -0,0 +1,70 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use transcribe_rs::{
+    audio::read_wav_samples,
+    engines::vosk::{VoskEngine, VoskInferenceParams, VoskUpdate},
+    postprocess::PostProcessOptions,
+    TranscriptionEngine,
+};
+
+// 100 ms of 16 kHz audio, a typical live capture frame
+const FRAME_SAMPLES: usize = 1600;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logger
+    env_logger::init();
+
+    let mut engine = VoskEngine::new();
+    let model_path = PathBuf::from("models/vosk-model-small-en-us-0.15");
+    let wav_path = PathBuf::from("samples/dots.wav");
+
+    println!("Using Vosk engine");
+    println!("Loading model: {:?}", model_path);
+
+    let load_start = Instant::now();
+    engine.load_model(&model_path)?;
+    println!("Model loaded in {:.2?}", load_start.elapsed());
+
+    // Feed the file frame by frame, as if it were being recorded
+    let samples = read_wav_samples(&wav_path)?;
+    println!(
+        "Streaming file: {:?} ({:.2}s)",
+        wav_path,
+        samples.len() as f32 / 16000.0
+    );
+
+    let params = VoskInferenceParams {
+        post_process: PostProcessOptions::all(),
+        ..Default::default()
+    };
+
+    let transcribe_start = Instant::now();
+    let mut stream = engine.stream(Some(params))?;
+    for frame in samples.chunks(FRAME_SAMPLES) {
+        match stream.accept_samples(frame)? {
+            VoskUpdate::Partial(text) => {
+                print!("\r\x1b[K... {}", text);
+                std::io::stdout().flush()?;
+            }
+            VoskUpdate::Final(segment) => {
+                println!(
+                    "\r\x1b[K[{:.2}s - {:.2}s]: {}",
+                    segment.start, segment.end, segment.text
+                );
+            }
+        }
+    }
+    let result = stream.finish()?;
+    println!("\r\x1b[K");
+    println!(
+        "Transcription completed in {:.2?}",
+        transcribe_start.elapsed()
+    );
+
+    println!("Transcription result:");
+    println!("{}", result.text);
+
+    Ok(())
+}
@@ -50,12 +50,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let transcribe_start = Instant::now();
 
     // Configure inference parameters
-    let params = WhisperfileInferenceParams {
-        language: Some("en".to_string()), // Set to None for auto-detection
-        translate: false,                 // Set to true to translate to English
-        temperature: Some(0.0),           // 0.0 = greedy decoding
-        response_format: Some("verbose_json".to_string()),
-    };
+    let params = WhisperfileInferenceParams::builder()
+        .language("en".to_string()) // Leave unset for auto-detection
+        .translate(false) // Set to true to translate to English
+        .temperature(0.0) // 0.0 = greedy decoding
+        .response_format("verbose_json".to_string())
+        .build()?;
 
     let result = engine.transcribe_file(&wav_path, Some(params))?;
     let transcribe_duration = transcribe_start.elapsed();
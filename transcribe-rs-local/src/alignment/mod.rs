@@ -0,0 +1,401 @@
+//! Forced alignment of transcripts to audio for word-level timestamps.
+//!
+//! Many engines only report segment timing (or none at all). Following
+//! WhisperX, the transcript is aligned to the audio with a CTC acoustic model
+//! such as wav2vec2: the model scores every character for every ~20 ms frame,
+//! and a Viterbi pass finds the most likely frames at which the known
+//! characters are spoken. Word boundaries then come from the first and last
+//! character of each word.
+//!
+//! The alignment search lives here and needs no extra dependencies; running
+//! the acoustic model requires the `alignment` feature, which provides
+//! [`Aligner`].
+//!
+//! # Model Format
+//!
+//! [`Aligner`] expects a directory containing:
+//! - `model.onnx` - wav2vec2 CTC model taking `input_values` and producing `logits`
+//! - `vocab.json` - Hugging Face vocabulary mapping characters to ids, with
+//!   `<pad>` as the CTC blank and `|` as the word delimiter
+//!
+//! Character models such as `facebook/wav2vec2-base-960h` (English) or the
+//! `jonatasgrosman/wav2vec2-large-xlsr-53-*` family work; use one matching
+//! the spoken language. Characters missing from the vocabulary (digits,
+//! symbols) are skipped and their words get interpolated timing.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use std::path::PathBuf;
+//! use transcribe_rs::{alignment::Aligner, audio::read_wav_samples};
+//!
+//! let samples = read_wav_samples(&PathBuf::from("audio.wav"))?;
+//! let result = engine.transcribe_samples(samples.clone(), None)?;
+//!
+//! let mut aligner = Aligner::new("models/wav2vec2-base-960h")?;
+//! for segment in aligner.align(&samples, &result)? {
+//!     for word in segment.words {
+//!         println!("[{:.2}s - {:.2}s] {}", word.start, word.end, word.text);
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#[cfg(feature = "alignment")]
+mod model;
+
+#[cfg(feature = "alignment")]
+pub use model::{Aligner, AlignmentError};
+
+use std::collections::HashMap;
+
+use crate::TranscriptionSegment;
+
+/// A transcript segment with aligned word timings. Times are in seconds.
+#[derive(Debug)]
+pub struct AlignedSegment {
+    /// Start of the first word, or the original start if nothing aligned
+    pub start: f32,
+    /// End of the last word, or the original end if nothing aligned
+    pub end: f32,
+    /// The segment text, unchanged
+    pub text: String,
+    /// The words of `text` in order
+    pub words: Vec<TranscriptionSegment>,
+}
+
+/// Frames `[start, end)` in which a token is spoken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Character vocabulary of a CTC model.
+#[derive(Debug, Clone)]
+pub struct CtcVocabulary {
+    ids: HashMap<char, usize>,
+    blank: usize,
+    word_delimiter: Option<usize>,
+    uppercase: bool,
+}
+
+impl CtcVocabulary {
+    /// Parse a Hugging Face `vocab.json` (`{"<pad>": 0, "|": 4, "E": 5, ...}`).
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let tokens: HashMap<String, usize> = serde_json::from_str(json)?;
+        Ok(Self::from_tokens(&tokens))
+    }
+
+    /// Build from a token to id map. Only single-character tokens are used
+    /// for alignment; `<pad>` (or `<blank>`) is the blank, falling back to 0.
+    pub fn from_tokens(tokens: &HashMap<String, usize>) -> Self {
+        let mut ids = HashMap::new();
+        for (token, &id) in tokens {
+            let mut chars = token.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                ids.insert(c, id);
+            }
+        }
+
+        let blank = ["<pad>", "<blank>", "[PAD]"]
+            .iter()
+            .find_map(|t| tokens.get(*t).copied())
+            .unwrap_or(0);
+        let word_delimiter = ids.get(&'|').copied();
+        // English wav2vec2 vocabularies are upper case
+        let uppercase =
+            ids.keys().any(|c| c.is_uppercase()) && !ids.keys().any(|c| c.is_lowercase());
+
+        Self {
+            ids,
+            blank,
+            word_delimiter,
+            uppercase,
+        }
+    }
+
+    /// Id of the CTC blank token.
+    pub fn blank(&self) -> usize {
+        self.blank
+    }
+
+    /// Token ids for the characters of `word` the model knows, case-folded
+    /// to match the vocabulary.
+    fn word_tokens(&self, word: &str) -> Vec<usize> {
+        word.chars()
+            .flat_map(|c| {
+                let folded: Vec<char> = if self.uppercase {
+                    c.to_uppercase().collect()
+                } else {
+                    c.to_lowercase().collect()
+                };
+                folded
+            })
+            .filter_map(|c| self.ids.get(&c).copied())
+            .collect()
+    }
+}
+
+/// Find the most likely frames for `tokens` given per-frame log
+/// probabilities (`emission`, `frames x num_classes`, row-major).
+///
+/// Every frame either stays on the current token (repeating it or emitting
+/// the blank) or advances to the next token. Returns `None` when the tokens
+/// cannot fit in the available frames.
+pub fn ctc_align(
+    emission: &[f32],
+    num_classes: usize,
+    tokens: &[usize],
+    blank: usize,
+) -> Option<Vec<TokenSpan>> {
+    const STAY_BLANK: u8 = 0;
+    const STAY_TOKEN: u8 = 1;
+    const ADVANCE: u8 = 2;
+
+    let num_frames = emission.len() / num_classes.max(1);
+    let states = tokens.len() + 1;
+    if tokens.is_empty() {
+        return Some(Vec::new());
+    }
+    if num_frames < tokens.len() {
+        return None;
+    }
+
+    // score[j]: best log probability with the first j tokens emitted
+    let mut score = vec![f32::NEG_INFINITY; states];
+    score[0] = 0.0;
+    let mut moves = vec![STAY_BLANK; num_frames * states];
+
+    for t in 0..num_frames {
+        let frame = &emission[t * num_classes..(t + 1) * num_classes];
+        let mut next = vec![f32::NEG_INFINITY; states];
+
+        for j in 0..states {
+            let (mut best, mut kind) = (score[j] + frame[blank], STAY_BLANK);
+            if j > 0 {
+                let token = frame[tokens[j - 1]];
+                if score[j] + token > best {
+                    best = score[j] + token;
+                    kind = STAY_TOKEN;
+                }
+                if score[j - 1] + token > best {
+                    best = score[j - 1] + token;
+                    kind = ADVANCE;
+                }
+            }
+            next[j] = best;
+            moves[t * states + j] = kind;
+        }
+        score = next;
+    }
+
+    if !score[tokens.len()].is_finite() {
+        return None;
+    }
+
+    // Walk back from the final state, recording where each token was spoken
+    let mut spans = vec![TokenSpan { start: 0, end: 0 }; tokens.len()];
+    let mut ends: Vec<Option<usize>> = vec![None; tokens.len()];
+    let mut j = tokens.len();
+    for t in (0..num_frames).rev() {
+        match moves[t * states + j] {
+            STAY_TOKEN => {
+                ends[j - 1].get_or_insert(t + 1);
+            }
+            ADVANCE => {
+                spans[j - 1] = TokenSpan {
+                    start: t,
+                    end: *ends[j - 1].get_or_insert(t + 1),
+                };
+                j -= 1;
+            }
+            _ => {}
+        }
+    }
+
+    Some(spans)
+}
+
+/// Align the words of `text` within `emission`, which covers
+/// `start..start + duration` seconds of audio.
+///
+/// Returns `None` if the text cannot be aligned, e.g. because the audio is
+/// too short for it.
+pub fn align_words(
+    text: &str,
+    emission: &[f32],
+    num_classes: usize,
+    vocabulary: &CtcVocabulary,
+    start: f32,
+    duration: f32,
+) -> Option<Vec<TranscriptionSegment>> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let num_frames = emission.len() / num_classes.max(1);
+    if words.is_empty() || num_frames == 0 {
+        return Some(Vec::new());
+    }
+
+    // Token sequence with word delimiters between words, remembering which
+    // tokens belong to which word
+    let mut tokens = Vec::new();
+    let mut word_tokens = Vec::with_capacity(words.len());
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            if let Some(delimiter) = vocabulary.word_delimiter {
+                tokens.push(delimiter);
+            }
+        }
+        let ids = vocabulary.word_tokens(word);
+        word_tokens.push(tokens.len()..tokens.len() + ids.len());
+        tokens.extend(ids);
+    }
+
+    let spans = ctc_align(emission, num_classes, &tokens, vocabulary.blank)?;
+    let seconds_per_frame = duration / num_frames as f32;
+
+    let timed: Vec<Option<(f32, f32)>> = word_tokens
+        .iter()
+        .map(|range| {
+            let first = spans.get(range.start).filter(|_| !range.is_empty())?;
+            let last = spans[range.end - 1];
+            Some((
+                start + first.start as f32 * seconds_per_frame,
+                start + last.end as f32 * seconds_per_frame,
+            ))
+        })
+        .collect();
+
+    Some(interpolate_words(&words, &timed, start, start + duration))
+}
+
+/// Spread the words of `text` evenly over `start..end`, for segments that
+/// could not be aligned.
+pub fn spread_words(text: &str, start: f32, end: f32) -> Vec<TranscriptionSegment> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    interpolate_words(&words, &vec![None; words.len()], start, end)
+}
+
+/// Fill in timing for words without any (`None`) by dividing the gap between
+/// their timed neighbours, or the segment bounds, evenly among them.
+fn interpolate_words(
+    words: &[&str],
+    timed: &[Option<(f32, f32)>],
+    start: f32,
+    end: f32,
+) -> Vec<TranscriptionSegment> {
+    let mut result = Vec::with_capacity(words.len());
+    let mut i = 0;
+    while i < words.len() {
+        if let Some((word_start, word_end)) = timed[i] {
+            result.push(TranscriptionSegment {
+                start: word_start,
+                end: word_end,
+                text: words[i].to_string(),
+            });
+            i += 1;
+            continue;
+        }
+
+        // Run of untimed words between two timed neighbours
+        let run_end = (i..words.len())
+            .find(|&k| timed[k].is_some())
+            .unwrap_or(words.len());
+        let gap_start = result
+            .last()
+            .map_or(start, |w: &TranscriptionSegment| w.end);
+        let gap_end = timed
+            .get(run_end)
+            .copied()
+            .flatten()
+            .map_or(end, |(s, _)| s)
+            .max(gap_start);
+        let step = (gap_end - gap_start) / (run_end - i) as f32;
+
+        for (n, word) in words[i..run_end].iter().enumerate() {
+            result.push(TranscriptionSegment {
+                start: gap_start + step * n as f32,
+                end: gap_start + step * (n + 1) as f32,
+                text: word.to_string(),
+            });
+        }
+        i = run_end;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary() -> CtcVocabulary {
+        let tokens = [("<pad>", 0), ("|", 1), ("A", 2), ("B", 3), ("C", 4)]
+            .into_iter()
+            .map(|(t, id)| (t.to_string(), id))
+            .collect();
+        CtcVocabulary::from_tokens(&tokens)
+    }
+
+    /// Log probabilities that strongly favour the given class in each frame.
+    fn emission(frames: &[usize], num_classes: usize) -> Vec<f32> {
+        frames
+            .iter()
+            .flat_map(|&best| (0..num_classes).map(move |c| if c == best { -0.01 } else { -5.0 }))
+            .collect()
+    }
+
+    #[test]
+    fn test_ctc_align_finds_token_frames() {
+        // blank, A, A, blank, B, blank
+        let emission = emission(&[0, 2, 2, 0, 3, 0], 5);
+        let spans = ctc_align(&emission, 5, &[2, 3], 0).unwrap();
+
+        assert_eq!(
+            spans,
+            vec![
+                TokenSpan { start: 1, end: 3 },
+                TokenSpan { start: 4, end: 5 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ctc_align_rejects_too_few_frames() {
+        let emission = emission(&[2], 5);
+        assert!(ctc_align(&emission, 5, &[2, 3], 0).is_none());
+    }
+
+    #[test]
+    fn test_align_words_maps_frames_to_seconds() {
+        // "ab c": blank, A, B, |, blank, C, blank, blank (0.1 s per frame)
+        let emission = emission(&[0, 2, 3, 1, 0, 4, 0, 0], 5);
+        let words = align_words("ab c", &emission, 5, &vocabulary(), 10.0, 0.8).unwrap();
+
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "ab");
+        assert!((words[0].start - 10.1).abs() < 1e-4);
+        assert!((words[0].end - 10.3).abs() < 1e-4);
+        assert_eq!(words[1].text, "c");
+        assert!((words[1].start - 10.5).abs() < 1e-4);
+        assert!((words[1].end - 10.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_unknown_words_are_interpolated() {
+        // "a 42 b": digits are not in the vocabulary
+        let emission = emission(&[2, 0, 1, 0, 0, 1, 3, 0], 5);
+        let words = align_words("a 42 b", &emission, 5, &vocabulary(), 0.0, 0.8).unwrap();
+
+        assert_eq!(words[1].text, "42");
+        assert!(words[1].start >= words[0].end);
+        assert!(words[1].end <= words[2].start);
+    }
+
+    #[test]
+    fn test_spread_words() {
+        let words = spread_words("one two", 1.0, 2.0);
+        assert_eq!(words.len(), 2);
+        assert!((words[0].end - 1.5).abs() < 1e-6);
+        assert!((words[1].end - 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,160 @@
+use ndarray::{Array2, Axis};
+use ort::inputs;
+use ort::session::Session;
+use ort::value::TensorRef;
+
+use std::fs;
+use std::path::Path;
+
+use super::{align_words, spread_words, AlignedSegment, CtcVocabulary};
+use crate::engines::onnx;
+use crate::{TranscriptionResult, TranscriptionSegment};
+
+const SAMPLE_RATE: usize = 16000;
+
+/// Audio is run through the model in windows of this many samples (30s) to
+/// bound memory use.
+const WINDOW_SAMPLES: usize = 30 * SAMPLE_RATE;
+
+/// Segments longer than this are not aligned; their words are spread evenly
+/// instead. The alignment search grows with duration times text length.
+pub const MAX_SEGMENT_SECS: f32 = 300.0;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AlignmentError {
+    #[error("ORT error: {0}")]
+    Ort(#[from] ort::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid vocabulary: {0}")]
+    Vocabulary(#[from] serde_json::Error),
+    #[error("ndarray shape error: {0}")]
+    Shape(#[from] ndarray::ShapeError),
+    #[error("Model output not found: {0}")]
+    OutputNotFound(String),
+}
+
+/// wav2vec2-style CTC model used to align transcripts to audio.
+pub struct Aligner {
+    session: Session,
+    vocabulary: CtcVocabulary,
+}
+
+impl Aligner {
+    /// Load `model.onnx` and `vocab.json` from `model_dir`.
+    pub fn new<P: AsRef<Path>>(model_dir: P) -> Result<Self, AlignmentError> {
+        let session = onnx::init_session(&model_dir, "model", None, false)?;
+        let vocab = fs::read_to_string(model_dir.as_ref().join("vocab.json"))?;
+        let vocabulary = CtcVocabulary::from_json(&vocab)?;
+
+        log::info!("Loaded alignment model from {:?}", model_dir.as_ref());
+
+        Ok(Self {
+            session,
+            vocabulary,
+        })
+    }
+
+    /// Align the segments of `result` (or its whole text when it has no
+    /// segments) to `samples`, producing word-level timestamps.
+    ///
+    /// Segments that cannot be aligned keep their timing and get evenly
+    /// spread words.
+    pub fn align(
+        &mut self,
+        samples: &[f32],
+        result: &TranscriptionResult,
+    ) -> Result<Vec<AlignedSegment>, AlignmentError> {
+        let duration = samples.len() as f32 / SAMPLE_RATE as f32;
+        let spans: Vec<(f32, f32, &str)> = match &result.segments {
+            Some(segments) => segments
+                .iter()
+                .map(|s| (s.start, s.end, s.text.as_str()))
+                .collect(),
+            None => vec![(0.0, duration, result.text.as_str())],
+        };
+
+        let mut aligned = Vec::with_capacity(spans.len());
+        for (start, end, text) in spans {
+            let words = self
+                .align_segment(samples, start, end, text)?
+                .unwrap_or_else(|| {
+                    log::debug!("Could not align segment at {:.2}s, spreading words", start);
+                    spread_words(text, start, end)
+                });
+
+            aligned.push(AlignedSegment {
+                start: words.first().map_or(start, |w| w.start),
+                end: words.last().map_or(end, |w| w.end),
+                text: text.to_string(),
+                words,
+            });
+        }
+
+        Ok(aligned)
+    }
+
+    fn align_segment(
+        &mut self,
+        samples: &[f32],
+        start: f32,
+        end: f32,
+        text: &str,
+    ) -> Result<Option<Vec<TranscriptionSegment>>, AlignmentError> {
+        let first = ((start.max(0.0) * SAMPLE_RATE as f32) as usize).min(samples.len());
+        let last = ((end * SAMPLE_RATE as f32) as usize).clamp(first, samples.len());
+        let audio = &samples[first..last];
+
+        if audio.is_empty() || text.trim().is_empty() || end - start > MAX_SEGMENT_SECS {
+            return Ok(None);
+        }
+
+        let (emission, num_classes) = self.emission(audio)?;
+        let duration = audio.len() as f32 / SAMPLE_RATE as f32;
+        Ok(align_words(
+            text,
+            &emission,
+            num_classes,
+            &self.vocabulary,
+            first as f32 / SAMPLE_RATE as f32,
+            duration,
+        ))
+    }
+
+    /// Per-frame log probabilities for `audio`, flattened row-major, and the
+    /// number of classes per frame.
+    fn emission(&mut self, audio: &[f32]) -> Result<(Vec<f32>, usize), AlignmentError> {
+        let mut emission = Vec::new();
+        let mut num_classes = 0;
+
+        for window in audio.chunks(WINDOW_SAMPLES) {
+            let input = Array2::from_shape_vec((1, window.len()), normalize(window))?;
+            let outputs = self.session.run(inputs![
+                "input_values" => TensorRef::from_array_view(input.view())?,
+            ])?;
+            let logits = outputs
+                .get("logits")
+                .ok_or_else(|| AlignmentError::OutputNotFound("logits".to_string()))?
+                .try_extract_array::<f32>()?;
+
+            // [1, frames, classes]
+            num_classes = logits.shape()[2];
+            for frame in logits.index_axis(Axis(0), 0).outer_iter() {
+                let max = frame.fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+                let log_sum = frame.iter().map(|&x| (x - max).exp()).sum::<f32>().ln() + max;
+                emission.extend(frame.iter().map(|&x| x - log_sum));
+            }
+        }
+
+        Ok((emission, num_classes))
+    }
+}
+
+/// Zero-mean, unit-variance normalization expected by wav2vec2.
+fn normalize(samples: &[f32]) -> Vec<f32> {
+    let len = samples.len().max(1) as f32;
+    let mean = samples.iter().sum::<f32>() / len;
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / len;
+    let std = (variance + 1e-7).sqrt();
+    samples.iter().map(|s| (s - mean) / std).collect()
+}
@@ -5,6 +5,9 @@
 
 use std::path::Path;
 
+#[cfg(feature = "capture")]
+pub mod capture;
+
 /// Read WAV file samples and convert them to the required format.
 ///
 /// This function reads a WAV file and converts it to the format expected by
@@ -0,0 +1,681 @@
+//! Live audio capture from input devices and system audio.
+//!
+//! This module wraps [cpal](https://docs.rs/cpal) to deliver a stream of
+//! fixed-size frames of 16 kHz mono `f32` samples, the format expected by all
+//! transcription engines, regardless of the device's native sample rate,
+//! channel count or sample format.
+//!
+//! Besides microphones, [`CaptureSource::Loopback`] records whatever is
+//! playing through the speakers (e.g. a meeting):
+//!
+//! - **Windows**: WASAPI loopback on an output device.
+//! - **Linux**: the PulseAudio/PipeWire monitor of the default sink,
+//!   recorded with `parec` (see [`PulseMonitor`]).
+//! - **macOS**: there is no native system audio capture; a third-party
+//!   virtual loopback driver such as BlackHole must be installed and playback
+//!   routed through it, since cpal does not expose ScreenCaptureKit or
+//!   CoreAudio process taps.
+//!
+//! The cpal stream lives on a dedicated worker thread, so [`AudioCapture`] is
+//! `Send` and can be moved freely between threads on every platform.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use transcribe_rs::audio::capture::{list_input_devices, AudioCapture, CaptureConfig};
+//!
+//! for device in list_input_devices()? {
+//!     println!("{}{}", device.name, if device.is_default { " (default)" } else { "" });
+//! }
+//!
+//! let capture = AudioCapture::start(CaptureConfig::default())?;
+//! let mut samples = Vec::new();
+//! while samples.len() < 16000 * 5 {
+//!     match capture.recv() {
+//!         Some(frame) => samples.extend_from_slice(&frame),
+//!         None => break,
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#[cfg(target_os = "linux")]
+use std::io::Read;
+#[cfg(target_os = "linux")]
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SizedSample};
+use rubato::{FftFixedIn, Resampler};
+
+/// Sample rate of frames produced by [`AudioCapture`].
+pub const CAPTURE_SAMPLE_RATE: u32 = 16000;
+
+const RESAMPLER_CHUNK_SIZE: usize = 1024;
+
+/// Errors that can occur while enumerating devices or capturing audio.
+#[derive(thiserror::Error, Debug)]
+pub enum CaptureError {
+    #[error("No default input device available")]
+    NoDefaultDevice,
+    #[error("Input device not found: {0}")]
+    DeviceNotFound(String),
+    #[error("System audio capture is not available: {0}")]
+    LoopbackUnavailable(String),
+    #[error("Failed to enumerate devices: {0}")]
+    Devices(#[from] cpal::DevicesError),
+    #[error("Failed to query device configuration: {0}")]
+    Config(#[from] cpal::DefaultStreamConfigError),
+    #[error("Failed to build input stream: {0}")]
+    BuildStream(#[from] cpal::BuildStreamError),
+    #[error("Failed to start input stream: {0}")]
+    PlayStream(#[from] cpal::PlayStreamError),
+    #[error("Failed to create resampler: {0}")]
+    Resampler(#[from] rubato::ResamplerConstructionError),
+    #[error("Capture thread exited unexpectedly")]
+    WorkerExited,
+}
+
+/// An audio input device.
+#[derive(Debug, Clone)]
+pub struct InputDevice {
+    /// Device name, as accepted by [`CaptureConfig::device_name`].
+    pub name: String,
+    /// Whether this is the host's default input device.
+    pub is_default: bool,
+}
+
+/// List available input devices on the default host.
+pub fn list_input_devices() -> Result<Vec<InputDevice>, CaptureError> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let devices = host
+        .input_devices()?
+        .map(|device| {
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            let is_default = Some(&name) == default_name.as_ref();
+            InputDevice { name, is_default }
+        })
+        .collect();
+
+    Ok(devices)
+}
+
+/// List devices that can be used with [`CaptureSource::Loopback`].
+///
+/// On Windows these are output devices; elsewhere they are input devices that
+/// expose system audio, such as PulseAudio monitors or BlackHole.
+pub fn list_loopback_devices() -> Result<Vec<InputDevice>, CaptureError> {
+    #[cfg(target_os = "windows")]
+    {
+        let host = cpal::default_host();
+        let default_name = host.default_output_device().and_then(|d| d.name().ok());
+
+        let devices = host
+            .output_devices()?
+            .map(|device| {
+                let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+                let is_default = Some(&name) == default_name.as_ref();
+                InputDevice { name, is_default }
+            })
+            .collect();
+
+        Ok(devices)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Ok(list_input_devices()?
+            .into_iter()
+            .filter(|d| is_loopback_device_name(&d.name))
+            .collect())
+    }
+}
+
+/// Where [`AudioCapture`] records from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaptureSource {
+    /// A microphone or other input device.
+    #[default]
+    Microphone,
+    /// System audio playing through an output device.
+    Loopback,
+}
+
+/// Configuration for [`AudioCapture::start`].
+#[derive(Debug, Clone)]
+pub struct CaptureConfig {
+    /// Whether to record from a microphone or from system audio.
+    pub source: CaptureSource,
+    /// Name of the device to use, as listed by [`list_input_devices`] or
+    /// [`list_loopback_devices`]. `None` selects the default device.
+    pub device_name: Option<String>,
+    /// Duration of each emitted frame.
+    pub frame_duration: Duration,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            source: CaptureSource::default(),
+            device_name: None,
+            frame_duration: Duration::from_millis(30),
+        }
+    }
+}
+
+/// A running capture session delivering 16 kHz mono frames.
+///
+/// Capture stops when this value is dropped or [`AudioCapture::stop`] is called.
+pub struct AudioCapture {
+    frames: mpsc::Receiver<Vec<f32>>,
+    stop_tx: Option<mpsc::Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl AudioCapture {
+    /// Open the configured device and start capturing.
+    pub fn start(config: CaptureConfig) -> Result<Self, CaptureError> {
+        let (frame_tx, frame_rx) = mpsc::channel::<Vec<f32>>();
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (init_tx, init_rx) = mpsc::sync_channel::<Result<(), CaptureError>>(1);
+
+        let worker = std::thread::spawn(move || {
+            let (stream, raw_rx, mut assembler) = match open_stream(&config) {
+                Ok(opened) => opened,
+                Err(e) => {
+                    let _ = init_tx.send(Err(e));
+                    return;
+                }
+            };
+            let _ = init_tx.send(Ok(()));
+
+            loop {
+                if stop_rx.try_recv().is_ok() {
+                    break;
+                }
+                match raw_rx.recv_timeout(Duration::from_millis(50)) {
+                    Ok(raw) => {
+                        let mut receiver_gone = false;
+                        assembler.push(&raw, |frame| {
+                            receiver_gone |= frame_tx.send(frame.to_vec()).is_err();
+                        });
+                        if receiver_gone {
+                            break;
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+
+            drop(stream);
+        });
+
+        match init_rx.recv() {
+            Ok(Ok(())) => Ok(Self {
+                frames: frame_rx,
+                stop_tx: Some(stop_tx),
+                worker: Some(worker),
+            }),
+            Ok(Err(e)) => {
+                let _ = worker.join();
+                Err(e)
+            }
+            Err(_) => {
+                let _ = worker.join();
+                Err(CaptureError::WorkerExited)
+            }
+        }
+    }
+
+    /// Block until the next frame is available.
+    ///
+    /// Returns `None` once capture has stopped.
+    pub fn recv(&self) -> Option<Vec<f32>> {
+        self.frames.recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next frame.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Vec<f32>> {
+        self.frames.recv_timeout(timeout).ok()
+    }
+
+    /// Return the next frame if one is already buffered.
+    pub fn try_recv(&self) -> Option<Vec<f32>> {
+        self.frames.try_recv().ok()
+    }
+
+    /// Iterate over frames until capture stops.
+    pub fn frames(&self) -> impl Iterator<Item = Vec<f32>> + '_ {
+        self.frames.iter()
+    }
+
+    /// Stop capturing and release the device.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(tx) = self.stop_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.worker.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// A running stream, held until capture stops.
+#[allow(dead_code)]
+enum CaptureStream {
+    Cpal(cpal::Stream),
+    #[cfg(target_os = "linux")]
+    Pulse(PulseMonitor),
+}
+
+type OpenedStream = (CaptureStream, mpsc::Receiver<Vec<f32>>, FrameAssembler);
+
+fn find_device(
+    mut devices: impl Iterator<Item = cpal::Device>,
+    name: &str,
+) -> Result<cpal::Device, CaptureError> {
+    devices
+        .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+        .ok_or_else(|| CaptureError::DeviceNotFound(name.to_string()))
+}
+
+/// Name fragments of input devices that carry system audio rather than a
+/// microphone signal.
+pub const LOOPBACK_NAME_HINTS: &[&str] = &["monitor", "loopback", "blackhole", "soundflower"];
+
+/// Whether `name` is an input device that carries system audio, such as a
+/// PulseAudio monitor or BlackHole.
+pub fn is_loopback_device_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    LOOPBACK_NAME_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// Select the device and stream configuration for `config`.
+fn resolve_device(
+    config: &CaptureConfig,
+) -> Result<(cpal::Device, cpal::SupportedStreamConfig), CaptureError> {
+    let host = cpal::default_host();
+
+    if config.source == CaptureSource::Microphone {
+        let device = match &config.device_name {
+            Some(name) => find_device(host.input_devices()?, name)?,
+            None => host
+                .default_input_device()
+                .ok_or(CaptureError::NoDefaultDevice)?,
+        };
+        let supported = device.default_input_config()?;
+        return Ok((device, supported));
+    }
+
+    // WASAPI opens an input stream on a render device in loopback mode
+    #[cfg(target_os = "windows")]
+    {
+        let device = match &config.device_name {
+            Some(name) => find_device(host.output_devices()?, name)?,
+            None => host.default_output_device().ok_or_else(|| {
+                CaptureError::LoopbackUnavailable("no default output device".to_string())
+            })?,
+        };
+        let supported = device.default_output_config()?;
+        Ok((device, supported))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let device = match &config.device_name {
+            Some(name) => find_device(host.input_devices()?, name)?,
+            None => find_loopback_input(&host)?.ok_or_else(|| {
+                CaptureError::LoopbackUnavailable(
+                    "no loopback input device found; install a virtual audio device such as BlackHole"
+                        .to_string(),
+                )
+            })?,
+        };
+        let supported = device.default_input_config()?;
+        Ok((device, supported))
+    }
+}
+
+/// The first input device that carries system audio, if any.
+#[cfg(not(target_os = "windows"))]
+fn find_loopback_input(host: &cpal::Host) -> Result<Option<cpal::Device>, CaptureError> {
+    Ok(host
+        .input_devices()?
+        .find(|d| d.name().is_ok_and(|n| is_loopback_device_name(&n))))
+}
+
+/// Records a PulseAudio or PipeWire source by name with `parec`, as mono
+/// `f32` at [`PulseMonitor::SAMPLE_RATE`].
+///
+/// Monitor sources aren't ALSA devices, and cpal's `pulse` device only
+/// reaches the default source, so this is how the monitor of an output is
+/// recorded on Linux. Recording stops when the value is dropped.
+#[cfg(target_os = "linux")]
+pub struct PulseMonitor {
+    child: Child,
+    reader: Option<JoinHandle<()>>,
+}
+
+#[cfg(target_os = "linux")]
+impl PulseMonitor {
+    /// Rate the source is recorded at; the sound server resamples to it.
+    pub const SAMPLE_RATE: u32 = CAPTURE_SAMPLE_RATE;
+
+    /// The monitor of the default output, e.g.
+    /// "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor".
+    pub fn default_source() -> Result<String, CaptureError> {
+        let output = Command::new("pactl")
+            .arg("get-default-sink")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| {
+                CaptureError::LoopbackUnavailable(format!("failed to run pactl: {}", e))
+            })?;
+        let sink = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || sink.is_empty() {
+            return Err(CaptureError::LoopbackUnavailable(
+                "no default PulseAudio or PipeWire output".to_string(),
+            ));
+        }
+        Ok(format!("{}.monitor", sink))
+    }
+
+    /// Start recording `source`, passing the samples to `on_samples` as
+    /// they arrive.
+    pub fn open(
+        source: &str,
+        mut on_samples: impl FnMut(Vec<f32>) + Send + 'static,
+    ) -> Result<Self, CaptureError> {
+        let mut child = Command::new("parec")
+            .arg(format!("--device={}", source))
+            .arg(format!("--rate={}", Self::SAMPLE_RATE))
+            .args(["--format=float32le", "--channels=1", "--latency-msec=20"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                CaptureError::LoopbackUnavailable(format!("failed to run parec: {}", e))
+            })?;
+        let mut stdout = child.stdout.take().ok_or(CaptureError::WorkerExited)?;
+        let source = source.to_string();
+
+        let reader = std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            let mut pending = Vec::new();
+            loop {
+                match stdout.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => pending.extend_from_slice(&buf[..n]),
+                }
+                let samples = take_f32le(&mut pending);
+                if !samples.is_empty() {
+                    on_samples(samples);
+                }
+            }
+            log::debug!("Stopped recording {}", source);
+        });
+
+        Ok(Self {
+            child,
+            reader: Some(reader),
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for PulseMonitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+/// Decode the whole little-endian `f32` samples at the start of `bytes`,
+/// leaving a partial sample behind.
+#[cfg(target_os = "linux")]
+fn take_f32le(bytes: &mut Vec<u8>) -> Vec<f32> {
+    let whole = bytes.len() / 4 * 4;
+    let samples = bytes[..whole]
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    bytes.drain(..whole);
+    samples
+}
+
+fn open_stream(config: &CaptureConfig) -> Result<OpenedStream, CaptureError> {
+    // Without a device carrying system audio, record the monitor of the
+    // default output through the sound server
+    #[cfg(target_os = "linux")]
+    if config.source == CaptureSource::Loopback
+        && config.device_name.is_none()
+        && find_loopback_input(&cpal::default_host())?.is_none()
+    {
+        let source = PulseMonitor::default_source()?;
+        log::info!("Capturing from {}", source);
+        let (raw_tx, raw_rx) = mpsc::channel::<Vec<f32>>();
+        let monitor = PulseMonitor::open(&source, move |samples| {
+            // The receiver is gone once capture stops; nothing left to do
+            let _ = raw_tx.send(samples);
+        })?;
+        let assembler =
+            FrameAssembler::new(PulseMonitor::SAMPLE_RATE as usize, config.frame_duration)?;
+        return Ok((CaptureStream::Pulse(monitor), raw_rx, assembler));
+    }
+
+    let (device, supported) = resolve_device(config)?;
+    let sample_rate = supported.sample_rate().0;
+    let channels = supported.channels() as usize;
+    log::info!(
+        "Capturing from {:?}: {} Hz, {} channels, {:?}",
+        device.name(),
+        sample_rate,
+        channels,
+        supported.sample_format()
+    );
+
+    let (raw_tx, raw_rx) = mpsc::channel::<Vec<f32>>();
+    let stream_config: cpal::StreamConfig = supported.config();
+
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::U8 => build_stream::<u8>(&device, &stream_config, raw_tx, channels)?,
+        cpal::SampleFormat::I8 => build_stream::<i8>(&device, &stream_config, raw_tx, channels)?,
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, raw_tx, channels)?,
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, raw_tx, channels)?,
+        cpal::SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, raw_tx, channels)?,
+        cpal::SampleFormat::F64 => build_stream::<f64>(&device, &stream_config, raw_tx, channels)?,
+        _ => build_stream::<f32>(&device, &stream_config, raw_tx, channels)?,
+    };
+    stream.play()?;
+
+    let assembler = FrameAssembler::new(sample_rate as usize, config.frame_duration)?;
+    Ok((CaptureStream::Cpal(stream), raw_rx, assembler))
+}
+
+/// Build an input stream that downmixes to mono `f32` and forwards buffers.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    raw_tx: mpsc::Sender<Vec<f32>>,
+    channels: usize,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: Sample + SizedSample + Send + 'static,
+    f32: FromSample<T>,
+{
+    let callback = move |data: &[T], _: &cpal::InputCallbackInfo| {
+        let mono: Vec<f32> = if channels <= 1 {
+            data.iter().map(|&s| s.to_sample::<f32>()).collect()
+        } else {
+            data.chunks_exact(channels)
+                .map(|frame| {
+                    frame.iter().map(|&s| s.to_sample::<f32>()).sum::<f32>() / channels as f32
+                })
+                .collect()
+        };
+        // The receiver is gone once capture stops; nothing left to do
+        let _ = raw_tx.send(mono);
+    };
+
+    device.build_input_stream(
+        config,
+        callback,
+        |err| log::error!("Input stream error: {}", err),
+        None,
+    )
+}
+
+/// Resamples mono audio to 16 kHz and slices it into fixed-size frames.
+struct FrameAssembler {
+    resampler: Option<FftFixedIn<f32>>,
+    in_buf: Vec<f32>,
+    frame_samples: usize,
+    pending: Vec<f32>,
+}
+
+impl FrameAssembler {
+    fn new(in_hz: usize, frame_duration: Duration) -> Result<Self, CaptureError> {
+        let out_hz = CAPTURE_SAMPLE_RATE as usize;
+        let frame_samples =
+            ((out_hz as f64 * frame_duration.as_secs_f64()).round() as usize).max(1);
+
+        let resampler = if in_hz != out_hz {
+            Some(FftFixedIn::<f32>::new(
+                in_hz,
+                out_hz,
+                RESAMPLER_CHUNK_SIZE,
+                1,
+                1,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            resampler,
+            in_buf: Vec::with_capacity(RESAMPLER_CHUNK_SIZE),
+            frame_samples,
+            pending: Vec::with_capacity(frame_samples),
+        })
+    }
+
+    fn push(&mut self, mut src: &[f32], mut emit: impl FnMut(&[f32])) {
+        let Some(resampler) = self.resampler.as_mut() else {
+            Self::emit_frames(&mut self.pending, self.frame_samples, src, &mut emit);
+            return;
+        };
+
+        while !src.is_empty() {
+            let take = (RESAMPLER_CHUNK_SIZE - self.in_buf.len()).min(src.len());
+            self.in_buf.extend_from_slice(&src[..take]);
+            src = &src[take..];
+
+            if self.in_buf.len() == RESAMPLER_CHUNK_SIZE {
+                match resampler.process(&[&self.in_buf[..]], None) {
+                    Ok(out) => {
+                        Self::emit_frames(&mut self.pending, self.frame_samples, &out[0], &mut emit)
+                    }
+                    Err(e) => log::warn!("Resampler error (dropping chunk): {}", e),
+                }
+                self.in_buf.clear();
+            }
+        }
+    }
+
+    fn emit_frames(
+        pending: &mut Vec<f32>,
+        frame_samples: usize,
+        mut data: &[f32],
+        emit: &mut impl FnMut(&[f32]),
+    ) {
+        while !data.is_empty() {
+            let take = (frame_samples - pending.len()).min(data.len());
+            pending.extend_from_slice(&data[..take]);
+            data = &data[take..];
+
+            if pending.len() == frame_samples {
+                emit(pending);
+                pending.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_device_names() {
+        assert!(is_loopback_device_name(
+            "Monitor of Built-in Audio Analog Stereo"
+        ));
+        assert!(is_loopback_device_name("BlackHole 2ch"));
+        assert!(!is_loopback_device_name("MacBook Pro Microphone"));
+        assert!(!is_loopback_device_name("default"));
+        assert!(!is_loopback_device_name("pulse"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_take_f32le_keeps_partial_sample() {
+        let mut bytes: Vec<u8> = [0.5f32, -1.0]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        bytes.extend_from_slice(&0.25f32.to_le_bytes()[..3]);
+
+        assert_eq!(take_f32le(&mut bytes), vec![0.5, -1.0]);
+        assert_eq!(bytes.len(), 3);
+        bytes.push(0.25f32.to_le_bytes()[3]);
+        assert_eq!(take_f32le(&mut bytes), vec![0.25]);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_frames_have_fixed_size_at_native_rate() {
+        let mut assembler = FrameAssembler::new(16000, Duration::from_millis(30)).unwrap();
+        let mut frames = Vec::new();
+        assembler.push(&vec![0.1; 1000], |f| frames.push(f.len()));
+        assembler.push(&vec![0.1; 500], |f| frames.push(f.len()));
+
+        // 1500 samples -> three full 480-sample frames, 60 samples pending
+        assert_eq!(frames, vec![480, 480, 480]);
+        assert_eq!(assembler.pending.len(), 60);
+    }
+
+    #[test]
+    fn test_resamples_to_capture_rate() {
+        let mut assembler = FrameAssembler::new(48000, Duration::from_millis(10)).unwrap();
+        let mut total = 0;
+        assembler.push(&vec![0.0; 48000], |f| {
+            assert_eq!(f.len(), 160);
+            total += f.len();
+        });
+
+        // One second of 48 kHz input yields close to one second at 16 kHz;
+        // up to one resampler chunk may still be buffered.
+        assert!(total <= 16000);
+        assert!(total >= 16000 - RESAMPLER_CHUNK_SIZE);
+    }
+}
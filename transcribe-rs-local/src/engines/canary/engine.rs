@@ -0,0 +1,185 @@
+use std::path::{Path, PathBuf};
+
+use derive_builder::Builder;
+
+use super::model::{CanaryError, CanaryModel, MAX_SEQUENCE_LENGTH};
+use crate::{vocabulary, TranscriptionEngine, TranscriptionResult};
+
+const SAMPLE_RATE: usize = 16000;
+
+/// Parameters for model loading.
+#[derive(Debug, Clone, Default)]
+pub struct CanaryModelParams {
+    /// Load `*.int8.onnx` weights when present (faster, slightly less accurate).
+    pub quantized: bool,
+}
+
+impl CanaryModelParams {
+    /// Create params for the full precision (FP32) model.
+    pub fn fp32() -> Self {
+        Self { quantized: false }
+    }
+
+    /// Create params for the Int8 quantized model.
+    pub fn int8() -> Self {
+        Self { quantized: true }
+    }
+}
+
+/// Parameters for inference.
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into), default)]
+pub struct CanaryInferenceParams {
+    /// Language spoken in the audio as an ISO 639-1 code. Canary does not
+    /// detect the language itself. Defaults to `"en"`.
+    pub source_language: Option<String>,
+
+    /// Language of the output text. When it differs from the source language
+    /// the speech is translated. Defaults to the source language.
+    pub target_language: Option<String>,
+
+    /// Produce punctuation and capitalization (default `true`).
+    pub punctuation: bool,
+
+    /// Maximum number of tokens to generate.
+    /// If None, the decoder's sequence limit applies.
+    pub max_tokens: Option<usize>,
+
+    /// Domain terms and names whose near-miss spellings are corrected in the
+    /// output.
+    pub vocabulary: Vec<String>,
+}
+
+impl CanaryInferenceParams {
+    pub fn builder() -> CanaryInferenceParamsBuilder {
+        CanaryInferenceParamsBuilder::default()
+    }
+}
+
+impl Default for CanaryInferenceParams {
+    fn default() -> Self {
+        Self {
+            source_language: None,
+            target_language: None,
+            punctuation: true,
+            max_tokens: None,
+            vocabulary: Vec::new(),
+        }
+    }
+}
+
+impl CanaryInferenceParams {
+    /// Create params that translate speech in `source` into `target`.
+    pub fn translate(source: &str, target: &str) -> Self {
+        Self {
+            source_language: Some(source.to_string()),
+            target_language: Some(target.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// NVIDIA Canary transcription and translation engine.
+///
+/// Implements the `TranscriptionEngine` trait for Canary ONNX exports.
+pub struct CanaryEngine {
+    loaded_model_path: Option<PathBuf>,
+    model: Option<CanaryModel>,
+}
+
+impl CanaryEngine {
+    /// Create a new Canary engine (model not loaded).
+    pub fn new() -> Self {
+        Self {
+            loaded_model_path: None,
+            model: None,
+        }
+    }
+
+    /// Languages the loaded model accepts as source or target, as ISO 639-1
+    /// codes. Empty if no model is loaded.
+    pub fn languages(&self) -> Vec<String> {
+        self.model
+            .as_ref()
+            .map(CanaryModel::languages)
+            .unwrap_or_default()
+    }
+}
+
+impl Default for CanaryEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for CanaryEngine {
+    fn drop(&mut self) {
+        self.unload_model();
+    }
+}
+
+impl TranscriptionEngine for CanaryEngine {
+    type InferenceParams = CanaryInferenceParams;
+    type ModelParams = CanaryModelParams;
+
+    fn load_model_with_params(
+        &mut self,
+        model_path: &Path,
+        params: Self::ModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.unload_model();
+
+        self.model = Some(CanaryModel::new(model_path, params.quantized)?);
+        self.loaded_model_path = Some(model_path.to_path_buf());
+
+        log::info!("Loaded Canary model from {:?}", model_path);
+
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        if self.model.is_some() {
+            log::debug!("Unloading Canary model");
+            self.model = None;
+            self.loaded_model_path = None;
+        }
+    }
+
+    fn transcribe_samples(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<Self::InferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let model = self.model.as_mut().ok_or(CanaryError::ModelNotLoaded)?;
+
+        let params = params.unwrap_or_default();
+        let source_language = params.source_language.as_deref().unwrap_or("en");
+        let target_language = params.target_language.as_deref().unwrap_or(source_language);
+        let max_tokens = params.max_tokens.unwrap_or(MAX_SEQUENCE_LENGTH);
+
+        log::debug!(
+            "Transcribing {} samples ({:.2}s), {} -> {}",
+            samples.len(),
+            samples.len() as f32 / SAMPLE_RATE as f32,
+            source_language,
+            target_language
+        );
+
+        let tokens = model.generate(
+            samples,
+            source_language,
+            target_language,
+            params.punctuation,
+            max_tokens,
+        )?;
+
+        let mut result = TranscriptionResult {
+            text: model.decode_tokens(&tokens),
+            segments: None, // Decoded without timestamps
+            language: Some(target_language.to_string()),
+        };
+        vocabulary::apply(&mut result, &params.vocabulary);
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,52 @@
+//! NVIDIA Canary ONNX transcription and translation engine.
+//!
+//! Canary is NVIDIA's multilingual NeMo encoder-decoder model. It shares
+//! Parakeet's audio preprocessing and FastConformer encoder, but decodes with
+//! a transformer that is prompted with the task, so a single model both
+//! transcribes and translates between its supported languages.
+//!
+//! # Model Format
+//!
+//! Expects a directory containing:
+//! - `encoder-model.onnx` (or `encoder-model.int8.onnx`) - FastConformer encoder
+//! - `decoder-model.onnx` (or `decoder-model.int8.onnx`) - Transformer decoder with cache
+//! - `nemo128.onnx` - Audio preprocessor (same as Parakeet)
+//! - `vocab.txt` - SentencePiece vocabulary including the prompt tokens
+//!
+//! # Supported Models
+//!
+//! | Model | Languages |
+//! |-------|-----------|
+//! | canary-1b-v2 | 25 European languages, translation to and from English |
+//! | canary-180m-flash | English, German, Spanish, French |
+//!
+//! # Audio Requirements
+//!
+//! - Sample rate: 16 kHz
+//! - Format: Mono, 16-bit PCM
+//! - Duration: up to 40s per call; use `LongFormTranscriber` for longer audio
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::path::PathBuf;
+//! use transcribe_rs::{TranscriptionEngine, engines::canary::{CanaryEngine, CanaryInferenceParams, CanaryInferenceParamsBuilder, CanaryModelParams}};
+//!
+//! let mut engine = CanaryEngine::new();
+//! engine.load_model_with_params(
+//!     &PathBuf::from("models/canary-1b-v2"),
+//!     CanaryModelParams::int8(),
+//! )?;
+//!
+//! // Transcribe German speech into English text
+//! let params = CanaryInferenceParams::translate("de", "en");
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), Some(params))?;
+//! println!("Translation: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod engine;
+pub mod model;
+
+pub use engine::{CanaryEngine, CanaryInferenceParams, CanaryModelParams};
+pub use model::{CanaryError, CanaryModel};
@@ -0,0 +1,304 @@
+use ndarray::{Array1, Array2, Array4, ArrayD, ArrayViewD};
+use ort::inputs;
+use ort::session::Session;
+use ort::value::{DynValue, TensorRef, Value};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::engines::onnx;
+
+/// Longest decoded sequence, prompt included, supported by the exported decoder.
+pub const MAX_SEQUENCE_LENGTH: usize = 1024;
+
+const EOS_TOKEN: &str = "<|endoftext|>";
+
+#[derive(thiserror::Error, Debug)]
+pub enum CanaryError {
+    #[error("ORT error: {0}")]
+    Ort(#[from] ort::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ndarray shape error: {0}")]
+    Shape(#[from] ndarray::ShapeError),
+    #[error("Model input not found: {0}")]
+    InputNotFound(String),
+    #[error("Model output not found: {0}")]
+    OutputNotFound(String),
+    #[error("Failed to get tensor shape for input: {0}")]
+    TensorShape(String),
+    #[error("Token missing from vocabulary: {0}")]
+    MissingToken(String),
+    #[error("Language not supported by this model: {0}")]
+    UnsupportedLanguage(String),
+    #[error("Model not loaded")]
+    ModelNotLoaded,
+}
+
+/// Canary multitask encoder-decoder model.
+///
+/// The decoder is steered by a prompt of special tokens selecting the source
+/// language, the output language (translation when they differ) and whether
+/// to produce punctuation and capitalization.
+pub struct CanaryModel {
+    encoder: Session,
+    decoder: Session,
+    preprocessor: Session,
+    vocab: Vec<String>,
+    token_ids: HashMap<String, i64>,
+    eos_id: i64,
+}
+
+impl Drop for CanaryModel {
+    fn drop(&mut self) {
+        log::debug!(
+            "Dropping CanaryModel with {} vocab tokens",
+            self.vocab.len()
+        );
+    }
+}
+
+impl CanaryModel {
+    pub fn new<P: AsRef<Path>>(model_dir: P, quantized: bool) -> Result<Self, CanaryError> {
+        let encoder = onnx::init_session(&model_dir, "encoder-model", None, quantized)?;
+        let decoder = onnx::init_session(&model_dir, "decoder-model", None, quantized)?;
+        let preprocessor = onnx::init_session(&model_dir, "nemo128", None, false)?;
+
+        let (vocab, token_ids) = Self::load_vocab(&model_dir)?;
+        let eos_id = *token_ids
+            .get(EOS_TOKEN)
+            .ok_or_else(|| CanaryError::MissingToken(EOS_TOKEN.to_string()))?;
+
+        log::info!(
+            "Loaded vocabulary with {} tokens, eos_id={}",
+            vocab.len(),
+            eos_id
+        );
+
+        Ok(Self {
+            encoder,
+            decoder,
+            preprocessor,
+            vocab,
+            token_ids,
+            eos_id,
+        })
+    }
+
+    fn load_vocab<P: AsRef<Path>>(
+        model_dir: P,
+    ) -> Result<(Vec<String>, HashMap<String, i64>), CanaryError> {
+        let vocab_path = model_dir.as_ref().join("vocab.txt");
+        let content = fs::read_to_string(vocab_path)?;
+
+        let mut max_id = 0;
+        let mut token_ids = HashMap::new();
+
+        for line in content.lines() {
+            let parts: Vec<&str> = line.trim_end().split(' ').collect();
+            if parts.len() >= 2 {
+                if let Ok(id) = parts[1].parse::<usize>() {
+                    token_ids.insert(parts[0].to_string(), id as i64);
+                    max_id = max_id.max(id);
+                }
+            }
+        }
+
+        // Create vocab vector with ▁ replaced with space
+        let mut vocab = vec![String::new(); max_id + 1];
+        for (token, &id) in &token_ids {
+            vocab[id as usize] = token.replace('\u{2581}', " ");
+        }
+
+        Ok((vocab, token_ids))
+    }
+
+    /// Languages with a prompt token in the vocabulary, e.g. `["de", "en", ...]`.
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self
+            .token_ids
+            .keys()
+            .filter_map(|t| t.strip_prefix("<|")?.strip_suffix("|>").map(str::to_string))
+            .filter(|t| t.len() == 2 && t.chars().all(|c| c.is_ascii_lowercase()))
+            .collect();
+        languages.sort();
+        languages
+    }
+
+    fn token_id(&self, token: &str) -> Result<i64, CanaryError> {
+        self.token_ids
+            .get(token)
+            .copied()
+            .ok_or_else(|| CanaryError::MissingToken(token.to_string()))
+    }
+
+    fn language_id(&self, language: &str) -> Result<i64, CanaryError> {
+        self.token_ids
+            .get(&format!("<|{}|>", language))
+            .copied()
+            .ok_or_else(|| CanaryError::UnsupportedLanguage(language.to_string()))
+    }
+
+    /// Build the decoder prompt for the given task.
+    fn prompt(
+        &self,
+        source_language: &str,
+        target_language: &str,
+        punctuation: bool,
+    ) -> Result<Vec<i64>, CanaryError> {
+        Ok(vec![
+            self.token_id("<|startofcontext|>")?,
+            self.token_id("<|startoftranscript|>")?,
+            self.token_id("<|emo:undefined|>")?,
+            self.language_id(source_language)?,
+            self.language_id(target_language)?,
+            self.token_id(if punctuation { "<|pnc|>" } else { "<|nopnc|>" })?,
+            self.token_id("<|noitn|>")?,
+            self.token_id("<|notimestamp|>")?,
+            self.token_id("<|nodiarize|>")?,
+        ])
+    }
+
+    fn preprocess(
+        &mut self,
+        waveforms: &ArrayViewD<f32>,
+        waveforms_lens: &ArrayViewD<i64>,
+    ) -> Result<(ArrayD<f32>, ArrayD<i64>), CanaryError> {
+        log::trace!("Running preprocessor inference...");
+        let inputs = inputs![
+            "waveforms" => TensorRef::from_array_view(waveforms.view())?,
+            "waveforms_lens" => TensorRef::from_array_view(waveforms_lens.view())?,
+        ];
+        let outputs = self.preprocessor.run(inputs)?;
+
+        let features = outputs
+            .get("features")
+            .ok_or_else(|| CanaryError::OutputNotFound("features".to_string()))?
+            .try_extract_array()?;
+        let features_lens = outputs
+            .get("features_lens")
+            .ok_or_else(|| CanaryError::OutputNotFound("features_lens".to_string()))?
+            .try_extract_array()?;
+
+        Ok((features.to_owned(), features_lens.to_owned()))
+    }
+
+    /// Run the encoder, returning its embeddings and mask as produced, ready
+    /// to be passed to every decoder step.
+    fn encode(
+        &mut self,
+        audio_signal: &ArrayViewD<f32>,
+        length: &ArrayViewD<i64>,
+    ) -> Result<(DynValue, DynValue), CanaryError> {
+        log::trace!("Running encoder inference...");
+        let inputs = inputs![
+            "audio_signal" => TensorRef::from_array_view(audio_signal.view())?,
+            "length" => TensorRef::from_array_view(length.view())?,
+        ];
+        let mut outputs = self.encoder.run(inputs)?;
+
+        let embeddings = outputs
+            .remove("encoder_embeddings")
+            .ok_or_else(|| CanaryError::OutputNotFound("encoder_embeddings".to_string()))?;
+        let mask = outputs
+            .remove("encoder_mask")
+            .ok_or_else(|| CanaryError::OutputNotFound("encoder_mask".to_string()))?;
+
+        Ok((embeddings, mask))
+    }
+
+    /// Empty decoder cache of shape `[layers, 1, 0, hidden]`.
+    fn create_decoder_mems(&self) -> Result<DynValue, CanaryError> {
+        let shape = self
+            .decoder
+            .inputs
+            .iter()
+            .find(|input| input.name == "decoder_mems")
+            .ok_or_else(|| CanaryError::InputNotFound("decoder_mems".to_string()))?
+            .input_type
+            .tensor_shape()
+            .ok_or_else(|| CanaryError::TensorShape("decoder_mems".to_string()))?;
+
+        let mems = Array4::<f32>::zeros((shape[0] as usize, 1, 0, shape[3] as usize));
+        Ok(Value::from_array(mems)?.into_dyn())
+    }
+
+    /// Greedily decode `samples` for the given task, returning the generated
+    /// token ids without the prompt.
+    pub fn generate(
+        &mut self,
+        samples: Vec<f32>,
+        source_language: &str,
+        target_language: &str,
+        punctuation: bool,
+        max_tokens: usize,
+    ) -> Result<Vec<i64>, CanaryError> {
+        let prompt = self.prompt(source_language, target_language, punctuation)?;
+
+        let samples_len = samples.len();
+        let waveforms = Array2::from_shape_vec((1, samples_len), samples)?.into_dyn();
+        let waveforms_lens = Array1::from_vec(vec![samples_len as i64]).into_dyn();
+
+        let (features, features_lens) =
+            self.preprocess(&waveforms.view(), &waveforms_lens.view())?;
+        let (embeddings, mask) = self.encode(&features.view(), &features_lens.view())?;
+
+        let mut mems = self.create_decoder_mems()?;
+        let mut tokens = prompt.clone();
+        // Number of tokens already held in the decoder cache
+        let mut cached = 0;
+        let max_len = (prompt.len() + max_tokens).min(MAX_SEQUENCE_LENGTH);
+
+        while tokens.len() < max_len {
+            let new_tokens = &tokens[cached..];
+            let input_ids = Array2::from_shape_vec((1, new_tokens.len()), new_tokens.to_vec())?;
+
+            let mut outputs = self.decoder.run(inputs![
+                "input_ids" => TensorRef::from_array_view(input_ids.view())?,
+                "encoder_embeddings" => &embeddings,
+                "encoder_mask" => &mask,
+                "decoder_mems" => &mems,
+            ])?;
+
+            let logits = outputs
+                .get("logits")
+                .ok_or_else(|| CanaryError::OutputNotFound("logits".to_string()))?
+                .try_extract_array::<f32>()?;
+
+            // Greedy decode: argmax(logits[0, -1, :])
+            let last_pos = logits.shape()[1] - 1;
+            let next_token = logits
+                .slice(ndarray::s![0, last_pos, ..])
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(idx, _)| idx as i64)
+                .unwrap_or(self.eos_id);
+
+            mems = outputs
+                .remove("decoder_hidden_states")
+                .ok_or_else(|| CanaryError::OutputNotFound("decoder_hidden_states".to_string()))?;
+            cached = tokens.len();
+
+            if next_token == self.eos_id {
+                log::trace!("EOS token reached after {} tokens", tokens.len());
+                break;
+            }
+            tokens.push(next_token);
+        }
+
+        Ok(tokens.split_off(prompt.len()))
+    }
+
+    /// Convert token ids to text, dropping special tokens.
+    pub fn decode_tokens(&self, ids: &[i64]) -> String {
+        ids.iter()
+            .filter_map(|&id| self.vocab.get(id as usize))
+            .filter(|token| !(token.starts_with("<|") && token.ends_with("|>")))
+            .map(String::as_str)
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+}
@@ -0,0 +1,154 @@
+//! Greedy CTC decoding shared by the SenseVoice and GigaAM engines.
+
+use ndarray::ArrayView2;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::TranscriptionSegment;
+
+/// SentencePiece's word boundary marker.
+const WORD_START: char = '\u{2581}';
+
+/// A token emitted by the decoder and the output frame it was emitted at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CtcToken {
+    pub id: usize,
+    pub frame: usize,
+}
+
+/// Best path through `logits` (`[frames, vocab]`): the argmax of each frame
+/// with repeats collapsed and blanks removed.
+pub(crate) fn greedy_decode(logits: ArrayView2<f32>, blank: usize) -> Vec<CtcToken> {
+    let mut tokens = Vec::new();
+    let mut previous = blank;
+    for (frame, row) in logits.rows().into_iter().enumerate() {
+        let id = (0..row.len())
+            .max_by(|&a, &b| row[a].total_cmp(&row[b]))
+            .unwrap_or(blank);
+        if id != blank && id != previous {
+            tokens.push(CtcToken { id, frame });
+        }
+        previous = id;
+    }
+    tokens
+}
+
+/// Vocabulary from a `tokens.txt` file of "<token> <id>" lines, indexed by
+/// id.
+pub(crate) fn load_tokens(path: &Path) -> std::io::Result<Vec<String>> {
+    let content = fs::read_to_string(path)?;
+    let ids: HashMap<usize, &str> = content
+        .lines()
+        .filter_map(|line| {
+            let (token, id) = line.trim_end().rsplit_once(' ')?;
+            Some((id.parse().ok()?, token))
+        })
+        .collect();
+    let len = ids.keys().max().map_or(0, |max| max + 1);
+    let mut vocab = vec![String::new(); len];
+    for (id, token) in ids {
+        vocab[id] = token.to_string();
+    }
+    Ok(vocab)
+}
+
+/// Text of `tokens`, dropping special tokens such as `<|en|>` and `<unk>`.
+pub(crate) fn decode_text(tokens: &[CtcToken], vocab: &[String]) -> String {
+    pieces(tokens, vocab)
+        .map(|(piece, _)| piece)
+        .collect::<String>()
+        .replace(WORD_START, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One segment per word of `tokens`, timed by the frames the word's pieces
+/// were emitted at. A word ends where the next one starts, or one frame
+/// after its last piece.
+pub(crate) fn word_segments(
+    tokens: &[CtcToken],
+    vocab: &[String],
+    frame_secs: f32,
+) -> Vec<TranscriptionSegment> {
+    let mut words: Vec<(String, usize, usize)> = Vec::new();
+    for (piece, frame) in pieces(tokens, vocab) {
+        match words.last_mut() {
+            Some((text, _, end)) if !piece.starts_with([WORD_START, ' ']) => {
+                text.push_str(piece);
+                *end = frame;
+            }
+            _ => words.push((piece.to_string(), frame, frame)),
+        }
+    }
+
+    let starts: Vec<usize> = words.iter().map(|&(_, start, _)| start).collect();
+    words
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (text, start, last))| {
+            let text = text.replace(WORD_START, " ").trim().to_string();
+            let end = starts.get(i + 1).copied().unwrap_or(last + 1);
+            (!text.is_empty()).then_some(TranscriptionSegment {
+                start: start as f32 * frame_secs,
+                end: end as f32 * frame_secs,
+                text,
+            })
+        })
+        .collect()
+}
+
+fn pieces<'a>(
+    tokens: &'a [CtcToken],
+    vocab: &'a [String],
+) -> impl Iterator<Item = (&'a str, usize)> + 'a {
+    tokens.iter().filter_map(|token| {
+        let piece = vocab.get(token.id)?.as_str();
+        let special = piece.starts_with('<') && piece.ends_with('>');
+        (!special).then_some((piece, token.frame))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array2;
+
+    fn vocab() -> Vec<String> {
+        ["<blank>", "<|en|>", "\u{2581}he", "llo", "\u{2581}world"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn token(id: usize, frame: usize) -> CtcToken {
+        CtcToken { id, frame }
+    }
+
+    #[test]
+    fn test_greedy_decode() {
+        // Frames pick: 2, 2, blank, 2, 3, 3
+        let picks = [2, 2, 0, 2, 3, 3];
+        let mut logits = Array2::<f32>::zeros((picks.len(), 5));
+        for (frame, &id) in picks.iter().enumerate() {
+            logits[[frame, id]] = 1.0;
+        }
+        assert_eq!(
+            greedy_decode(logits.view(), 0),
+            [token(2, 0), token(2, 3), token(3, 4)]
+        );
+    }
+
+    #[test]
+    fn test_decode_text_and_words() {
+        let tokens = [token(1, 0), token(2, 1), token(3, 2), token(4, 5)];
+        assert_eq!(decode_text(&tokens, &vocab()), "hello world");
+
+        let words = word_segments(&tokens, &vocab(), 0.5);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "hello");
+        assert_eq!((words[0].start, words[0].end), (0.5, 2.5));
+        assert_eq!(words[1].text, "world");
+        assert_eq!((words[1].start, words[1].end), (2.5, 3.0));
+    }
+}
@@ -0,0 +1,673 @@
+//! faster-whisper (CTranslate2) speech recognition engine implementation.
+//!
+//! faster-whisper runs Whisper models on CTranslate2, which is often several
+//! times faster than whisper.cpp on GPUs. It runs as a separate server, and
+//! this engine speaks the two protocols such servers commonly expose:
+//!
+//! - [`FasterWhisperProtocol::Wyoming`], served by `wyoming-faster-whisper`.
+//!   The engine can spawn and manage this server itself, like the
+//!   whisperfile engine does.
+//! - [`FasterWhisperProtocol::OpenAI`], the `/v1/audio/transcriptions` API
+//!   of OpenAI-compatible servers such as speaches (formerly
+//!   faster-whisper-server).
+//!
+//! The "model path" passed to `load_model()` is handed to the server as is,
+//! so it can be a local CTranslate2 model directory or a model name the
+//! server knows how to download (e.g. `large-v3-turbo`,
+//! `Systran/faster-whisper-small`).
+//!
+//! # Examples
+//!
+//! ## Managed Wyoming Server
+//!
+//! ```rust,no_run
+//! use transcribe_rs::{TranscriptionEngine, engines::faster_whisper::FasterWhisperEngine};
+//! use std::path::{Path, PathBuf};
+//!
+//! let mut engine = FasterWhisperEngine::new(PathBuf::from("wyoming-faster-whisper"));
+//! engine.load_model(Path::new("small-int8"))?;
+//!
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), None)?;
+//! println!("Transcription: {}", result.text);
+//!
+//! // Server is automatically stopped when engine is dropped
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Existing OpenAI-compatible Server
+//!
+//! ```rust,no_run
+//! use transcribe_rs::{
+//!     TranscriptionEngine,
+//!     engines::faster_whisper::{FasterWhisperEngine, FasterWhisperProtocol},
+//! };
+//! use std::path::{Path, PathBuf};
+//!
+//! let mut engine =
+//!     FasterWhisperEngine::connect(FasterWhisperProtocol::OpenAI, "http://localhost:8000");
+//! engine.load_model(Path::new("Systran/faster-whisper-small"))?;
+//!
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), None)?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::engines::http::{self, MultipartForm, WavFormat};
+use crate::limits::ResourceLimits;
+use crate::{vocabulary, TranscriptionEngine, TranscriptionResult, TranscriptionSegment};
+use derive_builder::Builder;
+use log::{debug, error, info, trace, warn};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use ureq::Agent;
+
+/// Samples per Wyoming `audio-chunk` event.
+const WYOMING_CHUNK_SAMPLES: usize = 1024;
+
+/// Protocol spoken by the faster-whisper server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FasterWhisperProtocol {
+    /// Wyoming protocol over TCP (`wyoming-faster-whisper`, default)
+    #[default]
+    Wyoming,
+    /// OpenAI-compatible HTTP API (`/v1/audio/transcriptions`)
+    OpenAI,
+}
+
+/// Parameters for configuring faster-whisper model loading.
+///
+/// Server options only apply when the engine manages the server process.
+#[derive(Debug, Clone)]
+pub struct FasterWhisperModelParams {
+    /// Port for the managed server (default: 10300)
+    pub port: u16,
+    /// Host to bind the managed server to (default: "127.0.0.1")
+    pub host: String,
+    /// Timeout in seconds to wait for the server to become ready (default: 120).
+    /// The first start may download the model.
+    pub startup_timeout_secs: u64,
+    /// Device to run on, e.g. "cuda" or "cpu" (server default if None)
+    pub device: Option<String>,
+    /// CTranslate2 compute type, e.g. "float16" or "int8" (server default if None)
+    pub compute_type: Option<String>,
+    /// Beam size used for decoding (server default if None)
+    pub beam_size: Option<u32>,
+    /// Directory for downloaded models (defaults to the model's parent
+    /// directory, or the system temp directory for model names)
+    pub data_dir: Option<PathBuf>,
+    /// Priority, CPU affinity and memory limit of the server process
+    pub limits: ResourceLimits,
+}
+
+impl Default for FasterWhisperModelParams {
+    fn default() -> Self {
+        Self {
+            port: 10300,
+            host: "127.0.0.1".to_string(),
+            startup_timeout_secs: 120,
+            device: None,
+            compute_type: None,
+            beam_size: None,
+            data_dir: None,
+            limits: ResourceLimits::default(),
+        }
+    }
+}
+
+/// Parameters for configuring faster-whisper inference behavior.
+#[derive(Builder, Debug, Clone, Default)]
+#[builder(setter(into), default)]
+pub struct FasterWhisperInferenceParams {
+    /// Target language for transcription (e.g., "en", "es", "fr").
+    /// If None, the server will auto-detect the language.
+    pub language: Option<String>,
+
+    /// Temperature for sampling (0.0 = greedy). OpenAI protocol only.
+    pub temperature: Option<f32>,
+
+    /// Domain terms and names to favour. They are sent as a prompt over the
+    /// OpenAI protocol and near-miss spellings are corrected in the output.
+    pub vocabulary: Vec<String>,
+}
+
+impl FasterWhisperInferenceParams {
+    pub fn builder() -> FasterWhisperInferenceParamsBuilder {
+        FasterWhisperInferenceParamsBuilder::default()
+    }
+}
+
+/// JSON output from an OpenAI-compatible server (verbose_json format)
+#[derive(Deserialize)]
+struct OpenAIOutput {
+    text: String,
+    #[serde(default)]
+    segments: Vec<OpenAISegment>,
+    #[serde(default)]
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OpenAISegment {
+    text: String,
+    start: f32,
+    end: f32,
+}
+
+impl From<OpenAIOutput> for TranscriptionResult {
+    fn from(output: OpenAIOutput) -> Self {
+        let segments = if output.segments.is_empty() {
+            None
+        } else {
+            Some(
+                output
+                    .segments
+                    .into_iter()
+                    .map(|s| TranscriptionSegment {
+                        start: s.start,
+                        end: s.end,
+                        text: s.text,
+                    })
+                    .collect(),
+            )
+        };
+
+        TranscriptionResult {
+            text: output.text.trim().to_string(),
+            segments,
+            language: http::language_code(output.language),
+        }
+    }
+}
+
+/// Header line of a Wyoming event.
+#[derive(Deserialize)]
+struct WyomingHeader {
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    data: Option<Map<String, Value>>,
+    #[serde(default)]
+    data_length: Option<usize>,
+    #[serde(default)]
+    payload_length: Option<usize>,
+}
+
+/// faster-whisper speech recognition engine.
+///
+/// Create it with [`FasterWhisperEngine::new`] to have the engine spawn a
+/// `wyoming-faster-whisper` server on `load_model()` and stop it on unload,
+/// or with [`FasterWhisperEngine::connect`] to use a server that is already
+/// running.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use transcribe_rs::engines::faster_whisper::FasterWhisperEngine;
+/// use std::path::PathBuf;
+///
+/// let mut engine = FasterWhisperEngine::new(PathBuf::from("wyoming-faster-whisper"));
+/// ```
+pub struct FasterWhisperEngine {
+    /// Server binary, when the engine manages the server process
+    binary_path: Option<PathBuf>,
+    protocol: FasterWhisperProtocol,
+    /// `host:port` for Wyoming, base URL for OpenAI
+    address: String,
+    agent: Agent,
+    model: Option<String>,
+    server_process: Option<Child>,
+    /// Flag to signal the log reader thread to stop
+    log_shutdown: Arc<AtomicBool>,
+    /// Handle to the log reader thread
+    log_thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl FasterWhisperEngine {
+    /// Create an engine that manages a `wyoming-faster-whisper` server.
+    ///
+    /// # Arguments
+    ///
+    /// * `binary_path` - Path to the `wyoming-faster-whisper` executable
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use transcribe_rs::engines::faster_whisper::FasterWhisperEngine;
+    /// use std::path::PathBuf;
+    ///
+    /// let engine = FasterWhisperEngine::new(PathBuf::from("/usr/local/bin/wyoming-faster-whisper"));
+    /// ```
+    pub fn new(binary_path: impl Into<PathBuf>) -> Self {
+        Self {
+            binary_path: Some(binary_path.into()),
+            protocol: FasterWhisperProtocol::Wyoming,
+            address: String::new(),
+            agent: Agent::new_with_defaults(),
+            model: None,
+            server_process: None,
+            log_shutdown: Arc::new(AtomicBool::new(false)),
+            log_thread: None,
+        }
+    }
+
+    /// Create an engine for a server that is already running.
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol` - Protocol the server speaks
+    /// * `url` - `host:port` (or `tcp://host:port`) for Wyoming, or the base
+    ///   URL (e.g. `http://localhost:8000`) for OpenAI-compatible servers
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use transcribe_rs::engines::faster_whisper::{FasterWhisperEngine, FasterWhisperProtocol};
+    ///
+    /// let engine = FasterWhisperEngine::connect(FasterWhisperProtocol::Wyoming, "tcp://127.0.0.1:10300");
+    /// ```
+    pub fn connect(protocol: FasterWhisperProtocol, url: &str) -> Self {
+        let address = match protocol {
+            FasterWhisperProtocol::Wyoming => url.trim_start_matches("tcp://").to_string(),
+            FasterWhisperProtocol::OpenAI => url
+                .trim_end_matches('/')
+                .trim_end_matches("/v1")
+                .to_string(),
+        };
+        Self {
+            binary_path: None,
+            protocol,
+            address,
+            agent: Agent::new_with_defaults(),
+            model: None,
+            server_process: None,
+            log_shutdown: Arc::new(AtomicBool::new(false)),
+            log_thread: None,
+        }
+    }
+
+    /// Spawn the managed server process and start forwarding its logs
+    fn spawn_server(
+        &mut self,
+        binary_path: &Path,
+        model: &str,
+        model_path: &Path,
+        params: &FasterWhisperModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let data_dir = params
+            .data_dir
+            .clone()
+            .or_else(|| {
+                model_path
+                    .exists()
+                    .then(|| model_path.parent().map(Path::to_path_buf))
+                    .flatten()
+                    .filter(|p| !p.as_os_str().is_empty())
+            })
+            .unwrap_or_else(std::env::temp_dir);
+
+        self.address = format!("{}:{}", params.host, params.port);
+
+        info!(
+            "Starting faster-whisper server: binary={}, model={}, address={}, device={:?}",
+            binary_path.display(),
+            model,
+            self.address,
+            params.device
+        );
+
+        let mut command = Command::new(binary_path);
+        command
+            .arg("--model")
+            .arg(model)
+            .arg("--uri")
+            .arg(format!("tcp://{}", self.address))
+            .arg("--data-dir")
+            .arg(&data_dir);
+        if let Some(device) = &params.device {
+            command.arg("--device").arg(device);
+        }
+        if let Some(compute_type) = &params.compute_type {
+            command.arg("--compute-type").arg(compute_type);
+        }
+        if let Some(beam_size) = params.beam_size {
+            command.arg("--beam-size").arg(beam_size.to_string());
+        }
+        params.limits.apply(&mut command);
+
+        let mut child = command
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                error!("Failed to spawn faster-whisper server: {}", e);
+                format!("Failed to spawn faster-whisper server: {}", e)
+            })?;
+
+        debug!(
+            "faster-whisper server process spawned (pid: {:?})",
+            child.id()
+        );
+        if let Err(e) = params.limits.attach(&child) {
+            error!("Failed to limit faster-whisper server resources: {}", e);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("Failed to limit faster-whisper server resources: {}", e).into());
+        }
+
+        // Reset shutdown flag and spawn a thread to read server logs
+        self.log_shutdown.store(false, Ordering::SeqCst);
+
+        if let Some(stderr) = child.stderr.take() {
+            let shutdown_flag = Arc::clone(&self.log_shutdown);
+            let log_thread = std::thread::spawn(move || {
+                let reader = BufReader::new(stderr);
+                for line in reader.lines() {
+                    if shutdown_flag.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    match line {
+                        Ok(line) => {
+                            debug!("[faster-whisper] {}", line);
+                        }
+                        Err(e) => {
+                            trace!("Error reading faster-whisper stderr: {}", e);
+                            break;
+                        }
+                    }
+                }
+                trace!("faster-whisper log reader thread exiting");
+            });
+            self.log_thread = Some(log_thread);
+        }
+
+        self.server_process = Some(child);
+        Ok(())
+    }
+
+    /// Wait for the server to accept connections
+    fn wait_for_server(&mut self, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        let start = Instant::now();
+
+        debug!(
+            "Waiting for faster-whisper server at {} (timeout: {}s)",
+            self.address,
+            timeout.as_secs()
+        );
+
+        while start.elapsed() < timeout {
+            if let Some(child) = self.server_process.as_mut() {
+                if let Some(status) = child.try_wait()? {
+                    error!("faster-whisper server exited during startup: {}", status);
+                    return Err(
+                        format!("faster-whisper server exited during startup: {}", status).into(),
+                    );
+                }
+            }
+
+            let ready = match self.protocol {
+                FasterWhisperProtocol::Wyoming => TcpStream::connect(&self.address).is_ok(),
+                // Any HTTP response means the server is up, even if it has no /health route
+                FasterWhisperProtocol::OpenAI => matches!(
+                    self.agent.get(format!("{}/health", self.address)).call(),
+                    Ok(_) | Err(ureq::Error::StatusCode(_))
+                ),
+            };
+            if ready {
+                info!(
+                    "faster-whisper server ready after {:.2}s",
+                    start.elapsed().as_secs_f32()
+                );
+                return Ok(());
+            }
+
+            trace!(
+                "Polling faster-whisper server... ({:.1}s elapsed)",
+                start.elapsed().as_secs_f32()
+            );
+            std::thread::sleep(Duration::from_millis(250));
+        }
+
+        error!(
+            "faster-whisper server not reachable within {} seconds",
+            timeout.as_secs()
+        );
+        Err(format!(
+            "faster-whisper server not reachable within {} seconds",
+            timeout.as_secs()
+        )
+        .into())
+    }
+}
+
+impl Drop for FasterWhisperEngine {
+    fn drop(&mut self) {
+        self.unload_model();
+    }
+}
+
+impl TranscriptionEngine for FasterWhisperEngine {
+    type InferenceParams = FasterWhisperInferenceParams;
+    type ModelParams = FasterWhisperModelParams;
+
+    fn load_model_with_params(
+        &mut self,
+        model_path: &Path,
+        params: Self::ModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Stop any existing server
+        self.unload_model();
+
+        let model = model_path.to_string_lossy().into_owned();
+
+        if let Some(binary_path) = self.binary_path.clone() {
+            self.spawn_server(&binary_path, &model, model_path, &params)?;
+        }
+
+        if let Err(e) = self.wait_for_server(Duration::from_secs(params.startup_timeout_secs)) {
+            self.unload_model();
+            return Err(e);
+        }
+
+        self.model = Some(model);
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        // Signal the log reader thread to stop
+        self.log_shutdown.store(true, Ordering::SeqCst);
+
+        if let Some(mut child) = self.server_process.take() {
+            debug!("Stopping faster-whisper server (pid: {:?})", child.id());
+            let _ = child.kill();
+            let _ = child.wait();
+            info!("faster-whisper server stopped");
+        }
+
+        // Wait for the log thread to finish
+        if let Some(thread) = self.log_thread.take() {
+            trace!("Waiting for log reader thread to finish");
+            let _ = thread.join();
+        }
+
+        self.model = None;
+    }
+
+    fn transcribe_samples(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<Self::InferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let model = match &self.model {
+            Some(model) => model,
+            None => {
+                warn!("Attempted to transcribe samples without loading model");
+                return Err("Model not loaded. Call load_model() first.".into());
+            }
+        };
+        let params = params.unwrap_or_default();
+
+        debug!(
+            "Transcribing {} samples with faster-whisper ({:?})",
+            samples.len(),
+            self.protocol
+        );
+
+        let start = Instant::now();
+        let mut result = match self.protocol {
+            FasterWhisperProtocol::Wyoming => transcribe_wyoming(&self.address, &samples, &params)?,
+            FasterWhisperProtocol::OpenAI => {
+                transcribe_openai(&self.agent, &self.address, model, &samples, &params)?
+            }
+        };
+        vocabulary::apply(&mut result, &params.vocabulary);
+
+        debug!(
+            "Transcription completed in {:.2}s ({} chars)",
+            start.elapsed().as_secs_f32(),
+            result.text.len()
+        );
+        trace!("Transcription result: {:?}", result.text);
+
+        Ok(result)
+    }
+}
+
+fn transcribe_openai(
+    agent: &Agent,
+    base_url: &str,
+    model: &str,
+    samples: &[f32],
+    params: &FasterWhisperInferenceParams,
+) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+    let mut form = MultipartForm::new()
+        .wav("file", "audio.wav", samples, WavFormat::Int16)
+        .text("model", model)
+        .text("response_format", "verbose_json");
+
+    if let Some(lang) = &params.language {
+        form = form.text("language", lang);
+    }
+
+    if let Some(temp) = params.temperature {
+        form = form.text("temperature", &temp.to_string());
+    }
+
+    if let Some(prompt) = vocabulary::vocabulary_prompt(&params.vocabulary, None) {
+        form = form.text("prompt", &prompt);
+    }
+
+    let (content_type, body) = form.build();
+
+    let url = format!("{}/v1/audio/transcriptions", base_url);
+    debug!("Sending transcription request to {}", url);
+
+    let response = agent
+        .post(&url)
+        .content_type(&content_type)
+        .send(&body[..])
+        .map_err(|e| {
+            error!("Request to faster-whisper server failed: {}", e);
+            format!("Request to faster-whisper server failed: {}", e)
+        })?;
+
+    let json_response = response.into_body().read_to_string()?;
+    let output: OpenAIOutput = serde_json::from_str(&json_response)?;
+    Ok(output.into())
+}
+
+fn transcribe_wyoming(
+    address: &str,
+    samples: &[f32],
+    params: &FasterWhisperInferenceParams,
+) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+    let stream = TcpStream::connect(address).map_err(|e| {
+        error!("Connection to faster-whisper server failed: {}", e);
+        format!("Connection to faster-whisper server failed: {}", e)
+    })?;
+    let mut writer = BufWriter::new(stream.try_clone()?);
+    let mut reader = BufReader::new(stream);
+
+    let audio_format = json!({ "rate": 16000, "width": 2, "channels": 1 });
+    let mut transcribe = json!({});
+    if let Some(lang) = &params.language {
+        transcribe["language"] = json!(lang);
+    }
+
+    write_wyoming_event(&mut writer, "transcribe", transcribe, &[])?;
+    write_wyoming_event(&mut writer, "audio-start", audio_format.clone(), &[])?;
+    for chunk in samples.chunks(WYOMING_CHUNK_SAMPLES) {
+        let payload: Vec<u8> = chunk
+            .iter()
+            .flat_map(|s| ((s * i16::MAX as f32) as i16).to_le_bytes())
+            .collect();
+        write_wyoming_event(&mut writer, "audio-chunk", audio_format.clone(), &payload)?;
+    }
+    write_wyoming_event(&mut writer, "audio-stop", json!({}), &[])?;
+    writer.flush()?;
+
+    // Skip any streaming events until the final transcript arrives
+    loop {
+        let (event_type, data) = read_wyoming_event(&mut reader)?;
+        trace!("Received Wyoming event: {}", event_type);
+        if event_type == "transcript" {
+            let text = data.get("text").and_then(Value::as_str).unwrap_or_default();
+            let language = data
+                .get("language")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            return Ok(TranscriptionResult {
+                text: text.trim().to_string(),
+                segments: None,
+                language: http::language_code(language),
+            });
+        }
+    }
+}
+
+/// Write a Wyoming event: a JSON header line followed by the binary payload.
+fn write_wyoming_event(
+    writer: &mut impl Write,
+    event_type: &str,
+    data: Value,
+    payload: &[u8],
+) -> std::io::Result<()> {
+    let mut header = json!({ "type": event_type, "data": data });
+    if !payload.is_empty() {
+        header["payload_length"] = json!(payload.len());
+    }
+    serde_json::to_writer(&mut *writer, &header)?;
+    writer.write_all(b"\n")?;
+    writer.write_all(payload)
+}
+
+/// Read a Wyoming event, returning its type and data. Payloads are discarded.
+fn read_wyoming_event(
+    reader: &mut impl BufRead,
+) -> Result<(String, Map<String, Value>), Box<dyn std::error::Error>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err("faster-whisper server closed the connection".into());
+    }
+    let header: WyomingHeader = serde_json::from_str(&line)?;
+    let mut data = header.data.unwrap_or_default();
+
+    // Newer servers send the data as a separate JSON block after the header
+    if let Some(len) = header.data_length.filter(|&len| len > 0) {
+        let mut buf = vec![0; len];
+        reader.read_exact(&mut buf)?;
+        let extra: Map<String, Value> = serde_json::from_slice(&buf)?;
+        data.extend(extra);
+    }
+    if let Some(len) = header.payload_length.filter(|&len| len > 0) {
+        std::io::copy(&mut reader.take(len as u64), &mut std::io::sink())?;
+    }
+
+    Ok((header.event_type, data))
+}
@@ -0,0 +1,306 @@
+//! Log mel filterbank features for the CTC engines (SenseVoice and GigaAM),
+//! whose ONNX exports take features rather than raw audio.
+//!
+//! Two flavours are implemented, each matching what its models were trained
+//! on: Kaldi's `compute-fbank-feats` (SenseVoice) and torchaudio's
+//! `MelSpectrogram` followed by a log (GigaAM).
+
+use ndarray::Array2;
+use rustfft::num_complex::Complex32;
+use rustfft::FftPlanner;
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: f32 = 16000.0;
+
+/// Kaldi fbank options as used by FunASR models; the Kaldi defaults
+/// otherwise.
+#[cfg(feature = "sense_voice")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct KaldiFbank {
+    pub num_mel_bins: usize,
+    /// Frame length in samples (25 ms)
+    pub frame_length: usize,
+    /// Frame shift in samples (10 ms)
+    pub frame_shift: usize,
+    /// Lower edge of the first mel bin in Hz
+    pub low_freq: f32,
+}
+
+#[cfg(feature = "sense_voice")]
+impl Default for KaldiFbank {
+    fn default() -> Self {
+        Self {
+            num_mel_bins: 80,
+            frame_length: 400,
+            frame_shift: 160,
+            low_freq: 20.0,
+        }
+    }
+}
+
+#[cfg(feature = "sense_voice")]
+impl KaldiFbank {
+    /// Features of `samples` (16 kHz, at 16-bit scale) as `[frames, bins]`.
+    ///
+    /// Frames that don't fit entirely are dropped (`snip_edges`), so audio
+    /// shorter than one frame has no features.
+    pub fn compute(&self, samples: &[f32]) -> Array2<f32> {
+        let num_frames = if samples.len() < self.frame_length {
+            0
+        } else {
+            1 + (samples.len() - self.frame_length) / self.frame_shift
+        };
+        let n_fft = self.frame_length.next_power_of_two();
+        let banks = kaldi_mel_banks(self.num_mel_bins, n_fft, self.low_freq);
+        let window: Vec<f32> = (0..self.frame_length)
+            .map(|i| 0.54 - 0.46 * (2.0 * PI * i as f32 / (self.frame_length - 1) as f32).cos())
+            .collect();
+        let fft = FftPlanner::<f32>::new().plan_fft_forward(n_fft);
+
+        let mut features = Array2::zeros((num_frames, self.num_mel_bins));
+        let mut frame = vec![0.0f32; self.frame_length];
+        let mut buffer = vec![Complex32::default(); n_fft];
+        for (index, mut row) in features.rows_mut().into_iter().enumerate() {
+            let start = index * self.frame_shift;
+            frame.copy_from_slice(&samples[start..start + self.frame_length]);
+
+            // Remove the DC offset, then pre-emphasize
+            let mean = frame.iter().sum::<f32>() / frame.len() as f32;
+            frame.iter_mut().for_each(|sample| *sample -= mean);
+            for i in (1..frame.len()).rev() {
+                frame[i] -= 0.97 * frame[i - 1];
+            }
+            frame[0] -= 0.97 * frame[0];
+
+            buffer.fill(Complex32::default());
+            for ((slot, sample), weight) in buffer.iter_mut().zip(&frame).zip(&window) {
+                slot.re = sample * weight;
+            }
+            fft.process(&mut buffer);
+
+            let power: Vec<f32> = buffer[..n_fft / 2].iter().map(|c| c.norm_sqr()).collect();
+            for (value, bank) in row.iter_mut().zip(&banks) {
+                let energy: f32 = bank.iter().zip(&power).map(|(w, p)| w * p).sum();
+                *value = energy.max(f32::EPSILON).ln();
+            }
+        }
+        features
+    }
+}
+
+/// Kaldi's triangular mel filters over the first `n_fft / 2` FFT bins, with
+/// the upper edge at the Nyquist frequency.
+#[cfg(feature = "sense_voice")]
+fn kaldi_mel_banks(num_bins: usize, n_fft: usize, low_freq: f32) -> Vec<Vec<f32>> {
+    let mel = |freq: f32| 1127.0 * (1.0 + freq / 700.0).ln();
+    let bin_width = SAMPLE_RATE / n_fft as f32;
+    let mel_low = mel(low_freq);
+    let mel_delta = (mel(SAMPLE_RATE / 2.0) - mel_low) / (num_bins + 1) as f32;
+
+    (0..num_bins)
+        .map(|bin| {
+            let left = mel_low + bin as f32 * mel_delta;
+            let center = left + mel_delta;
+            let right = center + mel_delta;
+            (0..n_fft / 2)
+                .map(|i| {
+                    let m = mel(bin_width * i as f32);
+                    if m <= left || m >= right {
+                        0.0
+                    } else if m <= center {
+                        (m - left) / (center - left)
+                    } else {
+                        (right - m) / (right - center)
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// torchaudio `MelSpectrogram` options (HTK mel scale, no filter
+/// normalization, periodic Hann window, power spectrum).
+#[cfg(feature = "gigaam")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LogMel {
+    pub n_mels: usize,
+    pub n_fft: usize,
+    pub hop_length: usize,
+    /// Reflect-pad by `n_fft / 2` on both sides, as `torch.stft` does with
+    /// `center=True`
+    pub center: bool,
+}
+
+#[cfg(feature = "gigaam")]
+impl LogMel {
+    /// `ln(clamp(mel, 1e-9, 1e9))` of `samples` (16 kHz, in [-1, 1]) as
+    /// `[frames, n_mels]`.
+    pub fn compute(&self, samples: &[f32]) -> Array2<f32> {
+        let padded;
+        let samples = if self.center {
+            padded = reflect_pad(samples, self.n_fft / 2);
+            &padded[..]
+        } else {
+            samples
+        };
+        let num_frames = if samples.len() < self.n_fft {
+            0
+        } else {
+            1 + (samples.len() - self.n_fft) / self.hop_length
+        };
+        let banks = htk_mel_banks(self.n_mels, self.n_fft);
+        let window: Vec<f32> = (0..self.n_fft)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / self.n_fft as f32).cos())
+            .collect();
+        let fft = FftPlanner::<f32>::new().plan_fft_forward(self.n_fft);
+
+        let mut features = Array2::zeros((num_frames, self.n_mels));
+        let mut buffer = vec![Complex32::default(); self.n_fft];
+        for (index, mut row) in features.rows_mut().into_iter().enumerate() {
+            let frame = &samples[index * self.hop_length..][..self.n_fft];
+            for ((slot, sample), weight) in buffer.iter_mut().zip(frame).zip(&window) {
+                *slot = Complex32::new(sample * weight, 0.0);
+            }
+            fft.process(&mut buffer);
+
+            let power: Vec<f32> = buffer[..=self.n_fft / 2]
+                .iter()
+                .map(|c| c.norm_sqr())
+                .collect();
+            for (value, bank) in row.iter_mut().zip(&banks) {
+                let energy: f32 = bank.iter().zip(&power).map(|(w, p)| w * p).sum();
+                *value = energy.clamp(1e-9, 1e9).ln();
+            }
+        }
+        features
+    }
+}
+
+/// torchaudio's `melscale_fbanks` from 0 Hz to Nyquist with the HTK scale,
+/// one filter per mel over the `n_fft / 2 + 1` FFT bins.
+#[cfg(feature = "gigaam")]
+fn htk_mel_banks(n_mels: usize, n_fft: usize) -> Vec<Vec<f32>> {
+    let mel = |freq: f32| 2595.0 * (1.0 + freq / 700.0).log10();
+    let hz = |mel: f32| 700.0 * (10f32.powf(mel / 2595.0) - 1.0);
+    let n_freqs = n_fft / 2 + 1;
+    let nyquist = SAMPLE_RATE / 2.0;
+    let mel_max = mel(nyquist);
+    let points: Vec<f32> = (0..n_mels + 2)
+        .map(|i| hz(mel_max * i as f32 / (n_mels + 1) as f32))
+        .collect();
+
+    (0..n_mels)
+        .map(|m| {
+            let (left, center, right) = (points[m], points[m + 1], points[m + 2]);
+            (0..n_freqs)
+                .map(|i| {
+                    let freq = nyquist * i as f32 / (n_freqs - 1) as f32;
+                    let down = (freq - left) / (center - left);
+                    let up = (right - freq) / (right - center);
+                    down.min(up).max(0.0)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `samples` mirrored by `pad` at both ends, excluding the edge sample.
+#[cfg(feature = "gigaam")]
+fn reflect_pad(samples: &[f32], pad: usize) -> Vec<f32> {
+    if samples.len() <= pad {
+        // Too short to mirror; zero padding keeps the frame count right
+        let mut padded = vec![0.0; pad];
+        padded.extend_from_slice(samples);
+        padded.resize(samples.len() + 2 * pad, 0.0);
+        return padded;
+    }
+    let mut padded = Vec::with_capacity(samples.len() + 2 * pad);
+    padded.extend(samples[1..=pad].iter().rev());
+    padded.extend_from_slice(samples);
+    padded.extend(
+        samples[samples.len() - 1 - pad..samples.len() - 1]
+            .iter()
+            .rev(),
+    );
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(freq: f32, seconds: f32) -> Vec<f32> {
+        (0..(SAMPLE_RATE * seconds) as usize)
+            .map(|i| (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+            .collect()
+    }
+
+    fn loudest_bin(features: &Array2<f32>) -> usize {
+        let row = features.row(features.nrows() / 2);
+        (0..row.len())
+            .max_by(|&a, &b| row[a].total_cmp(&row[b]))
+            .unwrap()
+    }
+
+    #[cfg(feature = "sense_voice")]
+    #[test]
+    fn test_kaldi_fbank_frames() {
+        let fbank = KaldiFbank::default();
+        assert_eq!(fbank.compute(&[0.0; 399]).nrows(), 0);
+        assert_eq!(fbank.compute(&[0.0; 400]).nrows(), 1);
+        assert_eq!(fbank.compute(&tone(440.0, 1.0)).dim(), (98, 80));
+    }
+
+    #[cfg(feature = "gigaam")]
+    #[test]
+    fn test_log_mel_frames() {
+        let centered = LogMel {
+            n_mels: 64,
+            n_fft: 400,
+            hop_length: 160,
+            center: true,
+        };
+        assert_eq!(centered.compute(&tone(440.0, 1.0)).dim(), (101, 64));
+        assert_eq!(centered.compute(&[0.1; 10]).nrows(), 1);
+
+        let snipped = LogMel {
+            center: false,
+            n_fft: 320,
+            ..centered
+        };
+        assert_eq!(snipped.compute(&tone(440.0, 1.0)).dim(), (99, 64));
+    }
+
+    #[cfg(feature = "sense_voice")]
+    #[test]
+    fn test_kaldi_fbank_tones() {
+        let fbank = KaldiFbank::default();
+        let low = loudest_bin(&fbank.compute(&tone(300.0, 0.5)));
+        let high = loudest_bin(&fbank.compute(&tone(3000.0, 0.5)));
+        assert!(low < high, "300 Hz in bin {low}, 3 kHz in bin {high}");
+    }
+
+    #[cfg(feature = "gigaam")]
+    #[test]
+    fn test_log_mel_tones() {
+        let log_mel = LogMel {
+            n_mels: 64,
+            n_fft: 320,
+            hop_length: 160,
+            center: false,
+        };
+        let low = loudest_bin(&log_mel.compute(&tone(300.0, 0.5)));
+        let high = loudest_bin(&log_mel.compute(&tone(3000.0, 0.5)));
+        assert!(low < high, "300 Hz in bin {low}, 3 kHz in bin {high}");
+    }
+
+    #[cfg(feature = "gigaam")]
+    #[test]
+    fn test_reflect_pad() {
+        assert_eq!(
+            reflect_pad(&[1.0, 2.0, 3.0, 4.0], 2),
+            [3.0, 2.0, 1.0, 2.0, 3.0, 4.0, 3.0, 2.0]
+        );
+        assert_eq!(reflect_pad(&[1.0], 2), [0.0, 0.0, 1.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+
+use super::model::{GigaAMError, GigaAMModel};
+use crate::engines::ctc;
+use crate::{vocabulary, TranscriptionEngine, TranscriptionResult};
+
+const SAMPLE_RATE: usize = 16000;
+
+/// Parameters for model loading.
+#[derive(Debug, Clone, Default)]
+pub struct GigaAMModelParams {
+    /// Load `model.int8.onnx` when the model is a directory holding it.
+    /// A path to an `.onnx` file is loaded as is.
+    pub quantized: bool,
+}
+
+impl GigaAMModelParams {
+    /// Create params for the full precision (FP32) model.
+    pub fn fp32() -> Self {
+        Self { quantized: false }
+    }
+
+    /// Create params for the Int8 quantized model.
+    pub fn int8() -> Self {
+        Self { quantized: true }
+    }
+}
+
+/// Parameters for inference.
+#[derive(Debug, Clone, Default)]
+pub struct GigaAMInferenceParams {
+    /// Domain terms and names whose near-miss spellings are corrected in the
+    /// output.
+    pub vocabulary: Vec<String>,
+}
+
+/// GigaAM transcription engine.
+///
+/// Implements the `TranscriptionEngine` trait for GigaAM CTC ONNX exports.
+pub struct GigaAMEngine {
+    loaded_model_path: Option<PathBuf>,
+    model: Option<GigaAMModel>,
+}
+
+impl GigaAMEngine {
+    /// Create a new GigaAM engine (model not loaded).
+    pub fn new() -> Self {
+        Self {
+            loaded_model_path: None,
+            model: None,
+        }
+    }
+}
+
+impl Default for GigaAMEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for GigaAMEngine {
+    fn drop(&mut self) {
+        self.unload_model();
+    }
+}
+
+impl TranscriptionEngine for GigaAMEngine {
+    type InferenceParams = GigaAMInferenceParams;
+    type ModelParams = GigaAMModelParams;
+
+    fn load_model_with_params(
+        &mut self,
+        model_path: &Path,
+        params: Self::ModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.unload_model();
+
+        self.model = Some(GigaAMModel::new(model_path, params.quantized)?);
+        self.loaded_model_path = Some(model_path.to_path_buf());
+
+        log::info!("Loaded GigaAM model from {:?}", model_path);
+
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        if self.model.is_some() {
+            log::debug!("Unloading GigaAM model");
+            self.model = None;
+            self.loaded_model_path = None;
+        }
+    }
+
+    fn transcribe_samples(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<Self::InferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let model = self.model.as_mut().ok_or(GigaAMError::ModelNotLoaded)?;
+
+        let params = params.unwrap_or_default();
+
+        log::debug!(
+            "Transcribing {} samples ({:.2}s)",
+            samples.len(),
+            samples.len() as f32 / SAMPLE_RATE as f32
+        );
+
+        let (tokens, frame_secs) = model.transcribe(&samples)?;
+
+        let mut result = TranscriptionResult {
+            text: ctc::decode_text(&tokens, model.vocab()),
+            segments: Some(ctc::word_segments(&tokens, model.vocab(), frame_secs)),
+            language: Some("ru".to_string()),
+        };
+        vocabulary::apply(&mut result, &params.vocabulary);
+
+        Ok(result)
+    }
+}
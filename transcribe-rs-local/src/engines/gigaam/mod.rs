@@ -0,0 +1,47 @@
+//! GigaAM ONNX transcription engine.
+//!
+//! GigaAM is Sber's Conformer acoustic model for Russian. The CTC variants
+//! decode in a single pass; the v3 end-to-end model also punctuates and
+//! normalizes its output.
+//!
+//! # Model Format
+//!
+//! Either a single `.onnx` file, such as `giga-am-v3.int8.onnx`, or a
+//! directory containing `model.onnx` (or `model.int8.onnx`). The model
+//! takes 64-bin log mel features (20 ms windows every 10 ms, as GigaAM v3
+//! was trained on) and their lengths, and returns per-frame token scores.
+//!
+//! The vocabulary comes from a `tokens.txt` next to the model ("<token>
+//! <id>" per line), from a `vocab` metadata entry holding a JSON list, or,
+//! for the character models, is the built-in Russian alphabet.
+//!
+//! # Timestamps
+//!
+//! Results carry one segment per word, timed by the CTC frames its tokens
+//! were emitted at.
+//!
+//! # Audio Requirements
+//!
+//! - Sample rate: 16 kHz
+//! - Format: Mono, 16-bit PCM
+//! - Language: Russian
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::path::PathBuf;
+//! use transcribe_rs::{TranscriptionEngine, engines::gigaam::GigaAMEngine};
+//!
+//! let mut engine = GigaAMEngine::new();
+//! engine.load_model(&PathBuf::from("models/giga-am-v3.int8.onnx"))?;
+//!
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), None)?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod engine;
+pub mod model;
+
+pub use engine::{GigaAMEngine, GigaAMInferenceParams, GigaAMModelParams};
+pub use model::{GigaAMError, GigaAMModel};
@@ -0,0 +1,198 @@
+use ndarray::{s, Array1, Array3, Axis};
+use ort::session::{Session, SessionInputValue};
+use ort::tensor::TensorElementType;
+use ort::value::Value;
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::engines::ctc::{self, CtcToken};
+use crate::engines::fbank::LogMel;
+use crate::engines::onnx;
+
+const SAMPLE_RATE: f32 = 16000.0;
+
+/// GigaAM v3 front end: 64 log mel bins over 20 ms windows every 10 ms.
+const FRONT_END: LogMel = LogMel {
+    n_mels: 64,
+    n_fft: 320,
+    hop_length: 160,
+    center: false,
+};
+
+/// Character vocabulary of the GigaAM CTC models, which have no token file;
+/// the blank follows it.
+const RUSSIAN_ALPHABET: &str = " абвгдежзийклмнопрстуфхцчшщъыьэюя";
+
+#[derive(thiserror::Error, Debug)]
+pub enum GigaAMError {
+    #[error("ORT error: {0}")]
+    Ort(#[from] ort::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ndarray shape error: {0}")]
+    Shape(#[from] ndarray::ShapeError),
+    #[error("Model input not found: {0}")]
+    InputNotFound(String),
+    #[error("Model output not found: {0}")]
+    OutputNotFound(String),
+    #[error("No vocabulary for a model with {0} output classes; put its tokens.txt next to it")]
+    VocabNotFound(i64),
+    #[error("Model not loaded")]
+    ModelNotLoaded,
+}
+
+/// GigaAM CTC model exported to ONNX.
+///
+/// Takes log mel features (`[batch, 64, frames]`, or `[batch, frames, 64]`)
+/// and their lengths, and returns per-frame token scores. The vocabulary is
+/// read from a `tokens.txt` next to the model, from a `vocab` metadata entry
+/// (a JSON list), or is the Russian alphabet of the character models.
+pub struct GigaAMModel {
+    session: Session,
+    vocab: Vec<String>,
+    blank_id: usize,
+    features_input: String,
+    lengths_input: String,
+    lengths_i32: bool,
+    channels_first: bool,
+    output: String,
+}
+
+impl Drop for GigaAMModel {
+    fn drop(&mut self) {
+        log::debug!(
+            "Dropping GigaAMModel with {} vocab tokens",
+            self.vocab.len()
+        );
+    }
+}
+
+impl GigaAMModel {
+    /// Load the model at `model_path`: an `.onnx` file, or a directory with
+    /// `model.onnx` (`model.int8.onnx` if `quantized`) and `tokens.txt`.
+    pub fn new(model_path: &Path, quantized: bool) -> Result<Self, GigaAMError> {
+        let (session, tokens_path) = if model_path.is_dir() {
+            (
+                onnx::init_session(model_path, "model", None, quantized)?,
+                model_path.join("tokens.txt"),
+            )
+        } else {
+            log::info!("Loading model from {:?}...", model_path);
+            (
+                onnx::load_session(model_path, None, None)?,
+                model_path.with_file_name("tokens.txt"),
+            )
+        };
+
+        let [features, lengths] = &session.inputs[..] else {
+            return Err(GigaAMError::InputNotFound(
+                "features and feature lengths".to_string(),
+            ));
+        };
+        let channels_first = features
+            .input_type
+            .tensor_shape()
+            .is_some_and(|shape| shape.get(1) == Some(&(FRONT_END.n_mels as i64)));
+        let lengths_i32 = lengths.input_type.tensor_type() == Some(TensorElementType::Int32);
+        let features_input = features.name.clone();
+        let lengths_input = lengths.name.clone();
+
+        let output = session
+            .outputs
+            .first()
+            .ok_or_else(|| GigaAMError::OutputNotFound("log probabilities".to_string()))?;
+        let classes = output
+            .output_type
+            .tensor_shape()
+            .and_then(|shape| shape.last().copied())
+            .unwrap_or(-1);
+        let output = output.name.clone();
+
+        let vocab = if tokens_path.exists() {
+            ctc::load_tokens(&tokens_path)?
+        } else if let Some(vocab) = session
+            .metadata()?
+            .custom("vocab")?
+            .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok())
+        {
+            vocab
+        } else if classes == RUSSIAN_ALPHABET.chars().count() as i64 + 1 {
+            RUSSIAN_ALPHABET.chars().map(String::from).collect()
+        } else {
+            return Err(GigaAMError::VocabNotFound(classes));
+        };
+        let blank_id = vocab
+            .iter()
+            .position(|token| token == "<blk>" || token == "<blank>")
+            .unwrap_or(vocab.len());
+
+        log::info!(
+            "Loaded vocabulary with {} tokens, blank_id={}",
+            vocab.len(),
+            blank_id
+        );
+
+        Ok(Self {
+            session,
+            vocab,
+            blank_id,
+            features_input,
+            lengths_input,
+            lengths_i32,
+            channels_first,
+            output,
+        })
+    }
+
+    /// Greedily decode `samples`, returning the tokens and the duration of
+    /// one output frame in seconds.
+    pub(crate) fn transcribe(
+        &mut self,
+        samples: &[f32],
+    ) -> Result<(Vec<CtcToken>, f32), GigaAMError> {
+        let features = FRONT_END.compute(samples);
+        let num_frames = features.nrows();
+        if num_frames == 0 {
+            return Ok((Vec::new(), 0.0));
+        }
+        let features = if self.channels_first {
+            features.reversed_axes()
+        } else {
+            features
+        };
+        let features = features
+            .as_standard_layout()
+            .insert_axis(Axis(0))
+            .to_owned();
+
+        let lengths: Value = if self.lengths_i32 {
+            Value::from_array(Array1::from_vec(vec![num_frames as i32]))?.into_dyn()
+        } else {
+            Value::from_array(Array1::from_vec(vec![num_frames as i64]))?.into_dyn()
+        };
+        let inputs: Vec<(Cow<'_, str>, SessionInputValue<'_>)> = vec![
+            (
+                self.features_input.as_str().into(),
+                Value::from_array(features)?.into_dyn().into(),
+            ),
+            (self.lengths_input.as_str().into(), lengths.into()),
+        ];
+
+        log::trace!("Running GigaAM on {} frames...", num_frames);
+        let outputs = self.session.run(inputs)?;
+        let scores = outputs
+            .get(self.output.as_str())
+            .ok_or_else(|| GigaAMError::OutputNotFound(self.output.clone()))?
+            .try_extract_array::<f32>()?;
+        let scores: Array3<f32> = scores.to_owned().into_dimensionality()?;
+        let scores = scores.slice(s![0, .., ..]);
+
+        let frame_secs = samples.len() as f32 / SAMPLE_RATE / scores.nrows().max(1) as f32;
+        Ok((ctc::greedy_decode(scores, self.blank_id), frame_secs))
+    }
+
+    pub(crate) fn vocab(&self) -> &[String] {
+        &self.vocab
+    }
+}
@@ -0,0 +1,228 @@
+//! Helpers shared by engines that talk to a transcription server over HTTP.
+
+use std::io::Write;
+
+/// Custom multipart form-data builder for HTTP requests.
+pub(crate) struct MultipartForm {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl MultipartForm {
+    /// Create a new multipart form with a random boundary.
+    pub(crate) fn new() -> Self {
+        // Generate a simple boundary using timestamp and a fixed prefix
+        let boundary = format!(
+            "----transcribe-rs-boundary-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        );
+        Self {
+            boundary,
+            body: Vec::new(),
+        }
+    }
+
+    /// Add a file part to the form.
+    #[cfg_attr(not(any(feature = "whisperfile", feature = "handy")), allow(dead_code))]
+    pub(crate) fn file(
+        mut self,
+        name: &str,
+        filename: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Self {
+        self.file_header(name, filename, content_type);
+        // Write file data
+        self.body.extend_from_slice(&data);
+        // Trailing CRLF
+        write!(self.body, "\r\n").unwrap();
+        self
+    }
+
+    /// Add 16kHz mono `samples` as a WAV file part, encoded straight into
+    /// the body without an intermediate copy.
+    #[cfg_attr(
+        not(any(feature = "whisperfile", feature = "faster_whisper")),
+        allow(dead_code)
+    )]
+    pub(crate) fn wav(
+        mut self,
+        name: &str,
+        filename: &str,
+        samples: &[f32],
+        format: WavFormat,
+    ) -> Self {
+        self.file_header(name, filename, "audio/wav");
+        write_wav(&mut self.body, samples, format);
+        write!(self.body, "\r\n").unwrap();
+        self
+    }
+
+    /// Start a file part, up to the blank line before its content.
+    fn file_header(&mut self, name: &str, filename: &str, content_type: &str) {
+        // Write boundary
+        write!(self.body, "--{}\r\n", self.boundary).unwrap();
+        // Write content disposition header
+        write!(
+            self.body,
+            "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
+            name, filename
+        )
+        .unwrap();
+        // Write content type header
+        write!(self.body, "Content-Type: {}\r\n", content_type).unwrap();
+        // Blank line before content
+        write!(self.body, "\r\n").unwrap();
+    }
+
+    /// Add a text field to the form.
+    pub(crate) fn text(mut self, name: &str, value: &str) -> Self {
+        // Write boundary
+        write!(self.body, "--{}\r\n", self.boundary).unwrap();
+        // Write content disposition header
+        write!(
+            self.body,
+            "Content-Disposition: form-data; name=\"{}\"\r\n",
+            name
+        )
+        .unwrap();
+        // Blank line before content
+        write!(self.body, "\r\n").unwrap();
+        // Write text value
+        write!(self.body, "{}\r\n", value).unwrap();
+        self
+    }
+
+    /// Finalize the form and return the content type and body.
+    pub(crate) fn build(mut self) -> (String, Vec<u8>) {
+        // Write closing boundary
+        write!(self.body, "--{}--\r\n", self.boundary).unwrap();
+        let content_type = format!("multipart/form-data; boundary={}", self.boundary);
+        (content_type, self.body)
+    }
+}
+
+/// The language a server reports, if it is a code such as "en". Servers
+/// built on whisper.cpp report names like "english", which are dropped.
+pub(crate) fn language_code(language: Option<String>) -> Option<String> {
+    language.filter(|language| {
+        (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase())
+    })
+}
+
+/// Sample encoding of WAV files sent to a server.
+#[cfg_attr(
+    not(any(feature = "whisperfile", feature = "faster_whisper")),
+    allow(dead_code)
+)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WavFormat {
+    /// 32-bit float, lossless.
+    #[default]
+    Float32,
+    /// 16-bit PCM, half the size, for servers that can't read float WAV.
+    Int16,
+}
+
+/// Append 16kHz mono `samples` to `out` as a WAV file.
+#[cfg_attr(
+    not(any(feature = "whisperfile", feature = "faster_whisper")),
+    allow(dead_code)
+)]
+pub(crate) fn write_wav(out: &mut Vec<u8>, samples: &[f32], format: WavFormat) {
+    const SAMPLE_RATE: u32 = 16000;
+    let (format_tag, bytes_per_sample): (u16, u16) = match format {
+        // WAVE_FORMAT_IEEE_FLOAT
+        WavFormat::Float32 => (3, 4),
+        // WAVE_FORMAT_PCM
+        WavFormat::Int16 => (1, 2),
+    };
+    // The header is written up front since the length is known, so `out`
+    // needn't be seekable
+    let data_len = samples.len() as u32 * bytes_per_sample as u32;
+    out.reserve(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&format_tag.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    out.extend_from_slice(&(SAMPLE_RATE * bytes_per_sample as u32).to_le_bytes());
+    out.extend_from_slice(&bytes_per_sample.to_le_bytes());
+    out.extend_from_slice(&(bytes_per_sample * 8).to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+
+    match format {
+        WavFormat::Float32 => {
+            for sample in samples {
+                out.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+        WavFormat::Int16 => {
+            for sample in samples {
+                let sample_i16 = (sample * i16::MAX as f32) as i16;
+                out.extend_from_slice(&sample_i16.to_le_bytes());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_back(wav: &[u8]) -> (hound::WavSpec, Vec<f32>) {
+        let reader = hound::WavReader::new(wav).unwrap();
+        let spec = reader.spec();
+        let samples = match spec.sample_format {
+            hound::SampleFormat::Float => {
+                reader.into_samples::<f32>().map(Result::unwrap).collect()
+            }
+            hound::SampleFormat::Int => reader
+                .into_samples::<i16>()
+                .map(|s| s.unwrap() as f32 / i16::MAX as f32)
+                .collect(),
+        };
+        (spec, samples)
+    }
+
+    #[test]
+    fn test_wav_formats() {
+        let samples = [0.0, 0.25, -0.5, 1.0, 0.123_456_79];
+
+        let (spec, float) = read_back(&wav_bytes_with(&samples, WavFormat::Float32));
+        assert_eq!(spec.sample_rate, 16000);
+        assert_eq!(spec.bits_per_sample, 32);
+        // Float WAV is lossless
+        assert_eq!(float, samples);
+
+        let (spec, int) = read_back(&wav_bytes_with(&samples, WavFormat::Int16));
+        assert_eq!(spec.bits_per_sample, 16);
+        assert!((int[4] - samples[4]).abs() < 1e-4);
+        assert_ne!(int[4], samples[4]);
+    }
+
+    fn wav_bytes_with(samples: &[f32], format: WavFormat) -> Vec<u8> {
+        let mut wav = Vec::new();
+        write_wav(&mut wav, samples, format);
+        wav
+    }
+
+    #[test]
+    fn test_wav_part_is_written_in_place() {
+        let samples = [0.5; 100];
+        let (_, body) = MultipartForm::new()
+            .text("language", "en")
+            .wav("file", "audio.wav", &samples, WavFormat::Float32)
+            .build();
+
+        let start = body.windows(4).position(|w| w == b"RIFF").unwrap();
+        let (_, decoded) = read_back(&body[start..start + 44 + 400]);
+        assert_eq!(decoded, samples);
+    }
+}
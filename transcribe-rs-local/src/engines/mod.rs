@@ -9,8 +9,14 @@
 //! Enable engines via Cargo features:
 //! - `whisper` - OpenAI's Whisper (GGML format)
 //! - `parakeet` - NVIDIA NeMo Parakeet (ONNX format)
-//! - `moonshine` - Moonshine lightweight models (ONNX format)
+//! - `moonshine` - Moonshine lightweight models, including the streaming checkpoints (ONNX format)
+//! - `sense_voice` - SenseVoice Small for Chinese, Cantonese, English, Japanese and Korean (ONNX format)
+//! - `gigaam` - GigaAM Russian CTC models (ONNX format)
+//! - `canary` - NVIDIA NeMo Canary multilingual transcription and translation (ONNX format)
 //! - `whisperfile` - Mozilla whisperfile server wrapper
+//! - `faster_whisper` - faster-whisper (CTranslate2) server, managed or existing
+//! - `vosk` - Vosk (Kaldi) small models with streaming partial results
+//! - `native` - Speech recognizer built into the OS (macOS, Windows), no download
 //!
 //! # Example
 //!
@@ -19,10 +25,45 @@
 //! transcribe-rs = { version = "0.2", features = ["parakeet", "whisper"] }
 //! ```
 
+#[cfg(feature = "canary")]
+pub mod canary;
+#[cfg(any(feature = "sense_voice", feature = "gigaam"))]
+pub(crate) mod ctc;
+#[cfg(feature = "faster_whisper")]
+pub mod faster_whisper;
+#[cfg(any(feature = "sense_voice", feature = "gigaam"))]
+pub(crate) mod fbank;
+#[cfg(feature = "gigaam")]
+pub mod gigaam;
+#[cfg(any(feature = "whisperfile", feature = "faster_whisper", feature = "handy"))]
+pub(crate) mod http;
 #[cfg(feature = "moonshine")]
 pub mod moonshine;
+#[cfg(feature = "native")]
+pub mod native;
+#[cfg(any(
+    feature = "parakeet",
+    feature = "canary",
+    feature = "alignment",
+    feature = "sense_voice",
+    feature = "gigaam"
+))]
+pub(crate) mod onnx;
 #[cfg(feature = "parakeet")]
 pub mod parakeet;
+#[cfg(feature = "sense_voice")]
+pub mod sense_voice;
+#[cfg(any(
+    feature = "parakeet",
+    feature = "canary",
+    feature = "alignment",
+    feature = "moonshine",
+    feature = "sense_voice",
+    feature = "gigaam"
+))]
+pub(crate) mod session_cache;
+#[cfg(feature = "vosk")]
+pub mod vosk;
 #[cfg(feature = "whisper")]
 pub mod whisper;
 #[cfg(feature = "whisperfile")]
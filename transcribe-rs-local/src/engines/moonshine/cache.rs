@@ -1,61 +1,81 @@
 use ndarray::{ArrayD, IxDyn};
-use std::collections::HashMap;
+use ort::session::{SessionInputValue, SessionOutputs};
+use ort::value::{DynValue, Value};
+use std::borrow::Cow;
 
 use super::engine::ModelVariant;
-use super::model::MoonshineError;
+use super::model::{DecoderLayout, MoonshineError};
+
+/// One key or value tensor of the cache.
+struct CacheEntry {
+    /// Decoder input name, "past_key_values.{i}.{type}.{kv}"
+    input_name: String,
+    /// Decoder output name, "present.{i}.{type}.{kv}"
+    output_name: String,
+    /// Cross-attention (encoder) entries are only computed on the first step
+    is_encoder: bool,
+    value: DynValue,
+}
 
 /// KV Cache for Moonshine decoder.
 ///
 /// Manages key-value cache state for both self-attention (decoder) and
 /// cross-attention (encoder) across autoregressive decoding steps.
+///
+/// The tensors stay in ONNX Runtime values: each step's outputs are moved
+/// into the cache and borrowed as the next step's inputs, so no tensor is
+/// copied between steps.
 pub struct KVCache {
-    cache: HashMap<String, ArrayD<f32>>,
-    num_layers: usize,
+    entries: Vec<CacheEntry>,
 }
 
 impl KVCache {
     /// Create a new empty KV cache for the given model variant.
-    pub fn new(variant: &ModelVariant) -> Self {
-        let num_layers = variant.num_layers();
-        let num_heads = variant.num_key_value_heads();
-        let head_dim = variant.head_dim();
+    pub fn new(variant: &ModelVariant) -> Result<Self, MoonshineError> {
+        Self::with_layout(&DecoderLayout::of(variant))
+    }
+
+    /// Create a new empty KV cache for a decoder of the given shape.
+    pub fn with_layout(layout: &DecoderLayout) -> Result<Self, MoonshineError> {
+        let DecoderLayout {
+            num_layers,
+            num_heads,
+            head_dim,
+        } = *layout;
 
-        let mut cache = HashMap::new();
+        let mut entries = Vec::with_capacity(num_layers * 4);
 
         // Initialize empty cache tensors for all layers
         // Shape: (0, num_heads, 1, head_dim) - sequence length starts at 0
         for i in 0..num_layers {
             for attention_type in &["decoder", "encoder"] {
                 for kv_type in &["key", "value"] {
-                    let key = format!("past_key_values.{}.{}.{}", i, attention_type, kv_type);
                     let empty_tensor = ArrayD::<f32>::zeros(IxDyn(&[0, num_heads, 1, head_dim]));
-                    cache.insert(key, empty_tensor);
+                    entries.push(CacheEntry {
+                        input_name: format!("past_key_values.{}.{}.{}", i, attention_type, kv_type),
+                        // Output names are "present.{i}.{type}.{kv}" (not "present_key_values")
+                        output_name: format!("present.{}.{}.{}", i, attention_type, kv_type),
+                        is_encoder: *attention_type == "encoder",
+                        value: Value::from_array(empty_tensor)?.into_dyn(),
+                    });
                 }
             }
         }
 
-        Self { cache, num_layers }
+        Ok(Self { entries })
     }
 
-    /// Get all cache tensors as inputs for the decoder.
-    pub fn get_inputs(&self) -> Vec<(String, ArrayD<f32>)> {
-        let mut inputs = Vec::new();
-
-        for i in 0..self.num_layers {
-            for attention_type in &["decoder", "encoder"] {
-                for kv_type in &["key", "value"] {
-                    let key = format!("past_key_values.{}.{}.{}", i, attention_type, kv_type);
-                    if let Some(tensor) = self.cache.get(&key) {
-                        inputs.push((key, tensor.clone()));
-                    }
-                }
-            }
-        }
-
-        inputs
+    /// Borrow all cache tensors as inputs for the decoder.
+    pub fn inputs(&self) -> impl Iterator<Item = (Cow<'_, str>, SessionInputValue<'_>)> {
+        self.entries.iter().map(|entry| {
+            (
+                Cow::Borrowed(entry.input_name.as_str()),
+                SessionInputValue::from(&entry.value),
+            )
+        })
     }
 
-    /// Update cache from decoder outputs.
+    /// Move the updated cache tensors out of the decoder outputs.
     ///
     /// # Cache Update Logic
     ///
@@ -63,33 +83,16 @@ impl KVCache {
     /// - Subsequent iterations (`use_cache_branch=true`): Update ONLY decoder caches
     ///
     /// The encoder cross-attention cache is computed once and reused for all subsequent tokens.
-    pub fn update_from_outputs(
-        &mut self,
-        outputs: &ort::session::SessionOutputs,
-        use_cache_branch: bool,
-    ) -> Result<(), MoonshineError> {
-        for i in 0..self.num_layers {
-            for attention_type in &["decoder", "encoder"] {
-                // Skip encoder cache updates after first iteration
-                if use_cache_branch && *attention_type == "encoder" {
-                    continue;
-                }
-
-                for kv_type in &["key", "value"] {
-                    // Output names are "present.{i}.{type}.{kv}" (not "present_key_values")
-                    let output_key = format!("present.{}.{}.{}", i, attention_type, kv_type);
-                    let cache_key = format!("past_key_values.{}.{}.{}", i, attention_type, kv_type);
+    pub fn update_from_outputs(&mut self, outputs: &mut SessionOutputs, use_cache_branch: bool) {
+        for entry in &mut self.entries {
+            // Skip encoder cache updates after first iteration
+            if use_cache_branch && entry.is_encoder {
+                continue;
+            }
 
-                    if let Some(output) = outputs.get(&output_key) {
-                        let tensor = output
-                            .try_extract_array::<f32>()
-                            .map_err(|e| MoonshineError::Ort(e))?;
-                        self.cache.insert(cache_key, tensor.to_owned());
-                    }
-                }
+            if let Some(value) = outputs.remove(entry.output_name.as_str()) {
+                entry.value = value;
             }
         }
-
-        Ok(())
     }
 }
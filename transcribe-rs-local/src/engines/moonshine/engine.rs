@@ -1,17 +1,27 @@
 use std::path::{Path, PathBuf};
 
+use derive_builder::Builder;
+
+use crate::params::Granularity;
+use crate::postprocess::{self, PostProcessOptions};
+use crate::vocabulary;
 use crate::{TranscriptionEngine, TranscriptionResult};
 
 use super::model::MoonshineModel;
+use super::timestamps;
 
 const SAMPLE_RATE: u32 = 16000;
 
+/// Maximum number of clips decoded together by `transcribe_batch`.
+const MAX_BATCH_SIZE: usize = 8;
+
 /// Moonshine model variant.
 ///
 /// Each variant has different parameters for number of layers, heads, and head dimensions.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum ModelVariant {
     /// English model (6 layers, token_rate=6)
+    #[default]
     Tiny,
     /// Arabic model (6 layers, token_rate=13)
     TinyAr,
@@ -79,10 +89,36 @@ impl ModelVariant {
     }
 }
 
-impl Default for ModelVariant {
-    fn default() -> Self {
-        ModelVariant::Tiny
-    }
+/// ONNX Runtime execution provider for the Moonshine sessions.
+///
+/// GPU providers only take effect when `ort` is built with the matching
+/// feature (`cuda`, `coreml` or `directml`); otherwise ONNX Runtime logs a
+/// warning and runs on the CPU.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecutionProvider {
+    /// Plain CPU execution
+    #[default]
+    Cpu,
+    /// NVIDIA GPUs
+    Cuda,
+    /// Apple Neural Engine and GPU
+    CoreML,
+    /// DirectX 12 GPUs on Windows
+    DirectML,
+}
+
+/// ONNX Runtime graph optimization level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OptimizationLevel {
+    /// No graph optimizations
+    Disable,
+    /// Semantics-preserving rewrites such as constant folding
+    Basic,
+    /// Basic plus node fusions
+    Extended,
+    /// Extended plus layout optimizations
+    #[default]
+    All,
 }
 
 /// Parameters for loading a Moonshine model.
@@ -90,35 +126,78 @@ impl Default for ModelVariant {
 pub struct MoonshineModelParams {
     /// The model variant to load.
     pub variant: ModelVariant,
+
+    /// Where ONNX Runtime runs the encoder and decoder (default: CPU).
+    pub execution_provider: ExecutionProvider,
+
+    /// Threads used within an operator. `None` lets ONNX Runtime use all
+    /// cores; small ARM boards are often faster with 1-2.
+    pub intra_threads: Option<usize>,
+
+    /// Threads used to run independent operators in parallel. `None` uses
+    /// ONNX Runtime's default.
+    pub inter_threads: Option<usize>,
+
+    /// Graph optimization level (default: all optimizations).
+    pub optimization_level: OptimizationLevel,
+
+    /// Directory to keep the optimized graphs (or, with CoreML, the compiled
+    /// models) in, so later loads skip that work. Use one directory per
+    /// model.
+    pub cache_dir: Option<PathBuf>,
 }
 
 impl MoonshineModelParams {
     /// Create params for the Tiny English model.
     pub fn tiny() -> Self {
-        Self {
-            variant: ModelVariant::Tiny,
-        }
+        Self::variant(ModelVariant::Tiny)
     }
 
     /// Create params for the Base English model.
     pub fn base() -> Self {
-        Self {
-            variant: ModelVariant::Base,
-        }
+        Self::variant(ModelVariant::Base)
     }
 
     /// Create params for a specific variant.
     pub fn variant(variant: ModelVariant) -> Self {
-        Self { variant }
+        Self {
+            variant,
+            ..Default::default()
+        }
+    }
+
+    /// Keep the optimized models in `dir`, making later loads faster.
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
     }
 }
 
 /// Parameters for inference.
-#[derive(Debug, Clone, Default)]
+#[derive(Builder, Debug, Clone, Default)]
+#[builder(setter(into), default)]
 pub struct MoonshineInferenceParams {
     /// Maximum number of tokens to generate.
     /// If None, automatically calculated from audio duration and model's token_rate.
     pub max_length: Option<usize>,
+
+    /// Punctuation and capitalization restoration applied to the output,
+    /// which Moonshine does not produce itself.
+    pub post_process: PostProcessOptions,
+
+    /// Domain terms and names whose near-miss spellings are corrected in the
+    /// output.
+    pub vocabulary: Vec<String>,
+
+    /// Return segments with timestamps estimated from the audio at this
+    /// level of detail. `None` returns text only.
+    pub timestamps: Option<Granularity>,
+}
+
+impl MoonshineInferenceParams {
+    pub fn builder() -> MoonshineInferenceParamsBuilder {
+        MoonshineInferenceParamsBuilder::default()
+    }
 }
 
 /// Moonshine ONNX transcription engine.
@@ -139,6 +218,77 @@ impl MoonshineEngine {
             variant: ModelVariant::default(),
         }
     }
+
+    /// Transcribe several clips, batching the encoder and decoder runs.
+    ///
+    /// Much faster than calling `transcribe_samples()` for each clip when
+    /// transcribing many short recordings such as voice notes. Clips are
+    /// grouped by length to keep padding low; results are returned in input
+    /// order. Each clip must be 16 kHz mono audio of 0.1s to 64s.
+    pub fn transcribe_batch(
+        &mut self,
+        clips: Vec<Vec<f32>>,
+        params: Option<MoonshineInferenceParams>,
+    ) -> Result<Vec<TranscriptionResult>, Box<dyn std::error::Error>> {
+        let token_rate = self.variant.token_rate();
+        let model = self
+            .model
+            .as_mut()
+            .ok_or(super::model::MoonshineError::ModelNotLoaded)?;
+
+        let params = params.unwrap_or_default();
+
+        let mut order: Vec<usize> = (0..clips.len()).collect();
+        order.sort_by_key(|&i| clips[i].len());
+
+        let mut results: Vec<Option<TranscriptionResult>> = clips.iter().map(|_| None).collect();
+        for batch in order.chunks(MAX_BATCH_SIZE) {
+            let batch_clips: Vec<&[f32]> = batch.iter().map(|&i| clips[i].as_slice()).collect();
+            let max_lengths: Vec<usize> = batch_clips
+                .iter()
+                .map(|clip| max_length(clip.len(), token_rate, &params))
+                .collect();
+
+            log::debug!(
+                "Transcribing batch of {} clips ({:.2}s longest)",
+                batch_clips.len(),
+                batch_clips.last().map_or(0, |clip| clip.len()) as f32 / SAMPLE_RATE as f32
+            );
+
+            let batch_tokens = model.generate_batch(&batch_clips, &max_lengths)?;
+            for (&i, tokens) in batch.iter().zip(batch_tokens) {
+                let mut result = TranscriptionResult {
+                    text: model.decode_tokens(&tokens)?,
+                    segments: params.timestamps.map(|granularity| {
+                        timestamps::estimate_segments(
+                            &model.decode_pieces(&tokens),
+                            &clips[i],
+                            token_rate,
+                            granularity,
+                        )
+                    }),
+                    language: None,
+                };
+                vocabulary::apply(&mut result, &params.vocabulary);
+                postprocess::apply(&mut result, &params.post_process);
+                results[i] = Some(result);
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|result| result.expect("every clip is in a batch"))
+            .collect())
+    }
+}
+
+/// `params.max_length`, or the number of tokens the model can produce for
+/// `num_samples` of audio at its token rate.
+fn max_length(num_samples: usize, token_rate: usize, params: &MoonshineInferenceParams) -> usize {
+    params.max_length.unwrap_or_else(|| {
+        let audio_duration_sec = num_samples as f32 / SAMPLE_RATE as f32;
+        (audio_duration_sec * token_rate as f32).ceil() as usize
+    })
 }
 
 impl Default for MoonshineEngine {
@@ -166,7 +316,7 @@ impl TranscriptionEngine for MoonshineEngine {
         self.unload_model();
 
         self.variant = params.variant;
-        self.model = Some(MoonshineModel::new(model_path, params.variant)?);
+        self.model = Some(MoonshineModel::new(model_path, &params)?);
         self.loaded_model_path = Some(model_path.to_path_buf());
 
         log::info!(
@@ -194,32 +344,58 @@ impl TranscriptionEngine for MoonshineEngine {
         let model = self
             .model
             .as_mut()
-            .ok_or_else(|| super::model::MoonshineError::ModelNotLoaded)?;
+            .ok_or(super::model::MoonshineError::ModelNotLoaded)?;
 
-        let params = params.unwrap_or_default();
+        transcribe(
+            model,
+            &samples,
+            self.variant.token_rate(),
+            &params.unwrap_or_default(),
+        )
+    }
+}
 
-        // Calculate max_length from audio duration if not provided
-        let max_length = params.max_length.unwrap_or_else(|| {
-            let audio_duration_sec = samples.len() as f32 / SAMPLE_RATE as f32;
-            (audio_duration_sec * self.variant.token_rate() as f32).ceil() as usize
-        });
-
-        log::debug!(
-            "Transcribing {} samples ({:.2}s), max_length={}",
-            samples.len(),
-            samples.len() as f32 / SAMPLE_RATE as f32,
-            max_length
-        );
+/// Transcribe `samples` with `model`, which produces at most `token_rate`
+/// tokens per second of audio.
+pub(super) fn transcribe(
+    model: &mut MoonshineModel,
+    samples: &[f32],
+    token_rate: usize,
+    params: &MoonshineInferenceParams,
+) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+    // Calculate max_length from audio duration if not provided
+    let max_length = max_length(samples.len(), token_rate, params);
 
-        // Generate tokens
-        let tokens = model.generate(&samples, max_length)?;
+    log::debug!(
+        "Transcribing {} samples ({:.2}s), max_length={}",
+        samples.len(),
+        samples.len() as f32 / SAMPLE_RATE as f32,
+        max_length
+    );
 
-        // Decode tokens to text
-        let text = model.decode_tokens(&tokens)?;
+    // Generate tokens
+    let tokens = model.generate(samples, max_length)?;
 
-        Ok(TranscriptionResult {
-            text,
-            segments: None, // Moonshine doesn't provide timestamp segments
-        })
-    }
+    // Decode tokens to text
+    let text = model.decode_tokens(&tokens)?;
+
+    // Moonshine doesn't predict timestamps, so they can only be estimated
+    let segments = params.timestamps.map(|granularity| {
+        timestamps::estimate_segments(
+            &model.decode_pieces(&tokens),
+            samples,
+            token_rate,
+            granularity,
+        )
+    });
+
+    let mut result = TranscriptionResult {
+        text,
+        segments,
+        language: None,
+    };
+    vocabulary::apply(&mut result, &params.vocabulary);
+    postprocess::apply(&mut result, &params.post_process);
+
+    Ok(result)
 }
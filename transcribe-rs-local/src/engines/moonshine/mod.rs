@@ -30,6 +30,20 @@
 //! | Base | English | 6 |
 //! | BaseEs | Spanish | 6 |
 //!
+//! # Streaming Models
+//!
+//! The Moonshine streaming checkpoints (e.g. `moonshine-tiny-streaming-en`)
+//! come in the same layout and load with [`MoonshineStreamingEngine`],
+//! which reads the decoder size from the model instead of a
+//! [`ModelVariant`].
+//!
+//! # Timestamps
+//!
+//! Moonshine does not predict timing. Setting `timestamps` in
+//! [`MoonshineInferenceParams`] returns segments with estimated times: the
+//! tokens are spread over the speech in the clip in proportion to their
+//! length. Good enough for subtitles, not for word-accurate editing.
+//!
 //! # Audio Requirements
 //!
 //! - Sample rate: 16 kHz
@@ -56,6 +70,12 @@
 pub mod cache;
 pub mod engine;
 pub mod model;
+mod streaming;
+mod timestamps;
 mod tokenizer;
 
-pub use engine::{ModelVariant, MoonshineEngine, MoonshineInferenceParams, MoonshineModelParams};
+pub use engine::{
+    ExecutionProvider, ModelVariant, MoonshineEngine, MoonshineInferenceParams,
+    MoonshineInferenceParamsBuilder, MoonshineModelParams, OptimizationLevel,
+};
+pub use streaming::{MoonshineStreamingEngine, StreamingModelParams};
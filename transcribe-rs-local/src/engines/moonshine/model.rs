@@ -1,14 +1,18 @@
-use ndarray::{Array2, ArrayD};
-use ort::execution_providers::CPUExecutionProvider;
+use ndarray::{s, Array2, ArrayD, ArrayView1};
+use ort::execution_providers::{
+    CPUExecutionProvider, CUDAExecutionProvider, CoreMLExecutionProvider,
+    DirectMLExecutionProvider, ExecutionProviderDispatch,
+};
 use ort::inputs;
-use ort::session::builder::GraphOptimizationLevel;
-use ort::session::Session;
-use ort::value::TensorRef;
+use ort::session::builder::{GraphOptimizationLevel, SessionBuilder};
+use ort::session::{Session, SessionInputValue};
+use ort::value::{TensorRef, Value};
 use std::path::Path;
 
 use super::cache::KVCache;
-use super::engine::ModelVariant;
+use super::engine::{ExecutionProvider, ModelVariant, MoonshineModelParams, OptimizationLevel};
 use super::tokenizer::MoonshineTokenizer;
+use crate::engines::session_cache;
 
 const DECODER_START_TOKEN_ID: i64 = 1;
 const EOS_TOKEN_ID: i64 = 2;
@@ -26,6 +30,8 @@ pub enum MoonshineError {
     ModelNotFound(String),
     #[error("Tokenizer file not found: {0}")]
     TokenizerNotFound(String),
+    #[error("Model input not found: {0}")]
+    InputNotFound(String),
     #[error("Model output not found: {0}")]
     OutputNotFound(String),
     #[error("Tokenization error: {0}")]
@@ -36,23 +42,76 @@ pub enum MoonshineError {
     ModelNotLoaded,
 }
 
+/// Shape of the decoder's self- and cross-attention cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderLayout {
+    pub num_layers: usize,
+    pub num_heads: usize,
+    pub head_dim: usize,
+}
+
+impl DecoderLayout {
+    /// The layout of a known model variant.
+    pub fn of(variant: &ModelVariant) -> Self {
+        Self {
+            num_layers: variant.num_layers(),
+            num_heads: variant.num_key_value_heads(),
+            head_dim: variant.head_dim(),
+        }
+    }
+
+    /// Read the layout off the decoder's `past_key_values.{i}.decoder.key`
+    /// inputs, shaped `[batch, heads, sequence, head_dim]`.
+    fn from_decoder(decoder: &Session) -> Result<Self, MoonshineError> {
+        let keys: Vec<_> = decoder
+            .inputs
+            .iter()
+            .filter(|input| {
+                input.name.starts_with("past_key_values.") && input.name.ends_with(".decoder.key")
+            })
+            .collect();
+        let shape = keys
+            .first()
+            .and_then(|input| input.input_type.tensor_shape())
+            .filter(|shape| shape.len() == 4 && shape[1] > 0 && shape[3] > 0)
+            .ok_or_else(|| {
+                MoonshineError::InputNotFound("past_key_values.0.decoder.key".to_string())
+            })?;
+        Ok(Self {
+            num_layers: keys.len(),
+            num_heads: shape[1] as usize,
+            head_dim: shape[3] as usize,
+        })
+    }
+}
+
 pub struct MoonshineModel {
     encoder: Session,
     decoder: Session,
     tokenizer: MoonshineTokenizer,
-    variant: ModelVariant,
+    layout: DecoderLayout,
     encoder_input_names: Vec<String>,
     decoder_input_names: Vec<String>,
 }
 
 impl Drop for MoonshineModel {
     fn drop(&mut self) {
-        log::debug!("Dropping MoonshineModel ({:?})", self.variant);
+        log::debug!("Dropping MoonshineModel ({:?})", self.layout);
     }
 }
 
 impl MoonshineModel {
-    pub fn new(model_dir: &Path, variant: ModelVariant) -> Result<Self, MoonshineError> {
+    pub fn new(model_dir: &Path, params: &MoonshineModelParams) -> Result<Self, MoonshineError> {
+        Self::load(model_dir, params, Some(DecoderLayout::of(&params.variant)))
+    }
+
+    /// Load the model in `model_dir`, reading the decoder layout off the
+    /// graph unless `layout` is given.
+    pub(crate) fn load(
+        model_dir: &Path,
+        params: &MoonshineModelParams,
+        layout: Option<DecoderLayout>,
+    ) -> Result<Self, MoonshineError> {
         let encoder_path = model_dir.join("encoder_model.onnx");
         let decoder_path = model_dir.join("decoder_model_merged.onnx");
 
@@ -68,10 +127,10 @@ impl MoonshineModel {
         }
 
         log::info!("Loading Moonshine encoder from {:?}...", encoder_path);
-        let encoder = Self::init_session(&encoder_path)?;
+        let encoder = Self::init_session(&encoder_path, params)?;
 
         log::info!("Loading Moonshine decoder from {:?}...", decoder_path);
-        let decoder = Self::init_session(&decoder_path)?;
+        let decoder = Self::init_session(&decoder_path, params)?;
 
         let encoder_input_names: Vec<String> =
             encoder.inputs.iter().map(|i| i.name.clone()).collect();
@@ -81,26 +140,69 @@ impl MoonshineModel {
         log::debug!("Encoder inputs: {:?}", encoder_input_names);
         log::debug!("Decoder inputs: {:?}", decoder_input_names);
 
+        let layout = match layout {
+            Some(layout) => layout,
+            None => DecoderLayout::from_decoder(&decoder)?,
+        };
+
         let tokenizer = MoonshineTokenizer::new(model_dir)?;
 
         Ok(Self {
             encoder,
             decoder,
             tokenizer,
-            variant,
+            layout,
             encoder_input_names,
             decoder_input_names,
         })
     }
 
-    fn init_session(path: &Path) -> Result<Session, MoonshineError> {
-        let providers = vec![CPUExecutionProvider::default().build()];
+    fn init_session(path: &Path, params: &MoonshineModelParams) -> Result<Session, MoonshineError> {
+        let cache_dir = params.cache_dir.as_deref();
+        let builder = || -> ort::Result<SessionBuilder> {
+            let mut providers: Vec<ExecutionProviderDispatch> = match params.execution_provider {
+                ExecutionProvider::Cpu => vec![],
+                ExecutionProvider::Cuda => vec![CUDAExecutionProvider::default().build()],
+                ExecutionProvider::CoreML => {
+                    let mut coreml = CoreMLExecutionProvider::default();
+                    if let Some(dir) = cache_dir {
+                        coreml = coreml.with_model_cache_dir(dir.display());
+                    }
+                    vec![coreml.build()]
+                }
+                ExecutionProvider::DirectML => vec![DirectMLExecutionProvider::default().build()],
+            };
+            // Unavailable providers fall through to the CPU
+            providers.push(CPUExecutionProvider::default().build());
+
+            let optimization_level = match params.optimization_level {
+                OptimizationLevel::Disable => GraphOptimizationLevel::Disable,
+                OptimizationLevel::Basic => GraphOptimizationLevel::Level1,
+                OptimizationLevel::Extended => GraphOptimizationLevel::Level2,
+                OptimizationLevel::All => GraphOptimizationLevel::Level3,
+            };
+
+            let mut builder = Session::builder()?
+                .with_optimization_level(optimization_level)?
+                .with_execution_providers(providers)?
+                .with_parallel_execution(true)?;
+
+            if let Some(threads) = params.intra_threads {
+                builder = builder.with_intra_threads(threads)?;
+            }
+            if let Some(threads) = params.inter_threads {
+                builder = builder.with_inter_threads(threads)?;
+            }
+            Ok(builder)
+        };
 
-        let session = Session::builder()?
-            .with_optimization_level(GraphOptimizationLevel::Level3)?
-            .with_execution_providers(providers)?
-            .with_parallel_execution(true)?
-            .commit_from_file(path)?;
+        // Graphs partitioned for GPU providers can't be saved, and there is
+        // nothing to gain without optimizations
+        let graph_cache = cache_dir.filter(|_| {
+            params.execution_provider == ExecutionProvider::Cpu
+                && params.optimization_level != OptimizationLevel::Disable
+        });
+        let session = session_cache::commit(builder, path, graph_cache)?;
 
         for input in &session.inputs {
             log::info!(
@@ -113,7 +215,11 @@ impl MoonshineModel {
         Ok(session)
     }
 
-    fn encode(&mut self, audio: &Array2<f32>) -> Result<ArrayD<f32>, MoonshineError> {
+    fn encode(
+        &mut self,
+        audio: &Array2<f32>,
+        attention_mask: &Array2<i64>,
+    ) -> Result<ArrayD<f32>, MoonshineError> {
         let audio_dyn = audio.clone().into_dyn();
 
         // Check if encoder expects attention_mask
@@ -121,8 +227,7 @@ impl MoonshineModel {
             .encoder_input_names
             .contains(&"attention_mask".to_string())
         {
-            let attention_mask =
-                Array2::<i64>::ones((audio.shape()[0], audio.shape()[1])).into_dyn();
+            let attention_mask = attention_mask.clone().into_dyn();
             let inputs = inputs![
                 "input_values" => TensorRef::from_array_view(audio_dyn.view())?,
                 "attention_mask" => TensorRef::from_array_view(attention_mask.view())?,
@@ -148,51 +253,87 @@ impl MoonshineModel {
         samples: &[f32],
         max_length: usize,
     ) -> Result<Vec<i64>, MoonshineError> {
-        // Validate audio duration
-        let audio_duration = samples.len() as f32 / SAMPLE_RATE as f32;
-        if audio_duration < 0.1 || audio_duration > 64.0 {
-            return Err(MoonshineError::AudioDuration(audio_duration));
+        let mut tokens = self.generate_batch(&[samples], &[max_length])?;
+        Ok(tokens.remove(0))
+    }
+
+    /// Greedy-decode several clips at once, generating at most
+    /// `max_lengths[i]` tokens for clip `i`.
+    ///
+    /// Shorter clips are zero-padded to the longest one and masked out, so
+    /// clips of similar length batch best. Returns the tokens of each clip in
+    /// input order.
+    pub fn generate_batch(
+        &mut self,
+        clips: &[&[f32]],
+        max_lengths: &[usize],
+    ) -> Result<Vec<Vec<i64>>, MoonshineError> {
+        debug_assert_eq!(clips.len(), max_lengths.len());
+
+        // Validate audio durations
+        for clip in clips {
+            let audio_duration = clip.len() as f32 / SAMPLE_RATE as f32;
+            if !(0.1..=64.0).contains(&audio_duration) {
+                return Err(MoonshineError::AudioDuration(audio_duration));
+            }
         }
 
-        // Prepare audio as [1, num_samples]
-        let audio = Array2::from_shape_vec((1, samples.len()), samples.to_vec())?;
-        let audio_attention_mask = Array2::<i64>::ones((1, samples.len()));
+        let batch_size = clips.len();
+        if batch_size == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Prepare audio as [batch_size, num_samples], padding shorter clips
+        let num_samples = clips.iter().map(|clip| clip.len()).max().unwrap_or(0);
+        let mut audio = Array2::<f32>::zeros((batch_size, num_samples));
+        let mut audio_attention_mask = Array2::<i64>::zeros((batch_size, num_samples));
+        for (row, clip) in clips.iter().enumerate() {
+            audio
+                .slice_mut(s![row, ..clip.len()])
+                .assign(&ArrayView1::from(*clip));
+            audio_attention_mask
+                .slice_mut(s![row, ..clip.len()])
+                .fill(1);
+        }
 
         // Run encoder once
-        log::trace!("Running encoder...");
-        let encoder_hidden_states = self.encode(&audio)?;
+        log::trace!("Running encoder on {} clip(s)...", batch_size);
+        let encoder_hidden_states = self.encode(&audio, &audio_attention_mask)?;
         log::trace!("Encoder output shape: {:?}", encoder_hidden_states.shape());
 
+        // Converted once and borrowed by every decoder step
+        let encoder_hidden_states = Value::from_array(encoder_hidden_states)?.into_dyn();
+        let encoder_attention_mask = Value::from_array(audio_attention_mask.into_dyn())?.into_dyn();
+
         // Initialize KV cache
-        let mut cache = KVCache::new(&self.variant);
+        let mut cache = KVCache::with_layout(&self.layout)?;
 
-        // Start with decoder_start_token_id
-        let mut tokens: Vec<i64> = vec![DECODER_START_TOKEN_ID];
-        let mut input_ids = Array2::from_shape_vec((1, 1), vec![DECODER_START_TOKEN_ID])?;
+        // Start every clip with decoder_start_token_id
+        let mut tokens: Vec<Vec<i64>> = vec![vec![DECODER_START_TOKEN_ID]; batch_size];
+        let mut finished: Vec<bool> = max_lengths.iter().map(|&max| max == 0).collect();
+        let mut input_ids = Array2::from_elem((batch_size, 1), DECODER_START_TOKEN_ID);
+        let steps = max_lengths.iter().copied().max().unwrap_or(0);
 
-        for i in 0..max_length {
+        for i in 0..steps {
             let use_cache_branch = i > 0;
 
             // Build decoder inputs
             let input_ids_dyn = input_ids.clone().into_dyn();
             let use_cache_branch_arr = ndarray::arr1(&[use_cache_branch]).into_dyn();
 
-            // Prepare cache inputs
-            let cache_inputs = cache.get_inputs();
-
             // Build inputs dynamically based on what decoder expects
-            let mut ort_inputs: Vec<(std::borrow::Cow<'_, str>, ort::value::DynValue)> = vec![
+            let mut ort_inputs: Vec<(std::borrow::Cow<'_, str>, SessionInputValue<'_>)> = vec![
                 (
                     "input_ids".into(),
-                    ort::value::Value::from_array(input_ids_dyn)?.into_dyn(),
+                    Value::from_array(input_ids_dyn)?.into_dyn().into(),
                 ),
                 (
                     "encoder_hidden_states".into(),
-                    ort::value::Value::from_array(encoder_hidden_states.clone())?.into_dyn(),
+                    (&encoder_hidden_states).into(),
                 ),
                 (
                     "use_cache_branch".into(),
-                    ort::value::Value::from_array(use_cache_branch_arr)?.into_dyn(),
+                    Value::from_array(use_cache_branch_arr)?.into_dyn().into(),
                 ),
             ];
 
@@ -201,58 +342,76 @@ impl MoonshineModel {
                 .decoder_input_names
                 .contains(&"encoder_attention_mask".to_string())
             {
-                let mask_dyn = audio_attention_mask.clone().into_dyn();
                 ort_inputs.push((
                     "encoder_attention_mask".into(),
-                    ort::value::Value::from_array(mask_dyn)?.into_dyn(),
+                    (&encoder_attention_mask).into(),
                 ));
             }
 
             // Add all cache inputs
-            for (name, arr) in cache_inputs {
-                ort_inputs.push((name.into(), ort::value::Value::from_array(arr)?.into_dyn()));
-            }
+            ort_inputs.extend(cache.inputs());
 
             // Run decoder
-            let outputs = self.decoder.run(ort_inputs)?;
+            let mut outputs = self.decoder.run(ort_inputs)?;
 
-            // Extract logits [1, seq_len, vocab_size]
+            // Extract logits [batch_size, seq_len, vocab_size]
             let logits = outputs
                 .get("logits")
                 .ok_or_else(|| MoonshineError::OutputNotFound("logits".to_string()))?
                 .try_extract_array::<f32>()?;
+            let last_pos = logits.shape()[1] - 1;
+
+            for row in 0..batch_size {
+                // Finished clips keep decoding EOS until the whole batch is done
+                if finished[row] {
+                    input_ids[[row, 0]] = EOS_TOKEN_ID;
+                    continue;
+                }
+
+                // Greedy decode: argmax(logits[row, -1, :])
+                let next_token = argmax(logits.slice(s![row, last_pos, ..]));
+                tokens[row].push(next_token);
+                input_ids[[row, 0]] = next_token;
+
+                if next_token == EOS_TOKEN_ID {
+                    log::trace!("EOS token reached for clip {} at position {}", row, i + 1);
+                    finished[row] = true;
+                } else if tokens[row].len() > max_lengths[row] {
+                    finished[row] = true;
+                }
+            }
 
-            // Greedy decode: argmax(logits[0, -1, :])
-            let logits_shape = logits.shape();
-            let last_pos = logits_shape[1] - 1;
-
-            let last_logits = logits.slice(ndarray::s![0, last_pos, ..]);
-            let next_token = last_logits
-                .iter()
-                .enumerate()
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-                .map(|(idx, _)| idx as i64)
-                .unwrap_or(EOS_TOKEN_ID);
-
-            tokens.push(next_token);
-
-            if next_token == EOS_TOKEN_ID {
-                log::trace!("EOS token reached at position {}", i + 1);
+            if finished.iter().all(|&done| done) {
                 break;
             }
 
-            // Update input_ids for next iteration
-            input_ids = Array2::from_shape_vec((1, 1), vec![next_token])?;
-
             // Update cache from outputs
-            cache.update_from_outputs(&outputs, use_cache_branch)?;
+            cache.update_from_outputs(&mut outputs, use_cache_branch);
         }
 
-        log::trace!("Generated {} tokens", tokens.len());
+        log::trace!(
+            "Generated {} tokens for {} clip(s)",
+            tokens.iter().map(Vec::len).sum::<usize>(),
+            batch_size
+        );
         Ok(tokens)
     }
 
     pub fn decode_tokens(&self, tokens: &[i64]) -> Result<String, MoonshineError> {
         self.tokenizer.decode(tokens)
     }
+
+    pub fn decode_pieces(&self, tokens: &[i64]) -> Vec<String> {
+        self.tokenizer.decode_pieces(tokens)
+    }
+}
+
+/// Index of the highest logit, or EOS for empty logits.
+fn argmax<'a>(logits: impl IntoIterator<Item = &'a f32>) -> i64 {
+    logits
+        .into_iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(idx, _)| idx as i64)
+        .unwrap_or(EOS_TOKEN_ID)
 }
@@ -0,0 +1,123 @@
+//! Moonshine streaming checkpoints.
+//!
+//! The streaming models pair a sliding-window encoder, whose cost grows
+//! with the audio rather than being padded to a fixed window, with the same
+//! cached decoder as the other Moonshine models. Their decoder sizes don't
+//! follow the [`ModelVariant`](super::ModelVariant) table, so the layout is
+//! read from the exported graph instead.
+
+use std::path::{Path, PathBuf};
+
+use super::engine::{
+    self, ExecutionProvider, MoonshineInferenceParams, MoonshineModelParams, OptimizationLevel,
+};
+use super::model::{MoonshineError, MoonshineModel};
+use crate::{TranscriptionEngine, TranscriptionResult};
+
+/// Most tokens the (English) streaming models produce per second of audio.
+const TOKEN_RATE: usize = 6;
+
+/// Parameters for loading a Moonshine streaming model.
+#[derive(Debug, Clone, Default)]
+pub struct StreamingModelParams {
+    /// Where ONNX Runtime runs the encoder and decoder (default: CPU).
+    pub execution_provider: ExecutionProvider,
+
+    /// Threads used within an operator. `None` lets ONNX Runtime use all
+    /// cores.
+    pub intra_threads: Option<usize>,
+
+    /// Threads used to run independent operators in parallel. `None` uses
+    /// ONNX Runtime's default.
+    pub inter_threads: Option<usize>,
+
+    /// Graph optimization level (default: all optimizations).
+    pub optimization_level: OptimizationLevel,
+
+    /// Directory to keep the optimized graphs in, so later loads skip that
+    /// work. Use one directory per model.
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl From<StreamingModelParams> for MoonshineModelParams {
+    fn from(params: StreamingModelParams) -> Self {
+        Self {
+            execution_provider: params.execution_provider,
+            intra_threads: params.intra_threads,
+            inter_threads: params.inter_threads,
+            optimization_level: params.optimization_level,
+            cache_dir: params.cache_dir,
+            ..Default::default()
+        }
+    }
+}
+
+/// Moonshine streaming transcription engine.
+///
+/// Implements the `TranscriptionEngine` trait for the Moonshine streaming
+/// checkpoints, which use the same directory layout as the other Moonshine
+/// models.
+pub struct MoonshineStreamingEngine {
+    loaded_model_path: Option<PathBuf>,
+    model: Option<MoonshineModel>,
+}
+
+impl MoonshineStreamingEngine {
+    /// Create a new Moonshine streaming engine (model not loaded).
+    pub fn new() -> Self {
+        Self {
+            loaded_model_path: None,
+            model: None,
+        }
+    }
+}
+
+impl Default for MoonshineStreamingEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MoonshineStreamingEngine {
+    fn drop(&mut self) {
+        self.unload_model();
+    }
+}
+
+impl TranscriptionEngine for MoonshineStreamingEngine {
+    type InferenceParams = MoonshineInferenceParams;
+    type ModelParams = StreamingModelParams;
+
+    fn load_model_with_params(
+        &mut self,
+        model_path: &Path,
+        params: Self::ModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.unload_model();
+
+        self.model = Some(MoonshineModel::load(model_path, &params.into(), None)?);
+        self.loaded_model_path = Some(model_path.to_path_buf());
+
+        log::info!("Loaded Moonshine streaming model from {:?}", model_path);
+
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        if self.model.is_some() {
+            log::debug!("Unloading Moonshine streaming model");
+            self.model = None;
+            self.loaded_model_path = None;
+        }
+    }
+
+    fn transcribe_samples(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<Self::InferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let model = self.model.as_mut().ok_or(MoonshineError::ModelNotLoaded)?;
+
+        engine::transcribe(model, &samples, TOKEN_RATE, &params.unwrap_or_default())
+    }
+}
@@ -0,0 +1,180 @@
+//! Estimated timestamps for Moonshine output.
+//!
+//! The decoder produces text only, so times are approximated: the part of
+//! the clip that contains speech is found from its loudness, and the tokens
+//! are spread over it, each taking time in proportion to its length.
+
+use crate::params::Granularity;
+use crate::TranscriptionSegment;
+
+const SAMPLE_RATE: usize = 16000;
+
+/// Length of the frames used to find where speech starts and ends.
+const FRAME_SAMPLES: usize = SAMPLE_RATE * 30 / 1000;
+
+/// Frames quieter than this fraction of the loudest frame count as silence.
+const SILENCE_RATIO: f32 = 0.1;
+
+/// Estimate segments for the decoded `pieces` (one per token) of `samples`.
+///
+/// `token_rate` is the most tokens the model produces per second of speech,
+/// which bounds how short the speech can be when the loudness-based
+/// estimate is too tight.
+pub(crate) fn estimate_segments(
+    pieces: &[String],
+    samples: &[f32],
+    token_rate: usize,
+    granularity: Granularity,
+) -> Vec<TranscriptionSegment> {
+    let pieces: Vec<&String> = pieces.iter().filter(|p| !p.trim().is_empty()).collect();
+    if pieces.is_empty() {
+        return Vec::new();
+    }
+
+    let duration = samples.len() as f32 / SAMPLE_RATE as f32;
+    let (mut start, mut end) = speech_span(samples);
+    let min_span = (pieces.len() as f32 / token_rate.max(1) as f32).min(duration);
+    if end - start < min_span {
+        let center = (start + end) / 2.0;
+        start = (center - min_span / 2.0).clamp(0.0, duration - min_span);
+        end = start + min_span;
+    }
+
+    // Split the speech between the tokens by their length in characters
+    let weights: Vec<usize> = pieces
+        .iter()
+        .map(|p| p.trim().chars().count().max(1))
+        .collect();
+    let total = weights.iter().sum::<usize>() as f32;
+    let mut tokens = Vec::with_capacity(pieces.len());
+    let mut elapsed = 0;
+    for (piece, weight) in pieces.iter().zip(&weights) {
+        let t0 = start + (end - start) * elapsed as f32 / total;
+        elapsed += weight;
+        let t1 = start + (end - start) * elapsed as f32 / total;
+        tokens.push(TranscriptionSegment {
+            start: t0,
+            end: t1,
+            text: piece.to_string(),
+        });
+    }
+
+    let segments = match granularity {
+        Granularity::Token => tokens,
+        Granularity::Word => merge(tokens, |_, next| next.text.starts_with(' ')),
+        Granularity::Segment => {
+            let words = merge(tokens, |_, next| next.text.starts_with(' '));
+            merge(words, |prev, _| {
+                prev.text.trim_end().ends_with(['.', '!', '?'])
+            })
+        }
+    };
+
+    segments
+        .into_iter()
+        .map(|seg| TranscriptionSegment {
+            text: seg.text.trim().to_string(),
+            ..seg
+        })
+        .collect()
+}
+
+/// Join consecutive segments, starting a new one wherever `splits(prev, next)`.
+fn merge(
+    segments: Vec<TranscriptionSegment>,
+    splits: impl Fn(&TranscriptionSegment, &TranscriptionSegment) -> bool,
+) -> Vec<TranscriptionSegment> {
+    let mut merged: Vec<TranscriptionSegment> = Vec::new();
+    for seg in segments {
+        match merged.last_mut() {
+            Some(last) if !splits(last, &seg) => {
+                last.end = seg.end;
+                last.text.push_str(&seg.text);
+            }
+            _ => merged.push(seg),
+        }
+    }
+    merged
+}
+
+/// Start and end of the audible part of `samples`, in seconds. The whole
+/// clip if it is silent throughout.
+fn speech_span(samples: &[f32]) -> (f32, f32) {
+    let energies: Vec<f32> = samples
+        .chunks(FRAME_SAMPLES)
+        .map(|frame| (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt())
+        .collect();
+    let duration = samples.len() as f32 / SAMPLE_RATE as f32;
+    let peak = energies.iter().copied().fold(0.0, f32::max);
+    if peak <= 0.0 {
+        return (0.0, duration);
+    }
+
+    let threshold = peak * SILENCE_RATIO;
+    let first = energies.iter().position(|&e| e >= threshold).unwrap_or(0);
+    let last = energies.iter().rposition(|&e| e >= threshold).unwrap_or(0);
+    let frame_secs = FRAME_SAMPLES as f32 / SAMPLE_RATE as f32;
+    (
+        first as f32 * frame_secs,
+        ((last + 1) as f32 * frame_secs).min(duration),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pieces(text: &[&str]) -> Vec<String> {
+        text.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// One second of silence, two seconds of tone, one second of silence.
+    fn audio() -> Vec<f32> {
+        let mut samples = vec![0.0; SAMPLE_RATE];
+        samples.extend((0..2 * SAMPLE_RATE).map(|i| (i as f32 * 0.05).sin() * 0.5));
+        samples.extend(vec![0.0; SAMPLE_RATE]);
+        samples
+    }
+
+    #[test]
+    fn test_tokens_cover_speech() {
+        let segments = estimate_segments(
+            &pieces(&[" ask", " not", "."]),
+            &audio(),
+            6,
+            Granularity::Token,
+        );
+        assert_eq!(segments.len(), 3);
+        assert!((segments[0].start - 1.0).abs() < 0.05);
+        assert!((segments[2].end - 3.0).abs() < 0.05);
+        // Longer tokens take longer
+        assert!(segments[0].end - segments[0].start > segments[2].end - segments[2].start);
+    }
+
+    #[test]
+    fn test_words_and_sentences() {
+        let tokens = pieces(&[
+            " And", " so", " my", " fell", "ow", " Americ", "ans", ".", " Ask",
+        ]);
+
+        let words = estimate_segments(&tokens, &audio(), 6, Granularity::Word);
+        let texts: Vec<&str> = words.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["And", "so", "my", "fellow", "Americans.", "Ask"]);
+
+        let sentences = estimate_segments(&tokens, &audio(), 6, Granularity::Segment);
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(sentences[0].text, "And so my fellow Americans.");
+        assert_eq!(sentences[0].end, sentences[1].start);
+    }
+
+    #[test]
+    fn test_token_rate_bounds_short_speech() {
+        // A click of speech cannot hold 12 tokens at 6 tokens per second
+        let mut samples = vec![0.0; 4 * SAMPLE_RATE];
+        samples[2 * SAMPLE_RATE] = 1.0;
+        let tokens = pieces(&[" word"; 12]);
+        let segments = estimate_segments(&tokens, &samples, 6, Granularity::Segment);
+        assert_eq!(segments.len(), 1);
+        assert!((segments[0].end - segments[0].start - 2.0).abs() < 0.01);
+    }
+}
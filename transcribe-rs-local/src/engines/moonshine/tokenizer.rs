@@ -26,11 +26,13 @@ impl MoonshineTokenizer {
 
         log::info!("Loading tokenizer from {:?}...", tokenizer_path);
 
-        let file = File::open(&tokenizer_path)
-            .map_err(|e| MoonshineError::Tokenization(format!("Failed to open tokenizer: {}", e)))?;
+        let file = File::open(&tokenizer_path).map_err(|e| {
+            MoonshineError::Tokenization(format!("Failed to open tokenizer: {}", e))
+        })?;
         let reader = BufReader::new(file);
-        let json: serde_json::Value = serde_json::from_reader(reader)
-            .map_err(|e| MoonshineError::Tokenization(format!("Failed to parse tokenizer JSON: {}", e)))?;
+        let json: serde_json::Value = serde_json::from_reader(reader).map_err(|e| {
+            MoonshineError::Tokenization(format!("Failed to parse tokenizer JSON: {}", e))
+        })?;
 
         // Build id → token vocabulary (inverse of the stored token → id mapping)
         let mut vocab = HashMap::new();
@@ -56,7 +58,10 @@ impl MoonshineTokenizer {
         let mut special_token_ids = Vec::new();
         if let Some(added_tokens) = json.get("added_tokens").and_then(|v| v.as_array()) {
             for token in added_tokens {
-                let is_special = token.get("special").and_then(|v| v.as_bool()).unwrap_or(false);
+                let is_special = token
+                    .get("special")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
                 if is_special {
                     if let Some(id) = token.get("id").and_then(|v| v.as_u64()) {
                         special_token_ids.push(id as u32);
@@ -119,6 +124,41 @@ impl MoonshineTokenizer {
         Ok(text.to_string())
     }
 
+    /// Decode each token to its own piece of text, e.g. for timestamps.
+    ///
+    /// Special and unknown tokens are skipped, and runs of byte fallback
+    /// tokens are merged into one piece so multi-byte characters stay whole.
+    /// Pieces keep their leading space; joined they give the full text.
+    pub fn decode_pieces(&self, token_ids: &[i64]) -> Vec<String> {
+        let mut pieces = Vec::with_capacity(token_ids.len());
+        let mut bytes: Vec<u8> = Vec::new();
+
+        for &id in token_ids {
+            let id = id as u32;
+            if self.special_token_ids.contains(&id) {
+                continue;
+            }
+            let Some(token) = self.vocab.get(&id) else {
+                continue;
+            };
+
+            if let Some(byte_val) = Self::parse_byte_token(token) {
+                bytes.push(byte_val);
+            } else {
+                if !bytes.is_empty() {
+                    pieces.push(String::from_utf8_lossy(&bytes).into_owned());
+                    bytes.clear();
+                }
+                pieces.push(token.replace('▁', " "));
+            }
+        }
+        if !bytes.is_empty() {
+            pieces.push(String::from_utf8_lossy(&bytes).into_owned());
+        }
+
+        pieces
+    }
+
     /// Parse a byte fallback token like "<0x41>" and return the byte value
     fn parse_byte_token(token: &str) -> Option<u8> {
         if token.starts_with("<0x") && token.ends_with('>') && token.len() == 6 {
@@ -0,0 +1,213 @@
+//! macOS backend built on the Speech framework's `SFSpeechRecognizer`.
+//!
+//! The framework has no Rust bindings, so its classes are looked up at
+//! runtime and driven with `msg_send!`.
+
+use std::ffi::CStr;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use block2::RcBlock;
+use objc2::rc::{Allocated, Retained};
+use objc2::runtime::{AnyClass, AnyObject};
+use objc2::{msg_send, sel};
+use objc2_foundation::{NSError, NSLocale, NSOperationQueue, NSString, NSURL};
+
+use super::{NativeError, NativeModelParams, Recognition};
+use crate::TranscriptionSegment;
+
+#[link(name = "Speech", kind = "framework")]
+extern "C" {}
+
+/// `SFSpeechRecognizerAuthorizationStatus` values.
+const STATUS_NOT_DETERMINED: isize = 0;
+const STATUS_AUTHORIZED: isize = 3;
+
+/// Error code reported when the audio contains no speech.
+const NO_SPEECH_DETECTED: isize = 1110;
+
+/// How long to wait for the user to answer the permission prompt.
+const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+pub(super) fn is_available(locale: Option<&str>) -> bool {
+    recognizer(locale, false).is_ok()
+}
+
+pub(super) fn prepare(params: &NativeModelParams) -> Result<(), NativeError> {
+    authorize()?;
+    recognizer(params.locale.as_deref(), params.allow_network).map(|_| ())
+}
+
+pub(super) fn recognize(
+    wav_path: &Path,
+    params: &NativeModelParams,
+    timeout: Duration,
+) -> Result<Recognition, NativeError> {
+    let recognizer = recognizer(params.locale.as_deref(), params.allow_network)?;
+    let url = NSURL::fileURLWithPath(&NSString::from_str(&wav_path.to_string_lossy()));
+
+    let (tx, rx) = mpsc::channel();
+    let handler = RcBlock::new(move |result: *mut AnyObject, error: *mut NSError| {
+        // Called for every partial result; only the final one (or an error)
+        // is sent back
+        if let Some(outcome) = unsafe { final_outcome(result.as_ref(), error.as_ref()) } {
+            let _ = tx.send(outcome);
+        }
+    });
+
+    let task: Option<Retained<AnyObject>> = unsafe {
+        let request: Allocated<AnyObject> =
+            msg_send![class(c"SFSpeechURLRecognitionRequest")?, alloc];
+        let request: Option<Retained<AnyObject>> = msg_send![request, initWithURL: &*url];
+        let request = request.ok_or_else(|| {
+            NativeError::Unavailable("could not create recognition request".to_string())
+        })?;
+
+        let _: () = msg_send![&*request, setShouldReportPartialResults: false];
+        let _: () = msg_send![&*request, setRequiresOnDeviceRecognition: !params.allow_network];
+        // macOS 13+
+        let punctuation: bool = msg_send![&*request, respondsToSelector: sel!(setAddsPunctuation:)];
+        if punctuation {
+            let _: () = msg_send![&*request, setAddsPunctuation: true];
+        }
+
+        msg_send![&*recognizer, recognitionTaskWithRequest: &*request, resultHandler: &*handler]
+    };
+
+    match rx.recv_timeout(timeout) {
+        Ok(outcome) => outcome,
+        Err(_) => {
+            if let Some(task) = task {
+                let _: () = unsafe { msg_send![&*task, cancel] };
+            }
+            Err(NativeError::Timeout(timeout))
+        }
+    }
+}
+
+fn class(name: &CStr) -> Result<&'static AnyClass, NativeError> {
+    AnyClass::get(name)
+        .ok_or_else(|| NativeError::Unavailable(format!("{} not found", name.to_string_lossy())))
+}
+
+/// Ask for speech recognition permission if the user has not decided yet.
+fn authorize() -> Result<(), NativeError> {
+    let cls = class(c"SFSpeechRecognizer")?;
+    let mut status: isize = unsafe { msg_send![cls, authorizationStatus] };
+
+    if status == STATUS_NOT_DETERMINED {
+        let (tx, rx) = mpsc::channel();
+        let handler = RcBlock::new(move |status: isize| {
+            let _ = tx.send(status);
+        });
+        let _: () = unsafe { msg_send![cls, requestAuthorization: &*handler] };
+        status = rx
+            .recv_timeout(AUTHORIZATION_TIMEOUT)
+            .unwrap_or(STATUS_NOT_DETERMINED);
+    }
+
+    if status == STATUS_AUTHORIZED {
+        Ok(())
+    } else {
+        Err(NativeError::NotAuthorized)
+    }
+}
+
+/// Create a recognizer for `locale` that delivers results on its own queue.
+fn recognizer(
+    locale: Option<&str>,
+    allow_network: bool,
+) -> Result<Retained<AnyObject>, NativeError> {
+    let cls = class(c"SFSpeechRecognizer")?;
+    let recognizer: Option<Retained<AnyObject>> = unsafe {
+        let allocated: Allocated<AnyObject> = msg_send![cls, alloc];
+        match locale {
+            Some(locale) => {
+                let locale = NSLocale::localeWithLocaleIdentifier(&NSString::from_str(locale));
+                msg_send![allocated, initWithLocale: &*locale]
+            }
+            None => msg_send![allocated, init],
+        }
+    };
+    // init returns nil for unsupported locales
+    let recognizer = recognizer.ok_or_else(|| {
+        NativeError::Unavailable(format!(
+            "locale not supported: {}",
+            locale.unwrap_or("system")
+        ))
+    })?;
+
+    unsafe {
+        let available: bool = msg_send![&*recognizer, isAvailable];
+        if !available {
+            return Err(NativeError::Unavailable(
+                "recognizer is currently unavailable".to_string(),
+            ));
+        }
+
+        let on_device: bool = msg_send![&*recognizer, supportsOnDeviceRecognition];
+        if !on_device && !allow_network {
+            return Err(NativeError::Unavailable(format!(
+                "no on-device model for locale {}",
+                locale.unwrap_or("system")
+            )));
+        }
+
+        // Results default to the main queue, which would deadlock a caller
+        // blocking on the main thread
+        let queue = NSOperationQueue::new();
+        let _: () = msg_send![&*recognizer, setQueue: &*queue];
+    }
+
+    Ok(recognizer)
+}
+
+/// Turn a result handler invocation into the final outcome, or `None` while
+/// recognition is still in progress.
+unsafe fn final_outcome(
+    result: Option<&AnyObject>,
+    error: Option<&NSError>,
+) -> Option<Result<Recognition, NativeError>> {
+    if let Some(error) = error {
+        if error.code() == NO_SPEECH_DETECTED {
+            return Some(Ok(Recognition {
+                text: String::new(),
+                segments: Vec::new(),
+            }));
+        }
+        return Some(Err(NativeError::Recognition(
+            error.localizedDescription().to_string(),
+        )));
+    }
+
+    let result = result?;
+    let is_final: bool = msg_send![result, isFinal];
+    if !is_final {
+        return None;
+    }
+
+    let transcription: Retained<AnyObject> = msg_send![result, bestTranscription];
+    let text: Retained<NSString> = msg_send![&*transcription, formattedString];
+    let items: Retained<AnyObject> = msg_send![&*transcription, segments];
+    let count: usize = msg_send![&*items, count];
+
+    let segments = (0..count)
+        .map(|i| {
+            let segment: Retained<AnyObject> = msg_send![&*items, objectAtIndex: i];
+            let substring: Retained<NSString> = msg_send![&*segment, substring];
+            let timestamp: f64 = msg_send![&*segment, timestamp];
+            let duration: f64 = msg_send![&*segment, duration];
+            TranscriptionSegment {
+                start: timestamp as f32,
+                end: (timestamp + duration) as f32,
+                text: substring.to_string(),
+            }
+        })
+        .collect();
+
+    Some(Ok(Recognition {
+        text: text.to_string(),
+        segments,
+    }))
+}
@@ -0,0 +1,248 @@
+//! OS-native speech recognition engine.
+//!
+//! Uses the speech recognizer that ships with the operating system, so
+//! nothing has to be downloaded. Accuracy is below the dedicated models, which
+//! makes this engine best suited as a fallback until a model is available.
+//!
+//! # Backends
+//!
+//! | Platform | Recognizer |
+//! |----------|------------|
+//! | macOS | `SFSpeechRecognizer` (Speech framework), on-device only by default |
+//! | Windows | SAPI in-process dictation recognizer |
+//!
+//! macOS' newer `SpeechAnalyzer` API is Swift-only and cannot be called from
+//! Rust; `SFSpeechRecognizer` uses the same on-device models. Windows'
+//! `Windows.Media.SpeechRecognition` only listens to the microphone, whereas
+//! SAPI accepts audio from a stream.
+//!
+//! On other platforms the module builds, but [`NativeEngine::is_available`]
+//! returns `false` and loading fails with [`NativeError::Unsupported`].
+//!
+//! # Permissions
+//!
+//! On macOS the user is asked to allow speech recognition on first use, and
+//! the host application's `Info.plist` must contain
+//! `NSSpeechRecognitionUsageDescription`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::path::{Path, PathBuf};
+//! use transcribe_rs::{TranscriptionEngine, engines::native::{NativeEngine, NativeModelParams}};
+//!
+//! let mut engine = NativeEngine::new();
+//! // There is no model file; the path is ignored
+//! engine.load_model_with_params(
+//!     Path::new(""),
+//!     NativeModelParams {
+//!         locale: Some("en-US".to_string()),
+//!         ..Default::default()
+//!     },
+//! )?;
+//!
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), None)?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod unsupported;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "macos")]
+use macos as backend;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use unsupported as backend;
+#[cfg(target_os = "windows")]
+use windows as backend;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use derive_builder::Builder;
+
+use crate::{vocabulary, TranscriptionEngine, TranscriptionResult, TranscriptionSegment};
+
+const SAMPLE_RATE: u32 = 16000;
+
+#[derive(thiserror::Error, Debug)]
+pub enum NativeError {
+    #[error("Native speech recognition is not supported on this platform")]
+    Unsupported,
+    #[error("Speech recognition permission was denied")]
+    NotAuthorized,
+    #[error("Speech recognizer unavailable: {0}")]
+    Unavailable(String),
+    #[error("Speech recognition timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("Speech recognition failed: {0}")]
+    Recognition(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("WAV error: {0}")]
+    Wav(#[from] hound::Error),
+    #[error("Recognizer not loaded")]
+    ModelNotLoaded,
+}
+
+/// Parameters for setting up the recognizer.
+#[derive(Debug, Clone, Default)]
+pub struct NativeModelParams {
+    /// Recognition locale, e.g. `"en-US"`. `None` uses the system language.
+    /// On Windows the installed SAPI recognizer determines the language and
+    /// this is ignored.
+    pub locale: Option<String>,
+
+    /// macOS: allow sending audio to Apple's servers when the locale has no
+    /// on-device model. Off by default, keeping audio on the device.
+    pub allow_network: bool,
+}
+
+/// Parameters for inference.
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into), default)]
+pub struct NativeInferenceParams {
+    /// Give up if recognition takes longer than this.
+    pub timeout: Duration,
+
+    /// Domain terms and names whose near-miss spellings are corrected in the
+    /// output.
+    pub vocabulary: Vec<String>,
+}
+
+impl NativeInferenceParams {
+    pub fn builder() -> NativeInferenceParamsBuilder {
+        NativeInferenceParamsBuilder::default()
+    }
+}
+
+impl Default for NativeInferenceParams {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(60),
+            vocabulary: Vec::new(),
+        }
+    }
+}
+
+/// Text and timed segments produced by a backend.
+pub(crate) struct Recognition {
+    pub text: String,
+    pub segments: Vec<TranscriptionSegment>,
+}
+
+/// OS-native speech recognition engine.
+///
+/// Implements the `TranscriptionEngine` trait on top of the platform
+/// recognizer. Loading checks availability and permissions; the model path
+/// is ignored.
+pub struct NativeEngine {
+    params: Option<NativeModelParams>,
+}
+
+impl NativeEngine {
+    /// Create a new native engine (not loaded).
+    pub fn new() -> Self {
+        Self { params: None }
+    }
+
+    /// Whether the platform has a usable recognizer for `locale` (`None` for
+    /// the system language).
+    pub fn is_available(locale: Option<&str>) -> bool {
+        backend::is_available(locale)
+    }
+}
+
+impl Default for NativeEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranscriptionEngine for NativeEngine {
+    type InferenceParams = NativeInferenceParams;
+    type ModelParams = NativeModelParams;
+
+    fn load_model_with_params(
+        &mut self,
+        _model_path: &Path,
+        params: Self::ModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        backend::prepare(&params)?;
+        log::info!(
+            "Using native speech recognizer (locale: {})",
+            params.locale.as_deref().unwrap_or("system")
+        );
+        self.params = Some(params);
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        self.params = None;
+    }
+
+    fn transcribe_samples(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<Self::InferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let model_params = self.params.as_ref().ok_or(NativeError::ModelNotLoaded)?;
+        let params = params.unwrap_or_default();
+
+        // Both recognizers read audio from a file
+        let wav = TempWav::write(&samples)?;
+        let recognition = backend::recognize(wav.path(), model_params, params.timeout)?;
+
+        let mut result = TranscriptionResult {
+            text: recognition.text,
+            segments: Some(recognition.segments),
+            language: None,
+        };
+        vocabulary::apply(&mut result, &params.vocabulary);
+
+        Ok(result)
+    }
+}
+
+/// 16-bit PCM WAV file in the temp directory, removed on drop.
+struct TempWav(PathBuf);
+
+impl TempWav {
+    fn write(samples: &[f32]) -> Result<Self, NativeError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "transcribe-rs-native-{}-{}.wav",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec)?;
+        for sample in samples {
+            writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+        }
+        writer.finalize()?;
+
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempWav {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
@@ -0,0 +1,22 @@
+//! Fallback for platforms without a supported native recognizer.
+
+use std::path::Path;
+use std::time::Duration;
+
+use super::{NativeError, NativeModelParams, Recognition};
+
+pub(super) fn is_available(_locale: Option<&str>) -> bool {
+    false
+}
+
+pub(super) fn prepare(_params: &NativeModelParams) -> Result<(), NativeError> {
+    Err(NativeError::Unsupported)
+}
+
+pub(super) fn recognize(
+    _wav_path: &Path,
+    _params: &NativeModelParams,
+    _timeout: Duration,
+) -> Result<Recognition, NativeError> {
+    Err(NativeError::Unsupported)
+}
@@ -0,0 +1,194 @@
+//! Windows backend built on the SAPI in-process dictation recognizer.
+//!
+//! SAPI is driven from a dedicated thread in the multithreaded apartment so
+//! callers on an STA thread (such as a UI thread) are not affected.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use windows::core::{Interface, HSTRING, PCWSTR, PWSTR};
+use windows::Win32::Foundation::BOOL;
+use windows::Win32::Media::Speech::{
+    ISpRecoContext, ISpRecoGrammar, ISpRecoResult, ISpRecognizer, ISpStream, SpInprocRecognizer,
+    SpStream, SPEI_END_SR_STREAM, SPEI_RECOGNITION, SPEI_RESERVED1, SPEI_RESERVED2, SPEVENT,
+    SPFM_OPEN_READONLY, SPLO_STATIC, SPRECORESULTTIMES, SPRS_ACTIVE,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_ALL,
+    COINIT_MULTITHREADED,
+};
+
+use super::{NativeError, NativeModelParams, Recognition};
+use crate::TranscriptionSegment;
+
+/// SAPI times are in 100 ns units.
+const TICKS_PER_SECOND: f64 = 10_000_000.0;
+
+pub(super) fn is_available(_locale: Option<&str>) -> bool {
+    on_com_thread(|| {
+        unsafe { dictation_context(None) }
+            .map(|_| ())
+            .map_err(com_error)
+    })
+    .is_ok()
+}
+
+pub(super) fn prepare(_params: &NativeModelParams) -> Result<(), NativeError> {
+    on_com_thread(|| {
+        unsafe { dictation_context(None) }
+            .map(|_| ())
+            .map_err(|e| NativeError::Unavailable(e.to_string()))
+    })
+}
+
+pub(super) fn recognize(
+    wav_path: &Path,
+    _params: &NativeModelParams,
+    timeout: Duration,
+) -> Result<Recognition, NativeError> {
+    let wav_path = wav_path.to_path_buf();
+    on_com_thread(move || {
+        unsafe { recognize_file(&wav_path, timeout) }
+            .map_err(com_error)?
+            .ok_or(NativeError::Timeout(timeout))
+    })
+}
+
+/// Run `f` on a new thread with COM initialized for the multithreaded apartment.
+fn on_com_thread<T, F>(f: F) -> Result<T, NativeError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, NativeError> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }
+            .ok()
+            .map_err(com_error)?;
+        let result = f();
+        unsafe { CoUninitialize() };
+        result
+    })
+    .join()
+    .map_err(|_| NativeError::Recognition("recognizer thread panicked".to_string()))?
+}
+
+fn com_error(error: windows::core::Error) -> NativeError {
+    NativeError::Recognition(error.to_string())
+}
+
+/// Recognition context with the dictation grammar active. The grammar is
+/// kept alive for as long as the context is used.
+struct Dictation {
+    context: ISpRecoContext,
+    _grammar: ISpRecoGrammar,
+}
+
+/// Set up dictation reading from `wav_path` or, without a path, from the
+/// default audio input.
+unsafe fn dictation_context(wav_path: Option<&Path>) -> windows::core::Result<Dictation> {
+    let recognizer: ISpRecognizer = CoCreateInstance(&SpInprocRecognizer, None, CLSCTX_ALL)?;
+
+    if let Some(wav_path) = wav_path {
+        let stream: ISpStream = CoCreateInstance(&SpStream, None, CLSCTX_ALL)?;
+        // The format is read from the WAV header
+        stream.BindToFile(&HSTRING::from(wav_path), SPFM_OPEN_READONLY, None, None, 0)?;
+        recognizer.SetInput(&stream, BOOL::from(true))?;
+    }
+
+    let context = recognizer.CreateRecoContext()?;
+    let interest = event_mask(&[SPEI_RECOGNITION.0, SPEI_END_SR_STREAM.0]);
+    context.SetNotifyWin32Event()?;
+    context.SetInterest(interest, interest)?;
+
+    let grammar = context.CreateGrammar(0)?;
+    grammar.LoadDictation(PCWSTR::null(), SPLO_STATIC)?;
+    grammar.SetDictationState(SPRS_ACTIVE)?;
+
+    Ok(Dictation {
+        context,
+        _grammar: grammar,
+    })
+}
+
+/// SPFEI(): event flags plus the two reserved bits SAPI requires.
+fn event_mask(events: &[i32]) -> u64 {
+    events
+        .iter()
+        .chain(&[SPEI_RESERVED1.0, SPEI_RESERVED2.0])
+        .fold(0, |mask, &id| mask | (1u64 << id))
+}
+
+/// Recognize the whole file, returning `None` if `timeout` elapses first.
+unsafe fn recognize_file(
+    wav_path: &Path,
+    timeout: Duration,
+) -> windows::core::Result<Option<Recognition>> {
+    let dictation = dictation_context(Some(wav_path))?;
+    let context = &dictation.context;
+    let deadline = Instant::now() + timeout;
+    let mut segments = Vec::new();
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+        // Returns S_FALSE when nothing arrived in time, which is not an error
+        context.WaitForNotifyEvent(remaining.as_millis().min(u32::MAX as u128) as u32)?;
+
+        loop {
+            let mut event = SPEVENT::default();
+            let mut fetched = 0;
+            context.GetEvents(1, &mut event, &mut fetched)?;
+            if fetched == 0 {
+                break;
+            }
+
+            // The event id is the low word of the bitfield
+            match event._bitfield & 0xFFFF {
+                id if id == SPEI_RECOGNITION.0 => {
+                    // lParam holds a reference to the result, released on drop
+                    let result = ISpRecoResult::from_raw(event.lParam.0 as *mut _);
+                    if let Some(segment) = result_segment(&result)? {
+                        segments.push(segment);
+                    }
+                }
+                id if id == SPEI_END_SR_STREAM.0 => {
+                    let text = segments
+                        .iter()
+                        .map(|s| s.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    return Ok(Some(Recognition { text, segments }));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+unsafe fn result_segment(
+    result: &ISpRecoResult,
+) -> windows::core::Result<Option<TranscriptionSegment>> {
+    let mut text = PWSTR::null();
+    // SP_GETWHOLEPHRASE for both start and count, with display replacements
+    result.GetText(u32::MAX, u32::MAX, BOOL::from(true), &mut text, None)?;
+    let phrase = text.to_string().unwrap_or_default();
+    CoTaskMemFree(Some(text.0 as *const _));
+
+    let phrase = phrase.trim();
+    if phrase.is_empty() {
+        return Ok(None);
+    }
+
+    let mut times = SPRECORESULTTIMES::default();
+    result.GetResultTimes(&mut times)?;
+    let start = times.ullStart as f64 / TICKS_PER_SECOND;
+    let end = start + times.ullLength as f64 / TICKS_PER_SECOND;
+
+    Ok(Some(TranscriptionSegment {
+        start: start as f32,
+        end: end as f32,
+        text: phrase.to_string(),
+    }))
+}
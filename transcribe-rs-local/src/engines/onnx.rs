@@ -0,0 +1,101 @@
+//! ONNX Runtime helpers shared by the NeMo engines (Parakeet and Canary), the
+//! CTC engines (SenseVoice and GigaAM) and the alignment model.
+
+use ort::execution_providers::CPUExecutionProvider;
+use ort::session::builder::{GraphOptimizationLevel, SessionBuilder};
+use ort::session::Session;
+use std::path::Path;
+#[cfg(feature = "parakeet")]
+use std::path::PathBuf;
+
+use super::session_cache;
+
+/// Load `<model_name>.onnx` from `model_dir`, preferring
+/// `<model_name>.int8.onnx` when `try_quantized` is set and it exists.
+pub(crate) fn init_session<P: AsRef<Path>>(
+    model_dir: P,
+    model_name: &str,
+    intra_threads: Option<usize>,
+    try_quantized: bool,
+) -> Result<Session, ort::Error> {
+    // Try quantized version first if requested, fallback to regular version
+    let model_filename = if try_quantized {
+        let quantized_name = format!("{}.int8.onnx", model_name);
+        let quantized_path = model_dir.as_ref().join(&quantized_name);
+        if quantized_path.exists() {
+            log::info!("Loading quantized model from {}...", quantized_name);
+            quantized_name
+        } else {
+            let regular_name = format!("{}.onnx", model_name);
+            log::info!(
+                "Quantized model not found, loading regular model from {}...",
+                regular_name
+            );
+            regular_name
+        }
+    } else {
+        let regular_name = format!("{}.onnx", model_name);
+        log::info!("Loading model from {}...", regular_name);
+        regular_name
+    };
+
+    load_session(
+        &model_dir.as_ref().join(model_filename),
+        intra_threads,
+        None,
+    )
+}
+
+/// The first of `<model_name><suffix>.onnx` in `model_dir` that exists,
+/// trying `suffixes` in order, with the index of its suffix.
+#[cfg(feature = "parakeet")]
+pub(crate) fn find_variant<P: AsRef<Path>>(
+    model_dir: P,
+    model_name: &str,
+    suffixes: &[&str],
+) -> Option<(usize, PathBuf)> {
+    suffixes.iter().enumerate().find_map(|(index, suffix)| {
+        let path = model_dir
+            .as_ref()
+            .join(format!("{}{}.onnx", model_name, suffix));
+        path.exists().then_some((index, path))
+    })
+}
+
+/// Load the ONNX model at `path` on the CPU, keeping its optimized graph in
+/// `cache_dir` if given.
+pub(crate) fn load_session(
+    path: &Path,
+    intra_threads: Option<usize>,
+    cache_dir: Option<&Path>,
+) -> Result<Session, ort::Error> {
+    let builder = || -> ort::Result<SessionBuilder> {
+        let providers = vec![CPUExecutionProvider::default().build()];
+
+        let mut builder = Session::builder()?
+            .with_optimization_level(GraphOptimizationLevel::Level3)?
+            .with_execution_providers(providers)?
+            .with_parallel_execution(true)?;
+
+        if let Some(threads) = intra_threads {
+            builder = builder
+                .with_intra_threads(threads)?
+                .with_inter_threads(threads)?;
+        }
+        Ok(builder)
+    };
+
+    let session = session_cache::commit(builder, path, cache_dir)?;
+
+    let model_filename = path.file_name().unwrap_or_default().to_string_lossy();
+    for input in &session.inputs {
+        log::info!(
+            "Model '{}' input: name={}, type={:?}",
+            model_filename,
+            input.name,
+            input.input_type
+        );
+    }
+
+    Ok(session)
+}
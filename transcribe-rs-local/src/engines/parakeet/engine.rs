@@ -65,6 +65,7 @@
 //!
 //! let params = ParakeetInferenceParams {
 //!     timestamp_granularity: TimestampGranularity::Word,  // Get word-level timestamps
+//!     ..Default::default()
 //! };
 //!
 //! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), Some(params))?;
@@ -78,9 +79,15 @@
 //! ```
 
 use crate::{
-    engines::parakeet::{model::ParakeetModel, timestamps::convert_timestamps},
-    TranscriptionEngine, TranscriptionResult,
+    engines::parakeet::{
+        model::{ParakeetError, ParakeetModel, TimestampedResult},
+        stream::{ParakeetStream, StreamingConfig},
+        timestamps::{build_utterance, convert_timestamps, Utterance},
+    },
+    postprocess::{self, PostProcessOptions},
+    vocabulary, TranscriptionEngine, TranscriptionResult,
 };
+use derive_builder::Builder;
 use std::path::{Path, PathBuf};
 
 /// Granularity level for timestamp generation.
@@ -102,13 +109,108 @@ pub enum TimestampGranularity {
 ///
 /// Controls the precision/performance trade-off for the loaded model.
 /// Int8 quantization provides faster inference at the cost of some accuracy.
-#[derive(Debug, Clone, Default, PartialEq)]
+/// When a model directory lacks the files for the requested precision,
+/// another one is loaded instead; see [`ParakeetEngine::precision`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum QuantizationType {
     /// Full precision (32-bit floating point, default)
     #[default]
     FP32,
+    /// Half precision (16-bit floating point). Half the size of FP32, but
+    /// ONNX Runtime's CPU provider computes most operators in FP32 anyway,
+    /// so it is rarely faster on the CPU.
+    FP16,
     /// 8-bit integer quantization (faster, slightly lower accuracy)
     Int8,
+    /// The precision [`QuantizationType::recommended`] picks for this machine
+    Auto,
+}
+
+impl QuantizationType {
+    /// The precision that decodes fastest on this machine's CPU.
+    ///
+    /// Int8 on CPUs with fast integer SIMD (AVX2 on x86-64, NEON on ARM64),
+    /// FP32 elsewhere, where ONNX Runtime's int8 kernels fall back to slow
+    /// paths. `benches/parakeet.rs` checks the recommendation against all
+    /// precisions on the machine it runs on.
+    pub fn recommended() -> Self {
+        if fast_int8() {
+            QuantizationType::Int8
+        } else {
+            QuantizationType::FP32
+        }
+    }
+
+    /// `Auto` replaced by the recommendation.
+    pub fn resolve(self) -> Self {
+        match self {
+            QuantizationType::Auto => Self::recommended(),
+            precision => precision,
+        }
+    }
+
+    /// Precisions to load, starting with this one and then the next best
+    /// fallbacks.
+    pub(crate) fn fallback_order(self) -> [QuantizationType; 3] {
+        match self.resolve() {
+            QuantizationType::FP16 => [
+                QuantizationType::FP16,
+                QuantizationType::FP32,
+                QuantizationType::Int8,
+            ],
+            QuantizationType::Int8 => [
+                QuantizationType::Int8,
+                QuantizationType::FP32,
+                QuantizationType::FP16,
+            ],
+            _ => [
+                QuantizationType::FP32,
+                QuantizationType::FP16,
+                QuantizationType::Int8,
+            ],
+        }
+    }
+
+    /// Model file name suffix before `.onnx`, e.g. `.int8`.
+    pub(crate) fn file_suffix(self) -> &'static str {
+        match self.resolve() {
+            QuantizationType::FP16 => ".fp16",
+            QuantizationType::Int8 => ".int8",
+            _ => "",
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn fast_int8() -> bool {
+    std::arch::is_x86_feature_detected!("avx2")
+}
+
+#[cfg(target_arch = "aarch64")]
+fn fast_int8() -> bool {
+    true
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn fast_int8() -> bool {
+    false
+}
+
+/// Precision a Parakeet model was loaded with, per ONNX model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadedPrecision {
+    /// The precision asked for, with `Auto` resolved.
+    pub requested: QuantizationType,
+    pub encoder: QuantizationType,
+    pub decoder_joint: QuantizationType,
+}
+
+impl LoadedPrecision {
+    /// Whether a model had to be loaded at another precision than
+    /// requested, because its files were missing.
+    pub fn is_fallback(&self) -> bool {
+        self.encoder != self.requested || self.decoder_joint != self.requested
+    }
 }
 
 /// Parameters for configuring Parakeet model loading.
@@ -118,6 +220,12 @@ pub enum QuantizationType {
 pub struct ParakeetModelParams {
     /// The quantization type to use for the model
     pub quantization: QuantizationType,
+    /// Chunking for [`ParakeetEngine::stream`] with cache-aware streaming
+    /// models; ignored by offline models
+    pub streaming: StreamingConfig,
+    /// Directory to keep the optimized ONNX graphs in, so later loads of
+    /// the model skip optimizing them. Use one directory per model.
+    pub cache_dir: Option<PathBuf>,
 }
 
 impl ParakeetModelParams {
@@ -133,9 +241,20 @@ impl ParakeetModelParams {
     /// let params = ParakeetModelParams::fp32();
     /// ```
     pub fn fp32() -> Self {
-        Self {
-            quantization: QuantizationType::FP32,
-        }
+        Self::quantized(QuantizationType::FP32)
+    }
+
+    /// Create parameters for half precision (FP16) model loading.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use transcribe_rs::engines::parakeet::ParakeetModelParams;
+    ///
+    /// let params = ParakeetModelParams::fp16();
+    /// ```
+    pub fn fp16() -> Self {
+        Self::quantized(QuantizationType::FP16)
     }
 
     /// Create parameters for Int8 quantized model loading.
@@ -150,9 +269,7 @@ impl ParakeetModelParams {
     /// let params = ParakeetModelParams::int8();
     /// ```
     pub fn int8() -> Self {
-        Self {
-            quantization: QuantizationType::Int8,
-        }
+        Self::quantized(QuantizationType::Int8)
     }
 
     /// Create parameters with a specific quantization type.
@@ -169,7 +286,38 @@ impl ParakeetModelParams {
     /// let params = ParakeetModelParams::quantized(QuantizationType::Int8);
     /// ```
     pub fn quantized(quantization: QuantizationType) -> Self {
-        Self { quantization }
+        Self {
+            quantization,
+            ..Default::default()
+        }
+    }
+
+    /// Create parameters for the precision recommended for this machine.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use transcribe_rs::engines::parakeet::ParakeetModelParams;
+    ///
+    /// let params = ParakeetModelParams::auto();
+    /// ```
+    pub fn auto() -> Self {
+        Self::quantized(QuantizationType::Auto)
+    }
+
+    /// Keep the optimized ONNX graphs in `dir`, making later loads of the
+    /// model much faster.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use transcribe_rs::engines::parakeet::ParakeetModelParams;
+    ///
+    /// let params = ParakeetModelParams::int8().with_cache_dir("cache/parakeet");
+    /// ```
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
     }
 }
 
@@ -177,16 +325,41 @@ impl ParakeetModelParams {
 ///
 /// Controls the level of detail in timestamp generation and other
 /// inference-specific settings.
-#[derive(Debug, Clone)]
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into), default)]
 pub struct ParakeetInferenceParams {
     /// The granularity level for timestamp generation
     pub timestamp_granularity: TimestampGranularity,
+    /// Beam search width; 1 decodes greedily (default). Wider beams are
+    /// slightly more accurate and proportionally slower.
+    pub beam_size: usize,
+    /// Factor applied to all timestamps, e.g. to correct for audio that was
+    /// resampled before transcription (default 1.0)
+    pub timestamp_scale: f32,
+    /// Seconds added to all timestamps, e.g. the start of a chunk within a
+    /// longer recording (default 0.0)
+    pub timestamp_offset: f32,
+    /// Punctuation and capitalization restoration applied to the output text
+    pub post_process: PostProcessOptions,
+    /// Domain terms and names whose near-miss spellings are corrected in the output
+    pub vocabulary: Vec<String>,
+}
+
+impl ParakeetInferenceParams {
+    pub fn builder() -> ParakeetInferenceParamsBuilder {
+        ParakeetInferenceParamsBuilder::default()
+    }
 }
 
 impl Default for ParakeetInferenceParams {
     fn default() -> Self {
         Self {
             timestamp_granularity: TimestampGranularity::Token,
+            beam_size: 1,
+            timestamp_scale: 1.0,
+            timestamp_offset: 0.0,
+            post_process: PostProcessOptions::default(),
+            vocabulary: Vec::new(),
         }
     }
 }
@@ -213,6 +386,7 @@ impl Default for ParakeetInferenceParams {
 pub struct ParakeetEngine {
     loaded_model_path: Option<PathBuf>,
     model: Option<ParakeetModel>,
+    streaming: StreamingConfig,
 }
 
 impl Default for ParakeetEngine {
@@ -239,8 +413,87 @@ impl ParakeetEngine {
         Self {
             loaded_model_path: None,
             model: None,
+            streaming: StreamingConfig::default(),
         }
     }
+
+    /// Precision of the loaded model, which differs from the requested one
+    /// when the model directory lacks its files.
+    pub fn precision(&self) -> Option<LoadedPrecision> {
+        self.model.as_ref().map(|model| model.precision())
+    }
+
+    /// Start a streaming session on the loaded model, which must be a
+    /// cache-aware streaming export.
+    ///
+    /// The session borrows the engine until it is finished or dropped.
+    pub fn stream(
+        &mut self,
+        params: Option<ParakeetInferenceParams>,
+    ) -> Result<ParakeetStream<'_>, ParakeetError> {
+        let model = self.model.as_mut().ok_or(ParakeetError::ModelNotLoaded)?;
+        ParakeetStream::new(model, params.unwrap_or_default(), self.streaming.clone())
+    }
+
+    /// Transcribe `samples` into sentences, their words and the words'
+    /// tokens, each with its own timing.
+    ///
+    /// Use this instead of `transcribe_samples()` to get word timings
+    /// together with the sentences they belong to. `timestamp_granularity`,
+    /// `post_process` and `vocabulary` are ignored.
+    pub fn transcribe_utterance(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<ParakeetInferenceParams>,
+    ) -> Result<Utterance, Box<dyn std::error::Error>> {
+        let params = params.unwrap_or_default();
+        let mut timestamped_result = self.transcribe_timestamped(samples, &params)?;
+        map_timestamps(&mut timestamped_result, &params);
+        Ok(build_utterance(&timestamped_result))
+    }
+
+    /// Run the model with the params' decoding settings.
+    fn transcribe_timestamped(
+        &mut self,
+        samples: Vec<f32>,
+        params: &ParakeetInferenceParams,
+    ) -> Result<TimestampedResult, Box<dyn std::error::Error>> {
+        let model: &mut ParakeetModel = self
+            .model
+            .as_mut()
+            .ok_or("Model not loaded. Call load_model() first.")?;
+
+        Ok(model.transcribe_samples(samples, params.beam_size.max(1))?)
+    }
+}
+
+/// Apply the params' timestamp scale and offset.
+fn map_timestamps(timestamped_result: &mut TimestampedResult, params: &ParakeetInferenceParams) {
+    for timestamp in &mut timestamped_result.timestamps {
+        *timestamp = *timestamp * params.timestamp_scale + params.timestamp_offset;
+    }
+}
+
+/// Turn the model's output into the result for `params`: mapped
+/// timestamps at the requested granularity, vocabulary and post-processing.
+pub(super) fn finish_result(
+    mut timestamped_result: TimestampedResult,
+    params: &ParakeetInferenceParams,
+) -> TranscriptionResult {
+    map_timestamps(&mut timestamped_result, params);
+
+    // Convert timestamps based on requested granularity
+    let segments = convert_timestamps(&timestamped_result, params.timestamp_granularity.clone());
+
+    let mut result = TranscriptionResult {
+        text: timestamped_result.text,
+        segments: Some(segments),
+        language: None,
+    };
+    vocabulary::apply(&mut result, &params.vocabulary);
+    postprocess::apply(&mut result, &params.post_process);
+
+    result
 }
 
 impl Drop for ParakeetEngine {
@@ -258,13 +511,14 @@ impl TranscriptionEngine for ParakeetEngine {
         model_path: &Path,
         params: Self::ModelParams,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let quantized = match params.quantization {
-            QuantizationType::FP32 => false,
-            QuantizationType::Int8 => true,
-        };
-        let model = ParakeetModel::new(model_path, quantized)?;
+        let model = ParakeetModel::with_quantization(
+            model_path,
+            params.quantization,
+            params.cache_dir.as_deref(),
+        )?;
 
         self.model = Some(model);
+        self.streaming = params.streaming;
         self.loaded_model_path = Some(model_path.to_path_buf());
         Ok(())
     }
@@ -279,23 +533,11 @@ impl TranscriptionEngine for ParakeetEngine {
         samples: Vec<f32>,
         params: Option<Self::InferenceParams>,
     ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
-        let model: &mut ParakeetModel = self
-            .model
-            .as_mut()
-            .ok_or("Model not loaded. Call load_model() first.")?;
-
         let parakeet_params = params.unwrap_or_default();
 
         // Get the timestamped result from the model
-        let timestamped_result = model.transcribe_samples(samples)?;
-
-        // Convert timestamps based on requested granularity
-        let segments =
-            convert_timestamps(&timestamped_result, parakeet_params.timestamp_granularity);
+        let timestamped_result = self.transcribe_timestamped(samples, &parakeet_params)?;
 
-        Ok(TranscriptionResult {
-            text: timestamped_result.text,
-            segments: Some(segments),
-        })
+        Ok(finish_result(timestamped_result, &parakeet_params))
     }
 }
@@ -5,10 +5,13 @@
 //!
 //! # Key Features
 //!
-//! - **Quantization Support**: FP32 and Int8 quantized models
+//! - **Quantization Support**: FP32, FP16 and Int8 models, with fallback to
+//!   whichever precision the model directory has
 //! - **Flexible Timestamps**: Token, word, and segment-level timing
 //! - **High Performance**: Optimized for real-time transcription
 //! - **Directory Models**: Uses model directories rather than single files
+//! - **Streaming**: Partial results with cache-aware streaming FastConformer
+//!   exports via [`ParakeetEngine::stream`]
 //!
 //! # Model Structure
 //!
@@ -19,6 +22,7 @@
 //! ├── encoder-model.int8.onnx      # Encoder model (Int8 quantized)
 //! ├── decoder_joint-model.onnx    # Decoder/joint model (FP32)
 //! ├── decoder_joint-model.int8.onnx # Decoder/joint model (Int8 quantized)
+//! ├── *.fp16.onnx                  # Optional FP16 encoder and decoder/joint
 //! ├── nemo128.onnx                 # Audio preprocessor
 //! ├── vocab.txt                    # Vocabulary file
 //! └── config.json                  # Model configuration
@@ -60,6 +64,7 @@
 //! // Configure for word-level timestamps
 //! let params = ParakeetInferenceParams {
 //!     timestamp_granularity: TimestampGranularity::Word,
+//!     ..Default::default()
 //! };
 //!
 //! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), Some(params))?;
@@ -74,11 +79,13 @@
 
 pub mod engine;
 pub mod model;
+pub mod stream;
 pub mod timestamps;
 
 pub use engine::{
-    ParakeetEngine, ParakeetInferenceParams, ParakeetModelParams, QuantizationType,
-    TimestampGranularity,
+    LoadedPrecision, ParakeetEngine, ParakeetInferenceParams, ParakeetInferenceParamsBuilder,
+    ParakeetModelParams, QuantizationType, TimestampGranularity,
 };
 pub use model::{ParakeetError, ParakeetModel, TimestampedResult};
-pub use timestamps::{convert_timestamps, WordBoundary};
+pub use stream::{ParakeetStream, StreamingConfig};
+pub use timestamps::{build_utterance, convert_timestamps, Utterance, WordBoundary};
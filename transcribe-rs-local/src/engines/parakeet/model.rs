@@ -1,8 +1,6 @@
 use ndarray::{Array, Array1, Array2, Array3, ArrayD, ArrayViewD, IxDyn};
 use once_cell::sync::Lazy;
-use ort::execution_providers::CPUExecutionProvider;
 use ort::inputs;
-use ort::session::builder::GraphOptimizationLevel;
 use ort::session::Session;
 use ort::value::TensorRef;
 use regex::Regex;
@@ -10,12 +8,61 @@ use regex::Regex;
 use std::fs;
 use std::path::Path;
 
+use crate::engines::onnx;
+use crate::engines::parakeet::engine::{LoadedPrecision, QuantizationType};
+
 pub type DecoderState = (Array3<f32>, Array3<f32>);
 
+/// Greedy decoding progress, carried across chunks when streaming.
+pub struct DecodingState {
+    decoder_state: DecoderState,
+    pub(crate) tokens: Vec<i32>,
+    pub(crate) timestamps: Vec<usize>,
+    /// Encoder frames decoded so far
+    frames: usize,
+}
+
+/// Encoder caches of a cache-aware streaming model, carried across chunks.
+pub struct EncoderCache {
+    last_channel: ArrayD<f32>,
+    last_time: ArrayD<f32>,
+    last_channel_len: ArrayD<i64>,
+}
+
+/// A partial transcript kept by beam search.
+#[derive(Clone)]
+struct Hypothesis {
+    /// Log probability of the tokens so far
+    score: f32,
+    tokens: Vec<i32>,
+    timestamps: Vec<usize>,
+    state: DecoderState,
+}
+
 const SUBSAMPLING_FACTOR: usize = 8;
 const WINDOW_SIZE: f32 = 0.01;
 const MAX_TOKENS_PER_STEP: usize = 10;
 
+/// Load `model_name` at the first precision in `requested`'s fallback order
+/// that has a file.
+fn init_session<P: AsRef<Path>>(
+    model_dir: P,
+    model_name: &str,
+    requested: QuantizationType,
+    cache_dir: Option<&Path>,
+) -> Result<(Session, QuantizationType), ParakeetError> {
+    let order = requested.fallback_order();
+    let suffixes = order.map(QuantizationType::file_suffix);
+    let (index, path) = onnx::find_variant(&model_dir, model_name, &suffixes)
+        .ok_or_else(|| ParakeetError::ModelFileNotFound(model_name.to_string()))?;
+    log::info!(
+        "Loading {:?} model from {}...",
+        order[index],
+        path.display()
+    );
+    Ok((onnx::load_session(&path, None, cache_dir)?, order[index]))
+}
+
 static DECODE_SPACE_RE: Lazy<Result<Regex, regex::Error>> =
     Lazy::new(|| Regex::new(r"\A\s|\s\B|(\s)\b"));
 
@@ -40,6 +87,12 @@ pub enum ParakeetError {
     OutputNotFound(String),
     #[error("Failed to get tensor shape for input: {0}")]
     TensorShape(String),
+    #[error("Model not loaded")]
+    ModelNotLoaded,
+    #[error("Model is not a cache-aware streaming export (no cache_last_channel input)")]
+    StreamingNotSupported,
+    #[error("No {0}.onnx, {0}.fp16.onnx or {0}.int8.onnx in the model directory")]
+    ModelFileNotFound(String),
 }
 
 pub struct ParakeetModel {
@@ -49,19 +102,57 @@ pub struct ParakeetModel {
     vocab: Vec<String>,
     blank_idx: i32,
     vocab_size: usize,
+    precision: LoadedPrecision,
 }
 
 impl Drop for ParakeetModel {
     fn drop(&mut self) {
-        log::debug!("Dropping ParakeetModel with {} vocab tokens", self.vocab.len());
+        log::debug!(
+            "Dropping ParakeetModel with {} vocab tokens",
+            self.vocab.len()
+        );
     }
 }
 
 impl ParakeetModel {
     pub fn new<P: AsRef<Path>>(model_dir: P, quantized: bool) -> Result<Self, ParakeetError> {
-        let encoder = Self::init_session(&model_dir, "encoder-model", None, quantized)?;
-        let decoder_joint = Self::init_session(&model_dir, "decoder_joint-model", None, quantized)?;
-        let preprocessor = Self::init_session(&model_dir, "nemo128", None, false)?;
+        let quantization = if quantized {
+            QuantizationType::Int8
+        } else {
+            QuantizationType::FP32
+        };
+        Self::with_quantization(model_dir, quantization, None)
+    }
+
+    /// Load the model at `quantization`, falling back to another precision
+    /// for each ONNX model whose files for it are missing. With a
+    /// `cache_dir`, the optimized encoder and decoder are kept there so
+    /// later loads skip graph optimization.
+    pub fn with_quantization<P: AsRef<Path>>(
+        model_dir: P,
+        quantization: QuantizationType,
+        cache_dir: Option<&Path>,
+    ) -> Result<Self, ParakeetError> {
+        let requested = quantization.resolve();
+        let (encoder, encoder_precision) =
+            init_session(&model_dir, "encoder-model", requested, cache_dir)?;
+        let (decoder_joint, decoder_joint_precision) =
+            init_session(&model_dir, "decoder_joint-model", requested, cache_dir)?;
+        let precision = LoadedPrecision {
+            requested,
+            encoder: encoder_precision,
+            decoder_joint: decoder_joint_precision,
+        };
+        if precision.is_fallback() {
+            log::warn!(
+                "Parakeet {:?} model files not found; loaded the encoder as {:?} and the \
+                 decoder as {:?}",
+                requested,
+                precision.encoder,
+                precision.decoder_joint
+            );
+        }
+        let preprocessor = onnx::init_session(&model_dir, "nemo128", None, false)?;
 
         let (vocab, blank_idx) = Self::load_vocab(&model_dir)?;
         let vocab_size = vocab.len();
@@ -79,61 +170,13 @@ impl ParakeetModel {
             vocab,
             blank_idx,
             vocab_size,
+            precision,
         })
     }
 
-    fn init_session<P: AsRef<Path>>(
-        model_dir: P,
-        model_name: &str,
-        intra_threads: Option<usize>,
-        try_quantized: bool,
-    ) -> Result<Session, ParakeetError> {
-        let providers = vec![CPUExecutionProvider::default().build()];
-
-        // Try quantized version first if requested, fallback to regular version
-        let model_filename = if try_quantized {
-            let quantized_name = format!("{}.int8.onnx", model_name);
-            let quantized_path = model_dir.as_ref().join(&quantized_name);
-            if quantized_path.exists() {
-                log::info!("Loading quantized model from {}...", quantized_name);
-                quantized_name
-            } else {
-                let regular_name = format!("{}.onnx", model_name);
-                log::info!(
-                    "Quantized model not found, loading regular model from {}...",
-                    regular_name
-                );
-                regular_name
-            }
-        } else {
-            let regular_name = format!("{}.onnx", model_name);
-            log::info!("Loading model from {}...", regular_name);
-            regular_name
-        };
-
-        let mut builder = Session::builder()?
-            .with_optimization_level(GraphOptimizationLevel::Level3)?
-            .with_execution_providers(providers)?
-            .with_parallel_execution(true)?;
-
-        if let Some(threads) = intra_threads {
-            builder = builder
-                .with_intra_threads(threads)?
-                .with_inter_threads(threads)?;
-        }
-
-        let session = builder.commit_from_file(model_dir.as_ref().join(&model_filename))?;
-
-        for input in &session.inputs {
-            log::info!(
-                "Model '{}' input: name={}, type={:?}",
-                model_filename,
-                input.name,
-                input.input_type
-            );
-        }
-
-        Ok(session)
+    /// Precision each ONNX model was loaded with.
+    pub fn precision(&self) -> LoadedPrecision {
+        self.precision
     }
 
     fn load_vocab<P: AsRef<Path>>(model_dir: P) -> Result<(Vec<String>, i32), ParakeetError> {
@@ -224,6 +267,87 @@ impl ParakeetModel {
         Ok((encoder_output.to_owned(), encoded_lengths.to_owned()))
     }
 
+    /// Whether the encoder is a cache-aware streaming export, which takes
+    /// its attention and convolution caches as inputs.
+    pub fn supports_streaming(&self) -> bool {
+        self.encoder
+            .inputs
+            .iter()
+            .any(|input| input.name == "cache_last_channel")
+    }
+
+    pub fn create_encoder_cache(&self) -> Result<EncoderCache, ParakeetError> {
+        let cache_shape = |name: &str| -> Result<Vec<usize>, ParakeetError> {
+            let shape = self
+                .encoder
+                .inputs
+                .iter()
+                .find(|input| input.name == name)
+                .ok_or_else(|| ParakeetError::InputNotFound(name.to_string()))?
+                .input_type
+                .tensor_shape()
+                .ok_or_else(|| ParakeetError::TensorShape(name.to_string()))?;
+            // Only the batch dimension is dynamic; use batch_size=1
+            Ok(shape
+                .iter()
+                .map(|&dim| if dim < 0 { 1 } else { dim as usize })
+                .collect())
+        };
+
+        Ok(EncoderCache {
+            last_channel: ArrayD::zeros(IxDyn(&cache_shape("cache_last_channel")?)),
+            last_time: ArrayD::zeros(IxDyn(&cache_shape("cache_last_time")?)),
+            last_channel_len: ArrayD::zeros(IxDyn(&[1])),
+        })
+    }
+
+    /// Encode one chunk of features `[1, n_mels, frames]` with a cache-aware
+    /// streaming encoder, updating `cache`. Returns `[time_steps, 1024]`.
+    pub fn encode_chunk(
+        &mut self,
+        features: &ArrayViewD<f32>,
+        cache: &mut EncoderCache,
+    ) -> Result<ArrayD<f32>, ParakeetError> {
+        log::trace!("Running streaming encoder inference...");
+        let length = Array1::from_vec(vec![features.shape()[2] as i64]);
+        let inputs = inputs![
+            "audio_signal" => TensorRef::from_array_view(features.view())?,
+            "length" => TensorRef::from_array_view(length.view())?,
+            "cache_last_channel" => TensorRef::from_array_view(cache.last_channel.view())?,
+            "cache_last_time" => TensorRef::from_array_view(cache.last_time.view())?,
+            "cache_last_channel_len" => TensorRef::from_array_view(cache.last_channel_len.view())?,
+        ];
+        let outputs = self.encoder.run(inputs)?;
+
+        let output = |name: &str| {
+            outputs
+                .get(name)
+                .ok_or_else(|| ParakeetError::OutputNotFound(name.to_string()))
+        };
+        let encoder_output = output("outputs")?.try_extract_array::<f32>()?;
+        let encoded_lengths = output("encoded_lengths")?.try_extract_array::<i64>()?;
+        cache.last_channel = output("cache_last_channel_next")?
+            .try_extract_array::<f32>()?
+            .to_owned();
+        cache.last_time = output("cache_last_time_next")?
+            .try_extract_array::<f32>()?
+            .to_owned();
+        cache.last_channel_len = output("cache_last_channel_next_len")?
+            .try_extract_array::<i64>()?
+            .to_owned();
+
+        // [1, 1024, time_steps] -> [time_steps, 1024], valid frames only
+        let valid = encoded_lengths.iter().next().copied().unwrap_or(0) as usize;
+        let encodings = encoder_output.index_axis(ndarray::Axis(0), 0);
+        let encodings = encodings.t();
+        let valid = valid.min(encodings.shape()[0]);
+
+        Ok(encodings
+            .slice(ndarray::s![..valid, ..])
+            .to_owned()
+            .into_dyn())
+    }
+
     pub fn create_decoder_state(&self) -> Result<DecoderState, ParakeetError> {
         // Get input shapes from decoder model
         let inputs = &self.decoder_joint.inputs;
@@ -318,10 +442,13 @@ impl ParakeetModel {
         Ok((logits.to_owned(), (state1_3d, state2_3d)))
     }
 
+    /// Transcribe a batch of waveforms. `beam_size` 1 decodes greedily,
+    /// larger values run a beam search of that width.
     pub fn recognize_batch(
         &mut self,
         waveforms: &ArrayViewD<f32>,
         waveforms_len: &ArrayViewD<i64>,
+        beam_size: usize,
     ) -> Result<Vec<TimestampedResult>, ParakeetError> {
         // Preprocess and encode
         let (features, features_lens) = self.preprocess(waveforms, waveforms_len)?;
@@ -331,8 +458,11 @@ impl ParakeetModel {
         // Decode for each batch item
         let mut results = Vec::new();
         for (encodings, &encodings_len) in encoder_out.outer_iter().zip(encoder_out_lens.iter()) {
-            let (tokens, timestamps) =
-                self.decode_sequence(&encodings.view(), encodings_len as usize)?;
+            let (tokens, timestamps) = if beam_size > 1 {
+                self.decode_sequence_beam(&encodings.view(), encodings_len as usize, beam_size)?
+            } else {
+                self.decode_sequence(&encodings.view(), encodings_len as usize)?
+            };
             let result = self.decode_tokens(tokens, timestamps);
             results.push(result);
         }
@@ -345,10 +475,28 @@ impl ParakeetModel {
         encodings: &ArrayViewD<f32>, // [time_steps, 1024]
         encodings_len: usize,
     ) -> Result<(Vec<i32>, Vec<usize>), ParakeetError> {
-        let mut prev_state = self.create_decoder_state()?;
-        let mut tokens = Vec::new();
-        let mut timestamps = Vec::new();
+        let mut decoding = self.create_decoding_state()?;
+        self.decode_greedy(encodings, encodings_len, &mut decoding)?;
+        Ok((decoding.tokens, decoding.timestamps))
+    }
 
+    pub fn create_decoding_state(&self) -> Result<DecodingState, ParakeetError> {
+        Ok(DecodingState {
+            decoder_state: self.create_decoder_state()?,
+            tokens: Vec::new(),
+            timestamps: Vec::new(),
+            frames: 0,
+        })
+    }
+
+    /// Greedy-decode `encodings_len` encoder frames, continuing from
+    /// `decoding`. Timestamps count frames across calls.
+    pub fn decode_greedy(
+        &mut self,
+        encodings: &ArrayViewD<f32>, // [time_steps, 1024]
+        encodings_len: usize,
+        decoding: &mut DecodingState,
+    ) -> Result<(), ParakeetError> {
         let mut t = 0;
         let mut emitted_tokens = 0;
 
@@ -356,33 +504,15 @@ impl ParakeetModel {
             let encoder_step = encodings.slice(ndarray::s![t, ..]);
             // Convert to dynamic dimension to match decode_step parameter type
             let encoder_step_dyn = encoder_step.to_owned().into_dyn();
-            let (probs, new_state) =
-                self.decode_step(&tokens, &prev_state, &encoder_step_dyn.view())?;
-
-            // For TDT models, split output into vocab logits and duration logits
-            // output[:vocab_size] = vocabulary logits
-            // output[vocab_size:] = duration logits
-            let vocab_logits_slice = probs.as_slice().ok_or_else(|| {
-                ParakeetError::Shape(ndarray::ShapeError::from_kind(
-                    ndarray::ErrorKind::IncompatibleShape,
-                ))
-            })?;
-
-            let vocab_logits = if probs.len() > self.vocab_size {
-                // TDT model - extract only vocabulary logits
-                log::trace!(
-                    "TDT model detected: splitting {} logits into vocab({}) + duration",
-                    probs.len(),
-                    self.vocab_size
-                );
-                &vocab_logits_slice[..self.vocab_size]
-            } else {
-                // Regular RNN-T model
-                vocab_logits_slice
-            };
+            let (probs, new_state) = self.decode_step(
+                &decoding.tokens,
+                &decoding.decoder_state,
+                &encoder_step_dyn.view(),
+            )?;
 
             // Get argmax token from vocabulary logits only
-            let token = vocab_logits
+            let token = self
+                .vocab_logits(&probs)?
                 .iter()
                 .enumerate()
                 .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
@@ -390,9 +520,9 @@ impl ParakeetModel {
                 .unwrap_or(self.blank_idx);
 
             if token != self.blank_idx {
-                prev_state = new_state;
-                tokens.push(token);
-                timestamps.push(t);
+                decoding.decoder_state = new_state;
+                decoding.tokens.push(token);
+                decoding.timestamps.push(decoding.frames + t);
                 emitted_tokens += 1;
             }
 
@@ -403,10 +533,111 @@ impl ParakeetModel {
             }
         }
 
-        Ok((tokens, timestamps))
+        decoding.frames += encodings_len;
+        Ok(())
     }
 
-    fn decode_tokens(&self, ids: Vec<i32>, timestamps: Vec<usize>) -> TimestampedResult {
+    /// Beam search over the transducer output, emitting at most one token
+    /// per encoder frame. Hypotheses that reach the same tokens are merged,
+    /// keeping the more likely one.
+    fn decode_sequence_beam(
+        &mut self,
+        encodings: &ArrayViewD<f32>, // [time_steps, 1024]
+        encodings_len: usize,
+        beam_size: usize,
+    ) -> Result<(Vec<i32>, Vec<usize>), ParakeetError> {
+        let mut beam = vec![Hypothesis {
+            score: 0.0,
+            tokens: Vec::new(),
+            timestamps: Vec::new(),
+            state: self.create_decoder_state()?,
+        }];
+
+        for t in 0..encodings_len {
+            let encoder_step = encodings.slice(ndarray::s![t, ..]).to_owned().into_dyn();
+            let mut candidates = Vec::with_capacity(beam.len() * (beam_size + 1));
+
+            for hyp in &beam {
+                let (probs, new_state) =
+                    self.decode_step(&hyp.tokens, &hyp.state, &encoder_step.view())?;
+                let log_probs = log_softmax(self.vocab_logits(&probs)?);
+
+                // Blank keeps the hypothesis and its decoder state
+                candidates.push(Hypothesis {
+                    score: hyp.score + log_probs[self.blank_idx as usize],
+                    ..hyp.clone()
+                });
+
+                let mut ranked: Vec<(usize, f32)> = log_probs
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .filter(|&(idx, _)| idx != self.blank_idx as usize)
+                    .collect();
+                ranked.sort_unstable_by(|a, b| {
+                    b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
+                });
+                for (token, log_prob) in ranked.into_iter().take(beam_size) {
+                    let mut tokens = hyp.tokens.clone();
+                    tokens.push(token as i32);
+                    let mut timestamps = hyp.timestamps.clone();
+                    timestamps.push(t);
+                    candidates.push(Hypothesis {
+                        score: hyp.score + log_prob,
+                        tokens,
+                        timestamps,
+                        state: new_state.clone(),
+                    });
+                }
+            }
+
+            candidates.sort_unstable_by(|a, b| {
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            beam.clear();
+            for candidate in candidates {
+                if beam.len() == beam_size {
+                    break;
+                }
+                if !beam.iter().any(|hyp| hyp.tokens == candidate.tokens) {
+                    beam.push(candidate);
+                }
+            }
+        }
+
+        // The beam is sorted by score
+        let best = beam.into_iter().next().expect("beam is never empty");
+        Ok((best.tokens, best.timestamps))
+    }
+
+    /// The vocabulary part of the joint network's output.
+    ///
+    /// For TDT models, output[:vocab_size] are vocabulary logits and
+    /// output[vocab_size:] duration logits.
+    fn vocab_logits<'a>(&self, probs: &'a ArrayD<f32>) -> Result<&'a [f32], ParakeetError> {
+        let logits = probs.as_slice().ok_or_else(|| {
+            ParakeetError::Shape(ndarray::ShapeError::from_kind(
+                ndarray::ErrorKind::IncompatibleShape,
+            ))
+        })?;
+
+        if logits.len() > self.vocab_size {
+            // TDT model - extract only vocabulary logits
+            log::trace!(
+                "TDT model detected: splitting {} logits into vocab({}) + duration",
+                logits.len(),
+                self.vocab_size
+            );
+            Ok(&logits[..self.vocab_size])
+        } else {
+            // Regular RNN-T model
+            Ok(logits)
+        }
+    }
+
+    pub(crate) fn decode_tokens(&self, ids: Vec<i32>, timestamps: Vec<usize>) -> TimestampedResult {
         let tokens: Vec<String> = ids
             .iter()
             .filter_map(|&id| {
@@ -447,6 +678,7 @@ impl ParakeetModel {
     pub fn transcribe_samples(
         &mut self,
         samples: Vec<f32>,
+        beam_size: usize,
     ) -> Result<TimestampedResult, ParakeetError> {
         let batch_size = 1;
         let samples_len = samples.len();
@@ -458,7 +690,7 @@ impl ParakeetModel {
         let waveforms_lens = Array1::from_vec(vec![samples_len as i64]).into_dyn();
 
         // Run recognition to get detailed results
-        let results = self.recognize_batch(&waveforms.view(), &waveforms_lens.view())?;
+        let results = self.recognize_batch(&waveforms.view(), &waveforms_lens.view(), beam_size)?;
 
         // Extract the first (and only) result
         let timestamped_result = results.into_iter().next().ok_or_else(|| {
@@ -471,3 +703,9 @@ impl ParakeetModel {
         Ok(timestamped_result)
     }
 }
+
+fn log_softmax(logits: &[f32]) -> Vec<f32> {
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let log_sum = logits.iter().map(|&l| (l - max).exp()).sum::<f32>().ln() + max;
+    logits.iter().map(|&l| l - log_sum).collect()
+}
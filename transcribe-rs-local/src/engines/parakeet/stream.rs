@@ -0,0 +1,159 @@
+//! Streaming transcription with cache-aware FastConformer models.
+//!
+//! NVIDIA's cache-aware streaming checkpoints keep the encoder's attention
+//! and convolution state in caches that are passed from one chunk to the
+//! next, so each chunk is encoded once and partial results are available
+//! after every chunk instead of only at the end of the audio.
+
+use ndarray::{s, Array1, Array2};
+
+use super::engine::{finish_result, ParakeetInferenceParams};
+use super::model::{DecodingState, EncoderCache, ParakeetError, ParakeetModel};
+use crate::TranscriptionResult;
+
+/// Samples per preprocessor (mel) frame.
+const HOP_SAMPLES: usize = 160;
+
+/// Samples covered by one preprocessor window.
+const WINDOW_SAMPLES: usize = 400;
+
+/// Chunking of the audio fed to a streaming encoder.
+///
+/// The defaults match NeMo's cache-aware FastConformer models with a right
+/// attention context of 13 encoder frames (1.12 s chunks). Other latency
+/// settings need the `chunk_size` and `pre_encode_cache_size` from the
+/// model's `streaming_cfg`.
+#[derive(Debug, Clone)]
+pub struct StreamingConfig {
+    /// Mel frames (10 ms each) encoded per chunk.
+    pub chunk_frames: usize,
+    /// Mel frames from the end of the previous chunk that the encoder's
+    /// subsampling layers need in front of each chunk.
+    pub pre_encode_cache_frames: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            chunk_frames: 112,
+            pre_encode_cache_frames: 9,
+        }
+    }
+}
+
+/// A streaming transcription session created by
+/// [`ParakeetEngine::stream`](super::ParakeetEngine::stream).
+///
+/// Feed 16 kHz mono frames of any length with
+/// [`accept_samples`](Self::accept_samples) and call
+/// [`finish`](Self::finish) once the audio ends. Audio is transcribed one
+/// chunk at a time, so partial results lag the input by up to one chunk.
+pub struct ParakeetStream<'a> {
+    model: &'a mut ParakeetModel,
+    params: ParakeetInferenceParams,
+    config: StreamingConfig,
+    cache: EncoderCache,
+    decoding: DecodingState,
+    /// Audio not yet encoded, preceded by the context the next chunk needs
+    pending: Vec<f32>,
+}
+
+impl<'a> ParakeetStream<'a> {
+    pub(crate) fn new(
+        model: &'a mut ParakeetModel,
+        params: ParakeetInferenceParams,
+        config: StreamingConfig,
+    ) -> Result<Self, ParakeetError> {
+        if !model.supports_streaming() {
+            return Err(ParakeetError::StreamingNotSupported);
+        }
+
+        let cache = model.create_encoder_cache()?;
+        let decoding = model.create_decoding_state()?;
+        let context = context_samples(&config);
+
+        Ok(Self {
+            model,
+            params,
+            config,
+            cache,
+            decoding,
+            // The first chunk sees silence before it
+            pending: vec![0.0; context],
+        })
+    }
+
+    /// Feed the next frame of audio and return the transcript so far.
+    pub fn accept_samples(&mut self, samples: &[f32]) -> Result<String, ParakeetError> {
+        self.pending.extend_from_slice(samples);
+
+        let window = context_samples(&self.config) + self.chunk_samples();
+        while self.pending.len() >= window {
+            self.encode_chunk()?;
+        }
+
+        Ok(self
+            .model
+            .decode_tokens(
+                self.decoding.tokens.clone(),
+                self.decoding.timestamps.clone(),
+            )
+            .text)
+    }
+
+    /// Transcribe the remaining audio and return the whole transcription.
+    pub fn finish(mut self) -> Result<TranscriptionResult, ParakeetError> {
+        let context = context_samples(&self.config);
+        if self.pending.len() > context {
+            // Pad the last chunk with silence
+            self.pending.resize(context + self.chunk_samples(), 0.0);
+            self.encode_chunk()?;
+        }
+
+        let timestamped_result = self
+            .model
+            .decode_tokens(self.decoding.tokens, self.decoding.timestamps);
+        Ok(finish_result(timestamped_result, &self.params))
+    }
+
+    fn chunk_samples(&self) -> usize {
+        self.config.chunk_frames * HOP_SAMPLES
+    }
+
+    /// Encode and decode the chunk at the start of `pending`.
+    fn encode_chunk(&mut self) -> Result<(), ParakeetError> {
+        let chunk_samples = self.chunk_samples();
+        let window_len = context_samples(&self.config) + chunk_samples;
+
+        let waveform =
+            Array2::from_shape_vec((1, window_len), self.pending[..window_len].to_vec())?
+                .into_dyn();
+        let waveform_len = Array1::from_vec(vec![window_len as i64]).into_dyn();
+        let (features, _) = self
+            .model
+            .preprocess(&waveform.view(), &waveform_len.view())?;
+
+        // Keep the pre-encode cache and the chunk's frames. The window's last
+        // frame is centered on its end and would see padding.
+        let frames = features.shape()[2];
+        let wanted = self.config.pre_encode_cache_frames + self.config.chunk_frames;
+        let end = frames.saturating_sub(1);
+        let features = features.slice(s![.., .., end.saturating_sub(wanted)..end]);
+
+        let encodings = self
+            .model
+            .encode_chunk(&features.into_dyn(), &mut self.cache)?;
+        let encodings_len = encodings.shape()[0];
+        self.model
+            .decode_greedy(&encodings.view(), encodings_len, &mut self.decoding)?;
+
+        self.pending.drain(..chunk_samples);
+        Ok(())
+    }
+}
+
+/// Audio kept in front of each chunk: the pre-encode cache frames plus one
+/// preprocessor window.
+fn context_samples(config: &StreamingConfig) -> usize {
+    config.pre_encode_cache_frames * HOP_SAMPLES + WINDOW_SAMPLES
+}
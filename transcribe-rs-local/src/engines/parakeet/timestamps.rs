@@ -90,6 +90,12 @@ fn convert_to_raw_token_segments(
     segments
 }
 
+/// Group the tokens of `timestamped_result` into words and the words into
+/// sentences, each with its own timing.
+pub fn build_utterance(timestamped_result: &TimestampedResult) -> Utterance {
+    build_utterance_from_tokens(timestamped_result, &['.', '?', '!'], ' ')
+}
+
 // Uses hierarchical approach for clean word-level timestamps
 fn convert_to_hierarchical_word_segments(
     timestamped_result: &TimestampedResult,
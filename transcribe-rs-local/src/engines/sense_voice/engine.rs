@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+use super::model::{SenseVoiceError, SenseVoiceModel};
+use crate::engines::ctc;
+use crate::{TranscriptionEngine, TranscriptionResult};
+
+const SAMPLE_RATE: usize = 16000;
+
+/// Language spoken in the audio.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    /// Let the model detect the language
+    #[default]
+    Auto,
+    /// Mandarin
+    Chinese,
+    English,
+    Japanese,
+    Korean,
+    Cantonese,
+}
+
+impl Language {
+    pub(crate) const ALL: [Language; 6] = [
+        Language::Auto,
+        Language::Chinese,
+        Language::English,
+        Language::Japanese,
+        Language::Korean,
+        Language::Cantonese,
+    ];
+
+    /// The language for an ISO 639 code such as "en" or "yue"; `Auto` for
+    /// codes the model doesn't know.
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "zh" | "zh-Hans" | "zh-Hant" => Language::Chinese,
+            "en" => Language::English,
+            "ja" => Language::Japanese,
+            "ko" => Language::Korean,
+            "yue" => Language::Cantonese,
+            _ => Language::Auto,
+        }
+    }
+
+    /// Metadata key holding the id of this language's prompt.
+    pub(crate) fn metadata_key(self) -> &'static str {
+        match self {
+            Language::Auto => "lang_auto",
+            Language::Chinese => "lang_zh",
+            Language::English => "lang_en",
+            Language::Japanese => "lang_ja",
+            Language::Korean => "lang_ko",
+            Language::Cantonese => "lang_yue",
+        }
+    }
+}
+
+/// Parameters for model loading.
+#[derive(Debug, Clone, Default)]
+pub struct SenseVoiceModelParams {
+    /// Load `model.int8.onnx` when present (faster, slightly less accurate).
+    pub quantized: bool,
+}
+
+impl SenseVoiceModelParams {
+    /// Create params for the full precision (FP32) model.
+    pub fn fp32() -> Self {
+        Self { quantized: false }
+    }
+
+    /// Create params for the Int8 quantized model.
+    pub fn int8() -> Self {
+        Self { quantized: true }
+    }
+}
+
+/// Parameters for inference.
+#[derive(Debug, Clone)]
+pub struct SenseVoiceInferenceParams {
+    /// Language spoken in the audio (default: detected).
+    pub language: Language,
+
+    /// Punctuate and write numbers as digits (default `true`).
+    pub use_itn: bool,
+}
+
+impl Default for SenseVoiceInferenceParams {
+    fn default() -> Self {
+        Self {
+            language: Language::Auto,
+            use_itn: true,
+        }
+    }
+}
+
+/// SenseVoice transcription engine.
+///
+/// Implements the `TranscriptionEngine` trait for SenseVoice Small ONNX
+/// exports.
+pub struct SenseVoiceEngine {
+    loaded_model_path: Option<PathBuf>,
+    model: Option<SenseVoiceModel>,
+}
+
+impl SenseVoiceEngine {
+    /// Create a new SenseVoice engine (model not loaded).
+    pub fn new() -> Self {
+        Self {
+            loaded_model_path: None,
+            model: None,
+        }
+    }
+}
+
+impl Default for SenseVoiceEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SenseVoiceEngine {
+    fn drop(&mut self) {
+        self.unload_model();
+    }
+}
+
+impl TranscriptionEngine for SenseVoiceEngine {
+    type InferenceParams = SenseVoiceInferenceParams;
+    type ModelParams = SenseVoiceModelParams;
+
+    fn load_model_with_params(
+        &mut self,
+        model_path: &Path,
+        params: Self::ModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.unload_model();
+
+        self.model = Some(SenseVoiceModel::new(model_path, params.quantized)?);
+        self.loaded_model_path = Some(model_path.to_path_buf());
+
+        log::info!("Loaded SenseVoice model from {:?}", model_path);
+
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        if self.model.is_some() {
+            log::debug!("Unloading SenseVoice model");
+            self.model = None;
+            self.loaded_model_path = None;
+        }
+    }
+
+    fn transcribe_samples(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<Self::InferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let model = self.model.as_mut().ok_or(SenseVoiceError::ModelNotLoaded)?;
+
+        let params = params.unwrap_or_default();
+
+        log::debug!(
+            "Transcribing {} samples ({:.2}s), language {:?}",
+            samples.len(),
+            samples.len() as f32 / SAMPLE_RATE as f32,
+            params.language
+        );
+
+        let output = model.transcribe(&samples, params.language, params.use_itn)?;
+
+        Ok(TranscriptionResult {
+            text: ctc::decode_text(&output.tokens, model.vocab()),
+            segments: Some(ctc::word_segments(
+                &output.tokens,
+                model.vocab(),
+                output.frame_secs,
+            )),
+            language: output.language,
+        })
+    }
+}
@@ -0,0 +1,52 @@
+//! SenseVoice ONNX transcription engine.
+//!
+//! SenseVoice Small is Alibaba FunAudioLLM's non-autoregressive CTC model for
+//! Mandarin, Cantonese, English, Japanese and Korean. It detects the
+//! language itself and, with inverse text normalization, punctuates and
+//! writes numbers as digits. Being non-autoregressive it transcribes a clip
+//! in a single pass, which makes it very fast.
+//!
+//! # Model Format
+//!
+//! Expects a directory as exported by sherpa-onnx containing:
+//! - `model.onnx` (or `model.int8.onnx`) - the model, with the front end
+//!   settings (`lfr_window_size`, `neg_mean`, `inv_stddev`, `lang_*`,
+//!   `with_itn`, ...) in its metadata
+//! - `tokens.txt` - SentencePiece vocabulary, one "<token> <id>" per line
+//!
+//! # Timestamps
+//!
+//! Results carry one segment per word, timed by the CTC frames (60 ms) its
+//! tokens were emitted at.
+//!
+//! # Audio Requirements
+//!
+//! - Sample rate: 16 kHz
+//! - Format: Mono, 16-bit PCM
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::path::PathBuf;
+//! use transcribe_rs::{TranscriptionEngine, engines::sense_voice::{Language, SenseVoiceEngine, SenseVoiceInferenceParams, SenseVoiceModelParams}};
+//!
+//! let mut engine = SenseVoiceEngine::new();
+//! engine.load_model_with_params(
+//!     &PathBuf::from("models/sense-voice-int8"),
+//!     SenseVoiceModelParams::int8(),
+//! )?;
+//!
+//! let params = SenseVoiceInferenceParams {
+//!     language: Language::English,
+//!     use_itn: true,
+//! };
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), Some(params))?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod engine;
+pub mod model;
+
+pub use engine::{Language, SenseVoiceEngine, SenseVoiceInferenceParams, SenseVoiceModelParams};
+pub use model::{SenseVoiceError, SenseVoiceModel};
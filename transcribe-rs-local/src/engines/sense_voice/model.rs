@@ -0,0 +1,265 @@
+use ndarray::{s, Array1, Array2, Array3, ArrayView2};
+use ort::inputs;
+use ort::session::Session;
+use ort::value::TensorRef;
+
+use std::path::Path;
+
+use super::engine::Language;
+use crate::engines::ctc::{self, CtcToken};
+use crate::engines::fbank::KaldiFbank;
+use crate::engines::onnx;
+
+/// Output frames holding the language, emotion, event and text
+/// normalization predictions ahead of the transcript.
+const PROMPT_FRAMES: usize = 4;
+
+const BLANK_ID: usize = 0;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SenseVoiceError {
+    #[error("ORT error: {0}")]
+    Ort(#[from] ort::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ndarray shape error: {0}")]
+    Shape(#[from] ndarray::ShapeError),
+    #[error("Model output not found: {0}")]
+    OutputNotFound(String),
+    #[error("Model metadata missing or invalid: {0}")]
+    Metadata(String),
+    #[error("Model not loaded")]
+    ModelNotLoaded,
+}
+
+/// Decoded audio: transcript tokens and the language the model heard.
+pub(crate) struct SenseVoiceOutput {
+    pub tokens: Vec<CtcToken>,
+    /// Language code such as "en", `None` for no speech
+    pub language: Option<String>,
+    /// Duration of one output frame in seconds
+    pub frame_secs: f32,
+}
+
+/// Front end settings and prompt ids the sherpa-onnx export stores in the
+/// model metadata.
+struct Metadata {
+    /// Low frame rate stacking: `lfr_window_size` frames every
+    /// `lfr_window_shift`
+    lfr_window_size: usize,
+    lfr_window_shift: usize,
+    /// CMVN statistics
+    neg_mean: Vec<f32>,
+    inv_stddev: Vec<f32>,
+    /// Whether the model takes samples in [-1, 1] rather than 16-bit scale
+    normalize_samples: bool,
+    language_ids: Vec<(Language, i32)>,
+    with_itn: i32,
+    without_itn: i32,
+}
+
+impl Metadata {
+    fn read(session: &Session) -> Result<Self, SenseVoiceError> {
+        let metadata = session.metadata()?;
+        let value = |key: &str| -> Result<String, SenseVoiceError> {
+            metadata
+                .custom(key)?
+                .ok_or_else(|| SenseVoiceError::Metadata(key.to_string()))
+        };
+        let int = |key: &str| -> Result<i32, SenseVoiceError> {
+            value(key)?
+                .trim()
+                .parse()
+                .map_err(|_| SenseVoiceError::Metadata(key.to_string()))
+        };
+        let floats = |key: &str| -> Result<Vec<f32>, SenseVoiceError> {
+            value(key)?
+                .split(',')
+                .map(|v| v.trim().parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| SenseVoiceError::Metadata(key.to_string()))
+        };
+
+        let language_ids = Language::ALL
+            .iter()
+            .map(|&language| Ok((language, int(language.metadata_key())?)))
+            .collect::<Result<_, SenseVoiceError>>()?;
+        Ok(Self {
+            lfr_window_size: int("lfr_window_size")? as usize,
+            lfr_window_shift: int("lfr_window_shift")? as usize,
+            neg_mean: floats("neg_mean")?,
+            inv_stddev: floats("inv_stddev")?,
+            normalize_samples: int("normalize_samples").unwrap_or(0) != 0,
+            language_ids,
+            with_itn: int("with_itn")?,
+            without_itn: int("without_itn")?,
+        })
+    }
+}
+
+/// SenseVoice Small CTC model as exported by sherpa-onnx.
+pub struct SenseVoiceModel {
+    session: Session,
+    vocab: Vec<String>,
+    metadata: Metadata,
+}
+
+impl Drop for SenseVoiceModel {
+    fn drop(&mut self) {
+        log::debug!(
+            "Dropping SenseVoiceModel with {} vocab tokens",
+            self.vocab.len()
+        );
+    }
+}
+
+impl SenseVoiceModel {
+    pub fn new<P: AsRef<Path>>(model_dir: P, quantized: bool) -> Result<Self, SenseVoiceError> {
+        let session = onnx::init_session(&model_dir, "model", None, quantized)?;
+        let vocab = ctc::load_tokens(&model_dir.as_ref().join("tokens.txt"))?;
+        let metadata = Metadata::read(&session)?;
+
+        log::info!(
+            "Loaded vocabulary with {} tokens, LFR {}/{}",
+            vocab.len(),
+            metadata.lfr_window_size,
+            metadata.lfr_window_shift
+        );
+
+        Ok(Self {
+            session,
+            vocab,
+            metadata,
+        })
+    }
+
+    /// Features of `samples` ready for the model: Kaldi fbank stacked to the
+    /// low frame rate and normalized, `[frames, 80 * lfr_window_size]`.
+    fn features(&self, samples: &[f32]) -> Array2<f32> {
+        let scaled: Vec<f32>;
+        let metadata = &self.metadata;
+        let samples = if metadata.normalize_samples {
+            samples
+        } else {
+            scaled = samples.iter().map(|s| s * 32768.0).collect();
+            &scaled
+        };
+        let fbank = KaldiFbank::default().compute(samples);
+        let mut features = stack_frames(
+            fbank.view(),
+            metadata.lfr_window_size,
+            metadata.lfr_window_shift,
+        );
+        for mut row in features.rows_mut() {
+            let cmvn = metadata.neg_mean.iter().zip(&metadata.inv_stddev);
+            for (value, (mean, scale)) in row.iter_mut().zip(cmvn) {
+                *value = (*value + mean) * scale;
+            }
+        }
+        features
+    }
+
+    /// Transcribe `samples`, in `language` or detecting it with
+    /// `Language::Auto`, with inverse text normalization (punctuation and
+    /// written-out numbers) if `use_itn`.
+    pub(crate) fn transcribe(
+        &mut self,
+        samples: &[f32],
+        language: Language,
+        use_itn: bool,
+    ) -> Result<SenseVoiceOutput, SenseVoiceError> {
+        let features = self.features(samples);
+        let num_frames = features.nrows();
+        if num_frames == 0 {
+            return Ok(SenseVoiceOutput {
+                tokens: Vec::new(),
+                language: None,
+                frame_secs: 0.0,
+            });
+        }
+        let x = features.insert_axis(ndarray::Axis(0));
+        let x_length = Array1::from_vec(vec![num_frames as i32]);
+        let metadata = &self.metadata;
+        let language_id = metadata
+            .language_ids
+            .iter()
+            .find(|(l, _)| *l == language)
+            .map_or(0, |&(_, id)| id);
+        let language_ids = Array1::from_vec(vec![language_id]);
+        let text_norm = Array1::from_vec(vec![if use_itn {
+            metadata.with_itn
+        } else {
+            metadata.without_itn
+        }]);
+
+        log::trace!("Running SenseVoice on {} frames...", num_frames);
+        let outputs = self.session.run(inputs![
+            "x" => TensorRef::from_array_view(x.view())?,
+            "x_length" => TensorRef::from_array_view(x_length.view())?,
+            "language" => TensorRef::from_array_view(language_ids.view())?,
+            "text_norm" => TensorRef::from_array_view(text_norm.view())?,
+        ])?;
+        let logits = outputs
+            .get("logits")
+            .ok_or_else(|| SenseVoiceError::OutputNotFound("logits".to_string()))?
+            .try_extract_array::<f32>()?;
+        let logits: Array3<f32> = logits.to_owned().into_dimensionality()?;
+        let logits = logits.slice(s![0, .., ..]);
+
+        let frame_secs = 0.01 * self.metadata.lfr_window_shift as f32;
+        // The first frame predicts the language token, e.g. "<|en|>"
+        let first = logits.row(0);
+        let language = (0..first.len())
+            .max_by(|&a, &b| first[a].total_cmp(&first[b]))
+            .and_then(|id| self.vocab.get(id))
+            .and_then(|token| token.strip_prefix("<|")?.strip_suffix("|>"))
+            .filter(|code| *code != "nospeech")
+            .map(str::to_string);
+        let prompt = PROMPT_FRAMES.min(logits.nrows());
+        let tokens = ctc::greedy_decode(logits.slice(s![prompt.., ..]), BLANK_ID);
+
+        Ok(SenseVoiceOutput {
+            tokens,
+            language,
+            frame_secs,
+        })
+    }
+
+    pub(crate) fn vocab(&self) -> &[String] {
+        &self.vocab
+    }
+}
+
+/// Stack `window` consecutive frames every `shift` frames, padding the
+/// start with copies of the first frame as FunASR does and the end with
+/// copies of the last.
+fn stack_frames(fbank: ArrayView2<f32>, window: usize, shift: usize) -> Array2<f32> {
+    let (frames, bins) = fbank.dim();
+    let left_pad = (window - 1) / 2;
+    let out_frames = frames.div_ceil(shift);
+    let mut stacked = Array2::zeros((out_frames, window * bins));
+    for i in 0..out_frames {
+        for j in 0..window {
+            let source = (i * shift + j).saturating_sub(left_pad).min(frames - 1);
+            stacked
+                .slice_mut(s![i, j * bins..(j + 1) * bins])
+                .assign(&fbank.row(source));
+        }
+    }
+    stacked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_frames() {
+        // Ten frames of one bin each, numbered 0..10
+        let fbank = Array2::from_shape_fn((10, 1), |(i, _)| i as f32);
+        let stacked = stack_frames(fbank.view(), 7, 6);
+        assert_eq!(stacked.dim(), (2, 7));
+        assert_eq!(stacked.row(0).to_vec(), [0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(stacked.row(1).to_vec(), [3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+    }
+}
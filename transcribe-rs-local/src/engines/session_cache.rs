@@ -0,0 +1,195 @@
+//! Warm starts for ONNX Runtime sessions.
+//!
+//! Most of the time it takes to load an ONNX model goes into optimizing its
+//! graph. With a cache directory, the optimized graph is saved there the
+//! first time and loaded as is, without optimizing again, from then on.
+//!
+//! Cache entries are keyed on the model file's path, size and modification
+//! time and on the ONNX Runtime build, so a changed model or runtime is
+//! optimized again. Optimized graphs can contain kernels for this CPU, which
+//! is fine for a cache that stays on one machine. The weights are saved in a
+//! separate `.data` file next to each entry, since ONNX files can't hold
+//! more than 2 GB.
+
+use ort::session::builder::{GraphOptimizationLevel, SessionBuilder};
+use ort::session::Session;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Initializers smaller than this stay in the optimized model file.
+const EXTERNAL_MIN_BYTES: &str = "1024";
+
+/// Commit the session configured by `builder` for the model at `path`.
+///
+/// With a `cache_dir`, the optimized copy of the model there is loaded
+/// when there is one, and saved there otherwise. Problems with the cache
+/// are logged and fall back to loading `path`.
+pub(crate) fn commit(
+    builder: impl Fn() -> ort::Result<SessionBuilder>,
+    path: &Path,
+    cache_dir: Option<&Path>,
+) -> ort::Result<Session> {
+    let Some((cache_dir, cached)) =
+        cache_dir.and_then(|dir| entry_path(dir, path).map(|entry| (dir, entry)))
+    else {
+        return builder()?.commit_from_file(path);
+    };
+
+    if cached.exists() {
+        let session = builder()
+            .and_then(|builder| builder.with_optimization_level(GraphOptimizationLevel::Disable))
+            .and_then(|builder| builder.commit_from_file(&cached));
+        match session {
+            Ok(session) => {
+                log::info!("Loaded optimized {} from the cache", path.display());
+                return Ok(session);
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to load the cached {}, optimizing it again: {}",
+                    path.display(),
+                    e
+                );
+                remove_entry(&cached);
+            }
+        }
+    }
+
+    if let Err(e) = std::fs::create_dir_all(cache_dir) {
+        log::warn!(
+            "Failed to create the session cache {}: {}",
+            cache_dir.display(),
+            e
+        );
+        return builder()?.commit_from_file(path);
+    }
+    remove_stale(cache_dir, path, &cached);
+
+    let data_name = data_path(&cached)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let session = builder()
+        .and_then(|builder| builder.with_optimized_model_path(&cached))
+        .and_then(|builder| {
+            builder.with_config_entry(
+                "session.optimized_model_external_initializers_file_name",
+                &data_name,
+            )
+        })
+        .and_then(|builder| {
+            builder.with_config_entry(
+                "session.optimized_model_external_initializers_min_size_in_bytes",
+                EXTERNAL_MIN_BYTES,
+            )
+        })
+        .and_then(|builder| builder.commit_from_file(path));
+    match session {
+        Ok(session) => {
+            log::info!(
+                "Cached the optimized {} in {}",
+                path.display(),
+                cache_dir.display()
+            );
+            Ok(session)
+        }
+        Err(e) => {
+            log::warn!("Failed to cache the optimized {}: {}", path.display(), e);
+            remove_entry(&cached);
+            builder()?.commit_from_file(path)
+        }
+    }
+}
+
+/// Where the optimized copy of the model at `path` goes in `cache_dir`:
+/// `<file name>.<key>.onnx`, where the key changes with the model file and
+/// the ONNX Runtime build.
+fn entry_path(cache_dir: &Path, path: &Path) -> Option<PathBuf> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    modified.hash(&mut hasher);
+    ort::info().hash(&mut hasher);
+
+    let file_name = path.file_name()?.to_string_lossy();
+    Some(cache_dir.join(format!("{}.{:016x}.onnx", file_name, hasher.finish())))
+}
+
+fn data_path(entry: &Path) -> PathBuf {
+    let mut path = entry.as_os_str().to_owned();
+    path.push(".data");
+    PathBuf::from(path)
+}
+
+fn remove_entry(entry: &Path) {
+    let _ = std::fs::remove_file(entry);
+    let _ = std::fs::remove_file(data_path(entry));
+}
+
+/// Remove cache entries for older versions of the model at `path`.
+fn remove_stale(cache_dir: &Path, path: &Path, current: &Path) {
+    let Some(prefix) = path
+        .file_name()
+        .map(|name| format!("{}.", name.to_string_lossy()))
+    else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let entry = entry.path();
+        let is_stale = entry.extension().is_some_and(|ext| ext == "onnx")
+            && entry != current
+            && entry
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with(&prefix));
+        if is_stale {
+            log::debug!("Removing stale session cache {}", entry.display());
+            remove_entry(&entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_path() {
+        let dir = std::env::temp_dir().join(format!("session-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let model = dir.join("encoder-model.onnx");
+        std::fs::write(&model, b"model").unwrap();
+
+        let cache_dir = dir.join("cache");
+        let entry = entry_path(&cache_dir, &model).unwrap();
+        assert_eq!(entry, entry_path(&cache_dir, &model).unwrap());
+        let name = entry.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("encoder-model.onnx.") && name.ends_with(".onnx"));
+        assert!(data_path(&entry).to_string_lossy().ends_with(".onnx.data"));
+
+        // A changed model gets a new entry, and the old one is removed
+        std::fs::write(&model, b"a new model").unwrap();
+        let new_entry = entry_path(&cache_dir, &model).unwrap();
+        assert_ne!(entry, new_entry);
+
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let quantized = cache_dir.join("encoder-model.int8.onnx.0123456789abcdef.onnx");
+        for file in [&entry, &data_path(&entry), &quantized] {
+            std::fs::write(file, b"cached").unwrap();
+        }
+        remove_stale(&cache_dir, &model, &new_entry);
+        assert!(!entry.exists());
+        assert!(!data_path(&entry).exists());
+        assert!(quantized.exists());
+
+        assert!(entry_path(&cache_dir, &dir.join("missing.onnx")).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,378 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::ffi;
+use crate::{
+    postprocess::{self, PostProcessOptions},
+    vocabulary, TranscriptionEngine, TranscriptionResult, TranscriptionSegment,
+};
+
+const SAMPLE_RATE: f32 = 16000.0;
+
+/// Samples fed to the recognizer at once by `transcribe_samples` (0.25s).
+const CHUNK_SAMPLES: usize = 4000;
+
+#[derive(thiserror::Error, Debug)]
+pub enum VoskError {
+    #[error("Model directory not found: {0}")]
+    ModelNotFound(PathBuf),
+    #[error("Failed to load Vosk model from {0}")]
+    ModelLoad(PathBuf),
+    #[error("Model path is not valid UTF-8 or contains a NUL byte: {0}")]
+    InvalidPath(PathBuf),
+    #[error("Failed to create recognizer (is the grammar valid for this model?)")]
+    Recognizer,
+    #[error("Invalid grammar: {0}")]
+    Grammar(String),
+    #[error("Vosk failed to decode audio")]
+    Decode,
+    #[error("Failed to parse Vosk result: {0}")]
+    Result(#[from] serde_json::Error),
+    #[error("Model not loaded")]
+    ModelNotLoaded,
+}
+
+/// Parameters for model loading.
+#[derive(Debug, Clone, Default)]
+pub struct VoskModelParams {
+    /// Let Kaldi log model loading and decoding details to stderr.
+    /// Vosk's logging is process-wide, so this affects all loaded models.
+    pub verbose: bool,
+}
+
+/// Parameters for inference.
+#[derive(Builder, Debug, Clone, Default)]
+#[builder(setter(into), default)]
+pub struct VoskInferenceParams {
+    /// Restrict recognition to these words and phrases. Add `"[unk]"` to
+    /// allow out-of-grammar speech. Only supported by the small models with
+    /// a dynamic graph; big models reject a grammar.
+    pub grammar: Option<Vec<String>>,
+
+    /// Punctuation and capitalization restoration applied to the output,
+    /// which Vosk does not produce itself.
+    pub post_process: PostProcessOptions,
+
+    /// Domain terms and names whose near-miss spellings are corrected in the
+    /// output.
+    pub vocabulary: Vec<String>,
+}
+
+impl VoskInferenceParams {
+    pub fn builder() -> VoskInferenceParamsBuilder {
+        VoskInferenceParamsBuilder::default()
+    }
+}
+
+/// Incremental output of a [`VoskStream`].
+#[derive(Debug)]
+pub enum VoskUpdate {
+    /// Best guess for the utterance in progress. Empty when nothing has been
+    /// recognized since the last final result.
+    Partial(String),
+    /// An utterance ended (Vosk detected an endpoint) and will not change.
+    Final(TranscriptionSegment),
+}
+
+/// Vosk model handle. Vosk reference-counts models internally and its models
+/// are safe to share between recognizers on different threads.
+struct Model(NonNull<ffi::VoskModel>);
+
+unsafe impl Send for Model {}
+unsafe impl Sync for Model {}
+
+impl Model {
+    fn new(model_path: &Path) -> Result<Self, VoskError> {
+        if !model_path.is_dir() {
+            return Err(VoskError::ModelNotFound(model_path.to_path_buf()));
+        }
+        let path = model_path
+            .to_str()
+            .and_then(|p| CString::new(p).ok())
+            .ok_or_else(|| VoskError::InvalidPath(model_path.to_path_buf()))?;
+
+        let model = unsafe { ffi::vosk_model_new(path.as_ptr()) };
+        NonNull::new(model)
+            .map(Self)
+            .ok_or_else(|| VoskError::ModelLoad(model_path.to_path_buf()))
+    }
+}
+
+impl Drop for Model {
+    fn drop(&mut self) {
+        unsafe { ffi::vosk_model_free(self.0.as_ptr()) }
+    }
+}
+
+struct Recognizer(NonNull<ffi::VoskRecognizer>);
+
+unsafe impl Send for Recognizer {}
+
+impl Recognizer {
+    fn new(model: &Model, grammar: Option<&[String]>) -> Result<Self, VoskError> {
+        let recognizer = match grammar {
+            Some(grammar) => {
+                let grammar = CString::new(serde_json::to_string(grammar)?)
+                    .map_err(|e| VoskError::Grammar(e.to_string()))?;
+                unsafe {
+                    ffi::vosk_recognizer_new_grm(model.0.as_ptr(), SAMPLE_RATE, grammar.as_ptr())
+                }
+            }
+            None => unsafe { ffi::vosk_recognizer_new(model.0.as_ptr(), SAMPLE_RATE) },
+        };
+        let recognizer = NonNull::new(recognizer).ok_or(VoskError::Recognizer)?;
+        unsafe { ffi::vosk_recognizer_set_words(recognizer.as_ptr(), 1) };
+        Ok(Self(recognizer))
+    }
+
+    /// Feed samples, returning whether Vosk detected the end of an utterance.
+    fn accept(&mut self, samples: &[f32]) -> Result<bool, VoskError> {
+        let pcm: Vec<i16> = samples
+            .iter()
+            .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .collect();
+        let state = unsafe {
+            ffi::vosk_recognizer_accept_waveform_s(
+                self.0.as_ptr(),
+                pcm.as_ptr(),
+                pcm.len() as c_int,
+            )
+        };
+        match state {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(VoskError::Decode),
+        }
+    }
+
+    fn partial(&mut self) -> Result<String, VoskError> {
+        let partial: PartialResult =
+            parse(unsafe { ffi::vosk_recognizer_partial_result(self.0.as_ptr()) })?;
+        Ok(partial.partial)
+    }
+
+    fn result(&mut self) -> Result<Option<TranscriptionSegment>, VoskError> {
+        let result: CompleteResult =
+            parse(unsafe { ffi::vosk_recognizer_result(self.0.as_ptr()) })?;
+        Ok(result.into_segment())
+    }
+
+    fn final_result(&mut self) -> Result<Option<TranscriptionSegment>, VoskError> {
+        let result: CompleteResult =
+            parse(unsafe { ffi::vosk_recognizer_final_result(self.0.as_ptr()) })?;
+        Ok(result.into_segment())
+    }
+}
+
+impl Drop for Recognizer {
+    fn drop(&mut self) {
+        unsafe { ffi::vosk_recognizer_free(self.0.as_ptr()) }
+    }
+}
+
+#[derive(Deserialize)]
+struct PartialResult {
+    #[serde(default)]
+    partial: String,
+}
+
+#[derive(Deserialize)]
+struct CompleteResult {
+    #[serde(default)]
+    result: Vec<WordResult>,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct WordResult {
+    start: f32,
+    end: f32,
+}
+
+impl CompleteResult {
+    fn into_segment(self) -> Option<TranscriptionSegment> {
+        let text = self.text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        Some(TranscriptionSegment {
+            start: self.result.first().map_or(0.0, |w| w.start),
+            end: self.result.last().map_or(0.0, |w| w.end),
+            text: text.to_string(),
+        })
+    }
+}
+
+/// Parse a JSON result string owned by the recognizer.
+fn parse<T: DeserializeOwned>(json: *const c_char) -> Result<T, VoskError> {
+    if json.is_null() {
+        return Err(VoskError::Decode);
+    }
+    let json = unsafe { CStr::from_ptr(json) };
+    Ok(serde_json::from_slice(json.to_bytes())?)
+}
+
+/// A streaming recognition session created by [`VoskEngine::stream`].
+///
+/// Feed 16 kHz mono frames of any length with [`accept_samples`](Self::accept_samples)
+/// and call [`finish`](Self::finish) once the audio ends. Timestamps are
+/// relative to the first sample fed to the stream.
+pub struct VoskStream {
+    recognizer: Recognizer,
+    // Keeps the model alive for the recognizer, even past an engine unload
+    _model: Arc<Model>,
+    params: VoskInferenceParams,
+    segments: Vec<TranscriptionSegment>,
+}
+
+impl VoskStream {
+    /// Feed the next frame of audio and return the current partial result,
+    /// or the final result if the frame completed an utterance.
+    pub fn accept_samples(&mut self, samples: &[f32]) -> Result<VoskUpdate, VoskError> {
+        if !self.recognizer.accept(samples)? {
+            return Ok(VoskUpdate::Partial(self.recognizer.partial()?));
+        }
+
+        match self.recognizer.result()? {
+            Some(segment) => {
+                let update = TranscriptionSegment {
+                    start: segment.start,
+                    end: segment.end,
+                    text: vocabulary::correct_text(&segment.text, &self.params.vocabulary),
+                };
+                self.segments.push(segment);
+                Ok(VoskUpdate::Final(update))
+            }
+            None => Ok(VoskUpdate::Partial(String::new())),
+        }
+    }
+
+    /// Flush the utterance in progress and return the whole transcription.
+    pub fn finish(mut self) -> Result<TranscriptionResult, VoskError> {
+        if let Some(segment) = self.recognizer.final_result()? {
+            self.segments.push(segment);
+        }
+
+        let text = self
+            .segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut result = TranscriptionResult {
+            text,
+            segments: Some(self.segments),
+            language: None,
+        };
+        vocabulary::apply(&mut result, &self.params.vocabulary);
+        postprocess::apply(&mut result, &self.params.post_process);
+
+        Ok(result)
+    }
+}
+
+/// Vosk transcription engine.
+///
+/// Implements the `TranscriptionEngine` trait for batch transcription and
+/// offers [`VoskEngine::stream`] for incremental recognition.
+pub struct VoskEngine {
+    loaded_model_path: Option<PathBuf>,
+    model: Option<Arc<Model>>,
+}
+
+impl VoskEngine {
+    /// Create a new Vosk engine (model not loaded).
+    pub fn new() -> Self {
+        Self {
+            loaded_model_path: None,
+            model: None,
+        }
+    }
+
+    /// Start a streaming recognition session on the loaded model.
+    ///
+    /// Sessions are independent, so several can run concurrently (e.g. one
+    /// per audio source).
+    pub fn stream(&self, params: Option<VoskInferenceParams>) -> Result<VoskStream, VoskError> {
+        let model = self.model.clone().ok_or(VoskError::ModelNotLoaded)?;
+        let params = params.unwrap_or_default();
+        let recognizer = Recognizer::new(&model, params.grammar.as_deref())?;
+
+        Ok(VoskStream {
+            recognizer,
+            _model: model,
+            params,
+            segments: Vec::new(),
+        })
+    }
+}
+
+impl Default for VoskEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for VoskEngine {
+    fn drop(&mut self) {
+        self.unload_model();
+    }
+}
+
+impl TranscriptionEngine for VoskEngine {
+    type InferenceParams = VoskInferenceParams;
+    type ModelParams = VoskModelParams;
+
+    fn load_model_with_params(
+        &mut self,
+        model_path: &Path,
+        params: Self::ModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.unload_model();
+
+        unsafe { ffi::vosk_set_log_level(if params.verbose { 0 } else { -1 }) };
+
+        self.model = Some(Arc::new(Model::new(model_path)?));
+        self.loaded_model_path = Some(model_path.to_path_buf());
+
+        log::info!("Loaded Vosk model from {:?}", model_path);
+
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        if self.model.is_some() {
+            log::debug!("Unloading Vosk model");
+            self.model = None;
+            self.loaded_model_path = None;
+        }
+    }
+
+    fn transcribe_samples(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<Self::InferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let mut stream = self.stream(params)?;
+
+        log::debug!(
+            "Transcribing {} samples ({:.2}s)",
+            samples.len(),
+            samples.len() as f32 / SAMPLE_RATE
+        );
+
+        for chunk in samples.chunks(CHUNK_SAMPLES) {
+            stream.accept_samples(chunk)?;
+        }
+
+        Ok(stream.finish()?)
+    }
+}
@@ -0,0 +1,41 @@
+//! Raw bindings to the Vosk C API (`vosk_api.h`).
+//!
+//! Only the functions used by the engine are declared. Results are returned
+//! as JSON strings owned by the recognizer and valid until the next call on it.
+
+use std::os::raw::{c_char, c_float, c_int, c_short};
+
+#[repr(C)]
+pub struct VoskModel {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct VoskRecognizer {
+    _private: [u8; 0],
+}
+
+#[link(name = "vosk")]
+extern "C" {
+    pub fn vosk_set_log_level(log_level: c_int);
+
+    pub fn vosk_model_new(model_path: *const c_char) -> *mut VoskModel;
+    pub fn vosk_model_free(model: *mut VoskModel);
+
+    pub fn vosk_recognizer_new(model: *mut VoskModel, sample_rate: c_float) -> *mut VoskRecognizer;
+    pub fn vosk_recognizer_new_grm(
+        model: *mut VoskModel,
+        sample_rate: c_float,
+        grammar: *const c_char,
+    ) -> *mut VoskRecognizer;
+    pub fn vosk_recognizer_set_words(recognizer: *mut VoskRecognizer, words: c_int);
+    pub fn vosk_recognizer_accept_waveform_s(
+        recognizer: *mut VoskRecognizer,
+        data: *const c_short,
+        length: c_int,
+    ) -> c_int;
+    pub fn vosk_recognizer_result(recognizer: *mut VoskRecognizer) -> *const c_char;
+    pub fn vosk_recognizer_partial_result(recognizer: *mut VoskRecognizer) -> *const c_char;
+    pub fn vosk_recognizer_final_result(recognizer: *mut VoskRecognizer) -> *const c_char;
+    pub fn vosk_recognizer_free(recognizer: *mut VoskRecognizer);
+}
@@ -0,0 +1,57 @@
+//! Vosk (Kaldi) transcription engine.
+//!
+//! Vosk runs small Kaldi models on the CPU with a few hundred megabytes of
+//! memory or less, which makes it usable on hardware too old for Moonshine.
+//! Unlike the other engines it decodes incrementally, so [`VoskStream`]
+//! reports partial results while audio is still arriving - suited for
+//! real-time captioning.
+//!
+//! # Model Format
+//!
+//! Expects an unpacked model directory from
+//! <https://alphacephei.com/vosk/models>, e.g. `vosk-model-small-en-us-0.15/`
+//! containing `am/`, `conf/`, `graph/` and `ivector/`.
+//!
+//! # Linking
+//!
+//! The engine links against the prebuilt `libvosk` shared library, which is
+//! not built from source. Download it from the Vosk releases page and point
+//! the linker at it, e.g. `RUSTFLAGS="-L /opt/vosk"`, and make it loadable at
+//! runtime (`LD_LIBRARY_PATH`, `DYLD_LIBRARY_PATH` or next to the binary).
+//!
+//! # Output
+//!
+//! Vosk emits lowercase text without punctuation. Segments are utterances
+//! delimited by Vosk's endpoint detection, timed from their first and last
+//! word.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::path::PathBuf;
+//! use transcribe_rs::engines::vosk::{VoskEngine, VoskUpdate};
+//! use transcribe_rs::TranscriptionEngine;
+//!
+//! let mut engine = VoskEngine::new();
+//! engine.load_model(&PathBuf::from("models/vosk-model-small-en-us-0.15"))?;
+//!
+//! let mut stream = engine.stream(None)?;
+//! # let frames: Vec<Vec<f32>> = Vec::new();
+//! for frame in frames {
+//!     match stream.accept_samples(&frame)? {
+//!         VoskUpdate::Partial(text) => println!("... {}", text),
+//!         VoskUpdate::Final(segment) => println!("{}", segment.text),
+//!     }
+//! }
+//! let result = stream.finish()?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod engine;
+mod ffi;
+
+pub use engine::{
+    VoskEngine, VoskError, VoskInferenceParams, VoskInferenceParamsBuilder, VoskModelParams,
+    VoskStream, VoskUpdate,
+};
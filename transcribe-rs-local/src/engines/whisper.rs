@@ -59,29 +59,101 @@
 //! println!("Transcription: {}", result.text);
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
+//!
+//! ## With a Speed Preset
+//!
+//! ```rust,no_run
+//! use transcribe_rs::{TranscriptionEngine, engines::whisper::{WhisperEngine, WhisperPreset}};
+//! use std::path::PathBuf;
+//!
+//! let preset = WhisperPreset::LargeV3Turbo;
+//! // Download preset.model_url() to models/ first
+//! let mut engine = WhisperEngine::new();
+//! engine.load_model(&PathBuf::from("models").join(preset.file_name()))?;
+//!
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), Some(preset.inference_params()))?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Long Audio on Several Cores
+//!
+//! ```rust,no_run
+//! use transcribe_rs::{TranscriptionEngine, engines::whisper::{WhisperEngine, WhisperParallelParams}};
+//! use std::path::PathBuf;
+//!
+//! let mut engine = WhisperEngine::new();
+//! engine.load_model(&PathBuf::from("models/whisper-medium-q4_1.bin"))?;
+//!
+//! let samples = transcribe_rs::audio::read_wav_samples(&PathBuf::from("lecture.wav"))?;
+//! let parallel = WhisperParallelParams {
+//!     thread_budget: Some(8),
+//!     ..Default::default()
+//! };
+//! let result = engine.transcribe_parallel(&samples, None, &parallel)?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
 
-use crate::{TranscriptionEngine, TranscriptionResult, TranscriptionSegment};
+use crate::longform::{LongFormConfig, LongFormTranscriber};
+use crate::{
+    hallucination::HallucinationFilter, restrict_languages, vocabulary, LanguageDetection,
+    LanguageProbability, TranscriptionEngine, TranscriptionResult, TranscriptionSegment,
+    LANGUAGE_DETECTION_WINDOW_SECS,
+};
+use derive_builder::Builder;
 use std::path::{Path, PathBuf};
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
 
 /// Parameters for configuring Whisper model loading.
 ///
-/// Currently, Whisper model loading doesn't require additional parameters
-/// beyond the model file path. This struct exists for API consistency
-/// and future extensibility.
-#[derive(Debug, Clone, Default)]
-pub struct WhisperModelParams {}
+/// Controls where whisper.cpp runs the model, e.g. to pick one of several
+/// GPUs or to stay on the CPU on servers without a usable GPU.
+#[derive(Debug, Clone)]
+pub struct WhisperModelParams {
+    /// Run the model on the GPU (Metal on macOS, Vulkan elsewhere) when one
+    /// is available (default: true).
+    pub use_gpu: bool,
+
+    /// Index of the GPU to use on machines with several (default: 0).
+    pub gpu_device: i32,
+
+    /// Use flash attention, which is faster on most GPUs (default: false).
+    pub flash_attn: bool,
+
+    /// Number of CPU threads for decoding. None uses whisper.cpp's default
+    /// (at most 4).
+    pub n_threads: Option<i32>,
+}
+
+impl Default for WhisperModelParams {
+    fn default() -> Self {
+        Self {
+            use_gpu: true,
+            gpu_device: 0,
+            flash_attn: false,
+            n_threads: None,
+        }
+    }
+}
 
 /// Parameters for configuring Whisper inference behavior.
 ///
 /// These parameters control various aspects of the transcription process,
 /// including language detection, output formatting, and noise suppression.
-#[derive(Debug, Clone)]
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into), default)]
 pub struct WhisperInferenceParams {
     /// Target language for transcription (e.g., "en", "es", "fr").
     /// If None, Whisper will auto-detect the language.
     pub language: Option<String>,
 
+    /// Languages auto-detection may pick from when `language` is None,
+    /// e.g. `["en", "de"]` for a bilingual speaker. Empty allows all.
+    pub language_hints: Vec<String>,
+
     /// Whether to translate the transcription to English.
     /// Only works with multilingual models (not .en models).
     pub translate: bool,
@@ -107,17 +179,50 @@ pub struct WhisperInferenceParams {
     /// Threshold for detecting silence/no-speech segments (0.0-1.0).
     pub no_speech_thold: f32,
 
+    /// Number of beams for beam search. 1 or less decodes greedily.
+    pub beam_size: i32,
+
+    /// Beam search patience factor. -1.0 uses whisper.cpp's default.
+    pub patience: f32,
+
+    /// Number of candidates sampled when decoding greedily with a
+    /// temperature above 0.
+    pub best_of: i32,
+
+    /// Initial sampling temperature (0.0 = greedy). whisper.cpp raises it
+    /// when a segment fails to decode. None uses whisper.cpp's default.
+    pub temperature: Option<f32>,
+
+    /// Whether to use the text of previous segments as context for the next
+    /// one. More consistent spelling and style, but errors can propagate.
+    pub condition_on_previous_text: bool,
+
     /// Initial prompt to provide context to the model.
     /// This can be used to improve transcription accuracy by providing
     /// context, vocabulary hints, or style guidance to the model.
     /// Limited to 224 tokens maximum.
     pub initial_prompt: Option<String>,
+
+    /// Domain terms and names to favour. They are appended to the initial
+    /// prompt and near-miss spellings are corrected in the output.
+    pub vocabulary: Vec<String>,
+
+    /// Filters for segments Whisper hallucinates on silence or noise, such as
+    /// "Thank you for watching" or repetition loops. `None` disables them.
+    pub hallucination_filter: Option<HallucinationFilter>,
+}
+
+impl WhisperInferenceParams {
+    pub fn builder() -> WhisperInferenceParamsBuilder {
+        WhisperInferenceParamsBuilder::default()
+    }
 }
 
 impl Default for WhisperInferenceParams {
     fn default() -> Self {
         Self {
             language: None,
+            language_hints: Vec::new(),
             translate: false,
             print_special: false,
             print_progress: false,
@@ -126,7 +231,104 @@ impl Default for WhisperInferenceParams {
             suppress_blank: true,
             suppress_non_speech_tokens: true,
             no_speech_thold: 0.2,
+            beam_size: 3,
+            patience: -1.0,
+            best_of: 1,
+            temperature: None,
+            condition_on_previous_text: false,
             initial_prompt: None,
+            vocabulary: Vec::new(),
+            hallucination_filter: None,
+        }
+    }
+}
+
+/// How [`WhisperEngine::transcribe_parallel`] spreads long audio over CPU
+/// cores.
+#[derive(Debug, Clone)]
+pub struct WhisperParallelParams {
+    /// CPU threads to use across all decoders. None uses every core.
+    pub thread_budget: Option<usize>,
+
+    /// Threads per decoder; the budget is split into decoders of this many
+    /// threads (default: 2). whisper.cpp gains little from more than a few
+    /// threads per decode, so several small decoders use the cores better.
+    pub threads_per_decoder: usize,
+
+    /// Most decoders to run at once (default: 8). Each holds its own state
+    /// of the model, from tens of MB for small models to several hundred
+    /// for large ones.
+    pub max_decoders: usize,
+
+    /// How the audio is split into windows.
+    pub longform: LongFormConfig,
+}
+
+impl Default for WhisperParallelParams {
+    fn default() -> Self {
+        Self {
+            thread_budget: None,
+            threads_per_decoder: 2,
+            max_decoders: 8,
+            longform: LongFormConfig::default(),
+        }
+    }
+}
+
+/// Fast Whisper checkpoints with recommended decode settings.
+///
+/// Both have far fewer decoder layers than large-v3 (2 and 4 instead of 32),
+/// so decoding is several times faster at close to large-v3 accuracy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhisperPreset {
+    /// Distil-Whisper distil-large-v3. English only.
+    DistilLargeV3,
+    /// Whisper large-v3-turbo. Multilingual, but cannot translate.
+    LargeV3Turbo,
+}
+
+impl WhisperPreset {
+    /// All presets.
+    pub const ALL: [WhisperPreset; 2] = [WhisperPreset::DistilLargeV3, WhisperPreset::LargeV3Turbo];
+
+    /// File name of the GGML model.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            WhisperPreset::DistilLargeV3 => "ggml-distil-large-v3.bin",
+            WhisperPreset::LargeV3Turbo => "ggml-large-v3-turbo.bin",
+        }
+    }
+
+    /// Download URL of the GGML model.
+    pub fn model_url(&self) -> &'static str {
+        match self {
+            WhisperPreset::DistilLargeV3 => {
+                "https://huggingface.co/distil-whisper/distil-large-v3-ggml/resolve/main/ggml-distil-large-v3.bin"
+            }
+            WhisperPreset::LargeV3Turbo => {
+                "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo.bin"
+            }
+        }
+    }
+
+    /// Recommended inference parameters; adjust the remaining fields with
+    /// struct update syntax.
+    ///
+    /// Distil-Whisper was distilled for greedy decoding, where beam search
+    /// adds cost for no measurable gain. Turbo keeps a small beam, which is
+    /// cheap with its 4-layer decoder and avoids most greedy repetition loops.
+    pub fn inference_params(&self) -> WhisperInferenceParams {
+        match self {
+            WhisperPreset::DistilLargeV3 => WhisperInferenceParams {
+                language: Some("en".to_string()),
+                beam_size: 1,
+                ..Default::default()
+            },
+            WhisperPreset::LargeV3Turbo => WhisperInferenceParams {
+                beam_size: 2,
+                patience: 1.0,
+                ..Default::default()
+            },
         }
     }
 }
@@ -154,6 +356,8 @@ pub struct WhisperEngine {
     loaded_model_path: Option<PathBuf>,
     state: Option<whisper_rs::WhisperState>,
     context: Option<whisper_rs::WhisperContext>,
+    /// Decoding threads from the model params
+    n_threads: Option<i32>,
 }
 
 impl Default for WhisperEngine {
@@ -181,7 +385,122 @@ impl WhisperEngine {
             loaded_model_path: None,
             state: None,
             context: None,
+            n_threads: None,
+        }
+    }
+
+    /// Run a short decode of silence to prepare the loaded model.
+    ///
+    /// whisper.cpp allocates its compute buffers and, on GPUs, compiles its
+    /// shaders during the first decode, which makes the first transcription
+    /// after a load noticeably slower. Calling this right after
+    /// `load_model()` moves that cost out of the first request. The decoder
+    /// state is kept between calls, so later transcriptions reuse it.
+    pub fn warm_up(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let state = self
+            .state
+            .as_mut()
+            .ok_or("Model not loaded. Call load_model() first.")?;
+
+        let mut full_params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        full_params.set_language(Some("en"));
+        full_params.set_no_context(true);
+        full_params.set_single_segment(true);
+        full_params.set_print_special(false);
+        full_params.set_print_progress(false);
+        full_params.set_print_realtime(false);
+        full_params.set_print_timestamps(false);
+        if let Some(n_threads) = self.n_threads {
+            full_params.set_n_threads(n_threads);
+        }
+
+        // One second of silence at 16 kHz
+        state.full(full_params, &[0.0; 16000])?;
+        Ok(())
+    }
+
+    /// Transcribe long audio with several decoders at once.
+    ///
+    /// The audio is split into windows at quiet points as with
+    /// [`LongFormTranscriber`], and the windows are decoded in parallel on
+    /// separate states of the loaded model, within the thread budget of
+    /// `parallel`. This mainly pays off on the CPU: an hour of audio on
+    /// eight cores decodes several times faster than in one pass. The
+    /// language is detected once for the whole audio, so all windows use
+    /// the same one.
+    pub fn transcribe_parallel(
+        &mut self,
+        samples: &[f32],
+        params: Option<WhisperInferenceParams>,
+        parallel: &WhisperParallelParams,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let mut whisper_params = params.unwrap_or_default();
+        self.resolve_language(&mut whisper_params, samples, true)?;
+
+        let context = self
+            .context
+            .as_ref()
+            .ok_or("Model not loaded. Call load_model() first.")?;
+        let state = self
+            .state
+            .as_mut()
+            .ok_or("Model not loaded. Call load_model() first.")?;
+
+        let budget = parallel
+            .thread_budget
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+        let threads = parallel.threads_per_decoder.max(1);
+        // No more decoders than there are windows to keep them busy
+        let min_window = (parallel.longform.min_window_secs.max(1.0) * 16000.0) as usize;
+        let decoders = (budget / threads)
+            .min(parallel.max_decoders)
+            .min(samples.len().div_ceil(min_window))
+            .max(1);
+
+        let mut extra_states = (1..decoders)
+            .map(|_| context.create_state())
+            .collect::<Result<Vec<_>, _>>()?;
+        let workers: Vec<&mut WhisperState> = std::iter::once(state)
+            .chain(extra_states.iter_mut())
+            .collect();
+
+        let n_threads = Some(threads as i32);
+        LongFormTranscriber::new(parallel.longform.clone()).transcribe_parallel(
+            samples,
+            workers,
+            |state, audio| decode(state, n_threads, &whisper_params, &audio),
+        )
+    }
+
+    /// Settle the language to decode `samples` in when none is requested.
+    ///
+    /// whisper.cpp detects among all languages, so with several hints the
+    /// language is detected here and the most likely hinted one kept. With
+    /// `always` it is also detected without hints, rather than by
+    /// whisper.cpp.
+    fn resolve_language(
+        &mut self,
+        whisper_params: &mut WhisperInferenceParams,
+        samples: &[f32],
+        always: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let multilingual = self
+            .context
+            .as_ref()
+            .is_some_and(|context| context.is_multilingual());
+        if whisper_params.language.is_some() || !multilingual {
+            return Ok(());
         }
+        whisper_params.language = match whisper_params.language_hints.as_slice() {
+            [] if !always => None,
+            // "zh-Hans" is "zh" to whisper.cpp
+            [language] => language.split('-').next().map(str::to_string),
+            hints => restrict_languages(self.detect_language(samples)?, hints)
+                .into_iter()
+                .next()
+                .map(|candidate| candidate.language),
+        };
+        Ok(())
     }
 }
 
@@ -198,19 +517,24 @@ impl TranscriptionEngine for WhisperEngine {
     fn load_model_with_params(
         &mut self,
         model_path: &Path,
-        _params: Self::ModelParams,
+        params: Self::ModelParams,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut context_params = WhisperContextParameters::default();
+        context_params
+            .use_gpu(params.use_gpu)
+            .gpu_device(params.gpu_device)
+            .flash_attn(params.flash_attn);
+
         // Create new context and state following your working pattern
-        let context = WhisperContext::new_with_params(
-            model_path.to_str().unwrap(),
-            WhisperContextParameters::default(),
-        )?;
+        let context =
+            WhisperContext::new_with_params(model_path.to_str().unwrap(), context_params)?;
 
         let state = context.create_state()?;
 
         self.context = Some(context);
         self.state = Some(state);
 
+        self.n_threads = params.n_threads;
         self.loaded_model_path = Some(model_path.to_path_buf());
         Ok(())
     }
@@ -219,6 +543,7 @@ impl TranscriptionEngine for WhisperEngine {
         self.loaded_model_path = None;
         self.state = None;
         self.context = None;
+        self.n_threads = None;
     }
 
     fn transcribe_samples(
@@ -226,56 +551,154 @@ impl TranscriptionEngine for WhisperEngine {
         samples: Vec<f32>,
         params: Option<Self::InferenceParams>,
     ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let mut whisper_params = params.unwrap_or_default();
+        self.resolve_language(&mut whisper_params, &samples, false)?;
+
+        let state = self
+            .state
+            .as_mut()
+            .ok_or("Model not loaded. Call load_model() first.")?;
+        decode(state, self.n_threads, &whisper_params, &samples)
+    }
+}
+
+impl LanguageDetection for WhisperEngine {
+    fn detect_language(
+        &mut self,
+        samples: &[f32],
+    ) -> Result<Vec<LanguageProbability>, Box<dyn std::error::Error>> {
+        let context = self
+            .context
+            .as_ref()
+            .ok_or("Model not loaded. Call load_model() first.")?;
+        if !context.is_multilingual() {
+            return Err("Language detection requires a multilingual Whisper model".into());
+        }
         let state = self
             .state
             .as_mut()
             .ok_or("Model not loaded. Call load_model() first.")?;
 
-        let whisper_params = params.unwrap_or_default();
+        let window = samples.len().min(LANGUAGE_DETECTION_WINDOW_SECS * 16000);
+        let threads = self.n_threads.map_or_else(
+            || {
+                std::thread::available_parallelism()
+                    .map_or(1, |n| n.get())
+                    .min(4)
+            },
+            |n| n.max(1) as usize,
+        );
+
+        state.pcm_to_mel(&samples[..window], threads)?;
+        let (_, probabilities) = state.lang_detect(0, threads)?;
+
+        let mut languages: Vec<LanguageProbability> = probabilities
+            .into_iter()
+            .enumerate()
+            .filter_map(|(id, probability)| {
+                whisper_rs::get_lang_str(id as i32).map(|language| LanguageProbability {
+                    language: language.to_string(),
+                    probability,
+                })
+            })
+            .collect();
+        languages.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+
+        Ok(languages)
+    }
+}
 
-        let mut full_params = FullParams::new(SamplingStrategy::BeamSearch {
-            beam_size: 3,
-            patience: -1.0,
-        });
-        full_params.set_language(whisper_params.language.as_deref());
-        full_params.set_translate(whisper_params.translate);
-        full_params.set_print_special(whisper_params.print_special);
-        full_params.set_print_progress(whisper_params.print_progress);
-        full_params.set_print_realtime(whisper_params.print_realtime);
-        full_params.set_print_timestamps(whisper_params.print_timestamps);
-        full_params.set_suppress_blank(whisper_params.suppress_blank);
-        full_params.set_suppress_non_speech_tokens(whisper_params.suppress_non_speech_tokens);
-        full_params.set_no_speech_thold(whisper_params.no_speech_thold);
-
-        if let Some(ref prompt) = whisper_params.initial_prompt {
-            full_params.set_initial_prompt(prompt);
+/// Decode `samples` on `state` with `whisper_params`, whose language has
+/// already been settled.
+fn decode(
+    state: &mut WhisperState,
+    n_threads: Option<i32>,
+    whisper_params: &WhisperInferenceParams,
+    samples: &[f32],
+) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+    let strategy = if whisper_params.beam_size > 1 {
+        SamplingStrategy::BeamSearch {
+            beam_size: whisper_params.beam_size,
+            patience: whisper_params.patience,
+        }
+    } else {
+        SamplingStrategy::Greedy {
+            best_of: whisper_params.best_of.max(1),
         }
+    };
+    let mut full_params = FullParams::new(strategy);
+    full_params.set_language(whisper_params.language.as_deref());
+    full_params.set_translate(whisper_params.translate);
+    full_params.set_print_special(whisper_params.print_special);
+    full_params.set_print_progress(whisper_params.print_progress);
+    full_params.set_print_realtime(whisper_params.print_realtime);
+    full_params.set_print_timestamps(whisper_params.print_timestamps);
+    full_params.set_suppress_blank(whisper_params.suppress_blank);
+    full_params.set_suppress_non_speech_tokens(whisper_params.suppress_non_speech_tokens);
+    full_params.set_no_speech_thold(whisper_params.no_speech_thold);
+    full_params.set_no_context(!whisper_params.condition_on_previous_text);
+    if let Some(n_threads) = n_threads {
+        full_params.set_n_threads(n_threads);
+    }
+    if let Some(temperature) = whisper_params.temperature {
+        full_params.set_temperature(temperature);
+    }
 
-        state.full(full_params, &samples)?;
+    let prompt = vocabulary::vocabulary_prompt(
+        &whisper_params.vocabulary,
+        whisper_params.initial_prompt.as_deref(),
+    );
+    if let Some(ref prompt) = prompt {
+        full_params.set_initial_prompt(prompt);
+    }
 
-        let num_segments = state
-            .full_n_segments()
-            .expect("failed to get number of segments");
+    state.full(full_params, samples)?;
 
-        let mut segments = Vec::new();
-        let mut full_text = String::new();
+    let num_segments = state
+        .full_n_segments()
+        .expect("failed to get number of segments");
 
-        for i in 0..num_segments {
-            let text = state.full_get_segment_text(i)?;
-            let start = state.full_get_segment_t0(i)? as f32 / 100.0;
-            let end = state.full_get_segment_t1(i)? as f32 / 100.0;
+    let mut segments = Vec::new();
+    let mut no_speech_probs = Vec::new();
+    let mut full_text = String::new();
 
-            segments.push(TranscriptionSegment {
-                start,  
-                end,
-                text: text.clone(),
-            });
-            full_text.push_str(&text);
-        }
+    for i in 0..num_segments {
+        let text = state.full_get_segment_text(i)?;
+        let start = state.full_get_segment_t0(i)? as f32 / 100.0;
+        let end = state.full_get_segment_t1(i)? as f32 / 100.0;
 
-        Ok(TranscriptionResult {
-            text: full_text.trim().to_string(),
-            segments: Some(segments),
-        })
+        segments.push(TranscriptionSegment {
+            start,
+            end,
+            text: text.clone(),
+        });
+        no_speech_probs.push(state.full_get_segment_no_speech_prob(i));
+        full_text.push_str(&text);
+    }
+
+    if let Some(ref filter) = whisper_params.hallucination_filter {
+        segments = filter.filter_segments(segments, &no_speech_probs);
+        full_text = segments
+            .iter()
+            .map(|s| s.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
     }
+
+    // The language whisper.cpp decoded with: detected, or as requested
+    let language = state
+        .full_lang_id_from_state()
+        .ok()
+        .and_then(whisper_rs::get_lang_str)
+        .map(str::to_string)
+        .or_else(|| whisper_params.language.clone());
+
+    let mut result = TranscriptionResult {
+        text: full_text.trim().to_string(),
+        segments: Some(segments),
+        language,
+    };
+    vocabulary::apply(&mut result, &whisper_params.vocabulary);
+
+    Ok(result)
 }
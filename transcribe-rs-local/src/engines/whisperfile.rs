@@ -3,14 +3,19 @@
 //! This module provides a transcription engine that uses Mozilla's whisperfile
 //! for speech-to-text conversion. The engine manages the whisperfile server
 //! lifecycle automatically - spawning it on model load and stopping it on unload.
+//! It can also use a whisperfile or whisper.cpp server that is already running,
+//! e.g. a long-lived GPU server on another machine.
 //!
 //! # Requirements
 //!
-//! - The whisperfile binary must be available on the system
+//! - The whisperfile binary must be available on the system, unless an
+//!   existing server is used
 //! - Whisper model in GGML/GGUF format
 //!
 //! # Examples
 //!
+//! ## Managed Server
+//!
 //! ```rust,no_run
 //! use transcribe_rs::{TranscriptionEngine, engines::whisperfile::WhisperfileEngine};
 //! use std::path::PathBuf;
@@ -24,88 +29,87 @@
 //! // Server is automatically stopped when engine is dropped
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
+//!
+//! ## Existing Server
+//!
+//! ```rust,no_run
+//! use transcribe_rs::{TranscriptionEngine, engines::whisperfile::WhisperfileEngine};
+//! use std::path::{Path, PathBuf};
+//!
+//! let mut engine = WhisperfileEngine::connect("http://gpu-box:8080");
+//! // Only checks that the server is reachable; it keeps its own model
+//! engine.load_model(Path::new(""))?;
+//!
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), None)?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## Several Models
+//!
+//! [`WhisperfilePool`] keeps a server per model alive and routes each
+//! request to the one for the requested model:
+//!
+//! ```rust,no_run
+//! use transcribe_rs::engines::whisperfile::{WhisperfileModelParams, WhisperfilePool};
+//!
+//! let pool = WhisperfilePool::new("/path/to/whisperfile", 2)
+//!     .with_model("small.en", "models/ggml-small.en.bin", WhisperfileModelParams::default())
+//!     .with_model("large-v3", "models/ggml-large-v3.bin", WhisperfileModelParams::default());
+//!
+//! let samples = vec![0.0; 16000];
+//! let result = pool.transcribe_samples("small.en", samples, None)?;
+//! println!("Transcription: {}", result.text);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
 
-use crate::{TranscriptionEngine, TranscriptionResult, TranscriptionSegment};
+pub use crate::engines::http::WavFormat;
+use crate::engines::http::{self, MultipartForm};
+use crate::limits::ResourceLimits;
+use crate::{vocabulary, TranscriptionEngine, TranscriptionResult, TranscriptionSegment};
+use derive_builder::Builder;
 use log::{debug, error, info, trace, warn};
 use serde::Deserialize;
-use std::io::{BufRead, BufReader, Write};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader};
+use std::net::TcpListener;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use ureq::Agent;
 
-/// Custom multipart form-data builder for HTTP requests.
-struct MultipartForm {
-    boundary: String,
-    body: Vec<u8>,
+/// Number of server output lines kept for crash reports.
+const STDERR_TAIL_LINES: usize = 20;
+
+#[derive(thiserror::Error, Debug)]
+pub enum WhisperfileError {
+    /// The managed server process exited. `stderr` holds its last output
+    /// lines, which usually say why.
+    #[error("Whisperfile server crashed ({status}). Last output:\n{}", .stderr.join("\n"))]
+    ServerCrashed {
+        status: ExitStatus,
+        stderr: Vec<String>,
+    },
+    /// A request hit the request or read timeout set on the engine.
+    #[error("Request to whisperfile server timed out after {timeout:?}")]
+    Timeout { timeout: Duration },
+    /// A [`WhisperfilePool`] was asked for a model it doesn't have.
+    #[error("No model named '{0}' in the whisperfile pool")]
+    UnknownModel(String),
 }
 
-impl MultipartForm {
-    /// Create a new multipart form with a random boundary.
-    fn new() -> Self {
-        // Generate a simple boundary using timestamp and a fixed prefix
-        let boundary = format!(
-            "----transcribe-rs-boundary-{}",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos()
-        );
-        Self {
-            boundary,
-            body: Vec::new(),
-        }
-    }
-
-    /// Add a file part to the form.
-    fn file(mut self, name: &str, filename: &str, content_type: &str, data: Vec<u8>) -> Self {
-        // Write boundary
-        write!(self.body, "--{}\r\n", self.boundary).unwrap();
-        // Write content disposition header
-        write!(
-            self.body,
-            "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
-            name, filename
-        )
-        .unwrap();
-        // Write content type header
-        write!(self.body, "Content-Type: {}\r\n", content_type).unwrap();
-        // Blank line before content
-        write!(self.body, "\r\n").unwrap();
-        // Write file data
-        self.body.extend_from_slice(&data);
-        // Trailing CRLF
-        write!(self.body, "\r\n").unwrap();
-        self
-    }
-
-    /// Add a text field to the form.
-    fn text(mut self, name: &str, value: &str) -> Self {
-        // Write boundary
-        write!(self.body, "--{}\r\n", self.boundary).unwrap();
-        // Write content disposition header
-        write!(
-            self.body,
-            "Content-Disposition: form-data; name=\"{}\"\r\n",
-            name
-        )
-        .unwrap();
-        // Blank line before content
-        write!(self.body, "\r\n").unwrap();
-        // Write text value
-        write!(self.body, "{}\r\n", value).unwrap();
-        self
-    }
-
-    /// Finalize the form and return the content type and body.
-    fn build(mut self) -> (String, Vec<u8>) {
-        // Write closing boundary
-        write!(self.body, "--{}--\r\n", self.boundary).unwrap();
-        let content_type = format!("multipart/form-data; boundary={}", self.boundary);
-        (content_type, self.body)
-    }
+/// What to do when the managed server process exits unexpectedly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RestartPolicy {
+    /// Fail every request with [`WhisperfileError::ServerCrashed`] until the
+    /// model is loaded again (default)
+    #[default]
+    Never,
+    /// Restart the server before the next request, at most `max_restarts`
+    /// times per `load_model()`
+    OnCrash { max_restarts: u32 },
 }
 
 /// JSON output structure from whisperfile server (verbose_json format)
@@ -114,6 +118,8 @@ struct WhisperfileOutput {
     text: String,
     #[serde(default)]
     segments: Vec<WhisperfileSegment>,
+    #[serde(default)]
+    language: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -123,6 +129,14 @@ struct WhisperfileSegment {
     end: f32,
 }
 
+/// Event of a streamed response: a decoded segment, or the final output
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WhisperfileEvent {
+    Segment(WhisperfileSegment),
+    Done(WhisperfileOutput),
+}
+
 impl From<WhisperfileOutput> for TranscriptionResult {
     fn from(output: WhisperfileOutput) -> Self {
         let segments = if output.segments.is_empty() {
@@ -144,6 +158,7 @@ impl From<WhisperfileOutput> for TranscriptionResult {
         TranscriptionResult {
             text: output.text.trim().to_string(),
             segments,
+            language: http::language_code(output.language),
         }
     }
 }
@@ -184,9 +199,13 @@ impl std::fmt::Display for GPUMode {
 }
 
 /// Parameters for configuring Whisperfile model loading.
+///
+/// With an existing server (see [`WhisperfileEngine::connect`]) only
+/// `startup_timeout_secs` is used, as the time to wait for it to respond.
 #[derive(Debug, Clone)]
 pub struct WhisperfileModelParams {
-    /// Port for the whisperfile server (default: 8080)
+    /// Port for the whisperfile server (default: 8080). 0 picks a free port;
+    /// see [`WhisperfileEngine::port`] for the one chosen.
     pub port: u16,
     /// Host to bind the server to (default: "127.0.0.1")
     pub host: String,
@@ -194,6 +213,26 @@ pub struct WhisperfileModelParams {
     pub startup_timeout_secs: u64,
     /// GPU acceleration mode (default: Auto)
     pub gpu: GPUMode,
+    /// Whether to restart the server when it crashes (default: Never)
+    pub restart: RestartPolicy,
+    /// Number of threads the server decodes with (`--threads`). None uses
+    /// whisper.cpp's default.
+    pub threads: Option<usize>,
+    /// Beam size for beam search (`--beam-size`). None uses whisper.cpp's
+    /// default.
+    pub beam_size: Option<usize>,
+    /// Maximum number of text context tokens carried over between windows
+    /// (`--max-context`). Lower values reduce repetition loops.
+    pub max_context: Option<usize>,
+    /// Use flash attention (`--flash-attn`), faster on supported GPUs
+    pub flash_attn: bool,
+    /// Skip timestamp prediction (`--no-timestamps`). Results then have no
+    /// usable segment timing.
+    pub no_timestamps: bool,
+    /// Further command-line arguments passed to the server as is
+    pub extra_args: Vec<String>,
+    /// Priority, CPU affinity and memory limit of the server process
+    pub limits: ResourceLimits,
 }
 
 impl Default for WhisperfileModelParams {
@@ -203,12 +242,21 @@ impl Default for WhisperfileModelParams {
             host: "127.0.0.1".to_string(),
             startup_timeout_secs: 30,
             gpu: GPUMode::default(),
+            restart: RestartPolicy::default(),
+            threads: None,
+            beam_size: None,
+            max_context: None,
+            flash_attn: false,
+            no_timestamps: false,
+            extra_args: Vec::new(),
+            limits: ResourceLimits::default(),
         }
     }
 }
 
 /// Parameters for configuring Whisperfile inference behavior.
-#[derive(Debug, Clone)]
+#[derive(Builder, Debug, Clone)]
+#[builder(setter(into), default)]
 pub struct WhisperfileInferenceParams {
     /// Target language for transcription (e.g., "en", "es", "fr").
     /// If None, whisperfile will auto-detect the language.
@@ -222,6 +270,25 @@ pub struct WhisperfileInferenceParams {
 
     /// Response format hint.
     pub response_format: Option<String>,
+
+    /// Text to condition the model on, e.g. names and domain terms or the
+    /// previous transcript. Sent as the server's `prompt` parameter.
+    pub prompt: Option<String>,
+
+    /// Domain terms and names to favour. They are sent to the server as a
+    /// prompt and near-miss spellings are corrected in the output.
+    pub vocabulary: Vec<String>,
+
+    /// Ask the server to stream segments as server-sent events while it
+    /// decodes, instead of answering once the whole file is done. See
+    /// [`WhisperfileEngine::transcribe_samples_with_callback`].
+    pub stream: bool,
+}
+
+impl WhisperfileInferenceParams {
+    pub fn builder() -> WhisperfileInferenceParamsBuilder {
+        WhisperfileInferenceParamsBuilder::default()
+    }
 }
 
 impl Default for WhisperfileInferenceParams {
@@ -231,6 +298,9 @@ impl Default for WhisperfileInferenceParams {
             translate: false,
             temperature: None,
             response_format: Some("verbose_json".to_string()),
+            prompt: None,
+            vocabulary: Vec::new(),
+            stream: false,
         }
     }
 }
@@ -240,6 +310,8 @@ impl Default for WhisperfileInferenceParams {
 /// This engine manages the whisperfile server lifecycle automatically.
 /// When you call `load_model()`, it spawns the whisperfile server process.
 /// When the engine is dropped or `unload_model()` is called, the server is stopped.
+/// Engines created with [`WhisperfileEngine::connect`] use a server that is
+/// already running instead and never start or stop it.
 ///
 /// # Examples
 ///
@@ -250,9 +322,26 @@ impl Default for WhisperfileInferenceParams {
 /// let mut engine = WhisperfileEngine::new(PathBuf::from("/path/to/whisperfile"));
 /// ```
 pub struct WhisperfileEngine {
-    binary_path: PathBuf,
+    /// Server binary, when the engine manages the server process
+    binary_path: Option<PathBuf>,
     server_url: String,
     agent: Agent,
+    /// Limit for a whole request, see `with_request_timeout()`
+    request_timeout: Option<Duration>,
+    /// Limit for waiting on the server's response, see `with_read_timeout()`
+    read_timeout: Option<Duration>,
+    /// Encoding of samples sent to the server, see `with_wav_format()`
+    wav_format: WavFormat,
+    /// Whether `load_model()` succeeded and the server can be used
+    loaded: bool,
+    /// Port of the managed server while it runs
+    port: Option<u16>,
+    /// Model and parameters the managed server was started with, for restarts
+    launch: Option<(PathBuf, WhisperfileModelParams)>,
+    /// Restarts after crashes since the last `load_model()`
+    restarts: u32,
+    /// Last lines the server wrote to stderr, reported when it crashes
+    stderr_tail: Arc<Mutex<VecDeque<String>>>,
     server_process: Option<Child>,
     /// Flag to signal the log reader thread to stop
     log_shutdown: Arc<AtomicBool>,
@@ -277,17 +366,176 @@ impl WhisperfileEngine {
     /// ```
     pub fn new(binary_path: impl Into<PathBuf>) -> Self {
         Self {
-            binary_path: binary_path.into(),
+            binary_path: Some(binary_path.into()),
             server_url: String::new(),
             agent: Agent::new_with_defaults(),
+            request_timeout: None,
+            read_timeout: None,
+            wav_format: WavFormat::default(),
+            loaded: false,
+            port: None,
+            launch: None,
+            restarts: 0,
+            stderr_tail: Arc::new(Mutex::new(VecDeque::new())),
+            server_process: None,
+            log_shutdown: Arc::new(AtomicBool::new(false)),
+            log_thread: None,
+        }
+    }
+
+    /// Create an engine for a whisperfile or whisper.cpp server that is
+    /// already running, possibly on another machine.
+    ///
+    /// `load_model()` then only checks that the server responds; the model
+    /// path is ignored and the server transcribes with the model it was
+    /// started with. Nothing is spawned or stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Base URL of the server, e.g. `http://localhost:8080`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use transcribe_rs::engines::whisperfile::WhisperfileEngine;
+    ///
+    /// let engine = WhisperfileEngine::connect("http://127.0.0.1:8080");
+    /// ```
+    pub fn connect(url: &str) -> Self {
+        Self {
+            binary_path: None,
+            server_url: url
+                .trim_end_matches('/')
+                .trim_end_matches("/inference")
+                .to_string(),
+            agent: Agent::new_with_defaults(),
+            request_timeout: None,
+            read_timeout: None,
+            wav_format: WavFormat::default(),
+            loaded: false,
+            port: None,
+            launch: None,
+            restarts: 0,
+            stderr_tail: Arc::new(Mutex::new(VecDeque::new())),
             server_process: None,
             log_shutdown: Arc::new(AtomicBool::new(false)),
             log_thread: None,
         }
     }
 
+    /// Fail requests that take longer than `timeout` in total, including
+    /// uploading the audio, with [`WhisperfileError::Timeout`]. Unlimited by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use transcribe_rs::engines::whisperfile::WhisperfileEngine;
+    /// use std::time::Duration;
+    ///
+    /// let engine = WhisperfileEngine::connect("http://127.0.0.1:8080")
+    ///     .with_request_timeout(Duration::from_secs(600))
+    ///     .with_read_timeout(Duration::from_secs(120));
+    /// ```
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self.agent = self.build_agent();
+        self
+    }
+
+    /// Fail requests when the server sends nothing for `timeout` with
+    /// [`WhisperfileError::Timeout`]. The server only responds once the
+    /// whole file is transcribed, so allow for long audio on slow hardware.
+    /// Unlimited by default.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self.agent = self.build_agent();
+        self
+    }
+
+    /// Encoding of the WAV files `transcribe_samples()` sends. Float WAV
+    /// keeps the samples exactly as given; 16-bit PCM halves the upload for
+    /// servers on slow links or ones that can't read float WAV.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use transcribe_rs::engines::whisperfile::{WavFormat, WhisperfileEngine};
+    ///
+    /// let engine = WhisperfileEngine::connect("http://gpu-box:8080")
+    ///     .with_wav_format(WavFormat::Int16);
+    /// ```
+    pub fn with_wav_format(mut self, format: WavFormat) -> Self {
+        self.wav_format = format;
+        self
+    }
+
+    fn build_agent(&self) -> Agent {
+        Agent::config_builder()
+            .timeout_global(self.request_timeout)
+            .timeout_recv_response(self.read_timeout)
+            .timeout_recv_body(self.read_timeout)
+            .build()
+            .into()
+    }
+
+    /// Convert a failed request into an error, typed for timeouts
+    fn request_error(&self, e: ureq::Error) -> Box<dyn std::error::Error> {
+        match e {
+            ureq::Error::Timeout(kind) => {
+                let timeout = match kind {
+                    ureq::Timeout::Global => self.request_timeout,
+                    _ => self.read_timeout,
+                }
+                .unwrap_or_default();
+                error!(
+                    "Request to whisperfile server timed out after {:?}",
+                    timeout
+                );
+                WhisperfileError::Timeout { timeout }.into()
+            }
+            e => {
+                error!("Request to whisperfile server failed: {}", e);
+                format!("Request to whisperfile server failed: {}", e).into()
+            }
+        }
+    }
+
+    /// Port the managed server listens on, e.g. the free port picked for
+    /// `port: 0`. `None` until `load_model()` starts it, and for engines
+    /// created with [`WhisperfileEngine::connect`].
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Check that the server is running and responds. A crashed managed
+    /// server is restarted first if the restart policy allows it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use transcribe_rs::{TranscriptionEngine, engines::whisperfile::WhisperfileEngine};
+    /// use std::path::Path;
+    ///
+    /// let mut engine = WhisperfileEngine::connect("http://gpu-box:8080");
+    /// engine.load_model(Path::new(""))?;
+    /// engine.health_check()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn health_check(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.loaded {
+            return Err("Model not loaded. Call load_model() first.".into());
+        }
+        self.check_server()?;
+        self.agent
+            .get(&format!("{}/", self.server_url))
+            .call()
+            .map_err(|e| self.request_error(e))?;
+        Ok(())
+    }
+
     /// Wait for the server to become ready
-    fn wait_for_server(&self, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
+    fn wait_for_server(&mut self, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
         let start = Instant::now();
         let url = format!("{}/", self.server_url);
 
@@ -298,6 +546,15 @@ impl WhisperfileEngine {
         );
 
         while start.elapsed() < timeout {
+            // Fail fast when the server dies, e.g. because the port was taken
+            if let Some(child) = self.server_process.as_mut() {
+                if let Some(status) = child.try_wait()? {
+                    error!("Whisperfile server exited during startup: {}", status);
+                    let stderr = self.stderr_tail.lock().unwrap().iter().cloned().collect();
+                    return Err(WhisperfileError::ServerCrashed { status, stderr }.into());
+                }
+            }
+
             trace!(
                 "Polling whisperfile server... ({:.1}s elapsed)",
                 start.elapsed().as_secs_f32()
@@ -313,76 +570,82 @@ impl WhisperfileEngine {
         }
 
         error!(
-            "Whisperfile server failed to start within {} seconds",
+            "Whisperfile server at {} not reachable within {} seconds",
+            self.server_url,
             timeout.as_secs()
         );
         Err(format!(
-            "Whisperfile server failed to start within {} seconds",
+            "Whisperfile server at {} not reachable within {} seconds",
+            self.server_url,
             timeout.as_secs()
         )
         .into())
     }
-}
 
-impl Drop for WhisperfileEngine {
-    fn drop(&mut self) {
-        self.unload_model();
-    }
-}
-
-impl TranscriptionEngine for WhisperfileEngine {
-    type InferenceParams = WhisperfileInferenceParams;
-    type ModelParams = WhisperfileModelParams;
-
-    fn load_model_with_params(
+    /// Spawn the managed server process and wait until it responds
+    fn start_server(
         &mut self,
+        binary_path: &Path,
         model_path: &Path,
-        params: Self::ModelParams,
+        params: &WhisperfileModelParams,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Stop any existing server
-        self.unload_model();
-
-        // Verify binary exists
-        if !self.binary_path.exists() {
-            warn!(
-                "Whisperfile binary not found: {}",
-                self.binary_path.display()
-            );
-            return Err(format!(
-                "Whisperfile binary not found: {}",
-                self.binary_path.display()
-            )
-            .into());
-        }
-
-        // Verify model exists
-        if !model_path.exists() {
-            warn!("Model file not found: {}", model_path.display());
-            return Err(format!("Model file not found: {}", model_path.display()).into());
-        }
-
-        self.server_url = format!("http://{}:{}", params.host, params.port);
+        // Pick a free port for 0. Otherwise make sure the port is free, or
+        // the readiness check could end up talking to another service.
+        let port = match params.port {
+            0 => free_port(&params.host)?,
+            port => {
+                if TcpListener::bind((params.host.as_str(), port)).is_err() {
+                    warn!("Port {} on {} is already in use", port, params.host);
+                    return Err(
+                        format!("Port {} on {} is already in use", port, params.host).into(),
+                    );
+                }
+                port
+            }
+        };
+        self.server_url = format!("http://{}:{}", params.host, port);
 
         info!(
             "Starting whisperfile server: binary={}, model={}, host={}, port={}, gpu={}",
-            self.binary_path.display(),
+            binary_path.display(),
             model_path.display(),
             params.host,
-            params.port,
+            port,
             params.gpu
         );
 
-        // Spawn the server process with stderr piped for logging
-        let mut child = Command::new(&self.binary_path)
+        let mut command = Command::new(binary_path);
+        command
             .arg("--server")
             .arg("-m")
             .arg(model_path)
             .arg("--host")
             .arg(&params.host)
             .arg("--port")
-            .arg(params.port.to_string())
+            .arg(port.to_string())
             .arg("--gpu")
-            .arg(params.gpu.as_arg())
+            .arg(params.gpu.as_arg());
+        if let Some(threads) = params.threads {
+            command.arg("--threads").arg(threads.to_string());
+        }
+        if let Some(beam_size) = params.beam_size {
+            command.arg("--beam-size").arg(beam_size.to_string());
+        }
+        if let Some(max_context) = params.max_context {
+            command.arg("--max-context").arg(max_context.to_string());
+        }
+        if params.flash_attn {
+            command.arg("--flash-attn");
+        }
+        if params.no_timestamps {
+            command.arg("--no-timestamps");
+        }
+        command.args(&params.extra_args);
+        params.limits.apply(&mut command);
+        debug!("Whisperfile server command: {:?}", command);
+
+        // Spawn the server process with stderr piped for logging
+        let mut child = command
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
             .spawn()
@@ -392,12 +655,20 @@ impl TranscriptionEngine for WhisperfileEngine {
             })?;
 
         debug!("Whisperfile server process spawned (pid: {:?})", child.id());
+        if let Err(e) = params.limits.attach(&child) {
+            error!("Failed to limit whisperfile server resources: {}", e);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("Failed to limit whisperfile server resources: {}", e).into());
+        }
 
         // Reset shutdown flag and spawn a thread to read server logs
         self.log_shutdown.store(false, Ordering::SeqCst);
+        self.stderr_tail.lock().unwrap().clear();
 
         if let Some(stderr) = child.stderr.take() {
             let shutdown_flag = Arc::clone(&self.log_shutdown);
+            let stderr_tail = Arc::clone(&self.stderr_tail);
             let log_thread = std::thread::spawn(move || {
                 let reader = BufReader::new(stderr);
                 for line in reader.lines() {
@@ -407,6 +678,11 @@ impl TranscriptionEngine for WhisperfileEngine {
                     match line {
                         Ok(line) => {
                             debug!("[whisperfile] {}", line);
+                            let mut tail = stderr_tail.lock().unwrap();
+                            if tail.len() == STDERR_TAIL_LINES {
+                                tail.pop_front();
+                            }
+                            tail.push_back(line);
                         }
                         Err(e) => {
                             trace!("Error reading whisperfile stderr: {}", e);
@@ -422,12 +698,18 @@ impl TranscriptionEngine for WhisperfileEngine {
         self.server_process = Some(child);
 
         // Wait for server to be ready
-        self.wait_for_server(Duration::from_secs(params.startup_timeout_secs))?;
+        if let Err(e) = self.wait_for_server(Duration::from_secs(params.startup_timeout_secs)) {
+            self.unload_model();
+            return Err(e);
+        }
 
+        self.port = Some(port);
+        self.loaded = true;
         Ok(())
     }
 
-    fn unload_model(&mut self) {
+    /// Stop the managed server process, if any
+    fn stop_server(&mut self) {
         // Signal the log reader thread to stop
         self.log_shutdown.store(true, Ordering::SeqCst);
 
@@ -444,7 +726,101 @@ impl TranscriptionEngine for WhisperfileEngine {
             let _ = thread.join();
         }
 
-        self.server_url.clear();
+        self.port = None;
+    }
+
+    /// Check that the managed server is still running. A crashed server is
+    /// restarted if the restart policy allows it, otherwise this returns
+    /// [`WhisperfileError::ServerCrashed`].
+    fn check_server(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let status = match self.server_process.as_mut().map(Child::try_wait) {
+            Some(Ok(Some(status))) => status,
+            _ => return Ok(()),
+        };
+        let stderr: Vec<String> = self.stderr_tail.lock().unwrap().iter().cloned().collect();
+        error!("Whisperfile server crashed ({})", status);
+
+        let (binary_path, model_path, params) = match (&self.binary_path, &self.launch) {
+            (Some(binary_path), Some((model_path, params))) => {
+                (binary_path.clone(), model_path.clone(), params.clone())
+            }
+            _ => return Err(WhisperfileError::ServerCrashed { status, stderr }.into()),
+        };
+        match params.restart {
+            RestartPolicy::OnCrash { max_restarts } if self.restarts < max_restarts => {
+                self.restarts += 1;
+                warn!(
+                    "Restarting whisperfile server (restart {} of {})",
+                    self.restarts, max_restarts
+                );
+                self.stop_server();
+                self.start_server(&binary_path, &model_path, &params)
+            }
+            _ => Err(WhisperfileError::ServerCrashed { status, stderr }.into()),
+        }
+    }
+}
+
+impl Drop for WhisperfileEngine {
+    fn drop(&mut self) {
+        self.unload_model();
+    }
+}
+
+impl TranscriptionEngine for WhisperfileEngine {
+    type InferenceParams = WhisperfileInferenceParams;
+    type ModelParams = WhisperfileModelParams;
+
+    fn load_model_with_params(
+        &mut self,
+        model_path: &Path,
+        params: Self::ModelParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Stop any existing server
+        self.unload_model();
+
+        let binary_path = match &self.binary_path {
+            Some(binary_path) => binary_path.clone(),
+            None => {
+                // Existing server: nothing to start, just check it is up
+                debug!(
+                    "Using existing whisperfile server at {}, ignoring model path {}",
+                    self.server_url,
+                    model_path.display()
+                );
+                self.wait_for_server(Duration::from_secs(params.startup_timeout_secs))?;
+                self.loaded = true;
+                return Ok(());
+            }
+        };
+
+        // Verify binary exists
+        if !binary_path.exists() {
+            warn!("Whisperfile binary not found: {}", binary_path.display());
+            return Err(format!("Whisperfile binary not found: {}", binary_path.display()).into());
+        }
+
+        // Verify model exists
+        if !model_path.exists() {
+            warn!("Model file not found: {}", model_path.display());
+            return Err(format!("Model file not found: {}", model_path.display()).into());
+        }
+
+        self.start_server(&binary_path, model_path, &params)?;
+        self.launch = Some((model_path.to_path_buf(), params));
+        self.restarts = 0;
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        self.stop_server();
+
+        self.loaded = false;
+        self.launch = None;
+        // An existing server keeps its URL so the engine can be loaded again
+        if self.binary_path.is_some() {
+            self.server_url.clear();
+        }
     }
 
     fn transcribe_samples(
@@ -452,31 +828,15 @@ impl TranscriptionEngine for WhisperfileEngine {
         samples: Vec<f32>,
         params: Option<Self::InferenceParams>,
     ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
-        if self.server_process.is_none() {
+        if !self.loaded {
             warn!("Attempted to transcribe samples without loading model");
             return Err("Model not loaded. Call load_model() first.".into());
         }
+        self.check_server()?;
 
         debug!("Transcribing {} samples", samples.len());
 
-        // Write samples to a WAV buffer in memory
-        let mut wav_buffer = std::io::Cursor::new(Vec::new());
-        let spec = hound::WavSpec {
-            channels: 1,
-            sample_rate: 16000,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-
-        let mut writer = hound::WavWriter::new(&mut wav_buffer, spec)?;
-        for sample in &samples {
-            let sample_i16 = (sample * i16::MAX as f32) as i16;
-            writer.write_sample(sample_i16)?;
-        }
-        writer.finalize()?;
-
-        let wav_data = wav_buffer.into_inner();
-        self.transcribe_wav_bytes(wav_data, params)
+        self.transcribe_audio(Audio::Samples(&samples), params, &mut |_| {})
     }
 
     fn transcribe_file(
@@ -484,36 +844,110 @@ impl TranscriptionEngine for WhisperfileEngine {
         wav_path: &Path,
         params: Option<Self::InferenceParams>,
     ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
-        if self.server_process.is_none() {
+        if !self.loaded {
             warn!("Attempted to transcribe file without loading model");
             return Err("Model not loaded. Call load_model() first.".into());
         }
+        self.check_server()?;
 
         debug!("Transcribing file: {}", wav_path.display());
 
         let wav_data = std::fs::read(wav_path)?;
-        self.transcribe_wav_bytes(wav_data, params)
+        self.transcribe_audio(Audio::Wav(wav_data), params, &mut |_| {})
     }
 }
 
+/// Audio to send to the server.
+enum Audio<'a> {
+    /// A WAV file as is
+    Wav(Vec<u8>),
+    /// 16kHz mono samples, encoded with the engine's `wav_format`
+    Samples(&'a [f32]),
+}
+
+/// Ask the OS for a port that is free on `host`.
+///
+/// The port is released again before the server binds it, so another
+/// process could take it in between; the server then fails to start and
+/// `load_model()` returns an error.
+fn free_port(host: &str) -> Result<u16, Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind((host, 0))
+        .map_err(|e| format!("Failed to find a free port on {}: {}", host, e))?;
+    Ok(listener.local_addr()?.port())
+}
+
 impl WhisperfileEngine {
-    fn transcribe_wav_bytes(
+    /// Transcribe audio samples, calling `on_segment` with every segment as
+    /// soon as it is available, e.g. for live captions.
+    ///
+    /// With `stream` set in `params`, segments arrive while the server is
+    /// still decoding. Otherwise, or if the server does not stream, they are
+    /// all passed once the response is complete.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use transcribe_rs::{TranscriptionEngine, engines::whisperfile::{WhisperfileEngine, WhisperfileInferenceParams}};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut engine = WhisperfileEngine::connect("http://127.0.0.1:8080");
+    /// engine.load_model(&PathBuf::new())?;
+    ///
+    /// let params = WhisperfileInferenceParams {
+    ///     stream: true,
+    ///     ..Default::default()
+    /// };
+    /// let samples = vec![0.0; 16000];
+    /// engine.transcribe_samples_with_callback(samples, Some(params), |segment| {
+    ///     println!("[{:.2}s] {}", segment.start, segment.text);
+    /// })?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn transcribe_samples_with_callback(
+        &mut self,
+        samples: Vec<f32>,
+        params: Option<WhisperfileInferenceParams>,
+        mut on_segment: impl FnMut(&TranscriptionSegment),
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        if !self.loaded {
+            warn!("Attempted to transcribe samples without loading model");
+            return Err("Model not loaded. Call load_model() first.".into());
+        }
+        self.check_server()?;
+
+        debug!(
+            "Transcribing {} samples with segment callback",
+            samples.len()
+        );
+
+        self.transcribe_audio(Audio::Samples(&samples), params, &mut on_segment)
+    }
+
+    fn transcribe_audio(
         &self,
-        wav_data: Vec<u8>,
+        audio: Audio<'_>,
         params: Option<WhisperfileInferenceParams>,
+        on_segment: &mut dyn FnMut(&TranscriptionSegment),
     ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
         let params = params.unwrap_or_default();
 
         trace!(
-            "Preparing transcription request: {} bytes, language={:?}, translate={}, temp={:?}",
-            wav_data.len(),
+            "Preparing transcription request: language={:?}, translate={}, temp={:?}",
             params.language,
             params.translate,
             params.temperature
         );
 
-        // Build multipart form using custom builder
-        let mut form = MultipartForm::new().file("file", "audio.wav", "audio/wav", wav_data);
+        // Build multipart form using custom builder. Samples are encoded
+        // straight into the request body
+        let mut form = match audio {
+            Audio::Wav(wav_data) => {
+                MultipartForm::new().file("file", "audio.wav", "audio/wav", wav_data)
+            }
+            Audio::Samples(samples) => {
+                MultipartForm::new().wav("file", "audio.wav", samples, self.wav_format)
+            }
+        };
 
         // Add optional parameters
         if let Some(lang) = &params.language {
@@ -532,10 +966,24 @@ impl WhisperfileEngine {
             form = form.text("response_format", fmt);
         }
 
+        if let Some(prompt) =
+            vocabulary::vocabulary_prompt(&params.vocabulary, params.prompt.as_deref())
+        {
+            form = form.text("prompt", &prompt);
+        }
+
+        if params.stream {
+            form = form.text("stream", "true");
+        }
+
         let (content_type, body) = form.build();
 
         let url = format!("{}/inference", self.server_url);
-        debug!("Sending transcription request to {}", url);
+        debug!(
+            "Sending transcription request to {} ({} bytes)",
+            url,
+            body.len()
+        );
 
         let start = Instant::now();
         let response = self
@@ -543,10 +991,7 @@ impl WhisperfileEngine {
             .post(&url)
             .content_type(&content_type)
             .send(&body[..])
-            .map_err(|e| {
-                error!("Request to whisperfile server failed: {}", e);
-                format!("Request to whisperfile server failed: {}", e)
-            })?;
+            .map_err(|e| self.request_error(e))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -555,16 +1000,358 @@ impl WhisperfileEngine {
             return Err(format!("Whisperfile server error {}: {}", status, body).into());
         }
 
-        let json_response = response.into_body().read_to_string()?;
-        let whisperfile_output: WhisperfileOutput = serde_json::from_str(&json_response)?;
+        let streamed = response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        let result = if streamed {
+            let reader = BufReader::new(response.into_body().into_reader());
+            self.read_event_stream(reader, &params.vocabulary, on_segment)?
+        } else {
+            let json_response = response
+                .into_body()
+                .read_to_string()
+                .map_err(|e| self.request_error(e))?;
+            let whisperfile_output: WhisperfileOutput = serde_json::from_str(&json_response)?;
+
+            let mut result: TranscriptionResult = whisperfile_output.into();
+            vocabulary::apply(&mut result, &params.vocabulary);
+            for segment in result.segments.iter().flatten() {
+                on_segment(segment);
+            }
+            result
+        };
 
         debug!(
             "Transcription completed in {:.2}s ({} chars)",
             start.elapsed().as_secs_f32(),
-            whisperfile_output.text.len()
+            result.text.len()
         );
-        trace!("Transcription result: {:?}", whisperfile_output.text);
+        trace!("Transcription result: {:?}", result.text);
 
-        Ok(whisperfile_output.into())
+        Ok(result)
+    }
+
+    /// Read a streamed response, passing each segment to `on_segment` as it
+    /// arrives. The server's final output is the result if it sends one,
+    /// otherwise the result is assembled from the segments.
+    fn read_event_stream(
+        &self,
+        reader: impl BufRead,
+        vocabulary: &[String],
+        on_segment: &mut dyn FnMut(&TranscriptionSegment),
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let mut segments = Vec::new();
+        let mut output = None;
+
+        for line in reader.lines() {
+            let line = line.map_err(|e| self.request_error(e.into()))?;
+            let data = match line.strip_prefix("data:") {
+                Some(data) => data.trim(),
+                None => continue,
+            };
+            if data.is_empty() || data == "[DONE]" {
+                continue;
+            }
+
+            match serde_json::from_str(data)? {
+                WhisperfileEvent::Segment(segment) => {
+                    let segment = TranscriptionSegment {
+                        start: segment.start,
+                        end: segment.end,
+                        text: vocabulary::correct_text(&segment.text, vocabulary),
+                    };
+                    trace!("Streamed segment: {:?}", segment.text);
+                    on_segment(&segment);
+                    segments.push(segment);
+                }
+                WhisperfileEvent::Done(done) => output = Some(done),
+            }
+        }
+
+        let mut result = match output {
+            Some(output) => output.into(),
+            None => TranscriptionResult {
+                text: segments
+                    .iter()
+                    .map(|segment| segment.text.trim())
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                segments: Some(segments),
+                language: None,
+            },
+        };
+        vocabulary::apply(&mut result, vocabulary);
+        Ok(result)
+    }
+}
+
+/// A model a [`WhisperfilePool`] can serve.
+struct PoolModel {
+    path: PathBuf,
+    params: WhisperfileModelParams,
+}
+
+/// A server of a [`WhisperfilePool`]. The engine is loaded on first use.
+struct PoolServer {
+    engine: Mutex<WhisperfileEngine>,
+    last_used: Mutex<Instant>,
+    /// When the server last passed a health check
+    last_checked: Mutex<Option<Instant>>,
+}
+
+/// Warm pool of managed whisperfile servers, one per model, for serving
+/// several models at once, e.g. `small.en` for dictation and `large-v3` for
+/// files.
+///
+/// Servers are spawned on the first request for their model and then kept
+/// running. At most `max_servers` run at a time; spawning another stops the
+/// least recently used one, after its current request. A server that hasn't
+/// passed a health check within the health check interval is checked before
+/// its next request, and started again when it doesn't respond.
+///
+/// Each server listens on a free port, so `port` in the model parameters is
+/// ignored. The pool can be shared between threads; requests for different
+/// models run in parallel.
+pub struct WhisperfilePool {
+    binary_path: PathBuf,
+    max_servers: usize,
+    health_check_interval: Duration,
+    request_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    models: HashMap<String, PoolModel>,
+    servers: Mutex<HashMap<String, Arc<PoolServer>>>,
+}
+
+impl WhisperfilePool {
+    /// Create a pool running at most `max_servers` servers of `binary_path`
+    /// at once. Models are added with [`WhisperfilePool::with_model`].
+    pub fn new(binary_path: impl Into<PathBuf>, max_servers: usize) -> Self {
+        Self {
+            binary_path: binary_path.into(),
+            max_servers: max_servers.max(1),
+            health_check_interval: Duration::from_secs(30),
+            request_timeout: None,
+            read_timeout: None,
+            models: HashMap::new(),
+            servers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Serve the model at `model_path` as `name`, the name requests ask for.
+    pub fn with_model(
+        mut self,
+        name: impl Into<String>,
+        model_path: impl Into<PathBuf>,
+        params: WhisperfileModelParams,
+    ) -> Self {
+        self.models.insert(
+            name.into(),
+            PoolModel {
+                path: model_path.into(),
+                params: WhisperfileModelParams { port: 0, ..params },
+            },
+        );
+        self
+    }
+
+    /// How long a server may go without a health check before it is
+    /// checked again ahead of a request (default: 30 seconds). Zero checks
+    /// before every request.
+    pub fn with_health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
+    /// See [`WhisperfileEngine::with_request_timeout`].
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// See [`WhisperfileEngine::with_read_timeout`].
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Names of the models the pool serves, sorted.
+    pub fn models(&self) -> Vec<String> {
+        let mut models: Vec<_> = self.models.keys().cloned().collect();
+        models.sort();
+        models
+    }
+
+    /// Models with a running server, most recently used first.
+    pub fn running(&self) -> Vec<String> {
+        let servers = self.servers.lock().unwrap();
+        let mut running: Vec<_> = servers
+            .iter()
+            .filter(|(_, server)| server.engine.try_lock().map_or(true, |e| e.loaded))
+            .map(|(name, server)| (*server.last_used.lock().unwrap(), name.clone()))
+            .collect();
+        running.sort_by_key(|(last_used, _)| std::cmp::Reverse(*last_used));
+        running.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Start the server for `model` ahead of the first request.
+    pub fn warm(&self, model: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.with_engine(model, |_| Ok(()))
+    }
+
+    /// Transcribe samples with the server for `model`, starting it if needed.
+    pub fn transcribe_samples(
+        &self,
+        model: &str,
+        samples: Vec<f32>,
+        params: Option<WhisperfileInferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        self.with_engine(model, |engine| engine.transcribe_samples(samples, params))
+    }
+
+    /// Transcribe a WAV file with the server for `model`, starting it if
+    /// needed.
+    pub fn transcribe_file(
+        &self,
+        model: &str,
+        wav_path: &Path,
+        params: Option<WhisperfileInferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        self.with_engine(model, |engine| engine.transcribe_file(wav_path, params))
+    }
+
+    /// Check every running server now and stop the ones that don't respond;
+    /// they are started again on their next request. Returns the models
+    /// whose servers were stopped.
+    pub fn health_check(&self) -> Vec<String> {
+        let servers: Vec<_> = self
+            .servers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, server)| (name.clone(), server.clone()))
+            .collect();
+
+        let mut stopped = Vec::new();
+        for (name, server) in servers {
+            let mut engine = server.engine.lock().unwrap();
+            if !engine.loaded {
+                continue;
+            }
+            match engine.health_check() {
+                Ok(()) => *server.last_checked.lock().unwrap() = Some(Instant::now()),
+                Err(e) => {
+                    warn!(
+                        "Whisperfile server for {} failed its health check: {}",
+                        name, e
+                    );
+                    engine.unload_model();
+                    stopped.push(name);
+                }
+            }
+        }
+        stopped
+    }
+
+    /// Stop all servers.
+    pub fn shutdown(&self) {
+        let servers: Vec<_> = self.servers.lock().unwrap().drain().collect();
+        for (name, server) in servers {
+            debug!("Stopping whisperfile server for {}", name);
+            server.engine.lock().unwrap().unload_model();
+        }
+    }
+
+    /// Run `f` with the loaded engine for `model`.
+    fn with_engine<T>(
+        &self,
+        model: &str,
+        f: impl FnOnce(&mut WhisperfileEngine) -> Result<T, Box<dyn std::error::Error>>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let pool_model = self
+            .models
+            .get(model)
+            .ok_or_else(|| WhisperfileError::UnknownModel(model.to_string()))?;
+        let server = self.server(model);
+
+        // Other models' servers stay usable while this one starts
+        let mut engine = server.engine.lock().unwrap();
+        let due = server
+            .last_checked
+            .lock()
+            .unwrap()
+            .is_none_or(|checked| checked.elapsed() >= self.health_check_interval);
+        if engine.loaded && due {
+            if let Err(e) = engine.health_check() {
+                warn!(
+                    "Whisperfile server for {} failed its health check, restarting it: {}",
+                    model, e
+                );
+                engine.unload_model();
+            }
+        }
+        if !engine.loaded {
+            info!("Starting whisperfile server for {}", model);
+            if let Err(e) =
+                engine.load_model_with_params(&pool_model.path, pool_model.params.clone())
+            {
+                drop(engine);
+                self.remove(model, &server);
+                return Err(e);
+            }
+        }
+        if due {
+            *server.last_checked.lock().unwrap() = Some(Instant::now());
+        }
+        f(&mut engine)
+    }
+
+    /// The server for `model`, making room for it if it isn't running.
+    fn server(&self, model: &str) -> Arc<PoolServer> {
+        let mut servers = self.servers.lock().unwrap();
+        if let Some(server) = servers.get(model) {
+            *server.last_used.lock().unwrap() = Instant::now();
+            return server.clone();
+        }
+
+        // Stop the least recently used server. A request still using it
+        // holds its own reference, so it is stopped once that finishes.
+        while servers.len() >= self.max_servers {
+            let lru = servers
+                .iter()
+                .min_by_key(|(_, server)| *server.last_used.lock().unwrap())
+                .map(|(name, _)| name.clone());
+            let Some(lru) = lru else { break };
+            info!(
+                "Stopping whisperfile server for {} to make room for {}",
+                lru, model
+            );
+            servers.remove(&lru);
+        }
+
+        let mut engine = WhisperfileEngine::new(&self.binary_path);
+        engine.request_timeout = self.request_timeout;
+        engine.read_timeout = self.read_timeout;
+        engine.agent = engine.build_agent();
+        let server = Arc::new(PoolServer {
+            engine: Mutex::new(engine),
+            last_used: Mutex::new(Instant::now()),
+            last_checked: Mutex::new(None),
+        });
+        servers.insert(model.to_string(), server.clone());
+        server
+    }
+
+    /// Forget `server` if it still is the one for `model`.
+    fn remove(&self, model: &str, server: &Arc<PoolServer>) {
+        let mut servers = self.servers.lock().unwrap();
+        if servers
+            .get(model)
+            .is_some_and(|current| Arc::ptr_eq(current, server))
+        {
+            servers.remove(model);
+        }
     }
 }
@@ -0,0 +1,425 @@
+//! Accuracy evaluation against reference transcripts.
+//!
+//! Computes word error rate (WER) and character error rate (CER) between a
+//! [`TranscriptionResult`] and a reference transcript, along with a word-level
+//! diff showing where they differ. [`evaluate_dir`] runs an engine over a
+//! directory of audio files with reference transcripts and reports the
+//! error rates over the whole set, so engines and models can be compared on
+//! the same data.
+//!
+//! Both texts are normalized before comparison: lowercased, with punctuation
+//! removed and whitespace collapsed, so only the words themselves count.
+//!
+//! # Examples
+//!
+//! ```
+//! use transcribe_rs::eval::word_error_rate;
+//!
+//! let wer = word_error_rate("the cat sat on the mat", "the cat sat on a mat");
+//! assert_eq!(wer.substitutions, 1);
+//! assert!((wer.rate() - 1.0 / 6.0).abs() < 1e-6);
+//! ```
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::{audio, TranscriptionEngine, TranscriptionResult};
+
+const SAMPLE_RATE: f32 = 16000.0;
+
+/// Edit counts between a reference and a hypothesis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorRate {
+    pub substitutions: usize,
+    pub deletions: usize,
+    pub insertions: usize,
+    /// Number of words (or characters) in the reference.
+    pub reference_len: usize,
+}
+
+impl ErrorRate {
+    /// Total number of edits.
+    pub fn errors(&self) -> usize {
+        self.substitutions + self.deletions + self.insertions
+    }
+
+    /// Edits per reference unit. Can exceed 1 when the hypothesis has many
+    /// insertions. An empty reference gives 0 for an empty hypothesis and 1
+    /// otherwise.
+    pub fn rate(&self) -> f32 {
+        if self.reference_len == 0 {
+            return if self.errors() == 0 { 0.0 } else { 1.0 };
+        }
+        self.errors() as f32 / self.reference_len as f32
+    }
+}
+
+impl std::ops::Add for ErrorRate {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            substitutions: self.substitutions + other.substitutions,
+            deletions: self.deletions + other.deletions,
+            insertions: self.insertions + other.insertions,
+            reference_len: self.reference_len + other.reference_len,
+        }
+    }
+}
+
+/// One step of a word-level diff from reference to hypothesis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordEdit {
+    Match(String),
+    Substitution {
+        reference: String,
+        hypothesis: String,
+    },
+    /// A word in the hypothesis that is not in the reference.
+    Insertion(String),
+    /// A reference word missing from the hypothesis.
+    Deletion(String),
+}
+
+/// Accuracy of one hypothesis against its reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub wer: ErrorRate,
+    pub cer: ErrorRate,
+    pub diff: Vec<WordEdit>,
+}
+
+/// Evaluate the text of `result` against `reference`.
+pub fn evaluate(result: &TranscriptionResult, reference: &str) -> Evaluation {
+    Evaluation {
+        wer: word_error_rate(reference, &result.text),
+        cer: character_error_rate(reference, &result.text),
+        diff: word_diff(reference, &result.text),
+    }
+}
+
+/// Word-level edit counts between `reference` and `hypothesis`.
+pub fn word_error_rate(reference: &str, hypothesis: &str) -> ErrorRate {
+    let reference = normalize(reference);
+    let hypothesis = normalize(hypothesis);
+    let reference: Vec<&str> = reference.split(' ').filter(|w| !w.is_empty()).collect();
+    let hypothesis: Vec<&str> = hypothesis.split(' ').filter(|w| !w.is_empty()).collect();
+    edit_counts(&reference, &hypothesis)
+}
+
+/// Character-level edit counts between `reference` and `hypothesis`. Spaces
+/// between words count as characters.
+pub fn character_error_rate(reference: &str, hypothesis: &str) -> ErrorRate {
+    let reference: Vec<char> = normalize(reference).chars().collect();
+    let hypothesis: Vec<char> = normalize(hypothesis).chars().collect();
+    edit_counts(&reference, &hypothesis)
+}
+
+/// Word-level alignment of `hypothesis` to `reference`, in reference order.
+pub fn word_diff(reference: &str, hypothesis: &str) -> Vec<WordEdit> {
+    let reference = normalize(reference);
+    let hypothesis = normalize(hypothesis);
+    let reference: Vec<&str> = reference.split(' ').filter(|w| !w.is_empty()).collect();
+    let hypothesis: Vec<&str> = hypothesis.split(' ').filter(|w| !w.is_empty()).collect();
+
+    let (n, m) = (reference.len(), hypothesis.len());
+    // Full cost matrix, (n + 1) x (m + 1), for the backtrace
+    let mut cost = vec![0usize; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in 0..=n {
+        cost[at(i, 0)] = i;
+    }
+    for j in 0..=m {
+        cost[at(0, j)] = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let substitution = usize::from(reference[i - 1] != hypothesis[j - 1]);
+            cost[at(i, j)] = (cost[at(i - 1, j - 1)] + substitution)
+                .min(cost[at(i - 1, j)] + 1)
+                .min(cost[at(i, j - 1)] + 1);
+        }
+    }
+
+    // Walk back preferring matches, then gaps over substitutions of equal
+    // cost, so the diff keeps as many matching words as possible
+    let mut diff = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0
+            && j > 0
+            && reference[i - 1] == hypothesis[j - 1]
+            && cost[at(i, j)] == cost[at(i - 1, j - 1)]
+        {
+            diff.push(WordEdit::Match(reference[i - 1].to_string()));
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && cost[at(i, j)] == cost[at(i - 1, j)] + 1 {
+            diff.push(WordEdit::Deletion(reference[i - 1].to_string()));
+            i -= 1;
+        } else if j > 0 && cost[at(i, j)] == cost[at(i, j - 1)] + 1 {
+            diff.push(WordEdit::Insertion(hypothesis[j - 1].to_string()));
+            j -= 1;
+        } else {
+            diff.push(WordEdit::Substitution {
+                reference: reference[i - 1].to_string(),
+                hypothesis: hypothesis[j - 1].to_string(),
+            });
+            i -= 1;
+            j -= 1;
+        }
+    }
+    diff.reverse();
+    diff
+}
+
+/// Lowercase, replace punctuation with spaces and collapse whitespace.
+/// Apostrophes inside words are kept, so "don't" stays one word.
+pub fn normalize(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut cleaned = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let inner_apostrophe = (c == '\'' || c == '\u{2019}')
+            && i > 0
+            && chars[i - 1].is_alphanumeric()
+            && chars.get(i + 1).is_some_and(|n| n.is_alphanumeric());
+        if c.is_alphanumeric() {
+            cleaned.extend(c.to_lowercase());
+        } else if inner_apostrophe {
+            cleaned.push('\'');
+        } else {
+            cleaned.push(' ');
+        }
+    }
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Minimum edit counts, keeping only two rows of the cost matrix so long
+/// character sequences stay cheap.
+fn edit_counts<T: PartialEq>(reference: &[T], hypothesis: &[T]) -> ErrorRate {
+    let row = |j: usize| ErrorRate {
+        insertions: j,
+        ..Default::default()
+    };
+    let mut previous: Vec<ErrorRate> = (0..=hypothesis.len()).map(row).collect();
+    let mut current = previous.clone();
+
+    for (i, r) in reference.iter().enumerate() {
+        current[0] = ErrorRate {
+            deletions: i + 1,
+            ..Default::default()
+        };
+        for (j, h) in hypothesis.iter().enumerate() {
+            let mut diagonal = previous[j];
+            if r != h {
+                diagonal.substitutions += 1;
+            }
+            let mut up = previous[j + 1];
+            up.deletions += 1;
+            let mut left = current[j];
+            left.insertions += 1;
+            current[j + 1] = [diagonal, up, left]
+                .into_iter()
+                .min_by_key(ErrorRate::errors)
+                .unwrap();
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    ErrorRate {
+        reference_len: reference.len(),
+        ..previous[hypothesis.len()]
+    }
+}
+
+/// Evaluation of one file in a test suite.
+#[derive(Debug, Clone)]
+pub struct SuiteEntry {
+    /// File name without extension.
+    pub name: String,
+    pub hypothesis: String,
+    pub reference: String,
+    pub evaluation: Evaluation,
+    /// Duration of the audio.
+    pub audio_duration: Duration,
+    /// Time taken to transcribe it.
+    pub elapsed: Duration,
+}
+
+/// Results of [`evaluate_dir`].
+#[derive(Debug, Clone, Default)]
+pub struct SuiteReport {
+    pub entries: Vec<SuiteEntry>,
+}
+
+impl SuiteReport {
+    /// Word errors summed over all files, so longer files weigh more.
+    pub fn wer(&self) -> ErrorRate {
+        self.entries
+            .iter()
+            .fold(ErrorRate::default(), |acc, e| acc + e.evaluation.wer)
+    }
+
+    /// Character errors summed over all files.
+    pub fn cer(&self) -> ErrorRate {
+        self.entries
+            .iter()
+            .fold(ErrorRate::default(), |acc, e| acc + e.evaluation.cer)
+    }
+
+    /// Total transcription time divided by total audio duration.
+    pub fn real_time_factor(&self) -> f32 {
+        let audio: f32 = self
+            .entries
+            .iter()
+            .map(|e| e.audio_duration.as_secs_f32())
+            .sum();
+        let elapsed: f32 = self.entries.iter().map(|e| e.elapsed.as_secs_f32()).sum();
+        if audio > 0.0 {
+            elapsed / audio
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Transcribe every `.wav` file in `dir` that has a reference transcript
+/// next to it with the same name and a `.txt` extension, and evaluate the
+/// results. Audio files without a reference are skipped. Entries are sorted
+/// by name.
+///
+/// # Examples
+///
+/// ```ignore
+/// use transcribe_rs::{engines::whisper::WhisperEngine, eval, TranscriptionEngine};
+///
+/// let mut engine = WhisperEngine::new();
+/// // ... load model ...
+/// let report = eval::evaluate_dir(&mut engine, "test-set".as_ref(), None)?;
+/// println!("WER {:.2}%", report.wer().rate() * 100.0);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn evaluate_dir<E>(
+    engine: &mut E,
+    dir: &Path,
+    params: Option<E::InferenceParams>,
+) -> Result<SuiteReport, Box<dyn std::error::Error>>
+where
+    E: TranscriptionEngine,
+    E::InferenceParams: Clone,
+{
+    let mut audio_paths: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+        })
+        .collect();
+    audio_paths.sort();
+
+    let mut entries = Vec::with_capacity(audio_paths.len());
+    for audio_path in audio_paths {
+        let reference_path = audio_path.with_extension("txt");
+        let reference = match fs::read_to_string(&reference_path) {
+            Ok(reference) => reference,
+            Err(_) => {
+                log::warn!("No reference transcript for {:?}, skipping", audio_path);
+                continue;
+            }
+        };
+
+        let samples = audio::read_wav_samples(&audio_path)?;
+        let audio_duration = Duration::from_secs_f32(samples.len() as f32 / SAMPLE_RATE);
+        let start = Instant::now();
+        let result = engine.transcribe_samples(samples, params.clone())?;
+        let elapsed = start.elapsed();
+
+        let evaluation = evaluate(&result, &reference);
+        log::debug!(
+            "{:?}: WER {:.3}, CER {:.3}",
+            audio_path,
+            evaluation.wer.rate(),
+            evaluation.cer.rate()
+        );
+
+        entries.push(SuiteEntry {
+            name: audio_path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            hypothesis: result.text,
+            reference: reference.trim().to_string(),
+            evaluation,
+            audio_duration,
+            elapsed,
+        });
+    }
+
+    Ok(SuiteReport { entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_case_and_punctuation() {
+        assert_eq!(
+            normalize("Hello, World!  Don't   stop..."),
+            "hello world don't stop"
+        );
+        assert_eq!(normalize("'quoted' text"), "quoted text");
+    }
+
+    #[test]
+    fn counts_word_edits() {
+        let wer = word_error_rate("one two three four", "one too three four five");
+        assert_eq!(
+            wer,
+            ErrorRate {
+                substitutions: 1,
+                deletions: 0,
+                insertions: 1,
+                reference_len: 4,
+            }
+        );
+        assert_eq!(wer.rate(), 0.5);
+
+        let wer = word_error_rate("one two three", "one three");
+        assert_eq!(wer.deletions, 1);
+        assert_eq!(wer.errors(), 1);
+    }
+
+    #[test]
+    fn identical_and_empty_texts() {
+        assert_eq!(word_error_rate("Same text.", "same text").rate(), 0.0);
+        assert_eq!(word_error_rate("", "").rate(), 0.0);
+        assert_eq!(word_error_rate("", "extra").rate(), 1.0);
+        assert_eq!(word_error_rate("missing words", "").rate(), 1.0);
+    }
+
+    #[test]
+    fn counts_character_edits() {
+        let cer = character_error_rate("kitten", "sitting");
+        assert_eq!(cer.errors(), 3);
+        assert_eq!(cer.reference_len, 6);
+    }
+
+    #[test]
+    fn diffs_words() {
+        assert_eq!(
+            word_diff("the quick brown fox", "the quack fox jumps"),
+            vec![
+                WordEdit::Match("the".to_string()),
+                WordEdit::Substitution {
+                    reference: "quick".to_string(),
+                    hypothesis: "quack".to_string(),
+                },
+                WordEdit::Deletion("brown".to_string()),
+                WordEdit::Match("fox".to_string()),
+                WordEdit::Insertion("jumps".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,468 @@
+//! Rendering of transcripts as plain text, JSON, SubRip and WebVTT subtitles,
+//! and Markdown, and shaping of segments into readable subtitle cues.
+//!
+//! # Examples
+//!
+//! ```
+//! use transcribe_rs::formats::{Transcript, TranscriptFormat};
+//! use transcribe_rs::{TranscriptionResult, TranscriptionSegment};
+//!
+//! let result = TranscriptionResult {
+//!     text: "Hello there.".to_string(),
+//!     segments: Some(vec![TranscriptionSegment {
+//!         start: 0.0,
+//!         end: 1.25,
+//!         text: "Hello there.".to_string(),
+//!     }]),
+//!     language: Some("en".to_string()),
+//! };
+//! let srt = TranscriptFormat::Srt.render(&Transcript::from_result(&result, 1.25));
+//! assert_eq!(srt, "1\n00:00:00,000 --> 00:00:01,250\nHello there.\n\n");
+//! ```
+
+use serde::Serialize;
+
+use crate::TranscriptionResult;
+
+/// Output format for a transcript.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    #[default]
+    Json,
+    Txt,
+    Srt,
+    Vtt,
+    Md,
+}
+
+impl TranscriptFormat {
+    /// Parse `json`, `txt`, `srt`, `vtt` or `md`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "json" => Some(Self::Json),
+            "txt" | "text" => Some(Self::Txt),
+            "srt" => Some(Self::Srt),
+            "vtt" => Some(Self::Vtt),
+            "md" | "markdown" => Some(Self::Md),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Txt => "text/plain; charset=utf-8",
+            Self::Srt => "application/x-subrip; charset=utf-8",
+            Self::Vtt => "text/vtt; charset=utf-8",
+            Self::Md => "text/markdown; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Txt => "txt",
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+            Self::Md => "md",
+        }
+    }
+
+    /// Render `transcript` in this format.
+    pub fn render(&self, transcript: &Transcript) -> String {
+        match self {
+            Self::Json => serde_json::to_string_pretty(transcript).unwrap_or_default(),
+            Self::Txt => format!("{}\n", transcript.text.trim()),
+            Self::Srt => render_srt(&transcript.timed_cues()),
+            Self::Vtt => render_vtt(&transcript.timed_cues()),
+            Self::Md => render_markdown(transcript),
+        }
+    }
+}
+
+/// A timed piece of text. Times are in seconds.
+#[derive(Clone, Debug, Serialize)]
+pub struct Cue {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+}
+
+/// Limits subtitle cues are shaped to with [`Transcript::shape_subtitles`].
+/// The defaults follow common broadcast guidelines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SubtitleLayout {
+    /// Characters per line.
+    pub max_line_chars: usize,
+    /// Lines per cue.
+    pub max_lines: usize,
+    /// Reading speed; cues are shown at least as long as their text takes
+    /// to read at this pace, as far as the gaps around them allow.
+    pub max_chars_per_second: f32,
+}
+
+impl Default for SubtitleLayout {
+    fn default() -> Self {
+        Self {
+            max_line_chars: 42,
+            max_lines: 2,
+            max_chars_per_second: 17.0,
+        }
+    }
+}
+
+/// A pause between words longer than this always ends a cue.
+const CUE_BREAK_GAP: f32 = 1.0;
+
+/// A transcript ready to be rendered.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Transcript {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub text: String,
+    pub segments: Vec<Cue>,
+    /// Length of the audio in seconds, used to time the whole text when
+    /// there are no segments.
+    #[serde(skip)]
+    pub duration: f32,
+}
+
+impl Transcript {
+    /// Transcript of an engine result for `duration` seconds of audio.
+    pub fn from_result(result: &TranscriptionResult, duration: f32) -> Self {
+        Self {
+            title: None,
+            text: result.text.clone(),
+            segments: result
+                .segments
+                .iter()
+                .flatten()
+                .map(|seg| Cue {
+                    start: seg.start,
+                    end: seg.end,
+                    text: seg.text.clone(),
+                })
+                .collect(),
+            duration,
+        }
+    }
+
+    /// Re-split the segments into subtitle cues within `layout`: words are
+    /// timed by their position in the segment, then filled into cues of at
+    /// most `max_lines` balanced lines, ending cues early at long pauses and
+    /// at the end of sentences once they are half full. Short segments are
+    /// merged this way, long ones split. Lines are separated by `\n`.
+    pub fn shape_subtitles(&mut self, layout: &SubtitleLayout) {
+        let words = timed_words(&self.timed_cues());
+        let max_line = layout.max_line_chars.max(1);
+        let max_lines = layout.max_lines.max(1);
+
+        let mut cues: Vec<Cue> = Vec::new();
+        let mut current: Vec<&Cue> = Vec::new();
+        let mut flush = |current: &mut Vec<&Cue>| {
+            if let (Some(first), Some(last)) = (current.first(), current.last()) {
+                let texts: Vec<&str> = current.iter().map(|word| word.text.as_str()).collect();
+                cues.push(Cue {
+                    start: first.start,
+                    end: last.end,
+                    text: balanced_lines(&texts, max_line).join("\n"),
+                });
+            }
+            current.clear();
+        };
+        for word in &words {
+            if let Some(last) = current.last() {
+                let mut texts: Vec<&str> = current.iter().map(|word| word.text.as_str()).collect();
+                let half_full = chars(&texts) * 2 >= max_line * max_lines;
+                texts.push(&word.text);
+                let fits = wrap(&texts, max_line).len() <= max_lines;
+                let sentence_end = half_full && last.text.ends_with(['.', '!', '?', '…']);
+                if !fits || sentence_end || word.start - last.end > CUE_BREAK_GAP {
+                    flush(&mut current);
+                }
+            }
+            current.push(word);
+        }
+        flush(&mut current);
+
+        if layout.max_chars_per_second > 0.0 {
+            slow_down(&mut cues, layout.max_chars_per_second);
+        }
+        self.segments = cues;
+    }
+
+    /// Segments, or the whole text as one cue spanning the audio when the
+    /// transcript has none.
+    fn timed_cues(&self) -> Vec<Cue> {
+        if !self.segments.is_empty() || self.text.trim().is_empty() {
+            return self.segments.clone();
+        }
+        vec![Cue {
+            start: 0.0,
+            end: self.duration,
+            text: self.text.trim().to_string(),
+        }]
+    }
+}
+
+/// The words of `cues`, timed by their character position in their cue.
+fn timed_words(cues: &[Cue]) -> Vec<Cue> {
+    let mut words = Vec::new();
+    for cue in cues {
+        let length = cue.text.chars().count().max(1) as f32;
+        let at = |offset: usize| {
+            let position = cue.text[..offset].chars().count() as f32 / length;
+            cue.start + (cue.end - cue.start) * position
+        };
+        for word in cue.text.split_whitespace() {
+            let offset = word.as_ptr() as usize - cue.text.as_ptr() as usize;
+            words.push(Cue {
+                start: at(offset),
+                end: at(offset + word.len()),
+                text: word.to_string(),
+            });
+        }
+    }
+    words
+}
+
+/// Characters of `words` on one line.
+fn chars(words: &[&str]) -> usize {
+    words.iter().map(|word| word.chars().count()).sum::<usize>() + words.len().saturating_sub(1)
+}
+
+/// `words` wrapped into lines of at most `width` characters; longer words
+/// get a line of their own.
+fn wrap(words: &[&str], width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line: Vec<&str> = Vec::new();
+    for &word in words {
+        line.push(word);
+        if line.len() > 1 && chars(&line) > width {
+            line.pop();
+            lines.push(line.join(" "));
+            line = vec![word];
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line.join(" "));
+    }
+    lines
+}
+
+/// `words` wrapped into as few lines as at `max_width`, but of even
+/// length, e.g. 20 and 20 characters rather than 30 and 10.
+fn balanced_lines(words: &[&str], max_width: usize) -> Vec<String> {
+    let lines = wrap(words, max_width);
+    if lines.len() < 2 {
+        return lines;
+    }
+    let even = chars(words).div_ceil(lines.len());
+    (even..max_width)
+        .map(|width| wrap(words, width))
+        .find(|balanced| balanced.len() == lines.len())
+        .unwrap_or(lines)
+}
+
+/// Show cues that are too fast to read longer, into the gaps after and
+/// then before them.
+fn slow_down(cues: &mut [Cue], chars_per_second: f32) {
+    for index in 0..cues.len() {
+        let needed = cues[index].text.chars().count() as f32 / chars_per_second;
+        let cue = &cues[index];
+        if cue.end - cue.start >= needed {
+            continue;
+        }
+        let next_start = cues.get(index + 1).map_or(f32::INFINITY, |next| next.start);
+        let previous_end = match index {
+            0 => 0.0,
+            _ => cues[index - 1].end,
+        };
+        let cue = &mut cues[index];
+        cue.end = (cue.start + needed).min(next_start.max(cue.end));
+        cue.start = (cue.end - needed).max(previous_end.min(cue.start));
+    }
+}
+
+fn render_srt(cues: &[Cue]) -> String {
+    let mut out = String::new();
+    for (index, cue) in cues.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1,
+            timestamp(cue.start, ','),
+            timestamp(cue.end, ','),
+            cue.text.trim()
+        ));
+    }
+    out
+}
+
+fn render_vtt(cues: &[Cue]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            timestamp(cue.start, '.'),
+            timestamp(cue.end, '.'),
+            cue.text.trim()
+        ));
+    }
+    out
+}
+
+/// A heading with the title, then one paragraph per segment prefixed with
+/// its start time, or the plain text when there are no segments.
+fn render_markdown(transcript: &Transcript) -> String {
+    let mut out = String::new();
+    if let Some(title) = &transcript.title {
+        out.push_str(&format!("# {}\n\n", title));
+    }
+    if transcript.segments.is_empty() {
+        out.push_str(transcript.text.trim());
+        out.push('\n');
+        return out;
+    }
+    for cue in &transcript.segments {
+        let start = timestamp(cue.start, '.');
+        // Drop the milliseconds, they are noise in a document
+        let start = start.split('.').next().unwrap_or(&start);
+        out.push_str(&format!("**[{}]** {}\n\n", start, cue.text.trim()));
+    }
+    out
+}
+
+/// `HH:MM:SS` plus milliseconds after `separator`, as used by SRT (`,`) and
+/// WebVTT (`.`).
+fn timestamp(seconds: f32, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript() -> Transcript {
+        Transcript {
+            title: Some("Standup".to_string()),
+            text: "Hello there. General Kenobi.".to_string(),
+            segments: vec![
+                Cue {
+                    start: 0.0,
+                    end: 1.25,
+                    text: " Hello there.".to_string(),
+                },
+                Cue {
+                    start: 3661.5,
+                    end: 3663.0,
+                    text: "General Kenobi.".to_string(),
+                },
+            ],
+            duration: 3663.0,
+        }
+    }
+
+    #[test]
+    fn renders_srt() {
+        assert_eq!(
+            TranscriptFormat::Srt.render(&transcript()),
+            "1\n00:00:00,000 --> 00:00:01,250\nHello there.\n\n\
+             2\n01:01:01,500 --> 01:01:03,000\nGeneral Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn renders_vtt() {
+        assert_eq!(
+            TranscriptFormat::Vtt.render(&transcript()),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.250\nHello there.\n\n\
+             01:01:01.500 --> 01:01:03.000\nGeneral Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn renders_markdown() {
+        assert_eq!(
+            TranscriptFormat::Md.render(&transcript()),
+            "# Standup\n\n**[00:00:00]** Hello there.\n\n**[01:01:01]** General Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn shapes_subtitles() {
+        let mut transcript = Transcript {
+            segments: vec![
+                Cue {
+                    start: 0.0,
+                    end: 6.0,
+                    text: "This segment is far too long for one subtitle. It goes on and on."
+                        .to_string(),
+                },
+                // Too fast to read in its own time, and merged with the next
+                Cue {
+                    start: 10.0,
+                    end: 10.5,
+                    text: "Short and".to_string(),
+                },
+                Cue {
+                    start: 10.5,
+                    end: 11.0,
+                    text: "quick.".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        transcript.shape_subtitles(&SubtitleLayout {
+            max_line_chars: 20,
+            max_lines: 2,
+            max_chars_per_second: 10.0,
+        });
+
+        let texts: Vec<&str> = transcript
+            .segments
+            .iter()
+            .map(|cue| cue.text.as_str())
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                "This segment is far\ntoo long for one",
+                "subtitle. It\ngoes on and on.",
+                "Short and quick."
+            ]
+        );
+        let last = &transcript.segments[2];
+        assert_eq!(last.start, 10.0);
+        assert_eq!(last.end, 11.6);
+        // Times follow the position of the words
+        let second = &transcript.segments[1];
+        assert!(second.start > 3.0 && second.start < 4.0);
+        // Shown long enough to read, into the pause after it
+        assert!((second.end - second.start - 2.8).abs() < 0.01);
+    }
+
+    #[test]
+    fn times_whole_text_without_segments() {
+        let transcript = Transcript {
+            text: "No segments here".to_string(),
+            duration: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            TranscriptFormat::Srt.render(&transcript),
+            "1\n00:00:00,000 --> 00:00:02,000\nNo segments here\n\n"
+        );
+        assert_eq!(
+            TranscriptFormat::Txt.render(&transcript),
+            "No segments here\n"
+        );
+    }
+}
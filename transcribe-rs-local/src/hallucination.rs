@@ -0,0 +1,212 @@
+//! Hallucination suppression for Whisper output.
+//!
+//! On silence and noise Whisper tends to produce text that was never spoken,
+//! most famously "Thank you for watching", or to get stuck repeating the same
+//! phrase. [`HallucinationFilter`] removes the common cases:
+//!
+//! - Segments the model itself considers unlikely to contain speech
+//!   (`no_speech_prob` above a threshold).
+//! - Segments consisting solely of a known hallucination phrase.
+//! - Runs of an n-gram repeated more often than a limit, collapsed to that limit.
+
+use crate::TranscriptionSegment;
+
+/// Longest n-gram (in words) checked for repetition loops.
+const MAX_NGRAM_LEN: usize = 8;
+
+/// Phrases Whisper commonly emits on silence, learned from subtitled videos.
+pub const DEFAULT_HALLUCINATION_PHRASES: &[&str] = &[
+    "thank you for watching",
+    "thanks for watching",
+    "thank you so much for watching",
+    "thank you for watching and see you next time",
+    "please subscribe",
+    "please like and subscribe",
+    "don't forget to like and subscribe",
+    "subtitles by the amara.org community",
+    "[BLANK_AUDIO]",
+];
+
+/// Configurable filters for hallucinated Whisper segments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HallucinationFilter {
+    /// Drop segments whose no-speech probability exceeds this value.
+    /// `None` keeps all segments regardless of the probability.
+    pub max_no_speech_prob: Option<f32>,
+    /// Drop segments whose entire text is one of these phrases, compared
+    /// ignoring case and punctuation.
+    pub phrases: Vec<String>,
+    /// Collapse a word sequence repeated back to back more than this many
+    /// times down to this many. `None` disables loop suppression.
+    pub max_repetitions: Option<usize>,
+}
+
+impl Default for HallucinationFilter {
+    fn default() -> Self {
+        Self {
+            max_no_speech_prob: Some(0.6),
+            phrases: DEFAULT_HALLUCINATION_PHRASES
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            max_repetitions: Some(3),
+        }
+    }
+}
+
+impl HallucinationFilter {
+    /// Filter `segments`, where `no_speech_probs[i]` is the no-speech
+    /// probability reported for `segments[i]`. Missing probabilities are
+    /// treated as speech.
+    pub fn filter_segments(
+        &self,
+        segments: Vec<TranscriptionSegment>,
+        no_speech_probs: &[f32],
+    ) -> Vec<TranscriptionSegment> {
+        let phrases: Vec<String> = self.phrases.iter().map(|p| normalize(p)).collect();
+
+        segments
+            .into_iter()
+            .enumerate()
+            .filter(
+                |(i, _)| match (self.max_no_speech_prob, no_speech_probs.get(*i)) {
+                    (Some(max), Some(&prob)) => prob <= max,
+                    _ => true,
+                },
+            )
+            .map(|(_, mut segment)| {
+                if let Some(max) = self.max_repetitions {
+                    segment.text = suppress_repetitions(&segment.text, max);
+                }
+                segment
+            })
+            .filter(|segment| {
+                let text = normalize(&segment.text);
+                !text.is_empty() && !phrases.contains(&text)
+            })
+            .collect()
+    }
+}
+
+/// Collapse any sequence of up to 8 words that repeats back to back more than
+/// `max_repetitions` times, keeping `max_repetitions` copies.
+///
+/// Words are compared ignoring case and punctuation, so "no, no, no." counts
+/// as three repetitions of "no".
+pub fn suppress_repetitions(text: &str, max_repetitions: usize) -> String {
+    let max_repetitions = max_repetitions.max(1);
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let keys: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+
+    let mut out: Vec<&str> = Vec::with_capacity(words.len());
+    let mut i = 0;
+
+    'outer: while i < words.len() {
+        for n in 1..=MAX_NGRAM_LEN {
+            if i + n * (max_repetitions + 1) > words.len() {
+                break;
+            }
+            let ngram = &keys[i..i + n];
+            let mut repeats = 1;
+            while i + (repeats + 1) * n <= words.len()
+                && keys[i + repeats * n..i + (repeats + 1) * n] == *ngram
+            {
+                repeats += 1;
+            }
+            if repeats > max_repetitions {
+                out.extend_from_slice(&words[i..i + n * max_repetitions]);
+                i += n * repeats;
+                continue 'outer;
+            }
+        }
+        out.push(words[i]);
+        i += 1;
+    }
+
+    out.join(" ")
+}
+
+/// Lowercase words without punctuation, single-space separated.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '\'')
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str) -> TranscriptionSegment {
+        TranscriptionSegment {
+            start: 0.0,
+            end: 1.0,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_drops_no_speech_and_known_phrases() {
+        let filter = HallucinationFilter::default();
+        let segments = vec![
+            segment(" Hello there."),
+            segment(" Thank you for watching!"),
+            segment(" Is anyone there?"),
+            segment(" [BLANK_AUDIO]"),
+        ];
+        let kept = filter.filter_segments(segments, &[0.1, 0.1, 0.9, 0.1]);
+        let texts: Vec<&str> = kept.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello there."]);
+    }
+
+    #[test]
+    fn test_keeps_phrases_inside_speech() {
+        let filter = HallucinationFilter::default();
+        let kept = filter.filter_segments(
+            vec![segment("I said thank you for watching the kids.")],
+            &[],
+        );
+        assert_eq!(kept.len(), 1);
+    }
+
+    #[test]
+    fn test_suppress_repetitions() {
+        assert_eq!(
+            suppress_repetitions("no no no no no way", 3),
+            "no no no way"
+        );
+        assert_eq!(
+            suppress_repetitions(
+                "I think so. I think so. I think so. I think so. I think so.",
+                2
+            ),
+            "I think so. I think so."
+        );
+        assert_eq!(
+            suppress_repetitions("that that is what it is", 3),
+            "that that is what it is"
+        );
+    }
+
+    #[test]
+    fn test_disabled_filters_keep_everything() {
+        let filter = HallucinationFilter {
+            max_no_speech_prob: None,
+            phrases: Vec::new(),
+            max_repetitions: None,
+        };
+        let kept = filter.filter_segments(
+            vec![segment("Thanks for watching."), segment("la la la la la")],
+            &[0.99, 0.99],
+        );
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[1].text, "la la la la la");
+    }
+}
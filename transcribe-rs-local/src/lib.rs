@@ -9,12 +9,28 @@
 //! - **Flexible Model Loading**: Load models with custom parameters (quantization, etc.)
 //! - **Timestamped Results**: Get detailed timing information for transcribed segments
 //! - **Audio Processing**: Built-in WAV file processing with proper format validation
+//! - **Live Capture**: Stream 16 kHz mono frames from a microphone (requires `capture` feature)
+//! - **Long-form Audio**: Split long recordings into overlapping windows and stitch the results
+//! - **Punctuation Restoration**: Optional rule-based punctuation and casing for engines that lack it
+//! - **Custom Vocabulary**: Bias engines towards domain terms and correct near-miss spellings
+//! - **Language Detection**: Identify the spoken language with Whisper models
+//! - **Hallucination Suppression**: Drop silent, boilerplate and looping Whisper segments
+//! - **Speech Translation**: Translate between languages with NVIDIA Canary (requires `canary` feature)
+//! - **Streaming Recognition**: Partial results while audio arrives with Vosk (requires `vosk` feature)
+//! - **Native OS Recognizer**: Zero-download fallback using the macOS or Windows speech recognizer (requires `native` feature)
+//! - **Forced Alignment**: Word-level timestamps from a wav2vec2 CTC model (requires `alignment` feature)
+//! - **Accuracy Evaluation**: Word and character error rates against reference transcripts
+//! - **Model Downloads**: Resumable downloads from Hugging Face with SHA-256 verification (requires `model_hub` feature)
+//! - **Output Formats**: Render results as text, JSON, SRT or WebVTT subtitles, or Markdown
 //! - **Unified API**: Common trait-based interface for all transcription engines
+//! - **Common Parameters**: Serializable inference options convertible into each engine's parameters
 //!
 //! ## Model Format Requirements
 //!
 //! - **Whisper**: Expects a single GGML format file (e.g., `whisper-medium-q4_1.bin`)
 //! - **Parakeet**: Expects a directory containing the model files (e.g., `parakeet-v0.3/`)
+//! - **SenseVoice**: Expects a sherpa-onnx export directory (`model.int8.onnx`, `tokens.txt`)
+//! - **GigaAM**: Expects a single ONNX file (e.g., `giga-am-v3.int8.onnx`) or a directory
 //!
 //! ## Quick Start
 //!
@@ -52,12 +68,24 @@
 //! - 16-bit samples
 //! - Mono (single channel)
 
+pub mod alignment;
 pub mod audio;
 pub mod engines;
+pub mod eval;
+pub mod formats;
+pub mod hallucination;
+#[cfg(feature = "limits")]
+pub mod limits;
+pub mod longform;
+#[cfg(feature = "model_hub")]
+pub mod model_hub;
+pub mod params;
+pub mod postprocess;
+pub mod vocabulary;
 
-#[cfg(feature = "openai")]
+#[cfg(any(feature = "openai", feature = "handy", feature = "failover"))]
 pub mod remote;
-#[cfg(feature = "openai")]
+#[cfg(any(feature = "openai", feature = "handy", feature = "failover"))]
 pub use remote::RemoteTranscriptionEngine;
 
 use std::path::Path;
@@ -72,6 +100,9 @@ pub struct TranscriptionResult {
     pub text: String,
     /// Individual segments with timing information
     pub segments: Option<Vec<TranscriptionSegment>>,
+    /// Language of the transcript as a code such as "en", detected or as
+    /// requested; `None` if the engine doesn't report it
+    pub language: Option<String>,
 }
 
 /// A single transcribed segment with timing information.
@@ -207,3 +238,99 @@ pub trait TranscriptionEngine {
         self.transcribe_samples(samples, params)
     }
 }
+
+/// Audio beyond this many seconds is ignored by [`LanguageDetection`].
+pub const LANGUAGE_DETECTION_WINDOW_SECS: usize = 30;
+
+/// A candidate spoken language and its probability.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageProbability {
+    /// ISO-639-1 language code (e.g. `"en"`)
+    pub language: String,
+    /// Probability between 0 and 1
+    pub probability: f32,
+}
+
+/// Spoken language identification, for engines whose models support it.
+///
+/// # Examples
+///
+/// ```ignore
+/// use std::path::PathBuf;
+/// use transcribe_rs::{engines::whisper::WhisperEngine, LanguageDetection, TranscriptionEngine};
+///
+/// let mut engine = WhisperEngine::new();
+/// engine.load_model(&PathBuf::from("models/whisper-medium-q4_1.bin"))?;
+///
+/// let samples = transcribe_rs::audio::read_wav_samples(&PathBuf::from("audio.wav"))?;
+/// for candidate in engine.detect_language(&samples)?.iter().take(3) {
+///     println!("{}: {:.2}", candidate.language, candidate.probability);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait LanguageDetection {
+    /// Detect the spoken language from the first
+    /// [`LANGUAGE_DETECTION_WINDOW_SECS`] seconds of `samples` (16kHz, mono).
+    ///
+    /// # Returns
+    ///
+    /// Returns every supported language with its probability, most likely first.
+    fn detect_language(
+        &mut self,
+        samples: &[f32],
+    ) -> Result<Vec<LanguageProbability>, Box<dyn std::error::Error>>;
+}
+
+/// Restrict detected languages to `hints`, such as the two languages of a
+/// bilingual speaker, so detection can't flip to a third one.
+///
+/// Candidates outside `hints` are dropped and the rest are rescaled to sum
+/// to 1, keeping the most likely first. Hints may carry a region
+/// (`"zh-Hans"` matches `"zh"`). All candidates are kept when `hints` is
+/// empty or matches none of them.
+///
+/// ```
+/// use transcribe_rs::{restrict_languages, LanguageProbability};
+///
+/// let candidate = |language: &str, probability| LanguageProbability {
+///     language: language.to_string(),
+///     probability,
+/// };
+/// let detected = vec![candidate("nl", 0.5), candidate("de", 0.3), candidate("en", 0.1)];
+///
+/// let restricted = restrict_languages(detected, &["en".to_string(), "de".to_string()]);
+/// assert_eq!(restricted[0].language, "de");
+/// assert_eq!(restricted.len(), 2);
+/// assert!((restricted[0].probability - 0.75).abs() < 1e-6);
+/// ```
+pub fn restrict_languages(
+    candidates: Vec<LanguageProbability>,
+    hints: &[String],
+) -> Vec<LanguageProbability> {
+    let hinted = |language: &str| {
+        hints.iter().any(|hint| {
+            let primary = hint.split('-').next().unwrap_or(hint);
+            primary.eq_ignore_ascii_case(language)
+        })
+    };
+    let mut restricted: Vec<LanguageProbability> = candidates
+        .iter()
+        .filter(|candidate| hinted(&candidate.language))
+        .cloned()
+        .collect();
+    if restricted.is_empty() {
+        return candidates;
+    }
+
+    let total: f32 = restricted
+        .iter()
+        .map(|candidate| candidate.probability)
+        .sum();
+    if total > 0.0 {
+        for candidate in &mut restricted {
+            candidate.probability /= total;
+        }
+    }
+    restricted.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+    restricted
+}
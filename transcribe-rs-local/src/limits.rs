@@ -0,0 +1,218 @@
+//! Resource limits for spawned processes, such as the whisperfile and
+//! faster-whisper servers or an ffmpeg decode, so a runaway process can't
+//! freeze the desktop.
+//!
+//! On Unix the limits are set in the child between fork and exec: the nice
+//! value with `setpriority`, CPU affinity with `sched_setaffinity` (Linux
+//! only) and memory with `RLIMIT_AS`. On Windows the process is put in a Job
+//! Object right after it starts, with a priority class, affinity mask and
+//! process memory limit.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use std::process::Command;
+//! use transcribe_rs::limits::ResourceLimits;
+//!
+//! let limits = ResourceLimits {
+//!     nice: Some(10),
+//!     cpu_affinity: vec![0, 1],
+//!     memory_bytes: Some(2 << 30),
+//! };
+//! let mut command = Command::new("ffmpeg");
+//! limits.apply(&mut command);
+//! let child = command.spawn()?;
+//! limits.attach(&child)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io;
+use std::process::{Child, Command};
+
+/// Limits for a spawned process. The default sets none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Scheduling priority from -20 (highest) to 19 (lowest), like `nice`.
+    /// Negative values need elevated privileges on Unix. On Windows, 1 to 14
+    /// is "below normal" and 15 or more "idle"; negative values raise the
+    /// priority class.
+    pub nice: Option<i32>,
+    /// CPU cores the process may run on, numbered from 0. Empty allows all.
+    /// Ignored on macOS, which has no affinity API.
+    pub cpu_affinity: Vec<usize>,
+    /// Memory limit in bytes. On Unix this caps the address space, which
+    /// also counts reserved but unused memory, so allow generous headroom;
+    /// GPU drivers in particular reserve large ranges. On Windows it caps
+    /// committed memory.
+    pub memory_bytes: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Whether no limit is set.
+    pub fn is_empty(&self) -> bool {
+        self.nice.is_none() && self.cpu_affinity.is_empty() && self.memory_bytes.is_none()
+    }
+
+    /// Have `command` apply the limits to the process it spawns. On Windows
+    /// this does nothing; call [`ResourceLimits::attach`] once it runs.
+    pub fn apply(&self, command: &mut Command) {
+        if self.is_empty() {
+            return;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            let limits = self.clone();
+            // SAFETY: the hook only makes async-signal-safe system calls
+            unsafe {
+                command.pre_exec(move || limits.apply_to_current_process());
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    /// Apply the limits to a process that was just spawned. Needed on
+    /// Windows, where it puts the process in a Job Object; does nothing
+    /// elsewhere, as [`ResourceLimits::apply`] already set them.
+    pub fn attach(&self, child: &Child) -> io::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        #[cfg(windows)]
+        {
+            self.attach_job(child.id())
+        }
+        #[cfg(not(windows))]
+        {
+            let _ = child;
+            Ok(())
+        }
+    }
+
+    /// Apply the limits to the calling process, e.g. from a `pre_exec` hook
+    /// of a command type [`ResourceLimits::apply`] doesn't take.
+    #[cfg(unix)]
+    pub fn apply_to_current_process(&self) -> io::Result<()> {
+        if let Some(nice) = self.nice {
+            // SAFETY: plain system call on this process
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        if !self.cpu_affinity.is_empty() {
+            // SAFETY: `set` is a valid, zeroed cpu_set_t for the calls below
+            unsafe {
+                let mut set: libc::cpu_set_t = std::mem::zeroed();
+                libc::CPU_ZERO(&mut set);
+                for &cpu in &self.cpu_affinity {
+                    libc::CPU_SET(cpu, &mut set);
+                }
+                if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+        }
+
+        if let Some(bytes) = self.memory_bytes {
+            let limit = libc::rlimit {
+                rlim_cur: bytes as libc::rlim_t,
+                rlim_max: bytes as libc::rlim_t,
+            };
+            // SAFETY: `limit` outlives the call
+            if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Put the process `pid` in a new Job Object carrying the limits. The
+    /// job lives on while the process is in it.
+    #[cfg(windows)]
+    fn attach_job(&self, pid: u32) -> io::Result<()> {
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+            SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_AFFINITY, JOB_OBJECT_LIMIT_PRIORITY_CLASS,
+            JOB_OBJECT_LIMIT_PROCESS_MEMORY,
+        };
+        use windows::Win32::System::Threading::{
+            OpenProcess, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+            HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, PROCESS_SET_QUOTA,
+            PROCESS_TERMINATE,
+        };
+
+        let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        if let Some(nice) = self.nice {
+            let class = match nice {
+                15.. => IDLE_PRIORITY_CLASS,
+                1..=14 => BELOW_NORMAL_PRIORITY_CLASS,
+                0 => NORMAL_PRIORITY_CLASS,
+                -10..=-1 => ABOVE_NORMAL_PRIORITY_CLASS,
+                _ => HIGH_PRIORITY_CLASS,
+            };
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PRIORITY_CLASS;
+            info.BasicLimitInformation.PriorityClass = class.0;
+        }
+        if !self.cpu_affinity.is_empty() {
+            let mask = self
+                .cpu_affinity
+                .iter()
+                .filter(|&&cpu| cpu < usize::BITS as usize)
+                .fold(0usize, |mask, &cpu| mask | (1 << cpu));
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_AFFINITY;
+            info.BasicLimitInformation.Affinity = mask;
+        }
+        if let Some(bytes) = self.memory_bytes {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+            info.ProcessMemoryLimit = bytes as usize;
+        }
+
+        // SAFETY: the handles are valid until closed below, and `info`
+        // outlives the call that reads it
+        unsafe {
+            let job = CreateJobObjectW(None, PCWSTR::null()).map_err(io::Error::other)?;
+            let result = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+            .and_then(|()| {
+                let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, false, pid)?;
+                let result = AssignProcessToJobObject(job, process);
+                let _ = CloseHandle(process);
+                result
+            });
+            let _ = CloseHandle(job);
+            result.map_err(io::Error::other)
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_nice_and_memory_limit() {
+        let limits = ResourceLimits {
+            nice: Some(5),
+            memory_bytes: Some(1 << 30),
+            ..Default::default()
+        };
+        let mut command = Command::new("sh");
+        command.args(["-c", "nice; ulimit -v"]);
+        limits.apply(&mut command);
+        let output = command.output().unwrap();
+        let output = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines, vec!["5", &(1u64 << 20).to_string()]);
+    }
+}
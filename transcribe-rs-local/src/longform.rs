@@ -0,0 +1,673 @@
+//! Long-form transcription with chunking and overlap stitching.
+//!
+//! Engines degrade (Whisper) or fail outright (Moonshine) on very long inputs.
+//! [`LongFormTranscriber`] splits audio into windows at the quietest point
+//! near each window boundary, transcribes every window with any
+//! [`TranscriptionEngine`], and stitches the results back together:
+//!
+//! - Adjacent windows overlap slightly so words at a split are never cut off.
+//! - Segment timestamps are shifted back onto the original timeline, and each
+//!   segment is kept only by the window that owns its midpoint.
+//! - Words repeated across the overlap are removed from the later window.
+//!
+//! [`LongFormTranscriber::stream`] does the same for audio that arrives in
+//! pieces, holding only about one window in memory, and
+//! [`LongFormTranscriber::transcribe_parallel`] transcribes several windows
+//! at once, e.g. with one decoder state per group of CPU cores.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use transcribe_rs::{engines::moonshine::MoonshineEngine, longform::LongFormTranscriber};
+//!
+//! let mut engine = MoonshineEngine::new();
+//! // ... load model ...
+//! let samples = transcribe_rs::audio::read_wav_samples(&path)?;
+//! let result = LongFormTranscriber::default().transcribe(&mut engine, &samples, None)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::{TranscriptionEngine, TranscriptionResult, TranscriptionSegment};
+
+/// Sample rate expected by all engines.
+const SAMPLE_RATE: usize = 16000;
+
+/// Length of the frames used to measure loudness when looking for silence.
+const ENERGY_FRAME_SAMPLES: usize = SAMPLE_RATE * 30 / 1000;
+
+/// Longest run of words considered when removing text repeated across an overlap.
+const MAX_OVERLAP_WORDS: usize = 24;
+
+/// Configuration for [`LongFormTranscriber`].
+#[derive(Debug, Clone)]
+pub struct LongFormConfig {
+    /// Upper bound on the length of each window, in seconds.
+    pub max_window_secs: f32,
+    /// Windows are never split before this many seconds, so the quietest
+    /// point is searched for between `min_window_secs` and `max_window_secs`.
+    pub min_window_secs: f32,
+    /// Audio shared by adjacent windows on either side of a split, in seconds.
+    pub overlap_secs: f32,
+}
+
+impl Default for LongFormConfig {
+    fn default() -> Self {
+        Self {
+            max_window_secs: 30.0,
+            min_window_secs: 20.0,
+            overlap_secs: 1.0,
+        }
+    }
+}
+
+/// A window of audio to transcribe, in samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Window {
+    start: usize,
+    end: usize,
+    /// Where this window hands over to the next one. Segments whose midpoint
+    /// falls between the previous window's split and this one belong here.
+    split: usize,
+}
+
+/// Transcribes arbitrarily long audio by splitting it into overlapping windows.
+#[derive(Debug, Clone, Default)]
+pub struct LongFormTranscriber {
+    config: LongFormConfig,
+}
+
+impl LongFormTranscriber {
+    pub fn new(config: LongFormConfig) -> Self {
+        Self { config }
+    }
+
+    /// Transcribe 16 kHz mono `samples` with `engine`, window by window.
+    ///
+    /// `params` is cloned for every window. The result always carries
+    /// segments; engines that return no segments contribute one segment per
+    /// window.
+    pub fn transcribe<E>(
+        &self,
+        engine: &mut E,
+        samples: &[f32],
+        params: Option<E::InferenceParams>,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>>
+    where
+        E: TranscriptionEngine,
+        E::InferenceParams: Clone,
+    {
+        let mut stitcher = Stitcher::default();
+        for window in plan_windows(samples, &self.config) {
+            let result = engine
+                .transcribe_samples(samples[window.start..window.end].to_vec(), params.clone())?;
+            stitcher.add(window, result, samples.len());
+        }
+        Ok(stitcher.finish())
+    }
+
+    /// Transcribe 16 kHz mono `samples` on one thread per worker. Each
+    /// worker transcribes the next window not yet taken with `transcribe`
+    /// until none are left, so a slow window doesn't hold up the others.
+    ///
+    /// Workers are typically independent decoder states of one model. The
+    /// result is the same as transcribing the windows in order; after an
+    /// error no further windows are started, and the first error is
+    /// returned.
+    pub fn transcribe_parallel<W, F>(
+        &self,
+        samples: &[f32],
+        workers: Vec<W>,
+        transcribe: F,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>>
+    where
+        W: Send,
+        F: Fn(&mut W, Vec<f32>) -> Result<TranscriptionResult, Box<dyn std::error::Error>> + Sync,
+    {
+        if workers.is_empty() {
+            return Err("No workers to transcribe with".into());
+        }
+        let windows = plan_windows(samples, &self.config);
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+
+        let outcomes: Vec<Result<Vec<(usize, TranscriptionResult)>, String>> =
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = workers
+                    .into_iter()
+                    .map(|mut worker| {
+                        let (windows, next, failed, transcribe) =
+                            (&windows, &next, &failed, &transcribe);
+                        scope.spawn(move || {
+                            let mut results = Vec::new();
+                            while !failed.load(Ordering::Relaxed) {
+                                let index = next.fetch_add(1, Ordering::Relaxed);
+                                let Some(window) = windows.get(index) else {
+                                    break;
+                                };
+                                let audio = samples[window.start..window.end].to_vec();
+                                match transcribe(&mut worker, audio) {
+                                    Ok(result) => results.push((index, result)),
+                                    Err(e) => {
+                                        failed.store(true, Ordering::Relaxed);
+                                        // Box<dyn Error> can't leave the thread
+                                        return Err(e.to_string());
+                                    }
+                                }
+                            }
+                            Ok(results)
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    })
+                    .collect()
+            });
+
+        let mut results = Vec::with_capacity(windows.len());
+        for outcome in outcomes {
+            results.extend(outcome?);
+        }
+        results.sort_by_key(|(index, _)| *index);
+
+        let mut stitcher = Stitcher::default();
+        for (index, result) in results {
+            stitcher.add(windows[index], result, samples.len());
+        }
+        Ok(stitcher.finish())
+    }
+
+    /// Start transcribing audio that arrives piece by piece, e.g. while a
+    /// large file is being decoded. See [`LongFormStream`].
+    pub fn stream(&self) -> LongFormStream {
+        LongFormStream {
+            config: self.config.clone(),
+            buffer: Vec::new(),
+            buffer_start: 0,
+            split_prev: 0,
+            stitcher: Stitcher::default(),
+        }
+    }
+}
+
+/// Incremental [`LongFormTranscriber`] for audio too long to hold in memory.
+///
+/// Samples are pushed as they are decoded and every window is transcribed
+/// as soon as it is complete, so only about one window of audio is buffered
+/// at a time. Windows and results are the same as transcribing all the
+/// samples at once.
+///
+/// Windows are transcribed by a callback rather than an engine, so callers
+/// can wrap their engine with locking, denoising and the like.
+pub struct LongFormStream {
+    config: LongFormConfig,
+    /// Samples from `buffer_start` on; earlier ones are no longer needed.
+    buffer: Vec<f32>,
+    buffer_start: usize,
+    /// Where the last window handed over to the next one.
+    split_prev: usize,
+    stitcher: Stitcher,
+}
+
+impl LongFormStream {
+    /// Total number of samples pushed so far.
+    pub fn len(&self) -> usize {
+        self.buffer_start + self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add 16 kHz mono `samples`, transcribing any windows they complete
+    /// with `transcribe`.
+    pub fn push<F>(
+        &mut self,
+        samples: &[f32],
+        mut transcribe: F,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnMut(Vec<f32>) -> Result<TranscriptionResult, Box<dyn std::error::Error>>,
+    {
+        self.buffer.extend_from_slice(samples);
+        let (max_len, _, half_overlap) = window_lengths(&self.config);
+        // A window can only be cut once the audio is known to run past it
+        // and its overlap
+        while self.len() > self.split_prev + max_len + half_overlap {
+            self.next_window(&mut transcribe)?;
+        }
+        Ok(())
+    }
+
+    /// Transcribe the remaining audio and return the stitched result.
+    pub fn finish<F>(
+        mut self,
+        mut transcribe: F,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>>
+    where
+        F: FnMut(Vec<f32>) -> Result<TranscriptionResult, Box<dyn std::error::Error>>,
+    {
+        while self.split_prev < self.len() {
+            self.next_window(&mut transcribe)?;
+        }
+        Ok(self.stitcher.finish())
+    }
+
+    /// Cut and transcribe the window after `split_prev`, then drop the
+    /// samples no later window needs.
+    fn next_window<F>(&mut self, transcribe: &mut F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnMut(Vec<f32>) -> Result<TranscriptionResult, Box<dyn std::error::Error>>,
+    {
+        let window = next_window(
+            &self.buffer,
+            self.buffer_start,
+            self.split_prev,
+            &self.config,
+        );
+        let audio =
+            self.buffer[window.start - self.buffer_start..window.end - self.buffer_start].to_vec();
+        let result = transcribe(audio)?;
+        self.stitcher.add(window, result, window.end);
+
+        self.split_prev = window.split;
+        let (_, _, half_overlap) = window_lengths(&self.config);
+        let keep_from = window.split.saturating_sub(half_overlap);
+        self.buffer.drain(..keep_from - self.buffer_start);
+        self.buffer_start = keep_from;
+        Ok(())
+    }
+}
+
+/// Assembles window results into one transcript on the original timeline.
+#[derive(Default)]
+struct Stitcher {
+    segments: Vec<TranscriptionSegment>,
+    language: Option<String>,
+    /// Where the previous window handed over to the next one.
+    owned_from: usize,
+}
+
+impl Stitcher {
+    /// Add the result for `window`, clamping timestamps to `audio_end`.
+    fn add(&mut self, window: Window, result: TranscriptionResult, audio_end: usize) {
+        let offset = window.start as f32 / SAMPLE_RATE as f32;
+        let owned = (
+            self.owned_from as f32 / SAMPLE_RATE as f32,
+            window.split as f32 / SAMPLE_RATE as f32,
+        );
+        self.owned_from = window.split;
+        self.language = self.language.take().or(result.language);
+
+        let window_segments = match result.segments {
+            Some(segs) if !segs.is_empty() => segs,
+            _ => vec![TranscriptionSegment {
+                start: 0.0,
+                end: (window.end - window.start) as f32 / SAMPLE_RATE as f32,
+                text: result.text,
+            }],
+        };
+
+        let mut first = true;
+        for seg in window_segments {
+            let start = seg.start + offset;
+            let end = seg.end + offset;
+            let mid = (start + end) / 2.0;
+            // Segments without timestamps span the whole window; keep them
+            // and rely on text de-duplication instead.
+            let spans_window = seg.start <= 0.0 && end >= window.end as f32 / SAMPLE_RATE as f32;
+            if !spans_window && (mid < owned.0 || mid >= owned.1) {
+                continue;
+            }
+
+            let mut text = seg.text.trim().to_string();
+            if first {
+                if let Some(prev) = self.segments.last() {
+                    text = strip_repeated_prefix(&prev.text, &text);
+                }
+                first = false;
+            }
+            if text.is_empty() {
+                continue;
+            }
+
+            // Keep timestamps monotonic across window boundaries
+            let prev_end = self.segments.last().map_or(0.0, |s| s.end);
+            self.segments.push(TranscriptionSegment {
+                start: start.max(prev_end),
+                end: end.min(audio_end as f32 / SAMPLE_RATE as f32),
+                text,
+            });
+        }
+    }
+
+    fn finish(self) -> TranscriptionResult {
+        let text = self
+            .segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        TranscriptionResult {
+            text,
+            segments: Some(self.segments),
+            language: self.language,
+        }
+    }
+}
+
+/// Maximum and minimum window length and half the overlap, in samples.
+fn window_lengths(config: &LongFormConfig) -> (usize, usize, usize) {
+    let max_len =
+        ((config.max_window_secs * SAMPLE_RATE as f32) as usize).max(ENERGY_FRAME_SAMPLES);
+    let min_len = ((config.min_window_secs * SAMPLE_RATE as f32) as usize).min(max_len);
+    let half_overlap = (config.overlap_secs * SAMPLE_RATE as f32 / 2.0) as usize;
+    (max_len, min_len, half_overlap)
+}
+
+/// Split `samples` into overlapping windows at the quietest points.
+fn plan_windows(samples: &[f32], config: &LongFormConfig) -> Vec<Window> {
+    let mut windows = Vec::new();
+    let mut split_prev: usize = 0;
+    while split_prev < samples.len() || windows.is_empty() {
+        let window = next_window(samples, 0, split_prev, config);
+        windows.push(window);
+        split_prev = window.split;
+    }
+    windows
+}
+
+/// The window after `split_prev`, given the audio from `offset` on. The
+/// window ends the audio when `samples` ends within `max_window_secs`.
+fn next_window(
+    samples: &[f32],
+    offset: usize,
+    split_prev: usize,
+    config: &LongFormConfig,
+) -> Window {
+    let len = offset + samples.len();
+    let (max_len, min_len, half_overlap) = window_lengths(config);
+    let window_start = split_prev.saturating_sub(half_overlap);
+    if len - split_prev <= max_len {
+        return Window {
+            start: window_start,
+            end: len,
+            split: len,
+        };
+    }
+
+    let split = offset
+        + quietest_point(
+            samples,
+            split_prev + min_len - offset,
+            split_prev + max_len - offset,
+        );
+    Window {
+        start: window_start,
+        end: (split + half_overlap).min(len),
+        split,
+    }
+}
+
+/// Sample index of the quietest energy frame between `from` and `to`.
+fn quietest_point(samples: &[f32], from: usize, to: usize) -> usize {
+    let to = to.min(samples.len());
+    let mut best = to;
+    let mut best_energy = f32::INFINITY;
+
+    let mut pos = from;
+    while pos + ENERGY_FRAME_SAMPLES <= to {
+        let frame = &samples[pos..pos + ENERGY_FRAME_SAMPLES];
+        let energy = frame.iter().map(|s| s * s).sum::<f32>();
+        // Prefer the later frame on ties so windows stay as long as possible
+        if energy <= best_energy {
+            best_energy = energy;
+            best = pos + ENERGY_FRAME_SAMPLES / 2;
+        }
+        pos += ENERGY_FRAME_SAMPLES;
+    }
+
+    best
+}
+
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Remove the leading words of `next` that repeat the trailing words of
+/// `prev`, ignoring case and punctuation. Single-word matches are ignored
+/// since they are as likely to be a natural repetition as an overlap.
+fn strip_repeated_prefix(prev: &str, next: &str) -> String {
+    let prev_words: Vec<String> = prev.split_whitespace().map(normalize_word).collect();
+    let next_raw: Vec<&str> = next.split_whitespace().collect();
+    let next_words: Vec<String> = next_raw.iter().map(|w| normalize_word(w)).collect();
+
+    let max = MAX_OVERLAP_WORDS
+        .min(prev_words.len())
+        .min(next_words.len());
+    let repeated = (2..=max)
+        .rev()
+        .find(|&n| prev_words[prev_words.len() - n..] == next_words[..n])
+        .unwrap_or(0);
+
+    next_raw[repeated..].join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn tone_with_gaps(secs: usize, gap_every: usize) -> Vec<f32> {
+        (0..secs * SAMPLE_RATE)
+            .map(|i| {
+                let t = i / SAMPLE_RATE;
+                if t % gap_every == gap_every - 1 {
+                    0.0
+                } else {
+                    (i as f32 * 0.05).sin() * 0.5
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_short_audio_is_single_window() {
+        let samples = vec![0.1; SAMPLE_RATE * 10];
+        let windows = plan_windows(&samples, &LongFormConfig::default());
+        assert_eq!(
+            windows,
+            vec![Window {
+                start: 0,
+                end: samples.len(),
+                split: samples.len()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_windows_split_in_silence_and_overlap() {
+        // One second of silence at the end of every 25 second block
+        let samples = tone_with_gaps(100, 25);
+        let config = LongFormConfig::default();
+        let windows = plan_windows(&samples, &config);
+
+        assert!(windows.len() >= 4);
+        assert_eq!(windows.first().unwrap().start, 0);
+        assert_eq!(windows.last().unwrap().end, samples.len());
+        for pair in windows.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            // The split lands inside a silent second
+            assert_eq!(a.split / SAMPLE_RATE % 25, 24, "split at {}", a.split);
+            // Consecutive windows overlap around the split
+            assert!(b.start < a.split && a.split < a.end);
+            assert!(a.end - a.start <= (config.max_window_secs as usize + 1) * SAMPLE_RATE);
+        }
+    }
+
+    #[test]
+    fn test_strip_repeated_prefix() {
+        assert_eq!(
+            strip_repeated_prefix("ask not what your country", "Your country can do for you"),
+            "can do for you"
+        );
+        // A single shared word is not treated as overlap
+        assert_eq!(
+            strip_repeated_prefix("the end", "end of story"),
+            "end of story"
+        );
+        assert_eq!(strip_repeated_prefix("", "hello there"), "hello there");
+    }
+
+    /// Engine that "transcribes" each run of equal sample values as one
+    /// segment named after the value.
+    struct CountingEngine {
+        calls: usize,
+    }
+
+    impl TranscriptionEngine for CountingEngine {
+        type InferenceParams = ();
+        type ModelParams = ();
+
+        fn load_model_with_params(
+            &mut self,
+            _: &Path,
+            _: (),
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            Ok(())
+        }
+
+        fn unload_model(&mut self) {}
+
+        fn transcribe_samples(
+            &mut self,
+            samples: Vec<f32>,
+            _: Option<()>,
+        ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+            self.calls += 1;
+            let mut segments: Vec<TranscriptionSegment> = Vec::new();
+            let mut run_start = 0;
+            for i in 1..=samples.len() {
+                if i == samples.len() || samples[i] != samples[run_start] {
+                    segments.push(TranscriptionSegment {
+                        start: run_start as f32 / SAMPLE_RATE as f32,
+                        end: i as f32 / SAMPLE_RATE as f32,
+                        text: format!("w{}", samples[run_start] as usize),
+                    });
+                    run_start = i;
+                }
+            }
+            let text = segments
+                .iter()
+                .map(|s| s.text.clone())
+                .collect::<Vec<_>>()
+                .join(" ");
+            Ok(TranscriptionResult {
+                text,
+                segments: Some(segments),
+                language: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_segments_are_stitched_without_duplicates() {
+        // Each second's samples hold its index; the engine reads it back
+        let samples: Vec<f32> = (0..75 * SAMPLE_RATE)
+            .map(|i| (i / SAMPLE_RATE) as f32)
+            .collect();
+        let mut engine = CountingEngine { calls: 0 };
+        let config = LongFormConfig {
+            max_window_secs: 20.0,
+            min_window_secs: 15.0,
+            overlap_secs: 2.0,
+        };
+
+        let result = LongFormTranscriber::new(config)
+            .transcribe(&mut engine, &samples, None)
+            .unwrap();
+        let segments = result.segments.unwrap();
+
+        assert!(engine.calls > 1);
+        // Every second appears exactly once, in order, at its own timestamp
+        assert_eq!(segments.len(), 75);
+        for (i, seg) in segments.iter().enumerate() {
+            assert_eq!(seg.text, format!("w{}", i));
+            assert!((seg.start - i as f32).abs() < 1e-3, "{:?}", seg);
+            assert!((seg.end - (i + 1) as f32).abs() < 1e-3, "{:?}", seg);
+        }
+        assert!(result.text.starts_with("w0 w1 w2"));
+    }
+
+    #[test]
+    fn test_parallel_matches_batch() {
+        let samples = tone_with_gaps(100, 25);
+        let transcriber = LongFormTranscriber::default();
+        let batch = transcriber
+            .transcribe(&mut CountingEngine { calls: 0 }, &samples, None)
+            .unwrap();
+
+        let workers: Vec<_> = (0..3).map(|_| CountingEngine { calls: 0 }).collect();
+        let parallel = transcriber
+            .transcribe_parallel(&samples, workers, |engine, audio| {
+                engine.transcribe_samples(audio, None)
+            })
+            .unwrap();
+        assert_eq!(parallel.text, batch.text);
+        let times = |result: TranscriptionResult| -> Vec<(f32, f32)> {
+            result
+                .segments
+                .unwrap()
+                .iter()
+                .map(|seg| (seg.start, seg.end))
+                .collect()
+        };
+        assert_eq!(times(parallel), times(batch));
+
+        let error = transcriber
+            .transcribe_parallel(&samples, vec![(), ()], |_, _| Err("decoder failed".into()))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "decoder failed");
+    }
+
+    #[test]
+    fn test_stream_matches_batch() {
+        let samples = tone_with_gaps(100, 25);
+        let transcriber = LongFormTranscriber::default();
+        let batch = transcriber
+            .transcribe(&mut CountingEngine { calls: 0 }, &samples, None)
+            .unwrap();
+
+        let mut engine = CountingEngine { calls: 0 };
+        let mut stream = transcriber.stream();
+        let mut max_buffered = 0;
+        for chunk in samples.chunks(12345) {
+            stream
+                .push(chunk, |audio| engine.transcribe_samples(audio, None))
+                .unwrap();
+            max_buffered = max_buffered.max(stream.buffer.len());
+        }
+        assert_eq!(stream.len(), samples.len());
+        let streamed = stream
+            .finish(|audio| engine.transcribe_samples(audio, None))
+            .unwrap();
+
+        assert_eq!(streamed.text, batch.text);
+        assert_eq!(
+            streamed.segments.unwrap().len(),
+            batch.segments.unwrap().len()
+        );
+        // Only about a window's worth of audio is held at a time
+        assert!(max_buffered < 40 * SAMPLE_RATE, "buffered {}", max_buffered);
+    }
+}
@@ -0,0 +1,379 @@
+//! Model downloads (requires `model_hub` feature).
+//!
+//! [`download`] fetches a model file from Hugging Face or any HTTP URL into
+//! a `.partial` file next to the destination, resumes an interrupted
+//! download with a `Range` request, checks that the disk has room for the
+//! rest, verifies the SHA-256 checksum and only then moves the file into
+//! place:
+//!
+//! ```ignore
+//! use std::path::Path;
+//! use transcribe_rs::model_hub::{self, DownloadOptions};
+//!
+//! let url = model_hub::hf_url("ggerganov/whisper.cpp", "ggml-base.en.bin");
+//! let options = DownloadOptions {
+//!     sha256: Some("a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002".into()),
+//!     ..Default::default()
+//! };
+//! model_hub::download(&url, Path::new("models/ggml-base.en.bin"), &options, |p| {
+//!     println!("{} / {:?} bytes", p.downloaded, p.total);
+//! })?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use ureq::Agent;
+
+/// Base URL of the Hugging Face hub.
+pub const HUGGING_FACE_URL: &str = "https://huggingface.co";
+
+/// Minimum time between two progress callbacks.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(thiserror::Error, Debug)]
+pub enum ModelHubError {
+    #[error("Download failed: {0}")]
+    Http(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// The downloaded file does not match the expected checksum. The file
+    /// is deleted.
+    #[error("Checksum mismatch: expected SHA-256 {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("Download incomplete: expected {expected} bytes, got {actual}")]
+    Incomplete { expected: u64, actual: u64 },
+    #[error("Not enough disk space: {required} bytes needed, {available} available")]
+    InsufficientSpace { required: u64, available: u64 },
+    /// The download was cancelled. The partial file is kept for resuming.
+    #[error("Download cancelled")]
+    Cancelled,
+}
+
+/// Progress of a download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Bytes on disk, including those of a resumed earlier attempt
+    pub downloaded: u64,
+    /// Size of the whole file, if the server or the options tell
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    /// Percentage done (0-100), if the size is known.
+    pub fn percentage(&self) -> Option<f64> {
+        self.total
+            .filter(|&total| total > 0)
+            .map(|total| self.downloaded as f64 / total as f64 * 100.0)
+    }
+}
+
+/// Options for [`download`].
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    /// Expected SHA-256 of the file (hex). Not verified if `None`.
+    pub sha256: Option<String>,
+    /// Expected size in bytes, used for the progress and disk space check
+    /// when the server doesn't send a `Content-Length`.
+    pub size: Option<u64>,
+    /// Set to `true` to stop the download with [`ModelHubError::Cancelled`].
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+/// URL of `file` on the main branch of the Hugging Face repository `repo`
+/// (e.g. `"nvidia/parakeet-tdt-0.6b-v3"`).
+pub fn hf_url(repo: &str, file: &str) -> String {
+    hf_url_at(repo, "main", file)
+}
+
+/// URL of `file` at `revision` (branch, tag or commit) of the Hugging Face
+/// repository `repo`.
+pub fn hf_url_at(repo: &str, revision: &str, file: &str) -> String {
+    format!(
+        "{}/{}/resolve/{}/{}",
+        HUGGING_FACE_URL,
+        repo.trim_matches('/'),
+        revision,
+        file.trim_start_matches('/')
+    )
+}
+
+/// Path of the partial download for `dest`.
+pub fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".partial");
+    dest.with_file_name(name)
+}
+
+/// Download `url` to `dest`, resuming a partial download left by an earlier
+/// call. `on_progress` is called at most every 100 ms, and once at the end.
+///
+/// Returns immediately if `dest` exists and matches the checksum.
+pub fn download(
+    url: &str,
+    dest: &Path,
+    options: &DownloadOptions,
+    mut on_progress: impl FnMut(DownloadProgress),
+) -> Result<(), ModelHubError> {
+    if dest.exists() {
+        if let Some(expected) = &options.sha256 {
+            verify_sha256(dest, expected)?;
+        }
+        return Ok(());
+    }
+
+    let dir = match dest.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    let partial = partial_path(dest);
+    let mut resume_from = fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+
+    let agent: Agent = Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let mut request = agent.get(url);
+    if resume_from > 0 {
+        log::info!("Resuming download of {} from byte {}", url, resume_from);
+        request = request.header("Range", format!("bytes={}-", resume_from));
+    }
+    let mut response = request
+        .call()
+        .map_err(|e| ModelHubError::Http(e.to_string()))?;
+
+    // The server ignored the range (200), or the partial file is not a
+    // prefix of the file (416): start over
+    let status = response.status().as_u16();
+    if resume_from > 0 && (status == 200 || status == 416) {
+        log::warn!("Server can't resume {}, restarting download", url);
+        resume_from = 0;
+        if status == 416 {
+            response = agent
+                .get(url)
+                .call()
+                .map_err(|e| ModelHubError::Http(e.to_string()))?;
+        }
+    }
+    if !response.status().is_success() {
+        return Err(ModelHubError::Http(format!("HTTP {}", response.status())));
+    }
+
+    let total = response
+        .body()
+        .content_length()
+        .map(|len| resume_from + len)
+        .or(options.size);
+    if let Some(total) = total {
+        ensure_free_space(dir, total.saturating_sub(resume_from))?;
+    }
+
+    let mut file = if resume_from > 0 {
+        OpenOptions::new().append(true).open(&partial)?
+    } else {
+        File::create(&partial)?
+    };
+
+    let mut progress = DownloadProgress {
+        downloaded: resume_from,
+        total,
+    };
+    on_progress(progress);
+    let mut last_report = Instant::now();
+
+    let mut reader = response.into_body().into_reader();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        if options
+            .cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+        {
+            log::info!("Download of {} cancelled", url);
+            return Err(ModelHubError::Cancelled);
+        }
+
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(ModelHubError::Http(e.to_string())),
+        };
+        file.write_all(&buf[..n])?;
+        progress.downloaded += n as u64;
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            on_progress(progress);
+            last_report = Instant::now();
+        }
+    }
+    file.flush()?;
+    drop(file);
+    on_progress(progress);
+
+    if let Some(expected) = total {
+        if progress.downloaded != expected {
+            // Keep the partial file: the next call resumes from it
+            return Err(ModelHubError::Incomplete {
+                expected,
+                actual: progress.downloaded,
+            });
+        }
+    }
+
+    if let Some(expected) = &options.sha256 {
+        if let Err(e) = verify_sha256(&partial, expected) {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+    }
+
+    fs::rename(&partial, dest)?;
+    Ok(())
+}
+
+/// SHA-256 of a file as lowercase hex.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Check that the file at `path` has the SHA-256 `expected` (hex, any case).
+pub fn verify_sha256(path: &Path, expected: &str) -> Result<(), ModelHubError> {
+    let actual = sha256_file(path)?;
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(ModelHubError::ChecksumMismatch {
+            expected: expected.trim().to_ascii_lowercase(),
+            actual,
+        })
+    }
+}
+
+/// Fail with [`ModelHubError::InsufficientSpace`] if the file system of
+/// `dir` has less than `required` bytes free. Passes if the free space
+/// can't be determined.
+pub fn ensure_free_space(dir: &Path, required: u64) -> Result<(), ModelHubError> {
+    match available_space(dir) {
+        Ok(available) if available < required => Err(ModelHubError::InsufficientSpace {
+            required,
+            available,
+        }),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            log::warn!("Couldn't determine free space in {}: {}", dir.display(), e);
+            Ok(())
+        }
+    }
+}
+
+/// Bytes available to the current user on the file system of `path`.
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: statvfs only writes to `stat` and reads the NUL-terminated path
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // The field types differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Bytes available to the current user on the volume of `path`.
+#[cfg(windows)]
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut available = 0u64;
+    // SAFETY: `wide` is NUL-terminated and outlives the call
+    unsafe {
+        GetDiskFreeSpaceExW(
+            PCWSTR(wide.as_ptr()),
+            Some(&mut available as *mut u64),
+            None,
+            None,
+        )
+    }
+    .map_err(io::Error::other)?;
+    Ok(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn available_space(_path: &Path) -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "free space is not available on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hf_url() {
+        assert_eq!(
+            hf_url("ggerganov/whisper.cpp", "ggml-base.en.bin"),
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin"
+        );
+        assert_eq!(
+            hf_url_at("/UsefulSensors/moonshine/", "v1", "/onnx/tiny/encoder.onnx"),
+            "https://huggingface.co/UsefulSensors/moonshine/resolve/v1/onnx/tiny/encoder.onnx"
+        );
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let path = std::env::temp_dir().join(format!("model_hub_sha_{}", std::process::id()));
+        fs::write(&path, b"abc").unwrap();
+        let expected = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(sha256_file(&path).unwrap(), expected);
+        assert!(verify_sha256(&path, &expected.to_ascii_uppercase()).is_ok());
+        assert!(matches!(
+            verify_sha256(&path, &"0".repeat(64)),
+            Err(ModelHubError::ChecksumMismatch { .. })
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_free_space() {
+        let dir = std::env::temp_dir();
+        assert!(available_space(&dir).unwrap() > 0);
+        assert!(ensure_free_space(&dir, 1).is_ok());
+        assert!(matches!(
+            ensure_free_space(&dir, u64::MAX),
+            Err(ModelHubError::InsufficientSpace { .. })
+        ));
+    }
+
+    #[test]
+    fn test_partial_path() {
+        assert_eq!(
+            partial_path(Path::new("models/ggml-small.bin")),
+            Path::new("models/ggml-small.bin.partial")
+        );
+    }
+}
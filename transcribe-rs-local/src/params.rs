@@ -0,0 +1,282 @@
+//! Engine-agnostic inference parameters.
+//!
+//! [`CommonInferenceParams`] holds the options most engines share, so API
+//! layers and config files can describe a request once and convert it into
+//! the native parameters of whichever engine is loaded:
+//!
+//! ```ignore
+//! use transcribe_rs::{engines::whisper::WhisperInferenceParams, params::CommonInferenceParams};
+//!
+//! let common: CommonInferenceParams = serde_json::from_str(r#"{"language": "de"}"#)?;
+//! let params = WhisperInferenceParams::from(common);
+//! ```
+//!
+//! Options an engine does not support are ignored; engine-specific settings
+//! keep their defaults.
+
+use serde::{Deserialize, Serialize};
+
+/// Options shared by the transcription engines.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommonInferenceParams {
+    /// Language spoken in the audio (e.g. "en", "de"). `None` lets engines
+    /// that can detect the language do so.
+    pub language: Option<String>,
+
+    /// Languages detection may pick from when `language` is `None`, e.g.
+    /// `["en", "de"]` for a bilingual speaker. Whisper detects among them;
+    /// engines that take a single language use a lone hint as `language`.
+    pub language_hints: Vec<String>,
+
+    /// Translate the speech to English (Whisper, whisperfile, Canary).
+    pub translate: bool,
+
+    /// Context for the model, e.g. names and domain terms or the previous
+    /// transcript (Whisper, whisperfile, OpenAI).
+    pub prompt: Option<String>,
+
+    /// Sampling temperature (0.0 = greedy).
+    pub temperature: Option<f32>,
+
+    /// Level of detail of the returned timestamps (Parakeet, Moonshine,
+    /// OpenAI).
+    /// `None` uses the engine's default.
+    pub timestamp_granularity: Option<Granularity>,
+
+    /// Domain terms and names to favour. See [`crate::vocabulary`].
+    pub vocabulary: Vec<String>,
+}
+
+impl CommonInferenceParams {
+    /// `language`, or the only entry of `language_hints`; what engines that
+    /// take a single language are given.
+    pub fn single_language(&self) -> Option<String> {
+        match (&self.language, self.language_hints.as_slice()) {
+            (Some(language), _) => Some(language.clone()),
+            (None, [hint]) => Some(hint.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Level of detail of segment timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    /// One segment per token.
+    Token,
+    /// One segment per word.
+    Word,
+    /// Phrases or sentences.
+    Segment,
+}
+
+#[cfg(feature = "whisper")]
+impl From<CommonInferenceParams> for crate::engines::whisper::WhisperInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        Self {
+            language: params.language,
+            language_hints: params.language_hints,
+            translate: params.translate,
+            initial_prompt: params.prompt,
+            temperature: params.temperature,
+            vocabulary: params.vocabulary,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "whisperfile")]
+impl From<CommonInferenceParams> for crate::engines::whisperfile::WhisperfileInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        Self {
+            language: params.single_language(),
+            translate: params.translate,
+            temperature: params.temperature,
+            prompt: params.prompt,
+            vocabulary: params.vocabulary,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "faster_whisper")]
+impl From<CommonInferenceParams> for crate::engines::faster_whisper::FasterWhisperInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        Self {
+            language: params.single_language(),
+            temperature: params.temperature,
+            vocabulary: params.vocabulary,
+        }
+    }
+}
+
+#[cfg(feature = "parakeet")]
+impl From<CommonInferenceParams> for crate::engines::parakeet::ParakeetInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        use crate::engines::parakeet::TimestampGranularity;
+
+        Self {
+            timestamp_granularity: match params.timestamp_granularity {
+                Some(Granularity::Token) | None => TimestampGranularity::Token,
+                Some(Granularity::Word) => TimestampGranularity::Word,
+                Some(Granularity::Segment) => TimestampGranularity::Segment,
+            },
+            vocabulary: params.vocabulary,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "moonshine")]
+impl From<CommonInferenceParams> for crate::engines::moonshine::MoonshineInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        Self {
+            timestamps: params.timestamp_granularity,
+            vocabulary: params.vocabulary,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "canary")]
+impl From<CommonInferenceParams> for crate::engines::canary::CanaryInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        Self {
+            target_language: params.translate.then(|| "en".to_string()),
+            source_language: params.single_language(),
+            vocabulary: params.vocabulary,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "sense_voice")]
+impl From<CommonInferenceParams> for crate::engines::sense_voice::SenseVoiceInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        use crate::engines::sense_voice::Language;
+
+        Self {
+            language: params
+                .single_language()
+                .map_or(Language::Auto, |code| Language::from_code(&code)),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "gigaam")]
+impl From<CommonInferenceParams> for crate::engines::gigaam::GigaAMInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        Self {
+            vocabulary: params.vocabulary,
+        }
+    }
+}
+
+#[cfg(feature = "vosk")]
+impl From<CommonInferenceParams> for crate::engines::vosk::VoskInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        Self {
+            vocabulary: params.vocabulary,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl From<CommonInferenceParams> for crate::engines::native::NativeInferenceParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        Self {
+            vocabulary: params.vocabulary,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "openai")]
+impl From<CommonInferenceParams> for crate::remote::openai::OpenAIRequestParams {
+    fn from(params: CommonInferenceParams) -> Self {
+        use crate::remote::openai::OpenAITimestampGranularity;
+
+        let mut builder = Self::builder();
+        builder
+            .language(params.single_language())
+            .prompt(params.prompt)
+            .vocabulary(params.vocabulary)
+            .temperature(params.temperature)
+            .timestamp_granularity(params.timestamp_granularity.map(|g| match g {
+                // The API has no token-level timestamps
+                Granularity::Token | Granularity::Word => OpenAITimestampGranularity::Word,
+                Granularity::Segment => OpenAITimestampGranularity::Segment,
+            }));
+        builder.build().expect("all request params have defaults")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_partial() {
+        let params: CommonInferenceParams = serde_json::from_str(
+            r#"{"language": "de", "timestamp_granularity": "word", "vocabulary": ["Handy"]}"#,
+        )
+        .unwrap();
+        assert_eq!(params.language.as_deref(), Some("de"));
+        assert_eq!(params.timestamp_granularity, Some(Granularity::Word));
+        assert_eq!(params.vocabulary, vec!["Handy".to_string()]);
+        assert!(!params.translate);
+        assert_eq!(params.prompt, None);
+    }
+
+    #[test]
+    fn test_single_language() {
+        let hinted = |hints: &[&str]| CommonInferenceParams {
+            language_hints: hints.iter().map(|hint| hint.to_string()).collect(),
+            ..Default::default()
+        };
+        assert_eq!(hinted(&["de"]).single_language().as_deref(), Some("de"));
+        assert_eq!(hinted(&["de", "en"]).single_language(), None);
+        let params = CommonInferenceParams {
+            language: Some("fr".to_string()),
+            ..hinted(&["de"])
+        };
+        assert_eq!(params.single_language().as_deref(), Some("fr"));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let params = CommonInferenceParams {
+            translate: true,
+            prompt: Some("Kubernetes".to_string()),
+            temperature: Some(0.2),
+            timestamp_granularity: Some(Granularity::Segment),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&params).unwrap();
+        assert!(json.contains(r#""timestamp_granularity":"segment""#));
+        assert_eq!(
+            serde_json::from_str::<CommonInferenceParams>(&json).unwrap(),
+            params
+        );
+    }
+
+    #[cfg(feature = "whisperfile")]
+    #[test]
+    fn test_into_whisperfile_params() {
+        use crate::engines::whisperfile::WhisperfileInferenceParams;
+
+        let params = WhisperfileInferenceParams::from(CommonInferenceParams {
+            language: Some("fr".to_string()),
+            prompt: Some("Handy".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(params.language.as_deref(), Some("fr"));
+        assert_eq!(params.prompt.as_deref(), Some("Handy"));
+        // Engine-specific options keep their defaults
+        assert_eq!(params.response_format.as_deref(), Some("verbose_json"));
+    }
+}
@@ -0,0 +1,172 @@
+//! Engine-agnostic post-processing of transcription output.
+//!
+//! Some engines (notably Moonshine) emit lowercase text without punctuation.
+//! [`restore_punctuation`] applies lightweight, rule-based restoration:
+//!
+//! - The first word and every word following `.`, `!` or `?` are capitalized.
+//! - The pronoun "i" and its contractions ("i'm", "i'll", ...) are capitalized.
+//! - A missing terminal mark is added: `?` when the text opens with a question
+//!   word, `.` otherwise.
+//!
+//! Engines without native punctuation expose this as the `post_process`
+//! inference option; [`apply`] can be used with the output of any engine.
+
+use crate::TranscriptionResult;
+
+/// Post-processing applied to a transcription result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PostProcessOptions {
+    /// Add missing sentence-final punctuation.
+    pub punctuation: bool,
+    /// Restore sentence-initial and pronoun capitalization.
+    pub capitalization: bool,
+}
+
+impl PostProcessOptions {
+    /// Enable both punctuation and capitalization restoration.
+    pub fn all() -> Self {
+        Self {
+            punctuation: true,
+            capitalization: true,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.punctuation || self.capitalization
+    }
+}
+
+/// Words that usually open a question.
+const QUESTION_WORDS: &[&str] = &[
+    "who", "what", "when", "where", "why", "how", "which", "whose", "is", "are", "am", "was",
+    "were", "do", "does", "did", "can", "could", "would", "will", "should", "shall", "may",
+    "might", "have", "has",
+];
+
+/// Apply `options` to the text of `result`.
+///
+/// Segments are left unchanged so that word- and token-level timestamps stay
+/// aligned with the engine output.
+pub fn apply(result: &mut TranscriptionResult, options: &PostProcessOptions) {
+    if options.is_enabled() {
+        result.text = restore_punctuation(&result.text, options);
+    }
+}
+
+/// Restore punctuation and capitalization in `text`.
+pub fn restore_punctuation(text: &str, options: &PostProcessOptions) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return String::new();
+    }
+
+    let mut out = Vec::with_capacity(words.len());
+    let mut sentence_start = true;
+
+    for word in &words {
+        let mut word = word.to_string();
+        if options.capitalization && (sentence_start || is_pronoun_i(&word)) {
+            word = capitalize(&word);
+        }
+        sentence_start = word.ends_with(['.', '!', '?']);
+        out.push(word);
+    }
+
+    let mut restored = out.join(" ");
+    if options.punctuation && !restored.ends_with(['.', '!', '?', ',', ';', ':', '…']) {
+        // Only the last sentence can be missing its mark; check how it opens
+        let last_sentence_start = words
+            .iter()
+            .rposition(|w| w.ends_with(['.', '!', '?']))
+            .map_or(0, |i| i + 1);
+        let opener = words
+            .get(last_sentence_start)
+            .map(|w| w.to_lowercase())
+            .unwrap_or_default();
+        let opener = opener.trim_matches(|c: char| !c.is_alphanumeric());
+        let mark = if QUESTION_WORDS.contains(&opener) {
+            '?'
+        } else {
+            '.'
+        };
+        restored.push(mark);
+    }
+
+    restored
+}
+
+fn is_pronoun_i(word: &str) -> bool {
+    let core = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+    matches!(core, "i" | "i'm" | "i'll" | "i've" | "i'd")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    let mut out = String::with_capacity(word.len());
+    // Skip leading quotes or brackets
+    for c in chars.by_ref() {
+        if c.is_alphanumeric() {
+            out.extend(c.to_uppercase());
+            break;
+        }
+        out.push(c);
+    }
+    out.extend(chars);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restores_sentence_case_and_period() {
+        assert_eq!(
+            restore_punctuation("and so my fellow americans", &PostProcessOptions::all()),
+            "And so my fellow americans."
+        );
+    }
+
+    #[test]
+    fn test_question_detection() {
+        assert_eq!(
+            restore_punctuation("what time is it", &PostProcessOptions::all()),
+            "What time is it?"
+        );
+        assert_eq!(
+            restore_punctuation("ok. can i help", &PostProcessOptions::all()),
+            "Ok. Can I help?"
+        );
+    }
+
+    #[test]
+    fn test_pronoun_and_existing_punctuation() {
+        assert_eq!(
+            restore_punctuation("i think i'm done! yes", &PostProcessOptions::all()),
+            "I think I'm done! Yes."
+        );
+        assert_eq!(
+            restore_punctuation("Already fine.", &PostProcessOptions::all()),
+            "Already fine."
+        );
+    }
+
+    #[test]
+    fn test_options_are_independent() {
+        let casing = PostProcessOptions {
+            punctuation: false,
+            capitalization: true,
+        };
+        assert_eq!(restore_punctuation("hello world", &casing), "Hello world");
+
+        let punctuation = PostProcessOptions {
+            punctuation: true,
+            capitalization: false,
+        };
+        assert_eq!(
+            restore_punctuation("hello world", &punctuation),
+            "hello world."
+        );
+        assert_eq!(restore_punctuation("  ", &punctuation), "");
+    }
+}
@@ -0,0 +1,407 @@
+//! Failover across several engines
+//!
+//! [`FailoverEngine`] holds an ordered list of engines, local or remote, and
+//! tries the next one when a request fails or takes too long, e.g. a local
+//! Whisper model first and the OpenAI API as a fallback. An engine that
+//! fails is skipped for a cooldown period, so later requests don't wait on
+//! it again. With [`FailoverEngine::with_round_robin`], requests are spread
+//! over the healthy engines instead of always starting with the first.
+//!
+//! Engines in cooldown are still tried, last, when all others fail, so a
+//! request only fails once every engine has.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use std::path::PathBuf;
+//! use std::time::Duration;
+//! use transcribe_rs::engines::whisper::WhisperEngine;
+//! use transcribe_rs::remote::failover::FailoverEngine;
+//! use transcribe_rs::remote::openai::{self, OpenAIRequestParams};
+//! use transcribe_rs::{RemoteTranscriptionEngine, TranscriptionEngine};
+//!
+//! let mut whisper = WhisperEngine::new();
+//! whisper.load_model(&PathBuf::from("models/ggml-small.bin"))?;
+//!
+//! let engine = FailoverEngine::new()
+//!     .local("whisper", whisper, || None)
+//!     .remote("openai", openai::default_engine(), OpenAIRequestParams::default)
+//!     .with_timeout(Duration::from_secs(60));
+//!
+//! let result = engine.transcribe_file(&PathBuf::from("audio.wav"), ()).await?;
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use log::{debug, warn};
+
+use crate::{RemoteTranscriptionEngine, TranscriptionEngine, TranscriptionResult};
+
+/// How long a failed engine is skipped by default.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// An engine behind [`FailoverEngine`], with its request parameters bound.
+#[async_trait]
+trait Member: Send + Sync {
+    /// Transcribe `wav_path`. Errors are returned as messages so they can
+    /// be held across the next attempt.
+    async fn transcribe(&self, wav_path: &Path) -> Result<TranscriptionResult, String>;
+}
+
+struct RemoteMember<E: RemoteTranscriptionEngine, P> {
+    engine: E,
+    params: P,
+}
+
+#[async_trait]
+impl<E, P> Member for RemoteMember<E, P>
+where
+    E: RemoteTranscriptionEngine,
+    P: Fn() -> E::RequestParams + Send + Sync,
+{
+    async fn transcribe(&self, wav_path: &Path) -> Result<TranscriptionResult, String> {
+        self.engine
+            .transcribe_file(wav_path, (self.params)())
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+struct LocalMember<E, P> {
+    engine: Arc<Mutex<E>>,
+    params: Arc<P>,
+}
+
+#[async_trait]
+impl<E, P> Member for LocalMember<E, P>
+where
+    E: TranscriptionEngine + Send + 'static,
+    P: Fn() -> Option<E::InferenceParams> + Send + Sync + 'static,
+{
+    async fn transcribe(&self, wav_path: &Path) -> Result<TranscriptionResult, String> {
+        let engine = self.engine.clone();
+        let params = self.params.clone();
+        let wav_path = PathBuf::from(wav_path);
+        // Local inference blocks, so keep it off the async runtime's workers
+        tokio::task::spawn_blocking(move || {
+            let mut engine = engine.lock().unwrap_or_else(|e| e.into_inner());
+            engine
+                .transcribe_file(&wav_path, params())
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
+
+/// Health of one engine of a [`FailoverEngine`].
+#[derive(Clone, Debug, Default)]
+pub struct EngineHealth {
+    pub name: String,
+    /// Failures since the last success.
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// The engine is skipped until then, unless all others fail.
+    pub cooldown_until: Option<Instant>,
+}
+
+impl EngineHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.cooldown_until
+            .is_none_or(|until| Instant::now() >= until)
+    }
+}
+
+/// Tries its engines in order until one succeeds. See the module docs.
+pub struct FailoverEngine {
+    members: Vec<(Box<dyn Member>, Mutex<EngineHealth>)>,
+    timeout: Option<Duration>,
+    cooldown: Duration,
+    round_robin: bool,
+    /// Where the next round-robin request starts.
+    next: AtomicUsize,
+}
+
+impl Default for FailoverEngine {
+    fn default() -> Self {
+        Self {
+            members: Vec::new(),
+            timeout: None,
+            cooldown: DEFAULT_COOLDOWN,
+            round_robin: false,
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl FailoverEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a remote engine, sent the parameters `params` returns with each
+    /// request.
+    pub fn remote<E, P>(self, name: impl Into<String>, engine: E, params: P) -> Self
+    where
+        E: RemoteTranscriptionEngine + 'static,
+        P: Fn() -> E::RequestParams + Send + Sync + 'static,
+    {
+        self.member(name.into(), Box::new(RemoteMember { engine, params }))
+    }
+
+    /// Add a local engine with its model already loaded. Requests run on a
+    /// blocking thread, one at a time.
+    pub fn local<E, P>(self, name: impl Into<String>, engine: E, params: P) -> Self
+    where
+        E: TranscriptionEngine + Send + 'static,
+        P: Fn() -> Option<E::InferenceParams> + Send + Sync + 'static,
+    {
+        self.member(
+            name.into(),
+            Box::new(LocalMember {
+                engine: Arc::new(Mutex::new(engine)),
+                params: Arc::new(params),
+            }),
+        )
+    }
+
+    fn member(mut self, name: String, member: Box<dyn Member>) -> Self {
+        let health = EngineHealth {
+            name,
+            ..Default::default()
+        };
+        self.members.push((member, Mutex::new(health)));
+        self
+    }
+
+    /// Move on to the next engine when one takes longer than `timeout`. A
+    /// local engine keeps running in the background until it finishes, and
+    /// isn't available to later requests in the meantime.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// How long a failed engine is skipped; 30 seconds by default.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Start each request with the next healthy engine instead of the
+    /// first, spreading the load.
+    pub fn with_round_robin(mut self) -> Self {
+        self.round_robin = true;
+        self
+    }
+
+    /// Health of each engine, in order.
+    pub fn health(&self) -> Vec<EngineHealth> {
+        self.members
+            .iter()
+            .map(|(_, health)| lock(health).clone())
+            .collect()
+    }
+
+    /// Indices of the engines in the order a request tries them: healthy
+    /// engines from the starting point, then those in cooldown.
+    fn attempt_order(&self) -> Vec<usize> {
+        let count = self.members.len();
+        let start = if self.round_robin && count > 0 {
+            self.next.fetch_add(1, Ordering::Relaxed) % count
+        } else {
+            0
+        };
+        let (mut healthy, cooling): (Vec<usize>, Vec<usize>) = (0..count)
+            .map(|offset| (start + offset) % count)
+            .partition(|&index| lock(&self.members[index].1).is_healthy());
+        healthy.extend(cooling);
+        healthy
+    }
+
+    fn record_success(&self, index: usize) {
+        let mut health = lock(&self.members[index].1);
+        health.consecutive_failures = 0;
+        health.cooldown_until = None;
+    }
+
+    fn record_failure(&self, index: usize, error: &str) {
+        let mut health = lock(&self.members[index].1);
+        health.consecutive_failures += 1;
+        health.last_error = Some(error.to_string());
+        health.cooldown_until = Some(Instant::now() + self.cooldown);
+        warn!(
+            "Engine '{}' failed ({} in a row): {}",
+            health.name, health.consecutive_failures, error
+        );
+    }
+}
+
+fn lock(health: &Mutex<EngineHealth>) -> std::sync::MutexGuard<'_, EngineHealth> {
+    health.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[async_trait]
+impl RemoteTranscriptionEngine for FailoverEngine {
+    type RequestParams = ();
+
+    async fn transcribe_file(
+        &self,
+        wav_path: &Path,
+        _params: Self::RequestParams,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        if self.members.is_empty() {
+            return Err("FailoverEngine has no engines".into());
+        }
+
+        let mut errors = Vec::new();
+        for index in self.attempt_order() {
+            let (member, health) = &self.members[index];
+            let name = lock(health).name.clone();
+            debug!("Transcribing with engine '{}'", name);
+
+            let attempt = member.transcribe(wav_path);
+            let result = match self.timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, attempt).await {
+                    Ok(result) => result,
+                    Err(_) => Err(format!("Timed out after {:?}", timeout)),
+                },
+                None => attempt.await,
+            };
+
+            match result {
+                Ok(result) => {
+                    self.record_success(index);
+                    return Ok(result);
+                }
+                Err(error) => {
+                    self.record_failure(index, &error);
+                    errors.push(format!("{}: {}", name, error));
+                }
+            }
+        }
+
+        Err(format!("All engines failed. {}", errors.join("; ")).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    /// Remote engine that answers with its name, or fails while `failing`.
+    struct FakeEngine {
+        name: &'static str,
+        failing: Arc<AtomicBool>,
+        delay: Duration,
+    }
+
+    impl FakeEngine {
+        fn new(name: &'static str) -> (Self, Arc<AtomicBool>) {
+            let failing = Arc::new(AtomicBool::new(false));
+            let engine = Self {
+                name,
+                failing: failing.clone(),
+                delay: Duration::ZERO,
+            };
+            (engine, failing)
+        }
+    }
+
+    #[async_trait]
+    impl RemoteTranscriptionEngine for FakeEngine {
+        type RequestParams = ();
+
+        async fn transcribe_file(
+            &self,
+            _wav_path: &Path,
+            _params: (),
+        ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+            tokio::time::sleep(self.delay).await;
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(format!("{} is down", self.name).into());
+            }
+            Ok(TranscriptionResult {
+                text: self.name.to_string(),
+                segments: None,
+                language: None,
+            })
+        }
+    }
+
+    async fn transcribe(engine: &FailoverEngine) -> Result<String, String> {
+        engine
+            .transcribe_file(Path::new("audio.wav"), ())
+            .await
+            .map(|result| result.text)
+            .map_err(|e| e.to_string())
+    }
+
+    #[tokio::test]
+    async fn falls_back_and_skips_failed_engines() {
+        let (primary, primary_failing) = FakeEngine::new("primary");
+        let (backup, backup_failing) = FakeEngine::new("backup");
+        let engine = FailoverEngine::new()
+            .remote("primary", primary, || ())
+            .remote("backup", backup, || ())
+            .with_cooldown(Duration::from_secs(60));
+
+        assert_eq!(transcribe(&engine).await.unwrap(), "primary");
+
+        primary_failing.store(true, Ordering::SeqCst);
+        assert_eq!(transcribe(&engine).await.unwrap(), "backup");
+        let health = engine.health();
+        assert!(!health[0].is_healthy());
+        assert_eq!(health[0].consecutive_failures, 1);
+        assert_eq!(health[0].last_error.as_deref(), Some("primary is down"));
+
+        // The primary is in cooldown, so it isn't tried even once it's back
+        primary_failing.store(false, Ordering::SeqCst);
+        assert_eq!(transcribe(&engine).await.unwrap(), "backup");
+
+        // ...unless everything else fails
+        backup_failing.store(true, Ordering::SeqCst);
+        assert_eq!(transcribe(&engine).await.unwrap(), "primary");
+        assert!(engine.health()[0].is_healthy());
+
+        primary_failing.store(true, Ordering::SeqCst);
+        let error = transcribe(&engine).await.unwrap_err();
+        assert!(error.contains("primary is down") && error.contains("backup is down"));
+    }
+
+    #[tokio::test]
+    async fn times_out_slow_engines() {
+        let (mut slow, _) = FakeEngine::new("slow");
+        slow.delay = Duration::from_secs(5);
+        let (fast, _) = FakeEngine::new("fast");
+        let engine = FailoverEngine::new()
+            .remote("slow", slow, || ())
+            .remote("fast", fast, || ())
+            .with_timeout(Duration::from_millis(50));
+
+        assert_eq!(transcribe(&engine).await.unwrap(), "fast");
+        assert!(engine.health()[0]
+            .last_error
+            .as_deref()
+            .is_some_and(|error| error.starts_with("Timed out")));
+    }
+
+    #[tokio::test]
+    async fn round_robin_rotates_engines() {
+        let (a, _) = FakeEngine::new("a");
+        let (b, _) = FakeEngine::new("b");
+        let engine = FailoverEngine::new()
+            .remote("a", a, || ())
+            .remote("b", b, || ())
+            .with_round_robin();
+
+        assert_eq!(transcribe(&engine).await.unwrap(), "a");
+        assert_eq!(transcribe(&engine).await.unwrap(), "b");
+        assert_eq!(transcribe(&engine).await.unwrap(), "a");
+    }
+}
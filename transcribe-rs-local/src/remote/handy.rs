@@ -0,0 +1,253 @@
+//! Client for another Handy-API server
+//!
+//! Sends audio to the `POST /transcribe` endpoint of a Handy instance, so a
+//! machine with a GPU can transcribe for lightweight clients through the
+//! same [`RemoteTranscriptionEngine`] trait as the other remote APIs. The
+//! server transcribes with its selected model, or the model routed for the
+//! requested language.
+//!
+//! # Usage
+//!
+//! ```rust,no_run
+//! use std::path::PathBuf;
+//! use transcribe_rs::remote::handy::{HandyEngine, HandyRequestParams};
+//! use transcribe_rs::RemoteTranscriptionEngine;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let engine = HandyEngine::new("http://gpu-box:8720").with_token("secret");
+//!
+//! let result = engine
+//!     .transcribe_file(
+//!         &PathBuf::from("audio.wav"),
+//!         HandyRequestParams::builder().language("de").build()?,
+//!     )
+//!     .await?;
+//! println!("{}", result.text);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use derive_builder::Builder;
+use log::{debug, error};
+use serde::Deserialize;
+use ureq::Agent;
+
+use crate::engines::http::{self, MultipartForm};
+use crate::{RemoteTranscriptionEngine, TranscriptionResult, TranscriptionSegment};
+
+#[derive(Clone, Debug)]
+pub struct HandyEngine {
+    /// Server address without a trailing slash, e.g. `http://localhost:8720`
+    base_url: String,
+    /// Bearer token for servers with `auth_token` set
+    token: Option<String>,
+    agent: Agent,
+}
+
+impl HandyEngine {
+    /// Engine for the Handy server at `base_url`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            agent: build_agent(None),
+        }
+    }
+
+    /// Authenticate with the server's bearer token.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Give up on requests that take longer than `timeout`. Long recordings
+    /// on a busy server can take minutes, so there is no limit by default.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.agent = build_agent(Some(timeout));
+        self
+    }
+}
+
+fn build_agent(timeout: Option<Duration>) -> Agent {
+    Agent::config_builder()
+        .timeout_global(timeout)
+        // Error responses carry a JSON message worth reporting
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+/// Fields of the server's `POST /transcribe` request. Options the server
+/// doesn't support for its model are ignored by it.
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct HandyRequestParams {
+    /// Spoken language such as "ja", or "auto"; the server's selected
+    /// language when unset. Also picks the server's model for that language.
+    language: Option<String>,
+    /// Languages the server's detection may pick from when `language` is
+    /// "auto", e.g. `["en", "de"]`.
+    language_hints: Vec<String>,
+    /// Context for the model, e.g. names and domain terms (Whisper only).
+    prompt: Option<String>,
+    /// Domain terms and names to favour, appended to `prompt`.
+    vocabulary: Vec<String>,
+    /// Sampling temperature between 0 and 1 (Whisper only).
+    temperature: Option<f32>,
+    /// "fast", "balanced" or "best"; the server's default when unset.
+    quality: Option<String>,
+    /// Translate the transcript into this language. The server needs
+    /// translation configured.
+    target_language: Option<String>,
+    /// Run noise suppression on the server first.
+    denoise: bool,
+    /// Ask for word timings. The server needs an alignment model; each
+    /// word is returned as a segment.
+    words: bool,
+}
+
+impl HandyRequestParams {
+    pub fn builder() -> HandyRequestParamsBuilder {
+        HandyRequestParamsBuilder::default()
+    }
+}
+
+#[derive(Deserialize)]
+struct HandyResponse {
+    text: String,
+    #[serde(default)]
+    segments: Option<Vec<HandySegment>>,
+    #[serde(default)]
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HandySegment {
+    start: f32,
+    end: f32,
+    text: String,
+    #[serde(default)]
+    words: Option<Vec<HandyWord>>,
+}
+
+#[derive(Deserialize)]
+struct HandyWord {
+    start: f32,
+    end: f32,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct HandyError {
+    error: String,
+}
+
+impl HandyEngine {
+    /// Send `wav_data` to the server. Blocks until the response arrives.
+    fn transcribe_wav_bytes(
+        &self,
+        wav_data: Vec<u8>,
+        params: HandyRequestParams,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error + Send + Sync>> {
+        let mut form = MultipartForm::new().file("file", "audio.wav", "audio/wav", wav_data);
+        if let Some(language) = &params.language {
+            form = form.text("language", language);
+        }
+        if !params.language_hints.is_empty() {
+            form = form.text("language_hints", &params.language_hints.join(","));
+        }
+        if let Some(prompt) =
+            crate::vocabulary::vocabulary_prompt(&params.vocabulary, params.prompt.as_deref())
+        {
+            form = form.text("prompt", &prompt);
+        }
+        if let Some(temperature) = params.temperature {
+            form = form.text("temperature", &temperature.to_string());
+        }
+        if let Some(quality) = &params.quality {
+            form = form.text("quality", quality);
+        }
+        if let Some(target_language) = &params.target_language {
+            form = form.text("target_language", target_language);
+        }
+        if params.denoise {
+            form = form.text("denoise", "true");
+        }
+        if params.words {
+            form = form.text("align", "true");
+        }
+        let (content_type, body) = form.build();
+
+        let url = format!("{}/transcribe", self.base_url);
+        debug!("Sending transcription request to {}", url);
+        let mut request = self.agent.post(&url).content_type(&content_type);
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", &format!("Bearer {}", token));
+        }
+        let response = request
+            .send(&body[..])
+            .map_err(|e| format!("Failed to reach Handy server at {}: {}", self.base_url, e))?;
+
+        let status = response.status();
+        let body = response.into_body().read_to_string()?;
+        if !status.is_success() {
+            let message = serde_json::from_str::<HandyError>(&body)
+                .map(|e| e.error)
+                .unwrap_or(body);
+            error!("Handy server error {}: {}", status, message);
+            return Err(format!("Handy server error {}: {}", status, message).into());
+        }
+
+        let response: HandyResponse = serde_json::from_str(&body)?;
+        let segments = response.segments.map(|segments| {
+            segments
+                .into_iter()
+                .flat_map(|segment| match segment.words {
+                    // Each word becomes a segment when word timings were asked for
+                    Some(words) if params.words => words
+                        .into_iter()
+                        .map(|word| TranscriptionSegment {
+                            start: word.start,
+                            end: word.end,
+                            text: word.text,
+                        })
+                        .collect(),
+                    _ => vec![TranscriptionSegment {
+                        start: segment.start,
+                        end: segment.end,
+                        text: segment.text,
+                    }],
+                })
+                .collect()
+        });
+
+        Ok(TranscriptionResult {
+            text: response.text,
+            segments,
+            language: http::language_code(response.language),
+        })
+    }
+}
+
+#[async_trait]
+impl RemoteTranscriptionEngine for HandyEngine {
+    type RequestParams = HandyRequestParams;
+
+    async fn transcribe_file(
+        &self,
+        wav_path: &Path,
+        params: Self::RequestParams,
+    ) -> Result<TranscriptionResult, Box<dyn std::error::Error>> {
+        let wav_data = std::fs::read(wav_path)?;
+        let engine = self.clone();
+        // ureq blocks, so keep it off the async runtime's worker threads
+        let result =
+            tokio::task::spawn_blocking(move || engine.transcribe_wav_bytes(wav_data, params))
+                .await?;
+        result.map_err(|e| e as Box<dyn std::error::Error>)
+    }
+}
@@ -4,6 +4,11 @@ use async_trait::async_trait;
 
 use crate::TranscriptionResult;
 
+#[cfg(feature = "failover")]
+pub mod failover;
+#[cfg(feature = "handy")]
+pub mod handy;
+#[cfg(feature = "openai")]
 pub mod openai;
 
 /// Common interface for speech transcription through remote APIs.
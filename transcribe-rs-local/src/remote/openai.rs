@@ -96,6 +96,8 @@ pub struct OpenAIRequestParams {
     /// around OpenAI, GPT-4.5 and the future of AI.
     /// ```
     prompt: Option<String>,
+    /// Domain terms and names to favour, appended to `prompt`.
+    vocabulary: Vec<String>,
     /// The sampling temprature between 0 and 1.
     temperature: Option<f32>,
     /// The timestamp granularities to populate for this transcription.
@@ -116,6 +118,7 @@ impl Default for OpenAIRequestParams {
             model: OpenAIModel::Gpt4oMiniTranscribe,
             language: None,
             prompt: None,
+            vocabulary: Vec::new(),
             temperature: None,
             timestamp_granularity: None,
         }
@@ -167,7 +170,9 @@ where
             request.language(language);
         }
 
-        if let Some(prompt) = params.prompt {
+        if let Some(prompt) =
+            crate::vocabulary::vocabulary_prompt(&params.vocabulary, params.prompt.as_deref())
+        {
             request.prompt(prompt);
         }
 
@@ -188,6 +193,7 @@ where
                 return Ok(TranscriptionResult {
                     text: response.text,
                     segments: None,
+                    language: params.language.clone(),
                 });
             }
             OpenAIModel::Whisper1 => {
@@ -234,6 +240,7 @@ where
                 return Ok(TranscriptionResult {
                     text: response.text,
                     segments,
+                    language: params.language.clone(),
                 });
             }
         }
@@ -0,0 +1,192 @@
+//! Custom vocabulary (keyword boosting) support.
+//!
+//! Every engine accepts a `vocabulary` inference option listing domain terms
+//! and product names that should be transcribed verbatim:
+//!
+//! - Whisper, whisperfile and the OpenAI API receive the terms in their
+//!   prompt via [`vocabulary_prompt`], biasing decoding towards them.
+//! - Local engines additionally run [`correct_text`], a fuzzy post-correction
+//!   pass that replaces near-miss spellings with the listed terms.
+
+use crate::TranscriptionResult;
+
+/// Minimum similarity (1.0 = identical) for a word to be replaced by a term.
+const MIN_SIMILARITY: f32 = 0.8;
+
+/// Terms shorter than this are too ambiguous to fuzzy-match.
+const MIN_TERM_LEN: usize = 4;
+
+/// Build a prompt that lists `vocabulary`, appended to an existing `prompt`.
+///
+/// Returns `prompt` unchanged when the vocabulary is empty.
+pub fn vocabulary_prompt(vocabulary: &[String], prompt: Option<&str>) -> Option<String> {
+    let terms: Vec<&str> = vocabulary
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let prompt = prompt.map(str::trim).filter(|p| !p.is_empty());
+
+    if terms.is_empty() {
+        return prompt.map(str::to_string);
+    }
+
+    let glossary = format!("Glossary: {}.", terms.join(", "));
+    Some(match prompt {
+        Some(p) => format!("{} {}", p, glossary),
+        None => glossary,
+    })
+}
+
+/// Apply [`correct_text`] to the text and segments of `result`.
+pub fn apply(result: &mut TranscriptionResult, vocabulary: &[String]) {
+    if vocabulary.is_empty() {
+        return;
+    }
+
+    result.text = correct_text(&result.text, vocabulary);
+    if let Some(segments) = result.segments.as_mut() {
+        for segment in segments {
+            segment.text = correct_text(&segment.text, vocabulary);
+        }
+    }
+}
+
+/// Replace words in `text` that closely resemble a `vocabulary` term with
+/// the term itself. Multi-word terms are matched against runs of words, and
+/// single-word terms also against two adjacent words written apart
+/// (e.g. "chat gpt" for "ChatGPT").
+pub fn correct_text(text: &str, vocabulary: &[String]) -> String {
+    let terms: Vec<(&str, String, usize)> = vocabulary
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| normalize(t).chars().count() >= MIN_TERM_LEN)
+        .map(|t| (t, normalize(t), t.split_whitespace().count()))
+        .collect();
+    if terms.is_empty() {
+        return text.to_string();
+    }
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut out: Vec<String> = Vec::with_capacity(words.len());
+    let mut i = 0;
+
+    while i < words.len() {
+        let mut best: Option<(f32, usize, &str)> = None;
+
+        for (term, normalized_term, term_words) in &terms {
+            for span in [*term_words, term_words + 1] {
+                if i + span > words.len() {
+                    continue;
+                }
+                let candidate: String = words[i..i + span].iter().map(|w| normalize(w)).collect();
+                let score = similarity(&candidate, normalized_term);
+                if score >= MIN_SIMILARITY && best.is_none_or(|(s, _, _)| score > s) {
+                    best = Some((score, span, term));
+                }
+            }
+        }
+
+        match best {
+            Some((_, span, term)) => {
+                // Keep punctuation attached to the last replaced word
+                let trailing: String = words[i + span - 1]
+                    .chars()
+                    .rev()
+                    .take_while(|c| !c.is_alphanumeric())
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .rev()
+                    .collect();
+                out.push(format!("{}{}", term, trailing));
+                i += span;
+            }
+            None => {
+                out.push(words[i].to_string());
+                i += 1;
+            }
+        }
+    }
+
+    out.join(" ")
+}
+
+/// Lowercase alphanumeric characters only, so "Chat-GPT," matches "chatgpt".
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Normalized Levenshtein similarity between `a` and `b`.
+fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    1.0 - prev[b.len()] as f32 / max_len as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocab(terms: &[&str]) -> Vec<String> {
+        terms.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_vocabulary_prompt() {
+        let terms = vocab(&["Kubernetes", "Handy"]);
+        assert_eq!(
+            vocabulary_prompt(&terms, None).as_deref(),
+            Some("Glossary: Kubernetes, Handy.")
+        );
+        assert_eq!(
+            vocabulary_prompt(&terms, Some("A tech talk.")).as_deref(),
+            Some("A tech talk. Glossary: Kubernetes, Handy.")
+        );
+        assert_eq!(
+            vocabulary_prompt(&[], Some("A tech talk.")).as_deref(),
+            Some("A tech talk.")
+        );
+        assert_eq!(vocabulary_prompt(&[], None), None);
+    }
+
+    #[test]
+    fn test_corrects_near_misses() {
+        let terms = vocab(&["Kubernetes", "ChatGPT", "Visual Studio Code"]);
+        assert_eq!(
+            correct_text("we deployed it on kubernetis, then asked chat gpt", &terms),
+            "we deployed it on Kubernetes, then asked ChatGPT"
+        );
+        assert_eq!(
+            correct_text("open visual studio coat.", &terms),
+            "open Visual Studio Code."
+        );
+    }
+
+    #[test]
+    fn test_leaves_unrelated_words() {
+        let terms = vocab(&["Kubernetes", "API"]);
+        assert_eq!(
+            correct_text("the cat sat on the mat", &terms),
+            "the cat sat on the mat"
+        );
+    }
+}
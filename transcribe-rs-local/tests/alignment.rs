@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+use transcribe_rs::alignment::Aligner;
+use transcribe_rs::audio::read_wav_samples;
+use transcribe_rs::{TranscriptionResult, TranscriptionSegment};
+
+const JFK_TEXT: &str = "And so my fellow Americans, ask not what your country can do for you, ask what you can do for your country.";
+
+// Model directory - can be overridden with ALIGN_MODEL env var
+fn model_path() -> PathBuf {
+    std::env::var("ALIGN_MODEL")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("models/wav2vec2-base-960h"))
+}
+
+#[test]
+fn test_alignment_jfk() {
+    let model_path = model_path();
+    if !model_path.exists() {
+        eprintln!(
+            "Skipping test: alignment model not found at {:?}",
+            model_path
+        );
+        return;
+    }
+
+    let mut aligner = Aligner::new(&model_path).expect("Failed to load model");
+    let samples = read_wav_samples(&PathBuf::from("samples/jfk.wav")).expect("Failed to read");
+    let duration = samples.len() as f32 / 16000.0;
+
+    // A single segment spanning the whole file, as from an engine without timestamps
+    let result = TranscriptionResult {
+        text: JFK_TEXT.to_string(),
+        segments: Some(vec![TranscriptionSegment {
+            start: 0.0,
+            end: duration,
+            text: JFK_TEXT.to_string(),
+        }]),
+        language: Some("en".to_string()),
+    };
+
+    let aligned = aligner.align(&samples, &result).expect("Failed to align");
+    assert_eq!(aligned.len(), 1);
+
+    let words = &aligned[0].words;
+    for word in words {
+        println!("[{:.2}s - {:.2}s] {}", word.start, word.end, word.text);
+    }
+
+    assert_eq!(words.len(), JFK_TEXT.split_whitespace().count());
+    assert!(words.windows(2).all(|w| w[0].end <= w[1].start + 1e-3));
+    assert!(words.iter().all(|w| w.start <= w.end && w.end <= duration));
+    // Speech starts after a short pause, not at the segment start
+    assert!(
+        words[0].start > 0.1,
+        "First word starts at {}",
+        words[0].start
+    );
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use transcribe_rs::engines::canary::{CanaryEngine, CanaryInferenceParams, CanaryModelParams};
+use transcribe_rs::TranscriptionEngine;
+
+// Model directory - can be overridden with CANARY_MODEL env var
+fn model_path() -> PathBuf {
+    std::env::var("CANARY_MODEL")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("models/canary-1b-v2"))
+}
+
+fn load_engine() -> Option<CanaryEngine> {
+    let model_path = model_path();
+    if !model_path.exists() {
+        eprintln!("Skipping test: Canary model not found at {:?}", model_path);
+        return None;
+    }
+
+    let mut engine = CanaryEngine::new();
+    engine
+        .load_model_with_params(&model_path, CanaryModelParams::int8())
+        .expect("Failed to load model");
+    Some(engine)
+}
+
+#[test]
+fn test_canary_jfk() {
+    let Some(mut engine) = load_engine() else {
+        return;
+    };
+
+    let result = engine
+        .transcribe_file(&PathBuf::from("samples/jfk.wav"), None)
+        .expect("Failed to transcribe");
+
+    println!("Transcription: {}", result.text);
+
+    let text = result.text.to_lowercase();
+    assert!(
+        text.contains("ask") && text.contains("country"),
+        "Transcription should contain 'ask' and 'country'. Got: '{}'",
+        result.text
+    );
+    assert!(
+        result.text.ends_with(['.', '!', '?']),
+        "Canary should punctuate by default. Got: '{}'",
+        result.text
+    );
+}
+
+#[test]
+fn test_canary_translation() {
+    let Some(mut engine) = load_engine() else {
+        return;
+    };
+
+    let result = engine
+        .transcribe_file(
+            &PathBuf::from("samples/jfk.wav"),
+            Some(CanaryInferenceParams::translate("en", "de")),
+        )
+        .expect("Failed to translate");
+
+    println!("Translation: {}", result.text);
+
+    let text = result.text.to_lowercase();
+    assert!(
+        text.contains("land") && !text.contains("country"),
+        "Translation should be German. Got: '{}'",
+        result.text
+    );
+}
+
+#[test]
+fn test_canary_unsupported_language() {
+    let Some(mut engine) = load_engine() else {
+        return;
+    };
+
+    let result = engine.transcribe_file(
+        &PathBuf::from("samples/jfk.wav"),
+        Some(CanaryInferenceParams::translate("en", "xx")),
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,99 @@
+use std::path::{Path, PathBuf};
+use transcribe_rs::engines::faster_whisper::{
+    FasterWhisperEngine, FasterWhisperInferenceParams, FasterWhisperModelParams,
+    FasterWhisperProtocol,
+};
+use transcribe_rs::TranscriptionEngine;
+
+// Address of a running server, e.g. tcp://127.0.0.1:10300 or http://localhost:8000
+fn server_url() -> Option<String> {
+    std::env::var("FASTER_WHISPER_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+}
+
+fn protocol() -> FasterWhisperProtocol {
+    match std::env::var("FASTER_WHISPER_PROTOCOL").as_deref() {
+        Ok("openai") => FasterWhisperProtocol::OpenAI,
+        _ => FasterWhisperProtocol::Wyoming,
+    }
+}
+
+// Model name sent to the server - can be overridden with FASTER_WHISPER_MODEL env var
+fn model_name() -> String {
+    std::env::var("FASTER_WHISPER_MODEL").unwrap_or_else(|_| "small".to_string())
+}
+
+fn connect() -> Option<FasterWhisperEngine> {
+    let url = match server_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping test: FASTER_WHISPER_URL not set");
+            return None;
+        }
+    };
+
+    let mut engine = FasterWhisperEngine::connect(protocol(), &url);
+    let params = FasterWhisperModelParams {
+        startup_timeout_secs: 5,
+        ..Default::default()
+    };
+    engine
+        .load_model_with_params(Path::new(&model_name()), params)
+        .expect("Failed to connect to faster-whisper server");
+    Some(engine)
+}
+
+#[test]
+fn test_jfk_transcription() {
+    let Some(mut engine) = connect() else {
+        return;
+    };
+
+    let result = engine
+        .transcribe_file(&PathBuf::from("samples/jfk.wav"), None)
+        .expect("Failed to transcribe");
+
+    let text_normalized: String = result
+        .text
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    assert!(
+        text_normalized.contains("ask not what your country can do for you"),
+        "Should contain 'ask not what your country can do for you', got: {}",
+        result.text
+    );
+}
+
+#[test]
+fn test_transcribe_samples_with_language() {
+    let Some(mut engine) = connect() else {
+        return;
+    };
+
+    let samples = transcribe_rs::audio::read_wav_samples(&PathBuf::from("samples/jfk.wav"))
+        .expect("Failed to read samples");
+    let params = FasterWhisperInferenceParams {
+        language: Some("en".to_string()),
+        ..Default::default()
+    };
+
+    let result = engine
+        .transcribe_samples(samples, Some(params))
+        .expect("Failed to transcribe samples");
+
+    assert!(
+        result.text.to_lowercase().contains("my fellow americans"),
+        "Should contain 'my fellow Americans', got: {}",
+        result.text
+    );
+}
+
+#[test]
+fn test_transcribe_without_model_fails() {
+    let mut engine = FasterWhisperEngine::connect(FasterWhisperProtocol::Wyoming, "127.0.0.1:1");
+    assert!(engine.transcribe_samples(vec![0.0; 16000], None).is_err());
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+use transcribe_rs::engines::gigaam::GigaAMEngine;
+use transcribe_rs::TranscriptionEngine;
+
+// Model file - can be overridden with GIGAAM_MODEL env var
+fn model_path() -> PathBuf {
+    std::env::var("GIGAAM_MODEL")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("models/giga-am-v3.int8.onnx"))
+}
+
+#[test]
+fn test_gigaam_russian() {
+    let model_path = model_path();
+    if !model_path.exists() {
+        eprintln!("Skipping test: GigaAM model not found at {:?}", model_path);
+        return;
+    }
+
+    let mut engine = GigaAMEngine::new();
+    engine
+        .load_model(&model_path)
+        .expect("Failed to load model");
+
+    let result = engine
+        .transcribe_file(&PathBuf::from("samples/russian.wav"), None)
+        .expect("Failed to transcribe");
+
+    println!("Transcription: {}", result.text);
+
+    assert!(
+        result
+            .text
+            .chars()
+            .any(|c| matches!(c, 'а'..='я' | 'А'..='Я')),
+        "Transcription should be Cyrillic. Got: '{}'",
+        result.text
+    );
+    assert_eq!(result.language.as_deref(), Some("ru"));
+
+    let segments = result.segments.expect("GigaAM returns word segments");
+    assert!(!segments.is_empty());
+    assert!(segments.windows(2).all(|w| w[0].start <= w[1].start));
+}
@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use transcribe_rs::remote::handy::{HandyEngine, HandyRequestParams};
+use transcribe_rs::RemoteTranscriptionEngine;
+
+// Address of a running Handy API server, e.g. http://localhost:8720
+fn connect() -> Option<HandyEngine> {
+    let url = match std::env::var("HANDY_API_URL") {
+        Ok(url) if !url.is_empty() => url,
+        _ => {
+            eprintln!("Skipping test: HANDY_API_URL not set");
+            return None;
+        }
+    };
+
+    let engine = HandyEngine::new(url);
+    Some(match std::env::var("HANDY_API_TOKEN") {
+        Ok(token) if !token.is_empty() => engine.with_token(token),
+        _ => engine,
+    })
+}
+
+#[tokio::test]
+async fn test_jfk_transcription() {
+    let Some(engine) = connect() else {
+        return;
+    };
+
+    let result = engine
+        .transcribe_file(
+            &PathBuf::from("samples/jfk.wav"),
+            HandyRequestParams::builder()
+                .language("en")
+                .build()
+                .expect("Parameters should be valid"),
+        )
+        .await
+        .expect("Failed to transcribe");
+
+    let text_normalized = result.text.to_lowercase();
+    assert!(
+        text_normalized.contains("ask not what your country can do for you"),
+        "Should contain 'ask not what your country can do for you', got: {}",
+        result.text
+    );
+    assert_eq!(result.language.as_deref(), Some("en"));
+}
+
+#[tokio::test]
+async fn test_invalid_request_is_an_error() {
+    let Some(engine) = connect() else {
+        return;
+    };
+
+    let error = engine
+        .transcribe_file(
+            &PathBuf::from("samples/jfk.wav"),
+            HandyRequestParams::builder()
+                .quality("fastest")
+                .build()
+                .expect("Parameters should be valid"),
+        )
+        .await
+        .expect_err("The server should reject the quality");
+    assert!(error.to_string().contains("400"), "got: {}", error);
+}
@@ -0,0 +1,174 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use transcribe_rs::model_hub::{self, DownloadOptions, ModelHubError};
+
+/// SHA-256 of `model_bytes()`.
+fn model_sha256() -> String {
+    let path = temp_path("reference");
+    fs::write(&path, model_bytes()).unwrap();
+    let sha = model_hub::sha256_file(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    sha
+}
+
+fn model_bytes() -> Vec<u8> {
+    (0..200_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("model_hub_test_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+/// Serve `model_bytes()` on a free port, honouring `Range: bytes=N-`
+/// headers if `ranges` is set. Returns the port.
+fn stub_server(ranges: bool) -> u16 {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let body = model_bytes();
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => break,
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut start = 0;
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header).unwrap_or(0) == 0 || header == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if ranges && name.eq_ignore_ascii_case("range") {
+                        start = value
+                            .trim()
+                            .trim_start_matches("bytes=")
+                            .trim_end_matches('-')
+                            .parse()
+                            .unwrap_or(0);
+                    }
+                }
+            }
+
+            let head = if start > 0 {
+                format!(
+                    "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\nConnection: close\r\n\r\n",
+                    body.len() - start,
+                    start,
+                    body.len() - 1,
+                    body.len()
+                )
+            } else {
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+            };
+            stream.write_all(head.as_bytes()).ok();
+            stream.write_all(&body[start..]).ok();
+        }
+    });
+    port
+}
+
+#[test]
+fn test_download_and_verify() {
+    let port = stub_server(true);
+    let dest = temp_path("fresh.bin");
+    let options = DownloadOptions {
+        sha256: Some(model_sha256()),
+        ..Default::default()
+    };
+
+    let mut last = None;
+    model_hub::download(
+        &format!("http://127.0.0.1:{}/model.bin", port),
+        &dest,
+        &options,
+        |progress| last = Some(progress),
+    )
+    .expect("Download should succeed");
+
+    assert_eq!(fs::read(&dest).unwrap(), model_bytes());
+    assert!(!model_hub::partial_path(&dest).exists());
+    let last = last.expect("Progress should be reported");
+    assert_eq!(last.downloaded, 200_000);
+    assert_eq!(last.total, Some(200_000));
+    assert_eq!(last.percentage(), Some(100.0));
+    fs::remove_file(&dest).unwrap();
+}
+
+#[test]
+fn test_resume_partial_download() {
+    for ranges in [true, false] {
+        let port = stub_server(ranges);
+        let dest = temp_path(&format!("resumed_{}.bin", ranges));
+        fs::write(model_hub::partial_path(&dest), &model_bytes()[..50_000]).unwrap();
+
+        let mut first = None;
+        model_hub::download(
+            &format!("http://127.0.0.1:{}/model.bin", port),
+            &dest,
+            &DownloadOptions {
+                sha256: Some(model_sha256()),
+                ..Default::default()
+            },
+            |progress| {
+                first.get_or_insert(progress.downloaded);
+            },
+        )
+        .expect("Download should succeed");
+
+        // Resumed at the partial size, or restarted if the server ignores ranges
+        assert_eq!(first, Some(if ranges { 50_000 } else { 0 }));
+        assert_eq!(fs::read(&dest).unwrap(), model_bytes());
+        fs::remove_file(&dest).unwrap();
+    }
+}
+
+#[test]
+fn test_checksum_mismatch() {
+    let port = stub_server(true);
+    let dest = temp_path("corrupt.bin");
+    let err = model_hub::download(
+        &format!("http://127.0.0.1:{}/model.bin", port),
+        &dest,
+        &DownloadOptions {
+            sha256: Some("0".repeat(64)),
+            ..Default::default()
+        },
+        |_| {},
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, ModelHubError::ChecksumMismatch { .. }));
+    assert!(!dest.exists());
+    assert!(!model_hub::partial_path(&dest).exists());
+}
+
+#[test]
+fn test_cancel_keeps_partial() {
+    let port = stub_server(true);
+    let dest = temp_path("cancelled.bin");
+    let err = model_hub::download(
+        &format!("http://127.0.0.1:{}/model.bin", port),
+        &dest,
+        &DownloadOptions {
+            cancel: Some(Arc::new(AtomicBool::new(true))),
+            ..Default::default()
+        },
+        |_| {},
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, ModelHubError::Cancelled));
+    assert!(!dest.exists());
+    assert!(model_hub::partial_path(&dest).exists());
+    fs::remove_file(model_hub::partial_path(&dest)).unwrap();
+}
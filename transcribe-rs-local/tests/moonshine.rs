@@ -45,3 +45,30 @@ fn test_moonshine_base_jfk() {
     //     result.text
     // );
 }
+
+#[test]
+fn test_moonshine_base_batch() {
+    let mut engine = MoonshineEngine::new();
+    engine
+        .load_model_with_params(
+            &PathBuf::from("models/moonshine-base"),
+            MoonshineModelParams::variant(ModelVariant::Base),
+        )
+        .expect("Failed to load model");
+
+    let samples = transcribe_rs::audio::read_wav_samples(&PathBuf::from("samples/jfk.wav"))
+        .expect("Failed to read audio");
+    let single = engine
+        .transcribe_samples(samples.clone(), None)
+        .expect("Failed to transcribe");
+
+    // A shorter clip in the same batch must not change the longer clip's result
+    let short = samples[..samples.len() / 2].to_vec();
+    let results = engine
+        .transcribe_batch(vec![samples, short], None)
+        .expect("Failed to transcribe batch");
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].text, single.text);
+    assert!(!results[1].text.is_empty());
+}
@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+use transcribe_rs::engines::native::{NativeEngine, NativeError, NativeModelParams};
+use transcribe_rs::TranscriptionEngine;
+
+// Recognition locale - can be overridden with NATIVE_LOCALE env var
+fn locale() -> String {
+    std::env::var("NATIVE_LOCALE").unwrap_or_else(|_| "en-US".to_string())
+}
+
+#[test]
+fn test_native_jfk() {
+    let locale = locale();
+    if !NativeEngine::is_available(Some(&locale)) {
+        eprintln!("Skipping test: no native recognizer for {}", locale);
+        return;
+    }
+
+    let mut engine = NativeEngine::new();
+    if let Err(e) = engine.load_model_with_params(
+        Path::new(""),
+        NativeModelParams {
+            locale: Some(locale),
+            ..Default::default()
+        },
+    ) {
+        // Permission prompts cannot be answered in CI
+        eprintln!("Skipping test: {}", e);
+        return;
+    }
+
+    let result = engine
+        .transcribe_file(&PathBuf::from("samples/jfk.wav"), None)
+        .expect("Failed to transcribe");
+
+    println!("Transcription: {}", result.text);
+
+    assert!(
+        result.text.to_lowercase().contains("country"),
+        "Transcription should contain 'country'. Got: '{}'",
+        result.text
+    );
+}
+
+#[test]
+fn test_native_not_loaded() {
+    let mut engine = NativeEngine::new();
+    let err = engine
+        .transcribe_samples(vec![0.0; 16000], None)
+        .expect_err("Transcribing without loading should fail");
+
+    assert!(matches!(
+        err.downcast_ref::<NativeError>(),
+        Some(NativeError::ModelNotLoaded)
+    ));
+}
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
-use transcribe_rs::engines::parakeet::{ParakeetEngine, ParakeetModelParams};
+use transcribe_rs::engines::parakeet::{
+    ParakeetEngine, ParakeetInferenceParams, ParakeetModelParams, QuantizationType,
+};
 use transcribe_rs::TranscriptionEngine;
 
 #[test]
@@ -116,3 +118,95 @@ fn test_timestamps() {
         first_segment.start
     );
 }
+
+#[test]
+fn test_beam_search_and_utterance() {
+    let mut engine = ParakeetEngine::new();
+
+    let model_path = PathBuf::from("models/parakeet-tdt-0.6b-v3-int8");
+    engine
+        .load_model_with_params(&model_path, ParakeetModelParams::int8())
+        .expect("Failed to load model");
+
+    let samples = transcribe_rs::audio::read_wav_samples(&PathBuf::from("samples/jfk.wav"))
+        .expect("Failed to read audio");
+
+    let params = ParakeetInferenceParams {
+        beam_size: 4,
+        timestamp_offset: 100.0,
+        ..Default::default()
+    };
+    let result = engine
+        .transcribe_samples(samples.clone(), Some(params.clone()))
+        .expect("Failed to transcribe");
+    assert_eq!(
+        result.text.trim(),
+        "And so, my fellow Americans, ask not what your country can do for you. Ask what you can do for your country."
+    );
+
+    let utterance = engine
+        .transcribe_utterance(samples, Some(params))
+        .expect("Failed to transcribe");
+    assert_eq!(utterance.segments.len(), 2);
+    let words = &utterance.segments[0].words;
+    assert_eq!(words[0].text, "And");
+    // Timestamps are shifted by the offset
+    assert!(words[0].t_start >= 100.0 && words[0].t_start < 101.0);
+    assert!(words.windows(2).all(|w| w[0].t_end <= w[1].t_start + 1e-3));
+}
+
+#[test]
+fn test_streaming() {
+    let mut engine = ParakeetEngine::new();
+
+    // A cache-aware streaming FastConformer export
+    let model_path = PathBuf::from("models/parakeet-streaming");
+    engine
+        .load_model_with_params(&model_path, ParakeetModelParams::fp32())
+        .expect("Failed to load model");
+
+    let samples = transcribe_rs::audio::read_wav_samples(&PathBuf::from("samples/jfk.wav"))
+        .expect("Failed to read audio");
+
+    let mut stream = engine.stream(None).expect("Failed to start stream");
+    let mut partials = Vec::new();
+    for frame in samples.chunks(1600) {
+        partials.push(stream.accept_samples(frame).expect("Failed to stream"));
+    }
+    let result = stream.finish().expect("Failed to finish stream");
+
+    // Partial results grow as audio arrives
+    assert!(partials.first().unwrap().is_empty());
+    assert!(!partials.last().unwrap().is_empty());
+    assert!(partials
+        .windows(2)
+        .all(|p| p[1].starts_with(p[0].trim_end())));
+
+    let text = result.text.to_lowercase();
+    assert!(
+        text.contains("ask not what your country can do for you"),
+        "Unexpected transcription: '{}'",
+        result.text
+    );
+}
+
+#[test]
+fn test_precision_fallback() {
+    let mut engine = ParakeetEngine::new();
+
+    // The int8 model directory has no FP32 files, so those are replaced
+    let model_path = PathBuf::from("models/parakeet-tdt-0.6b-v3-int8");
+    engine
+        .load_model_with_params(&model_path, ParakeetModelParams::fp32())
+        .expect("Failed to load model");
+
+    let precision = engine.precision().expect("No model loaded");
+    assert_eq!(precision.requested, QuantizationType::FP32);
+    assert_eq!(precision.encoder, QuantizationType::Int8);
+    assert!(precision.is_fallback());
+
+    engine
+        .load_model_with_params(&model_path, ParakeetModelParams::int8())
+        .expect("Failed to load model");
+    assert!(!engine.precision().unwrap().is_fallback());
+}
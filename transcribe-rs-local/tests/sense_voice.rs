@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+use transcribe_rs::engines::sense_voice::{
+    Language, SenseVoiceEngine, SenseVoiceInferenceParams, SenseVoiceModelParams,
+};
+use transcribe_rs::TranscriptionEngine;
+
+// Model directory - can be overridden with SENSE_VOICE_MODEL env var
+fn model_path() -> PathBuf {
+    std::env::var("SENSE_VOICE_MODEL")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("models/sense-voice-int8"))
+}
+
+fn load_engine() -> Option<SenseVoiceEngine> {
+    let model_path = model_path();
+    if !model_path.exists() {
+        eprintln!(
+            "Skipping test: SenseVoice model not found at {:?}",
+            model_path
+        );
+        return None;
+    }
+
+    let mut engine = SenseVoiceEngine::new();
+    engine
+        .load_model_with_params(&model_path, SenseVoiceModelParams::int8())
+        .expect("Failed to load model");
+    Some(engine)
+}
+
+#[test]
+fn test_sense_voice_jfk() {
+    let Some(mut engine) = load_engine() else {
+        return;
+    };
+
+    let result = engine
+        .transcribe_file(&PathBuf::from("samples/jfk.wav"), None)
+        .expect("Failed to transcribe");
+
+    println!("Transcription: {}", result.text);
+
+    let text = result.text.to_lowercase();
+    assert!(
+        text.contains("ask") && text.contains("country"),
+        "Transcription should contain 'ask' and 'country'. Got: '{}'",
+        result.text
+    );
+    assert_eq!(result.language.as_deref(), Some("en"));
+
+    let segments = result.segments.expect("SenseVoice returns word segments");
+    assert!(segments.windows(2).all(|w| w[0].start <= w[1].start));
+    assert!(segments.last().is_some_and(|s| s.end <= 11.5));
+}
+
+#[test]
+fn test_sense_voice_language_and_itn() {
+    let Some(mut engine) = load_engine() else {
+        return;
+    };
+
+    let params = SenseVoiceInferenceParams {
+        language: Language::English,
+        use_itn: false,
+    };
+    let result = engine
+        .transcribe_file(&PathBuf::from("samples/jfk.wav"), Some(params))
+        .expect("Failed to transcribe");
+
+    println!("Transcription: {}", result.text);
+
+    assert!(
+        !result.text.contains([',', '.']),
+        "No punctuation without ITN. Got: '{}'",
+        result.text
+    );
+}
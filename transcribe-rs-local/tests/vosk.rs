@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+use transcribe_rs::audio::read_wav_samples;
+use transcribe_rs::engines::vosk::{VoskEngine, VoskUpdate};
+use transcribe_rs::TranscriptionEngine;
+
+// Model directory - can be overridden with VOSK_MODEL env var
+fn model_path() -> PathBuf {
+    std::env::var("VOSK_MODEL")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("models/vosk-model-small-en-us-0.15"))
+}
+
+fn load_engine() -> Option<VoskEngine> {
+    let model_path = model_path();
+    if !model_path.exists() {
+        eprintln!("Skipping test: Vosk model not found at {:?}", model_path);
+        return None;
+    }
+
+    let mut engine = VoskEngine::new();
+    engine
+        .load_model(&model_path)
+        .expect("Failed to load model");
+    Some(engine)
+}
+
+#[test]
+fn test_vosk_jfk() {
+    let Some(mut engine) = load_engine() else {
+        return;
+    };
+
+    let result = engine
+        .transcribe_file(&PathBuf::from("samples/jfk.wav"), None)
+        .expect("Failed to transcribe");
+
+    println!("Transcription: {}", result.text);
+
+    let text = result.text.to_lowercase();
+    assert!(
+        text.contains("ask") && text.contains("country"),
+        "Transcription should contain 'ask' and 'country'. Got: '{}'",
+        result.text
+    );
+
+    let segments = result.segments.expect("Vosk should return segments");
+    assert!(!segments.is_empty());
+    assert!(segments.windows(2).all(|w| w[0].end <= w[1].start));
+}
+
+#[test]
+fn test_vosk_streaming_partials() {
+    let Some(engine) = load_engine() else {
+        return;
+    };
+
+    let samples = read_wav_samples(&PathBuf::from("samples/jfk.wav")).expect("Failed to read");
+    let mut stream = engine.stream(None).expect("Failed to start stream");
+
+    let mut partials = 0;
+    for frame in samples.chunks(1600) {
+        if let VoskUpdate::Partial(text) = stream.accept_samples(frame).expect("Failed to decode") {
+            if !text.is_empty() {
+                partials += 1;
+            }
+        }
+    }
+    let result = stream.finish().expect("Failed to finish stream");
+
+    assert!(partials > 0, "Expected partial results while streaming");
+    assert!(result.text.to_lowercase().contains("country"));
+}
@@ -1,7 +1,9 @@
 use once_cell::sync::Lazy;
 use std::path::PathBuf;
 use std::sync::Mutex;
-use transcribe_rs::engines::whisper::{WhisperEngine, WhisperInferenceParams};
+use transcribe_rs::engines::whisper::{
+    WhisperEngine, WhisperInferenceParams, WhisperParallelParams,
+};
 use transcribe_rs::TranscriptionEngine;
 
 // Shared model loaded once for all tests
@@ -148,3 +150,32 @@ fn test_timestamps() {
         last_segment.end
     );
 }
+
+#[test]
+fn test_parallel_transcription() {
+    let mut engine = get_engine();
+
+    // The JFK clip four times over, with a second of silence between
+    let clip = transcribe_rs::audio::read_wav_samples(&PathBuf::from("samples/jfk.wav"))
+        .expect("Failed to read audio");
+    let mut samples = Vec::new();
+    for _ in 0..4 {
+        samples.extend_from_slice(&clip);
+        samples.extend(std::iter::repeat_n(0.0, 16000));
+    }
+
+    let parallel = WhisperParallelParams {
+        thread_budget: Some(4),
+        ..Default::default()
+    };
+    let result = engine
+        .transcribe_parallel(&samples, None, &parallel)
+        .expect("Failed to transcribe");
+
+    assert_eq!(result.text.matches("ask not what your country").count(), 4);
+    let segments = result.segments.unwrap();
+    for pair in segments.windows(2) {
+        assert!(pair[1].start >= pair[0].start);
+    }
+    assert!(segments.last().unwrap().end > 40.0);
+}
@@ -41,9 +41,9 @@ static ENGINE: Lazy<Mutex<Option<WhisperfileEngine>>> = Lazy::new(|| {
 
     let mut engine = WhisperfileEngine::new(binary_path());
 
-    // Use a specific port to avoid conflicts
+    // Let the engine pick a free port to avoid conflicts
     let params = WhisperfileModelParams {
-        port: 18080,
+        port: 0,
         startup_timeout_secs: 60,
         ..Default::default()
     };
@@ -284,3 +284,324 @@ fn test_language_parameter() {
         result.text
     );
 }
+
+#[test]
+fn test_connect_existing_server() {
+    skip_if_unavailable!();
+
+    let mut guard = match get_engine() {
+        Some(g) => g,
+        None => {
+            eprintln!("Skipping test: engine not available");
+            return;
+        }
+    };
+    let managed = match guard.as_mut() {
+        Some(e) => e,
+        None => {
+            eprintln!("Skipping test: engine not initialized");
+            return;
+        }
+    };
+
+    let audio_path = PathBuf::from("samples/jfk.wav");
+
+    let port = managed
+        .port()
+        .expect("Managed server should report its port");
+    assert_ne!(port, 0);
+
+    // Reuse the shared engine's server; the model path is ignored
+    let mut engine = WhisperfileEngine::connect(&format!("http://127.0.0.1:{}/", port));
+    engine
+        .load_model(&PathBuf::new())
+        .expect("Existing server should be reachable");
+    let result = engine
+        .transcribe_file(&audio_path, None)
+        .expect("Failed to transcribe");
+    assert!(
+        result.text.to_lowercase().contains("americans"),
+        "Unexpected transcription: {}",
+        result.text
+    );
+
+    // Dropping a connected engine must not stop the server
+    drop(engine);
+    managed
+        .transcribe_file(&audio_path, None)
+        .expect("Server should still be running");
+}
+
+#[test]
+fn test_connect_unreachable_server() {
+    // Nothing listens on a port that was just released
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to find a free port")
+        .port();
+
+    let mut engine = WhisperfileEngine::connect(&format!("http://127.0.0.1:{}", port));
+    let params = WhisperfileModelParams {
+        startup_timeout_secs: 1,
+        ..Default::default()
+    };
+    assert!(engine
+        .load_model_with_params(&PathBuf::new(), params)
+        .is_err());
+    assert!(engine
+        .transcribe_file(&PathBuf::from("samples/jfk.wav"), None)
+        .is_err());
+}
+
+#[test]
+fn test_port_in_use() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind a port");
+    let port = listener.local_addr().unwrap().port();
+
+    // The port check runs before the binary is started, so any path will do
+    let mut engine = WhisperfileEngine::new(std::env::current_exe().unwrap());
+    let params = WhisperfileModelParams {
+        port,
+        ..Default::default()
+    };
+    let err = engine
+        .load_model_with_params(&std::env::current_exe().unwrap(), params)
+        .unwrap_err();
+    assert!(err.to_string().contains("already in use"), "{}", err);
+    assert_eq!(engine.port(), None);
+}
+
+/// Write a shell script standing in for the whisperfile binary.
+#[cfg(unix)]
+fn fake_server(name: &str, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let binary = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    std::fs::write(&binary, format!("#!/bin/sh\n{}", script)).unwrap();
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+    binary
+}
+
+#[cfg(unix)]
+#[test]
+fn test_server_crash_reports_stderr() {
+    use transcribe_rs::engines::whisperfile::WhisperfileError;
+
+    // A "server" that fails the way a real one does on a bad model
+    let binary = fake_server(
+        "fake-whisperfile-crash",
+        "echo 'error: failed to load model' >&2\nsleep 0.5\nexit 3\n",
+    );
+
+    let mut engine = WhisperfileEngine::new(&binary);
+    let params = WhisperfileModelParams {
+        port: 0,
+        startup_timeout_secs: 10,
+        ..Default::default()
+    };
+    let err = engine.load_model_with_params(&binary, params).unwrap_err();
+    std::fs::remove_file(&binary).ok();
+
+    match err.downcast_ref::<WhisperfileError>() {
+        Some(WhisperfileError::ServerCrashed { status, stderr }) => {
+            assert_eq!(status.code(), Some(3));
+            assert_eq!(stderr, &vec!["error: failed to load model".to_string()]);
+        }
+        other => panic!("Expected ServerCrashed, got {:?} ({})", other, err),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_server_flags() {
+    use transcribe_rs::engines::whisperfile::WhisperfileError;
+
+    // Print the command line, then exit so it is reported with the crash
+    let binary = fake_server(
+        "fake-whisperfile-args",
+        "echo \"$@\" >&2\nsleep 0.5\nexit 1\n",
+    );
+
+    let mut engine = WhisperfileEngine::new(&binary);
+    let params = WhisperfileModelParams {
+        port: 0,
+        threads: Some(4),
+        beam_size: Some(5),
+        flash_attn: true,
+        extra_args: vec!["--split-on-word".to_string()],
+        ..Default::default()
+    };
+    let err = engine.load_model_with_params(&binary, params).unwrap_err();
+    std::fs::remove_file(&binary).ok();
+
+    let args = match err.downcast_ref::<WhisperfileError>() {
+        Some(WhisperfileError::ServerCrashed { stderr, .. }) => stderr.join(" "),
+        other => panic!("Expected ServerCrashed, got {:?} ({})", other, err),
+    };
+    assert!(args.starts_with("--server -m "), "{}", args);
+    assert!(
+        args.ends_with("--threads 4 --beam-size 5 --flash-attn --split-on-word"),
+        "{}",
+        args
+    );
+    assert!(!args.contains("--max-context"), "{}", args);
+    assert!(!args.contains("--no-timestamps"), "{}", args);
+}
+
+/// Serve the whisperfile HTTP API on a free port: the readiness check
+/// succeeds, and /inference gets `inference_response` (a full HTTP response)
+/// or no answer at all. Returns the port.
+fn stub_server(inference_response: Option<&'static str>) -> u16 {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => break,
+            };
+            std::thread::spawn(move || {
+                // Read the request head and body before answering
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).ok();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap_or(0) == 0 || header == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap_or(0);
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).ok();
+
+                if request_line.starts_with("GET ") {
+                    stream
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                        .ok();
+                } else if let Some(response) = inference_response {
+                    stream.write_all(response.as_bytes()).ok();
+                } else {
+                    std::thread::sleep(std::time::Duration::from_secs(5));
+                }
+            });
+        }
+    });
+    port
+}
+
+#[test]
+fn test_read_timeout() {
+    use std::time::Duration;
+    use transcribe_rs::engines::whisperfile::WhisperfileError;
+
+    let port = stub_server(None);
+    let mut engine = WhisperfileEngine::connect(&format!("http://127.0.0.1:{}", port))
+        .with_read_timeout(Duration::from_millis(300));
+    engine
+        .load_model(&PathBuf::new())
+        .expect("Stub server should be reachable");
+
+    let err = engine
+        .transcribe_samples(vec![0.0; 16000], None)
+        .unwrap_err();
+    match err.downcast_ref::<WhisperfileError>() {
+        Some(WhisperfileError::Timeout { timeout }) => {
+            assert_eq!(*timeout, Duration::from_millis(300))
+        }
+        other => panic!("Expected Timeout, got {:?} ({})", other, err),
+    }
+}
+
+#[test]
+fn test_streamed_segments() {
+    use transcribe_rs::engines::whisperfile::WhisperfileInferenceParams;
+
+    let port = stub_server(Some(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n\
+         data: {\"start\": 0.0, \"end\": 1.5, \"text\": \" Hello there.\"}\n\n\
+         data: {\"start\": 1.5, \"end\": 3.0, \"text\": \" General Kenobi.\"}\n\n\
+         data: [DONE]\n\n",
+    ));
+    let mut engine = WhisperfileEngine::connect(&format!("http://127.0.0.1:{}", port));
+    engine
+        .load_model(&PathBuf::new())
+        .expect("Stub server should be reachable");
+
+    let params = WhisperfileInferenceParams {
+        stream: true,
+        ..Default::default()
+    };
+    let mut streamed = Vec::new();
+    let result = engine
+        .transcribe_samples_with_callback(vec![0.0; 16000], Some(params), |segment| {
+            streamed.push((segment.start, segment.text.clone()))
+        })
+        .expect("Failed to transcribe");
+
+    assert_eq!(
+        streamed,
+        vec![
+            (0.0, " Hello there.".to_string()),
+            (1.5, " General Kenobi.".to_string())
+        ]
+    );
+    assert_eq!(result.text, "Hello there. General Kenobi.");
+    assert_eq!(result.segments.map(|s| s.len()), Some(2));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pool_spawns_lazily_and_evicts() {
+    use transcribe_rs::engines::whisperfile::{WhisperfileError, WhisperfilePool};
+
+    if std::process::Command::new("python3")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        eprintln!("Skipping test: python3 not available for the stub server");
+        return;
+    }
+    // Answers the readiness check on the port it is given (`--port` is
+    // the 7th argument)
+    let binary = fake_server(
+        "fake-whisperfile-pool",
+        "exec python3 -m http.server --bind \"$5\" \"$7\" 2>/dev/null\n",
+    );
+    let params = WhisperfileModelParams {
+        startup_timeout_secs: 10,
+        ..Default::default()
+    };
+    let pool = WhisperfilePool::new(&binary, 1)
+        .with_model("small.en", &binary, params.clone())
+        .with_model("large-v3", &binary, params);
+
+    assert_eq!(pool.models(), vec!["large-v3", "small.en"]);
+    assert!(pool.running().is_empty());
+
+    pool.warm("small.en").expect("Failed to start server");
+    assert_eq!(pool.running(), vec!["small.en"]);
+    assert!(pool.health_check().is_empty());
+
+    // The pool holds one server, so starting another stops the first
+    pool.warm("large-v3").expect("Failed to start server");
+    assert_eq!(pool.running(), vec!["large-v3"]);
+
+    let err = pool.warm("medium").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<WhisperfileError>(),
+        Some(WhisperfileError::UnknownModel(name)) if name == "medium"
+    ));
+
+    pool.shutdown();
+    assert!(pool.running().is_empty());
+    std::fs::remove_file(&binary).ok();
+}